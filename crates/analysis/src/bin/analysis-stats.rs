@@ -0,0 +1,100 @@
+//! Loads a project and reports how long it takes to analyze, and how much
+//! memory each stage of the analysis holds afterwards.
+//!
+//! ```text
+//! cargo run --release --bin analysis-stats -- path/to/project
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use analysis::{Database, IndexConfig, IndexProgress, ModuleId};
+use vfs::Vfs;
+
+/// Counts the bytes that are allocated, which is what memory usage is
+/// measured by.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(pointer, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let directory = env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+    let project = project_model::discover(&directory).unwrap_or_else(|error| {
+        eprintln!("{}", error.message());
+        process::exit(1);
+    });
+
+    let mut db = Database::new();
+    let mut vfs = Vfs::default();
+    let start = Instant::now();
+    // The cache lives alongside the output of the compiler, which projects
+    // already keep out of version control.
+    let config =
+        IndexConfig { cache: Some(project.output.join(".analyzer")), ..Default::default() };
+    let mut progress = |progress| {
+        if let IndexProgress::Loaded { packages, files } = progress {
+            println!("loaded {files} files of {packages} packages in {:.2?}", start.elapsed());
+        }
+    };
+    match db.load_project(&project, &mut vfs, &config, &mut progress) {
+        Ok(skipped) => {
+            for (path, error) in skipped {
+                eprintln!("skipped {}: {}", path.display(), error.message());
+            }
+        }
+        Err(error) => {
+            eprintln!("couldn't load the project: {error}");
+            process::exit(1);
+        }
+    }
+    let files = db.files();
+    println!("indexed {} files in {:.2?}", files.len(), start.elapsed());
+
+    let start = Instant::now();
+    for &file in &files {
+        db.lower(file);
+        db.scope(ModuleId::File(file));
+    }
+    println!("lowered and resolved every module in {:.2?}", start.elapsed());
+
+    let start = Instant::now();
+    for &file in &files {
+        db.infer(file);
+    }
+    println!("type checked every module in {:.2?}", start.elapsed());
+
+    println!();
+    println!("{:<16}{:>10}{:>12}", "queries", "entries", "memory");
+    for usage in db.memory_usage(&|| ALLOCATED.load(Ordering::Relaxed)) {
+        println!("{:<16}{:>10}{:>12}", usage.group.name(), usage.entries, size(usage.bytes));
+    }
+}
+
+fn size(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1048576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
@@ -0,0 +1,90 @@
+//! Item trees cached on disk between sessions.
+//!
+//! Most files of a project, and every file of its dependencies, are the same
+//! as the last time it was opened, so their item trees are cached by the
+//! hash of their text. Indexing a file with a cached item tree doesn't parse
+//! it at all, and its syntax is only parsed once it's needed. The interface
+//! of a module is derived from its item tree, so it's cheap to rebuild.
+//!
+//! Entries are never invalidated, since a changed file hashes to another
+//! entry. The cache is best-effort: entries that can't be read or written
+//! are lowered again.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use lowering::item_tree::ItemTree;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    directory: PathBuf,
+}
+
+impl Cache {
+    pub fn new(directory: &Path) -> Cache {
+        Cache { directory: directory.to_path_buf() }
+    }
+
+    pub fn load(&self, text: &str) -> Option<ItemTree> {
+        ItemTree::decode(&fs::read(self.path(text)).ok()?)
+    }
+
+    pub fn store(&self, text: &str, tree: &ItemTree) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        // Entries are written whole and then renamed into place, such that
+        // a concurrent session never reads half of one.
+        let path = self.path(text);
+        let temporary = path.with_extension(format!("{}", std::process::id()));
+        fs::write(&temporary, tree.encode())?;
+        fs::rename(temporary, path)
+    }
+
+    /// Entries are named by the hash and length of the text, which makes
+    /// collisions between different texts unlikely enough to ignore.
+    fn path(&self, text: &str) -> PathBuf {
+        self.directory.join(format!("{:016x}-{:x}.tree", hash(text), text.len()))
+    }
+}
+
+/// The FNV-1a hash of the text, which unlike the hashers of the standard
+/// library is the same across runs and versions.
+fn hash(text: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in text.as_bytes() {
+        hash = (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use lowering::item_tree::ItemTree;
+
+    use crate::{Database, FileId, IndexConfig, Parse, Query};
+
+    #[test]
+    fn index_from_the_cache() {
+        let directory = std::env::temp_dir().join(format!("analysis-cache-{}", std::process::id()));
+        let config =
+            IndexConfig { threads: 2, cache: Some(directory.clone()), ..Default::default() };
+        let text = "module Main where\n\nmain = 0\n";
+
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), text);
+        db.index(&config);
+        let expected = db.item_tree(FileId(0));
+
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), text);
+        db.index(&config);
+        let revision = db.revision;
+        assert!(db.memos.verified::<Parse>(Query::Parse(FileId(0)), revision).is_none());
+        let tree = db.memos.verified::<ItemTree>(Query::ItemTree(FileId(0)), revision).unwrap();
+        assert_eq!(tree, expected);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
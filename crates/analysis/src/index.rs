@@ -0,0 +1,162 @@
+//! Indexing every file of the workspace up front.
+//!
+//! Going to a definition needs the item tree of every module in the graph,
+//! so rather than building them one by one on the first request, they're
+//! built across threads at startup and memoized as though they had been
+//! queried. Each thread takes the next file as soon as it's done with one,
+//! such that a few large files don't hold the others up.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use lowering::{item_tree::ItemTree, name::ModuleName};
+use parsing::ParseError;
+use rowan::{ast::AstNode, GreenNode};
+use syntax::{ast, SyntaxNode};
+
+use crate::{cache::Cache, Database, FileId, Parse, Query};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexConfig {
+    pub threads: usize,
+    /// How many bytes of source to keep the syntax trees of. Files past the
+    /// budget are only parsed for their item trees, skipping the bodies of
+    /// their values, and are parsed in full when their syntax is needed.
+    pub memory_budget: usize,
+    /// The directory that item trees are cached in between sessions.
+    pub cache: Option<PathBuf>,
+}
+
+impl Default for IndexConfig {
+    fn default() -> IndexConfig {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        IndexConfig { threads, memory_budget: 64 * 1024 * 1024, cache: None }
+    }
+}
+
+/// How far indexing has got, which is reported as it goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexProgress {
+    /// The packages of the project were found and their files were read.
+    Loaded { packages: usize, files: usize },
+    /// The `indexed`th file out of `total` was parsed.
+    Parsed { indexed: usize, total: usize, module: Option<ModuleName> },
+    /// Every file is parsed, and the module graphs and symbols are built.
+    Resolving,
+}
+
+struct Indexed {
+    file: FileId,
+    /// `None` if the syntax isn't kept, such that the file is parsed again
+    /// when it's needed.
+    parse: Option<(GreenNode, Vec<ParseError>)>,
+    tree: ItemTree,
+}
+
+/// Parses a file and builds its item tree. Files that don't fit in what's
+/// left of the budget are only parsed for their interface, since their
+/// syntax isn't kept anyway.
+fn index_file(file: FileId, text: &str, budget: &AtomicUsize) -> Indexed {
+    let size = text.len();
+    let keep =
+        budget.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| left.checked_sub(size));
+    if keep.is_ok() {
+        let (root, errors) = parsing::parse_module(text);
+        let tree = ItemTree::lower(&ast::Module::cast(root.clone()).unwrap());
+        Indexed { file, parse: Some((root.green().into_owned(), errors)), tree }
+    } else {
+        let (root, _) = parsing::parse_module_interface(text);
+        let tree = ItemTree::lower(&ast::Module::cast(root).unwrap());
+        Indexed { file, parse: None, tree }
+    }
+}
+
+impl Database {
+    /// Parses every file and builds its item tree, unless it's cached,
+    /// followed by the module graph of every package and the symbol index.
+    pub fn index(&mut self, config: &IndexConfig) {
+        self.index_with_progress(config, &mut |_| {});
+    }
+
+    /// Indexes every file like [`Database::index`], reporting each file as
+    /// it's parsed.
+    pub fn index_with_progress(
+        &mut self,
+        config: &IndexConfig,
+        progress: &mut dyn FnMut(IndexProgress),
+    ) {
+        let cache = config.cache.as_deref().map(Cache::new);
+        let files: Vec<_> = self
+            .files
+            .iter()
+            .filter_map(|(&file, input)| Some((file, input.text.as_deref()?)))
+            .collect();
+        let next = AtomicUsize::new(0);
+        let budget = AtomicUsize::new(config.memory_budget);
+        let threads = config.threads.clamp(1, files.len().max(1));
+        // Results are only buffered for as long as it takes to memoize them,
+        // so at most a few syntax trees are in flight at once.
+        let (sender, receiver) = mpsc::sync_channel(threads);
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                let (files, next, budget, cache) = (&files, &next, &budget, &cache);
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    while let Some(&(file, text)) = files.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let indexed = match cache.as_ref().and_then(|cache| cache.load(text)) {
+                            Some(tree) => Indexed { file, parse: None, tree },
+                            None => {
+                                let indexed = index_file(file, text, budget);
+                                if let Some(cache) = cache {
+                                    cache.store(text, &indexed.tree).ok();
+                                }
+                                indexed
+                            }
+                        };
+                        if sender.send(indexed).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (indexed, Indexed { file, parse, tree }) in receiver.into_iter().enumerate() {
+                progress(IndexProgress::Parsed {
+                    indexed: indexed + 1,
+                    total: files.len(),
+                    module: tree.name,
+                });
+                let dependency = match parse {
+                    Some((green, errors)) => {
+                        let parse = Parse { root: SyntaxNode::new_root(green), errors };
+                        self.memos.insert(
+                            Query::Parse(file),
+                            parse,
+                            self.revision,
+                            vec![Query::FileText(file)],
+                        );
+                        Query::Parse(file)
+                    }
+                    None => Query::FileText(file),
+                };
+                self.memos.insert(Query::ItemTree(file), tree, self.revision, vec![dependency]);
+            }
+        });
+
+        progress(IndexProgress::Resolving);
+        self.graph(None);
+        for package in self.packages.ids() {
+            self.graph(Some(package));
+        }
+        self.symbols();
+    }
+}
@@ -0,0 +1,764 @@
+//! The query database that the rest of the analyzer is computed from.
+//!
+//! The text of each file is an input. Everything else is a query over the
+//! inputs, memoized in the [`Database`] along with what it read, such that
+//! changing a file only recomputes the queries that depend on it.
+
+mod cache;
+mod index;
+mod memo;
+mod project;
+mod stats;
+mod symbols;
+
+use std::{
+    cell::RefCell,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use lowering::{
+    arena::ArenaMap,
+    hir::{BodyId, Module},
+    item_tree::ItemTree,
+    lower_module_with_source_map,
+    name::ModuleName,
+    source_map::SourceMap,
+};
+use parsing::ParseError;
+use resolution::{body::ModuleScope, exports::Exports, graph::ModuleGraph, imports::Imports, prim};
+use rowan::ast::AstNode;
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{ast, SyntaxNode};
+use typecheck::{
+    derive::{check_derives, ModuleDerives},
+    infer::{BodyTypes, TypeDiagnostic},
+    kinds::{check_kinds, ModuleKinds},
+    patterns::{check_patterns, PatternDiagnostic},
+    query::Checker,
+    solve::check_coverage,
+    Workspace,
+};
+use vfs::{ChangeKind, ChangedFile, Vfs};
+
+use crate::memo::{Memos, Revision};
+pub use crate::{
+    index::{IndexConfig, IndexProgress},
+    project::{PackageData, PackageGraph, PackageId},
+    stats::{MemoryUsage, QueryGroup},
+    symbols::{Symbol, SymbolIndex},
+};
+
+pub use vfs::FileId;
+
+/// The inputs and queries of the database, which are what queries record
+/// as their dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Query {
+    FileText(FileId),
+    Files,
+    Parse(FileId),
+    ItemTree(FileId),
+    Lower(FileId),
+    Packages,
+    Compiled,
+    Modules,
+    Graph(Option<PackageId>),
+    Exports(ModuleId),
+    Imports(ModuleId),
+    Scope(ModuleId),
+    Infer(FileId),
+    Symbols,
+}
+
+/// A module of the workspace, a module that `purs` compiled without its
+/// source being in the workspace, or a submodule of `Prim`. The last two
+/// aren't files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModuleId {
+    File(FileId),
+    Compiled(ModuleName),
+    Prim(ModuleName),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Parse {
+    pub root: SyntaxNode,
+    pub errors: Vec<ParseError>,
+}
+
+impl Parse {
+    pub fn module(&self) -> ast::Module {
+        ast::Module::cast(self.root.clone()).unwrap()
+    }
+}
+
+/// The HIR of a module, along with the syntax it was lowered from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lowered {
+    pub module: Module,
+    pub source_map: SourceMap,
+}
+
+/// The types of a file: those inferred for each of its bodies, and the kinds
+/// of its type-level declarations along with the kind errors in its types.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Inferred {
+    pub bodies: ArenaMap<BodyId, Rc<BodyTypes>>,
+    pub kinds: ModuleKinds,
+    /// The arguments of instance heads with variables that the functional
+    /// dependencies of their class don't determine.
+    pub coverage: Vec<TypeDiagnostic>,
+    /// The matches of each body with patterns that aren't matched, or
+    /// branches that can't be reached.
+    pub patterns: ArenaMap<BodyId, Vec<PatternDiagnostic>>,
+    /// The `derive instance` declarations that can't be derived.
+    pub derives: ModuleDerives,
+}
+
+impl Inferred {
+    pub fn get(&self, body: BodyId) -> Option<&Rc<BodyTypes>> {
+        self.bodies.get(body)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BodyId, &Rc<BodyTypes>)> {
+        self.bodies.iter()
+    }
+}
+
+/// Raised by a query when an edit arrived while it was running, since its
+/// result would be stale by the time it's done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the query was cancelled by an edit")
+    }
+}
+
+/// Cancels the queries running on a [`Database`] from another thread, such
+/// as the one that receives edits from the editor.
+#[derive(Debug, Clone, Default)]
+pub struct Canceller(Arc<AtomicBool>);
+
+impl Canceller {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Input {
+    text: Option<Rc<str>>,
+    changed_at: Revision,
+}
+
+#[derive(Default)]
+pub struct Database {
+    revision: Revision,
+    files: FxHashMap<FileId, Input>,
+    /// The revision at which a file was last added or removed.
+    files_changed_at: Revision,
+    /// The item trees of the submodules of `Prim`, which aren't files.
+    prim: Vec<Rc<ItemTree>>,
+    memos: Memos,
+    canceller: Canceller,
+    /// The files of dependencies, which aren't edited. Nothing is computed
+    /// from this, so it isn't tracked as an input.
+    libraries: FxHashSet<FileId>,
+    packages: PackageGraph,
+    packages_changed_at: Revision,
+    /// The item trees of the modules in the output directory of `purs`.
+    compiled: Vec<Rc<ItemTree>>,
+    compiled_changed_at: Revision,
+    /// The types of every body that was checked, which checking a module
+    /// again reuses for the bodies that didn't change.
+    checker: RefCell<Checker>,
+}
+
+impl Database {
+    pub fn new() -> Database {
+        let prim = prim::modules().into_iter().map(Rc::new).collect();
+        Database { prim, ..Database::default() }
+    }
+
+    // Cancellation
+
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    /// Unwinds with [`Cancelled`] if an edit is waiting to be applied, which
+    /// every query checks before it starts.
+    pub fn unwind_if_cancelled(&self) {
+        if self.canceller.0.load(Ordering::Relaxed) {
+            panic::resume_unwind(Box::new(Cancelled));
+        }
+    }
+
+    /// Runs `f`, returning [`Cancelled`] rather than unwinding if an edit
+    /// arrived while it ran. The queries that were interrupted aren't
+    /// memoized, and are computed again once they're asked for.
+    pub fn catch_cancelled<T>(&self, f: impl FnOnce(&Self) -> T) -> Result<T, Cancelled> {
+        match panic::catch_unwind(AssertUnwindSafe(|| f(self))) {
+            Ok(value) => Ok(value),
+            Err(payload) if payload.is::<Cancelled>() => {
+                self.memos.interrupt();
+                Err(Cancelled)
+            }
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
+    /// Clears the flag without applying an edit, for when the queries were
+    /// cancelled for a reason of their own, such as the editor no longer
+    /// wanting the result of a request.
+    pub fn clear_cancelled(&mut self) {
+        self.canceller.0.store(false, Ordering::Relaxed);
+    }
+
+    // Inputs
+
+    fn bump(&mut self) {
+        self.revision += 1;
+        self.canceller.0.store(false, Ordering::Relaxed);
+    }
+
+    pub fn set_file_text(&mut self, file: FileId, text: impl Into<Rc<str>>) {
+        self.bump();
+        let input = Input { text: Some(text.into()), changed_at: self.revision };
+        if self.files.insert(file, input).is_none_or(|previous| previous.text.is_none()) {
+            self.files_changed_at = self.revision;
+        }
+    }
+
+    pub fn remove_file(&mut self, file: FileId) {
+        if self.files.get(&file).is_some_and(|input| input.text.is_some()) {
+            self.bump();
+            self.files.insert(file, Input { text: None, changed_at: self.revision });
+            self.files_changed_at = self.revision;
+        }
+    }
+
+    /// Brings the files up to date with the changes taken from a [`Vfs`].
+    pub fn apply_changes(&mut self, vfs: &Vfs, changes: Vec<ChangedFile>) {
+        for ChangedFile { file_id, kind } in changes {
+            match (kind, vfs.contents(file_id)) {
+                (ChangeKind::Deleted, _) | (_, None) => self.remove_file(file_id),
+                (_, Some(contents)) => self.set_file_text(file_id, contents),
+            }
+        }
+    }
+
+    pub fn file_text(&self, file: FileId) -> Option<Rc<str>> {
+        self.memos.read(Query::FileText(file));
+        self.files.get(&file)?.text.clone()
+    }
+
+    /// Every file in the database, in order.
+    pub fn files(&self) -> Vec<FileId> {
+        self.memos.read(Query::Files);
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, input)| input.text.is_some())
+            .map(|(&file, _)| file)
+            .collect();
+        files.sort();
+        files
+    }
+
+    // Syntax
+
+    pub fn parse(&self, file: FileId) -> Rc<Parse> {
+        self.query(Query::Parse(file), |db| {
+            let text = db.file_text(file).unwrap_or_default();
+            let (root, errors) = parsing::parse_module(&text);
+            Parse { root, errors }
+        })
+    }
+
+    pub fn item_tree(&self, file: FileId) -> Rc<ItemTree> {
+        self.query(Query::ItemTree(file), |db| ItemTree::lower(&db.parse(file).module()))
+    }
+
+    pub fn lower(&self, file: FileId) -> Rc<Lowered> {
+        self.query(Query::Lower(file), |db| {
+            let (module, source_map) = lower_module_with_source_map(&db.parse(file).module());
+            Lowered { module, source_map }
+        })
+    }
+
+    // Modules
+
+    /// The files that declare each module. Packages may declare modules of
+    /// the same name, such as their tests' `Test.Main`, so which of them an
+    /// import refers to depends on the package it's in.
+    pub fn modules(&self) -> Rc<FxHashMap<ModuleName, Vec<FileId>>> {
+        self.query(Query::Modules, |db| {
+            let mut modules: FxHashMap<_, Vec<_>> = FxHashMap::default();
+            for file in db.files() {
+                if let Some(name) = db.item_tree(file).name {
+                    modules.entry(name).or_default().push(file);
+                }
+            }
+            modules
+        })
+    }
+
+    /// The module that a name refers to from within `from`, which is a file
+    /// of the same package if there is one, then the first visible file that
+    /// declares it, then a compiled module, then a submodule of `Prim`.
+    pub fn resolve_module(&self, from: ModuleId, name: ModuleName) -> Option<ModuleId> {
+        let package = match from {
+            ModuleId::File(file) => self.package(file),
+            ModuleId::Compiled(_) | ModuleId::Prim(_) => None,
+        };
+        let modules = self.modules();
+        let files = modules.get(&name).map_or(&[][..], Vec::as_slice);
+        let file = (files.iter().find(|&&file| package.is_some() && self.package(file) == package))
+            .or_else(|| files.iter().find(|&&file| self.is_visible(package, self.package(file))));
+        match file {
+            Some(&file) => Some(ModuleId::File(file)),
+            None if self.compiled_tree(name).is_some() => Some(ModuleId::Compiled(name)),
+            None => {
+                let is_prim = self.prim.iter().any(|tree| tree.name == Some(name));
+                is_prim.then_some(ModuleId::Prim(name))
+            }
+        }
+    }
+
+    /// The modules that `from` can refer to by name, including compiled
+    /// modules and the submodules of `Prim`, in the order of their names.
+    pub fn visible_modules(&self, from: ModuleId) -> Vec<(ModuleName, ModuleId)> {
+        self.memos.read(Query::Compiled);
+        let compiled = self.compiled.iter().filter_map(|tree| tree.name);
+        let prim = self.prim.iter().filter_map(|tree| tree.name);
+        let mut names: Vec<_> =
+            self.modules().keys().copied().chain(compiled).chain(prim).collect();
+        names.sort_by_key(|name| name.as_str());
+        names.dedup();
+        let modules =
+            names.into_iter().filter_map(|name| Some((name, self.resolve_module(from, name)?)));
+        modules.collect()
+    }
+
+    /// The first file that declares a module, regardless of packages.
+    pub fn module_file(&self, name: ModuleName) -> Option<FileId> {
+        self.modules().get(&name)?.first().copied()
+    }
+
+    pub fn module_tree(&self, module: ModuleId) -> Option<Rc<ItemTree>> {
+        match module {
+            ModuleId::File(file) => Some(self.item_tree(file)),
+            ModuleId::Compiled(name) => self.compiled_tree(name),
+            ModuleId::Prim(name) => self.prim.iter().find(|tree| tree.name == Some(name)).cloned(),
+        }
+    }
+
+    /// Replaces the modules read from the output directory of `purs`, which
+    /// modules resolve to when no file declares them.
+    pub fn set_compiled(&mut self, trees: Vec<ItemTree>) {
+        self.bump();
+        self.compiled = trees.into_iter().map(Rc::new).collect();
+        self.compiled_changed_at = self.revision;
+    }
+
+    fn compiled_tree(&self, name: ModuleName) -> Option<Rc<ItemTree>> {
+        self.memos.read(Query::Compiled);
+        self.compiled.iter().find(|tree| tree.name == Some(name)).cloned()
+    }
+
+    /// The module graph of a package, made of its own modules and those of
+    /// the packages it can see, which its own modules shadow. Files outside
+    /// of any package are in the graph of `None`, along with every other
+    /// file.
+    pub fn graph(&self, package: Option<PackageId>) -> Rc<ModuleGraph> {
+        self.query(Query::Graph(package), |db| {
+            let files = db.files();
+            let (own, visible): (Vec<_>, Vec<_>) = match package {
+                Some(_) => files
+                    .into_iter()
+                    .filter(|&file| db.is_visible(package, db.package(file)))
+                    .partition(|&file| db.package(file) == package),
+                None => (files, vec![]),
+            };
+            let mut trees: Vec<_> = own.into_iter().map(|file| db.item_tree(file)).collect();
+            let shadowed: FxHashSet<_> = trees.iter().filter_map(|tree| tree.name).collect();
+            for file in visible {
+                let tree = db.item_tree(file);
+                if !tree.name.is_some_and(|name| shadowed.contains(&name)) {
+                    trees.push(tree);
+                }
+            }
+            ModuleGraph::new(trees.iter().map(|tree| &**tree))
+        })
+    }
+
+    // Resolution
+
+    /// The names a module exports. Modules in an import cycle see each
+    /// other as exporting nothing, as the cycle is reported by the
+    /// [`ModuleGraph`].
+    pub fn exports(&self, module: ModuleId) -> Rc<Exports> {
+        if self.memos.is_active(Query::Exports(module)) {
+            return Rc::default();
+        }
+        self.query(Query::Exports(module), |db| {
+            let Some(tree) = db.module_tree(module) else { return Exports::default() };
+            let imports = db.imports(module);
+            let exports = db.imported_exports(module, &tree);
+            Exports::resolve(&tree, &imports, |name| {
+                let (_, exports) = exports.iter().find(|(imported, _)| *imported == name)?;
+                Some(&exports.interface)
+            })
+        })
+    }
+
+    pub fn imports(&self, module: ModuleId) -> Rc<Imports> {
+        self.query(Query::Imports(module), |db| {
+            let Some(tree) = db.module_tree(module) else { return Imports::default() };
+            let exports = db.imported_exports(module, &tree);
+            Imports::resolve(&tree, |name| {
+                let (_, exports) = exports.iter().find(|(imported, _)| *imported == name)?;
+                Some(&exports.interface)
+            })
+        })
+    }
+
+    pub fn scope(&self, module: ModuleId) -> Option<Rc<ModuleScope>> {
+        self.module_tree(module)?;
+        Some(self.query(Query::Scope(module), |db| {
+            let tree = db.module_tree(module).unwrap_or_default();
+            ModuleScope::new(&tree, (*db.imports(module)).clone())
+        }))
+    }
+
+    // Types
+
+    /// The types inferred for each body of a file, and the kinds of its
+    /// declarations, which are checked against the modules it can see. Only
+    /// the bodies whose syntax, signature, or references changed since the
+    /// file was last checked are inferred again, the rest keep the types
+    /// they had.
+    pub fn infer(&self, file: FileId) -> Rc<Inferred> {
+        self.query(Query::Infer(file), |db| {
+            let Some(name) = db.item_tree(file).name else { return Inferred::default() };
+            let workspace = Snapshot::new(db, ModuleId::File(file));
+            let lowered = db.lower(file);
+            let bodies = db.checker.borrow_mut().check_module(&workspace, name, &lowered.module);
+            let kinds = check_kinds(&workspace, name, &lowered.module);
+            let coverage = check_coverage(&workspace, name, &lowered.module);
+            let patterns = check_patterns(&workspace, name, &lowered.module);
+            let derives = check_derives(&workspace, name, &lowered.module);
+            Inferred { bodies, kinds, coverage, patterns, derives }
+        })
+    }
+
+    /// The exports of every module that `tree` imports and can see.
+    fn imported_exports(
+        &self,
+        module: ModuleId,
+        tree: &ItemTree,
+    ) -> Vec<(ModuleName, Rc<Exports>)> {
+        let imported = tree.imports.iter().filter_map(|import| {
+            let resolved = self.resolve_module(module, import.module)?;
+            Some((import.module, self.exports(resolved)))
+        });
+        imported.collect()
+    }
+
+    // Memoization
+
+    fn query<T: PartialEq + 'static>(
+        &self,
+        query: Query,
+        compute: impl FnOnce(&Self) -> T,
+    ) -> Rc<T> {
+        self.unwind_if_cancelled();
+        self.memos.read(query);
+        if let Some(value) = self.memos.verified(query, self.revision) {
+            return value;
+        }
+        if self.unchanged(query) {
+            if let Some(value) = self.memos.verify(query, self.revision) {
+                return value;
+            }
+        }
+        self.memos.execute(query, self.revision, || compute(self))
+    }
+
+    /// Whether none of the dependencies of a memoized query changed since
+    /// it was last verified, bringing each of them up to date first.
+    fn unchanged(&self, query: Query) -> bool {
+        let Some((verified_at, dependencies)) = self.memos.dependencies(query) else {
+            return false;
+        };
+        dependencies.into_iter().all(|dependency| self.changed_at(dependency) <= verified_at)
+    }
+
+    fn changed_at(&self, query: Query) -> Revision {
+        match query {
+            Query::FileText(file) => self.files.get(&file).map_or(0, |input| input.changed_at),
+            Query::Files => self.files_changed_at,
+            Query::Packages => self.packages_changed_at,
+            Query::Compiled => self.compiled_changed_at,
+            _ => {
+                self.refresh(query);
+                self.memos.changed_at(query).unwrap_or(self.revision)
+            }
+        }
+    }
+
+    /// Brings a query up to date, discarding its value.
+    fn refresh(&self, query: Query) {
+        match query {
+            Query::FileText(_) | Query::Files | Query::Packages | Query::Compiled => {}
+            Query::Parse(file) => drop(self.parse(file)),
+            Query::ItemTree(file) => drop(self.item_tree(file)),
+            Query::Lower(file) => drop(self.lower(file)),
+            Query::Modules => drop(self.modules()),
+            Query::Graph(package) => drop(self.graph(package)),
+            Query::Exports(module) => drop(self.exports(module)),
+            Query::Imports(module) => drop(self.imports(module)),
+            Query::Scope(module) => drop(self.scope(module)),
+            Query::Infer(file) => drop(self.infer(file)),
+            Query::Symbols => drop(self.symbols()),
+        }
+    }
+}
+
+/// The modules visible from a module, as the type checker sees them. The
+/// checker borrows their item trees and scopes, so they're collected up
+/// front rather than queried as it goes.
+struct Snapshot {
+    modules: Vec<(ModuleName, Rc<ItemTree>, Rc<ModuleScope>)>,
+}
+
+impl Snapshot {
+    fn new(db: &Database, from: ModuleId) -> Snapshot {
+        let modules = db
+            .visible_modules(from)
+            .into_iter()
+            .filter_map(|(name, module)| Some((name, db.module_tree(module)?, db.scope(module)?)));
+        Snapshot { modules: modules.collect() }
+    }
+
+    fn get(&self, module: ModuleName) -> Option<&(ModuleName, Rc<ItemTree>, Rc<ModuleScope>)> {
+        self.modules.iter().find(|(name, _, _)| *name == module)
+    }
+}
+
+impl Workspace for Snapshot {
+    fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+        self.get(module).map(|(_, tree, _)| &**tree)
+    }
+
+    fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+        self.get(module).map(|(_, _, scope)| &**scope)
+    }
+
+    fn modules(&self) -> Vec<ModuleName> {
+        self.modules.iter().map(|&(name, _, _)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use lowering::{
+        item_tree::ItemTree,
+        name::{ModuleName, Name},
+    };
+    use resolution::interface::Namespace;
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{
+        Cancelled, Database, FileId, IndexConfig, ModuleId, PackageData, PackageGraph, Query,
+        QueryGroup,
+    };
+
+    #[test]
+    fn recompute_what_changed() {
+        let mut db = Database::new();
+        let (maybe, main) = (FileId(0), FileId(1));
+        db.set_file_text(
+            maybe,
+            "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 0\n",
+        );
+        db.set_file_text(main, "module Main where\n\nimport Data.Maybe\n\nmain = fromMaybe\n");
+
+        let name = ModuleId::File(main);
+        let scope = db.scope(name).unwrap();
+        assert_eq!(scope.lookup(None, Namespace::Value, Name::new("fromMaybe")).len(), 1);
+
+        // Editing a body leaves the item tree, and everything resolved from
+        // it, as it was.
+        db.set_file_text(
+            maybe,
+            "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 1\n",
+        );
+        let parse = db.parse(maybe);
+        assert!(Rc::ptr_eq(&db.scope(name).unwrap(), &scope));
+        assert!(Rc::ptr_eq(&db.parse(maybe), &parse));
+
+        db.set_file_text(
+            maybe,
+            "module Data.Maybe (Maybe) where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 1\n",
+        );
+        let edited = db.scope(name).unwrap();
+        assert!(!Rc::ptr_eq(&edited, &scope));
+        assert!(edited.lookup(None, Namespace::Value, Name::new("fromMaybe")).is_empty());
+
+        db.remove_file(maybe);
+        assert_eq!(db.module_file(ModuleName::new("Data.Maybe")), None);
+        assert_eq!(db.graph(None).diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn reuse_unchanged_bodies() {
+        let mut db = Database::new();
+        let main = FileId(0);
+        db.set_file_text(main, "module Main where\n\nf :: Int\nf = 1\n\ng = 2\n");
+        let before = db.infer(main);
+
+        // Editing `g` infers it again, but not `f`.
+        db.set_file_text(main, "module Main where\n\nf :: Int\nf = 1\n\ng = \"2\"\n");
+        let after = db.infer(main);
+        let unchanged: Vec<_> = before
+            .iter()
+            .map(|(body, types)| after.get(body).is_some_and(|after| Rc::ptr_eq(types, after)))
+            .collect();
+        assert_eq!(unchanged, [true, false]);
+    }
+
+    #[test]
+    fn cancel_on_edit() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Main where\n");
+        db.canceller().cancel();
+        assert_eq!(db.catch_cancelled(|db| db.item_tree(FileId(0)).name), Err(Cancelled));
+
+        db.set_file_text(FileId(0), "module Main where\n\nmain = 0\n");
+        let name = db.catch_cancelled(|db| db.item_tree(FileId(0)).name);
+        assert_eq!(name, Ok(Some(ModuleName::new("Main"))));
+    }
+
+    #[test]
+    fn index_in_parallel() {
+        let mut db = Database::new();
+        for index in 0..16 {
+            let text = format!("module M{index:02} where\n\nimport M{:02}\n", (index + 1) % 16);
+            db.set_file_text(FileId(index), text);
+        }
+        let source = "module M00 where\n\nimport M01\n".len();
+        db.index(&IndexConfig { threads: 4, memory_budget: 4 * source, cache: None });
+
+        let revision = db.revision;
+        let parses = (0..16)
+            .filter(|&index| {
+                db.memos.verified::<super::Parse>(Query::Parse(FileId(index)), revision).is_some()
+            })
+            .count();
+        assert_eq!(parses, 4);
+        assert!((0..16).all(|index| {
+            db.memos
+                .verified::<lowering::item_tree::ItemTree>(Query::ItemTree(FileId(index)), revision)
+                .is_some()
+        }));
+        assert_eq!(db.graph(None).diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn resolve_compiled_modules() {
+        let mut db = Database::new();
+        db.set_file_text(
+            FileId(0),
+            "module Main where\n\nimport Data.Maybe (Maybe(..))\n\nmain = Just 0\n",
+        );
+        let (node, _) =
+            parsing::parse_module("module Data.Maybe where\n\ndata Maybe a = Nothing | Just a\n");
+        db.set_compiled(vec![ItemTree::lower(&ast::Module::cast(node).unwrap())]);
+
+        let maybe = ModuleName::new("Data.Maybe");
+        let main = ModuleId::File(FileId(0));
+        assert_eq!(db.resolve_module(main, maybe), Some(ModuleId::Compiled(maybe)));
+        let scope = db.scope(main).unwrap();
+        assert_eq!(scope.lookup(None, Namespace::Constructor, Name::new("Just")).len(), 1);
+        assert!(db.imports(main).diagnostics().is_empty());
+
+        // A file that declares the module is preferred over its output.
+        db.set_file_text(FileId(1), "module Data.Maybe where\n");
+        assert_eq!(db.resolve_module(main, maybe), Some(ModuleId::File(FileId(1))));
+        assert_eq!(db.imports(main).diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn resolve_within_packages() {
+        let mut db = Database::new();
+        let files = [
+            "module Util where\n\nfromA = 0\n",
+            "module A.Lib where\n\nimport Util\n\nlib = fromA\n",
+            "module Util where\n\nfromB = 0\n",
+            "module B.Main where\n\nimport A.Lib\nimport Util\n\nmain = lib fromB\n",
+            "module A.Main where\n\nimport B.Main\n",
+        ];
+        for (index, text) in files.into_iter().enumerate() {
+            db.set_file_text(FileId(index as u32), text);
+        }
+        let mut packages = PackageGraph::default();
+        let a = packages.add(PackageData { name: "a".to_string(), dependencies: Some(vec![]) });
+        let b = packages.add(PackageData { name: "b".to_string(), dependencies: Some(vec![a]) });
+        let owners = [a, a, b, b, a];
+        packages.files.extend(
+            owners.into_iter().enumerate().map(|(index, package)| (FileId(index as u32), package)),
+        );
+        db.set_packages(packages);
+
+        let lookup = |db: &Database, file, name| {
+            let scope = db.scope(ModuleId::File(FileId(file))).unwrap();
+            scope.lookup(None, Namespace::Value, Name::new(name)).len()
+        };
+        assert_eq!(lookup(&db, 1, "fromA"), 1);
+        assert_eq!(lookup(&db, 3, "fromB"), 1);
+        assert_eq!(lookup(&db, 3, "fromA"), 0);
+        assert_eq!(lookup(&db, 3, "lib"), 1);
+        assert_eq!(lookup(&db, 4, "main"), 0);
+        // `A.Main` can't see the package that depends on it, and `Util` is
+        // only defined more than once when every package is in the graph.
+        assert_eq!(db.graph(Some(a)).diagnostics().len(), 1);
+        assert_eq!(db.graph(Some(b)).diagnostics().len(), 0);
+        assert_eq!(db.graph(None).diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn report_memory_usage() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Main where\n\nmain = 0\n");
+        let parse = db.parse(FileId(0));
+        db.scope(ModuleId::File(FileId(0)));
+
+        let entries: Vec<_> =
+            db.memory_usage(&|| 0).into_iter().map(|usage| (usage.group, usage.entries)).collect();
+        assert_eq!(
+            entries,
+            [
+                (QueryGroup::Parse, 1),
+                (QueryGroup::ItemTree, 1),
+                (QueryGroup::Lower, 0),
+                (QueryGroup::Modules, 0),
+                (QueryGroup::Resolution, 2),
+                (QueryGroup::Types, 0),
+                (QueryGroup::Symbols, 0),
+            ]
+        );
+        assert!(!Rc::ptr_eq(&db.parse(FileId(0)), &parse));
+    }
+}
@@ -0,0 +1,134 @@
+//! Memoization of queries with dependency tracking.
+//!
+//! Every query records the queries and inputs it reads while it runs. When
+//! an input changes, the revision is bumped, and a memoized query is reused
+//! if none of its dependencies changed since it was last verified. A query
+//! that's recomputed to a value equal to its previous one keeps the revision
+//! it last changed at, such that the queries that depend on it don't have to
+//! be recomputed either.
+
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use rustc_hash::FxHashMap;
+
+use crate::Query;
+
+/// Incremented whenever an input is set.
+pub(crate) type Revision = u32;
+
+struct Memo {
+    value: Rc<dyn Any>,
+    /// The revision at which the value was last different.
+    changed_at: Revision,
+    /// The revision at which the dependencies were last checked.
+    verified_at: Revision,
+    dependencies: Vec<Query>,
+}
+
+#[derive(Default)]
+pub(crate) struct Memos {
+    memos: RefCell<FxHashMap<Query, Memo>>,
+    /// The queries that are running, innermost last, along with the
+    /// dependencies each of them has read so far.
+    active: RefCell<Vec<(Query, Vec<Query>)>>,
+}
+
+impl Memos {
+    /// Forgets the queries that were running when they were unwound.
+    pub(crate) fn interrupt(&self) {
+        self.active.borrow_mut().clear();
+    }
+
+    /// Forgets the memoized queries that `evict` returns true for, returning
+    /// how many there were.
+    pub(crate) fn evict(&self, evict: impl Fn(Query) -> bool) -> usize {
+        let mut memos = self.memos.borrow_mut();
+        let before = memos.len();
+        memos.retain(|&query, _| !evict(query));
+        before - memos.len()
+    }
+
+    /// Records `query` as a dependency of the query that's running.
+    pub(crate) fn read(&self, query: Query) {
+        if let Some((_, dependencies)) = self.active.borrow_mut().last_mut() {
+            if !dependencies.contains(&query) {
+                dependencies.push(query);
+            }
+        }
+    }
+
+    /// Whether `query` is already running further up, which is a cycle.
+    pub(crate) fn is_active(&self, query: Query) -> bool {
+        self.active.borrow().iter().any(|&(active, _)| active == query)
+    }
+
+    /// The value of `query` if it was verified at `revision`.
+    pub(crate) fn verified<T: 'static>(&self, query: Query, revision: Revision) -> Option<Rc<T>> {
+        let memos = self.memos.borrow();
+        let memo = memos.get(&query).filter(|memo| memo.verified_at == revision)?;
+        memo.value.clone().downcast().ok()
+    }
+
+    /// The revision `query` was last verified at, and the dependencies it
+    /// had then, if it was ever computed.
+    pub(crate) fn dependencies(&self, query: Query) -> Option<(Revision, Vec<Query>)> {
+        let memos = self.memos.borrow();
+        memos.get(&query).map(|memo| (memo.verified_at, memo.dependencies.clone()))
+    }
+
+    pub(crate) fn changed_at(&self, query: Query) -> Option<Revision> {
+        self.memos.borrow().get(&query).map(|memo| memo.changed_at)
+    }
+
+    /// Marks `query` as verified at `revision`, returning its value.
+    pub(crate) fn verify<T: 'static>(&self, query: Query, revision: Revision) -> Option<Rc<T>> {
+        let mut memos = self.memos.borrow_mut();
+        let memo = memos.get_mut(&query)?;
+        memo.verified_at = revision;
+        memo.value.clone().downcast().ok()
+    }
+
+    /// Memoizes a value that was computed elsewhere, as though `query` had
+    /// read `dependencies` to compute it.
+    pub(crate) fn insert<T: 'static>(
+        &self,
+        query: Query,
+        value: T,
+        revision: Revision,
+        dependencies: Vec<Query>,
+    ) {
+        let memo = Memo {
+            value: Rc::new(value),
+            changed_at: revision,
+            verified_at: revision,
+            dependencies,
+        };
+        self.memos.borrow_mut().insert(query, memo);
+    }
+
+    /// Runs `compute` as `query`, recording what it reads, and memoizes its
+    /// value. The previous value is kept if it's equal to the new one.
+    pub(crate) fn execute<T: PartialEq + 'static>(
+        &self,
+        query: Query,
+        revision: Revision,
+        compute: impl FnOnce() -> T,
+    ) -> Rc<T> {
+        self.active.borrow_mut().push((query, vec![]));
+        let value = compute();
+        let (_, dependencies) = self.active.borrow_mut().pop().unwrap();
+
+        let mut memos = self.memos.borrow_mut();
+        let previous = memos.remove(&query).and_then(|memo| {
+            let changed_at = memo.changed_at;
+            memo.value.downcast::<T>().ok().map(|value| (value, changed_at))
+        });
+        let (value, changed_at) = match previous {
+            Some((previous, changed_at)) if *previous == value => (previous, changed_at),
+            _ => (Rc::new(value), revision),
+        };
+        let memo = Memo { value: value.clone(), changed_at, verified_at: revision, dependencies };
+        memos.insert(query, memo);
+        value
+    }
+}
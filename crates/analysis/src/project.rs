@@ -0,0 +1,194 @@
+//! Packages, and loading the source roots of a project into the database.
+//!
+//! Every file may belong to a package, and a module can only import the
+//! modules of its own package and of the packages it depends on. Tests are
+//! their own package that depends on the package they test, such that the
+//! sources can't import them.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use lowering::externs::{load_output, ExternsError};
+use project_model::{ProjectWorkspace, SourceRootKind};
+use rustc_hash::FxHashMap;
+use vfs::{FileId, Vfs};
+
+use crate::{Database, IndexConfig, IndexProgress, Query};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageId(pub u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageData {
+    pub name: String,
+    /// The packages whose modules can be imported, or `None` if they aren't
+    /// known, in which case every module can be.
+    pub dependencies: Option<Vec<PackageId>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageGraph {
+    pub packages: Vec<PackageData>,
+    pub files: FxHashMap<FileId, PackageId>,
+}
+
+impl PackageGraph {
+    pub fn add(&mut self, package: PackageData) -> PackageId {
+        self.packages.push(package);
+        PackageId(self.packages.len() as u32 - 1)
+    }
+
+    pub fn ids(&self) -> Vec<PackageId> {
+        (0..self.packages.len() as u32).map(PackageId).collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<PackageId> {
+        let index = self.packages.iter().position(|package| package.name == name)?;
+        Some(PackageId(index as u32))
+    }
+
+    pub fn package(&self, package: PackageId) -> &PackageData {
+        &self.packages[package.0 as usize]
+    }
+}
+
+impl Database {
+    pub fn set_packages(&mut self, packages: PackageGraph) {
+        self.bump();
+        self.packages = packages;
+        self.packages_changed_at = self.revision;
+    }
+
+    pub fn package(&self, file: FileId) -> Option<PackageId> {
+        self.memos.read(Query::Packages);
+        self.packages.files.get(&file).copied()
+    }
+
+    pub fn package_data(&self, package: PackageId) -> &PackageData {
+        self.memos.read(Query::Packages);
+        self.packages.package(package)
+    }
+
+    /// Whether a module in `from` can import the modules in `to`, where
+    /// files outside of any package can import, and be imported by, any.
+    pub fn is_visible(&self, from: Option<PackageId>, to: Option<PackageId>) -> bool {
+        let (Some(from), Some(to)) = (from, to) else { return true };
+        from == to
+            || self
+                .package_data(from)
+                .dependencies
+                .as_ref()
+                .is_none_or(|dependencies| dependencies.contains(&to))
+    }
+
+    /// Reads the files of every source root of the project into the
+    /// [`Vfs`], then applies and indexes them, such that the libraries are
+    /// as ready for going to a definition as the workspace itself. The
+    /// modules that `purs` compiled are read from its output directory, and
+    /// the files there that couldn't be read are returned.
+    pub fn load_project(
+        &mut self,
+        project: &ProjectWorkspace,
+        vfs: &mut Vfs,
+        config: &IndexConfig,
+        progress: &mut dyn FnMut(IndexProgress),
+    ) -> io::Result<Vec<(PathBuf, ExternsError)>> {
+        let mut packages = PackageGraph::default();
+        let named: Vec<_> = project.packages.iter().chain(&project.libraries).collect();
+        for package in &named {
+            packages.add(PackageData { name: package.name.clone(), dependencies: None });
+        }
+        // Dependencies that aren't installed are reported by the project
+        // model, and have no modules to import anyway.
+        let resolve = |packages: &PackageGraph, names: &[String]| -> Vec<_> {
+            names.iter().filter_map(|name| packages.find(name)).collect()
+        };
+        for (index, package) in named.iter().enumerate() {
+            // Libraries installed without a configuration don't list what
+            // they depend on, so they can see every module.
+            let is_library = index >= project.packages.len();
+            if !is_library || !package.dependencies.is_empty() {
+                let dependencies = resolve(&packages, &package.dependencies);
+                packages.packages[index].dependencies = Some(dependencies);
+            }
+        }
+        let mut tests = FxHashMap::default();
+        for (index, package) in project.packages.iter().enumerate() {
+            let Some(test) = &package.test else { continue };
+            let mut dependencies = vec![PackageId(index as u32)];
+            dependencies.extend(resolve(&packages, &package.dependencies));
+            dependencies.extend(resolve(&packages, &test.dependencies));
+            let name = test_package(&package.name);
+            let test = packages.add(PackageData { name, dependencies: Some(dependencies) });
+            tests.insert(package.name.as_str(), test);
+        }
+
+        for root in project.source_roots() {
+            let package = match root.kind {
+                SourceRootKind::Test => tests[root.package.as_str()],
+                _ => packages.find(&root.package).unwrap(),
+            };
+            let files = root.load(vfs)?;
+            if root.kind == SourceRootKind::Library {
+                self.libraries.extend(files.iter().copied());
+            }
+            packages.files.extend(files.into_iter().map(|file| (file, package)));
+        }
+        let loaded = IndexProgress::Loaded { packages: named.len(), files: packages.files.len() };
+        self.set_packages(packages);
+        let errors = self.load_output(&project.output);
+        progress(loaded);
+
+        let changes = vfs.take_changes();
+        self.apply_changes(vfs, changes);
+        self.index_with_progress(config, progress);
+        Ok(errors)
+    }
+
+    /// Reads the modules that `purs` compiled from its output directory,
+    /// replacing those read before, and returns the files there that
+    /// couldn't be read.
+    pub fn load_output(&mut self, output: &Path) -> Vec<(PathBuf, ExternsError)> {
+        let output = load_output(output);
+        self.set_compiled(output.trees);
+        output.errors
+    }
+
+    /// Adds a file that was created after the project was loaded to the
+    /// package of the source root it's in, returning whether it's in one.
+    pub fn add_project_file(
+        &mut self,
+        project: &ProjectWorkspace,
+        file: FileId,
+        path: &Path,
+    ) -> bool {
+        let roots = project.source_roots();
+        let Some(root) = roots.iter().find(|root| root.contains(path)) else { return false };
+        let name = match root.kind {
+            SourceRootKind::Test => test_package(&root.package),
+            _ => root.package.clone(),
+        };
+        let Some(package) = self.packages.find(&name) else { return false };
+        if root.kind == SourceRootKind::Library {
+            self.libraries.insert(file);
+        }
+        self.bump();
+        self.packages.files.insert(file, package);
+        self.packages_changed_at = self.revision;
+        true
+    }
+
+    /// Whether a file belongs to a dependency, which editor features such
+    /// as renaming shouldn't change.
+    pub fn is_library(&self, file: FileId) -> bool {
+        self.libraries.contains(&file)
+    }
+}
+
+/// The name of the package of the tests of a package, which can see the
+/// dependencies of both.
+fn test_package(package: &str) -> String {
+    format!("{package} (test)")
+}
@@ -0,0 +1,79 @@
+//! Reporting the memory that memoized queries hold.
+
+use crate::{Database, Query};
+
+/// The queries that memory usage is reported for, which are the stages of
+/// analysis rather than individual queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryGroup {
+    Parse,
+    ItemTree,
+    Lower,
+    Modules,
+    Resolution,
+    Types,
+    Symbols,
+}
+
+impl QueryGroup {
+    pub const ALL: [QueryGroup; 7] = [
+        QueryGroup::Parse,
+        QueryGroup::ItemTree,
+        QueryGroup::Lower,
+        QueryGroup::Modules,
+        QueryGroup::Resolution,
+        QueryGroup::Types,
+        QueryGroup::Symbols,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            QueryGroup::Parse => "parse trees",
+            QueryGroup::ItemTree => "item trees",
+            QueryGroup::Lower => "HIR",
+            QueryGroup::Modules => "module graphs",
+            QueryGroup::Resolution => "scopes",
+            QueryGroup::Types => "types",
+            QueryGroup::Symbols => "symbol index",
+        }
+    }
+
+    fn contains(self, query: Query) -> bool {
+        match query {
+            Query::FileText(_) | Query::Files | Query::Packages | Query::Compiled => false,
+            Query::Parse(_) => self == QueryGroup::Parse,
+            Query::ItemTree(_) => self == QueryGroup::ItemTree,
+            Query::Lower(_) => self == QueryGroup::Lower,
+            Query::Modules | Query::Graph(_) => self == QueryGroup::Modules,
+            Query::Exports(_) | Query::Imports(_) | Query::Scope(_) => {
+                self == QueryGroup::Resolution
+            }
+            Query::Infer(_) => self == QueryGroup::Types,
+            Query::Symbols => self == QueryGroup::Symbols,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub group: QueryGroup,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+impl Database {
+    /// Measures the memory each group of queries holds by evicting them one
+    /// by one, where `allocated` is the number of bytes that are currently
+    /// allocated, such as from a counting allocator. Evicted queries are
+    /// computed again when they're next asked for.
+    pub fn memory_usage(&mut self, allocated: &dyn Fn() -> usize) -> Vec<MemoryUsage> {
+        let mut usage = vec![];
+        for group in QueryGroup::ALL {
+            let before = allocated();
+            let entries = self.memos.evict(|query| group.contains(query));
+            let bytes = before.saturating_sub(allocated());
+            usage.push(MemoryUsage { group, entries, bytes });
+        }
+        usage
+    }
+}
@@ -0,0 +1,150 @@
+//! The names declared by every module, for searching the workspace.
+
+use std::rc::Rc;
+
+use lowering::{item_tree::Item, name::Name};
+use resolution::interface::Definition;
+
+use crate::{Database, FileId, Query};
+
+/// A name declared at the top level of a module, or a constructor or class
+/// member within a declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: Name,
+    pub file: FileId,
+    pub definition: Definition,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SymbolIndex {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolIndex {
+    /// The symbols that match `query`, best matches first. A character of
+    /// the query matches the character of a name right after the previous
+    /// match, or one that starts a word, such that `fB` matches `fooBar`
+    /// and `foo_bar`. Lowercase characters match either case, while
+    /// uppercase ones only match themselves.
+    pub fn search(&self, query: &str) -> Vec<&Symbol> {
+        let mut matches: Vec<_> = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| Some((fuzzy_score(query, symbol.name.as_str())?, symbol)))
+            .collect();
+        matches.sort_by_key(|&(score, symbol)| (score, symbol.name.as_str().len()));
+        matches.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+/// How well `query` matches `name`, where lower is better: exact matches
+/// come first, then those that only differ in case, then prefixes, then
+/// matches with the fewest gaps.
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    if name == query {
+        return Some(0);
+    }
+    if name.eq_ignore_ascii_case(query) {
+        return Some(1);
+    }
+    let name: Vec<char> = name.chars().collect();
+    let is_start = |index: usize| {
+        index == 0
+            || (name[index].is_uppercase() && !name[index - 1].is_uppercase())
+            || matches!(name[index - 1], '_' | '\'' | '.')
+            || (name[index].is_alphanumeric() != name[index - 1].is_alphanumeric())
+    };
+    let matches = |query: char, index: usize| {
+        let character = name[index];
+        character == query || (query.is_lowercase() && character.to_lowercase().eq([query]))
+    };
+
+    let mut gaps = 0;
+    let mut next = 0;
+    for character in query.chars() {
+        let found = (next..name.len()).find(|&index| {
+            matches(character, index) && (index == next && next > 0 || is_start(index))
+        })?;
+        if found != next {
+            gaps += 1;
+        }
+        next = found + 1;
+    }
+    Some(2 + gaps)
+}
+
+impl Database {
+    /// The symbols of every file, which is built along with the item trees
+    /// when indexing.
+    pub fn symbols(&self) -> Rc<SymbolIndex> {
+        self.query(Query::Symbols, |db| {
+            let mut symbols = vec![];
+            for file in db.files() {
+                let tree = db.item_tree(file);
+                let Some(module) = tree.name else { continue };
+                for (item, data) in tree.items.iter() {
+                    let mut push = |name: Name, member: Option<usize>| {
+                        let definition = Definition { module, item, member };
+                        symbols.push(Symbol { name, file, definition });
+                    };
+                    match data {
+                        Item::Value(value) => push(value.name, None),
+                        Item::Data(data) => {
+                            push(data.name, None);
+                            for (index, constructor) in data.constructors.iter().enumerate() {
+                                push(constructor.name, Some(index));
+                            }
+                        }
+                        Item::Newtype(newtype) => {
+                            push(newtype.name, None);
+                            push(newtype.constructor.name, Some(0));
+                        }
+                        Item::Synonym(synonym) => push(synonym.name, None),
+                        Item::Class(class) => {
+                            push(class.name, None);
+                            for (index, member) in class.members.iter().enumerate() {
+                                push(member.name, Some(index));
+                            }
+                        }
+                        Item::Instance(instance) => {
+                            if let Some(name) = instance.name {
+                                push(name, None);
+                            }
+                        }
+                        Item::ForeignData(data) => push(data.name, None),
+                        Item::ForeignValue(value) => push(value.name, None),
+                        Item::Fixity(fixity) => push(fixity.operator, None),
+                    }
+                }
+            }
+            SymbolIndex { symbols }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+
+    #[test]
+    fn fuzzy_matching() {
+        assert_eq!(fuzzy_score("map", "map"), Some(0));
+        assert_eq!(fuzzy_score("map", "mapMaybe"), Some(2));
+        assert!(fuzzy_score("mM", "mapMaybe") > fuzzy_score("mapM", "mapMaybe"));
+        assert!(fuzzy_score("fb", "foo_bar").is_some());
+        assert!(fuzzy_score("fb", "fooBar").is_some());
+        // Characters in the middle of a word only follow the previous match.
+        assert_eq!(fuzzy_score("ob", "fooBar"), None);
+        assert_eq!(fuzzy_score("Mb", "mapMaybe"), None);
+        assert_eq!(fuzzy_score("mapx", "mapMaybe"), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Signatures for the values that don't have one.
+
+use std::rc::Rc;
+
+use analysis::{Database, FileId, Inferred, ModuleId};
+use lowering::{lower_name, name::Name};
+use resolution::{
+    body::ModuleScope,
+    interface::{Definition, Namespace},
+};
+use rowan::{ast::AstNode, Direction, TextRange};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+use typecheck::{infer::BodyTypes, ty::TyId};
+
+use crate::{
+    edit::{indentation, TextEdit},
+    folding_ranges::text_range,
+    hover::value_body,
+    inlay_hints::BinderBodies,
+};
+
+/// A value of a module that doesn't have a signature, along with the one
+/// that's inferred for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MissingSignature {
+    /// The name of the value's first equation.
+    pub(crate) range: TextRange,
+    pub(crate) signature: String,
+    /// Inserts the signature above the first equation.
+    pub(crate) edit: TextEdit,
+}
+
+/// The values of `file` without a signature whose types are inferred.
+pub(crate) fn missing_signatures(db: &Database, file: FileId) -> Vec<MissingSignature> {
+    let parse = db.parse(file);
+    let signatures = Signatures::new(db, file);
+    let values = parse.module().declarations().filter_map(|declaration| match declaration {
+        ast::Declaration::Value(value) => Some(value),
+        _ => None,
+    });
+    let missing = values.filter_map(|value| {
+        let name = value.name()?;
+        if !is_first(value.syntax(), Some(name.clone())) {
+            return None;
+        }
+        signatures.inferred(value.syntax())
+    });
+    missing.collect()
+}
+
+/// Writes the signatures of the values and bindings of a file.
+pub(crate) struct Signatures<'a> {
+    db: &'a Database,
+    file: FileId,
+    text: Rc<str>,
+    types: Rc<Inferred>,
+    binders: BinderBodies,
+    scope: Option<Rc<ModuleScope>>,
+}
+
+impl Signatures<'_> {
+    pub(crate) fn new(db: &Database, file: FileId) -> Signatures<'_> {
+        Signatures {
+            db,
+            file,
+            text: db.file_text(file).unwrap_or_default(),
+            types: db.infer(file),
+            binders: BinderBodies::new(db, file),
+            scope: db.scope(ModuleId::File(file)),
+        }
+    }
+
+    /// The signature of the value or binding whose first equation is
+    /// `node`, which is the type inferred for it if it's known, and one read
+    /// off the equation otherwise.
+    pub(crate) fn missing(&self, node: &SyntaxNode) -> Option<MissingSignature> {
+        self.inferred(node).or_else(|| self.syntactic(node))
+    }
+
+    fn inferred(&self, node: &SyntaxNode) -> Option<MissingSignature> {
+        let (name, ty) = if let Some(value) = ast::ValueDeclaration::cast(node.clone()) {
+            let name = value.name()?;
+            let types =
+                self.types.get(value_body(self.db, self.file, lower_name(Some(name.clone())))?)?;
+            (name, self.render(types, types.ty?)?)
+        } else {
+            let binding = ast::LetBindingName::cast(node.clone())?;
+            let (body, binder) = self.binders.get(node)?;
+            let types = self.types.get(body)?;
+            (binding.name()?, self.render(types, types.binder(binder)?)?)
+        };
+        Some(self.signature(node, name, &ty))
+    }
+
+    /// Renders a type with the names of types and classes written the way
+    /// the file refers to them.
+    fn render(&self, types: &BodyTypes, ty: TyId) -> Option<String> {
+        if !types.types.is_known(ty) {
+            return None;
+        }
+        let scope = self.scope.as_deref();
+        Some(types.types.display_with(ty, &|namespace, constructor| {
+            written_name(scope, namespace, constructor.name, constructor.definition)
+        }))
+    }
+
+    fn syntactic(&self, node: &SyntaxNode) -> Option<MissingSignature> {
+        let (name, binders, guarded) =
+            if let Some(value) = ast::ValueDeclaration::cast(node.clone()) {
+                (value.name()?, value.binders(), value.guarded_expression())
+            } else {
+                let binding = ast::LetBindingName::cast(node.clone())?;
+                (binding.name()?, binding.binders(), binding.guarded_expression())
+            };
+        let ty = syntactic_type(binders, guarded);
+        Some(self.signature(node, name, &ty))
+    }
+
+    /// Inserts `name :: ty` above the equation at `node`, at the column that
+    /// the equation starts at such that it stays within the same block.
+    fn signature(&self, node: &SyntaxNode, name: ast::Name, ty: &str) -> MissingSignature {
+        let signature = format!("{} :: {ty}", name.syntax().text());
+        // The equation starts after the comments above it.
+        let start = text_range(node).map_or(node.text_range().start(), |range| range.start());
+        let edit =
+            TextEdit::insert(start, format!("{signature}\n{}", indentation(&self.text, start)));
+        MissingSignature { range: name.syntax().text_range(), signature, edit }
+    }
+}
+
+/// How the file with `scope` writes the name of a definition: unqualified
+/// if that refers to it, or else under the first alias it's imported with.
+pub(crate) fn written_name(
+    scope: Option<&ModuleScope>,
+    namespace: Namespace,
+    name: Name,
+    definition: Option<Definition>,
+) -> String {
+    let (Some(scope), Some(definition)) = (scope, definition) else {
+        return name.to_string();
+    };
+    if scope.lookup(None, namespace, name) == [definition] {
+        return name.to_string();
+    }
+    let aliases = scope.imports.qualified.iter().filter(|(_, names)| {
+        names.get(namespace).get(&name).is_some_and(|definitions| definitions.contains(&definition))
+    });
+    match aliases.map(|(&alias, _)| alias).min_by_key(|alias| alias.as_str()) {
+        Some(alias) => format!("{alias}.{name}"),
+        None => name.to_string(),
+    }
+}
+
+/// A type read off an equation for when checking can't tell, in which the
+/// arguments and the result are type variables unless they're literals or
+/// are annotated.
+fn syntactic_type(
+    binders: impl Iterator<Item = ast::Binder>,
+    guarded: Option<ast::GuardedExpression>,
+) -> String {
+    let mut variables = vec![];
+    let mut fresh = || {
+        // Named the way that checking names the variables it generalizes.
+        let variable = format!("t{}", variables.len());
+        variables.push(variable.clone());
+        variable
+    };
+    let mut types: Vec<_> =
+        binders.map(|binder| binder_type(&binder).unwrap_or_else(&mut fresh)).collect();
+    let result = match guarded {
+        Some(ast::GuardedExpression::Unconditional(unconditional)) => {
+            unconditional.expression().and_then(|expression| expression_type(&expression))
+        }
+        _ => None,
+    };
+    types.push(result.unwrap_or_else(&mut fresh));
+    let ty = types.join(" -> ");
+    if variables.is_empty() {
+        ty
+    } else {
+        format!("forall {}. {ty}", variables.join(" "))
+    }
+}
+
+fn binder_type(binder: &ast::Binder) -> Option<String> {
+    match binder {
+        ast::Binder::Literal(literal) => literal_type(literal.token()?.kind()),
+        ast::Binder::Negative(negative) => literal_type(negative.token()?.kind()),
+        ast::Binder::Parenthesized(parenthesized) => binder_type(&parenthesized.binder()?),
+        ast::Binder::Typed(typed) => Some(annotated_type(typed.type_()?.syntax(), true)),
+        _ => None,
+    }
+}
+
+fn expression_type(expression: &ast::Expression) -> Option<String> {
+    match expression {
+        ast::Expression::Literal(literal) => literal_type(literal.token()?.kind()),
+        ast::Expression::Parenthesized(parenthesized) => {
+            expression_type(&parenthesized.expression()?)
+        }
+        ast::Expression::Typed(typed) => Some(annotated_type(typed.type_()?.syntax(), false)),
+        _ => None,
+    }
+}
+
+fn literal_type(kind: SyntaxKind) -> Option<String> {
+    let ty = match kind {
+        SyntaxKind::LiteralInteger => "Int",
+        SyntaxKind::LiteralNumber => "Number",
+        SyntaxKind::LiteralString | SyntaxKind::LiteralRawString => "String",
+        SyntaxKind::LiteralChar => "Char",
+        SyntaxKind::LiteralTrue | SyntaxKind::LiteralFalse => "Boolean",
+        _ => return None,
+    };
+    Some(ty.to_string())
+}
+
+/// The text of an annotation, parenthesized if it's an argument that
+/// wouldn't be one otherwise.
+fn annotated_type(node: &SyntaxNode, is_argument: bool) -> String {
+    let text = node.text().to_string().trim().to_string();
+    let is_function = ["->", "=>", "forall"].iter().any(|word| text.contains(word));
+    if is_argument && is_function {
+        format!("({text})")
+    } else {
+        text
+    }
+}
+
+/// The name of a value or binding.
+pub(crate) fn equation_name(node: &SyntaxNode) -> Option<ast::Name> {
+    if let Some(value) = ast::ValueDeclaration::cast(node.clone()) {
+        value.name()
+    } else {
+        ast::LetBindingName::cast(node.clone())?.name()
+    }
+}
+
+/// The first equation of the value or binding that `node` is an equation
+/// of, which is `node` itself if the name has no equations before it.
+pub(crate) fn first_equation(node: &SyntaxNode) -> SyntaxNode {
+    let name = lower_name(equation_name(node));
+    // Equations of the same name are adjacent, so the first is the last of
+    // the run before `node`.
+    let previous = node.siblings(Direction::Prev).take_while(|sibling| {
+        let other = equation_name(sibling);
+        other.is_some() && lower_name(other) == name
+    });
+    previous.last().unwrap_or_else(|| node.clone())
+}
+
+/// Whether a value or binding is the first equation of its name, and has
+/// no signature among the declarations or bindings around it.
+pub(crate) fn is_first(node: &SyntaxNode, name: Option<ast::Name>) -> bool {
+    let name = lower_name(name);
+    let is_named = |other: Option<ast::Name>| other.is_some() && lower_name(other) == name;
+    let siblings = node.siblings(Direction::Prev).chain(node.siblings(Direction::Next));
+    let has_signature = siblings.into_iter().any(|sibling| {
+        if let Some(annotation) = ast::AnnotationDeclaration::cast(sibling.clone()) {
+            is_named(annotation.name())
+        } else if let Some(signature) = ast::LetBindingSignature::cast(sibling) {
+            is_named(signature.name())
+        } else {
+            false
+        }
+    });
+    let is_later = node.siblings(Direction::Prev).skip(1).any(|sibling| {
+        if let Some(value) = ast::ValueDeclaration::cast(sibling.clone()) {
+            is_named(value.name())
+        } else if let Some(binding) = ast::LetBindingName::cast(sibling) {
+            is_named(binding.name())
+        } else {
+            false
+        }
+    });
+    !has_signature && !is_later
+}
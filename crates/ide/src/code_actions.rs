@@ -0,0 +1,212 @@
+//! Edits offered for a range of a file, such as fixes for the diagnostics
+//! within it.
+
+mod case_split;
+mod do_notation;
+mod expand_synonym;
+mod explicit_import;
+mod extract;
+mod generate_function;
+mod hole;
+mod import;
+mod inline;
+mod instance_members;
+mod move_declaration;
+mod organize_imports;
+mod signature;
+
+use analysis::{Database, FileId};
+use rowan::TextRange;
+
+use crate::edit::WorkspaceEdit;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodeActionConfig {
+    pub import_style: ImportStyle,
+    pub import_layout: ImportLayout,
+}
+
+/// How a name that isn't in scope is imported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportStyle {
+    /// By adding it to the import list of its module.
+    #[default]
+    Item,
+    /// By importing its module qualified, and qualifying the name.
+    Qualified,
+    /// By importing every name of its module.
+    Open,
+}
+
+/// How organizing or sorting imports groups them and writes them out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportLayout {
+    pub grouping: ImportGrouping,
+    /// Whether the aliases of the qualified imports in a group are lined
+    /// up.
+    pub align_aliases: bool,
+    /// The width past which an import has an item of its list on each
+    /// line, if there's one.
+    pub max_width: Option<u32>,
+}
+
+/// Which imports are written together, in a block of their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportGrouping {
+    /// Unqualified imports, and then qualified ones.
+    #[default]
+    Qualification,
+    /// `Prelude`, then the modules of libraries, and then the modules of
+    /// the workspace.
+    Origin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeActionKind {
+    /// A fix for a diagnostic.
+    QuickFix,
+    /// A change to how code is written that keeps what it means.
+    RefactorRewrite,
+    /// Moving part of the code into a declaration or binding of its own.
+    RefactorExtract,
+    /// Replacing the uses of a binding or declaration with its body.
+    RefactorInline,
+    /// Moving a declaration into another module.
+    RefactorMove,
+    /// A rewrite of the imports of the whole file.
+    SourceOrganizeImports,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: CodeActionKind,
+    pub edit: WorkspaceEdit,
+}
+
+/// The actions that apply to `range`, in order, followed by those that
+/// apply to the whole file.
+pub fn code_actions(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    config: CodeActionConfig,
+) -> Vec<CodeAction> {
+    let mut actions = vec![];
+    import::import_fixes(db, file, range, config.import_style, &mut actions);
+    explicit_import::make_imports_explicit(db, file, range, &mut actions);
+    instance_members::add_missing_members(db, file, range, &mut actions);
+    hole::fill_holes(db, file, range, &mut actions);
+    generate_function::generate_function(db, file, range, &mut actions);
+    signature::add_signature(db, file, range, &mut actions);
+    case_split::split_cases(db, file, range, &mut actions);
+    do_notation::convert_do(db, file, range, &mut actions);
+    expand_synonym::expand_synonym(db, file, range, &mut actions);
+    extract::extract(db, file, range, &mut actions);
+    inline::inline(db, file, range, &mut actions);
+    move_declaration::move_declaration(db, file, range, &mut actions);
+    organize_imports::sort_imports(db, file, range, config.import_layout, &mut actions);
+    organize_imports::organize_imports(db, file, config.import_layout, &mut actions);
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::{TextRange, TextSize};
+
+    use super::{code_actions, CodeActionConfig, CodeActionKind, ImportStyle};
+    use crate::edit::apply_edits;
+
+    /// Applies the action titled `title` at the start of the first occurrence
+    /// of `find` in `files[main]`, where `files` are the texts of the files of
+    /// `db` by id, returning every file once it's applied.
+    pub(super) fn apply_action(
+        db: &Database,
+        files: &[&str],
+        main: usize,
+        find: &str,
+        title: &str,
+        config: CodeActionConfig,
+    ) -> Option<Vec<String>> {
+        let start = TextSize::from(files[main].find(find).unwrap() as u32);
+        apply_action_at(db, files, main, TextRange::empty(start), title, config)
+    }
+
+    /// Like [`apply_action`], but with the first occurrence of `find`
+    /// selected.
+    pub(super) fn apply_action_in_selection(
+        db: &Database,
+        files: &[&str],
+        main: usize,
+        find: &str,
+        title: &str,
+        config: CodeActionConfig,
+    ) -> Option<Vec<String>> {
+        let start = TextSize::from(files[main].find(find).unwrap() as u32);
+        let range = TextRange::at(start, TextSize::of(find));
+        apply_action_at(db, files, main, range, title, config)
+    }
+
+    fn apply_action_at(
+        db: &Database,
+        files: &[&str],
+        main: usize,
+        range: TextRange,
+        title: &str,
+        config: CodeActionConfig,
+    ) -> Option<Vec<String>> {
+        let actions = code_actions(db, FileId(main as u32), range, config);
+        let action = actions.into_iter().find(|action| action.title == title)?;
+        let mut applied: Vec<_> = files.iter().map(|text| text.to_string()).collect();
+        for (file, edits) in &action.edit.files {
+            applied[file.0 as usize] = apply_edits(files[file.0 as usize], edits);
+        }
+        Some(applied)
+    }
+
+    #[test]
+    fn import_unresolved_names() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a _ = a\n";
+        let prelude = "module Prelude (module Data.Maybe) where\n\nimport Data.Maybe\n";
+        let main = "module Main where\n\nimport Data.Maybe (Maybe)\n\nf = fromMaybe 1 (M.Just 2)\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), prelude);
+        db.set_file_text(FileId(2), main);
+
+        let fixed = |find: &str, import_style| {
+            let offset = TextSize::from(main.find(find).unwrap() as u32);
+            let config = CodeActionConfig { import_style, ..CodeActionConfig::default() };
+            let actions = code_actions(&db, FileId(2), TextRange::empty(offset), config);
+            let fixes =
+                actions.into_iter().filter(|action| action.kind == CodeActionKind::QuickFix);
+            let fixed = fixes.map(|action| {
+                let [(_, edits)] = &action.edit.files[..] else { panic!() };
+                (action.title, apply_edits(main, edits))
+            });
+            fixed.collect::<Vec<_>>()
+        };
+        assert_eq!(
+            fixed("fromMaybe", ImportStyle::Item),
+            [(
+                "Import `fromMaybe` from Data.Maybe".to_string(),
+                main.replace("(Maybe)", "(Maybe, fromMaybe)")
+            )]
+        );
+        assert_eq!(
+            fixed("fromMaybe", ImportStyle::Open)[0].1,
+            main.replace("import Data.Maybe (Maybe)", "import Data.Maybe")
+        );
+        assert_eq!(
+            fixed("fromMaybe", ImportStyle::Qualified)[0].1,
+            main.replace("(Maybe)", "(Maybe)\nimport Data.Maybe as Maybe")
+                .replace("= fromMaybe", "= Maybe.fromMaybe")
+        );
+        assert_eq!(
+            fixed("M.Just", ImportStyle::Item)[0].1,
+            main.replace("(Maybe)", "(Maybe)\nimport Data.Maybe as M")
+        );
+        assert_eq!(fixed("f =", ImportStyle::Item), []);
+    }
+}
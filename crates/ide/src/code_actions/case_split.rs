@@ -0,0 +1,158 @@
+//! Splitting a binder into a branch for each constructor of its type.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{item_tree::Item, name::Name};
+use resolution::interface::{Definition, Namespace};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+use typecheck::ty::Ty;
+
+use super::{CodeAction, CodeActionKind};
+use crate::{
+    annotations::written_name,
+    edit::{indentation, TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    inlay_hints::BinderBodies,
+    target::definition_module,
+};
+
+/// Offers to replace the branch or equation that a variable or wildcard
+/// binder at the start of `range` is in with one for each constructor of
+/// the binder's type. Each copy keeps the right-hand side, so a variable
+/// that it uses is kept as a named binder.
+pub(super) fn split_cases(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else { return };
+    let Some(binder) = token.parent_ancestors().find_map(ast::Binder::cast) else { return };
+    let name = match &binder {
+        ast::Binder::Variable(variable) => variable.name().map(|name| name.syntax().text()),
+        ast::Binder::Wildcard(_) => None,
+        _ => return,
+    };
+    let Some(branch) = binder.syntax().ancestors().find(|node| {
+        matches!(
+            node.kind(),
+            SyntaxKind::CaseBranch | SyntaxKind::ValueDeclaration | SyntaxKind::LetBindingName
+        )
+    }) else {
+        return;
+    };
+    // Binders within the right-hand side belong to a lambda or a binding.
+    let Some(guarded) = branch.children().find_map(ast::GuardedExpression::cast) else { return };
+    if guarded.syntax().text_range().contains_range(binder.syntax().text_range()) {
+        return;
+    }
+    let Some(constructors) = constructors(db, file, binder.syntax()) else { return };
+    let (Some(branch_range), Some(text)) = (text_range(&branch), db.file_text(file)) else {
+        return;
+    };
+
+    let name = name.map(|name| name.to_string());
+    let is_used = name.as_ref().is_some_and(|name| {
+        let tokens =
+            guarded.syntax().descendants_with_tokens().filter_map(|element| element.into_token());
+        tokens.into_iter().any(|token| token.kind() == SyntaxKind::Lower && token.text() == name)
+    });
+    let is_argument = binder.syntax().parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            SyntaxKind::ValueDeclaration
+                | SyntaxKind::LetBindingName
+                | SyntaxKind::BinderConstructor
+                | SyntaxKind::BinderNamed
+        )
+    });
+    let Some(binder_range) = text_range(binder.syntax()) else { return };
+    let binder_range = binder_range - branch_range.start();
+    let branch_text = &text[branch_range];
+    let branches = constructors.iter().map(|(constructor, arity)| {
+        let mut pattern = constructor.clone();
+        pattern.extend(std::iter::repeat_n(" _", *arity));
+        let pattern = match &name {
+            Some(name) if is_used => {
+                if *arity > 0 {
+                    format!("{name}@({pattern})")
+                } else {
+                    format!("{name}@{pattern}")
+                }
+            }
+            _ if *arity > 0 && is_argument => format!("({pattern})"),
+            _ => pattern,
+        };
+        let before = &branch_text[..usize::from(binder_range.start())];
+        let after = &branch_text[usize::from(binder_range.end())..];
+        format!("{before}{pattern}{after}")
+    });
+    let separator = format!("\n{}", indentation(&text, branch_range.start()));
+    let branches: Vec<_> = branches.collect();
+    let mut edit = WorkspaceEdit::default();
+    edit.push(file, TextEdit::replace(branch_range, branches.join(&separator)));
+    let title = "Split into cases".to_string();
+    actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+}
+
+/// The constructors of the type of a binder, as the file writes them, along
+/// with the number of fields each has.
+fn constructors(db: &Database, file: FileId, binder: &SyntaxNode) -> Option<Vec<(String, usize)>> {
+    let (body, binder) = BinderBodies::new(db, file).get(binder)?;
+    let types = db.infer(file);
+    let types = types.get(body)?;
+    let (head, _) = types.types.spine(types.binder(binder)?);
+    let Ty::Constructor(constructor) = &types.types[head] else { return None };
+    let definition = constructor.definition?;
+    let tree = db.module_tree(definition_module(db, file, definition)?)?;
+    let constructors: Vec<(Name, usize)> = match &tree.items[definition.item] {
+        Item::Data(item) => item
+            .constructors
+            .iter()
+            .map(|constructor| (constructor.name, constructor.fields.len()))
+            .collect(),
+        Item::Newtype(item) => vec![(item.constructor.name, item.constructor.fields.len())],
+        _ => return None,
+    };
+    let scope = db.scope(ModuleId::File(file));
+    let constructors = constructors.into_iter().enumerate().map(|(index, (name, arity))| {
+        let definition = Definition { member: Some(index), ..definition };
+        let written =
+            written_name(scope.as_deref(), Namespace::Constructor, name, Some(definition));
+        (written, arity)
+    });
+    Some(constructors.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn split_into_cases() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\n\nf :: M.Maybe Int -> Int\nf m = case m of\n  x -> g x\n\ng :: M.Maybe Int -> Int\ng _ = 0\n\nh :: Int -> Int\nh n = n\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let split = |find: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action(&db, &[maybe, main], 1, find, "Split into cases", config)?;
+            Some(files.swap_remove(1))
+        };
+        assert_eq!(
+            split("x ->").unwrap(),
+            main.replace("  x -> g x", "  x@(M.Just _) -> g x\n  x@M.Nothing -> g x")
+        );
+        assert_eq!(
+            split("_ = 0").unwrap(),
+            main.replace("g _ = 0", "g (M.Just _) = 0\ng M.Nothing = 0")
+        );
+        assert_eq!(split("n = n"), None);
+        assert_eq!(split("g x\n"), None);
+    }
+}
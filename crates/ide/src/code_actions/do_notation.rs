@@ -0,0 +1,295 @@
+//! Converting between `do` blocks and the chains of binds they stand for.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::item_tree::Item;
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{
+    ast::{self, Associativity},
+    unparse::{Expression, Fixities, Fixity},
+};
+
+use super::{inline::is_open, CodeAction, CodeActionKind};
+use crate::{
+    edit::{indentation, TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+};
+
+const BIND: Fixity = Fixity { associativity: Associativity::Left, precedence: 1 };
+const DISCARD: Fixity = Fixity { associativity: Associativity::Left, precedence: 4 };
+
+/// A statement of a `do` block, as it's written out in a chain.
+enum Statement {
+    Bind(String, Expression, String),
+    Discard(Expression, String),
+    Let(String),
+}
+
+/// Offers to rewrite the innermost `do` block at the start of `range` into
+/// a chain of `>>=` and `*>`, the way lowering desugars it, and the chain of
+/// binds at the start of `range` into a `do` block.
+pub(super) fn convert_do(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let Some(fixities) = fixities(db, file) else { return };
+    let parse = db.parse(file);
+    let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else { return };
+    let Some(text) = db.file_text(file) else { return };
+
+    if let Some(do_) = token.parent_ancestors().find_map(ast::ExpressionDo::cast) {
+        if let Some(chain) = bind_chain(&text, &do_, &fixities) {
+            let mut edit = WorkspaceEdit::default();
+            edit.push(file, chain);
+            let title = "Convert `do` block to binds".to_string();
+            actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+        }
+    }
+
+    let chains = token.parent_ancestors().filter_map(ast::ExpressionOperatorChain::cast);
+    let block = chains.filter_map(|chain| do_block(&text, &chain, &fixities)).last();
+    if let Some(block) = block {
+        let mut edit = WorkspaceEdit::default();
+        edit.push(file, block);
+        let title = "Convert binds to a `do` block".to_string();
+        actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+    }
+}
+
+/// Replaces `do_` with a chain of binds, with a line for each statement.
+/// A discard is written with `*>` unless a bind follows it within the same
+/// lambda, which `*>` would take in as its operand, and with `>>= \_ ->`
+/// otherwise.
+fn bind_chain(text: &str, do_: &ast::ExpressionDo, fixities: &Fixities) -> Option<TextEdit> {
+    let range = text_range(do_.syntax())?;
+    let mut statements = vec![];
+    for statement in do_.statements() {
+        let statement = match statement {
+            ast::DoStatement::Bind(bind) => {
+                let binder = text_range(bind.binder()?.syntax())?;
+                let (expression, written) = expression(text, &bind.expression()?, fixities)?;
+                let binder = &text[binder];
+                let binder = match binder.contains(char::is_whitespace) {
+                    true => format!("({binder})"),
+                    false => binder.to_string(),
+                };
+                Statement::Bind(binder, expression, written)
+            }
+            ast::DoStatement::Discard(discard) => {
+                let (expression, written) = expression(text, &discard.expression()?, fixities)?;
+                Statement::Discard(expression, written)
+            }
+            ast::DoStatement::Let(let_) => {
+                Statement::Let(text[text_range(let_.syntax())?].to_string())
+            }
+        };
+        statements.push(statement);
+    }
+    let Some(Statement::Discard(last, last_text)) = statements.pop() else { return None };
+
+    let mut lines = vec![];
+    // Whether the statement before is a discard that's written with `*>`.
+    let mut after_discard = false;
+    for (index, statement) in statements.iter().enumerate() {
+        let line = match statement {
+            Statement::Bind(binder, expression, written) => {
+                format!("{} >>= \\{binder} ->", operand(expression, written, BIND, after_discard))
+            }
+            Statement::Discard(expression, written) => {
+                let rest = &statements[index + 1..];
+                let is_bound = rest
+                    .iter()
+                    .take_while(|statement| !matches!(statement, Statement::Let(_)))
+                    .any(|statement| matches!(statement, Statement::Bind(..)));
+                let line = match is_bound {
+                    true => {
+                        format!("{} >>= \\_ ->", operand(expression, written, BIND, after_discard))
+                    }
+                    false => format!("{} *>", operand(expression, written, DISCARD, after_discard)),
+                };
+                after_discard = !is_bound;
+                lines.push(line);
+                continue;
+            }
+            Statement::Let(written) => format!("{written} in"),
+        };
+        after_discard = false;
+        lines.push(line);
+    }
+    let last = match after_discard && last.needs_parentheses_after(DISCARD) {
+        true => format!("({last_text})"),
+        false => last_text,
+    };
+    lines.push(last);
+
+    let first = do_.statements().next()?;
+    let indentation = indentation(text, text_range(first.syntax())?.start());
+    let chain = lines.join(&format!("\n{indentation}"));
+    let chain = match is_open(do_.syntax()) {
+        true => chain,
+        false => format!("({chain})"),
+    };
+    Some(TextEdit::replace(range, chain))
+}
+
+/// The expression of a statement along with how it's written.
+fn expression(
+    text: &str,
+    expression: &ast::Expression,
+    fixities: &Fixities,
+) -> Option<(Expression, String)> {
+    let written = text[text_range(expression.syntax())?].to_string();
+    Some((Expression::from_ast(expression, fixities), written))
+}
+
+/// How an expression is written as an operand of an operator of `fixity`,
+/// or as the right operand of `*>` if it follows a discard.
+fn operand(expression: &Expression, written: &str, fixity: Fixity, after_discard: bool) -> String {
+    let parenthesized = match after_discard {
+        true => expression.needs_parentheses_after(DISCARD),
+        false => expression.needs_parentheses_before(fixity),
+    };
+    match parenthesized {
+        true => format!("({written})"),
+        false => written.to_string(),
+    }
+}
+
+/// Replaces an operator chain of `>>=` and `*>` with a `do` block of the
+/// statements it's made of, where a bind into `\_ ->` stays a bind, since
+/// a discard only takes `Unit`. Statements have to fit on a line, since
+/// the chain is rendered anew.
+fn do_block(
+    text: &str,
+    chain: &ast::ExpressionOperatorChain,
+    fixities: &Fixities,
+) -> Option<TextEdit> {
+    let range = text_range(chain.syntax())?;
+    let expression = Expression::from_ast(&ast::Expression::OperatorChain(chain.clone()), fixities);
+    let mut statements = vec![];
+    let last = statements_of(&expression, &mut statements);
+    if statements.is_empty() {
+        return None;
+    }
+    statements.push(last.to_string());
+    if statements.iter().any(|statement| statement.contains('\n')) {
+        return None;
+    }
+    let indentation = format!("{}  ", line_indentation(text, range.start()));
+    let block = format!("do\n{indentation}{}", statements.join(&format!("\n{indentation}")));
+    let block = match is_open(chain.syntax()) {
+        true => block,
+        false => format!("({block})"),
+    };
+    Some(TextEdit::replace(range, block))
+}
+
+/// Collects the statements of a chain of binds, returning the expression
+/// that it ends with.
+fn statements_of<'a>(expression: &'a Expression, statements: &mut Vec<String>) -> &'a Expression {
+    let Expression::Binary(binary) = expression else { return expression };
+    match (binary.operator.as_str(), &binary.rhs) {
+        (">>=", Expression::Lambda(binders, body)) if binders.len() == 1 => {
+            statements.push(format!("{} <- {}", binders[0], binary.lhs));
+            statements_of(body, statements)
+        }
+        ("*>", rhs) => {
+            discards(&binary.lhs, statements);
+            statements_of(rhs, statements)
+        }
+        _ => expression,
+    }
+}
+
+/// Collects the operands of a chain of `*>`, which associates to the left.
+fn discards(expression: &Expression, statements: &mut Vec<String>) {
+    match expression {
+        Expression::Binary(binary) if binary.operator == "*>" => {
+            discards(&binary.lhs, statements);
+            statements.push(binary.rhs.to_string());
+        }
+        expression => statements.push(expression.to_string()),
+    }
+}
+
+/// The whitespace at the start of the line that `offset` is on.
+pub(super) fn line_indentation(text: &str, offset: TextSize) -> String {
+    let before = &text[..usize::from(offset)];
+    let line = &text[before.rfind('\n').map_or(0, |newline| newline + 1)..];
+    let indented = TextSize::of(line) - TextSize::of(line.trim_start());
+    indentation(text, TextSize::of(&text[..text.len() - line.len()]) + indented)
+}
+
+/// The fixities of the operators in scope in `file`. `>>=` and `*>` have
+/// the fixities that the prelude gives them, unless the module says
+/// otherwise.
+pub(super) fn fixities(db: &Database, file: FileId) -> Option<Fixities> {
+    let module = ModuleId::File(file);
+    let scope = db.scope(module)?;
+    let mut fixities = Fixities::default();
+    fixities.insert_value(">>=", BIND);
+    fixities.insert_value("*>", DISCARD);
+    let imported = std::iter::once(&scope.imports.unqualified)
+        .chain(scope.imports.qualified.values())
+        .flat_map(|names| names.operators.iter())
+        .flat_map(|(name, definitions)| {
+            definitions.iter().map(move |definition| (name, definition))
+        });
+    let declared = scope.declarations.names.operators.iter();
+    for (name, definition) in imported.chain(declared) {
+        let Some(tree) =
+            db.resolve_module(module, definition.module).and_then(|module| db.module_tree(module))
+        else {
+            continue;
+        };
+        if let Item::Fixity(fixity) = &tree.items[definition.item] {
+            let fixity = Fixity::new(fixity.associativity, fixity.precedence);
+            fixities.insert_value(name.to_string(), fixity);
+        }
+    }
+    Some(fixities)
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn convert_do_notation() {
+        let mut db = Database::new();
+        let main = "module Main where\n\ninfixr 0 apply as $\n\nmain = do\n  x <- pure 1\n  log \"a\"\n  let y = x\n  log $ show y\n  pure y\n\nf = do\n  log \"a\"\n  x <- pure 1\n  pure x\n\ng = pure 1 >>= \\x -> log \"a\" *> pure x\n";
+        db.set_file_text(FileId(0), main);
+
+        let converted = |find: &str, title: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action(&db, &[main], 0, find, title, config)?;
+            Some(files.swap_remove(0))
+        };
+        assert_eq!(
+            converted("log $", "Convert `do` block to binds").unwrap(),
+            main.replace(
+                "do\n  x <- pure 1\n  log \"a\"\n  let y = x\n  log $ show y\n  pure y\n",
+                "pure 1 >>= \\x ->\n  log \"a\" *>\n  let y = x in\n  (log $ show y) *>\n  pure y\n"
+            )
+        );
+        // `*>` would take the bind after it in as its operand.
+        assert_eq!(
+            converted("do\n  log", "Convert `do` block to binds").unwrap(),
+            main.replace(
+                "do\n  log \"a\"\n  x <- pure 1\n  pure x\n",
+                "log \"a\" >>= \\_ ->\n  pure 1 >>= \\x ->\n  pure x\n"
+            )
+        );
+        assert_eq!(
+            converted("*> pure x", "Convert binds to a `do` block").unwrap(),
+            main.replace(
+                "pure 1 >>= \\x -> log \"a\" *> pure x",
+                "do\n  x <- pure 1\n  log \"a\"\n  pure x"
+            )
+        );
+        assert_eq!(converted("apply", "Convert binds to a `do` block"), None);
+    }
+}
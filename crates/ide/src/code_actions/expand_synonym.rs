@@ -0,0 +1,253 @@
+//! Replacing the use of a type synonym with what it stands for.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{item_tree::Item, lower_name, lower_qualified_name, name::Name};
+use resolution::{
+    body::Resolution,
+    reference::{ReferenceKind, Target},
+};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use super::{do_notation::line_indentation, CodeAction, CodeActionKind};
+use crate::{
+    edit::{apply_edits, TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    target::{definition_module, definition_node, reference_at, FileResolver},
+};
+
+/// Offers to expand the type synonym at the start of `range` into its
+/// definition, with the arguments it's applied to in place of its
+/// parameters. A synonym that's applied to more arguments than it has
+/// parameters is applied to the rest once it's expanded.
+pub(super) fn expand_synonym(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let Some(text) = db.file_text(file) else { return };
+    let parse = db.parse(file);
+    let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else { return };
+    let Some(constructor) = token.parent_ancestors().find_map(ast::TypeConstructor::cast) else {
+        return;
+    };
+    let Some(reference) = reference_at(db, file, range.start()) else { return };
+    let (ReferenceKind::Name(_), Target::Global(definition)) = (reference.kind, reference.target)
+    else {
+        return;
+    };
+    let Some(module) = definition_module(db, file, definition) else { return };
+    let Some(tree) = db.module_tree(module) else { return };
+    let Item::Synonym(synonym) = &tree.items[definition.item] else { return };
+    let Some((definition_file, node)) = definition_node(db, module, definition) else { return };
+    let Some(declaration) = ast::TypeDeclaration::cast(node) else { return };
+    let Some(rhs) = declaration.type_() else { return };
+    let Some(definition_text) = db.file_text(definition_file) else { return };
+    if !is_in_scope(db, file, definition_file, &rhs) {
+        return;
+    }
+
+    let parameters: Vec<_> =
+        declaration.variables().map(|variable| lower_name(variable.name())).collect();
+    let application = constructor.syntax().parent().and_then(ast::TypeApplication::cast);
+    let application = application.filter(|application| {
+        application.head().is_some_and(|head| head.syntax() == constructor.syntax())
+    });
+    let arguments: Vec<_> =
+        application.iter().flat_map(|application| application.arguments()).collect();
+    if arguments.len() < parameters.len() {
+        return;
+    }
+    let (applied, rest) = arguments.split_at(parameters.len());
+    // Arguments are parenthesized anew where they're substituted.
+    let Some(arguments) = applied.iter().cloned().map(unparenthesized).collect::<Option<Vec<_>>>()
+    else {
+        return;
+    };
+    let Some(argument_texts) = arguments
+        .iter()
+        .map(|argument| Some(text[text_range(argument.syntax())?].to_string()))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return;
+    };
+
+    // A `forall` within the definition would capture the variables of an
+    // argument, or shadow a parameter.
+    let bound = rhs.syntax().descendants().filter_map(ast::TypeForall::cast).flat_map(|forall| {
+        forall.variables().map(|variable| lower_name(variable.name())).collect::<Vec<_>>()
+    });
+    let argument_variables: Vec<_> = arguments
+        .iter()
+        .flat_map(|argument| argument.syntax().descendants())
+        .filter_map(ast::TypeVariable::cast)
+        .filter_map(|variable| Some(Name::new(&variable.name_ref()?.syntax().text().to_string())))
+        .collect();
+    for name in bound.collect::<Vec<_>>() {
+        if parameters.contains(&name) || argument_variables.contains(&name) {
+            return;
+        }
+    }
+
+    let Some(rhs_range) = text_range(rhs.syntax()) else { return };
+    let mut substitutions = vec![];
+    for variable in rhs.syntax().descendants().filter_map(ast::TypeVariable::cast) {
+        let Some(name_ref) = variable.name_ref() else { continue };
+        let name = Name::new(&name_ref.syntax().text().to_string());
+        let Some(index) = parameters.iter().position(|parameter| *parameter == name) else {
+            continue;
+        };
+        let Some(range) = text_range(variable.syntax()) else { return };
+        let argument = match needs_parentheses(variable.syntax(), false, &arguments[index]) {
+            true => format!("({})", argument_texts[index]),
+            false => argument_texts[index].clone(),
+        };
+        substitutions.push(TextEdit::replace(range - rhs_range.start(), argument));
+    }
+    let expansion = apply_edits(&definition_text[rhs_range], &substitutions);
+
+    // The lines after the first keep their columns relative to the line the
+    // use is on, since the declaration starts at the margin.
+    let Some(usage) = application.as_ref().map_or_else(
+        || text_range(constructor.syntax()),
+        |application| {
+            let end = match applied.last() {
+                Some(last) => text_range(last.syntax())?.end(),
+                None => text_range(constructor.syntax())?.end(),
+            };
+            Some(TextRange::new(text_range(application.syntax())?.start(), end))
+        },
+    ) else {
+        return;
+    };
+    let indentation = line_indentation(&text, usage.start());
+    let expansion = expansion.replace('\n', &format!("\n{indentation}"));
+    let usage_node = match (&application, rest.is_empty()) {
+        (Some(application), true) => application.syntax().clone(),
+        _ => constructor.syntax().clone(),
+    };
+    let expansion = match needs_parentheses(&usage_node, !rest.is_empty(), &rhs) {
+        true => format!("({expansion})"),
+        false => expansion,
+    };
+
+    let mut edit = WorkspaceEdit::default();
+    edit.push(file, TextEdit::replace(usage, expansion));
+    let title = format!("Expand type synonym `{}`", synonym.name);
+    actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+}
+
+/// The type within the parentheses around `type_`, if there are any.
+fn unparenthesized(mut type_: ast::Type) -> Option<ast::Type> {
+    while let ast::Type::Parenthesized(parenthesized) = type_ {
+        type_ = parenthesized.type_()?;
+    }
+    Some(type_)
+}
+
+/// Whether the names that the definition at `rhs` refers to mean the same
+/// in `file` as they do in the file it's defined in.
+fn is_in_scope(db: &Database, file: FileId, definition_file: FileId, rhs: &ast::Type) -> bool {
+    if file == definition_file {
+        return true;
+    }
+    let (Some(resolver), Some(scope)) =
+        (FileResolver::new(db, definition_file), db.scope(ModuleId::File(file)))
+    else {
+        return false;
+    };
+    let tokens = rhs.syntax().descendants_with_tokens().filter_map(|element| element.into_token());
+    for token in tokens.filter(|token| !token.kind().is_trivia()) {
+        let offset = token.text_range().start();
+        let Some(reference) = resolver.reference_at(offset) else { continue };
+        let ReferenceKind::Name(namespace) = reference.kind else { continue };
+        if reference.range.start() != offset {
+            continue;
+        }
+        let resolution = match reference.target {
+            Target::Global(definition) => Resolution::Global(definition),
+            Target::Prim(name) => Resolution::Prim(name),
+            Target::Ambiguous(_) => return false,
+            _ => continue,
+        };
+        let Some(name) = token.parent_ancestors().find_map(ast::QualifiedName::cast) else {
+            continue;
+        };
+        if scope.resolve(namespace, lower_qualified_name(&name)) != Some(resolution) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether `type_` has to be parenthesized to take the place of `node`, or
+/// to be applied to arguments if `is_applied`.
+/// Applications only need them as arguments, while types that extend as
+/// far to the right as they can, such as arrows, need them anywhere but
+/// on their own or at the end of an arrow.
+fn needs_parentheses(node: &SyntaxNode, is_applied: bool, type_: &ast::Type) -> bool {
+    let is_open = match type_ {
+        ast::Type::Application(_) => false,
+        ast::Type::Forall(_)
+        | ast::Type::Constrained(_)
+        | ast::Type::Constraints(_)
+        | ast::Type::Arrow(_)
+        | ast::Type::OperatorChain(_)
+        | ast::Type::Kinded(_) => true,
+        _ => return false,
+    };
+    if is_applied {
+        return is_open;
+    }
+    let Some(parent) = node.parent() else { return false };
+    match parent.kind() {
+        SyntaxKind::TypeApplication => true,
+        SyntaxKind::TypeArrow => {
+            let is_argument = ast::TypeArrow::cast(parent)
+                .and_then(|arrow| arrow.argument())
+                .is_some_and(|argument| argument.syntax() == node);
+            is_argument && is_open
+        }
+        SyntaxKind::TypeOperatorChain | SyntaxKind::TypeKinded => is_open,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn expand_synonyms() {
+        let mut db = Database::new();
+        let types = "module Types where\n\ntype Pair a = { first :: a, second :: a }\n\ntype Function a b = a -> b\n\ntype Hidden = Secret\n\ndata Secret = Secret\n";
+        let main = "module Main where\n\nimport Types (Pair, Function, Hidden)\n\nf :: Pair (Array Int) -> Function Int Int -> Array Int\nf _ _ = []\n\ng :: Function Int Int\ng x = x\n\nh :: Hidden\nh = h\n";
+        db.set_file_text(FileId(0), types);
+        db.set_file_text(FileId(1), main);
+
+        let expanded = |find: &str, synonym: &str| {
+            let config = CodeActionConfig::default();
+            let title = format!("Expand type synonym `{synonym}`");
+            let mut files = apply_action(&db, &[types, main], 1, find, &title, config)?;
+            Some(files.swap_remove(1))
+        };
+        assert_eq!(
+            expanded("Pair (", "Pair").unwrap(),
+            main.replace(":: Pair (Array Int)", ":: { first :: Array Int, second :: Array Int }")
+        );
+        assert_eq!(
+            expanded("Function Int Int ->", "Function").unwrap(),
+            main.replace("-> Function Int Int ->", "-> (Int -> Int) ->")
+        );
+        assert_eq!(
+            expanded("Function Int Int\n", "Function").unwrap(),
+            main.replace(":: Function Int Int\n", ":: Int -> Int\n")
+        );
+        // `Secret` isn't in scope in Main.
+        assert_eq!(expanded("Hidden\n", "Hidden"), None);
+        assert_eq!(expanded("Array Int\nf", "Array"), None);
+    }
+}
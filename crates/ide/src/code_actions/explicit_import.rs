@@ -0,0 +1,87 @@
+//! Replacing an open import with a list of the names it's used for.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::lower_import;
+use resolution::unused::used_items;
+use rowan::{ast::AstNode, TextRange};
+
+use super::{
+    organize_imports::{item_text, union},
+    CodeAction, CodeActionKind,
+};
+use crate::{
+    diagnostics::wildcard_imports,
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+};
+
+/// Offers to give each open import within `range` that's reported as a
+/// wildcard import a list of exactly the names that the file refers to
+/// through it. An import that nothing is used from is left to be removed
+/// instead.
+pub(super) fn make_imports_explicit(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return };
+    let lowered = db.lower(file);
+    for declaration in wildcard_imports(db, file) {
+        let Some(import_range) = text_range(declaration.syntax()) else { continue };
+        if import_range.intersect(range).is_none() {
+            continue;
+        }
+        let (Some(import), Some(module_name)) =
+            (lower_import(&declaration), declaration.module_name())
+        else {
+            continue;
+        };
+        let Some(module) = db.resolve_module(ModuleId::File(file), import.module) else { continue };
+        let exports = db.exports(module);
+        let items =
+            used_items(&lowered.module, &lowered.source_map, &scope, &import, &exports.interface);
+        if items.is_empty() {
+            continue;
+        }
+        let items: Vec<_> = union(items).iter().map(item_text).collect();
+        let Some(end) = text_range(module_name.syntax()).map(|range| range.end()) else { continue };
+        let mut edit = WorkspaceEdit::default();
+        edit.push(file, TextEdit::insert(end, format!(" ({})", items.join(", "))));
+        let title = format!("Make the import of {} explicit", import.module);
+        actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn make_open_imports_explicit() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a _ = a\n\ninfixr 5 fromMaybe as <|\n";
+        let array = "module Data.Array where\n\nlength :: forall a. Array a -> Int\nlength _ = 0\n\ndata Tree = Leaf | Node Tree Tree\n";
+        let main = "module Main where\n\nimport Data.Maybe\nimport Data.Array\n\nf :: Maybe Int -> Int\nf m = 1 <| m\n\ng = Just Nothing\n\nh = Leaf\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), array);
+        db.set_file_text(FileId(2), main);
+
+        let explicit = |find: &str, title: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action(&db, &[maybe, array, main], 2, find, title, config)?;
+            Some(files.swap_remove(2))
+        };
+        assert_eq!(
+            explicit("import Data.Maybe", "Make the import of Data.Maybe explicit").unwrap(),
+            main.replace("import Data.Maybe\n", "import Data.Maybe (Maybe(..), (<|))\n")
+        );
+        assert_eq!(
+            explicit("import Data.Array", "Make the import of Data.Array explicit").unwrap(),
+            main.replace("import Data.Array\n", "import Data.Array (Tree(Leaf))\n")
+        );
+        assert_eq!(explicit("f ::", "Make the import of Data.Maybe explicit"), None);
+    }
+}
@@ -0,0 +1,335 @@
+//! Extracting an expression into a binding of its own.
+
+use analysis::{Database, FileId};
+use resolution::reference::Target;
+use rowan::{ast::AstNode, Direction, NodeOrToken, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use super::{CodeAction, CodeActionKind};
+use crate::{
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    target::FileResolver,
+};
+
+/// A local that the extracted expression refers to but doesn't bind.
+struct Local {
+    name: String,
+    /// The range of its binder, which decides whether the new binding can
+    /// see it or has to take it as a parameter.
+    binder: TextRange,
+}
+
+/// Offers to extract the expression that `range` selects into a binding in
+/// the `where` of its equation, a `let` before its statement in a `do`
+/// block, or a declaration after its own. Locals that the new binding
+/// can't see become its parameters, in the order they're first used, and
+/// the selection is replaced by a call with them as arguments.
+pub(super) fn extract(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    if range.is_empty() {
+        return;
+    }
+    let Some(text) = db.file_text(file) else { return };
+    let parse = db.parse(file);
+    let Some(expression) = selected_expression(&parse.root, &text, range) else { return };
+    let Some(expression_range) = text_range(&expression) else { return };
+    let Some(resolver) = FileResolver::new(db, file) else { return };
+    let locals = free_locals(db, file, &resolver, &expression, expression_range);
+    let name = fresh_name(&parse.root);
+    let body = &text[expression_range];
+    let body_column = column(&text, expression_range.start());
+
+    let extracted = |kind: &str, hidden: &dyn Fn(TextRange) -> bool, insert: TextEdit| {
+        let parameters: Vec<_> = locals
+            .iter()
+            .filter(|local| hidden(local.binder))
+            .map(|local| local.name.as_str())
+            .collect();
+        let mut call = name.clone();
+        for parameter in &parameters {
+            call.push(' ');
+            call.push_str(parameter);
+        }
+        // A name on its own doesn't need the parentheses that the
+        // expression was in, while an application needs them wherever an
+        // argument does.
+        let mut replaced = expression_range;
+        if parameters.is_empty() {
+            let parenthesized = expression
+                .parent()
+                .filter(|parent| parent.kind() == SyntaxKind::ExpressionParenthesized);
+            replaced = parenthesized.as_ref().and_then(text_range).unwrap_or(replaced);
+        } else if is_argument(&expression) {
+            call = format!("({call})");
+        }
+        let mut edit = WorkspaceEdit::default();
+        edit.push(file, TextEdit::replace(replaced, call));
+        edit.push(file, insert);
+        let title = match kind {
+            "top-level" if parameters.is_empty() => "Extract into a top-level value".to_string(),
+            "top-level" => "Extract into a top-level function".to_string(),
+            kind => format!("Extract into a {kind} binding"),
+        };
+        CodeAction { title, kind: CodeActionKind::RefactorExtract, edit }
+    };
+    let binding = |column: usize, hidden: &dyn Fn(TextRange) -> bool| {
+        let parameters = locals.iter().filter(|local| hidden(local.binder));
+        let mut binding = name.clone();
+        for local in parameters {
+            binding.push(' ');
+            binding.push_str(&local.name);
+        }
+        // Lines after the first move along with it, keeping the layout
+        // within the expression. If they'd end up outside of the binding,
+        // the expression starts on a line of its own instead, with the rest
+        // indented past it.
+        let least = continuation_indentation(body);
+        let inline = column + binding.chars().count() + 3;
+        match least {
+            Some(least) if least + inline <= column + body_column => {
+                let start = column + 2;
+                let moved = moved(body, (start + 2) as isize - least as isize);
+                format!("{binding} =\n{}{moved}", " ".repeat(start))
+            }
+            _ => format!("{binding} = {}", moved(body, inline as isize - body_column as isize)),
+        }
+    };
+
+    let equation = expression.ancestors().find(|node| {
+        matches!(node.kind(), SyntaxKind::ValueDeclaration | SyntaxKind::LetBindingName)
+    });
+    let guarded = equation
+        .iter()
+        .flat_map(|equation| equation.children())
+        .find(|node| ast::GuardedExpression::can_cast(node.kind()));
+    let guarded_range = guarded.as_ref().and_then(text_range);
+    if let (Some(equation), Some(guarded), Some(guarded_range)) = (equation, guarded, guarded_range)
+    {
+        let where_clause = guarded.children().find_map(ast::WhereClause::cast);
+        let where_range = where_clause.as_ref().and_then(|clause| text_range(clause.syntax()));
+        // The binders of the equation's arguments and of what's already
+        // in its `where` are in scope there.
+        let hidden = |binder: TextRange| {
+            guarded_range.contains_range(binder)
+                && !where_range.is_some_and(|clause| clause.contains_range(binder))
+        };
+        let equation_column = column(&text, equation.text_range().start());
+        let bindings = where_clause.iter().flat_map(|clause| clause.bindings());
+        let first = bindings.clone().next().and_then(|first| text_range(first.syntax()));
+        let last = bindings.last().and_then(|last| text_range(last.syntax()));
+        let insert = match (where_range, first, last) {
+            (Some(_), Some(first), Some(last)) => {
+                let column = column(&text, first.start());
+                let new_text = format!("\n{}{}", " ".repeat(column), binding(column, &hidden));
+                TextEdit::insert(last.end(), new_text)
+            }
+            (Some(clause), _, _) => {
+                let column = equation_column + 2;
+                let new_text = format!("\n{}{}", " ".repeat(column), binding(column, &hidden));
+                TextEdit::insert(clause.end(), new_text)
+            }
+            (None, _, _) => {
+                let indent = " ".repeat(equation_column + 2);
+                let binding = binding(equation_column + 2, &hidden);
+                TextEdit::insert(guarded_range.end(), format!("\n{indent}where\n{indent}{binding}"))
+            }
+        };
+        actions.push(extracted("where", &hidden, insert));
+    }
+
+    let statement = expression.ancestors().find(|node| {
+        matches!(node.kind(), SyntaxKind::DoBind | SyntaxKind::DoDiscard | SyntaxKind::DoLet)
+    });
+    if let Some(statement_range) = statement.as_ref().and_then(text_range) {
+        let hidden = |binder: TextRange| statement_range.contains_range(binder);
+        let column = column(&text, statement_range.start());
+        let new_text = format!("let {}\n{}", binding(column + 4, &hidden), " ".repeat(column));
+        actions.push(extracted(
+            "let",
+            &hidden,
+            TextEdit::insert(statement_range.start(), new_text),
+        ));
+    }
+
+    if let Some(end) = declaration_end(&expression) {
+        let hidden = |_: TextRange| true;
+        let insert = TextEdit::insert(end, format!("\n\n{}", binding(0, &hidden)));
+        actions.push(extracted("top-level", &hidden, insert));
+    }
+}
+
+/// The end of the top-level declaration that `node` is in, after the rest
+/// of its equations if it's a value, since they're kept together.
+pub(super) fn declaration_end(node: &SyntaxNode) -> Option<TextSize> {
+    let declaration = node
+        .ancestors()
+        .find(|node| node.parent().is_some_and(|parent| ast::Module::can_cast(parent.kind())))?;
+    let name = ast::ValueDeclaration::cast(declaration.clone()).and_then(|value| value.name());
+    let name = name.map(|name| name.syntax().text().to_string());
+    let equations = declaration.siblings(Direction::Next).skip(1).take_while(|sibling| {
+        let value = ast::ValueDeclaration::cast(sibling.clone());
+        let sibling_name = value.and_then(|value| value.name());
+        name.is_some() && sibling_name.map(|name| name.syntax().text().to_string()) == name
+    });
+    let last = equations.last().unwrap_or(declaration);
+    Some(text_range(&last)?.end())
+}
+
+/// The expression that `range` selects, which has to be the whole of it
+/// apart from the whitespace around it.
+fn selected_expression(root: &SyntaxNode, text: &str, range: TextRange) -> Option<SyntaxNode> {
+    let selected = &text[range];
+    let start =
+        range.start() + TextSize::of(&selected[..selected.len() - selected.trim_start().len()]);
+    let range = TextRange::at(start, TextSize::of(selected.trim()));
+    let node = match root.covering_element(range) {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent()?,
+    };
+    let mut expressions = node.ancestors().filter(|node| ast::Expression::can_cast(node.kind()));
+    expressions.find(|expression| text_range(expression) == Some(range))
+}
+
+/// The locals that the expression at `node` refers to, but which are bound
+/// outside of it.
+fn free_locals(
+    db: &Database,
+    file: FileId,
+    resolver: &FileResolver,
+    node: &SyntaxNode,
+    range: TextRange,
+) -> Vec<Local> {
+    let lowered = db.lower(file);
+    let mut locals: Vec<Local> = vec![];
+    let tokens = node.descendants_with_tokens().filter_map(|element| element.into_token());
+    for token in tokens.filter(|token| token.kind() == SyntaxKind::Lower) {
+        let Some(reference) = resolver.reference_at(token.text_range().start()) else { continue };
+        let Target::Local { body, binder } = reference.target else { continue };
+        let Some(binder) = lowered.source_map.body(body).binder_syntax(binder) else { continue };
+        let binder = binder.text_range();
+        if range.contains_range(binder) || locals.iter().any(|local| local.binder == binder) {
+            continue;
+        }
+        locals.push(Local { name: token.text().to_string(), binder });
+    }
+    locals
+}
+
+/// Whether the expression at `node` is an argument, or anything else that
+/// an application has to be parenthesized in.
+fn is_argument(node: &SyntaxNode) -> bool {
+    let Some(parent) = node.parent() else { return false };
+    match parent.kind() {
+        SyntaxKind::ExpressionApplication => parent.children().next().as_ref() != Some(node),
+        SyntaxKind::ExpressionRecordAccess
+        | SyntaxKind::ExpressionRecordUpdate
+        | SyntaxKind::ExpressionNegate
+        | SyntaxKind::ExpressionTypeArgument => true,
+        _ => false,
+    }
+}
+
+/// A name for the new binding that nothing in the file is named already.
+fn fresh_name(root: &SyntaxNode) -> String {
+    let names: Vec<_> = root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == SyntaxKind::Lower)
+        .map(|token| token.text().to_string())
+        .collect();
+    let mut name = "extracted".to_string();
+    let mut count = 0;
+    while names.contains(&name) {
+        count += 1;
+        name = format!("extracted{count}");
+    }
+    name
+}
+
+fn column(text: &str, offset: TextSize) -> usize {
+    let before = &text[..usize::from(offset)];
+    before[before.rfind('\n').map_or(0, |newline| newline + 1)..].chars().count()
+}
+
+/// The least indentation of the lines of `text` after the first.
+fn continuation_indentation(text: &str) -> Option<usize> {
+    let lines = text.split('\n').skip(1).filter(|line| !line.trim().is_empty());
+    lines.map(|line| line.len() - line.trim_start_matches(' ').len()).min()
+}
+
+/// Moves the lines of `text` after the first by `shift` columns.
+fn moved(text: &str, shift: isize) -> String {
+    let mut lines = text.split('\n');
+    let mut moved = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        moved.push('\n');
+        let indentation = line.len() - line.trim_start_matches(' ').len();
+        let column = (indentation as isize + shift).max(0) as usize;
+        if !line.trim().is_empty() {
+            moved.push_str(&" ".repeat(column));
+            moved.push_str(&line[indentation..]);
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action_in_selection, CodeActionConfig};
+
+    #[test]
+    fn extract_expressions() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nf x = g (x + 1) x\n  where\n  g a b = a\n\nh y = \\z -> y + (z * 2)\n\nmain = do\n  n <- pure 1\n  pure (n * n)\n\nk = do\n  log 1\n  log 2\n\nsomeLongerName = do\n  log 3\n";
+        db.set_file_text(FileId(0), main);
+
+        let extracted = |selected: &str, title: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action_in_selection(&db, &[main], 0, selected, title, config)?;
+            Some(files.swap_remove(0))
+        };
+        assert_eq!(
+            extracted("x + 1", "Extract into a where binding").unwrap(),
+            main.replace("g (x + 1) x", "g extracted x")
+                .replace("  g a b = a\n", "  g a b = a\n  extracted = x + 1\n")
+        );
+        assert_eq!(
+            extracted("x + 1", "Extract into a top-level function").unwrap(),
+            main.replace("g (x + 1) x", "g (extracted x) x")
+                .replace("  g a b = a\n", "  g a b = a\n\nextracted x = x + 1\n")
+        );
+        // Locals bound within the equation become parameters of its `where`.
+        assert_eq!(
+            extracted("z * 2", "Extract into a where binding").unwrap(),
+            main.replace("y + (z * 2)\n", "y + (extracted z)\n  where\n  extracted z = z * 2\n")
+        );
+        assert_eq!(
+            extracted("n * n", "Extract into a let binding").unwrap(),
+            main.replace("  pure (n * n)", "  let extracted = n * n\n  pure extracted")
+        );
+        // Lines after the first keep their layout relative to it.
+        assert_eq!(
+            extracted("do\n  log 1\n  log 2", "Extract into a top-level value").unwrap(),
+            main.replace(
+                "k = do\n  log 1\n  log 2\n",
+                "k = extracted\n\nextracted = do\n          log 1\n          log 2\n"
+            )
+        );
+        assert_eq!(
+            extracted("do\n  log 3\n", "Extract into a top-level value").unwrap(),
+            main.replace(
+                "someLongerName = do\n  log 3\n",
+                "someLongerName = extracted\n\nextracted =\n  do\n    log 3\n"
+            )
+        );
+        // Only whole expressions are extracted.
+        assert_eq!(extracted("+ 1", "Extract into a where binding"), None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Generating a function for a hole or a name that isn't in scope, from how
+//! it's applied.
+
+use std::cell::RefCell;
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    lower_qualified_name,
+    name::{Name, QualifiedName},
+};
+use resolution::{body::ModuleScope, diagnostics::ResolutionDiagnostic, interface::Namespace};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxNode};
+use typecheck::{
+    infer::{Origin, TypeDiagnostic},
+    ty::{Ty, TyId, TypeConstructor, Types, Unknown},
+};
+
+use super::{extract::declaration_end, CodeAction, CodeActionKind};
+use crate::{
+    annotations::written_name,
+    diagnostics::resolution_diagnostics,
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+};
+
+/// Offers to generate a top-level function after the declaration that a
+/// hole or a name that isn't in scope is applied in, where the hole is
+/// replaced with a call of it. The function takes the types of the
+/// arguments and returns what the hole, or the application of the name,
+/// is expected to, and its body is a hole of its own.
+pub(super) fn generate_function(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let lowered = db.lower(file);
+    let scope = db.scope(ModuleId::File(file));
+    let inferred = db.infer(file);
+
+    for (body, types) in inferred.iter() {
+        for diagnostic in types.diagnostics() {
+            let &TypeDiagnostic::Hole { origin, name, ty, .. } = diagnostic else { continue };
+            let Origin::Expr(expr) = origin else { continue };
+            let Some(ptr) = lowered.source_map.body(body).expr_syntax(expr) else { continue };
+            let node = ptr.to_node(&parse.root);
+            let Some(hole_range) = text_range(&node) else { continue };
+            if hole_range.intersect(range).is_none() {
+                continue;
+            }
+            let in_scope = scope
+                .as_ref()
+                .is_some_and(|scope| !scope.lookup(None, Namespace::Value, name).is_empty());
+            if in_scope || binds(&node, name) {
+                continue;
+            }
+            let Some(arguments) = arguments(&node) else { continue };
+            let mut parameters = vec![];
+            let mut result = ty;
+            for _ in &arguments {
+                let Some((parameter, rest)) = types.types.as_function(result) else { break };
+                parameters.push(parameter);
+                result = rest;
+            }
+            if parameters.len() < arguments.len() {
+                continue;
+            }
+            let signature = signature(&types.types, scope.as_deref(), &parameters, Some(result));
+            let Some(function) = function(&node, name, &arguments, &signature) else { continue };
+            let mut edit = WorkspaceEdit::default();
+            edit.push(file, TextEdit::replace(hole_range, name.to_string()));
+            edit.push(file, function);
+            let title = format!("Generate function `{name}`");
+            actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+        }
+    }
+
+    for (name_range, diagnostic) in resolution_diagnostics(db, file) {
+        let ResolutionDiagnostic::UnresolvedName {
+            namespace: Namespace::Value,
+            name: QualifiedName { qualifier: None, name },
+            ..
+        } = diagnostic
+        else {
+            continue;
+        };
+        if name_range.intersect(range).is_none() {
+            continue;
+        }
+        let token = parse.root.token_at_offset(name_range.start()).right_biased();
+        let Some(variable) = token
+            .and_then(|token| token.parent_ancestors().find_map(ast::ExpressionVariable::cast))
+        else {
+            continue;
+        };
+        let node = variable.syntax();
+        let Some(arguments) = arguments(node) else { continue };
+        let body = lowered
+            .module
+            .bodies
+            .iter()
+            .map(|(body, _)| body)
+            .find(|&body| lowered.source_map.body(body).node_expr(node).is_some());
+        let Some(body) = body else { continue };
+        let (source_map, Some(types)) = (lowered.source_map.body(body), inferred.get(body)) else {
+            continue;
+        };
+        let parameters =
+            arguments.iter().map(|argument| types.expr(source_map.node_expr(argument.syntax())?));
+        let Some(parameters) = parameters.collect::<Option<Vec<_>>>() else { continue };
+        // A name that isn't in scope has no type of its own, so what it
+        // returns is what the application is checked against, if it is.
+        let application = node.parent().and_then(|parent| source_map.node_expr(&parent));
+        let result = application.and_then(|application| types.expected(application));
+        let signature = signature(&types.types, scope.as_deref(), &parameters, result);
+        let Some(function) = function(node, name, &arguments, &signature) else { continue };
+        let mut edit = WorkspaceEdit::default();
+        edit.push(file, function);
+        let title = format!("Generate function `{name}`");
+        actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+    }
+}
+
+/// The arguments that the expression at `node` is applied to, if it's
+/// applied to any and none of them are types.
+fn arguments(node: &SyntaxNode) -> Option<Vec<ast::Expression>> {
+    let application = ast::ExpressionApplication::cast(node.parent()?)?;
+    if application.head()?.syntax() != node {
+        return None;
+    }
+    let arguments: Vec<_> = application.arguments().collect();
+    if arguments.is_empty()
+        || arguments.iter().any(|argument| matches!(argument, ast::Expression::TypeArgument(_)))
+    {
+        return None;
+    }
+    Some(arguments)
+}
+
+/// Whether the declaration that `node` is in binds `name` anywhere, which
+/// would capture the call that replaces a hole.
+fn binds(node: &SyntaxNode, name: Name) -> bool {
+    let declaration = node
+        .ancestors()
+        .find(|node| node.parent().is_some_and(|parent| ast::Module::can_cast(parent.kind())));
+    let names = declaration.iter().flat_map(|declaration| declaration.descendants());
+    let mut names = names.filter_map(ast::Name::cast);
+    names.any(|found| found.syntax().text() == name.as_str())
+}
+
+/// The declaration of the function, inserted after the declaration that
+/// `node` is in. Arguments that are variables lend their names to the
+/// parameters.
+fn function(
+    node: &SyntaxNode,
+    name: Name,
+    arguments: &[ast::Expression],
+    signature: &str,
+) -> Option<TextEdit> {
+    let mut parameters: Vec<String> = vec![];
+    for (index, argument) in arguments.iter().enumerate() {
+        let variable = match argument {
+            ast::Expression::Variable(variable) => variable.name(),
+            _ => None,
+        };
+        let variable = variable
+            .map(|variable| lower_qualified_name(&variable))
+            .filter(|variable| variable.qualifier.is_none())
+            .map(|variable| variable.name.to_string());
+        let parameter = match variable {
+            Some(variable) if !parameters.contains(&variable) && variable != name.as_str() => {
+                variable
+            }
+            _ => {
+                let mut count = index + 1;
+                while parameters.contains(&format!("x{count}")) {
+                    count += 1;
+                }
+                format!("x{count}")
+            }
+        };
+        parameters.push(parameter);
+    }
+    let end = declaration_end(node)?;
+    let declaration =
+        format!("\n\n{name} :: {signature}\n{name} {} = ?{name}", parameters.join(" "));
+    Some(TextEdit::insert(end, declaration))
+}
+
+/// The type of a function from `parameters` to `result`, written the way
+/// the file refers to the names in it. Unknowns, errors, and a result
+/// that isn't known become type variables, which are quantified along
+/// with the variables that the types already mention.
+fn signature(
+    types: &Types,
+    scope: Option<&ModuleScope>,
+    parameters: &[TyId],
+    result: Option<TyId>,
+) -> String {
+    let mut variables = vec![];
+    for &ty in parameters.iter().chain(&result) {
+        free_variables(types, ty, &mut vec![], &mut variables);
+    }
+    let mentioned: Vec<_> = variables.iter().map(|variable| variable.to_string()).collect();
+    let generated: RefCell<Vec<(Option<Unknown>, String)>> = RefCell::new(vec![]);
+    let unknown = |unknown: Option<Unknown>| {
+        let mut generated = generated.borrow_mut();
+        if let Some((_, name)) =
+            generated.iter().find(|(found, _)| found.is_some() && *found == unknown)
+        {
+            return name.clone();
+        }
+        let is_taken = |name: &String| {
+            mentioned.contains(name) || generated.iter().any(|(_, generated)| generated == name)
+        };
+        let name = (0..).map(|index| format!("t{index}")).find(|name| !is_taken(name));
+        let name = name.unwrap_or_default();
+        generated.push((unknown, name.clone()));
+        name
+    };
+    let name = |namespace, constructor: &TypeConstructor| {
+        written_name(scope, namespace, constructor.name, constructor.definition)
+    };
+
+    let mut written: Vec<_> = parameters
+        .iter()
+        .map(|&parameter| {
+            let text = types.display_with_unknowns(parameter, &name, &unknown);
+            let is_function = types.as_function(parameter).is_some()
+                || matches!(types[parameter], Ty::Forall(..) | Ty::Constrained(..));
+            if is_function {
+                format!("({text})")
+            } else {
+                text
+            }
+        })
+        .collect();
+    written.push(match result {
+        Some(result) => types.display_with_unknowns(result, &name, &unknown),
+        None => unknown(None),
+    });
+    let ty = written.join(" -> ");
+    let quantified: Vec<_> = mentioned
+        .into_iter()
+        .chain(generated.into_inner().into_iter().map(|(_, name)| name))
+        .collect();
+    if quantified.is_empty() {
+        ty
+    } else {
+        format!("forall {}. {ty}", quantified.join(" "))
+    }
+}
+
+/// Collects the type variables in `ty` that aren't bound within it, in the
+/// order they appear.
+fn free_variables(types: &Types, ty: TyId, bound: &mut Vec<Name>, free: &mut Vec<Name>) {
+    match &types[ty] {
+        &Ty::Variable(name) | &Ty::Skolem(name, _) => {
+            if !bound.contains(&name) && !free.contains(&name) {
+                free.push(name);
+            }
+        }
+        &Ty::Application(function, argument) => {
+            free_variables(types, function, bound, free);
+            free_variables(types, argument, bound, free);
+        }
+        &Ty::Forall(name, _, body) => {
+            bound.push(name);
+            free_variables(types, body, bound, free);
+            bound.pop();
+        }
+        Ty::Row(fields, tail) => {
+            for &(_, field) in fields {
+                free_variables(types, field, bound, free);
+            }
+            if let &Some(tail) = tail {
+                free_variables(types, tail, bound, free);
+            }
+        }
+        Ty::Constrained(constraint, body) => {
+            for &argument in &constraint.arguments {
+                free_variables(types, argument, bound, free);
+            }
+            free_variables(types, *body, bound, free);
+        }
+        Ty::Error | Ty::Unknown(_) | Ty::Constructor(_) | Ty::Symbol(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn generate_functions() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nf :: Int -> String\nf n = ?render n [n]\n\ng :: forall a. a -> Array a\ng x = [missing x \"a\"]\n\nh :: Int\nh = ?value\n";
+        db.set_file_text(FileId(0), main);
+
+        let generated = |find: &str, name: &str| {
+            let config = CodeActionConfig::default();
+            let title = format!("Generate function `{name}`");
+            let mut files = apply_action(&db, &[main], 0, find, &title, config)?;
+            Some(files.swap_remove(0))
+        };
+        assert_eq!(
+            generated("?render", "render").unwrap(),
+            main.replace(
+                "?render n [n]\n",
+                "render n [n]\n\nrender :: Int -> Array Int -> String\nrender n x2 = ?render\n"
+            )
+        );
+        // The elements of the array are checked against `a`.
+        assert_eq!(
+            generated("missing", "missing").unwrap(),
+            main.replace(
+                "\"a\"]\n",
+                "\"a\"]\n\nmissing :: forall a. a -> String -> a\nmissing x x2 = ?missing\n"
+            )
+        );
+        assert_eq!(generated("?value", "value"), None);
+    }
+
+    #[test]
+    fn return_what_the_application_is_checked_against() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nuseG :: Int -> String\nuseG n = render n \"x\"\n";
+        db.set_file_text(FileId(0), main);
+        let config = CodeActionConfig::default();
+        let title = "Generate function `render`";
+        let files = apply_action(&db, &[main], 0, "render", title, config).unwrap();
+        assert_eq!(
+            files[0],
+            format!("{main}\nrender :: Int -> String -> String\nrender n x2 = ?render\n")
+        );
+    }
+}
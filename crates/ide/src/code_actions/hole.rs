@@ -0,0 +1,162 @@
+//! Filling in a typed hole.
+
+use analysis::{Database, FileId};
+use lowering::name::Name;
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxKind};
+use typecheck::{
+    infer::{Origin, TypeDiagnostic},
+    ty::{Ty, TyId, Types},
+};
+
+use super::{CodeAction, CodeActionKind};
+use crate::{
+    diagnostics::origin_range,
+    edit::{TextEdit, WorkspaceEdit},
+};
+
+/// Offers to replace each hole within `range` with the values in scope that
+/// fit it, in the order that checking found them, and then with a lambda or
+/// record of the hole's type whose body and fields are holes of their own.
+pub(super) fn fill_holes(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let lowered = db.lower(file);
+    for (body, types) in db.infer(file).iter() {
+        for diagnostic in types.diagnostics() {
+            let &TypeDiagnostic::Hole { origin, name, ty, ref candidates } = diagnostic else {
+                continue;
+            };
+            let Origin::Expr(expr) = origin else { continue };
+            let Some(ptr) = lowered.source_map.body(body).expr_syntax(expr) else { continue };
+            let Some(hole) = ast::ExpressionHole::cast(ptr.to_node(&parse.root)) else { continue };
+            let Some(hole_range) = origin_range(&parse.root, &lowered.source_map, body, origin)
+            else {
+                continue;
+            };
+            if hole_range.intersect(range).is_none() {
+                continue;
+            }
+            let mut fills: Vec<_> = candidates.iter().map(ToString::to_string).collect();
+            if let Some(skeleton) = skeleton(&types.types, ty, name) {
+                let is_lambda = skeleton.starts_with('\\');
+                fills.push(if is_lambda && needs_parentheses(hole.syntax()) {
+                    format!("({skeleton})")
+                } else {
+                    skeleton
+                });
+            }
+            for fill in fills {
+                let mut edit = WorkspaceEdit::default();
+                let title = format!("Replace `?{name}` with `{fill}`");
+                edit.push(file, TextEdit::replace(hole_range, fill));
+                actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+            }
+        }
+    }
+}
+
+/// A lambda taking each argument of a function type, and a record with each
+/// field of a record type, with holes for everything that's left.
+fn skeleton(types: &Types, ty: TyId, name: Name) -> Option<String> {
+    let mut ty = unquantified(types, ty);
+    let mut arguments = vec![];
+    while let Some((_, result)) = types.as_function(ty) {
+        arguments.push(format!("x{}", arguments.len() + 1));
+        ty = unquantified(types, result);
+    }
+    let body = match types.as_record(ty) {
+        Some((fields, _)) if !fields.is_empty() => {
+            let fields = fields.iter().map(|(label, _)| {
+                let label = label.as_str();
+                if is_identifier(label) {
+                    format!("{label}: ?{label}")
+                } else {
+                    format!("{label:?}: ?field")
+                }
+            });
+            format!("{{ {} }}", fields.collect::<Vec<_>>().join(", "))
+        }
+        _ if arguments.is_empty() => return None,
+        _ => format!("?{name}"),
+    };
+    if arguments.is_empty() {
+        Some(body)
+    } else {
+        Some(format!("\\{} -> {body}", arguments.join(" ")))
+    }
+}
+
+/// A type under its `forall`s and constraints.
+fn unquantified(types: &Types, mut ty: TyId) -> TyId {
+    while let Ty::Forall(_, _, body) | Ty::Constrained(_, body) = &types[ty] {
+        ty = *body;
+    }
+    ty
+}
+
+fn is_identifier(label: &str) -> bool {
+    let mut chars = label.chars();
+    chars.next().is_some_and(|char| char.is_lowercase() || char == '_')
+        && chars.all(|char| char.is_alphanumeric() || char == '_' || char == '\'')
+}
+
+/// Whether a lambda that replaces `node` has to be parenthesized, which is
+/// everywhere but where an expression ends at a delimiter or a new line.
+fn needs_parentheses(node: &syntax::SyntaxNode) -> bool {
+    !node.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            SyntaxKind::Unconditional
+                | SyntaxKind::ExpressionParenthesized
+                | SyntaxKind::ExpressionArray
+                | SyntaxKind::RecordField
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn fill_typed_holes() {
+        let mut db = Database::new();
+        let main = "module Main where\n\none :: Int\none = 1\n\nf :: Int -> Int\nf n = ?int\n\ng :: { name :: String, age :: Int }\ng = ?person\n\nh :: Array (Int -> Int)\nh = [?function]\n";
+        db.set_file_text(FileId(0), main);
+
+        let filled = |hole: &str, title: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action(&db, &[main], 0, hole, title, config)?;
+            Some(files.swap_remove(0))
+        };
+        assert_eq!(filled("?int", "Replace `?int` with `n`").unwrap(), main.replace("?int", "n"));
+        assert_eq!(
+            filled("?int", "Replace `?int` with `one`").unwrap(),
+            main.replace("?int", "one")
+        );
+        assert_eq!(
+            filled("?person", "Replace `?person` with `g`").unwrap(),
+            main.replace("?person", "g")
+        );
+        assert_eq!(
+            filled("?person", "Replace `?person` with `{ name: ?name, age: ?age }`").unwrap(),
+            main.replace("?person", "{ name: ?name, age: ?age }")
+        );
+        assert_eq!(
+            filled("?function", "Replace `?function` with `f`").unwrap(),
+            main.replace("?function", "f")
+        );
+        assert_eq!(
+            filled("?function", "Replace `?function` with `\\x1 -> ?function`").unwrap(),
+            main.replace("[?function]", "[\\x1 -> ?function]")
+        );
+        assert_eq!(filled("?int", "Replace `?int` with `f`"), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Importing a name that isn't in scope from a module that exports it.
+
+use analysis::{Database, FileId};
+use lowering::name::{ModuleName, Name, QualifiedName};
+use resolution::{
+    diagnostics::ResolutionDiagnostic,
+    interface::{Definition, Namespace},
+};
+use rowan::TextRange;
+
+use super::{CodeAction, CodeActionKind, ImportStyle};
+use crate::{
+    diagnostics::resolution_diagnostics,
+    edit::{TextEdit, WorkspaceEdit},
+    imports::{
+        import_edit, import_name, importable_modules, open_import_edit, qualified_import_edit,
+    },
+};
+
+/// Offers to import each name within `range` that doesn't resolve from every
+/// module that exports it. A qualified name is imported under its qualifier
+/// whatever the style, since that's how it's already written.
+pub(super) fn import_fixes(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    style: ImportStyle,
+    actions: &mut Vec<CodeAction>,
+) {
+    for (name_range, diagnostic) in resolution_diagnostics(db, file) {
+        let ResolutionDiagnostic::UnresolvedName { namespace, name, .. } = diagnostic else {
+            continue;
+        };
+        if name_range.intersect(range).is_none() {
+            continue;
+        }
+        for candidate in exporting_modules(db, file, namespace, name.name) {
+            let Some(edits) = import_fix(db, file, name_range, namespace, name, candidate, style)
+            else {
+                continue;
+            };
+            let mut edit = WorkspaceEdit::default();
+            for text_edit in edits {
+                edit.push(file, text_edit);
+            }
+            let title = format!("Import `{}` from {}", name.name, candidate.0);
+            actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+        }
+    }
+}
+
+/// The modules that `file` can import `name` from, by name. A definition
+/// that's re-exported is only imported from the module that declares it,
+/// unless that module isn't visible.
+fn exporting_modules(
+    db: &Database,
+    file: FileId,
+    namespace: Namespace,
+    name: Name,
+) -> Vec<(ModuleName, Definition)> {
+    let mut candidates = vec![];
+    for (module_name, module) in importable_modules(db, file) {
+        let exports = db.exports(module);
+        if let Some(&definition) = exports.interface.names.get(namespace).get(&name) {
+            candidates.push((module_name, definition));
+        }
+    }
+    candidates.sort_by_key(|&(module, definition)| definition.module != module);
+    let mut seen = vec![];
+    candidates.retain(|&(_, definition)| {
+        let is_new = !seen.contains(&definition);
+        seen.push(definition);
+        is_new
+    });
+    candidates.sort_by_key(|(module, _)| module.as_str());
+    candidates
+}
+
+fn import_fix(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    namespace: Namespace,
+    name: QualifiedName,
+    (module, definition): (ModuleName, Definition),
+    style: ImportStyle,
+) -> Option<Vec<TextEdit>> {
+    if let Some(qualifier) = name.qualifier {
+        return Some(vec![qualified_import_edit(db, file, module, qualifier)?]);
+    }
+    let is_operator = matches!(namespace, Namespace::Operator | Namespace::TypeOperator);
+    match style {
+        // Operators are left unqualified, since qualifying them reads poorly.
+        ImportStyle::Qualified if !is_operator => {
+            let tree = db.item_tree(file);
+            let imported = tree.imports.iter().filter(|import| import.module == module);
+            let mut edits = vec![];
+            let alias = match imported.filter_map(|import| import.alias).next() {
+                Some(alias) => alias,
+                None => {
+                    let alias = ModuleName::new(module.segments().last()?);
+                    edits.push(qualified_import_edit(db, file, module, alias)?);
+                    alias
+                }
+            };
+            edits.push(TextEdit::insert(range.start(), format!("{alias}.")));
+            Some(edits)
+        }
+        ImportStyle::Open => Some(vec![open_import_edit(db, file, module)?]),
+        ImportStyle::Item | ImportStyle::Qualified => {
+            let import = import_name(db, file, name.name, definition)?;
+            import_edit(db, file, module, &[import])
+        }
+    }
+}
@@ -0,0 +1,506 @@
+//! Inlining a binding or a declaration into the places that use it.
+
+use analysis::{Database, FileId, Lowered, ModuleId};
+use lowering::{
+    hir::{BinderId, BodyId},
+    name::Name,
+    scope::ScopeTree,
+};
+use resolution::{interface::Namespace, reference::Target};
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{
+    ast,
+    unparse::{Expression, Fixities, Fixity},
+    SyntaxKind, SyntaxNode,
+};
+
+use super::{do_notation::fixities, CodeAction, CodeActionKind};
+use crate::{
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    references::{is_declaration, references, references_in},
+    target::{definition_module, local_target, reference_at, FileResolver},
+};
+
+/// Offers to inline the value that the name at the start of `range` refers
+/// to, at that use of it or at every use, which also removes the value
+/// where nothing else can refer to it. Only values without arguments,
+/// guards, or a `where` of their own, whose body is a single line, are
+/// inlined: `let` and `where` bindings, and declarations of the module.
+///
+/// Where a local at a use would capture a name that the body refers to,
+/// the local is renamed, such that the body still refers to what it did.
+pub(super) fn inline(db: &Database, file: FileId, range: TextRange, actions: &mut Vec<CodeAction>) {
+    let Some(text) = db.file_text(file) else { return };
+    let Some(reference) = reference_at(db, file, range.start()) else { return };
+    let is_global = match reference.target {
+        Target::Local { .. } => false,
+        Target::Global(definition) => {
+            if definition_module(db, file, definition) != Some(ModuleId::File(file)) {
+                return;
+            }
+            true
+        }
+        _ => return,
+    };
+    let Some(resolver) = FileResolver::new(db, file) else { return };
+    let name = &text[reference.range];
+    let parse = db.parse(file);
+
+    let found = references_in(db, file, &reference, file);
+    let (declarations, uses): (Vec<_>, Vec<_>) =
+        found.into_iter().partition(|range| is_declaration(db, file, range.start()));
+    let mut equations = vec![];
+    let mut signatures = vec![];
+    for range in declarations {
+        let token = parse.root.token_at_offset(range.start()).right_biased();
+        let Some(node) = token.and_then(|token| token.parent()?.parent()) else { return };
+        match node.kind() {
+            SyntaxKind::ValueDeclaration | SyntaxKind::LetBindingName => equations.push(node),
+            SyntaxKind::AnnotationDeclaration | SyntaxKind::LetBindingSignature => {
+                signatures.push(node)
+            }
+            _ => return,
+        }
+    }
+    let [equation] = &equations[..] else { return };
+    let is_top_level = equation.parent().is_some_and(|parent| ast::Module::can_cast(parent.kind()));
+    if is_global != is_top_level {
+        return;
+    }
+    let Some(body) = simple_body(equation) else { return };
+    let Some(body_range) = text_range(body.syntax()) else { return };
+    let body_text = &text[body_range];
+    if body_text.contains('\n') || uses.iter().any(|range| body_range.contains_range(*range)) {
+        return;
+    }
+    // The label of a pun isn't resolved as a use of the value it stands
+    // for, so a value that might be punned is left alone.
+    if is_punned(&parse.root, name) {
+        return;
+    }
+
+    let Some(fixities) = fixities(db, file) else { return };
+    let expression = Expression::from_ast(&body, &fixities);
+    let lowered = db.lower(file);
+    let free = free_names(&lowered, &resolver, body.syntax(), body_range);
+    // The edit of each use, along with the locals that would capture names
+    // of the body there.
+    let mut inlined = vec![];
+    for &range in &uses {
+        let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else { return };
+        let Some(variable) = token.parent_ancestors().find_map(ast::ExpressionVariable::cast)
+        else {
+            return;
+        };
+        let node = variable.syntax().clone();
+        let new_text = match needs_parentheses(&node, &expression, &fixities) {
+            true => format!("({expression})"),
+            false => expression.to_string(),
+        };
+        let Some(captured) = captures(&lowered, &node, &free) else { return };
+        inlined.push((range, TextEdit::replace(node.text_range(), new_text), captured));
+    }
+
+    let taken: Vec<_> = parse
+        .root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == SyntaxKind::Lower)
+        .map(|token| token.text().to_string())
+        .collect();
+    // Renames the locals that would capture names of the body, apart from
+    // where they're removed along with the value.
+    let renames = |captured: &[(BodyId, BinderId)], removed: &[TextRange]| {
+        let mut edits = vec![];
+        for &(body, binder) in captured {
+            let Some(target) = local_target(db, file, body, binder) else { continue };
+            let Some(local) = resolver.reference_at(target.focus_range.start()) else { continue };
+            let old = &text[local.range];
+            let mut count = 1;
+            while taken.contains(&format!("{old}{count}")) {
+                count += 1;
+            }
+            let new_name = format!("{old}{count}");
+            for range in references_in(db, file, &local, file) {
+                if !removed.iter().any(|removed| removed.contains_range(range)) {
+                    edits.push(TextEdit::replace(range, new_name.clone()));
+                }
+            }
+        }
+        edits
+    };
+
+    let at_cursor =
+        inlined.iter().find(|(use_range, _, _)| use_range.contains_inclusive(range.start()));
+    if let Some((_, edit, captured)) = at_cursor.filter(|_| inlined.len() > 1) {
+        let mut workspace_edit = WorkspaceEdit::default();
+        workspace_edit.push(file, edit.clone());
+        for rename in renames(captured, &[]) {
+            workspace_edit.push(file, rename);
+        }
+        let title = format!("Inline `{name}`");
+        actions.push(CodeAction {
+            title,
+            kind: CodeActionKind::RefactorInline,
+            edit: workspace_edit,
+        });
+    }
+
+    // A declaration stays if it's exported by name, or exported along with
+    // every other declaration and used by other modules.
+    let is_kept = is_global && {
+        let has_exports = parse.module().header().and_then(|header| header.exports()).is_some();
+        let used_elsewhere = || {
+            let elsewhere = references(db, file, reference.range.start(), false);
+            elsewhere.iter().any(|found| found.file != file)
+        };
+        let exports = db.exports(ModuleId::File(file));
+        let is_exported = exports
+            .interface
+            .names
+            .get(Namespace::Value)
+            .get(&Name::new(name))
+            .is_some_and(|&exported| Target::Global(exported) == reference.target);
+        is_exported && (has_exports || used_elsewhere())
+    };
+    let removed = match is_kept {
+        true => vec![],
+        false => removed_ranges(&text, equation, &signatures, is_global),
+    };
+    if inlined.is_empty() && removed.is_empty() {
+        return;
+    }
+    let mut edit = WorkspaceEdit::default();
+    let mut captured = vec![];
+    for (_, use_edit, use_captured) in &inlined {
+        edit.push(file, use_edit.clone());
+        for capture in use_captured {
+            if !captured.contains(capture) {
+                captured.push(*capture);
+            }
+        }
+    }
+    for &range in &removed {
+        edit.push(file, TextEdit::replace(range, ""));
+    }
+    for rename in renames(&captured, &removed) {
+        edit.push(file, rename);
+    }
+    let title = format!("Inline `{name}` everywhere");
+    actions.push(CodeAction { title, kind: CodeActionKind::RefactorInline, edit });
+}
+
+/// The body of an equation that only has one, without arguments, guards,
+/// or a `where`.
+fn simple_body(equation: &SyntaxNode) -> Option<ast::Expression> {
+    let guarded = match ast::ValueDeclaration::cast(equation.clone()) {
+        Some(value) => {
+            if value.binders().next().is_some() {
+                return None;
+            }
+            value.guarded_expression()?
+        }
+        None => {
+            let binding = ast::LetBindingName::cast(equation.clone())?;
+            if binding.binders().next().is_some() {
+                return None;
+            }
+            binding.guarded_expression()?
+        }
+    };
+    let ast::GuardedExpression::Unconditional(unconditional) = guarded else { return None };
+    if unconditional.where_clause().is_some() {
+        return None;
+    }
+    unconditional.expression()
+}
+
+/// The unqualified names that the body at `node` refers to but doesn't
+/// bind, as what they refer to.
+fn free_names(
+    lowered: &Lowered,
+    resolver: &FileResolver,
+    node: &SyntaxNode,
+    range: TextRange,
+) -> Vec<(Name, Target)> {
+    let mut names = vec![];
+    let tokens = node.descendants_with_tokens().filter_map(|element| element.into_token());
+    for token in tokens.filter(|token| token.kind() == SyntaxKind::Lower) {
+        let qualified = token.parent().and_then(ast::QualifiedName::cast);
+        if qualified.is_some_and(|qualified| qualified.qualifier().is_some()) {
+            continue;
+        }
+        let Some(reference) = resolver.reference_at(token.text_range().start()) else { continue };
+        let is_bound_within = match reference.target {
+            Target::Local { body, binder } => {
+                let source_map = lowered.source_map.body(body);
+                let binder = source_map.binder_syntax(binder);
+                binder.is_some_and(|binder| range.contains_range(binder.text_range()))
+            }
+            Target::Global(_) => false,
+            _ => continue,
+        };
+        let name = (Name::new(token.text()), reference.target);
+        if !is_bound_within && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// The locals at `node` that would capture the names of `free`, or `None`
+/// if a local that the body refers to isn't in scope there.
+fn captures(
+    lowered: &Lowered,
+    node: &SyntaxNode,
+    free: &[(Name, Target)],
+) -> Option<Vec<(BodyId, BinderId)>> {
+    let (body, expr) = lowered
+        .module
+        .bodies
+        .iter()
+        .find_map(|(body, _)| Some((body, lowered.source_map.body(body).node_expr(node)?)))?;
+    let scopes = ScopeTree::new(&lowered.module.bodies[body]);
+    let mut captured = vec![];
+    for (name, target) in free {
+        let visible = scopes.resolve(expr, *name);
+        match (target, visible) {
+            (&Target::Local { body: bound, binder }, Some(visible)) => {
+                if bound != body {
+                    return None;
+                }
+                if visible != binder {
+                    captured.push((body, visible));
+                }
+            }
+            (Target::Local { .. }, None) => return None,
+            (_, Some(visible)) => captured.push((body, visible)),
+            (_, None) => {}
+        }
+    }
+    Some(captured)
+}
+
+/// Whether a record expression in the module has a pun for `name`.
+fn is_punned(root: &SyntaxNode, name: &str) -> bool {
+    root.descendants().filter_map(ast::RecordPun::cast).any(|pun| {
+        let in_expression = pun.syntax().ancestors().find_map(|node| match node.kind() {
+            SyntaxKind::ExpressionRecord => Some(true),
+            SyntaxKind::BinderRecord => Some(false),
+            _ => None,
+        });
+        let label = pun.label().map(|label| label.syntax().text().to_string());
+        in_expression == Some(true) && label.as_deref().map(str::trim) == Some(name)
+    })
+}
+
+/// Whether `expression` has to be parenthesized to take the place of the
+/// variable at `node`, which depends on the operators next to it in a
+/// chain, or on whether it's applied or an argument. Anywhere else, only
+/// expressions that could be arguments go without parentheses.
+fn needs_parentheses(node: &SyntaxNode, expression: &Expression, fixities: &Fixities) -> bool {
+    if is_open(node) {
+        return false;
+    }
+    let Some(parent) = node.parent() else { return false };
+    match parent.kind() {
+        SyntaxKind::ExpressionOperatorChain | SyntaxKind::ExpressionInfixChain => {
+            let before = neighbouring_operator(node, fixities, SyntaxNode::prev_sibling);
+            let after = neighbouring_operator(node, fixities, SyntaxNode::next_sibling);
+            before.is_some_and(|fixity| expression.needs_parentheses_after(fixity))
+                || after.is_some_and(|fixity| expression.needs_parentheses_before(fixity))
+        }
+        SyntaxKind::ExpressionApplication => {
+            let application = ast::ExpressionApplication::cast(parent);
+            let head = application.and_then(|application| application.head());
+            match head.is_some_and(|head| head.syntax() == node) {
+                true => expression.needs_parentheses_applied(),
+                false => expression.needs_parentheses_as_argument(),
+            }
+        }
+        _ => expression.needs_parentheses_as_argument(),
+    }
+}
+
+/// The fixity of the operator on one side of an operand of a chain, where
+/// a chain that's itself an operand of another chain is part of it.
+fn neighbouring_operator(
+    node: &SyntaxNode,
+    fixities: &Fixities,
+    sibling: impl Fn(&SyntaxNode) -> Option<SyntaxNode>,
+) -> Option<Fixity> {
+    let mut node = node.clone();
+    loop {
+        if let Some(operator) = sibling(&node) {
+            return Some(match ast::QualifiedName::cast(operator) {
+                Some(operator) => fixities.value(&operator.syntax().text().to_string()),
+                None => Fixity::DEFAULT,
+            });
+        }
+        let parent = node.parent()?;
+        let is_chain = |node: &SyntaxNode| {
+            matches!(
+                node.kind(),
+                SyntaxKind::ExpressionOperatorChain | SyntaxKind::ExpressionInfixChain
+            )
+        };
+        if !parent.parent().is_some_and(|grandparent| is_chain(&grandparent)) {
+            return None;
+        }
+        node = parent;
+    }
+}
+
+/// Whether the expression at `node` is all of what it's in, such that any
+/// expression can take its place without parentheses.
+pub(super) fn is_open(node: &SyntaxNode) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            SyntaxKind::ExpressionParenthesized
+                | SyntaxKind::Unconditional
+                | SyntaxKind::GuardedBranch
+                | SyntaxKind::DoBind
+                | SyntaxKind::DoDiscard
+                | SyntaxKind::ExpressionArray
+                | SyntaxKind::RecordField
+        )
+    })
+}
+
+/// What's removed along with a value: its lines and those of its signature,
+/// or the whole `let` or `where` if nothing else is bound in it.
+fn removed_ranges(
+    text: &str,
+    equation: &SyntaxNode,
+    signatures: &[SyntaxNode],
+    is_global: bool,
+) -> Vec<TextRange> {
+    let nodes: Vec<_> = signatures.iter().chain([equation]).cloned().collect();
+    if is_global {
+        return declaration_lines(text, &nodes);
+    }
+    let node_lines =
+        || nodes.iter().filter_map(|node| Some(lines(text, text_range(node)?, false))).collect();
+    let Some(parent) = equation.parent() else { return vec![] };
+    let is_alone = parent
+        .children()
+        .all(|child| !ast::LetBinding::can_cast(child.kind()) || nodes.contains(&child));
+    if !is_alone {
+        return node_lines();
+    }
+    let range = match parent.kind() {
+        SyntaxKind::WhereClause => {
+            let before = parent.prev_sibling().as_ref().and_then(text_range);
+            before
+                .zip(text_range(&parent))
+                .map(|(before, clause)| TextRange::new(before.end(), clause.end()))
+        }
+        SyntaxKind::DoLet => text_range(&parent).map(|range| lines(text, range, false)),
+        SyntaxKind::ExpressionLetIn => {
+            let inner =
+                ast::ExpressionLetIn::cast(parent.clone()).and_then(|let_in| let_in.expression());
+            let inner = inner.and_then(|inner| text_range(inner.syntax()));
+            text_range(&parent)
+                .zip(inner)
+                .map(|(let_in, inner)| TextRange::new(let_in.start(), inner.start()))
+        }
+        _ => None,
+    };
+    range.into_iter().collect()
+}
+
+/// The lines of the top-level declarations at `nodes`, with their doc
+/// comments. Declarations that follow each other, such as a signature and
+/// its equation, are taken as one, such that the blank line after them goes
+/// along with them.
+pub(super) fn declaration_lines(text: &str, nodes: &[SyntaxNode]) -> Vec<TextRange> {
+    let mut ranges: Vec<_> = nodes.iter().filter_map(text_range).collect();
+    ranges.sort_by_key(|range| range.start());
+    let mut merged: Vec<TextRange> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if text[TextRange::new(last.end(), range.start())].trim().is_empty() => {
+                *last = last.cover(range);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged.into_iter().map(|range| lines(text, range, true)).collect()
+}
+
+/// The lines of `range`, if nothing else is on them. Declarations also take
+/// their doc comment with them, and a blank line if they're between two.
+fn lines(text: &str, range: TextRange, is_declaration: bool) -> TextRange {
+    let (mut start, mut end) = (usize::from(range.start()), usize::from(range.end()));
+    let line_start = text[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    if text[line_start..start].trim().is_empty() {
+        start = line_start;
+    }
+    let line_end = text[end..].find('\n').map_or(text.len(), |newline| end + newline + 1);
+    if text[end..line_end].trim().is_empty() {
+        end = line_end;
+    }
+    if is_declaration {
+        while let Some(previous) =
+            text[..start.saturating_sub(1)].rfind('\n').map(|newline| newline + 1)
+        {
+            if start == 0 || !text[previous..start].trim_start().starts_with("--") {
+                break;
+            }
+            start = previous;
+        }
+        if start > 0 && text[..start].ends_with("\n\n") && text[end..].starts_with('\n') {
+            end += 1;
+        }
+    }
+    TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn inline_values() {
+        let mut db = Database::new();
+        let main = "module Main where\n\ninfixl 6 add as +\ninfixl 7 mul as *\n\n-- | The answer.\nanswer :: Int\nanswer = 40 + 2\n\nf x = twice + twice\n  where\n  twice = x * 2\n\ng y = let z = y in \\y -> z + y\n\nh = { answer: answer }\n\nk = 1\n\ni = { k }\n\nm y = v + v\n  where\n  v = y + 1\n";
+        db.set_file_text(FileId(0), main);
+
+        let inlined = |find: &str, title: &str| {
+            let config = CodeActionConfig::default();
+            let mut files = apply_action(&db, &[main], 0, find, title, config)?;
+            Some(files.swap_remove(0))
+        };
+        assert_eq!(
+            inlined("twice +", "Inline `twice`").unwrap(),
+            main.replace("twice + twice", "x * 2 + twice")
+        );
+        assert_eq!(
+            inlined("twice = ", "Inline `twice` everywhere").unwrap(),
+            main.replace("twice + twice\n  where\n  twice = x * 2\n", "x * 2 + x * 2\n")
+        );
+        // Only the operand on the right needs parentheses to keep what it
+        // associates with.
+        assert_eq!(
+            inlined("v = ", "Inline `v` everywhere").unwrap(),
+            main.replace("v + v\n  where\n  v = y + 1\n", "y + 1 + (y + 1)\n")
+        );
+        // The lambda's `y` would capture the `y` of the body, so it's renamed.
+        assert_eq!(
+            inlined("z +", "Inline `z` everywhere").unwrap(),
+            main.replace("let z = y in \\y -> z + y", "\\y1 -> y + y1")
+        );
+        // The module exports everything, but nothing else uses the value.
+        assert_eq!(
+            inlined("answer }", "Inline `answer` everywhere").unwrap(),
+            main.replace("-- | The answer.\nanswer :: Int\nanswer = 40 + 2\n\n", "")
+                .replace("{ answer: answer }", "{ answer: 40 + 2 }")
+        );
+        assert_eq!(inlined("f x", "Inline `f` everywhere"), None);
+        assert_eq!(inlined("k = ", "Inline `k` everywhere"), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Adding the members that an instance is missing.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    arena::Arena,
+    hir::{Type, TypeId},
+    name::Name,
+};
+use resolution::{
+    instances::{resolve_class, InstanceDiagnostic},
+    interface::Namespace,
+};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use super::{CodeAction, CodeActionKind};
+use crate::{
+    diagnostics::instance_diagnostics,
+    edit::{indentation, TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    rename::is_valid,
+    target::visible_trees,
+};
+
+/// Offers to add an equation for each member that an instance within
+/// `range` is missing, after its last member, with a binder for each
+/// argument of the member's signature and a hole for the body.
+pub(super) fn add_missing_members(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return };
+    let Some(text) = db.file_text(file) else { return };
+    let parse = db.parse(file);
+    let trees = visible_trees(db, ModuleId::File(file));
+    let tree = |name| trees.iter().find(|(module, _)| *module == name).map(|(_, tree)| &**tree);
+    for (head_range, diagnostic) in instance_diagnostics(db, file) {
+        let InstanceDiagnostic::MissingMembers { class, names, ptr } = diagnostic else {
+            continue;
+        };
+        let Some(instance) = ptr.to_node(&parse.root).parent() else { continue };
+        let (Some(instance), Some(instance_range)) =
+            (ast::InstanceDeclaration::cast(instance.clone()), text_range(&instance))
+        else {
+            continue;
+        };
+        if instance_range.intersect(range).is_none() && head_range.intersect(range).is_none() {
+            continue;
+        }
+        let Some((definition, item)) = resolve_class(&scope, class, tree) else { continue };
+        let Some(class_tree) = tree(definition.module) else { continue };
+        let arguments: Vec<_> = instance
+            .head()
+            .into_iter()
+            .flat_map(|head| head.arguments())
+            .map(|argument| argument_name(argument.syntax()))
+            .collect();
+        let variables: Vec<_> = item.variables.iter().map(|variable| variable.name).collect();
+        let equations = names.iter().filter_map(|&name| {
+            let signature = item.members.iter().find(|signature| signature.name == name)?;
+            let binders = binders(&class_tree.types, signature.type_, &variables, &arguments);
+            let mut equation = name.to_string();
+            for binder in binders {
+                equation.push(' ');
+                equation.push_str(&binder);
+            }
+            Some(format!("{equation} = ?body"))
+        });
+        let equations: Vec<_> = equations.collect();
+
+        // Members line up with the first one, or else are indented within
+        // the instance.
+        let first = instance.members().next().and_then(|member| text_range(member.syntax()));
+        let indent = match first {
+            Some(first) => indentation(&text, first.start()),
+            None => format!("{}  ", indentation(&text, instance_range.start())),
+        };
+        let has_where = instance
+            .syntax()
+            .children_with_tokens()
+            .any(|element| element.kind() == SyntaxKind::WhereKw);
+        let mut new_text = if has_where { String::new() } else { " where".to_string() };
+        for equation in equations {
+            new_text.push_str(&format!("\n{indent}{equation}"));
+        }
+        let mut edit = WorkspaceEdit::default();
+        edit.push(file, TextEdit::insert(instance_range.end(), new_text));
+        let title = "Add missing members".to_string();
+        actions.push(CodeAction { title, kind: CodeActionKind::QuickFix, edit });
+    }
+}
+
+/// The name an argument of an instance head gives to binders of its type.
+fn argument_name(node: &SyntaxNode) -> Option<String> {
+    let tokens = node.descendants_with_tokens().filter_map(|element| element.into_token());
+    let mut tokens =
+        tokens.filter(|token| matches!(token.kind(), SyntaxKind::Upper | SyntaxKind::Lower));
+    let token = tokens.next()?;
+    Some(lowercase(token.text()))
+}
+
+/// A binder for each argument of a member's signature, named after its
+/// type, where a variable of the class is named after the type of the
+/// instance in its place. Names that come up more than once are numbered.
+fn binders(
+    types: &Arena<Type>,
+    mut ty: TypeId,
+    variables: &[Name],
+    arguments: &[Option<String>],
+) -> Vec<String> {
+    let mut names = vec![];
+    loop {
+        match &types[ty] {
+            Type::Forall { type_, .. }
+            | Type::Constrained { type_, .. }
+            | Type::Kinded { type_, .. } => ty = *type_,
+            &Type::Arrow { argument, result } => {
+                names.push(binder_name(types, argument, variables, arguments));
+                ty = result;
+            }
+            _ => break,
+        }
+    }
+    let mut counts: Vec<(String, usize)> = vec![];
+    let numbered = names.iter().map(|name| {
+        if names.iter().filter(|other| *other == name).count() == 1 {
+            return name.clone();
+        }
+        let count = match counts.iter_mut().find(|(other, _)| other == name) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                counts.push((name.clone(), 1));
+                1
+            }
+        };
+        format!("{name}{count}")
+    });
+    numbered.collect()
+}
+
+fn binder_name(
+    types: &Arena<Type>,
+    ty: TypeId,
+    variables: &[Name],
+    arguments: &[Option<String>],
+) -> String {
+    let name = match &types[ty] {
+        Type::Variable(variable) => match variables.iter().position(|other| other == variable) {
+            Some(position) => arguments.get(position).cloned().flatten(),
+            None => Some(variable.to_string()),
+        },
+        Type::Constructor(constructor) => Some(lowercase(constructor.name.as_str())),
+        &Type::Application { function, .. } => {
+            return binder_name(types, function, variables, arguments);
+        }
+        &Type::Kinded { type_, .. } => return binder_name(types, type_, variables, arguments),
+        Type::Arrow { .. } => Some("f".to_string()),
+        Type::Record(_) => Some("record".to_string()),
+        _ => None,
+    };
+    name.filter(|name| is_valid(Namespace::Value, name)).unwrap_or_else(|| "x".to_string())
+}
+
+fn lowercase(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn add_missing_members() {
+        let mut db = Database::new();
+        let classes = "module Classes where\n\nclass Container f where\n  empty :: forall a. f a\n  insert :: forall a. a -> f a -> f a\n  merge :: forall a. f a -> f a -> f a\n  size :: forall a. f a -> Int\n";
+        let main = "module Main where\n\nimport Classes (class Container)\n\ndata Stack a = Stack (Array a)\n\ninstance Container Stack where\n  empty = Stack []\n\ndata Queue a = Queue\n\ninstance Container Queue\n";
+        db.set_file_text(FileId(0), classes);
+        db.set_file_text(FileId(1), main);
+
+        let added = |find: &str| {
+            let config = CodeActionConfig::default();
+            let title = "Add missing members";
+            let mut files = apply_action(&db, &[classes, main], 1, find, title, config)?;
+            Some(files.swap_remove(1))
+        };
+        assert_eq!(
+            added("empty =").unwrap(),
+            main.replace(
+                "  empty = Stack []\n",
+                "  empty = Stack []\n  insert a stack = ?body\n  merge stack1 stack2 = ?body\n  size stack = ?body\n"
+            )
+        );
+        assert_eq!(
+            added("Container Queue").unwrap(),
+            main.replace(
+                "instance Container Queue\n",
+                "instance Container Queue where\n  empty = ?body\n  insert a queue = ?body\n  merge queue1 queue2 = ?body\n  size queue = ?body\n"
+            )
+        );
+        assert_eq!(added("data Stack"), None);
+    }
+}
@@ -0,0 +1,425 @@
+//! Moving a declaration of the module into one of the modules it imports.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    item_tree::Item,
+    lower_module_name, lower_qualified_name,
+    name::{ModuleName, Name, QualifiedName},
+};
+use resolution::{
+    body::Resolution,
+    interface::{Definition, Namespace},
+    reference::{ReferenceKind, Target},
+};
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use super::{inline::declaration_lines, CodeAction, CodeActionKind};
+use crate::{
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+    imports::{import_edit, import_name, qualified_import_edit, ImportName},
+    references::{is_declaration, references, references_in},
+    target::{definition_module, reference_at, FileRange, FileResolver},
+};
+
+/// A name that the moved value refers to, which the destination has to be
+/// able to refer to in the same way.
+struct Needed {
+    namespace: Namespace,
+    name: QualifiedName,
+    definition: Definition,
+    /// The module that the file imports the name from.
+    module: ModuleName,
+}
+
+/// Offers to move the value whose declaration is named at the start of
+/// `range` into each module of the workspace that the file imports, along
+/// with its signature and doc comment. An imported module can't import the
+/// file in turn, so the move never makes a cycle, as long as the value
+/// doesn't refer to other declarations of the file.
+pub(super) fn move_declaration(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    if !is_declaration(db, file, range.start()) {
+        return;
+    }
+    let Some(reference) = reference_at(db, file, range.start()) else { return };
+    let Target::Global(definition) = reference.target else { return };
+    let tree = db.item_tree(file);
+    if !matches!(tree.items[definition.item], Item::Value(_)) {
+        return;
+    }
+    let Some(text) = db.file_text(file) else { return };
+    let parse = db.parse(file);
+    let value = Name::new(&text[reference.range]);
+
+    let mut nodes = vec![];
+    for found in references_in(db, file, &reference, file) {
+        if !is_declaration(db, file, found.start()) {
+            continue;
+        }
+        let token = parse.root.token_at_offset(found.start()).right_biased();
+        let Some(node) = token.and_then(|token| token.parent()?.parent()) else { return };
+        if !matches!(node.kind(), SyntaxKind::ValueDeclaration | SyntaxKind::AnnotationDeclaration)
+        {
+            return;
+        }
+        nodes.push(node);
+    }
+    let moved = declaration_lines(&text, &nodes);
+    let Some(needed) = needed_names(db, file, &nodes, definition) else { return };
+    let moved_text: Vec<_> = moved.iter().map(|&range| text[range].trim()).collect();
+    let moved_text = moved_text.join("\n\n");
+
+    // The uses that stay where they are, which are the ones to update.
+    let is_moved = |found: &FileRange| {
+        found.file == file && moved.iter().any(|range| range.contains_range(found.range))
+    };
+    let mut uses = references(db, file, reference.range.start(), false);
+    uses.retain(|found| !is_moved(found));
+    let exports = db.exports(ModuleId::File(file));
+    let is_exported =
+        exports.interface.names.get(Namespace::Value).get(&value) == Some(&definition);
+    let is_used = uses.iter().any(|found| {
+        found.file == file && !is_in(&parse.root, found.range.start(), SyntaxKind::ExportList)
+    });
+
+    let mut destinations = vec![];
+    for import in &tree.imports {
+        let module = db.resolve_module(ModuleId::File(file), import.module);
+        let Some(ModuleId::File(destination)) = module else { continue };
+        if destination != file
+            && !db.is_library(destination)
+            && !destinations.contains(&(import.module, destination))
+        {
+            destinations.push((import.module, destination));
+        }
+    }
+    destinations.sort_by_key(|(module, _)| module.as_str());
+
+    for (module, destination) in destinations {
+        let Some(edits) = destination_edits(
+            db,
+            (module, destination),
+            value,
+            &needed,
+            &moved_text,
+            is_exported || is_used,
+        ) else {
+            continue;
+        };
+        let mut edit = WorkspaceEdit::default();
+        for text_edit in edits {
+            edit.push(destination, text_edit);
+        }
+        for &range in &moved {
+            edit.push(file, TextEdit::replace(range, ""));
+        }
+        let mut dependents: Vec<_> = uses.iter().map(|found| found.file).collect();
+        dependents.dedup();
+        for dependent in dependents {
+            let ranges = uses.iter().filter(|found| found.file == dependent);
+            let ranges: Vec<_> = ranges.map(|found| found.range).collect();
+            let is_source = dependent == file;
+            for text_edit in dependent_edits(db, dependent, is_source, value, module, &ranges) {
+                edit.push(dependent, text_edit);
+            }
+        }
+        let title = format!("Move `{value}` to {module}");
+        actions.push(CodeAction { title, kind: CodeActionKind::RefactorMove, edit });
+    }
+}
+
+/// The names of other modules that the declarations at `nodes` refer to,
+/// or `None` if they refer to another declaration of the file.
+fn needed_names(
+    db: &Database,
+    file: FileId,
+    nodes: &[SyntaxNode],
+    definition: Definition,
+) -> Option<Vec<Needed>> {
+    let resolver = FileResolver::new(db, file)?;
+    let tree = db.item_tree(file);
+    let mut needed = vec![];
+    let tokens = nodes.iter().flat_map(|node| node.descendants_with_tokens());
+    let tokens = tokens.filter_map(|element| element.into_token());
+    for token in tokens.filter(|token| !token.kind().is_trivia()) {
+        let offset = token.text_range().start();
+        let Some(reference) = resolver.reference_at(offset) else { continue };
+        if reference.range.start() != offset {
+            continue;
+        }
+        let (ReferenceKind::Name(namespace), Target::Global(found)) =
+            (reference.kind, reference.target)
+        else {
+            continue;
+        };
+        if found == definition {
+            continue;
+        }
+        if definition_module(db, file, found) == Some(ModuleId::File(file)) {
+            return None;
+        }
+        let name = match token.parent_ancestors().find_map(ast::QualifiedName::cast) {
+            Some(qualified) => lower_qualified_name(&qualified),
+            None => QualifiedName::unqualified(Name::new(token.text())),
+        };
+        let module = tree.imports.iter().find_map(|import| {
+            if import.alias != name.qualifier {
+                return None;
+            }
+            let imported = db.resolve_module(ModuleId::File(file), import.module)?;
+            let exports = db.exports(imported);
+            let exported = exports.interface.names.get(namespace).get(&name.name);
+            (exported == Some(&found)).then_some(import.module)
+        })?;
+        needed.push(Needed { namespace, name, definition: found, module });
+    }
+    Some(needed)
+}
+
+/// The edits that add the moved declarations to the end of `destination`,
+/// along with the imports they need and an export of the value if it has
+/// to be exported. Returns `None` if the destination already has a value
+/// of the same name in scope, if a name would have to be qualified with
+/// the destination itself, or if the destination would have to import a
+/// module that imports it.
+fn destination_edits(
+    db: &Database,
+    (destination_name, destination): (ModuleName, FileId),
+    value: Name,
+    needed: &[Needed],
+    moved_text: &str,
+    is_exported: bool,
+) -> Option<Vec<TextEdit>> {
+    let scope = db.scope(ModuleId::File(destination))?;
+    if !scope.lookup(None, Namespace::Value, value).is_empty() {
+        return None;
+    }
+    let graph = db.graph(None);
+    let importers = graph.id(destination_name).map(|id| graph.transitive_reverse_dependencies(id));
+    let importers: Vec<_> =
+        importers.into_iter().flatten().map(|id| graph.module(id).name).collect();
+    let mut unqualified: Vec<(ModuleName, Vec<ImportName>)> = vec![];
+    let mut qualified = vec![];
+    for needed in needed {
+        let resolution = scope.resolve(needed.namespace, needed.name);
+        if resolution == Some(Resolution::Global(needed.definition)) {
+            continue;
+        }
+        if definition_module(db, destination, needed.definition)
+            == Some(ModuleId::File(destination))
+            || importers.contains(&needed.module)
+        {
+            return None;
+        }
+        match needed.name.qualifier {
+            Some(alias) => {
+                if !qualified.contains(&(needed.module, alias)) {
+                    qualified.push((needed.module, alias));
+                }
+            }
+            None => {
+                let name = import_name(db, destination, needed.name.name, needed.definition)?;
+                match unqualified.iter_mut().find(|(module, _)| *module == needed.module) {
+                    Some((_, names)) if names.contains(&name) => {}
+                    Some((_, names)) => names.push(name),
+                    None => unqualified.push((needed.module, vec![name])),
+                }
+            }
+        }
+    }
+
+    let mut edits = vec![];
+    for (module, names) in unqualified {
+        edits.extend(import_edit(db, destination, module, &names).into_iter().flatten());
+    }
+    for (module, alias) in qualified {
+        edits.extend(qualified_import_edit(db, destination, module, alias));
+    }
+    let parse = db.parse(destination);
+    let exports = parse.module().header().and_then(|header| header.exports());
+    if let Some(last) = exports.and_then(|exports| exports.items().last()).filter(|_| is_exported) {
+        edits.push(TextEdit::insert(last.syntax().text_range().end(), format!(", {value}")));
+    }
+    let text = db.file_text(destination)?;
+    let separator = if text.is_empty() || text.ends_with("\n\n") {
+        ""
+    } else if text.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    edits.push(TextEdit::insert(TextSize::of(&*text), format!("{separator}{moved_text}\n")));
+    Some(edits)
+}
+
+/// The edits that make `dependent` refer to the value in `destination`, at
+/// the `ranges` that it refers to the value at. An import that only lists
+/// the value is changed to import it from the destination, it's dropped
+/// from other import lists, and the destination is imported under every
+/// qualifier that the value is used with. The file that the value is moved
+/// out of drops it from its export list instead.
+fn dependent_edits(
+    db: &Database,
+    dependent: FileId,
+    is_source: bool,
+    value: Name,
+    destination: ModuleName,
+    ranges: &[TextRange],
+) -> Vec<TextEdit> {
+    let parse = db.parse(dependent);
+    let mut edits = vec![];
+    // The qualifiers that the value has to be imported under, and those
+    // that an import already imports it under.
+    let mut needed = vec![];
+    let mut imported = vec![];
+    for range in ranges {
+        let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else {
+            continue;
+        };
+        if let Some(import) = token.parent_ancestors().find_map(ast::ImportDeclaration::cast) {
+            let (Some(module_name), Some(list)) = (import.module_name(), import.import_list())
+            else {
+                continue;
+            };
+            let items: Vec<_> = list.items().map(|item| item.syntax().clone()).collect();
+            let Some(item) = token.parent_ancestors().find(|node| items.contains(node)) else {
+                continue;
+            };
+            let alias = import.alias().and_then(|alias| alias.module_name());
+            let alias = alias.map(|alias| lower_module_name(&alias));
+            let (Some(name_range), Some(list_range)) =
+                (text_range(module_name.syntax()), text_range(list.syntax()))
+            else {
+                continue;
+            };
+            if list.hiding() && items.len() == 1 {
+                edits.push(TextEdit::replace(
+                    TextRange::new(name_range.end(), list_range.end()),
+                    "",
+                ));
+            } else if items.len() == 1 {
+                edits.push(TextEdit::replace(name_range, destination.to_string()));
+                imported.push(alias);
+            } else {
+                edits.extend(item_removal(&items, &item));
+                if !list.hiding() {
+                    needed.push(alias);
+                }
+            }
+        } else if let Some(exports) = token.parent_ancestors().find_map(ast::ExportList::cast) {
+            // Other modules re-export the value, which they have to import
+            // unqualified for.
+            if !is_source {
+                needed.push(None);
+                continue;
+            }
+            let items: Vec<_> = exports.items().map(|item| item.syntax().clone()).collect();
+            if let Some(item) = token.parent_ancestors().find(|node| items.contains(node)) {
+                edits.extend(item_removal(&items, &item));
+            }
+        } else {
+            let qualified = token.parent_ancestors().find_map(ast::QualifiedName::cast);
+            needed.push(qualified.and_then(|qualified| lower_qualified_name(&qualified).qualifier));
+        }
+    }
+
+    let header = parse.module().header();
+    let mut seen = vec![];
+    for qualifier in needed {
+        if imported.contains(&qualifier) || seen.contains(&qualifier) {
+            continue;
+        }
+        seen.push(qualifier);
+        let Some(alias) = qualifier else {
+            let names = [ImportName::Value(value.to_string())];
+            edits.extend(import_edit(db, dependent, destination, &names).into_iter().flatten());
+            continue;
+        };
+        let existing = header.iter().flat_map(|header| header.imports()).find(|import| {
+            let module = import.module_name().map(|name| lower_module_name(&name));
+            let other = import.alias().and_then(|alias| alias.module_name());
+            module == Some(destination)
+                && other.map(|other| lower_module_name(&other)) == Some(alias)
+        });
+        match existing.map(|import| import.import_list()) {
+            // A list of the destination under the same qualifier gains the
+            // value, and an open import already has it.
+            Some(Some(list)) => {
+                if let Some(last) = list.items().last().filter(|_| !list.hiding()) {
+                    let end = last.syntax().text_range().end();
+                    edits.push(TextEdit::insert(end, format!(", {value}")));
+                }
+            }
+            Some(None) => {}
+            None => edits.extend(qualified_import_edit(db, dependent, destination, alias)),
+        }
+    }
+    edits
+}
+
+/// Removes `item` from a list of `items`, along with the comma that
+/// separates it from the next item, or else from the previous one.
+fn item_removal(items: &[SyntaxNode], item: &SyntaxNode) -> Option<TextEdit> {
+    let index = items.iter().position(|other| other == item)?;
+    let item_range = text_range(item)?;
+    let range = match (index.checked_sub(1).map(|previous| &items[previous]), items.get(index + 1))
+    {
+        (_, Some(next)) => TextRange::new(item_range.start(), text_range(next)?.start()),
+        (Some(previous), None) => TextRange::new(text_range(previous)?.end(), item_range.end()),
+        (None, None) => item_range,
+    };
+    Some(TextEdit::replace(range, ""))
+}
+
+/// Whether the token at `offset` is within a node of `kind`.
+fn is_in(root: &SyntaxNode, offset: TextSize, kind: SyntaxKind) -> bool {
+    let token = root.token_at_offset(offset).right_biased();
+    token.is_some_and(|token| token.parent_ancestors().any(|node| node.kind() == kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn move_declarations() {
+        let mut db = Database::new();
+        let files = [
+            "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n",
+            "module Lib (double) where\n\nimport Data.Maybe (Maybe)\n\ndouble :: Int -> Int\ndouble x = x * 2\n",
+            "module Main (main, quadruple) where\n\nimport Data.Maybe (Maybe(..))\nimport Lib (double)\n\n-- | Four times.\nquadruple :: Int -> Maybe Int\nquadruple x = Just (double (double x))\n\nmain = quadruple 1\n\nhalf x = x / 2\n\nthird x = half x\n",
+            "module Other where\n\nimport Main (quadruple)\nimport Main (main, quadruple) as M\n\nf = quadruple 2\ng = M.quadruple 3\n",
+        ];
+        for (index, text) in files.iter().enumerate() {
+            db.set_file_text(FileId(index as u32), *text);
+        }
+
+        let move_at = |find: &str, title: &str| {
+            apply_action(&db, &files, 2, find, title, CodeActionConfig::default())
+        };
+        let moved = move_at("quadruple ::", "Move `quadruple` to Lib").unwrap();
+        assert_eq!(
+            moved[1],
+            "module Lib (double, quadruple) where\n\nimport Data.Maybe (Maybe(..))\n\ndouble :: Int -> Int\ndouble x = x * 2\n\n-- | Four times.\nquadruple :: Int -> Maybe Int\nquadruple x = Just (double (double x))\n"
+        );
+        assert_eq!(
+            moved[2],
+            "module Main (main) where\n\nimport Data.Maybe (Maybe(..))\nimport Lib (double, quadruple)\n\nmain = quadruple 1\n\nhalf x = x / 2\n\nthird x = half x\n"
+        );
+        assert_eq!(
+            moved[3],
+            "module Other where\n\nimport Lib (quadruple)\nimport Main (main) as M\nimport Lib as M\n\nf = quadruple 2\ng = M.quadruple 3\n"
+        );
+        // The value refers to another declaration of the module.
+        assert_eq!(move_at("third x", "Move `third` to Lib"), None);
+        assert_eq!(move_at("main =", "Move `main` to Lib"), None);
+    }
+}
@@ -0,0 +1,398 @@
+//! Sorting, merging, and cleaning up the imports of a module.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    hir::{DataMembers, Import, ImportItem},
+    lower_import, lower_import_item,
+};
+use resolution::unused::UnusedImport;
+use rowan::{
+    ast::{AstNode, SyntaxNodePtr},
+    TextRange,
+};
+use syntax::{ast, SyntaxKind};
+
+use super::{CodeAction, CodeActionKind, ImportGrouping, ImportLayout};
+use crate::{
+    diagnostics::unused_imports,
+    edit::{TextEdit, WorkspaceEdit},
+    folding_ranges::text_range,
+};
+
+/// Offers to rewrite the imports of a module: unused imports and items are
+/// removed, imports of the same module under the same alias are merged, and
+/// what's left is sorted by module within its group and laid out the way
+/// `layout` says, with every list written the same way. Nothing is offered
+/// if the imports are already organized, or if they have comments or errors
+/// among them, since those have nowhere to go once the imports are
+/// rewritten.
+pub(super) fn organize_imports(
+    db: &Database,
+    file: FileId,
+    layout: ImportLayout,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let root = parse.module();
+    let Some((header, declarations, range)) = import_declarations(db, file, &root) else { return };
+
+    let unused: Vec<_> = unused_imports(db, file).iter().map(UnusedImport::ptr).collect();
+    let mut imports = vec![];
+    for declaration in &declarations {
+        if unused.contains(&SyntaxNodePtr::new(declaration.syntax())) {
+            continue;
+        }
+        let Some(mut import) = lower_import(declaration) else { return };
+        if let (Some(list), Some(written)) = (&mut import.list, declaration.import_list()) {
+            let items =
+                written.items().filter(|item| !unused.contains(&SyntaxNodePtr::new(item.syntax())));
+            let Some(items) = items.map(|item| lower_import_item(&item)).collect::<Option<_>>()
+            else {
+                return;
+            };
+            list.items = items;
+        }
+        imports.push(import);
+    }
+    let organized = laid_out(db, file, merge(imports), layout);
+    let Some(text) = db.file_text(file) else { return };
+    if text[range] == organized {
+        return;
+    }
+    // Without any imports left, the blank lines before them go as well.
+    let mut range = range;
+    if organized.is_empty() {
+        let tokens =
+            header.syntax().descendants_with_tokens().filter_map(|element| element.into_token());
+        let before = tokens
+            .filter(|token| !token.kind().is_trivia() && token.text_range().end() <= range.start());
+        if let Some(token) = before.last() {
+            range = TextRange::new(token.text_range().end(), range.end());
+        }
+    }
+    let mut edit = WorkspaceEdit::default();
+    edit.push(file, TextEdit::replace(range, organized));
+    let title = "Organize imports".to_string();
+    actions.push(CodeAction { title, kind: CodeActionKind::SourceOrganizeImports, edit });
+}
+
+/// Offers to sort and lay out the imports that `range` is within the way
+/// organizing them would, but without removing or merging any of them, and
+/// with lists keeping the items they're written with in their order.
+pub(super) fn sort_imports(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    layout: ImportLayout,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let root = parse.module();
+    let Some((_, declarations, imports_range)) = import_declarations(db, file, &root) else {
+        return;
+    };
+    if imports_range.intersect(range).is_none() {
+        return;
+    }
+    let Some(imports) = declarations.iter().map(lower_import).collect::<Option<Vec<_>>>() else {
+        return;
+    };
+    let sorted = laid_out(db, file, imports, layout);
+    let Some(text) = db.file_text(file) else { return };
+    if text[imports_range] == sorted {
+        return;
+    }
+    let mut edit = WorkspaceEdit::default();
+    edit.push(file, TextEdit::replace(imports_range, sorted));
+    let title = "Sort imports".to_string();
+    actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+}
+
+/// The header of `root` along with its imports and the range they span,
+/// unless it has none or there are comments or errors among them.
+fn import_declarations(
+    db: &Database,
+    file: FileId,
+    root: &ast::Module,
+) -> Option<(ast::ModuleHeader, Vec<ast::ImportDeclaration>, TextRange)> {
+    let parse = db.parse(file);
+    let header = root.header()?;
+    let declarations: Vec<_> = header.imports().collect();
+    let first = text_range(declarations.first()?.syntax())?;
+    let last = text_range(declarations.last()?.syntax())?;
+    let range = TextRange::new(first.start(), last.end());
+    let mut tokens =
+        header.syntax().descendants_with_tokens().filter_map(|element| element.into_token());
+    let has_comments = tokens.any(|token| {
+        matches!(token.kind(), SyntaxKind::LineComment | SyntaxKind::BlockComment)
+            && range.contains_range(token.text_range())
+    });
+    let has_errors = parse.errors.iter().any(|error| error.range.intersect(range).is_some());
+    if has_comments || has_errors {
+        return None;
+    }
+    Some((header, declarations, range))
+}
+
+/// Sorts `imports` by module within their groups and writes them out, with
+/// the groups separated by blank lines.
+fn laid_out(db: &Database, file: FileId, imports: Vec<Import>, layout: ImportLayout) -> String {
+    let mut imports: Vec<_> = imports
+        .into_iter()
+        .map(|import| (group(db, file, &import, layout.grouping), import))
+        .collect();
+    imports.sort_by(|(a_group, a), (b_group, b)| {
+        let key = |group: usize, import: &Import| {
+            let hiding = import.list.as_ref().is_some_and(|list| list.hiding);
+            (group, import.module.as_str(), import.alias.map(|alias| alias.as_str()), hiding)
+        };
+        key(*a_group, a).cmp(&key(*b_group, b))
+    });
+
+    let mut groups: Vec<Vec<&Import>> = vec![];
+    let mut previous = None;
+    for (group, import) in &imports {
+        if previous != Some(group) {
+            groups.push(vec![]);
+            previous = Some(group);
+        }
+        groups.last_mut().unwrap().push(import);
+    }
+    let groups: Vec<_> = groups.iter().map(|group| group_text(group, layout)).collect();
+    groups.join("\n\n")
+}
+
+/// Which block of imports an import is written in.
+fn group(db: &Database, file: FileId, import: &Import, grouping: ImportGrouping) -> usize {
+    match grouping {
+        ImportGrouping::Qualification => usize::from(import.alias.is_some()),
+        ImportGrouping::Origin => {
+            if import.module.as_str() == "Prelude" {
+                return 0;
+            }
+            match db.resolve_module(ModuleId::File(file), import.module) {
+                Some(ModuleId::File(module)) if !db.is_library(module) => 2,
+                _ => 1,
+            }
+        }
+    }
+}
+
+/// The imports of a group, with the `as` of those that fit on a line lined
+/// up if the layout says so.
+fn group_text(imports: &[&Import], layout: ImportLayout) -> String {
+    let written: Vec<_> = imports.iter().map(|import| import_text(import, layout)).collect();
+    let column = written
+        .iter()
+        .filter(|(before, alias)| alias.is_some() && !before.contains('\n'))
+        .map(|(before, _)| before.chars().count())
+        .max();
+    let lines = written.iter().map(|(before, alias)| match alias {
+        Some(alias) => {
+            let padding = match (layout.align_aliases, column) {
+                (true, Some(column)) if !before.contains('\n') => {
+                    " ".repeat(column - before.chars().count())
+                }
+                _ => String::new(),
+            };
+            format!("{before}{padding} as {alias}")
+        }
+        None => before.clone(),
+    });
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+/// Merges the imports of the same module under the same alias. An open
+/// import brings in everything the others do, explicit lists are merged
+/// into one, and lists that hide names are only merged with the same list.
+fn merge(imports: Vec<Import>) -> Vec<Import> {
+    let mut merged: Vec<Import> = vec![];
+    for mut import in imports {
+        let (module, alias) = (import.module, import.alias);
+        let same = |other: &Import| other.module == module && other.alias == alias;
+        if merged.iter().any(|other| same(other) && other.list.is_none()) {
+            continue;
+        }
+        let Some(list) = &mut import.list else {
+            merged.retain(|other| !same(other));
+            merged.push(import);
+            continue;
+        };
+        list.items = union(std::mem::take(&mut list.items));
+        let is_explicit =
+            |other: &Import| other.list.as_ref().is_some_and(|other| !other.hiding && !list.hiding);
+        if let Some(Import { list: Some(explicit), .. }) =
+            merged.iter_mut().find(|other| same(other) && is_explicit(other))
+        {
+            let items = explicit.items.drain(..).chain(list.items.drain(..));
+            explicit.items = union(items.collect());
+        } else if !merged.contains(&import) {
+            merged.push(import);
+        }
+    }
+    merged
+}
+
+/// The items of a list without duplicates, where a type listed several
+/// times is listed once with all of the constructors, in order.
+pub(super) fn union(items: Vec<ImportItem>) -> Vec<ImportItem> {
+    let mut union: Vec<ImportItem> = vec![];
+    for item in items {
+        if let ImportItem::Type(name, members) = &item {
+            let listed = union.iter_mut().find_map(|other| match other {
+                ImportItem::Type(other, listed) if other == name => Some(listed),
+                _ => None,
+            });
+            if let Some(listed) = listed {
+                *listed = match (listed.take(), members.clone()) {
+                    (Some(DataMembers::All), _) | (_, Some(DataMembers::All)) => {
+                        Some(DataMembers::All)
+                    }
+                    (
+                        Some(DataMembers::Enumerated(mut constructors)),
+                        Some(DataMembers::Enumerated(more)),
+                    ) => {
+                        constructors.extend(more);
+                        Some(DataMembers::Enumerated(constructors))
+                    }
+                    (members, None) | (None, members) => members,
+                };
+                continue;
+            }
+        }
+        if !union.contains(&item) {
+            union.push(item);
+        }
+    }
+    for item in &mut union {
+        if let ImportItem::Type(_, Some(DataMembers::Enumerated(constructors))) = item {
+            constructors.sort_by_key(|name| name.as_str());
+            constructors.dedup();
+        }
+    }
+    union.sort_by_key(|item| match item {
+        ImportItem::Type(name, _) => (0, name.as_str()),
+        ImportItem::TypeOperator(name) => (1, name.as_str()),
+        ImportItem::Class(name) => (2, name.as_str()),
+        ImportItem::Value(name) => (3, name.as_str()),
+        ImportItem::Operator(name) => (4, name.as_str()),
+    });
+    union
+}
+
+/// An import up to its alias, along with the alias. A list that would
+/// make the import wider than the layout allows has an item on each line.
+fn import_text(import: &Import, layout: ImportLayout) -> (String, Option<String>) {
+    let mut text = format!("import {}", import.module);
+    let alias = import.alias.map(|alias| alias.to_string());
+    if let Some(list) = &import.list {
+        let items: Vec<_> = list.items.iter().map(item_text).collect();
+        let hiding = if list.hiding { " hiding" } else { "" };
+        let line = format!("{text}{hiding} ({})", items.join(", "));
+        let width = line.chars().count() + alias.as_ref().map_or(0, |alias| alias.len() + 4);
+        match layout.max_width {
+            Some(max_width) if width > max_width as usize && !items.is_empty() => {
+                text.push_str(&format!("{hiding}\n  ( {}\n  )", items.join("\n  , ")));
+            }
+            _ => text = line,
+        }
+    }
+    (text, alias)
+}
+
+pub(super) fn item_text(item: &ImportItem) -> String {
+    match item {
+        ImportItem::Value(name) => name.to_string(),
+        ImportItem::Operator(name) => format!("({name})"),
+        ImportItem::Type(name, None) => name.to_string(),
+        ImportItem::Type(name, Some(DataMembers::All)) => format!("{name}(..)"),
+        ImportItem::Type(name, Some(DataMembers::Enumerated(constructors))) => {
+            let constructors: Vec<_> = constructors.iter().map(ToString::to_string).collect();
+            format!("{name}({})", constructors.join(", "))
+        }
+        ImportItem::TypeOperator(name) => format!("type ({name})"),
+        ImportItem::Class(name) => format!("class {name}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{
+        code_actions::{tests::apply_action, ImportGrouping, ImportLayout},
+        CodeActionConfig,
+    };
+
+    #[test]
+    fn organize_imports() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a _ = a\n\nisJust :: forall a. Maybe a -> Boolean\nisJust _ = true\n";
+        let array = "module Data.Array where\n\nlength :: forall a. Array a -> Int\nlength _ = 0\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\nimport Data.Maybe (fromMaybe)\nimport Data.Array as A\nimport Data.Maybe (isJust, Maybe(Just))\nimport Data.Maybe (Maybe(Nothing))\n\nf :: Maybe Int\nf = Just (fromMaybe Nothing Nothing)\n\ng = M.Nothing\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), array);
+
+        let mut organized = |text: &str| {
+            db.set_file_text(FileId(2), text);
+            let config = CodeActionConfig::default();
+            let files = [maybe, array, text];
+            let mut files = apply_action(&db, &files, 2, "module", "Organize imports", config)?;
+            Some(files.swap_remove(2))
+        };
+        let organized_main = organized(main).unwrap();
+        assert_eq!(
+            organized_main,
+            main.replace(
+                "import Data.Maybe as M\nimport Data.Maybe (fromMaybe)\nimport Data.Array as A\nimport Data.Maybe (isJust, Maybe(Just))\nimport Data.Maybe (Maybe(Nothing))",
+                "import Data.Maybe (Maybe(Just, Nothing), fromMaybe)\n\nimport Data.Maybe as M"
+            )
+        );
+        assert_eq!(organized(&organized_main), None);
+        assert_eq!(
+            organized("module Main where\n\nimport Data.Array as A\n\nf = 1\n").unwrap(),
+            "module Main where\n\nf = 1\n"
+        );
+        assert_eq!(
+            organized(
+                "module Main where\n\nimport Data.Array as A\n-- Lengths\nimport Data.Maybe\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn sort_imports_with_layout() {
+        let mut db = Database::new();
+        // Modules that aren't in the workspace are imported from libraries.
+        let local = "module App.Types where\n\ntype Id = Int\n";
+        let main = "module Main where\n\nimport App.Types as Types\nimport Data.Array (null, length)\nimport Prelude\nimport Data.Map as Map\nimport Data.Array as Array\n\nf = identity length null Array.length Map.empty\n\ng :: Types.Id\ng = 1\n";
+        db.set_file_text(FileId(0), local);
+        db.set_file_text(FileId(1), main);
+
+        let import_layout = ImportLayout {
+            grouping: ImportGrouping::Origin,
+            align_aliases: true,
+            max_width: Some(30),
+        };
+        let sorted = |title: &str| {
+            let config = CodeActionConfig { import_layout, ..CodeActionConfig::default() };
+            let mut files = apply_action(&db, &[local, main], 1, "import", title, config)?;
+            Some(files.swap_remove(1))
+        };
+        assert_eq!(
+            sorted("Sort imports").unwrap(),
+            main.replace(
+                "import App.Types as Types\nimport Data.Array (null, length)\nimport Prelude\nimport Data.Map as Map\nimport Data.Array as Array",
+                "import Prelude\n\nimport Data.Array\n  ( null\n  , length\n  )\nimport Data.Array as Array\nimport Data.Map   as Map\n\nimport App.Types as Types"
+            )
+        );
+        assert_eq!(
+            sorted("Organize imports").unwrap(),
+            main.replace(
+                "import App.Types as Types\nimport Data.Array (null, length)\nimport Prelude\nimport Data.Map as Map\nimport Data.Array as Array",
+                "import Prelude\n\nimport Data.Array\n  ( length\n  , null\n  )\nimport Data.Array as Array\nimport Data.Map   as Map\n\nimport App.Types as Types"
+            )
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Adding a signature to a value or binding that doesn't have one.
+
+use analysis::{Database, FileId};
+use rowan::TextRange;
+use syntax::SyntaxKind;
+
+use super::{CodeAction, CodeActionKind};
+use crate::{
+    annotations::{equation_name, first_equation, is_first, Signatures},
+    edit::WorkspaceEdit,
+};
+
+/// Offers the signature of the innermost value or binding at the start of
+/// `range`, from any of its equations.
+pub(super) fn add_signature(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    actions: &mut Vec<CodeAction>,
+) {
+    let parse = db.parse(file);
+    let Some(token) = parse.root.token_at_offset(range.start()).right_biased() else { return };
+    let Some(node) = token.parent_ancestors().find(|node| {
+        matches!(node.kind(), SyntaxKind::ValueDeclaration | SyntaxKind::LetBindingName)
+    }) else {
+        return;
+    };
+    let first = first_equation(&node);
+    if !is_first(&first, equation_name(&first)) {
+        return;
+    }
+    let Some(missing) = Signatures::new(db, file).missing(&first) else { return };
+    let mut edit = WorkspaceEdit::default();
+    edit.push(file, missing.edit);
+    let title = "Add type signature".to_string();
+    actions.push(CodeAction { title, kind: CodeActionKind::RefactorRewrite, edit });
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use crate::{code_actions::tests::apply_action, CodeActionConfig};
+
+    #[test]
+    fn add_type_signatures() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\n\nf x = M.Just x\n\ng = h 1\n  where\n  h 0 = 0\n  h y = y\n\nk (n :: Int) = unknown n\n\nm :: Int\nm = 1\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let added = |find: &str| {
+            let config = CodeActionConfig::default();
+            let mut files =
+                apply_action(&db, &[maybe, main], 1, find, "Add type signature", config)?;
+            Some(files.swap_remove(1))
+        };
+        assert_eq!(
+            added("x = M.Just").unwrap(),
+            main.replace("f x =", "f :: forall t0. t0 -> M.Maybe t0\nf x =")
+        );
+        assert_eq!(added("h y").unwrap(), main.replace("  h 0", "  h :: Int -> Int\n  h 0"));
+        assert_eq!(added("k (n").unwrap(), main.replace("k (n", "k :: forall t0. Int -> t0\nk (n"));
+        assert_eq!(added("m = 1"), None);
+    }
+}
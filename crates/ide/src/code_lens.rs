@@ -0,0 +1,113 @@
+//! Actions and counts shown above the declarations of a file.
+
+use analysis::{Database, FileId};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{ast, SyntaxKind};
+
+use crate::{annotations::missing_signatures, edit::TextEdit};
+
+/// Which lenses are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeLensConfig {
+    /// Adding the inferred signature of a value that doesn't have one.
+    pub signatures: bool,
+    /// The number of references to each declaration.
+    pub references: bool,
+}
+
+impl Default for CodeLensConfig {
+    fn default() -> CodeLensConfig {
+        CodeLensConfig { signatures: true, references: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeLens {
+    /// The name of the declaration that the lens is above.
+    pub range: TextRange,
+    pub kind: CodeLensKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeLensKind {
+    AddSignature {
+        signature: String,
+        edit: TextEdit,
+    },
+    /// The references of the name, which are only searched for once the
+    /// lens is shown, since that's a search of every file that depends on
+    /// the file.
+    References,
+}
+
+/// The lenses of the declarations of a module, in order.
+pub fn code_lenses(db: &Database, file: FileId, config: CodeLensConfig) -> Vec<CodeLens> {
+    let mut lenses = vec![];
+    if config.signatures {
+        let missing = missing_signatures(db, file).into_iter();
+        lenses.extend(missing.map(|missing| CodeLens {
+            range: missing.range,
+            kind: CodeLensKind::AddSignature { signature: missing.signature, edit: missing.edit },
+        }));
+    }
+    if config.references {
+        let parse = db.parse(file);
+        let mut names: Vec<String> = vec![];
+        for declaration in parse.module().declarations() {
+            let Some(name) = declaration_name(&declaration) else { continue };
+            // Values are counted once rather than at each equation.
+            let text = name.text().to_string();
+            if matches!(declaration, ast::Declaration::Value(_)) && names.contains(&text) {
+                continue;
+            }
+            names.push(text);
+            lenses.push(CodeLens { range: name.text_range(), kind: CodeLensKind::References });
+        }
+    }
+    lenses.sort_by_key(|lens| lens.range.start());
+    lenses
+}
+
+/// The name of a declaration that can be referred to from elsewhere.
+fn declaration_name(declaration: &ast::Declaration) -> Option<syntax::SyntaxToken> {
+    let node = match declaration {
+        ast::Declaration::Value(_)
+        | ast::Declaration::Data(_)
+        | ast::Declaration::Newtype(_)
+        | ast::Declaration::Type(_)
+        | ast::Declaration::Class(_)
+        | ast::Declaration::ForeignData(_)
+        | ast::Declaration::ForeignValue(_) => declaration.syntax(),
+        _ => return None,
+    };
+    let name = node.children().find(|node| node.kind() == SyntaxKind::Name)?;
+    name.first_token()
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{code_lenses, CodeLensConfig, CodeLensKind};
+    use crate::edit::apply_edits;
+
+    #[test]
+    fn lenses_of_declarations() {
+        let mut db = Database::new();
+        let main = "module Main where\n\ndata Box = Box Int\n\n-- | Unboxes.\nunbox (Box x) = x\n\ninc :: Int -> Int\ninc x = x\n";
+        db.set_file_text(FileId(0), main);
+
+        let lenses = code_lenses(&db, FileId(0), CodeLensConfig::default());
+        let names: Vec<_> = lenses.iter().map(|lens| &main[lens.range]).collect();
+        assert_eq!(names, ["Box", "unbox", "unbox", "inc"]);
+        let CodeLensKind::AddSignature { signature, edit } = &lenses[1].kind else { panic!() };
+        assert_eq!(signature, "unbox :: Box -> Int");
+        assert_eq!(
+            apply_edits(main, std::slice::from_ref(edit)),
+            main.replace("unbox (Box", "unbox :: Box -> Int\nunbox (Box")
+        );
+
+        let config = CodeLensConfig { references: false, ..CodeLensConfig::default() };
+        assert_eq!(code_lenses(&db, FileId(0), config).len(), 1);
+    }
+}
@@ -0,0 +1,874 @@
+//! The names that can be written at the cursor.
+
+mod snippets;
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    hir::{Body, ExprId},
+    lower_label, lower_module_name,
+    name::{ModuleName, Name},
+    scope::ScopeTree,
+};
+use resolution::{
+    interface::{Definition, Namespace},
+    prim,
+};
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode, SyntaxToken};
+use typecheck::{infer::BodyTypes, ty::TyId};
+
+use crate::{
+    docs::{definition_docs, module_docs},
+    edit::TextEdit,
+    hover::definition_type,
+    imports::{import_edit, import_name, importable_modules, qualified_import_edit},
+    records::{expected_type, field_type, fields, updated_type},
+    symbols::{definition_kind, SymbolKind},
+    target::definition_module,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompletionConfig {
+    /// Whether the client can insert snippets, which offers the skeletons
+    /// of declarations and expressions along with names.
+    pub snippets: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A name bound within the body at the cursor.
+    Local,
+    /// A label of a record.
+    Field,
+    Module,
+    Declaration(SymbolKind),
+    /// The skeleton of a declaration or expression.
+    Snippet,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The type of values, constructors, class members, and fields.
+    pub detail: Option<String>,
+    /// The doc comment of declarations and modules, as markdown.
+    pub documentation: Option<String>,
+    /// What's inserted, if it isn't the label.
+    pub insert_text: Option<String>,
+    /// Whether what's inserted is a snippet, with `$1` and `${1:default}`
+    /// for the placeholders that the cursor moves through.
+    pub snippet: bool,
+    /// What's matched against the text that's replaced, if it isn't the
+    /// label.
+    pub filter_text: Option<String>,
+    /// What's replaced, if it's more than the name before the cursor, e.g.
+    /// every segment of a module name.
+    pub replace: Option<TextRange>,
+    /// The module that a name that isn't in scope yet is imported from.
+    pub import: Option<ModuleName>,
+    /// Edits elsewhere in the file, i.e. the import of a name that isn't in
+    /// scope yet.
+    pub additional_edits: Vec<TextEdit>,
+}
+
+/// The names in scope at `offset`: the binders of the enclosing body, then
+/// the declarations of the module, then its imports. Names in types are
+/// types and classes, and names after a qualifier are only those imported
+/// under it. Editors filter by what's been typed, so every name is offered.
+///
+/// Once a name has been started, the names exported by modules that aren't
+/// imported yet follow, along with the edit that imports them.
+///
+/// Labels after a record, or of a record literal or update, are the fields
+/// of its type instead, where that's known. Within an import, the names of
+/// modules are offered, and then what the module exports within its list.
+///
+/// Clients that can insert snippets are also offered the skeletons of
+/// declarations at the margin and of expressions within them, and
+/// templates that wrap the expression before a label, e.g. `x.case`.
+pub fn completions(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    config: CompletionConfig,
+) -> Vec<CompletionItem> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let Some(text) = db.file_text(file) else { return vec![] };
+    let parse = db.parse(file);
+    let Some(token) = parse.root.token_at_offset(offset).left_biased() else { return vec![] };
+    let before = &text[..usize::from(offset)];
+    if let Some(name) = imported_module_name(before) {
+        let range = TextRange::new(offset - TextSize::of(name), offset);
+        return module_items(db, file, range);
+    }
+    if let Some(items) = import_list_items(db, file, &token, offset) {
+        return items;
+    }
+    let namespaces: &[Namespace] = match is_type(&token) {
+        true => &[Namespace::Type, Namespace::Class],
+        false => &[Namespace::Value, Namespace::Constructor],
+    };
+
+    let prefix = &before[before.trim_end_matches(is_name).len()..];
+
+    let mut items = vec![];
+    match qualifier(before) {
+        Qualifier::Module(alias) => {
+            let Some(names) = scope.imports.qualified.get(&alias) else {
+                qualified_imports(db, file, alias, prefix, namespaces, &mut items);
+                return items;
+            };
+            for &namespace in namespaces {
+                let imported = names
+                    .get(namespace)
+                    .iter()
+                    .filter_map(|(&name, definitions)| Some((name, *definitions.first()?)));
+                globals(db, file, imported, &mut items);
+            }
+        }
+        Qualifier::Record => {
+            let mut items = access_labels(db, file, &token, offset);
+            if config.snippets {
+                snippets::postfix(&text, &token, offset, &mut items);
+            }
+            return items;
+        }
+        Qualifier::None => {
+            if let Some(labels) = record_labels(db, file, &token, offset) {
+                return labels;
+            }
+            if namespaces.contains(&Namespace::Value) {
+                locals(db, file, &token, &mut items);
+            }
+            for &namespace in namespaces {
+                let declared = scope.declarations.names.get(namespace);
+                let declared = declared.iter().map(|(&name, &definition)| (name, definition));
+                globals(db, file, declared, &mut items);
+            }
+            for &namespace in namespaces {
+                let imported = scope.imports.unqualified.get(namespace);
+                let imported = imported
+                    .iter()
+                    .filter_map(|(&name, definitions)| Some((name, *definitions.first()?)));
+                globals(db, file, imported, &mut items);
+            }
+            // Types and classes built into the compiler are always in scope.
+            for &namespace in namespaces {
+                let kind = match namespace {
+                    Namespace::Class => SymbolKind::Class,
+                    _ => SymbolKind::ForeignData,
+                };
+                for name in prim::names(namespace) {
+                    push(&mut items, Name::new(name), CompletionKind::Declaration(kind), None);
+                }
+            }
+            if !prefix.is_empty() {
+                unqualified_imports(db, file, prefix, namespaces, &mut items);
+            }
+            if config.snippets {
+                let line = &before[before.rfind('\n').map_or(0, |newline| newline + 1)..];
+                if line == prefix {
+                    snippets::declarations(&token, &mut items);
+                } else if namespaces.contains(&Namespace::Value) {
+                    snippets::expressions(&token, &mut items);
+                }
+            }
+        }
+    }
+    items
+}
+
+/// The start of the module name of an import that's being typed at the end
+/// of `text`, which is read from the text since there's no declaration to
+/// parse yet.
+fn imported_module_name(text: &str) -> Option<&str> {
+    let line = &text[text.rfind('\n').map_or(0, |index| index + 1)..];
+    let after = line.trim_start().strip_prefix("import")?;
+    let name = after.trim_start();
+    let is_name = name.chars().all(|character| is_name(character) || character == '.');
+    (name.len() < after.len() && is_name).then_some(name)
+}
+
+/// The modules that `file` can import, as they replace the whole module name
+/// that's been typed.
+fn module_items(db: &Database, file: FileId, range: TextRange) -> Vec<CompletionItem> {
+    let own = db.item_tree(file).name;
+    let modules = db.visible_modules(ModuleId::File(file));
+    let modules = modules.into_iter().filter(|&(name, _)| Some(name) != own);
+    modules
+        .map(|(name, module)| CompletionItem {
+            label: name.to_string(),
+            kind: CompletionKind::Module,
+            detail: None,
+            documentation: match module {
+                ModuleId::File(file) => module_docs(db, file),
+                ModuleId::Compiled(_) | ModuleId::Prim(_) => None,
+            },
+            insert_text: None,
+            snippet: false,
+            filter_text: None,
+            replace: Some(range),
+            import: None,
+            additional_edits: vec![],
+        })
+        .collect()
+}
+
+/// The names exported by the module of the import list at `token` that
+/// aren't listed yet, or the constructors of a type within its list of
+/// constructors. Returns `None` if the cursor isn't within an import list.
+fn import_list_items(
+    db: &Database,
+    file: FileId,
+    token: &SyntaxToken,
+    offset: TextSize,
+) -> Option<Vec<CompletionItem>> {
+    // The cursor is after the list.
+    if token.kind() == SyntaxKind::RightParenthesis && token.text_range().end() == offset {
+        return None;
+    }
+    let list = token.parent_ancestors().find_map(ast::ImportList::cast)?;
+    let import = ast::ImportDeclaration::cast(list.syntax().parent()?)?;
+    let name = lower_module_name(&import.module_name()?);
+    let module = db.resolve_module(ModuleId::File(file), name)?;
+    let exports = db.exports(module);
+    // The name at the cursor is the one that's being completed.
+    let is_written = |name_ref: Option<ast::NameRef>| {
+        let token = name_ref?.token()?;
+        (!token.text_range().contains_inclusive(offset)).then(|| Name::new(token.text()))
+    };
+
+    let mut items = vec![];
+    let mut push_definition = |name: Name, definition: Definition, insert_text: Option<String>| {
+        let Some(definition_module) = definition_module(db, file, definition) else { return };
+        let Some(tree) = db.module_tree(definition_module) else { return };
+        items.push(CompletionItem {
+            label: name.to_string(),
+            kind: CompletionKind::Declaration(definition_kind(&tree, definition)),
+            detail: definition_type(db, definition_module, &tree, definition),
+            documentation: definition_docs(db, definition_module, definition),
+            insert_text,
+            snippet: false,
+            filter_text: None,
+            replace: None,
+            import: None,
+            additional_edits: vec![],
+        });
+    };
+    let sorted = |namespace: Namespace| {
+        let mut names: Vec<_> = exports.interface.names.get(namespace).iter().collect();
+        names.sort_by_key(|(name, _)| name.as_str());
+        names.into_iter().map(|(&name, &definition)| (name, definition))
+    };
+
+    if let Some(enumerated) = token.parent_ancestors().find_map(ast::DataEnumerated::cast) {
+        let type_ = ast::ImportType::cast(enumerated.syntax().parent()?)?;
+        let type_ = Name::new(type_.name_ref()?.token()?.text());
+        let written = enumerated.constructors().map(Some).filter_map(is_written);
+        let written: Vec<_> = written.collect();
+        let constructors = exports.interface.type_constructors.get(&type_)?;
+        for (name, definition) in sorted(Namespace::Constructor) {
+            if constructors.contains(&name) && !written.contains(&name) {
+                push_definition(name, definition, None);
+            }
+        }
+        return Some(items);
+    }
+
+    let written: Vec<_> = list
+        .items()
+        .filter_map(|item| match item {
+            ast::ImportItem::Value(value) => is_written(value.name_ref()),
+            ast::ImportItem::Operator(operator) => is_written(operator.name_ref()),
+            ast::ImportItem::Type(type_) => is_written(type_.name_ref()),
+            ast::ImportItem::TypeOperator(operator) => is_written(operator.name_ref()),
+            ast::ImportItem::Class(class) => is_written(class.name_ref()),
+        })
+        .collect();
+    for namespace in [
+        Namespace::Value,
+        Namespace::Operator,
+        Namespace::Type,
+        Namespace::TypeOperator,
+        Namespace::Class,
+    ] {
+        for (name, definition) in sorted(namespace) {
+            if written.contains(&name) {
+                continue;
+            }
+            // Operators and classes are written differently from how they're
+            // named.
+            let insert_text = match namespace {
+                Namespace::Operator => Some(format!("({name})")),
+                Namespace::TypeOperator => Some(format!("type ({name})")),
+                Namespace::Class => Some(format!("class {name}")),
+                _ => None,
+            };
+            push_definition(name, definition, insert_text);
+        }
+    }
+    Some(items)
+}
+
+/// Names exported by modules visible from `file`, which are imported by
+/// extending an import or adding one. Modules that are only imported under
+/// an alias keep being referred to through it, rather than being imported
+/// again. A name exported by several modules is imported from the one that
+/// declares it where possible.
+fn unqualified_imports(
+    db: &Database,
+    file: FileId,
+    prefix: &str,
+    namespaces: &[Namespace],
+    items: &mut Vec<CompletionItem>,
+) {
+    let tree = db.item_tree(file);
+    let mut candidates = vec![];
+    for (name, module) in importable_modules(db, file) {
+        let imports: Vec<_> = tree.imports.iter().filter(|import| import.module == name).collect();
+        let is_open = imports.iter().any(|import| {
+            import.alias.is_none() && import.list.as_ref().is_none_or(|list| list.hiding)
+        });
+        if is_open {
+            continue;
+        }
+        let alias = imports.iter().find_map(|import| import.alias);
+        for (export, definition) in exported(db, module, prefix, namespaces) {
+            candidates.push((export, definition, name, alias));
+        }
+    }
+    // Declaring modules first, such that re-exports of the same definition
+    // are left out.
+    candidates.sort_by_key(|&(_, definition, module, _)| definition.module != module);
+    let mut seen = vec![];
+    for (export, definition, module, alias) in candidates {
+        if seen.contains(&definition) || items.iter().any(|item| item.label == export.as_str()) {
+            continue;
+        }
+        seen.push(definition);
+        let Some(mut item) = import_item(db, file, export, definition, module) else { continue };
+        match alias {
+            Some(alias) => item.insert_text = Some(format!("{alias}.{export}")),
+            None => {
+                let Some(name) = import_name(db, file, export, definition) else { continue };
+                item.additional_edits
+                    .extend(import_edit(db, file, module, &[name]).into_iter().flatten());
+            }
+        }
+        items.push(item);
+    }
+}
+
+/// Names exported by the modules that an alias that isn't imported yet is
+/// conventionally used for, i.e. those whose name or last segment it is.
+fn qualified_imports(
+    db: &Database,
+    file: FileId,
+    alias: ModuleName,
+    prefix: &str,
+    namespaces: &[Namespace],
+    items: &mut Vec<CompletionItem>,
+) {
+    for (name, module) in importable_modules(db, file) {
+        if name != alias && name.segments().last() != alias.segments().last() {
+            continue;
+        }
+        for (export, definition) in exported(db, module, prefix, namespaces) {
+            let Some(mut item) = import_item(db, file, export, definition, name) else { continue };
+            item.additional_edits.extend(qualified_import_edit(db, file, name, alias));
+            items.push(item);
+        }
+    }
+}
+
+fn exported(
+    db: &Database,
+    module: ModuleId,
+    prefix: &str,
+    namespaces: &[Namespace],
+) -> Vec<(Name, Definition)> {
+    let exports = db.exports(module);
+    let mut names = vec![];
+    for &namespace in namespaces {
+        let matching =
+            exports.interface.names.get(namespace).iter().filter(|(name, _)| {
+                name.as_str().to_lowercase().starts_with(&prefix.to_lowercase())
+            });
+        let mut matching: Vec<_> =
+            matching.map(|(&name, &definition)| (name, definition)).collect();
+        matching.sort_by_key(|(name, _)| name.as_str());
+        names.extend(matching);
+    }
+    names
+}
+
+fn import_item(
+    db: &Database,
+    file: FileId,
+    name: Name,
+    definition: Definition,
+    import: ModuleName,
+) -> Option<CompletionItem> {
+    let module = definition_module(db, file, definition)?;
+    let tree = db.module_tree(module)?;
+    Some(CompletionItem {
+        label: name.to_string(),
+        kind: CompletionKind::Declaration(definition_kind(&tree, definition)),
+        detail: definition_type(db, module, &tree, definition),
+        documentation: definition_docs(db, module, definition),
+        insert_text: None,
+        snippet: false,
+        filter_text: None,
+        replace: None,
+        import: Some(import),
+        additional_edits: vec![],
+    })
+}
+
+enum Qualifier {
+    None,
+    /// The alias of the module that a name is qualified with.
+    Module(ModuleName),
+    /// A label after a record.
+    Record,
+}
+
+/// The qualifier of the name that's being typed at the end of `text`.
+fn qualifier(text: &str) -> Qualifier {
+    let Some(before) = text.trim_end_matches(is_name).strip_suffix('.') else {
+        return Qualifier::None;
+    };
+    let start = before.trim_end_matches(|character| is_name(character) || character == '.');
+    let qualifier = &before[start.len()..];
+    match qualifier.chars().next() {
+        Some(first) if first.is_uppercase() => Qualifier::Module(ModuleName::new(qualifier)),
+        Some(_) => Qualifier::Record,
+        // A label after a parenthesized expression or a record literal.
+        None if before.ends_with([')', '}']) => Qualifier::Record,
+        None => Qualifier::None,
+    }
+}
+
+fn is_name(character: char) -> bool {
+    character.is_alphanumeric() || matches!(character, '_' | '\'')
+}
+
+/// Whether the cursor is within a type, or where one is expected.
+fn is_type(token: &SyntaxToken) -> bool {
+    for node in token.parent_ancestors() {
+        if ast::Type::can_cast(node.kind()) {
+            return true;
+        }
+        if ast::Expression::can_cast(node.kind()) || ast::Binder::can_cast(node.kind()) {
+            return false;
+        }
+        match node.kind() {
+            SyntaxKind::AnnotationDeclaration
+            | SyntaxKind::KindSignatureDeclaration
+            | SyntaxKind::ClassMember
+            | SyntaxKind::ForeignValueDeclaration
+            | SyntaxKind::ForeignDataDeclaration
+            | SyntaxKind::TypeDeclaration
+            | SyntaxKind::DataConstructor
+            | SyntaxKind::InstanceHead
+            | SyntaxKind::LetBindingSignature => return true,
+            SyntaxKind::ValueDeclaration | SyntaxKind::LetBindingName => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// The binders visible from the innermost expression at `token`, which
+/// shadow each other and every global.
+fn locals(db: &Database, file: FileId, token: &SyntaxToken, items: &mut Vec<CompletionItem>) {
+    let lowered = db.lower(file);
+    for node in token.parent_ancestors() {
+        for (body, _) in lowered.module.bodies.iter() {
+            let Some(expr) = lowered.source_map.body(body).node_expr(&node) else { continue };
+            let scopes = ScopeTree::new(&lowered.module.bodies[body]);
+            let inferred = db.infer(file);
+            let types = inferred.get(body);
+            // Desugaring binds names that can't be written, starting with `$`.
+            let visible =
+                scopes.visible(expr).filter(|entry| !entry.name.as_str().starts_with('$'));
+            for entry in visible {
+                let detail = types.and_then(|types| {
+                    let ty = types.binder(entry.binder)?;
+                    Some(types.types.display(ty).to_string())
+                });
+                push(items, entry.name, CompletionKind::Local, detail);
+            }
+            return;
+        }
+    }
+}
+
+/// The fields of the record that the labels before the cursor are taken
+/// from, e.g. those of `b` in `a.b.c`.
+fn access_labels(
+    db: &Database,
+    file: FileId,
+    token: &SyntaxToken,
+    offset: TextSize,
+) -> Vec<CompletionItem> {
+    let Some(access) = token.parent_ancestors().find_map(ast::ExpressionRecordAccess::cast) else {
+        return vec![];
+    };
+    let Some(record) = access.expression() else { return vec![] };
+    let path: Vec<_> = access
+        .labels()
+        .filter(|label| label.syntax().text_range().end() < offset)
+        .map(|label| lower_label(Some(label)))
+        .collect();
+    let record_type = |_: &Body, types: &BodyTypes, expr| types.expr(expr);
+    field_items(db, file, record.syntax(), record_type, &path, &[]).unwrap_or_default()
+}
+
+/// The fields of the record literal or update whose label is at the cursor,
+/// leaving out those that are already written. Returns `None` if the cursor
+/// isn't at a label, or the type of the record isn't known.
+fn record_labels(
+    db: &Database,
+    file: FileId,
+    token: &SyntaxToken,
+    offset: TextSize,
+) -> Option<Vec<CompletionItem>> {
+    let mut node = label_position(token)?;
+    let is_written = |label: Option<ast::Label>| {
+        let label = label.filter(|label| !label.syntax().text_range().contains_inclusive(offset));
+        label.map(|label| lower_label(Some(label)))
+    };
+    if let Some(record) = ast::ExpressionRecord::cast(node.clone()) {
+        let written: Vec<_> = record
+            .fields()
+            .filter_map(|field| match field {
+                ast::RecordItem::Field(field) => is_written(field.label()),
+                ast::RecordItem::Pun(pun) => is_written(pun.label()),
+            })
+            .collect();
+        let record_type = |body: &Body, types: &BodyTypes, expr| {
+            expected_type(body, types, expr).or_else(|| updated_type(body, types, expr))
+        };
+        return field_items(db, file, record.syntax(), record_type, &[], &written);
+    }
+
+    let updates = |node: &SyntaxNode| {
+        let updates = node.children().filter_map(ast::RecordUpdate::cast);
+        let labels = updates.filter_map(|update| match update {
+            ast::RecordUpdate::Leaf(leaf) => is_written(leaf.label()),
+            ast::RecordUpdate::Branch(branch) => is_written(branch.label()),
+        });
+        labels.collect::<Vec<_>>()
+    };
+    let written = updates(&node);
+    // Nested updates are of the fields along the path to them.
+    let mut path = vec![];
+    while let Some(branch) = ast::RecordUpdateBranch::cast(node.clone()) {
+        path.push(lower_label(branch.label()));
+        node = node.parent()?;
+    }
+    path.reverse();
+    let record = ast::ExpressionRecordUpdate::cast(node)?.expression()?;
+    let record_type = |_: &Body, types: &BodyTypes, expr| types.expr(expr);
+    field_items(db, file, record.syntax(), record_type, &path, &written)
+}
+
+/// The record literal, update, or nested update that a label can be written
+/// in at `token`.
+fn label_position(token: &SyntaxToken) -> Option<SyntaxNode> {
+    // The cursor is after the record.
+    if token.kind() == SyntaxKind::RightBracket {
+        return None;
+    }
+    let mut node = token.parent()?;
+    if node.kind() == SyntaxKind::Label {
+        node = node.parent()?;
+        if !matches!(
+            node.kind(),
+            SyntaxKind::RecordField
+                | SyntaxKind::RecordPun
+                | SyntaxKind::RecordUpdateLeaf
+                | SyntaxKind::RecordUpdateBranch
+        ) {
+            return None;
+        }
+        node = node.parent()?;
+    } else if node.kind() == SyntaxKind::Error {
+        node = node.parent()?;
+    }
+    let is_record = matches!(
+        node.kind(),
+        SyntaxKind::ExpressionRecord
+            | SyntaxKind::ExpressionRecordUpdate
+            | SyntaxKind::RecordUpdateBranch
+    );
+    is_record.then_some(node)
+}
+
+/// The fields of the record whose type `record_type` finds from the
+/// expression at `node`, walked down through the labels of `path`.
+fn field_items(
+    db: &Database,
+    file: FileId,
+    node: &SyntaxNode,
+    record_type: impl Fn(&Body, &BodyTypes, ExprId) -> Option<TyId>,
+    path: &[Name],
+    written: &[Name],
+) -> Option<Vec<CompletionItem>> {
+    let lowered = db.lower(file);
+    let (body, expr) = lowered
+        .module
+        .bodies
+        .iter()
+        .find_map(|(body, _)| Some((body, lowered.source_map.body(body).node_expr(node)?)))?;
+    let inferred = db.infer(file);
+    let types = inferred.get(body)?;
+    let mut ty = record_type(&lowered.module.bodies[body], types, expr)?;
+    for &label in path {
+        ty = field_type(&types.types, ty, label)?;
+    }
+
+    let mut items = vec![];
+    for &(label, field) in fields(&types.types, ty)? {
+        if !written.contains(&label) {
+            let detail = types.types.display(field).to_string();
+            push(&mut items, label, CompletionKind::Field, Some(detail));
+        }
+    }
+    Some(items)
+}
+
+/// Adds definitions in the order of their names, as they're collected from
+/// maps.
+fn globals(
+    db: &Database,
+    file: FileId,
+    definitions: impl Iterator<Item = (Name, Definition)>,
+    items: &mut Vec<CompletionItem>,
+) {
+    let mut definitions: Vec<_> = definitions.collect();
+    definitions.sort_by_key(|(name, _)| name.as_str());
+    for (name, definition) in definitions {
+        let Some(module) = definition_module(db, file, definition) else { continue };
+        let Some(tree) = db.module_tree(module) else { continue };
+        let kind = definition_kind(&tree, definition);
+        let detail = definition_type(db, module, &tree, definition);
+        let documentation = definition_docs(db, module, definition);
+        push_documented(items, name, CompletionKind::Declaration(kind), detail, documentation);
+    }
+}
+
+fn push(items: &mut Vec<CompletionItem>, name: Name, kind: CompletionKind, detail: Option<String>) {
+    push_documented(items, name, kind, detail, None);
+}
+
+fn push_documented(
+    items: &mut Vec<CompletionItem>,
+    name: Name,
+    kind: CompletionKind,
+    detail: Option<String>,
+    documentation: Option<String>,
+) {
+    let label = name.to_string();
+    if !items.iter().any(|item| item.label == label) {
+        items.push(CompletionItem {
+            label,
+            kind,
+            detail,
+            documentation,
+            insert_text: None,
+            snippet: false,
+            filter_text: None,
+            replace: None,
+            import: None,
+            additional_edits: vec![],
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::{completions, CompletionConfig, CompletionKind};
+    use crate::edit::apply_edits;
+
+    #[test]
+    fn complete_names_in_scope() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\n-- | Unwraps a `Maybe`.\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a _ = a\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\n\nanswer :: Int\nanswer = 42\n\nf x = let y = x in y\n\ng = M.f\n\nh :: In\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let completions_at = |text: &str| {
+            let offset = main.find(text).unwrap() + text.len();
+            completions(&db, FileId(1), TextSize::from(offset as u32), CompletionConfig::default())
+        };
+        let labels = |text: &str| {
+            let items = completions_at(text);
+            items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+        };
+        // Locals come first, innermost first.
+        assert_eq!(labels("in y")[..4], ["y", "x", "answer", "f"]);
+        let items = completions_at("in y");
+        assert_eq!(items[0].kind, CompletionKind::Local);
+        assert_eq!(items[2].detail.as_deref(), Some("Int"));
+
+        assert_eq!(labels("M.f"), ["fromMaybe", "Just", "Nothing"]);
+        let items = completions_at("M.f");
+        assert_eq!(items[0].detail.as_deref(), Some("forall a. a -> (Maybe a) -> a"));
+        assert_eq!(items[0].documentation.as_deref(), Some("Unwraps a `Maybe`."));
+        // Types include those built into the compiler.
+        assert!(labels("h :: In").contains(&"Int".to_string()));
+        assert!(!labels("h :: In").contains(&"answer".to_string()));
+    }
+
+    #[test]
+    fn complete_record_labels() {
+        let mut db = Database::new();
+        let main = "module Main where\n\ntype Person = { name :: String, age :: Int, home :: { city :: String } }\n\ngreet :: Person -> String\ngreet p = p.\n\ncity :: Person -> String\ncity p = p.home.\n\nnamed :: forall r. { name :: String | r } -> String\nnamed r = r.\n\nolder :: Person -> Person\nolder p = p { age = 1, }\n\nrenamed :: Person -> Person\nrenamed p = p { na }\n\nmake :: String -> Person\nmake n = { name: n, home: { }, }\n";
+        db.set_file_text(FileId(0), main);
+
+        let completions_at = |text: &str| {
+            let offset = main.find(text).unwrap() + text.len();
+            completions(&db, FileId(0), TextSize::from(offset as u32), CompletionConfig::default())
+        };
+        let labels = |text: &str| {
+            let items = completions_at(text);
+            items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+        };
+        assert_eq!(labels("greet p = p."), ["name", "age", "home"]);
+        let items = completions_at("greet p = p.");
+        assert_eq!(items[0].kind, CompletionKind::Field);
+        assert_eq!(items[2].detail.as_deref(), Some("{ city :: String }"));
+        assert_eq!(labels("p.home."), ["city"]);
+        // Only the fields before the tail of a polymorphic row are known.
+        assert_eq!(labels("named r = r."), ["name"]);
+        // Fields that are already written are left out.
+        assert_eq!(labels("age = 1, "), ["name", "home"]);
+        assert_eq!(labels("p { na"), ["name", "age", "home"]);
+        assert_eq!(labels("home: { "), ["city"]);
+        assert_eq!(labels("make n = { name: n, home: { }, "), ["age"]);
+    }
+
+    #[test]
+    fn complete_imports() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe a _ = a\n\ninfixl 4 fromMaybe as <?>\n\nclass Default a where\n  default :: a\n");
+        db.set_file_text(FileId(1), "module Data.Map where\n\ninsert k m = m\n");
+        let main = "module Main where\n\nimport Data.Ma\nimport Data.Maybe (Maybe(J), fromMaybe, )\n\nf = 1\n";
+        db.set_file_text(FileId(2), main);
+
+        let completions_at = |text: &str| {
+            let offset = main.find(text).unwrap() + text.len();
+            completions(&db, FileId(2), TextSize::from(offset as u32), CompletionConfig::default())
+        };
+        let labels = |text: &str| {
+            let items = completions_at(text);
+            items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+        };
+        // Module names replace every segment that's been typed.
+        let modules = completions_at("import Data.Ma");
+        assert_eq!(modules[0].label, "Data.Map");
+        assert_eq!(modules[0].kind, CompletionKind::Module);
+        assert_eq!(&main[modules[0].replace.unwrap()], "Data.Ma");
+        assert!(labels("import Data.Ma").contains(&"Prim.Row".to_string()));
+        assert!(!labels("import Data.Ma").contains(&"Main".to_string()));
+
+        assert_eq!(labels("Maybe(J"), ["Just", "Nothing"]);
+        // Names that are listed already are left out.
+        let items = completions_at("fromMaybe, ");
+        let inserted: Vec<_> =
+            items.into_iter().map(|item| item.insert_text.unwrap_or(item.label)).collect();
+        assert_eq!(inserted, ["default", "(<?>)", "class Default"]);
+    }
+
+    #[test]
+    fn complete_unimported_names() {
+        let mut db = Database::new();
+        db.set_file_text(
+            FileId(0),
+            "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe a _ = a\n",
+        );
+        db.set_file_text(FileId(1), "module Data.Map where\n\ninsert k m = m\n");
+        db.set_file_text(FileId(2), "module Data.Array where\n\nfromFoldable x = x\n");
+        let main = "module Main where\n\nimport Data.Array as A\nimport Data.Maybe (Maybe)\n\nf = from\n\ng = Ju\n\nh = Map.ins\n";
+        db.set_file_text(FileId(3), main);
+
+        let completions_at = |text: &str| {
+            let offset = main.find(text).unwrap() + text.len();
+            let items = completions(
+                &db,
+                FileId(3),
+                TextSize::from(offset as u32),
+                CompletionConfig::default(),
+            );
+            let items = items.into_iter().filter(|item| item.import.is_some());
+            items
+                .map(|item| {
+                    let inserted = item.insert_text.unwrap_or(item.label);
+                    (inserted, apply_edits(main, &item.additional_edits))
+                })
+                .collect::<Vec<_>>()
+        };
+        // Modules imported under an alias are referred to through it.
+        let from = completions_at("= from");
+        assert_eq!(from[0], ("A.fromFoldable".to_string(), main.to_string()));
+        assert_eq!(from[1].0, "fromMaybe");
+        assert!(from[1].1.contains("import Data.Maybe (Maybe, fromMaybe)\n"));
+
+        let just = completions_at("= Ju");
+        assert!(just[0].1.contains("import Data.Maybe (Maybe(..))\n"));
+
+        let insert = completions_at("Map.ins");
+        assert_eq!(insert[0].0, "insert");
+        assert!(insert[0].1.contains("import Data.Maybe (Maybe)\nimport Data.Map as Map\n"));
+    }
+
+    #[test]
+    fn complete_snippets() {
+        let mut db = Database::new();
+        let main = "module Main where
+
+da
+
+f x = ca
+
+g x = x.ca
+
+h x = (f x).pu
+";
+        db.set_file_text(FileId(0), main);
+
+        let snippets = |text: &str, snippets: bool| {
+            let offset = TextSize::from((main.find(text).unwrap() + text.len()) as u32);
+            let config = CompletionConfig { snippets };
+            let items = completions(&db, FileId(0), offset, config);
+            let items = items.into_iter().filter(|item| item.kind == CompletionKind::Snippet);
+            items.collect::<Vec<_>>()
+        };
+        let labels = |text: &str| {
+            let items = snippets(text, true);
+            items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+        };
+        assert_eq!(labels("\nda"), ["data", "instance"]);
+        assert_eq!(labels("= ca"), ["case"]);
+        // Snippets are only offered to clients that can insert them.
+        assert!(snippets("= ca", false).is_empty());
+
+        let postfix = snippets("x.ca", true);
+        assert_eq!(
+            postfix.iter().map(|item| item.label.as_str()).collect::<Vec<_>>(),
+            ["case", "if", "pure"]
+        );
+        assert_eq!(&main[postfix[0].replace.unwrap()], "x.ca");
+        assert_eq!(postfix[0].filter_text.as_deref(), Some("x.case"));
+        assert_eq!(postfix[0].insert_text.as_deref(), Some("case x of\n  ${1:_} -> ${0:?body}"));
+        let wrapped = snippets("(f x).pu", true);
+        assert_eq!(wrapped[2].insert_text.as_deref(), Some("pure ((f x))"));
+    }
+}
@@ -0,0 +1,113 @@
+//! The skeletons of declarations and expressions, and the templates that
+//! wrap the expression before them.
+
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxToken};
+
+use super::{CompletionItem, CompletionKind};
+
+/// The declarations that can start at the margin, as their label and what
+/// they insert. Lines after the first are indented relative to it, which
+/// clients carry over to the indentation of the line.
+const DECLARATIONS: [(&str, &str); 2] = [
+    ("data", "data ${1:Name} = ${0:Constructor}"),
+    ("instance", "instance ${1:Class} ${2:Type} where\n  $0"),
+];
+
+const EXPRESSIONS: [(&str, &str); 1] = [("case", "case ${1:value} of\n  ${2:_} -> ${0:?body}")];
+
+/// The labels of the templates that wrap an expression.
+const POSTFIX: [&str; 3] = ["case", "if", "pure"];
+
+/// The declarations that can be started at the margin, after the header of
+/// the module.
+pub(super) fn declarations(token: &SyntaxToken, items: &mut Vec<CompletionItem>) {
+    if token.parent_ancestors().any(|node| node.kind() == SyntaxKind::ModuleHeader) {
+        return;
+    }
+    for (label, snippet) in DECLARATIONS {
+        items.push(snippet_item(label, snippet.to_string()));
+    }
+}
+
+/// The expressions that can be started within a value declaration.
+pub(super) fn expressions(token: &SyntaxToken, items: &mut Vec<CompletionItem>) {
+    if !token.parent_ancestors().any(|node| node.kind() == SyntaxKind::ValueDeclaration) {
+        return;
+    }
+    for (label, snippet) in EXPRESSIONS {
+        items.push(snippet_item(label, snippet.to_string()));
+    }
+}
+
+/// The templates that replace the expression before the label at `offset`
+/// along with the label, e.g. `x.case` with `case x of`.
+pub(super) fn postfix(
+    text: &str,
+    token: &SyntaxToken,
+    offset: TextSize,
+    items: &mut Vec<CompletionItem>,
+) {
+    let Some(access) = token.parent_ancestors().find_map(ast::ExpressionRecordAccess::cast) else {
+        return;
+    };
+    let Some(expression) = access.expression() else { return };
+    let start = expression.syntax().text_range().start();
+    let before = &text[usize::from(start)..usize::from(offset)];
+    // The labels before the one that's being typed are part of the
+    // expression, as in `a.b.case`.
+    let Some(dot) = before.rfind('.') else { return };
+    let expression = before[..dot].trim();
+    if expression.is_empty() {
+        return;
+    }
+    let expression = escape(expression);
+    for label in POSTFIX {
+        let snippet = match label {
+            "case" => format!("case {expression} of\n  ${{1:_}} -> ${{0:?body}}"),
+            "if" => format!("if {expression} then ${{1:?yes}} else ${{0:?no}}"),
+            _ => format!("{label} {}", parenthesized(&expression)),
+        };
+        let mut item = snippet_item(label, snippet);
+        item.replace = Some(TextRange::new(start, offset));
+        item.filter_text = Some(format!("{}.{label}", &before[..dot]));
+        items.push(item);
+    }
+}
+
+fn snippet_item(label: &str, snippet: String) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: CompletionKind::Snippet,
+        detail: None,
+        documentation: None,
+        insert_text: Some(snippet),
+        snippet: true,
+        filter_text: None,
+        replace: None,
+        import: None,
+        additional_edits: vec![],
+    }
+}
+
+/// Wraps an expression that has spaces in it in parentheses, which might
+/// be redundant but never changes what it means.
+fn parenthesized(expression: &str) -> String {
+    if !expression.contains(char::is_whitespace) {
+        expression.to_string()
+    } else {
+        format!("({expression})")
+    }
+}
+
+/// Escapes the characters that snippets give a meaning to.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if matches!(character, '$' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
@@ -0,0 +1,190 @@
+//! Where the name under the cursor is defined.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{hir::Expr, lower_label, name::Name};
+use resolution::reference::Target;
+use rowan::{ast::AstNode, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use crate::target::{
+    definition_module, definition_target, local_target, module_target, reference_at,
+    NavigationTarget,
+};
+
+/// The definitions of what the name at `offset` refers to, which are
+/// several for names that are ambiguous. Definitions in dependencies are
+/// found in their sources, while those built into the compiler have none.
+pub fn definition(db: &Database, file: FileId, offset: TextSize) -> Vec<NavigationTarget> {
+    let Some(reference) = reference_at(db, file, offset) else { return vec![] };
+    match reference.target {
+        Target::Local { body, binder } => {
+            local_target(db, file, body, binder).into_iter().collect()
+        }
+        Target::Global(definition) => definition_module(db, file, definition)
+            .and_then(|module| definition_target(db, module, definition))
+            .into_iter()
+            .collect(),
+        Target::Ambiguous(definitions) => definitions
+            .into_iter()
+            .filter_map(|definition| {
+                definition_target(db, definition_module(db, file, definition)?, definition)
+            })
+            .collect(),
+        Target::Modules(modules) => modules
+            .into_iter()
+            .filter_map(|name| match db.resolve_module(ModuleId::File(file), name)? {
+                ModuleId::File(file) => Some(module_target(db, file)),
+                ModuleId::Compiled(_) | ModuleId::Prim(_) => None,
+            })
+            .collect(),
+        Target::Prim(_) => vec![],
+        Target::Label(label) => label_definitions(db, file, offset, label),
+    }
+}
+
+/// The fields that a label in a record update may refer to. Records are
+/// structural, so these are the fields of the record types declared in the
+/// modules in scope that have the labels of the updated record's type, or
+/// the labels that are updated if its type isn't known.
+fn label_definitions(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    label: Name,
+) -> Vec<NavigationTarget> {
+    let Some(labels) = update_labels(db, file, offset) else { return vec![] };
+    let mut files = vec![file];
+    for import in &db.item_tree(file).imports {
+        if let Some(ModuleId::File(imported)) =
+            db.resolve_module(ModuleId::File(file), import.module)
+        {
+            if !files.contains(&imported) {
+                files.push(imported);
+            }
+        }
+    }
+
+    let mut targets = vec![];
+    for file in files {
+        let root = db.parse(file).root.clone();
+        let records = root
+            .descendants()
+            .filter(|node| matches!(node.kind(), SyntaxKind::TypeRecord | SyntaxKind::TypeRow));
+        for record in records {
+            let fields: Vec<_> = record
+                .children()
+                .filter(|node| node.kind() == SyntaxKind::RowField)
+                .filter_map(|field| Some((field_label(&field)?, field)))
+                .collect();
+            let matches = match &labels {
+                Labels::Exactly(labels) => {
+                    fields.len() == labels.len()
+                        && fields.iter().all(|(name, _)| labels.contains(name))
+                }
+                Labels::AtLeast(labels) => {
+                    labels.iter().all(|label| fields.iter().any(|(name, _)| name == label))
+                }
+            };
+            if !matches {
+                continue;
+            }
+            if let Some((_, field)) = fields.iter().find(|(name, _)| *name == label) {
+                let focus_range =
+                    field.first_child().map_or(field.text_range(), |label| label.text_range());
+                targets.push(NavigationTarget {
+                    file,
+                    full_range: field.text_range(),
+                    focus_range,
+                });
+            }
+        }
+    }
+    targets
+}
+
+enum Labels {
+    /// The labels of a closed row.
+    Exactly(Vec<Name>),
+    AtLeast(Vec<Name>),
+}
+
+/// The labels of the record updated by the update that the label at
+/// `offset` belongs to.
+fn update_labels(db: &Database, file: FileId, offset: TextSize) -> Option<Labels> {
+    let parse = db.parse(file);
+    let token = parse
+        .root
+        .token_at_offset(offset)
+        .find(|token| token.parent().is_some_and(|parent| parent.kind() == SyntaxKind::Label))?;
+    let update = token.parent()?.parent()?;
+    let record_update = update.parent().filter(|node| {
+        matches!(update.kind(), SyntaxKind::RecordUpdateLeaf | SyntaxKind::RecordUpdateBranch)
+            && node.kind() == SyntaxKind::ExpressionRecordUpdate
+    })?;
+
+    let lowered = db.lower(file);
+    let (body, expr) = lowered.module.bodies.iter().find_map(|(body, _)| {
+        Some((body, lowered.source_map.body(body).node_expr(&record_update)?))
+    })?;
+    let Expr::RecordUpdate { record, updates } = &lowered.module.bodies[body].exprs[expr] else {
+        return None;
+    };
+    let updated = Labels::AtLeast(updates.iter().map(|&(name, _)| name).collect());
+
+    let types = db.infer(file);
+    let Some(types) = types.get(body) else { return Some(updated) };
+    let Some(ty) = types.expr(*record) else { return Some(updated) };
+    match types.types.as_record(ty) {
+        Some((fields, tail)) => {
+            let labels = fields.iter().map(|&(name, _)| name).collect();
+            Some(if tail.is_none() { Labels::Exactly(labels) } else { Labels::AtLeast(labels) })
+        }
+        None => Some(updated),
+    }
+}
+
+fn field_label(field: &SyntaxNode) -> Option<Name> {
+    let label = field.children().find_map(ast::Label::cast)?;
+    Some(lower_label(Some(label)))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::definition;
+
+    #[test]
+    fn go_to_definitions() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\ninfixl 4 map as <$>\n\nmap :: forall a b. (a -> b) -> Maybe a -> Maybe b\nmap f m = m\n";
+        let main = "module Main where\n\nimport Data.Maybe (Maybe(..), (<$>))\n\ntype Point = { x :: Int, y :: Int }\n\nmove :: Point -> Point\nmove p = p { x = 1 }\n\nf y = (_ + y) <$> Just y\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let definition_at = |text: &str, nth: usize| {
+            let (offset, _) = main.match_indices(text).nth(nth).unwrap();
+            let targets = definition(&db, FileId(1), TextSize::from(offset as u32));
+            let text = |file: FileId, range| {
+                let source = if file == FileId(0) { maybe } else { main };
+                &source[range]
+            };
+            targets
+                .into_iter()
+                .map(|target| (target.file, text(target.file, target.focus_range)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(definition_at("Just y", 0), [(FileId(0), "Just")]);
+        assert_eq!(definition_at("<$> Just", 0), [(FileId(0), "<$>")]);
+        assert_eq!(definition_at("<$>", 0), [(FileId(0), "<$>")]);
+        assert_eq!(definition_at("Data.Maybe", 0), [(FileId(0), "Data.Maybe")]);
+        assert_eq!(definition_at("Point ->", 0), [(FileId(1), "Point")]);
+        assert_eq!(definition_at("x = 1", 0), [(FileId(1), "x")]);
+        assert_eq!(definition_at("move p", 0), [(FileId(1), "move")]);
+        // `y` is bound by `f`, distinct from the label of the same name.
+        assert_eq!(definition_at("y)", 0), [(FileId(1), "y")]);
+        let local = definition(&db, FileId(1), TextSize::from(main.find("y)").unwrap() as u32));
+        assert_eq!(local[0].focus_range.start(), TextSize::from(main.find("y =").unwrap() as u32));
+    }
+}
@@ -0,0 +1,934 @@
+//! The errors and warnings reported for a file.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    diagnostics::LoweringDiagnostic,
+    hir::{BodyId, DataMembers, Export, ImportItem},
+    lower_export, lower_import_item, lower_module_name,
+    name::Name,
+    source_map::SourceMap,
+};
+use parsing::ParseErrorKind;
+use resolution::{
+    diagnostics::{check_names, ResolutionDiagnostic},
+    exports::ExportDiagnostic,
+    fixity::{check_fixities, Fixities, FixityDiagnostic},
+    graph::{GraphDiagnostic, ImportCycle},
+    imports::ImportDiagnostic,
+    instances::{check_instances, check_orphans, InstanceDiagnostic, OrphanWarning},
+    interface::Namespace,
+    shadowing::{check_shadowing, ShadowingWarning},
+    unused::{check_unused_imports, UnusedImport},
+};
+use rowan::{
+    ast::{AstNode, SyntaxNodePtr},
+    TextRange,
+};
+use syntax::{ast, PureScript, SyntaxNode};
+use typecheck::{infer::Origin, patterns::PatternDiagnostic, ty::Types};
+
+use crate::{
+    folding_ranges::text_range,
+    target::{definition_module, definition_target, visible_trees, FileRange},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: TextRange,
+    pub severity: Severity,
+    /// A stable name for the kind of diagnostic.
+    pub code: &'static str,
+    pub message: String,
+    /// Other places that explain the diagnostic, with what each of them is.
+    pub related: Vec<(FileRange, String)>,
+    /// Whether the diagnostic is about code that can be removed, which
+    /// editors show faded out.
+    pub unnecessary: bool,
+}
+
+/// Which binders that hide another name are reported, and how. Shadowing is
+/// idiomatic in a lot of code, so it's only reported where it's enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowingConfig {
+    /// Binders that shadow a binder of an enclosing scope.
+    pub locals: bool,
+    /// Binders that shadow a declaration or an unqualified import.
+    pub globals: bool,
+    pub severity: Severity,
+    /// Names that may shadow others without being reported.
+    pub allowed: Vec<String>,
+}
+
+impl Default for ShadowingConfig {
+    fn default() -> ShadowingConfig {
+        ShadowingConfig {
+            locals: false,
+            globals: false,
+            severity: Severity::Warning,
+            allowed: vec![],
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(
+        range: TextRange,
+        severity: Severity,
+        code: &'static str,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic { range, severity, code, message, related: Vec::new(), unnecessary: false }
+    }
+}
+
+/// The diagnostics of a file, in the order they appear in: syntax errors,
+/// malformed declarations and literals, exports and imports that don't
+/// resolve, names that don't resolve, instances that don't match their class,
+/// orphan instances, open imports, binders that shadow others as enabled by
+/// `shadowing`, kind errors in its types, instance heads that their class's
+/// functional dependencies don't cover, instances that can't be derived,
+/// pattern matches that are missing patterns or have unreachable branches,
+/// and errors in the types of its bodies.
+pub fn diagnostics(db: &Database, file: FileId, shadowing: &ShadowingConfig) -> Vec<Diagnostic> {
+    let parse = db.parse(file);
+    // A token that can't be lexed can't be parsed either, which is only
+    // worth reporting once.
+    let lexed: Vec<_> = parse
+        .errors
+        .iter()
+        .filter(|error| error.kind == ParseErrorKind::Lexer)
+        .map(|error| error.range)
+        .collect();
+    let errors = parse.errors.iter().filter(|error| {
+        error.kind == ParseErrorKind::Lexer
+            || !lexed.iter().any(|range| range.contains_range(error.range))
+    });
+    let errors = errors.map(|error| {
+        Diagnostic::new(error.range, Severity::Error, error.kind.code(), error.message.clone())
+    });
+    let mut diagnostics: Vec<_> = errors.collect();
+    let lowered = db.lower(file);
+    for diagnostic in lowered.source_map.diagnostics() {
+        let range = node_range(&parse.root, diagnostic.ptr());
+        let code = lowering_code(diagnostic);
+        let mut lowering = Diagnostic::new(range, Severity::Error, code, diagnostic.message());
+        if let Some((ptr, message)) = diagnostic.related() {
+            let range = FileRange { file, range: node_range(&parse.root, ptr) };
+            lowering.related.push((range, message.to_string()));
+        }
+        diagnostics.push(lowering);
+    }
+    diagnostics.extend(unknown_modules(db, file).into_iter().map(|(range, diagnostic)| {
+        Diagnostic::new(range, Severity::Error, "unknown-module", diagnostic.message())
+    }));
+    let cycles = import_cycles(db, file);
+    // The modules of a cycle see each other as exporting nothing, so what
+    // fails to resolve because of it is left to the cycle.
+    if cycles.is_empty() {
+        diagnostics.extend(export_diagnostics(db, file).into_iter().map(|(range, diagnostic)| {
+            Diagnostic::new(range, Severity::Error, export_code(&diagnostic), diagnostic.message())
+        }));
+        diagnostics.extend(import_diagnostics(db, file).into_iter().map(|(range, diagnostic)| {
+            Diagnostic::new(range, Severity::Error, import_code(&diagnostic), diagnostic.message())
+        }));
+        for unused in unused_imports(db, file) {
+            let range = node_range(&parse.root, &unused.ptr());
+            let mut diagnostic =
+                Diagnostic::new(range, Severity::Warning, "unused-import", unused.message());
+            diagnostic.unnecessary = true;
+            diagnostics.push(diagnostic);
+        }
+        let resolution = resolution_diagnostics(db, file).into_iter();
+        diagnostics.extend(resolution.map(|(range, diagnostic)| {
+            let code = resolution_code(&diagnostic);
+            Diagnostic::new(range, Severity::Error, code, diagnostic.message())
+        }));
+    }
+    for (cycle, related) in cycles {
+        let range = node_range(&parse.root, &cycle.ptr);
+        let mut diagnostic =
+            Diagnostic::new(range, Severity::Error, "import-cycle", cycle.message());
+        diagnostic.related = related;
+        diagnostics.push(diagnostic);
+    }
+    diagnostics.extend(fixity_diagnostics(db, file).into_iter().map(|(range, diagnostic)| {
+        Diagnostic::new(range, Severity::Error, "operator-chain", diagnostic.message())
+    }));
+    diagnostics.extend(instance_diagnostics(db, file).into_iter().map(|(range, diagnostic)| {
+        Diagnostic::new(range, Severity::Error, instance_code(&diagnostic), diagnostic.message())
+    }));
+    for warning in orphan_warnings(db, file) {
+        let range = node_range(&parse.root, warning.ptr());
+        diagnostics.push(Diagnostic::new(
+            range,
+            Severity::Warning,
+            "orphan-instance",
+            warning.message(),
+        ));
+    }
+    for warning in shadowing_warnings(db, file, shadowing) {
+        let range = node_range(&parse.root, warning.ptr());
+        let mut diagnostic =
+            Diagnostic::new(range, shadowing.severity, "shadowing", warning.message());
+        let related = match &warning {
+            ShadowingWarning::Local { previous, .. } => {
+                Some(FileRange { file, range: node_range(&parse.root, previous) })
+            }
+            ShadowingWarning::Global { definition, .. } => {
+                let module = definition_module(db, file, *definition);
+                let target = module.and_then(|module| definition_target(db, module, *definition));
+                target.map(|target| FileRange { file: target.file, range: target.focus_range })
+            }
+        };
+        if let Some(related) = related {
+            diagnostic.related.push((related, "shadowed definition".to_string()));
+        }
+        diagnostics.push(diagnostic);
+    }
+    for import in wildcard_imports(db, file) {
+        let (Some(range), Some(module)) = (text_range(import.syntax()), import.module_name())
+        else {
+            continue;
+        };
+        let message = format!("{} is imported without an import list", module.syntax().text());
+        diagnostics.push(Diagnostic::new(range, Severity::Warning, "wildcard-import", message));
+    }
+    let inferred = db.infer(file);
+    // Coverage is about the variables of instance heads, which are named
+    // rather than rendered from types.
+    let no_types = Types::default();
+    let module_diagnostics = [
+        (inferred.kinds.diagnostics(), &inferred.kinds.types),
+        (&inferred.coverage[..], &no_types),
+        (inferred.derives.diagnostics(), &inferred.derives.types),
+    ];
+    for (module_diagnostics, types) in module_diagnostics {
+        for diagnostic in module_diagnostics {
+            let Origin::Type(type_) = diagnostic.origin() else { continue };
+            let Some(ptr) = lowered.source_map.type_syntax(type_) else { continue };
+            let range = node_range(&parse.root, ptr);
+            let message = diagnostic.message(types);
+            diagnostics.push(Diagnostic::new(range, Severity::Error, diagnostic.code(), message));
+        }
+    }
+    for (body, matches) in inferred.patterns.iter() {
+        for diagnostic in matches {
+            let origin = diagnostic.origin();
+            let Some(range) = origin_range(&parse.root, &lowered.source_map, body, origin) else {
+                continue;
+            };
+            let (severity, code, unnecessary) = match diagnostic {
+                PatternDiagnostic::Missing { .. } => (Severity::Error, "missing-patterns", false),
+                PatternDiagnostic::Redundant { .. } => {
+                    (Severity::Warning, "redundant-pattern", true)
+                }
+            };
+            let mut pattern = Diagnostic::new(range, severity, code, diagnostic.message());
+            pattern.unnecessary = unnecessary;
+            diagnostics.push(pattern);
+        }
+    }
+    for (body, types) in inferred.iter() {
+        for diagnostic in types.diagnostics() {
+            let origin = diagnostic.origin();
+            let Some(range) = origin_range(&parse.root, &lowered.source_map, body, origin) else {
+                continue;
+            };
+            let message = diagnostic.message(&types.types);
+            diagnostics.push(Diagnostic::new(range, Severity::Error, diagnostic.code(), message));
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    diagnostics
+}
+
+/// The range of the text that a diagnostic of `body` is reported at.
+pub(crate) fn origin_range(
+    root: &SyntaxNode,
+    source_map: &SourceMap,
+    body: BodyId,
+    origin: Origin,
+) -> Option<TextRange> {
+    let ptr = match origin {
+        Origin::Expr(expr) => source_map.body(body).expr_syntax(expr),
+        Origin::Binder(binder) => source_map.body(body).binder_syntax(binder),
+        Origin::Type(type_) => source_map.type_syntax(type_),
+    }?;
+    Some(node_range(root, ptr))
+}
+
+/// The range of the text of a node, without its trivia.
+fn node_range(root: &SyntaxNode, ptr: &SyntaxNodePtr<PureScript>) -> TextRange {
+    let node = ptr.to_node(root);
+    text_range(&node).unwrap_or(node.text_range())
+}
+
+/// The items of the export list of a file that don't resolve, or that
+/// export a name that's already exported with another definition.
+pub(crate) fn export_diagnostics(
+    db: &Database,
+    file: FileId,
+) -> Vec<(TextRange, ExportDiagnostic)> {
+    let header = db.parse(file).module().header();
+    let Some(exports) = header.and_then(|header| header.exports()) else { return vec![] };
+    let mut located: Vec<(TextRange, ExportDiagnostic)> = vec![];
+    for diagnostic in db.exports(ModuleId::File(file)).diagnostics() {
+        let range = exports
+            .items()
+            .filter_map(|item| export_item_range(&item, diagnostic))
+            .find(|range| !located.iter().any(|(located, _)| located == range));
+        if let Some(range) = range {
+            located.push((range, diagnostic.clone()));
+        }
+    }
+    located
+}
+
+/// The range of `item` that `diagnostic` is about, if it's about `item`.
+fn export_item_range(item: &ast::ExportItem, diagnostic: &ExportDiagnostic) -> Option<TextRange> {
+    let lowered = lower_export(item)?;
+    let is_item = match (diagnostic, &lowered) {
+        (
+            ExportDiagnostic::UnknownConstructor { type_, name },
+            Export::Type(item_type, members),
+        ) => {
+            let Some(DataMembers::Enumerated(names)) = members else { return None };
+            if item_type != type_ {
+                return None;
+            }
+            let position = names.iter().position(|constructor| constructor == name)?;
+            let ast::ExportItem::Type(type_item) = item else { return None };
+            let Some(ast::DataMembers::Enumerated(constructors)) = type_item.data_members() else {
+                return None;
+            };
+            return text_range(constructors.constructors().nth(position)?.syntax());
+        }
+        (
+            ExportDiagnostic::UnknownModule { module }
+            | ExportDiagnostic::Conflict { module: Some(module), .. },
+            Export::Module(item_module),
+        ) => item_module == module,
+        (
+            ExportDiagnostic::UnknownName { namespace, name }
+            | ExportDiagnostic::Conflict { namespace, name, module: None },
+            lowered,
+        ) => export_name(lowered) == Some((*namespace, *name)),
+        _ => false,
+    };
+    if is_item {
+        text_range(item.syntax())
+    } else {
+        None
+    }
+}
+
+fn export_name(export: &Export) -> Option<(Namespace, Name)> {
+    match export {
+        Export::Value(name) => Some((Namespace::Value, *name)),
+        Export::Operator(name) => Some((Namespace::Operator, *name)),
+        Export::Type(name, _) => Some((Namespace::Type, *name)),
+        Export::TypeOperator(name) => Some((Namespace::TypeOperator, *name)),
+        Export::Class(name) => Some((Namespace::Class, *name)),
+        Export::Module(_) => None,
+    }
+}
+
+/// The imports of a file of modules that it can't see, at the name of the
+/// module.
+pub(crate) fn unknown_modules(db: &Database, file: FileId) -> Vec<(TextRange, GraphDiagnostic)> {
+    let Some(module) = db.item_tree(file).name else { return vec![] };
+    let Some(header) = db.parse(file).module().header() else { return vec![] };
+    let unknown = header.imports().filter_map(|import| {
+        let name = import.module_name()?;
+        let import = lower_module_name(&name);
+        if db.resolve_module(ModuleId::File(file), import).is_some() {
+            return None;
+        }
+        Some((text_range(name.syntax())?, GraphDiagnostic::UnknownModule { module, import }))
+    });
+    unknown.collect()
+}
+
+/// The imports of a file that lead back to it, along with the import of
+/// each module of the cycle that continues it.
+pub(crate) fn import_cycles(
+    db: &Database,
+    file: FileId,
+) -> Vec<(ImportCycle, Vec<(FileRange, String)>)> {
+    let graph = db.graph(db.package(file));
+    let cycles = graph.import_cycles(&db.parse(file).module()).into_iter();
+    let cycles = cycles.map(|cycle| {
+        let steps = cycle.path.windows(2).skip(1);
+        let related = steps.filter_map(|step| {
+            let ModuleId::File(importer) = db.resolve_module(ModuleId::File(file), step[0])? else {
+                return None;
+            };
+            let header = db.parse(importer).module().header()?;
+            let import = header.imports().find(|import| {
+                import.module_name().is_some_and(|name| lower_module_name(&name) == step[1])
+            })?;
+            let range = FileRange { file: importer, range: text_range(import.syntax())? };
+            Some((range, format!("{} imports {}", step[0], step[1])))
+        });
+        let related = related.collect();
+        (cycle, related)
+    });
+    cycles.collect()
+}
+
+/// The items of the import lists of a file that the imported module doesn't
+/// export, at the item or at the constructor that isn't exported.
+pub(crate) fn import_diagnostics(
+    db: &Database,
+    file: FileId,
+) -> Vec<(TextRange, ImportDiagnostic)> {
+    let Some(header) = db.parse(file).module().header() else { return vec![] };
+    let imports: Vec<_> = header.imports().collect();
+    let mut located: Vec<(TextRange, ImportDiagnostic)> = vec![];
+    for diagnostic in db.imports(ModuleId::File(file)).diagnostics() {
+        let module = match diagnostic {
+            ImportDiagnostic::UnknownName { module, .. }
+            | ImportDiagnostic::UnknownConstructor { module, .. } => *module,
+        };
+        let imports = imports.iter().filter(|import| {
+            import.module_name().is_some_and(|name| lower_module_name(&name) == module)
+        });
+        let items = imports.filter_map(|import| import.import_list()).flat_map(|list| list.items());
+        // The same item can be written more than once, and each of them is
+        // reported.
+        let range = items
+            .filter_map(|item| import_item_range(&item, diagnostic))
+            .find(|range| !located.iter().any(|(located, _)| located == range));
+        if let Some(range) = range {
+            located.push((range, diagnostic.clone()));
+        }
+    }
+    located
+}
+
+/// The range of `item` that `diagnostic` is about, if it's about `item`.
+fn import_item_range(item: &ast::ImportItem, diagnostic: &ImportDiagnostic) -> Option<TextRange> {
+    let lowered = lower_import_item(item)?;
+    match diagnostic {
+        ImportDiagnostic::UnknownName { namespace, name, .. } => {
+            let is_item = import_item_name(&lowered) == (*namespace, *name);
+            if is_item {
+                text_range(item.syntax())
+            } else {
+                None
+            }
+        }
+        ImportDiagnostic::UnknownConstructor { type_, name, .. } => {
+            let ImportItem::Type(item_type, Some(DataMembers::Enumerated(names))) = lowered else {
+                return None;
+            };
+            if item_type != *type_ {
+                return None;
+            }
+            let position = names.iter().position(|constructor| constructor == name)?;
+            let ast::ImportItem::Type(type_item) = item else { return None };
+            let Some(ast::DataMembers::Enumerated(constructors)) = type_item.data_members() else {
+                return None;
+            };
+            text_range(constructors.constructors().nth(position)?.syntax())
+        }
+    }
+}
+
+fn import_item_name(item: &ImportItem) -> (Namespace, Name) {
+    match item {
+        ImportItem::Value(name) => (Namespace::Value, *name),
+        ImportItem::Operator(name) => (Namespace::Operator, *name),
+        ImportItem::Type(name, _) => (Namespace::Type, *name),
+        ImportItem::TypeOperator(name) => (Namespace::TypeOperator, *name),
+        ImportItem::Class(name) => (Namespace::Class, *name),
+    }
+}
+
+/// The imports and import items of a file that can be removed without
+/// changing what any of its names refer to.
+pub(crate) fn unused_imports(db: &Database, file: FileId) -> Vec<UnusedImport> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let lowered = db.lower(file);
+    let tree = db.item_tree(file);
+    // Only the modules of the workspace are known well enough to tell that
+    // nothing is used from them.
+    let exports: Vec<_> = tree
+        .imports
+        .iter()
+        .filter_map(|import| {
+            let module = db.resolve_module(ModuleId::File(file), import.module)?;
+            matches!(module, ModuleId::File(_)).then(|| (import.module, db.exports(module)))
+        })
+        .collect();
+    let root = db.parse(file).module();
+    check_unused_imports(&root, &lowered.module, &lowered.source_map, &scope, |name| {
+        let (_, exports) = exports.iter().find(|(imported, _)| *imported == name)?;
+        Some(&exports.interface)
+    })
+}
+
+/// The names of a file that don't resolve to exactly one definition, along
+/// with the range of the text that they're lowered from.
+pub(crate) fn resolution_diagnostics(
+    db: &Database,
+    file: FileId,
+) -> Vec<(TextRange, ResolutionDiagnostic)> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let parse = db.parse(file);
+    let lowered = db.lower(file);
+    let diagnostics = check_names(&lowered.module, &lowered.source_map, &scope).into_iter();
+    let diagnostics = diagnostics.map(|diagnostic| {
+        let node = diagnostic.ptr().to_node(&parse.root);
+        (text_range(&node).unwrap_or(node.text_range()), diagnostic)
+    });
+    diagnostics.collect()
+}
+
+/// The operator chains of a file with operators that can't be chained
+/// without parentheses.
+pub(crate) fn fixity_diagnostics(
+    db: &Database,
+    file: FileId,
+) -> Vec<(TextRange, FixityDiagnostic)> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let parse = db.parse(file);
+    let lowered = db.lower(file);
+    let trees = visible_trees(db, ModuleId::File(file));
+    let tree = |name| trees.iter().find(|(module, _)| *module == name).map(|(_, tree)| &**tree);
+    let fixities = Fixities::new(&scope, tree);
+    let diagnostics = check_fixities(&lowered.module, &lowered.source_map, &scope, &fixities);
+    let diagnostics = diagnostics.into_iter();
+    diagnostics.map(|diagnostic| (node_range(&parse.root, &diagnostic.ptr), diagnostic)).collect()
+}
+
+/// The members of the instances of a file that their class doesn't declare,
+/// and the members of the class that they don't define.
+pub(crate) fn instance_diagnostics(
+    db: &Database,
+    file: FileId,
+) -> Vec<(TextRange, InstanceDiagnostic)> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let parse = db.parse(file);
+    let trees = visible_trees(db, ModuleId::File(file));
+    let tree = |name| trees.iter().find(|(module, _)| *module == name).map(|(_, tree)| &**tree);
+    let diagnostics = check_instances(&parse.module(), &scope, tree).into_iter();
+    let diagnostics = diagnostics.map(|diagnostic| {
+        let node = diagnostic.ptr().to_node(&parse.root);
+        (text_range(&node).unwrap_or(node.text_range()), diagnostic)
+    });
+    diagnostics.collect()
+}
+
+/// The instances of a file that belong in the module of their class or of
+/// one of their types instead.
+pub(crate) fn orphan_warnings(db: &Database, file: FileId) -> Vec<OrphanWarning> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let Some(name) = db.item_tree(file).name else { return vec![] };
+    let trees = visible_trees(db, ModuleId::File(file));
+    let tree = |name| trees.iter().find(|(module, _)| *module == name).map(|(_, tree)| &**tree);
+    check_orphans(&db.parse(file).module(), name, &scope, tree)
+}
+
+/// The binders of a file that shadow another name, without those that
+/// `config` allows.
+pub(crate) fn shadowing_warnings(
+    db: &Database,
+    file: FileId,
+    config: &ShadowingConfig,
+) -> Vec<ShadowingWarning> {
+    let Some(scope) = db.scope(ModuleId::File(file)) else { return vec![] };
+    let lowered = db.lower(file);
+    let checks =
+        resolution::shadowing::ShadowingConfig { locals: config.locals, globals: config.globals };
+    let mut warnings = check_shadowing(&lowered.module, &lowered.source_map, &scope, checks);
+    warnings.retain(|warning| {
+        let name = match warning {
+            ShadowingWarning::Local { name, .. } | ShadowingWarning::Global { name, .. } => name,
+        };
+        !config.allowed.iter().any(|allowed| allowed == name.as_str())
+    });
+    warnings
+}
+
+/// The open imports of a file that aren't qualified, when there's more than
+/// one of them, such that it's unclear which of them a name comes from.
+pub(crate) fn wildcard_imports(db: &Database, file: FileId) -> Vec<ast::ImportDeclaration> {
+    let Some(header) = db.parse(file).module().header() else { return vec![] };
+    let open = header
+        .imports()
+        .filter(|import| import.import_list().is_none() && import.alias().is_none());
+    let open: Vec<_> = open.collect();
+    if open.len() > 1 {
+        open
+    } else {
+        vec![]
+    }
+}
+
+fn lowering_code(diagnostic: &LoweringDiagnostic) -> &'static str {
+    match diagnostic {
+        LoweringDiagnostic::InvalidDoFinalStatement { .. } => "invalid-do-final-statement",
+        LoweringDiagnostic::ArityMismatch { .. } => "arity-mismatch",
+        LoweringDiagnostic::IntegerOutOfRange { .. } => "integer-out-of-range",
+        LoweringDiagnostic::InvalidEscape { .. } => "invalid-escape",
+        LoweringDiagnostic::DuplicateDeclaration { .. } => "duplicate-declaration",
+        LoweringDiagnostic::DuplicateLabel { .. } => "duplicate-label",
+        LoweringDiagnostic::DuplicateTypeVariable { .. } => "duplicate-type-variable",
+    }
+}
+
+fn export_code(diagnostic: &ExportDiagnostic) -> &'static str {
+    match diagnostic {
+        ExportDiagnostic::UnknownName { .. } => "unknown-export",
+        ExportDiagnostic::UnknownConstructor { .. } => "unknown-constructor",
+        ExportDiagnostic::UnknownModule { .. } => "unknown-export-module",
+        ExportDiagnostic::Conflict { .. } => "conflicting-export",
+    }
+}
+
+fn import_code(diagnostic: &ImportDiagnostic) -> &'static str {
+    match diagnostic {
+        ImportDiagnostic::UnknownName { .. } => "unknown-import",
+        ImportDiagnostic::UnknownConstructor { .. } => "unknown-constructor",
+    }
+}
+
+fn instance_code(diagnostic: &InstanceDiagnostic) -> &'static str {
+    match diagnostic {
+        InstanceDiagnostic::UnknownMember { .. } => "unknown-member",
+        InstanceDiagnostic::MissingMembers { .. } => "missing-members",
+    }
+}
+
+fn resolution_code(diagnostic: &ResolutionDiagnostic) -> &'static str {
+    match diagnostic {
+        ResolutionDiagnostic::UnresolvedName { .. } => "unresolved-name",
+        ResolutionDiagnostic::AmbiguousName { .. } => "ambiguous-name",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{diagnostics, Severity, ShadowingConfig};
+
+    #[test]
+    fn report_syntax_errors() {
+        let mut db = Database::new();
+        let source = "module Main where\n\nx = \"unterminated\ny = (1 +\nz = 2\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range]))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [("lexer-error", "\"unterminated"), ("layout-error", "z"), ("layout-error", "z")]
+        );
+    }
+
+    #[test]
+    fn report_unknown_imports() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Lib where\n\ndata T = A | B\n\nx = 0\n");
+        let source =
+            "module Main where\n\nimport Lib (x, y, T(A, C), class Show)\nimport Lib.Missing\n\nmain = x A\n";
+        db.set_file_text(FileId(1), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(1), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range], diagnostic.message))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("unknown-import", "y", "module Lib does not export y".to_string()),
+                ("unknown-constructor", "C", "C is not a constructor of T".to_string()),
+                ("unknown-import", "class Show", "module Lib does not export Show".to_string()),
+                (
+                    "unknown-module",
+                    "Lib.Missing",
+                    "module Lib.Missing could not be found".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_unknown_exports() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module A where\n\nx = 0\n");
+        db.set_file_text(FileId(1), "module B where\n\nx = 0\n");
+        let source = "module Main (module A, module B, T(U), missing, module C) where\n\nimport A (x)\nimport B (x)\n\ndata T = T\n";
+        db.set_file_text(FileId(2), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(2), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range]))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("conflicting-export", "module B"),
+                ("unknown-constructor", "U"),
+                ("unknown-export", "missing"),
+                ("unknown-export-module", "module C"),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_unused_imports() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Lib where\n\nx = 0\ny = 0\n");
+        db.set_file_text(FileId(1), "module Other where\n\nz = 0\n");
+        let source = "module Main where\n\nimport Lib (x, y)\nimport Other\n\nmain = x\n";
+        db.set_file_text(FileId(2), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(2), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range], diagnostic.unnecessary))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [("unused-import", "y", true), ("unused-import", "import Other", true)]
+        );
+    }
+
+    #[test]
+    fn report_shadowing() {
+        let mut db = Database::new();
+        let source =
+            "module Main where\n\nx = 0\n\nf x = \\y -> case y of\n  y -> \\z -> \\z -> 0\n";
+        db.set_file_text(FileId(0), source);
+        let shadowing = |config| {
+            let diagnostics = diagnostics(&db, FileId(0), &config).into_iter();
+            let diagnostics = diagnostics.map(|diagnostic| {
+                let related: Vec<_> =
+                    diagnostic.related.iter().map(|(range, _)| &source[range.range]).collect();
+                (diagnostic.severity, &source[diagnostic.range], related)
+            });
+            diagnostics.collect::<Vec<_>>()
+        };
+        assert_eq!(shadowing(ShadowingConfig::default()), []);
+        let config = ShadowingConfig {
+            locals: true,
+            globals: true,
+            severity: Severity::Hint,
+            allowed: vec!["z".to_string()],
+        };
+        assert_eq!(
+            shadowing(config),
+            [(Severity::Hint, "x", vec!["x"]), (Severity::Hint, "y", vec!["y"])]
+        );
+    }
+
+    #[test]
+    fn report_operator_chains() {
+        let mut db = Database::new();
+        let source = "module Main where\n\ninfix 4 eq as ==\ninfixl 4 lt as <\n\neq a b = a\nlt a b = a\n\nx = 1 == 2 == 3\ny = 1 == 2 < 3\nz = (1 == 2) == 3\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range]))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [("operator-chain", "1 == 2 == 3"), ("operator-chain", "1 == 2 < 3")]
+        );
+    }
+
+    #[test]
+    fn report_import_cycles() {
+        let mut db = Database::new();
+        let source = "module A where\n\nimport B (b)\n\na = b\n";
+        db.set_file_text(FileId(0), source);
+        let b = "module B where\n\nimport C\n\nb = 0\n";
+        db.set_file_text(FileId(1), b);
+        db.set_file_text(FileId(2), "module C where\n\nimport A\n");
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| {
+                let related: Vec<_> = diagnostic
+                    .related
+                    .iter()
+                    .map(|(range, message)| (range.file, message.clone()))
+                    .collect();
+                (diagnostic.code, &source[diagnostic.range], diagnostic.message, related)
+            })
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [(
+                "import-cycle",
+                "import B (b)",
+                "this import forms a cycle: A -> B -> C -> A".to_string(),
+                vec![
+                    (FileId(1), "B imports C".to_string()),
+                    (FileId(2), "C imports A".to_string())
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn report_kind_errors() {
+        let mut db = Database::new();
+        let source =
+            "module Main where\n\ndata Maybe a = Nothing | Just a\n\nbad :: Maybe\nbad = bad\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (&source[diagnostic.range], diagnostic.message))
+            .collect();
+        let message =
+            "expected  Type\n          ^^^^\nbut found Type -> Type\n          ^^^^^^^^^^^^";
+        assert_eq!(diagnostics, [("Maybe", message.to_string())]);
+    }
+
+    #[test]
+    fn underline_mismatched_types() {
+        let mut db = Database::new();
+        let source = "module Main where\n\nwrong :: Int\nwrong = []\n\nrecord :: { age :: Int } -> Int\nrecord r = r.age\n\ncalled = record { age: 1, name: \"\" }\n";
+        db.set_file_text(FileId(0), source);
+        let messages: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "expected  Int\n          ^^^\nbut found Array t0\n          ^^^^^^^^",
+                "expected  { age :: Int }\nbut found { age :: Int, name :: String }\n                        ^^^^^^^^^^^^^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_uncovered_instances() {
+        let mut db = Database::new();
+        let source =
+            "module Main where\n\nclass Convert a b | a -> b\n\ninstance Convert (Array a) b\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range], diagnostic.message))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [(
+                "uncovered-variables",
+                "b",
+                "b isn't determined by the functional dependencies of Convert".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn report_pattern_matches() {
+        let mut db = Database::new();
+        let source = "module Main where\n\ndata T = A | B\n\nf t = case t of\n  A -> 0\n\ng t = case t of\n  _ -> 0\n  B -> 1\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.message, diagnostic.unnecessary))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("missing-patterns", "patterns not matched: B".to_string(), false),
+                ("redundant-pattern", "this branch is unreachable".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_underivable_instances() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Data.Show where\n\nclass Show a\n");
+        let source = "module Main where\n\nimport Data.Show (class Show)\n\ndata T = T\n\nderive instance Show T\n";
+        db.set_file_text(FileId(1), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(1), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range]))
+            .collect();
+        assert_eq!(diagnostics, [("not-derivable", "T")]);
+    }
+
+    #[test]
+    fn report_orphan_instances() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Data.Show where\n\nclass Show a\n");
+        db.set_file_text(FileId(1), "module Data.Maybe where\n\ndata Maybe a = Nothing | Just a\n");
+        let source = "module Main where\n\nimport Data.Maybe (Maybe)\nimport Data.Show (class Show)\n\ndata T = T\n\ninstance Show T\ninstance Show (Maybe Int)\n";
+        db.set_file_text(FileId(2), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(2), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range], diagnostic.message))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [(
+                "orphan-instance",
+                "Show (Maybe Int)",
+                "orphan instance of Show, which can be declared in Data.Show or Data.Maybe instead, or on a newtype wrapper".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn report_lowering_errors() {
+        let mut db = Database::new();
+        let source = "module Main where\n\nx = 1\ny = 2147483648\nx = 2\n";
+        db.set_file_text(FileId(0), source);
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| {
+                let related: Vec<_> =
+                    diagnostic.related.iter().map(|(range, _)| &source[range.range]).collect();
+                (diagnostic.code, &source[diagnostic.range], related)
+            })
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("integer-out-of-range", "2147483648", vec![]),
+                ("duplicate-declaration", "x", vec!["x"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_arity_mismatches() {
+        let mut db = Database::new();
+        let source = "module Main where\n\ng :: Int -> Int -> Int\ng x y = x\ng x = x\n\nh :: Int -> Int\nh x = x\nh = 0\n";
+        db.set_file_text(FileId(0), source);
+        // The mismatched equations aren't checked, so they're the only errors.
+        let diagnostics: Vec<_> = diagnostics(&db, FileId(0), &ShadowingConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.range], diagnostic.message))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    "arity-mismatch",
+                    "g x = x",
+                    "equation has 1 argument, but the first equation has 2".to_string()
+                ),
+                (
+                    "arity-mismatch",
+                    "h = 0",
+                    "equation has 0 arguments, but the first equation has 1".to_string()
+                ),
+            ]
+        );
+    }
+}
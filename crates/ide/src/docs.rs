@@ -0,0 +1,94 @@
+//! The doc comments attached to declarations.
+//!
+//! A doc comment is a run of comments before a declaration, constructor, or
+//! class member that starts with `-- |` or `{-|`. Comments within the run
+//! before the one that starts it aren't part of the documentation.
+
+use analysis::{Database, FileId, ModuleId};
+use resolution::interface::Definition;
+use rowan::{ast::AstNode, Direction};
+use syntax::{SyntaxKind, SyntaxNode};
+
+use crate::target::definition_node;
+
+/// The documentation of a definition, which is only known for modules that
+/// were loaded from source. Values are documented on their signature if
+/// they have one.
+pub(crate) fn definition_docs(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+) -> Option<String> {
+    let (_, node) = definition_node(db, module, definition)?;
+    docs(&node)
+}
+
+/// The documentation of a module, written before its header.
+pub(crate) fn module_docs(db: &Database, file: FileId) -> Option<String> {
+    let header = db.parse(file).module().header()?;
+    docs(header.syntax())
+}
+
+fn docs(node: &SyntaxNode) -> Option<String> {
+    let mut comments = vec![];
+    for element in node.siblings_with_tokens(Direction::Prev).skip(1) {
+        match element.kind() {
+            SyntaxKind::LineComment | SyntaxKind::BlockComment => {
+                comments.push(element.into_token()?.text().to_string())
+            }
+            // Constructors are documented before the `=` or `|` that
+            // separates them.
+            SyntaxKind::Whitespace | SyntaxKind::Equal | SyntaxKind::Pipe => {}
+            _ => break,
+        }
+    }
+    comments.reverse();
+    let start = comments
+        .iter()
+        .position(|comment| comment.starts_with("-- |") || comment.starts_with("{-|"))?;
+    let lines: Vec<_> =
+        comments[start..].iter().flat_map(|comment| comment_lines(comment)).collect();
+    // Leading spaces are kept, since they can start an indented code block.
+    let docs = code_blocks(lines).join("\n");
+    Some(docs.trim_start_matches('\n').trim_end().to_string())
+}
+
+/// Marks the fenced code blocks of `lines` that don't name a language as
+/// PureScript, so that they're highlighted the way signatures are.
+fn code_blocks(mut lines: Vec<String>) -> Vec<String> {
+    let mut is_open = false;
+    for line in &mut lines {
+        let fence = line.trim_start();
+        if !fence.starts_with("```") {
+            continue;
+        }
+        if !is_open && fence == "```" {
+            line.push_str("purescript");
+        }
+        is_open = !is_open;
+    }
+    lines
+}
+
+/// The lines of a comment without its delimiters, or the `|` that marks it
+/// as documentation.
+fn comment_lines(comment: &str) -> Vec<String> {
+    if let Some(line) = comment.strip_prefix("--") {
+        let line = line.strip_prefix(" |").or_else(|| line.strip_prefix('|')).unwrap_or(line);
+        return vec![line.strip_prefix(' ').unwrap_or(line).to_string()];
+    }
+    let text = comment.trim_start_matches("{-").trim_end_matches("-}");
+    let text = text.strip_prefix('|').unwrap_or(text);
+    let lines: Vec<_> = text.lines().collect();
+    // Lines after the first are indented to line up with the comment.
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let first = lines.first().map(|line| line.trim().to_string());
+    let rest = lines.iter().skip(1).map(|line| line.get(indent..).unwrap_or("").to_string());
+    first.into_iter().chain(rest).collect()
+}
@@ -0,0 +1,89 @@
+//! The uses of the name under the cursor within its file.
+
+use analysis::{Database, FileId};
+use resolution::reference::Target;
+use rowan::{TextRange, TextSize};
+
+use crate::{
+    references::{is_declaration, references_in},
+    target::reference_at,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    /// A use of the name.
+    Read,
+    /// A declaration of the name, or a binder that introduces it.
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentHighlight {
+    pub range: TextRange,
+    pub kind: HighlightKind,
+}
+
+/// The names of `file` that refer to the same thing as the name at
+/// `offset`, which unlike references doesn't search the files that depend
+/// on the file.
+pub fn document_highlights(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+) -> Vec<DocumentHighlight> {
+    let Some(reference) = reference_at(db, file, offset) else { return vec![] };
+    if !matches!(reference.target, Target::Local { .. } | Target::Global(_)) {
+        return vec![];
+    }
+    let ranges = references_in(db, file, &reference, file).into_iter();
+    let highlights = ranges.map(|range| {
+        let kind = if is_declaration(db, file, range.start()) {
+            HighlightKind::Write
+        } else {
+            HighlightKind::Read
+        };
+        DocumentHighlight { range, kind }
+    });
+    highlights.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::{document_highlights, HighlightKind};
+
+    #[test]
+    fn highlight_reads_and_writes() {
+        let mut db = Database::new();
+        let main = "module Main (f) where\n\nf :: Int -> Int\nf x = let y = x in y + x\n";
+        db.set_file_text(FileId(0), main);
+
+        let highlights_at = |text: &str| {
+            let offset = TextSize::from(main.find(text).unwrap() as u32);
+            let highlights = document_highlights(&db, FileId(0), offset);
+            let highlights = highlights
+                .iter()
+                .map(|highlight| (u32::from(highlight.range.start()) as usize, highlight.kind));
+            highlights.collect::<Vec<_>>()
+        };
+        let offset = |text: &str| main.find(text).unwrap();
+        assert_eq!(
+            highlights_at("f ::"),
+            [
+                (offset("f)"), HighlightKind::Read),
+                (offset("f ::"), HighlightKind::Write),
+                (offset("f x"), HighlightKind::Write),
+            ]
+        );
+        assert_eq!(
+            highlights_at("x in"),
+            [
+                (offset("x ="), HighlightKind::Write),
+                (offset("x in"), HighlightKind::Read),
+                (main.rfind('x').unwrap(), HighlightKind::Read),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Links from the imports of a file to the files of the modules they import.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::lower_module_name;
+use rowan::{ast::AstNode, TextRange, TextSize};
+
+use crate::folding_ranges::text_range;
+
+/// The module name of each import of `file`. Resolving the module a name
+/// links to is left to [`document_link_target`], so that the links of a
+/// file can be listed without finding every module it imports.
+pub fn document_links(db: &Database, file: FileId) -> Vec<TextRange> {
+    let Some(header) = db.parse(file).module().header() else { return vec![] };
+    let names = header.imports().filter_map(|import| text_range(import.module_name()?.syntax()));
+    names.collect()
+}
+
+/// The file of the module imported by the name at `offset`, whether it's
+/// in the workspace or one of its dependencies. Modules without a source,
+/// such as `Prim`, don't have one.
+pub fn document_link_target(db: &Database, file: FileId, offset: TextSize) -> Option<FileId> {
+    let header = db.parse(file).module().header()?;
+    let name = header.imports().filter_map(|import| import.module_name()).find(|name| {
+        text_range(name.syntax()).is_some_and(|range| range.contains_inclusive(offset))
+    })?;
+    match db.resolve_module(ModuleId::File(file), lower_module_name(&name))? {
+        ModuleId::File(target) => Some(target),
+        ModuleId::Compiled(_) | ModuleId::Prim(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{document_link_target, document_links};
+
+    #[test]
+    fn link_imported_modules() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n";
+        let main = "module Main where\n\nimport Prim.Row (class Cons)\nimport Data.Maybe (Maybe)\nimport Data.Missing as M\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let links = document_links(&db, FileId(1));
+        let names: Vec<_> = links.iter().map(|&range| &main[range]).collect();
+        assert_eq!(names, ["Prim.Row", "Data.Maybe", "Data.Missing"]);
+        let targets: Vec<_> =
+            links.iter().map(|range| document_link_target(&db, FileId(1), range.start())).collect();
+        assert_eq!(targets, [None, Some(FileId(0)), None]);
+    }
+}
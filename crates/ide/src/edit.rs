@@ -0,0 +1,60 @@
+//! Changes to the text of a file.
+
+use analysis::FileId;
+use rowan::{TextRange, TextSize};
+
+/// Replaces `range` with `new_text`, which inserts it if `range` is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: TextRange,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn insert(offset: TextSize, new_text: impl Into<String>) -> TextEdit {
+        TextEdit { range: TextRange::empty(offset), new_text: new_text.into() }
+    }
+
+    pub fn replace(range: TextRange, new_text: impl Into<String>) -> TextEdit {
+        TextEdit { range, new_text: new_text.into() }
+    }
+}
+
+/// Edits to any number of files, grouped by file in the order that they
+/// were first edited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceEdit {
+    pub files: Vec<(FileId, Vec<TextEdit>)>,
+}
+
+impl WorkspaceEdit {
+    pub fn push(&mut self, file: FileId, edit: TextEdit) {
+        match self.files.iter_mut().find(|(edited, _)| *edited == file) {
+            Some((_, edits)) => edits.push(edit),
+            None => self.files.push((file, vec![edit])),
+        }
+    }
+}
+
+/// Applies edits that don't overlap to `text`, in any order.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<_> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.range.start(), edit.range.end()));
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for edit in edits {
+        result.push_str(&text[last..usize::from(edit.range.start())]);
+        result.push_str(&edit.new_text);
+        last = usize::from(edit.range.end());
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// The whitespace that lines a new line up with `offset`, which keeps the
+/// tabs of the line that `offset` is on and spaces over everything else.
+pub(crate) fn indentation(text: &str, offset: TextSize) -> String {
+    let before = &text[..usize::from(offset)];
+    let line = &before[before.rfind('\n').map_or(0, |newline| newline + 1)..];
+    line.chars().map(|char| if char == '\t' { '\t' } else { ' ' }).collect()
+}
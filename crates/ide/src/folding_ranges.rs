@@ -0,0 +1,145 @@
+//! The ranges of a file that can be folded away.
+
+use analysis::{Database, FileId};
+use rowan::{ast::AstNode, TextRange};
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldingKind {
+    Imports,
+    Comment,
+    /// The lines between a `-- #region` and an `-- #endregion`.
+    Region,
+    Code,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldingRange {
+    pub range: TextRange,
+    pub kind: FoldingKind,
+}
+
+/// The ranges that span more than one line, in the order that they start:
+/// the imports of the module, declarations, the blocks that layout opens,
+/// comments, and regions. Ranges end at the last token that isn't trivia,
+/// since nodes are followed by the whitespace before the next one.
+pub fn folding_ranges(db: &Database, file: FileId) -> Vec<FoldingRange> {
+    let parse = db.parse(file);
+    let mut ranges = vec![];
+    let mut push = |range: Option<TextRange>, kind| {
+        let Some(range) = range else { return };
+        if parse.root.text().slice(range).contains_char('\n') {
+            ranges.push(FoldingRange { range, kind });
+        }
+    };
+
+    let imports: Vec<_> =
+        parse.module().header().into_iter().flat_map(|header| header.imports()).collect();
+    if let (Some(first), Some(last)) = (imports.first(), imports.last()) {
+        let range = text_range(first.syntax()).zip(text_range(last.syntax()));
+        push(range.map(|(first, last)| first.cover(last)), FoldingKind::Imports);
+    }
+    for node in parse.root.descendants() {
+        if is_folded(node.kind()) {
+            push(text_range(&node), FoldingKind::Code);
+        }
+    }
+
+    let mut comments: Option<TextRange> = None;
+    let mut regions = vec![];
+    let tokens = parse.root.descendants_with_tokens().filter_map(|element| element.into_token());
+    for token in tokens {
+        let range = token.text_range();
+        match token.kind() {
+            SyntaxKind::LineComment => match region_marker(&token) {
+                Some(true) => regions.push(range.start()),
+                Some(false) => {
+                    let start = regions.pop();
+                    push(
+                        start.map(|start| TextRange::new(start, range.end())),
+                        FoldingKind::Region,
+                    );
+                }
+                // Consecutive line comments fold together.
+                None => comments = Some(comments.map_or(range, |comments| comments.cover(range))),
+            },
+            SyntaxKind::BlockComment => push(Some(range), FoldingKind::Comment),
+            SyntaxKind::Whitespace if token.text().matches('\n').count() <= 1 => {}
+            _ => push(comments.take(), FoldingKind::Comment),
+        }
+    }
+    push(comments, FoldingKind::Comment);
+    ranges.sort_by_key(|range| (range.range.start(), std::cmp::Reverse(range.range.end())));
+    ranges
+}
+
+fn is_folded(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::ValueDeclaration
+            | SyntaxKind::DataDeclaration
+            | SyntaxKind::NewtypeDeclaration
+            | SyntaxKind::TypeDeclaration
+            | SyntaxKind::ClassDeclaration
+            | SyntaxKind::InstanceChain
+            | SyntaxKind::WhereClause
+            | SyntaxKind::ExpressionDo
+            | SyntaxKind::ExpressionAdo
+            | SyntaxKind::ExpressionLetIn
+            | SyntaxKind::ExpressionCase
+            | SyntaxKind::DoLet
+    )
+}
+
+/// Whether a line comment opens a region, or closes one.
+fn region_marker(token: &SyntaxToken) -> Option<bool> {
+    let marker = token.text().trim_start_matches('-').trim_start();
+    if marker.starts_with("#region") {
+        Some(true)
+    } else if marker.starts_with("#endregion") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// The range from the first token of a node that isn't trivia to the last.
+pub(crate) fn text_range(node: &SyntaxNode) -> Option<TextRange> {
+    let mut tokens = node
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_trivia());
+    let first = tokens.next()?.text_range();
+    let end = tokens.last().map_or(first.end(), |token| token.text_range().end());
+    Some(TextRange::new(first.start(), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{folding_ranges, FoldingKind};
+
+    #[test]
+    fn fold_blocks() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nimport A\nimport B\n\n-- #region Values\n\n-- | Adds one\n-- | to a number.\nf x = y\n  where\n  y = x\n\n{- A\n   block -}\ng = do\n  a <- b\n  c\n\n-- #endregion\n";
+        db.set_file_text(FileId(0), main);
+
+        let ranges = folding_ranges(&db, FileId(0));
+        let ranges: Vec<_> = ranges.iter().map(|range| (&main[range.range], range.kind)).collect();
+        assert_eq!(
+            ranges,
+            [
+                ("import A\nimport B", FoldingKind::Imports),
+                (&main[main.find("-- #region").unwrap()..main.len() - 1], FoldingKind::Region),
+                ("-- | Adds one\n-- | to a number.", FoldingKind::Comment),
+                ("f x = y\n  where\n  y = x", FoldingKind::Code),
+                ("where\n  y = x", FoldingKind::Code),
+                ("{- A\n   block -}", FoldingKind::Comment),
+                ("g = do\n  a <- b\n  c", FoldingKind::Code),
+                ("do\n  a <- b\n  c", FoldingKind::Code),
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Formatting a file, and turning formatted text into edits of the text it
+//! was formatted from.
+
+use analysis::{Database, FileId};
+use rowan::{TextRange, TextSize};
+use syntax::SyntaxKind;
+
+use crate::edit::TextEdit;
+
+/// Formats `file` by normalizing its whitespace: trailing whitespace is
+/// removed, runs of blank lines are collapsed into one, and the file ends
+/// with a single newline. Every other token stays where it is, so the
+/// layout of the file is kept as written. Files with syntax errors aren't
+/// formatted, since it can't be told what they'd be laid out as.
+pub fn format(db: &Database, file: FileId) -> Option<String> {
+    let parse = db.parse(file);
+    if !parse.errors.is_empty() {
+        return None;
+    }
+    let mut formatted = String::new();
+    let tokens = parse.root.descendants_with_tokens().filter_map(|element| element.into_token());
+    for token in tokens {
+        match token.kind() {
+            SyntaxKind::Whitespace => {
+                let newlines = token.text().matches('\n').count();
+                if newlines == 0 {
+                    formatted.push_str(token.text());
+                    continue;
+                }
+                let indentation = &token.text()[token.text().rfind('\n').unwrap() + 1..];
+                trim_line_end(&mut formatted);
+                formatted.push_str(&"\n".repeat(newlines.min(2)));
+                formatted.push_str(indentation);
+            }
+            SyntaxKind::LineComment => formatted.push_str(token.text().trim_end()),
+            _ => formatted.push_str(token.text()),
+        }
+    }
+    let formatted = formatted.trim_start_matches('\n').trim_end();
+    Some(format!("{formatted}\n"))
+}
+
+/// Removes the spaces at the end of the last line of `text`.
+fn trim_line_end(text: &mut String) {
+    let trimmed = text.trim_end_matches([' ', '\t']).len();
+    text.truncate(trimmed);
+}
+
+/// The edits that turn `before` into `after`, one for each run of lines
+/// that differ, which keeps the cursor and marks of the editor on the lines
+/// that stay the same.
+pub fn text_edits(before: &str, after: &str) -> Vec<TextEdit> {
+    let old: Vec<_> = before.split_inclusive('\n').collect();
+    let new: Vec<_> = after.split_inclusive('\n').collect();
+    let prefix = old.iter().zip(&new).take_while(|(old, new)| old == new).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_lines = &old[prefix..old.len() - suffix];
+    let new_lines = &new[prefix..new.len() - suffix];
+
+    let mut starts = vec![TextSize::of(old[..prefix].concat().as_str())];
+    for line in old_lines {
+        starts.push(*starts.last().unwrap() + TextSize::of(*line));
+    }
+    // Lines are matched up where there aren't too many of them to compare,
+    // and the changed lines are replaced as a whole otherwise.
+    let matched = if old_lines.len() * new_lines.len() <= 1 << 20 {
+        common_lines(old_lines, new_lines)
+    } else {
+        vec![]
+    };
+    let mut edits = vec![];
+    let (mut old_line, mut new_line) = (0, 0);
+    for (old_match, new_match) in matched.into_iter().chain([(old_lines.len(), new_lines.len())]) {
+        if old_match > old_line || new_match > new_line {
+            let range = TextRange::new(starts[old_line], starts[old_match]);
+            edits.push(TextEdit::replace(range, new_lines[new_line..new_match].concat()));
+        }
+        (old_line, new_line) = (old_match + 1, new_match + 1);
+    }
+    edits
+}
+
+/// The longest sequence of lines that `old` and `new` have in common, as
+/// the index of each line in both.
+fn common_lines(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    // `lengths[i][j]` is the length of the longest common sequence of the
+    // lines from `i` and `j` on.
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut common = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            common.push((i, j));
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
+}
+
+/// The edits that touch `range`, for formatting only part of a file. An
+/// edit is kept whole, so it can reach past the range to the edge of the
+/// lines it changes.
+pub fn edits_in_range(edits: Vec<TextEdit>, range: TextRange) -> Vec<TextEdit> {
+    edits.into_iter().filter(|edit| edit.range.intersect(range).is_some()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::{TextRange, TextSize};
+
+    use super::{edits_in_range, format, text_edits};
+    use crate::edit::apply_edits;
+
+    #[test]
+    fn format_whitespace() {
+        let mut db = Database::new();
+        let main = "\nmodule Main where  \n\n\n\nx = 1 -- one  \n\ny = \"\"\"a  \nb\"\"\"\n  where\n\n\n  z = 2\n\n";
+        db.set_file_text(FileId(0), main);
+        let formatted = format(&db, FileId(0)).unwrap();
+        assert_eq!(
+            formatted,
+            "module Main where\n\nx = 1 -- one\n\ny = \"\"\"a  \nb\"\"\"\n  where\n\n  z = 2\n"
+        );
+
+        let edits = text_edits(main, &formatted);
+        assert_eq!(apply_edits(main, &edits), formatted);
+        // The lines of `y` are the same, so they're left alone.
+        assert_eq!(edits.len(), 4);
+        let start = TextSize::from(main.find("where\n\n\n").unwrap() as u32);
+        let edits = edits_in_range(edits, TextRange::at(start, TextSize::from(8)));
+        assert_eq!(edits.len(), 1);
+
+        db.set_file_text(FileId(0), "module Main where\n\nx = (\n");
+        assert_eq!(format(&db, FileId(0)), None);
+    }
+}
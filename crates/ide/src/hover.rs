@@ -0,0 +1,291 @@
+//! What the name under the cursor is, rendered as markdown.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    hir::{Associativity, Binder, BodyId, Constructor, Declaration, Type, TypeVariable},
+    item_tree::{Item, ItemTree},
+    name::{ModuleName, Name},
+    pretty::print_type,
+};
+use resolution::{
+    interface::{Definition, Interface, Namespace},
+    reference::{ReferenceKind, Target},
+};
+use rowan::{TextRange, TextSize};
+
+use crate::{
+    docs::{definition_docs, module_docs},
+    target::{definition_module, reference_at},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hover {
+    /// The range of the name that was hovered.
+    pub range: TextRange,
+    pub markdown: String,
+}
+
+/// Shows the signature of what the name at `offset` refers to, the module
+/// that defines it, and its documentation. Values without a signature show
+/// their inferred type instead.
+pub fn hover(db: &Database, file: FileId, offset: TextSize) -> Option<Hover> {
+    let reference = reference_at(db, file, offset)?;
+    let markdown = match reference.target {
+        Target::Local { body, binder } => {
+            let lowered = db.lower(file);
+            let name = match lowered.module.bodies[body].binders[binder] {
+                Binder::Variable(name) | Binder::Named { name, .. } => name,
+                _ => return None,
+            };
+            let types = db.infer(file);
+            let types = types.get(body)?;
+            let ty = types.binder(binder)?;
+            code(&format!("{name} :: {}", types.types.display(ty)))
+        }
+        Target::Global(definition) => {
+            let module = definition_module(db, file, definition)?;
+            definition_hover(db, module, definition)?
+        }
+        Target::Ambiguous(definitions) => {
+            let hovers = definitions.into_iter().filter_map(|definition| {
+                definition_hover(db, definition_module(db, file, definition)?, definition)
+            });
+            hovers.collect::<Vec<_>>().join("\n\n---\n\n")
+        }
+        Target::Prim(name) => {
+            let ReferenceKind::Name(namespace) = reference.kind else { return None };
+            prim_hover(db, namespace, name)?
+        }
+        Target::Modules(modules) => {
+            let hovers = modules.into_iter().map(|name| {
+                let mut markdown = code(&format!("module {name}"));
+                let file = db.resolve_module(ModuleId::File(file), name);
+                if let Some(docs) = file.and_then(|file| match file {
+                    ModuleId::File(file) => module_docs(db, file),
+                    ModuleId::Compiled(_) | ModuleId::Prim(_) => None,
+                }) {
+                    markdown.push_str("\n\n---\n\n");
+                    markdown.push_str(&docs);
+                }
+                markdown
+            });
+            hovers.collect::<Vec<_>>().join("\n\n---\n\n")
+        }
+        // Labels aren't resolved to the records they belong to.
+        Target::Label(_) => return None,
+    };
+    Some(Hover { range: reference.range, markdown })
+}
+
+fn definition_hover(db: &Database, module: ModuleId, definition: Definition) -> Option<String> {
+    let tree = db.module_tree(module)?;
+    let signature = signature(db, module, &tree, definition)?;
+    let mut markdown = format!("{}\n\n*{}*", code(&signature), definition.module);
+    if let Some(docs) = definition_docs(db, module, definition) {
+        markdown.push_str("\n\n---\n\n");
+        markdown.push_str(&docs);
+    }
+    Some(markdown)
+}
+
+/// Types and classes built into the compiler are declared by the `Prim`
+/// module, without any syntax.
+fn prim_hover(db: &Database, namespace: Namespace, name: Name) -> Option<String> {
+    let module = ModuleId::Prim(ModuleName::new("Prim"));
+    let tree = db.module_tree(module)?;
+    let definition = *Interface::local(&tree).names.get(namespace).get(&name)?;
+    definition_hover(db, module, definition)
+}
+
+fn code(text: &str) -> String {
+    format!("```purescript\n{text}\n```")
+}
+
+/// The declaration of a definition as it would be written, without its
+/// body.
+fn signature(
+    db: &Database,
+    module: ModuleId,
+    tree: &ItemTree,
+    definition: Definition,
+) -> Option<String> {
+    let print = |id| print_type(&tree.types, id);
+    let signature = match (&tree.items[definition.item], definition.member) {
+        (Item::Value(item), _) => match definition_type(db, module, tree, definition) {
+            Some(type_) => format!("{} :: {type_}", item.name),
+            None => item.name.to_string(),
+        },
+        (Item::Data(item), None) => header(tree, "data", item.name, &item.variables),
+        (Item::Data(item), Some(index)) => {
+            let name = item.constructors.get(index)?.name;
+            format!("{name} :: {}", definition_type(db, module, tree, definition)?)
+        }
+        (Item::Newtype(item), None) => header(tree, "newtype", item.name, &item.variables),
+        (Item::Newtype(item), Some(_)) => {
+            let name = item.constructor.name;
+            format!("{name} :: {}", definition_type(db, module, tree, definition)?)
+        }
+        (Item::Synonym(item), _) => {
+            format!("{} = {}", header(tree, "type", item.name, &item.variables), print(item.type_))
+        }
+        (Item::Class(item), None) => {
+            let constraints: Vec<_> = item.constraints.iter().map(|&id| print(id)).collect();
+            let head = header(tree, "class", item.name, &item.variables);
+            match constraints.len() {
+                0 => head,
+                _ => format!("class ({}) <= {}", constraints.join(", "), &head["class ".len()..]),
+            }
+        }
+        (Item::Class(item), Some(index)) => {
+            let member = item.members.get(index)?;
+            format!("{} :: {}", member.name, definition_type(db, module, tree, definition)?)
+        }
+        (Item::ForeignData(item), _) => {
+            format!("foreign import data {} :: {}", item.name, print(item.kind))
+        }
+        (Item::ForeignValue(item), _) => {
+            format!("foreign import {} :: {}", item.name, print(item.type_))
+        }
+        (Item::Fixity(item), _) => {
+            let associativity = match item.associativity {
+                Associativity::Left => "infixl",
+                Associativity::Right => "infixr",
+                Associativity::None => "infix",
+            };
+            let type_ = if item.is_type { "type " } else { "" };
+            format!(
+                "{associativity} {} {type_}{} as {}",
+                item.precedence, item.target, item.operator
+            )
+        }
+        (Item::Instance(_), _) => return None,
+    };
+    Some(signature)
+}
+
+/// The type of a value, constructor, or class member, which is inferred
+/// for values without a signature.
+pub(crate) fn definition_type(
+    db: &Database,
+    module: ModuleId,
+    tree: &ItemTree,
+    definition: Definition,
+) -> Option<String> {
+    let print = |id| print_type(&tree.types, id);
+    match (&tree.items[definition.item], definition.member) {
+        (Item::Value(item), _) => match item.signature {
+            Some(type_) => Some(print(type_)),
+            None => inferred_type(db, module, item.name),
+        },
+        (Item::Data(item), Some(index)) => {
+            Some(constructor(tree, item.name, &item.variables, item.constructors.get(index)?))
+        }
+        (Item::Newtype(item), Some(_)) => {
+            Some(constructor(tree, item.name, &item.variables, &item.constructor))
+        }
+        (Item::Class(item), Some(index)) => Some(print(item.members.get(index)?.type_)),
+        (Item::ForeignValue(item), _) => Some(print(item.type_)),
+        _ => None,
+    }
+}
+
+/// The type inferred for a value without a signature.
+fn inferred_type(db: &Database, module: ModuleId, name: Name) -> Option<String> {
+    let ModuleId::File(file) = module else { return None };
+    let body = value_body(db, file, name)?;
+    let types = db.infer(file);
+    let types = types.get(body)?;
+    let type_ = types.types.display(types.ty?).to_string();
+    Some(type_)
+}
+
+/// The body of the value named `name` in `file`, which combines all of its
+/// equations.
+pub(crate) fn value_body(db: &Database, file: FileId, name: Name) -> Option<BodyId> {
+    let lowered = db.lower(file);
+    let body = lowered.module.declarations.iter().find_map(|(_, declaration)| match declaration {
+        Declaration::Value(value) if value.name == name => Some(value.body),
+        _ => None,
+    });
+    body
+}
+
+fn header(tree: &ItemTree, keyword: &str, name: Name, variables: &[TypeVariable]) -> String {
+    let mut header = format!("{keyword} {name}");
+    for variable in variables {
+        match variable.kind {
+            Some(kind) => header.push_str(&format!(
+                " ({} :: {})",
+                variable.name,
+                print_type(&tree.types, kind)
+            )),
+            None => header.push_str(&format!(" {}", variable.name)),
+        }
+    }
+    header
+}
+
+/// The type of a constructor, as a function from its fields to the type it
+/// belongs to.
+fn constructor(
+    tree: &ItemTree,
+    type_name: Name,
+    variables: &[TypeVariable],
+    constructor: &Constructor,
+) -> String {
+    let names: Vec<_> = variables.iter().map(|variable| variable.name.to_string()).collect();
+    let result = [type_name.to_string()].into_iter().chain(names.iter().cloned());
+    let fields = constructor.fields.iter().map(|&field| {
+        let printed = print_type(&tree.types, field);
+        match tree.types[field] {
+            Type::Forall { .. } | Type::Constrained { .. } | Type::Arrow { .. } => {
+                format!("({printed})")
+            }
+            _ => printed,
+        }
+    });
+    let type_: Vec<_> = fields.chain([result.collect::<Vec<_>>().join(" ")]).collect();
+    let forall = match names.len() {
+        0 => String::new(),
+        _ => format!("forall {}. ", names.join(" ")),
+    };
+    format!("{forall}{}", type_.join(" -> "))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::hover;
+
+    #[test]
+    fn hover_names() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\n-- | An optional value.\n-- | Either `Just` or `Nothing`.\ndata Maybe a\n  -- | A value.\n  = Just a\n  | Nothing\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\n\n-- | The answer.\n-- |\n-- | ```\n-- | answer == 42\n-- | ```\n-- |\n-- |     answer > 0\nanswer :: Int\nanswer = 42\n\nwrap x = M.Just x\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let hover_at = |text: &str| {
+            let offset = TextSize::from(main.find(text).unwrap() as u32);
+            hover(&db, FileId(1), offset).map(|hover| hover.markdown)
+        };
+        assert_eq!(
+            hover_at("Just x").unwrap(),
+            "```purescript\nJust :: forall a. a -> Maybe a\n```\n\n*Data.Maybe*\n\n---\n\nA value."
+        );
+        assert_eq!(
+            hover_at("answer ::").unwrap(),
+            "```purescript\nanswer :: Int\n```\n\n*Main*\n\n---\n\nThe answer.\n\n```purescript\nanswer == 42\n```\n\n    answer > 0"
+        );
+        assert_eq!(hover_at("M.Just").unwrap(), "```purescript\nmodule Data.Maybe\n```");
+        // Values without a signature and locals show their inferred types.
+        assert_eq!(
+            hover_at("wrap").unwrap(),
+            "```purescript\nwrap :: forall t0. t0 -> Maybe t0\n```\n\n*Main*"
+        );
+        assert!(hover_at("x = ").unwrap().starts_with("```purescript\nx :: "));
+        assert_eq!(hover_at("= M"), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! The instances that implement the class under the cursor.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{item_tree::Item, lower_name, lower_qualified_name};
+use resolution::{
+    body::Resolution,
+    interface::{Definition, Namespace},
+    reference::Target,
+};
+use rowan::{ast::AstNode, TextSize};
+use syntax::ast;
+
+use crate::{
+    folding_ranges::text_range,
+    references::candidate_files,
+    target::{definition_module, reference_at, NavigationTarget},
+};
+
+/// The instances of the class that the name at `offset` refers to, across
+/// every file that can see the class, or the first equation of the member
+/// within each of them for a class member. Derived instances don't have
+/// equations, so they're only found for the class.
+pub fn implementation(db: &Database, file: FileId, offset: TextSize) -> Vec<NavigationTarget> {
+    let Some(reference) = reference_at(db, file, offset) else { return vec![] };
+    let Target::Global(definition) = reference.target else { return vec![] };
+    let Some(module) = definition_module(db, file, definition) else { return vec![] };
+    let Some(tree) = db.module_tree(module) else { return vec![] };
+    let Item::Class(class) = &tree.items[definition.item] else { return vec![] };
+    let member = definition.member.map(|index| class.members[index].name);
+    let class = Definition { member: None, ..definition };
+
+    let mut targets = vec![];
+    for candidate in candidate_files(db, module, class) {
+        let Some(scope) = db.scope(ModuleId::File(candidate)) else { continue };
+        let parse = db.parse(candidate);
+        let is_instance = |head: Option<ast::InstanceHead>| {
+            let Some(name) = head.and_then(|head| head.class_name()) else { return false };
+            match scope.resolve(Namespace::Class, lower_qualified_name(&name)) {
+                Some(Resolution::Global(other)) => {
+                    other == class && definition_module(db, candidate, other) == Some(module)
+                }
+                _ => false,
+            }
+        };
+        for node in parse.root.descendants() {
+            if let Some(instance) = ast::InstanceDeclaration::cast(node.clone()) {
+                if !is_instance(instance.head()) {
+                    continue;
+                }
+                let Some(member) = member else {
+                    targets.extend(instance_target(candidate, instance.syntax(), instance.head()));
+                    continue;
+                };
+                let equation =
+                    instance.members().find_map(|instance_member| match instance_member {
+                        ast::InstanceMember::Value(value) if lower_name(value.name()) == member => {
+                            Some(value)
+                        }
+                        _ => None,
+                    });
+                let Some(equation) = equation else { continue };
+                let (Some(full_range), Some(name)) =
+                    (text_range(equation.syntax()), equation.name())
+                else {
+                    continue;
+                };
+                let focus_range = name.syntax().text_range();
+                targets.push(NavigationTarget { file: candidate, full_range, focus_range });
+            } else if let Some(derived) = ast::DeriveInstanceDeclaration::cast(node) {
+                if member.is_none() && is_instance(derived.head()) {
+                    targets.extend(instance_target(candidate, derived.syntax(), derived.head()));
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// An instance, focused on its head.
+fn instance_target(
+    file: FileId,
+    instance: &syntax::SyntaxNode,
+    head: Option<ast::InstanceHead>,
+) -> Option<NavigationTarget> {
+    let full_range = text_range(instance)?;
+    let focus_range = text_range(head?.syntax())?;
+    Some(NavigationTarget { file, full_range, focus_range })
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::implementation;
+
+    #[test]
+    fn find_implementations() {
+        let mut db = Database::new();
+        let files = [
+            "module Lib where\n\nclass Size a where\n  size :: a -> Int\n  empty :: a\n\ninstance Size Int where\n  size n = n\n  empty = 0\n",
+            "module Main where\n\nimport Lib (class Size, size)\n\ndata Box = Box\n\ninstance Size Box where\n  empty = Box\n  size _ = 1\n\nderive instance Eq Box\n\nmain = size Box\n",
+            "module Other where\n\nclass Size a where\n  size :: a -> Int\n\ninstance Size Int where\n  size _ = 0\n",
+        ];
+        for (index, text) in files.iter().enumerate() {
+            db.set_file_text(FileId(index as u32), *text);
+        }
+
+        let implementations_at = |file: usize, text: &str| {
+            let offset = TextSize::from(files[file].find(text).unwrap() as u32);
+            let targets = implementation(&db, FileId(file as u32), offset);
+            let targets = targets
+                .into_iter()
+                .map(|target| (target.file.0, &files[target.file.0 as usize][target.focus_range]));
+            targets.collect::<Vec<_>>()
+        };
+        assert_eq!(implementations_at(0, "Size a"), [(0, "Size Int"), (1, "Size Box")]);
+        assert_eq!(implementations_at(1, "size Box"), [(0, "size"), (1, "size")]);
+        assert_eq!(implementations_at(1, "empty ="), [(0, "empty"), (1, "empty")]);
+        assert_eq!(implementations_at(1, "Box ="), []);
+    }
+}
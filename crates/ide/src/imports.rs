@@ -0,0 +1,176 @@
+//! Edits that bring a name from another module into scope.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    item_tree::Item,
+    lower_module_name,
+    name::{ModuleName, Name},
+};
+use resolution::interface::Definition;
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::ast;
+
+use crate::{edit::TextEdit, target::definition_module};
+
+/// An item of an import list, as it's written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ImportName {
+    Value(String),
+    Type(String),
+    /// A type along with every one of its constructors.
+    TypeWithConstructors(String),
+    Class(String),
+    Operator(String),
+    TypeOperator(String),
+}
+
+impl ImportName {
+    fn text(&self) -> String {
+        match self {
+            ImportName::Value(name) | ImportName::Type(name) => name.clone(),
+            ImportName::TypeWithConstructors(name) => format!("{name}(..)"),
+            ImportName::Class(name) => format!("class {name}"),
+            ImportName::Operator(name) => format!("({name})"),
+            ImportName::TypeOperator(name) => format!("type ({name})"),
+        }
+    }
+}
+
+/// The modules that `file` can import, in order.
+pub(crate) fn importable_modules(db: &Database, file: FileId) -> Vec<(ModuleName, ModuleId)> {
+    let own = db.item_tree(file).name;
+    let modules = db.visible_modules(ModuleId::File(file)).into_iter();
+    let modules =
+        modules.filter(|&(name, module)| Some(name) != own && matches!(module, ModuleId::File(_)));
+    modules.collect()
+}
+
+/// How a definition is written in an import list. Constructors are imported
+/// along with every other constructor of their type.
+pub(crate) fn import_name(
+    db: &Database,
+    file: FileId,
+    name: Name,
+    definition: Definition,
+) -> Option<ImportName> {
+    let tree = db.module_tree(definition_module(db, file, definition)?)?;
+    let name = name.to_string();
+    let import = match (&tree.items[definition.item], definition.member) {
+        (Item::Data(item), Some(_)) => ImportName::TypeWithConstructors(item.name.to_string()),
+        (Item::Newtype(item), Some(_)) => ImportName::TypeWithConstructors(item.name.to_string()),
+        (Item::Data(_) | Item::Newtype(_) | Item::Synonym(_) | Item::ForeignData(_), _) => {
+            ImportName::Type(name)
+        }
+        (Item::Class(_), None) => ImportName::Class(name),
+        (Item::Value(_) | Item::ForeignValue(_) | Item::Class(_), _) => ImportName::Value(name),
+        (Item::Fixity(item), _) if item.is_type => ImportName::TypeOperator(name),
+        (Item::Fixity(_), _) => ImportName::Operator(name),
+        (Item::Instance(_), _) => return None,
+    };
+    Some(import)
+}
+
+/// Imports `names` from `module`, by adding them to the import list of an
+/// unqualified import of the module if there is one, or else by adding an
+/// import of their own after the last one. Returns `None` if an unqualified
+/// import already brings every name of the module into scope.
+pub(crate) fn import_edit(
+    db: &Database,
+    file: FileId,
+    module: ModuleName,
+    names: &[ImportName],
+) -> Option<Vec<TextEdit>> {
+    let parse = db.parse(file);
+    let header = parse.module().header()?;
+    let imports: Vec<_> = header
+        .imports()
+        .filter(|import| {
+            import.module_name().is_some_and(|name| lower_module_name(&name) == module)
+        })
+        .filter(|import| import.alias().is_none())
+        .collect();
+    for import in &imports {
+        // Open imports already bring every name into scope.
+        let list = import.import_list()?;
+        if list.hiding() {
+            return None;
+        }
+        let Some(last) = list.items().last() else { continue };
+        let mut edits = vec![];
+        let mut added = String::new();
+        for name in names {
+            // A type that's listed without its constructors is listed
+            // again with all of them.
+            let listed = list.items().find_map(|item| match (item, name) {
+                (ast::ImportItem::Type(listed), ImportName::TypeWithConstructors(type_))
+                    if listed.name_ref()?.token()?.text() == type_.as_str() =>
+                {
+                    Some(listed)
+                }
+                _ => None,
+            });
+            match listed {
+                Some(listed) => {
+                    edits.push(TextEdit::replace(listed.syntax().text_range(), name.text()))
+                }
+                None => added.push_str(&format!(", {}", name.text())),
+            }
+        }
+        if !added.is_empty() {
+            edits.push(TextEdit::insert(last.syntax().text_range().end(), added));
+        }
+        return Some(edits);
+    }
+    let names: Vec<_> = names.iter().map(ImportName::text).collect();
+    Some(vec![new_import(&header, &format!("import {module} ({})", names.join(", ")))])
+}
+
+/// Imports every name of `module`, by dropping the import list of an
+/// unqualified import of the module if there is one, or else by adding an
+/// import of its own. Returns `None` if every name is already imported.
+pub(crate) fn open_import_edit(
+    db: &Database,
+    file: FileId,
+    module: ModuleName,
+) -> Option<TextEdit> {
+    let parse = db.parse(file);
+    let header = parse.module().header()?;
+    let mut imports = header.imports().filter(|import| {
+        import.alias().is_none()
+            && import.module_name().is_some_and(|name| lower_module_name(&name) == module)
+    });
+    match imports.next() {
+        Some(import) => {
+            let list = import.import_list()?;
+            let start = import.module_name()?.syntax().text_range().end();
+            let end = end_of_text(list.syntax());
+            Some(TextEdit::replace(TextRange::new(start, end), ""))
+        }
+        None => Some(new_import(&header, &format!("import {module}"))),
+    }
+}
+
+/// Imports `module` qualified under `alias`.
+pub(crate) fn qualified_import_edit(
+    db: &Database,
+    file: FileId,
+    module: ModuleName,
+    alias: ModuleName,
+) -> Option<TextEdit> {
+    let header = db.parse(file).module().header()?;
+    Some(new_import(&header, &format!("import {module} as {alias}")))
+}
+
+fn new_import(header: &ast::ModuleHeader, import: &str) -> TextEdit {
+    match header.imports().last() {
+        Some(last) => TextEdit::insert(end_of_text(last.syntax()), format!("\n{import}")),
+        None => TextEdit::insert(end_of_text(header.syntax()), format!("\n\n{import}")),
+    }
+}
+
+/// The end of the last token of a node that isn't trivia.
+fn end_of_text(node: &syntax::SyntaxNode) -> TextSize {
+    let tokens = node.descendants_with_tokens().filter_map(|element| element.into_token());
+    let last = tokens.filter(|token| !token.kind().is_trivia()).last();
+    last.map_or(node.text_range().end(), |token| token.text_range().end())
+}
@@ -0,0 +1,158 @@
+//! The types of names that don't have a signature, shown after them.
+
+use std::collections::HashMap;
+
+use analysis::{Database, FileId, Inferred};
+use lowering::{
+    hir::{BinderId, BodyId},
+    lower_name,
+};
+use rowan::{
+    ast::{AstNode, SyntaxNodePtr},
+    TextRange, TextSize,
+};
+use syntax::{ast, PureScript, SyntaxNode};
+
+use crate::{annotations::is_first, hover::value_body};
+
+/// Which names have their types shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InlayHintsConfig {
+    /// Values of the module without a signature.
+    pub declarations: bool,
+    /// Let and where bindings without a signature.
+    pub bindings: bool,
+    /// The variables that do statements bind.
+    pub binds: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> InlayHintsConfig {
+        InlayHintsConfig { declarations: true, bindings: true, binds: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    /// Where the hint is shown, which is the end of the name.
+    pub offset: TextSize,
+    pub label: String,
+    /// The start of the name, which the tooltip is the hover of.
+    pub target: TextSize,
+}
+
+/// The types of the names within `range` that don't have a signature: the
+/// first equation of a value or binding, and the variable of a bind.
+pub fn inlay_hints(
+    db: &Database,
+    file: FileId,
+    range: TextRange,
+    config: InlayHintsConfig,
+) -> Vec<InlayHint> {
+    let parse = db.parse(file);
+    let binders = BinderBodies::new(db, file);
+    let types = db.infer(file);
+    let mut hints = vec![];
+    let nodes =
+        parse.root.descendants().filter(|node| node.text_range().intersect(range).is_some());
+    for node in nodes {
+        let (name, ty) = if let Some(value) = ast::ValueDeclaration::cast(node.clone()) {
+            if !config.declarations || !is_first(&node, value.name()) {
+                continue;
+            }
+            let Some(name) = value.name() else { continue };
+            let Some(body) = value_body(db, file, lower_name(Some(name.clone()))) else {
+                continue;
+            };
+            let Some(types) = types.get(body) else { continue };
+            (name, types.ty.map(|ty| types.types.display(ty).to_string()))
+        } else if let Some(binding) = ast::LetBindingName::cast(node.clone()) {
+            if !config.bindings || !is_first(&node, binding.name()) {
+                continue;
+            }
+            let Some(name) = binding.name() else { continue };
+            (name, binders.display(&types, &node))
+        } else if let Some(bind) = ast::DoBind::cast(node.clone()) {
+            let Some(ast::Binder::Variable(variable)) = bind.binder().filter(|_| config.binds)
+            else {
+                continue;
+            };
+            let Some(name) = variable.name() else { continue };
+            (name, binders.display(&types, variable.syntax()))
+        } else {
+            continue;
+        };
+        let Some(ty) = ty else { continue };
+        let name = name.syntax().text_range();
+        if range.contains_range(name) {
+            let label = format!(":: {ty}");
+            hints.push(InlayHint { offset: name.end(), label, target: name.start() });
+        }
+    }
+    hints
+}
+
+/// The body and binder that each binder of a file was lowered from.
+pub(crate) struct BinderBodies(HashMap<SyntaxNodePtr<PureScript>, (BodyId, BinderId)>);
+
+impl BinderBodies {
+    pub(crate) fn new(db: &Database, file: FileId) -> BinderBodies {
+        let lowered = db.lower(file);
+        let mut binders = HashMap::new();
+        for (body, lowered_body) in lowered.module.bodies.iter() {
+            let source_map = lowered.source_map.body(body);
+            for (binder, _) in lowered_body.binders.iter() {
+                // The arguments that equations are desugared to point at the
+                // equation as well, after the binder of its name.
+                if let Some(pointer) = source_map.binder_syntax(binder) {
+                    binders.entry(pointer.clone()).or_insert((body, binder));
+                }
+            }
+        }
+        BinderBodies(binders)
+    }
+
+    pub(crate) fn get(&self, node: &SyntaxNode) -> Option<(BodyId, BinderId)> {
+        self.0.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    fn display(&self, types: &Inferred, node: &SyntaxNode) -> Option<String> {
+        let (body, binder) = self.get(node)?;
+        let types = types.get(body)?;
+        Some(types.types.display(types.binder(binder)?).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::{TextRange, TextSize};
+
+    use super::{inlay_hints, InlayHintsConfig};
+
+    #[test]
+    fn hint_types() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nf :: Int -> Int\nf x = x\n\ng 0 = 1\ng x = h x\n  where\n  h :: Int -> Int\n  h y = y\n  k = 1\n\nm = let n = \"n\" in n\n";
+        db.set_file_text(FileId(0), main);
+
+        let range = TextRange::up_to(TextSize::of(main));
+        let hints_with = |config| {
+            let hints = inlay_hints(&db, FileId(0), range, config);
+            let hints =
+                hints.into_iter().map(|hint| (&main[..usize::from(hint.offset)], hint.label));
+            hints.map(|(before, label)| (&before[before.len() - 1..], label)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            hints_with(InlayHintsConfig::default()),
+            [
+                ("g", ":: Int -> Int".to_string()),
+                ("k", ":: Int".to_string()),
+                ("m", ":: String".to_string()),
+                ("n", ":: String".to_string()),
+            ]
+        );
+        let config = InlayHintsConfig { declarations: false, ..InlayHintsConfig::default() };
+        assert_eq!(hints_with(config).len(), 2);
+    }
+}
@@ -0,0 +1,60 @@
+//! The features of the editor, computed from the analysis database.
+//!
+//! Features work in terms of files and byte offsets, and know nothing of
+//! the protocol that the server speaks; converting positions and building
+//! responses is left to the server.
+
+mod annotations;
+pub mod code_actions;
+pub mod code_lens;
+pub mod completion;
+pub mod definition;
+pub mod diagnostics;
+mod docs;
+pub mod document_highlight;
+pub mod document_links;
+pub mod edit;
+pub mod folding_ranges;
+pub mod formatting;
+pub mod hover;
+pub mod implementation;
+mod imports;
+pub mod inlay_hints;
+pub mod on_type_formatting;
+mod records;
+pub mod references;
+pub mod rename;
+pub mod selection_ranges;
+pub mod semantic_tokens;
+pub mod signature_help;
+pub mod symbols;
+mod target;
+pub mod type_definition;
+
+pub use crate::{
+    code_actions::{
+        code_actions, CodeAction, CodeActionConfig, CodeActionKind, ImportGrouping, ImportLayout,
+        ImportStyle,
+    },
+    code_lens::{code_lenses, CodeLens, CodeLensConfig, CodeLensKind},
+    completion::{completions, CompletionConfig, CompletionItem, CompletionKind},
+    definition::definition,
+    diagnostics::{diagnostics, Diagnostic, Severity, ShadowingConfig},
+    document_highlight::{document_highlights, DocumentHighlight, HighlightKind},
+    document_links::{document_link_target, document_links},
+    edit::{apply_edits, TextEdit, WorkspaceEdit},
+    folding_ranges::{folding_ranges, FoldingKind, FoldingRange},
+    formatting::{edits_in_range, format, text_edits},
+    hover::{hover, Hover},
+    implementation::implementation,
+    inlay_hints::{inlay_hints, InlayHint, InlayHintsConfig},
+    on_type_formatting::on_type_formatting,
+    references::references,
+    rename::{prepare_rename, rename, RenameError},
+    selection_ranges::selection_ranges,
+    semantic_tokens::{semantic_tokens, SemanticToken, SemanticTokenKind},
+    signature_help::{signature_help, SignatureHelp},
+    symbols::{document_symbols, workspace_symbols, DocumentSymbol, SymbolKind, WorkspaceSymbol},
+    target::{FileRange, NavigationTarget},
+    type_definition::type_definition,
+};
@@ -0,0 +1,196 @@
+//! Indenting a line as it's typed, from the layout of the lines before it.
+
+use analysis::{Database, FileId};
+use rowan::{TextRange, TextSize};
+use syntax::{SyntaxKind, SyntaxToken};
+
+use crate::edit::TextEdit;
+
+/// The characters that format the line they're typed on.
+pub const TRIGGER_CHARACTERS: [char; 3] = ['\n', '|', '='];
+
+/// Indents the line at `offset` after `typed` is typed on it. A new line
+/// continues the innermost layout block that's open at the end of the line
+/// before, such as the statements of a do block or the branches of a case,
+/// and is indented past the line before if that ends in a keyword that
+/// opens a block or in something that has to be followed by an expression.
+/// A `|` or `=` that starts a line lines up with the one on the line
+/// before, or is indented past it otherwise, for guards and constructors.
+pub fn on_type_formatting(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    typed: char,
+) -> Option<TextEdit> {
+    let text = db.file_text(file)?;
+    let before = &text[..usize::from(offset)];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    let indentation = text[line_start..].chars().take_while(|&char| char == ' ').count();
+    let line = &text[line_start + indentation..];
+    let wanted = match typed {
+        '\n' => {
+            let parse = db.parse(file);
+            let tokens = parse.root.descendants_with_tokens().filter_map(|element| {
+                let token = element.into_token()?;
+                let is_before = usize::from(token.text_range().end()) < line_start;
+                (is_before && !token.kind().is_trivia()).then_some(token)
+            });
+            new_line_indentation(&text, tokens)?
+        }
+        '|' | '=' => {
+            // Only a lone `|` or `=` that starts the line was just typed.
+            let is_first = line_start + indentation + 1 == usize::from(offset);
+            let rest = line[1..].chars().next();
+            if !is_first || !line.starts_with(typed) || rest.is_some_and(is_symbol) {
+                return None;
+            }
+            alternative_indentation(&text[..line_start])?
+        }
+        _ => return None,
+    };
+    if wanted == indentation {
+        return None;
+    }
+    let start = TextSize::from(line_start as u32);
+    let range = TextRange::at(start, TextSize::from(indentation as u32));
+    Some(TextEdit::replace(range, " ".repeat(wanted)))
+}
+
+fn new_line_indentation(text: &str, tokens: impl Iterator<Item = SyntaxToken>) -> Option<usize> {
+    // The column of each layout block that's open, along with the keyword
+    // that opened it.
+    let mut blocks: Vec<(usize, SyntaxKind)> = vec![];
+    let mut opening = None;
+    let mut last = None;
+    for token in tokens {
+        let start = usize::from(token.text_range().start());
+        let column = column(text, start);
+        if column == line_indentation(text, start) {
+            while blocks.last().is_some_and(|&(block, _)| block > column) {
+                blocks.pop();
+            }
+        }
+        if let Some(keyword) = opening.take() {
+            blocks.push((column, keyword));
+        }
+        match token.kind() {
+            kind @ (SyntaxKind::DoKw
+            | SyntaxKind::AdoKw
+            | SyntaxKind::OfKw
+            | SyntaxKind::WhereKw
+            | SyntaxKind::LetKw) => opening = Some(kind),
+            SyntaxKind::InKw => {
+                while let Some((_, keyword)) = blocks.pop() {
+                    if keyword == SyntaxKind::LetKw {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+        last = Some(token);
+    }
+
+    let last = last?;
+    let start = usize::from(last.text_range().start());
+    let continued = line_indentation(text, start) + 2;
+    match opening {
+        // Declarations of the module start at the margin.
+        Some(SyntaxKind::WhereKw) if blocks.is_empty() => Some(0),
+        Some(_) => Some(continued),
+        None if matches!(
+            last.kind(),
+            SyntaxKind::Equal
+                | SyntaxKind::RightArrow
+                | SyntaxKind::LeftArrow
+                | SyntaxKind::ThenKw
+                | SyntaxKind::ElseKw
+        ) =>
+        {
+            Some(continued)
+        }
+        None => blocks.last().map(|&(column, _)| column),
+    }
+}
+
+/// The column of a `|` or `=` that starts the line after `before`.
+fn alternative_indentation(before: &str) -> Option<usize> {
+    let previous = before.lines().rev().find(|line| !line.trim().is_empty())?;
+    let indentation = previous.chars().take_while(|&char| char == ' ').count();
+    let previous = previous.trim_start();
+    let is_alternative = (previous.starts_with('|') || previous.starts_with('='))
+        && !previous[1..].chars().next().is_some_and(is_symbol);
+    if is_alternative {
+        Some(indentation)
+    } else {
+        Some(indentation + 2)
+    }
+}
+
+/// Whether `char` can be part of an operator.
+fn is_symbol(char: char) -> bool {
+    ":!#$%&*+./<=>?@\\^|-~".contains(char)
+}
+
+fn column(text: &str, offset: usize) -> usize {
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    text[line_start..offset].chars().count()
+}
+
+/// The indentation of the line that `offset` is on.
+fn line_indentation(text: &str, offset: usize) -> usize {
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    text[line_start..].chars().take_while(|&char| char == ' ').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::on_type_formatting;
+    use crate::edit::apply_edits;
+
+    #[test]
+    fn indent_typed_lines() {
+        let indented = |before: &str, after: &str, typed: char| {
+            let mut db = Database::new();
+            let text = format!("{before}{after}");
+            db.set_file_text(FileId(0), text.as_str());
+            let offset = TextSize::of(before);
+            let edit = on_type_formatting(&db, FileId(0), offset, typed);
+            edit.map(|edit| apply_edits(&text, &[edit]))
+        };
+        let module = "module Main where\n\n";
+        assert_eq!(
+            indented(&format!("{module}main = do\n"), "\n", '\n').unwrap(),
+            format!("{module}main = do\n  \n")
+        );
+        assert_eq!(
+            indented(&format!("{module}main = do\n    log 1\n"), "\n", '\n').unwrap(),
+            format!("{module}main = do\n    log 1\n    \n")
+        );
+        assert_eq!(
+            indented(&format!("{module}f m = case m of\n  Just x ->\n"), "\n", '\n').unwrap(),
+            format!("{module}f m = case m of\n  Just x ->\n    \n")
+        );
+        assert_eq!(
+            indented(&format!("{module}f m = case m of\n  Just x -> x\n"), "      \n", '\n')
+                .unwrap(),
+            format!("{module}f m = case m of\n  Just x -> x\n  \n")
+        );
+        assert_eq!(
+            indented(&format!("{module}f = let x = 1 in do\n  x\n\n"), "\n", '\n').unwrap(),
+            format!("{module}f = let x = 1 in do\n  x\n\n  \n")
+        );
+        assert_eq!(
+            indented(&format!("{module}data Maybe a\n  = Just a\n|"), "\n", '|').unwrap(),
+            format!("{module}data Maybe a\n  = Just a\n  |\n")
+        );
+        assert_eq!(
+            indented(&format!("{module}f x\n    ="), "\n", '=').unwrap(),
+            format!("{module}f x\n  =\n")
+        );
+        assert_eq!(indented(&format!("{module}f x\n  =="), "\n", '='), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! The types of records, as far as they're known from the expressions
+//! around them.
+
+use lowering::{
+    hir::{Body, Expr, ExprId, GuardedExpr, LetBinding, Literal},
+    name::Name,
+};
+use typecheck::{
+    infer::BodyTypes,
+    ty::{Ty, TyId, Types},
+};
+
+/// The fields of a record type. Types are zonked, so the fields of a tail
+/// that's been solved are part of the row, while those of a tail that's
+/// still a variable aren't known.
+pub(crate) fn fields(types: &Types, record: TyId) -> Option<&[(Name, TyId)]> {
+    types.as_record(strip(types, record)).map(|(fields, _)| fields)
+}
+
+/// The type of the field `label` of a record type.
+pub(crate) fn field_type(types: &Types, record: TyId, label: Name) -> Option<TyId> {
+    let fields = fields(types, record)?;
+    fields.iter().find(|&&(name, _)| name == label).map(|&(_, ty)| ty)
+}
+
+/// The type of the record that a record literal is applied to, since the
+/// first label of an update is parsed as a pun until the `=` after it is
+/// typed, e.g. `person { na }`.
+pub(crate) fn updated_type(body: &Body, types: &BodyTypes, expr: ExprId) -> Option<TyId> {
+    body.exprs.iter().find_map(|(_, parent)| match parent {
+        Expr::Application { function, arguments } if arguments.first() == Some(&expr) => {
+            let ty = types.expr(*function)?;
+            fields(&types.types, ty).map(|_| ty)
+        }
+        _ => None,
+    })
+}
+
+/// The type that `expr` is checked against: the parameter of the function
+/// it's applied to, the annotation it's under, the field of the record
+/// it's in, or the result of the body, branch, or binding that it is.
+///
+/// Record literals are inferred as closed rows before they're unified with
+/// what's expected, so their own type only has the fields that are written.
+pub(crate) fn expected_type(body: &Body, types: &BodyTypes, expr: ExprId) -> Option<TyId> {
+    if results(&body.guarded).contains(&expr) {
+        return types.ty;
+    }
+    if let Some(ty) = binding_type(bindings(&body.guarded), types, expr) {
+        return Some(ty);
+    }
+    for (id, parent) in body.exprs.iter() {
+        let ty = match parent {
+            Expr::Application { function, arguments } => {
+                let Some(index) = arguments.iter().position(|&argument| argument == expr) else {
+                    continue;
+                };
+                let mut ty = types.expr(*function)?;
+                for _ in 0..index {
+                    ty = function_type(&types.types, ty)?.1;
+                }
+                function_type(&types.types, ty)?.0
+            }
+            Expr::Lambda { binders, body } if *body == expr => {
+                let mut ty = types.expr(id)?;
+                for _ in binders {
+                    ty = function_type(&types.types, ty)?.1;
+                }
+                ty
+            }
+            Expr::Typed { expr: typed, .. } if *typed == expr => types.expr(id)?,
+            Expr::IfThenElse { then, else_, .. } if [*then, *else_].contains(&expr) => {
+                types.expr(id)?
+            }
+            Expr::Case { branches, .. } => {
+                let guarded = || branches.iter().map(|branch| &branch.guarded);
+                match guarded().find_map(|guarded| binding_type(bindings(guarded), types, expr)) {
+                    Some(ty) => ty,
+                    None if guarded().any(|guarded| results(guarded).contains(&expr)) => {
+                        types.expr(id)?
+                    }
+                    None => continue,
+                }
+            }
+            Expr::LetIn { bindings, body } => match binding_type(bindings, types, expr) {
+                Some(ty) => ty,
+                None if *body == expr => types.expr(id)?,
+                None => continue,
+            },
+            Expr::Literal(Literal::Record(fields)) => {
+                let Some(&(label, _)) = fields.iter().find(|&&(_, field)| field == expr) else {
+                    continue;
+                };
+                let record = expected_type(body, types, id)?;
+                field_type(&types.types, record, label)?
+            }
+            _ => continue,
+        };
+        return Some(ty);
+    }
+    None
+}
+
+/// The type of the let binding that `expr` is a result of.
+fn binding_type(bindings: &[LetBinding], types: &BodyTypes, expr: ExprId) -> Option<TyId> {
+    bindings.iter().find_map(|binding| match binding {
+        LetBinding::Value { binder, guarded } if results(guarded).contains(&expr) => {
+            types.binder(*binder)
+        }
+        _ => None,
+    })
+}
+
+fn bindings(guarded: &GuardedExpr) -> &[LetBinding] {
+    match guarded {
+        GuardedExpr::LetIn { bindings, .. } => bindings,
+        _ => &[],
+    }
+}
+
+/// The expressions that a guarded expression evaluates to.
+fn results(guarded: &GuardedExpr) -> Vec<ExprId> {
+    match guarded {
+        GuardedExpr::Unconditional(expr) => vec![*expr],
+        GuardedExpr::Guarded(branches) | GuardedExpr::LetIn { branches, .. } => {
+            branches.iter().map(|branch| branch.expr).collect()
+        }
+    }
+}
+
+/// Splits a function type under any `forall`s and constraints.
+fn function_type(types: &Types, ty: TyId) -> Option<(TyId, TyId)> {
+    types.as_function(strip(types, ty))
+}
+
+fn strip(types: &Types, mut ty: TyId) -> TyId {
+    while let Ty::Forall(_, _, body) | Ty::Constrained(_, body) = &types[ty] {
+        ty = *body;
+    }
+    ty
+}
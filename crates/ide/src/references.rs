@@ -0,0 +1,171 @@
+//! Every use of the name under the cursor.
+
+use analysis::{Database, FileId, ModuleId};
+use resolution::{
+    interface::Definition,
+    reference::{Reference, Target},
+};
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{ast, SyntaxKind};
+
+use crate::target::{definition_module, reference_at, FileRange, FileResolver};
+
+/// The names that refer to the same thing as the name at `offset`, in the
+/// order of their files and offsets. The names of its declarations are only
+/// included if `include_declaration` is set.
+pub fn references(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    include_declaration: bool,
+) -> Vec<FileRange> {
+    let Some(reference) = reference_at(db, file, offset) else { return vec![] };
+    let files = match reference.target {
+        Target::Local { .. } => vec![file],
+        Target::Global(definition) => match definition_module(db, file, definition) {
+            Some(module) => candidate_files(db, module, definition),
+            None => return vec![],
+        },
+        _ => return vec![],
+    };
+
+    let mut references = vec![];
+    for candidate in files {
+        let found = references_in(db, file, &reference, candidate).into_iter();
+        let found = found
+            .filter(|range| include_declaration || !is_declaration(db, candidate, range.start()));
+        references.extend(found.map(|range| FileRange { file: candidate, range }));
+    }
+    references
+}
+
+/// The names of `candidate` that refer to the same thing as `reference`,
+/// which is a name of `file`, in order.
+pub(crate) fn references_in(
+    db: &Database,
+    file: FileId,
+    reference: &Reference,
+    candidate: FileId,
+) -> Vec<TextRange> {
+    let Some(text) = db.file_text(file) else { return vec![] };
+    let name = &text[reference.range];
+    let Some(resolver) = FileResolver::new(db, candidate) else { return vec![] };
+    let Some(text) = db.file_text(candidate) else { return vec![] };
+    let mut references = vec![];
+    // Names are only resolved where their text occurs, which is a small
+    // fraction of the tokens of a file.
+    for (start, _) in text.match_indices(name) {
+        let offset = TextSize::from(start as u32);
+        let Some(found) = resolver.reference_at(offset) else { continue };
+        if found.range.start() != offset || &text[found.range] != name {
+            continue;
+        }
+        let same = match (&reference.target, &found.target) {
+            (Target::Local { .. }, Target::Local { .. }) => found.target == reference.target,
+            (&Target::Global(definition), &Target::Global(other)) => {
+                other == definition
+                    && definition_module(db, candidate, other)
+                        == definition_module(db, file, definition)
+            }
+            _ => false,
+        };
+        if same {
+            references.push(found.range);
+        }
+    }
+    references
+}
+
+/// The file that defines a definition and the files of every module that
+/// imports it, directly or through the modules that re-export it. Modules
+/// are matched by name, since the graph of every file keeps only one module
+/// of each name, and the rest is left to resolution.
+pub(crate) fn candidate_files(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+) -> Vec<FileId> {
+    let mut files = vec![];
+    if let ModuleId::File(file) = module {
+        files.push(file);
+    }
+    let graph = db.graph(None);
+    let Some(id) = graph.id(definition.module) else { return files };
+    let modules = db.modules();
+    for dependent in graph.transitive_reverse_dependencies(id) {
+        let name = graph.module(dependent).name;
+        for &file in modules.get(&name).into_iter().flatten() {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Whether the name at `offset` declares what it refers to, rather than
+/// using it. Instance members implement a class member, so they're uses.
+pub(crate) fn is_declaration(db: &Database, file: FileId, offset: TextSize) -> bool {
+    let parse = db.parse(file);
+    let Some(token) = parse
+        .root
+        .token_at_offset(offset)
+        .find(|token| token.parent().is_some_and(|parent| parent.kind() == SyntaxKind::Name))
+    else {
+        return false;
+    };
+    !token.parent_ancestors().any(|node| ast::InstanceDeclaration::can_cast(node.kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::references;
+
+    #[test]
+    fn find_references() {
+        let mut db = Database::new();
+        let files = [
+            "module Lib (module Lib, size) where\n\nclass Size a where\n  size :: a -> Int\n\ninstance Size Int where\n  size n = n\n",
+            "module Reexport (module Lib) where\n\nimport Lib\n",
+            "module Main where\n\nimport Reexport (size)\n\nmain = size 1 + size 2\n\nshadow size = size\n",
+            "module Other where\n\nsize = 0\n",
+        ];
+        for (index, text) in files.iter().enumerate() {
+            db.set_file_text(FileId(index as u32), *text);
+        }
+
+        let references_at = |file: usize, text: &str, include_declaration| {
+            let offset = TextSize::from(files[file].find(text).unwrap() as u32);
+            let references = references(&db, FileId(file as u32), offset, include_declaration);
+            references
+                .into_iter()
+                .map(|reference| (reference.file.0, u32::from(reference.range.start())))
+                .collect::<Vec<_>>()
+        };
+        let offset = |file: usize, text: &str| files[file].find(text).unwrap() as u32;
+
+        let class_member = references_at(2, "size 1", false);
+        assert_eq!(
+            class_member,
+            [
+                (0, offset(0, "size)")),
+                (0, offset(0, "size n")),
+                (2, offset(2, "size)")),
+                (2, offset(2, "size 1")),
+                (2, offset(2, "size 2")),
+            ]
+        );
+        let with_declaration = references_at(0, "size ::", true);
+        assert_eq!(with_declaration.len(), class_member.len() + 1);
+
+        // Locals are only found within their scope.
+        assert_eq!(
+            references_at(2, "size = size", true),
+            [(2, offset(2, "size = size")), (2, offset(2, "size\n"))]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Renaming the name under the cursor, everywhere it's used.
+
+use std::fmt;
+
+use analysis::{Database, FileId, ModuleId};
+use resolution::{
+    interface::Namespace,
+    reference::{Reference, ReferenceKind, Target},
+};
+use rowan::{TextRange, TextSize};
+use syntax::SyntaxKind;
+
+use crate::{
+    edit::{TextEdit, WorkspaceEdit},
+    references::references,
+    target::{definition_module, reference_at},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The cursor isn't on a name that can be renamed, e.g. a keyword, a
+    /// literal, or a label.
+    NotRenameable,
+    /// The name is defined by a dependency, or is built into the compiler.
+    Library,
+    /// The new name can't be written where the old one is.
+    InvalidName(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotRenameable => write!(f, "there's no name to rename here"),
+            RenameError::Library => write!(f, "names from dependencies can't be renamed"),
+            RenameError::InvalidName(name) => write!(f, "`{name}` isn't a valid name here"),
+        }
+    }
+}
+
+/// The range of the name at `offset`, if it can be renamed.
+pub fn prepare_rename(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+) -> Result<TextRange, RenameError> {
+    renamed(db, file, offset).map(|(reference, _)| reference.range)
+}
+
+/// Renames the name at `offset` to `new_name`, at its declarations and
+/// every use of it, including import and export lists.
+pub fn rename(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    new_name: &str,
+) -> Result<WorkspaceEdit, RenameError> {
+    let (_, namespace) = renamed(db, file, offset)?;
+    if !is_valid(namespace, new_name) {
+        return Err(RenameError::InvalidName(new_name.to_string()));
+    }
+    let mut edit = WorkspaceEdit::default();
+    for reference in references(db, file, offset, true) {
+        edit.push(reference.file, TextEdit::replace(reference.range, new_name));
+    }
+    Ok(edit)
+}
+
+/// The name at `offset` along with its namespace, if it's defined by a
+/// module of the workspace.
+fn renamed(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+) -> Result<(Reference, Namespace), RenameError> {
+    let reference = reference_at(db, file, offset).ok_or(RenameError::NotRenameable)?;
+    let ReferenceKind::Name(namespace) = reference.kind else {
+        return Err(RenameError::NotRenameable);
+    };
+    match reference.target {
+        Target::Local { .. } => {}
+        Target::Global(definition) => match definition_module(db, file, definition) {
+            Some(ModuleId::File(defined)) if !db.is_library(defined) => {}
+            Some(_) => return Err(RenameError::Library),
+            None => return Err(RenameError::NotRenameable),
+        },
+        Target::Prim(_) => return Err(RenameError::Library),
+        _ => return Err(RenameError::NotRenameable),
+    }
+    Ok((reference, namespace))
+}
+
+/// Whether `name` lexes as a single name of the kind that `namespace` has,
+/// which rules out keywords and qualified names.
+pub(crate) fn is_valid(namespace: Namespace, name: &str) -> bool {
+    let lexed = parsing::lexer::lex(name);
+    if lexed.len() != 1 || !lexed.errors().is_empty() {
+        return false;
+    }
+    let expected = match namespace {
+        Namespace::Value => SyntaxKind::Lower,
+        Namespace::Constructor | Namespace::Type | Namespace::Class => SyntaxKind::Upper,
+        Namespace::Operator | Namespace::TypeOperator => SyntaxKind::Operator,
+    };
+    lexed.kind(0) == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::{prepare_rename, rename, RenameError};
+    use crate::edit::apply_edits;
+
+    #[test]
+    fn rename_across_files() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe (Maybe(..), fromMaybe) where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a Nothing = a\nfromMaybe _ (Just a) = a\n";
+        let main = "module Main where\n\nimport Data.Maybe (fromMaybe)\nimport Data.Maybe as M\n\nf = fromMaybe 1 (M.Just 2)\n\ng x = y where y = x\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let offset = |text: &str, find: &str| TextSize::from(text.find(find).unwrap() as u32);
+        let renamed = |file: FileId, offset: TextSize, new_name: &str| {
+            let edit = rename(&db, file, offset, new_name).unwrap();
+            let texts = edit.files.iter().map(|(file, edits)| {
+                let text = db.file_text(*file).unwrap();
+                (*file, apply_edits(&text, edits))
+            });
+            texts.collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            renamed(FileId(1), offset(main, "fromMaybe 1"), "withDefault"),
+            [
+                (FileId(0), maybe.replace("fromMaybe", "withDefault")),
+                (FileId(1), main.replace("fromMaybe", "withDefault")),
+            ]
+        );
+        assert_eq!(
+            renamed(FileId(0), offset(maybe, "Just a |"), "Some"),
+            [(FileId(0), maybe.replace("Just", "Some")), (FileId(1), main.replace("Just", "Some"))]
+        );
+        assert_eq!(
+            renamed(FileId(1), offset(main, "y = x"), "z"),
+            [(FileId(1), main.replace("y where y", "z where z"))]
+        );
+
+        assert_eq!(
+            prepare_rename(&db, FileId(1), offset(main, "where y")),
+            Err(RenameError::NotRenameable)
+        );
+        assert_eq!(
+            prepare_rename(&db, FileId(1), offset(main, "1 (")),
+            Err(RenameError::NotRenameable)
+        );
+        assert_eq!(
+            rename(&db, FileId(1), offset(main, "fromMaybe 1"), "Maybe"),
+            Err(RenameError::InvalidName("Maybe".to_string()))
+        );
+        assert_eq!(
+            rename(&db, FileId(1), offset(main, "fromMaybe 1"), "where"),
+            Err(RenameError::InvalidName("where".to_string()))
+        );
+    }
+}
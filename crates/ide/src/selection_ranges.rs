@@ -0,0 +1,62 @@
+//! The ranges that expanding the selection goes through.
+
+use analysis::{Database, FileId};
+use rowan::{TextRange, TextSize};
+use syntax::SyntaxKind;
+
+use crate::folding_ranges::text_range;
+
+/// The ranges around `offset`, innermost first: the token at the cursor,
+/// then each node that contains it, such as an expression, the statement
+/// or branch it's in, its declaration, and finally the module. Ranges
+/// leave out the trivia around nodes, and a node with the same range as
+/// the one within it is skipped, as is the `= ...` of an equation.
+pub fn selection_ranges(db: &Database, file: FileId, offset: TextSize) -> Vec<TextRange> {
+    let parse = db.parse(file);
+    // A cursor between a name and whitespace selects the name.
+    let tokens = parse.root.token_at_offset(offset);
+    let Some(token) = tokens.max_by_key(|token| !token.kind().is_trivia()) else { return vec![] };
+    let mut ranges = vec![token.text_range()];
+    for node in token.parent_ancestors() {
+        if node.kind() == SyntaxKind::Unconditional {
+            continue;
+        }
+        let Some(range) = text_range(&node) else { continue };
+        if ranges.last().is_some_and(|&last| last != range && range.contains_range(last)) {
+            ranges.push(range);
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::selection_ranges;
+
+    #[test]
+    fn expand_selection() {
+        let mut db = Database::new();
+        let main = "module Main where\n\nf x = do\n  y <- pure (x + 1)\n  pure y\n";
+        db.set_file_text(FileId(0), main);
+
+        let offset = TextSize::from(main.find("1)").unwrap() as u32);
+        let ranges = selection_ranges(&db, FileId(0), offset);
+        let ranges: Vec<_> = ranges.iter().map(|&range| &main[range]).collect();
+        assert_eq!(
+            ranges,
+            [
+                "1",
+                "x + 1",
+                "(x + 1)",
+                "pure (x + 1)",
+                "y <- pure (x + 1)",
+                "do\n  y <- pure (x + 1)\n  pure y",
+                "f x = do\n  y <- pure (x + 1)\n  pure y",
+                "module Main where\n\nf x = do\n  y <- pure (x + 1)\n  pure y",
+            ]
+        );
+    }
+}
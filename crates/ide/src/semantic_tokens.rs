@@ -0,0 +1,171 @@
+//! The kinds of the tokens of a file, for highlighting beyond what the
+//! editor can tell from the text alone.
+
+use analysis::{Database, FileId};
+use resolution::{
+    interface::Namespace,
+    reference::{ReferenceKind, Target},
+};
+use rowan::TextRange;
+use syntax::{SyntaxKind, SyntaxToken};
+
+use crate::target::FileResolver;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenKind {
+    /// A module name, qualifier, or alias.
+    Module,
+    Type,
+    Class,
+    Constructor,
+    TypeVariable,
+    /// A value of the module or of another one.
+    Value,
+    /// A binder within a body, or a use of one.
+    Local,
+    Label,
+    Operator,
+    Keyword,
+    Comment,
+    String,
+    Number,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: TextRange,
+    pub kind: SemanticTokenKind,
+}
+
+/// The tokens of a file that have a kind, in order. Names are classified
+/// by what they resolve to, so a name that doesn't resolve only has a kind
+/// if its syntax gives one.
+pub fn semantic_tokens(db: &Database, file: FileId) -> Vec<SemanticToken> {
+    let parse = db.parse(file);
+    let resolver = FileResolver::new(db, file);
+    let tokens = parse.root.descendants_with_tokens().filter_map(|element| element.into_token());
+    let tokens = tokens.filter_map(|token| {
+        let kind = match token.kind() {
+            SyntaxKind::Upper | SyntaxKind::Lower | SyntaxKind::Operator => {
+                let reference = resolver
+                    .as_ref()
+                    .and_then(|resolver| resolver.reference_at(token.text_range().start()));
+                let resolved = reference
+                    .filter(|reference| reference.range == token.text_range())
+                    .and_then(|reference| resolved_kind(reference.kind, &reference.target));
+                resolved.or_else(|| syntax_kind(&token))?
+            }
+            kind => token_kind(kind)?,
+        };
+        Some(SemanticToken { range: token.text_range(), kind })
+    });
+    tokens.collect()
+}
+
+fn resolved_kind(kind: ReferenceKind, target: &Target) -> Option<SemanticTokenKind> {
+    let kind = match (kind, target) {
+        (ReferenceKind::Module, _) => SemanticTokenKind::Module,
+        (ReferenceKind::Label, _) => SemanticTokenKind::Label,
+        (ReferenceKind::Name(_), Target::Local { .. }) => SemanticTokenKind::Local,
+        (ReferenceKind::Name(namespace), _) => match namespace {
+            Namespace::Value => SemanticTokenKind::Value,
+            Namespace::Constructor => SemanticTokenKind::Constructor,
+            Namespace::Operator | Namespace::TypeOperator => SemanticTokenKind::Operator,
+            Namespace::Type => SemanticTokenKind::Type,
+            Namespace::Class => SemanticTokenKind::Class,
+        },
+    };
+    Some(kind)
+}
+
+/// The kind of a name that doesn't resolve, from where it's written.
+fn syntax_kind(token: &SyntaxToken) -> Option<SemanticTokenKind> {
+    let parent = token.parent()?;
+    let kind = match parent.kind() {
+        SyntaxKind::ModuleName => SemanticTokenKind::Module,
+        SyntaxKind::Label => SemanticTokenKind::Label,
+        _ if token.kind() == SyntaxKind::Operator => SemanticTokenKind::Operator,
+        _ => {
+            let variable = parent.ancestors().take(2).any(|node| {
+                matches!(node.kind(), SyntaxKind::TypeVariable | SyntaxKind::TypeVariableBinding)
+            });
+            variable.then_some(SemanticTokenKind::TypeVariable)?
+        }
+    };
+    Some(kind)
+}
+
+fn token_kind(kind: SyntaxKind) -> Option<SemanticTokenKind> {
+    let kind = match kind {
+        SyntaxKind::LineComment | SyntaxKind::BlockComment => SemanticTokenKind::Comment,
+        SyntaxKind::LiteralChar | SyntaxKind::LiteralString | SyntaxKind::LiteralRawString => {
+            SemanticTokenKind::String
+        }
+        SyntaxKind::LiteralInteger | SyntaxKind::LiteralNumber => SemanticTokenKind::Number,
+        kind if kind.is_keyword() => SemanticTokenKind::Keyword,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{semantic_tokens, SemanticTokenKind};
+
+    #[test]
+    fn classify_names() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nclass Functor f where\n  map :: forall a b. (a -> b) -> f a -> f b\n\ninfixl 4 map as <$>\n";
+        let main = "module Main where\n\nimport Data.Maybe as M\n\n-- | Doubles.\nf :: M.Maybe Int -> M.Maybe Int\nf m = M.map (\\x -> x * 2) m\n\ng = { label: \"text\" }.label\n\nh :: forall a. a -> a\nh a = a\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let tokens = semantic_tokens(&db, FileId(1));
+        let kinds: Vec<_> = tokens.iter().map(|token| (&main[token.range], token.kind)).collect();
+        use SemanticTokenKind::*;
+        assert_eq!(
+            kinds,
+            [
+                ("module", Keyword),
+                ("Main", Module),
+                ("where", Keyword),
+                ("import", Keyword),
+                ("Data", Module),
+                ("Maybe", Module),
+                ("as", Keyword),
+                ("M", Module),
+                ("-- | Doubles.", Comment),
+                ("f", Value),
+                ("M", Module),
+                ("Maybe", Type),
+                ("Int", Type),
+                ("M", Module),
+                ("Maybe", Type),
+                ("Int", Type),
+                ("f", Value),
+                ("m", Local),
+                ("M", Module),
+                ("map", Value),
+                ("x", Local),
+                ("x", Local),
+                ("*", Operator),
+                ("2", Number),
+                ("m", Local),
+                ("g", Value),
+                ("label", Label),
+                ("\"text\"", String),
+                ("label", Label),
+                ("h", Value),
+                ("forall", Keyword),
+                ("a", TypeVariable),
+                ("a", TypeVariable),
+                ("a", TypeVariable),
+                ("h", Value),
+                ("a", Local),
+                ("a", Local),
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! The type of the function that's being applied at the cursor.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::item_tree::Item;
+use resolution::{interface::Definition, reference::Target};
+use rowan::{ast::AstNode, Direction, TextRange, TextSize};
+use syntax::{ast, SyntaxNode, SyntaxToken};
+use typecheck::ty::{TyId, Types};
+
+use crate::{
+    docs::definition_docs,
+    hover::value_body,
+    target::{definition_module, reference_at},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelp {
+    /// The name of the function along with its type.
+    pub label: String,
+    /// The ranges of the label that are the types of the parameters.
+    pub parameters: Vec<TextRange>,
+    /// The argument at the cursor, which is past the parameters when a
+    /// function is applied to more arguments than its type shows.
+    pub active_parameter: usize,
+    pub documentation: Option<String>,
+}
+
+/// Shows the type of the function applied around `offset`, with the number
+/// of arguments before the cursor as the active parameter. A name followed
+/// by a space is a function that's about to be applied, such that help is
+/// shown before its first argument is typed.
+pub fn signature_help(db: &Database, file: FileId, offset: TextSize) -> Option<SignatureHelp> {
+    let parse = db.parse(file);
+    let token = parse.root.token_at_offset(offset).left_biased()?;
+    // The last token before the cursor that isn't whitespace.
+    let last = std::iter::successors(Some(token), |token| token.prev_token())
+        .find(|token| !token.kind().is_trivia())?;
+    let (head, active_parameter) = applied_function(&last, offset)?;
+
+    let name = match &head {
+        ast::Expression::Variable(variable) => variable.name()?.syntax().clone(),
+        ast::Expression::Constructor(constructor) => constructor.name()?.syntax().clone(),
+        _ => return None,
+    };
+    let name_token = name.last_token()?;
+    let reference = reference_at(db, file, name_token.text_range().start());
+    let mut documentation = None;
+    let (label, parameters) = match reference.map(|reference| reference.target) {
+        Some(Target::Global(definition)) => {
+            let module = definition_module(db, file, definition)?;
+            documentation = definition_docs(db, module, definition);
+            match value_signature(db, module, definition, name_token.text()) {
+                Some(signature) => signature,
+                None => expression_signature(db, file, head.syntax(), name_token.text())?,
+            }
+        }
+        Some(Target::Local { body, binder }) => {
+            let types = db.infer(file);
+            let types = types.get(body)?;
+            signature(&types.types, name_token.text(), types.binder(binder)?)
+        }
+        _ => expression_signature(db, file, head.syntax(), name_token.text())?,
+    };
+    if parameters.is_empty() {
+        return None;
+    }
+    Some(SignatureHelp { label, parameters, active_parameter, documentation })
+}
+
+/// The function of the innermost application that the cursor is within the
+/// arguments of, along with the index of the argument at the cursor. `last`
+/// is the last token before the cursor that isn't whitespace.
+fn applied_function(last: &SyntaxToken, offset: TextSize) -> Option<(ast::Expression, usize)> {
+    let after_last = last.text_range().end() < offset;
+    for node in last.parent_ancestors() {
+        let Some(application) = ast::ExpressionApplication::cast(node.clone()) else { continue };
+        let head = application.head()?;
+        if head.syntax().text_range().end() >= offset {
+            continue;
+        }
+        // Type arguments aren't parameters of the function's type.
+        let arguments = application
+            .arguments()
+            .filter(|argument| !matches!(argument, ast::Expression::TypeArgument(_)));
+        let before = arguments.filter(|argument| argument.syntax().text_range().end() < offset);
+        return Some((head, before.count()));
+    }
+    // A function followed by a space, before its first argument.
+    if !after_last {
+        return None;
+    }
+    let node = last.parent_ancestors().find_map(ast::Expression::cast)?;
+    let is_end = node.syntax().last_token().as_ref() == Some(last);
+    let is_name = matches!(node, ast::Expression::Variable(_) | ast::Expression::Constructor(_));
+    (is_end && is_name && !is_followed(node.syntax())).then_some((node, 0))
+}
+
+/// Whether an expression is followed by another token within the same
+/// expression, e.g. an operator, such that it isn't applied.
+fn is_followed(node: &SyntaxNode) -> bool {
+    let next = node.siblings_with_tokens(Direction::Next).skip(1);
+    next.into_iter().any(|element| !element.kind().is_trivia())
+}
+
+/// The type of a value from its own body, which is its signature if it has
+/// one and the type inferred for it otherwise.
+fn value_signature(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+    name: &str,
+) -> Option<(String, Vec<TextRange>)> {
+    let ModuleId::File(file) = module else { return None };
+    let tree = db.item_tree(file);
+    let Item::Value(value) = &tree.items[definition.item] else { return None };
+    let body = value_body(db, file, value.name)?;
+    let types = db.infer(file);
+    let types = types.get(body)?;
+    Some(signature(&types.types, name, types.ty?))
+}
+
+/// The type of the function at `node` where it's used, with everything
+/// that's known about its arguments substituted, for constructors, class
+/// members, and foreign values.
+fn expression_signature(
+    db: &Database,
+    file: FileId,
+    node: &SyntaxNode,
+    name: &str,
+) -> Option<(String, Vec<TextRange>)> {
+    let lowered = db.lower(file);
+    let (body, expr) = lowered
+        .module
+        .bodies
+        .iter()
+        .find_map(|(body, _)| Some((body, lowered.source_map.body(body).node_expr(node)?)))?;
+    let types = db.infer(file);
+    let types = types.get(body)?;
+    Some(signature(&types.types, name, types.expr(expr)?))
+}
+
+/// Renders `name :: ty` with the ranges of the parameters of `ty`.
+fn signature(types: &Types, name: &str, ty: TyId) -> (String, Vec<TextRange>) {
+    let prefix = format!("{name} :: ");
+    let rendered = types.signature(ty);
+    let parameters = rendered.highlights.iter().map(|highlight| {
+        let start = TextSize::of(prefix.as_str()) + TextSize::from(highlight.start as u32);
+        TextRange::at(start, TextSize::from(highlight.len() as u32))
+    });
+    let parameters = parameters.collect();
+    (prefix + &rendered.text, parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::signature_help;
+
+    #[test]
+    fn active_parameters() {
+        let mut db = Database::new();
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\n-- | Unwraps a `Maybe`.\nfromMaybe :: forall a. a -> Maybe a -> a\nfromMaybe a _ = a\n";
+        let main = "module Main where\n\nimport Data.Maybe\n\nf = fromMaybe 1 \n\ng = fromMaybe (Just 1) Nothing\n\nh = Just \n\nk x = let apply g y = g y in apply Just x\n";
+        db.set_file_text(FileId(0), maybe);
+        db.set_file_text(FileId(1), main);
+
+        let help_at = |text: &str| {
+            let offset = main.find(text).unwrap() + text.len();
+            signature_help(&db, FileId(1), TextSize::from(offset as u32))
+        };
+        let active = |text: &str| {
+            let help = help_at(text).unwrap();
+            let parameters = help.parameters.iter().map(|&range| help.label[range].to_string());
+            (parameters.collect::<Vec<_>>(), help.active_parameter)
+        };
+        let help = help_at("f = fromMaybe 1 ").unwrap();
+        assert_eq!(help.label, "fromMaybe :: forall a. a -> Maybe a -> a");
+        assert_eq!(help.documentation.as_deref(), Some("Unwraps a `Maybe`."));
+        assert_eq!(active("f = fromMaybe 1 ").0, ["a", "Maybe a"]);
+        assert_eq!(active("f = fromMaybe 1 ").1, 1);
+        assert_eq!(active("f = fromMaybe 1").1, 0);
+        assert_eq!(active("f = fromMaybe ").1, 0);
+        // The innermost application is the one that's shown.
+        assert_eq!(active("g = fromMaybe (Just 1").0, ["Int"]);
+        assert_eq!(active("g = fromMaybe (Just 1) ").1, 1);
+        assert_eq!(active("h = Just ").0, ["t0"]);
+        assert_eq!(active("apply Just").0, ["(t0 -> t1)", "t0"]);
+        assert_eq!(help_at("f = fromMaybe"), None);
+    }
+}
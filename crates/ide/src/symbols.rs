@@ -0,0 +1,340 @@
+//! The outline of a module, and the search for names across the workspace.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    item_tree::{Item, ItemTree},
+    name::ModuleName,
+};
+use resolution::interface::Definition;
+use rowan::{ast::AstNode, TextRange};
+use syntax::{
+    ast,
+    unparse::{Fixities, Type},
+};
+
+use crate::target::{definition_target, NavigationTarget};
+
+/// How many symbols a search returns at most, since short queries match
+/// most of the workspace.
+const SEARCH_LIMIT: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Value,
+    Data,
+    Newtype,
+    Synonym,
+    Constructor,
+    Class,
+    Member,
+    Instance,
+    ForeignData,
+    ForeignValue,
+    Operator,
+}
+
+/// A declaration along with the declarations nested within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    /// The signature of values and members, or the head of a named instance.
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    /// The range of the whole declaration, including every equation of a
+    /// value and its signature.
+    pub full_range: TextRange,
+    /// The range of its name.
+    pub focus_range: TextRange,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A declaration found by searching the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The module that declares it.
+    pub module: ModuleName,
+    pub target: NavigationTarget,
+}
+
+/// The declarations whose names fuzzily match `query`, from the symbol
+/// index of the database. Declarations of the workspace come before those
+/// of its dependencies.
+pub fn workspace_symbols(db: &Database, query: &str) -> Vec<WorkspaceSymbol> {
+    let index = db.symbols();
+    let mut matches = index.search(query);
+    matches.sort_by_key(|symbol| db.is_library(symbol.file));
+
+    let mut symbols = vec![];
+    for symbol in matches {
+        if symbols.len() == SEARCH_LIMIT {
+            break;
+        }
+        let module = ModuleId::File(symbol.file);
+        let kind = definition_kind(&db.item_tree(symbol.file), symbol.definition);
+        let Some(target) = definition_target(db, module, symbol.definition) else { continue };
+        symbols.push(WorkspaceSymbol {
+            name: symbol.name.to_string(),
+            kind,
+            module: symbol.definition.module,
+            target,
+        });
+    }
+    symbols
+}
+
+pub(crate) fn definition_kind(tree: &ItemTree, definition: Definition) -> SymbolKind {
+    match (&tree.items[definition.item], definition.member) {
+        (Item::Value(_), _) => SymbolKind::Value,
+        (Item::Data(_) | Item::Newtype(_), Some(_)) => SymbolKind::Constructor,
+        (Item::Data(_), None) => SymbolKind::Data,
+        (Item::Newtype(_), None) => SymbolKind::Newtype,
+        (Item::Synonym(_), _) => SymbolKind::Synonym,
+        (Item::Class(_), Some(_)) => SymbolKind::Member,
+        (Item::Class(_), None) => SymbolKind::Class,
+        (Item::Instance(_), _) => SymbolKind::Instance,
+        (Item::ForeignData(_), _) => SymbolKind::ForeignData,
+        (Item::ForeignValue(_), _) => SymbolKind::ForeignValue,
+        (Item::Fixity(_), _) => SymbolKind::Operator,
+    }
+}
+
+/// The declarations of a module in the order they're written in. Data types
+/// contain their constructors, classes their members, and instances their
+/// methods.
+pub fn document_symbols(db: &Database, file: FileId) -> Vec<DocumentSymbol> {
+    let parse = db.parse(file);
+    let module = parse.module();
+    let fixities = Fixities::from_module(&module);
+    let print =
+        |type_: Option<ast::Type>| type_.map(|type_| Type::from_ast(&type_, &fixities).to_string());
+
+    let mut symbols = vec![];
+    for declaration in module.declarations() {
+        let range = declaration.syntax().text_range();
+        match declaration {
+            ast::Declaration::Value(value) => {
+                value_symbol(&mut symbols, SymbolKind::Value, value.name(), range, None)
+            }
+            ast::Declaration::Annotation(annotation) => {
+                let detail = print(annotation.type_());
+                value_symbol(&mut symbols, SymbolKind::Value, annotation.name(), range, detail)
+            }
+            ast::Declaration::Data(data) => {
+                let Some(mut symbol) = symbol(SymbolKind::Data, data.name(), range, None) else {
+                    continue;
+                };
+                symbol.children = data.constructors().filter_map(constructor_symbol).collect();
+                symbols.push(symbol);
+            }
+            ast::Declaration::Newtype(newtype) => {
+                let Some(mut symbol) = symbol(SymbolKind::Newtype, newtype.name(), range, None)
+                else {
+                    continue;
+                };
+                symbol.children =
+                    newtype.constructor().and_then(constructor_symbol).into_iter().collect();
+                symbols.push(symbol);
+            }
+            ast::Declaration::Type(synonym) => {
+                let detail = print(synonym.type_());
+                symbols.extend(symbol(SymbolKind::Synonym, synonym.name(), range, detail));
+            }
+            ast::Declaration::Class(class) => {
+                let Some(mut symbol) = symbol(SymbolKind::Class, class.name(), range, None) else {
+                    continue;
+                };
+                symbol.children = class
+                    .members()
+                    .filter_map(|member| {
+                        let range = member.syntax().text_range();
+                        let detail = print(member.type_());
+                        self::symbol(SymbolKind::Member, member.name(), range, detail)
+                    })
+                    .collect();
+                symbols.push(symbol);
+            }
+            ast::Declaration::InstanceChain(chain) => {
+                for instance in chain.instances() {
+                    let range = instance.syntax().text_range();
+                    let Some(mut symbol) = instance_symbol(
+                        instance.instance_name(),
+                        instance.head(),
+                        range,
+                        &fixities,
+                    ) else {
+                        continue;
+                    };
+                    for member in instance.members() {
+                        let range = member.syntax().text_range();
+                        match member {
+                            ast::InstanceMember::Value(value) => value_symbol(
+                                &mut symbol.children,
+                                SymbolKind::Member,
+                                value.name(),
+                                range,
+                                None,
+                            ),
+                            ast::InstanceMember::Annotation(annotation) => value_symbol(
+                                &mut symbol.children,
+                                SymbolKind::Member,
+                                annotation.name(),
+                                range,
+                                print(annotation.type_()),
+                            ),
+                        }
+                    }
+                    symbols.push(symbol);
+                }
+            }
+            ast::Declaration::DeriveInstance(instance) => {
+                symbols.extend(instance_symbol(
+                    instance.instance_name(),
+                    instance.head(),
+                    range,
+                    &fixities,
+                ));
+            }
+            ast::Declaration::ForeignData(data) => {
+                let detail = print(data.kind());
+                symbols.extend(symbol(SymbolKind::ForeignData, data.name(), range, detail));
+            }
+            ast::Declaration::ForeignValue(value) => {
+                let detail = print(value.type_());
+                symbols.extend(symbol(SymbolKind::ForeignValue, value.name(), range, detail));
+            }
+            ast::Declaration::Fixity(fixity) => {
+                let detail = fixity.target().map(|target| target.syntax().text().to_string());
+                symbols.extend(symbol(SymbolKind::Operator, fixity.operator(), range, detail));
+            }
+            // Kind signatures belong to the declarations that follow them.
+            ast::Declaration::KindSignature(_) => {}
+        }
+    }
+    symbols
+}
+
+fn symbol(
+    kind: SymbolKind,
+    name: Option<ast::Name>,
+    full_range: TextRange,
+    detail: Option<String>,
+) -> Option<DocumentSymbol> {
+    let name = name?;
+    let token = name.token()?;
+    Some(DocumentSymbol {
+        name: token.text().to_string(),
+        detail,
+        kind,
+        full_range,
+        focus_range: name.syntax().text_range(),
+        children: vec![],
+    })
+}
+
+/// Adds a signature or an equation of a value, which extends the symbol of
+/// the signature or equations before it.
+fn value_symbol(
+    symbols: &mut Vec<DocumentSymbol>,
+    kind: SymbolKind,
+    name: Option<ast::Name>,
+    range: TextRange,
+    detail: Option<String>,
+) {
+    let Some(new) = symbol(kind, name, range, detail) else { return };
+    match symbols.last_mut() {
+        Some(last) if last.kind == kind && last.name == new.name => {
+            last.full_range = last.full_range.cover(range);
+            last.detail = last.detail.take().or(new.detail);
+        }
+        _ => symbols.push(new),
+    }
+}
+
+fn constructor_symbol(constructor: ast::DataConstructor) -> Option<DocumentSymbol> {
+    let range = constructor.syntax().text_range();
+    symbol(SymbolKind::Constructor, constructor.name(), range, None)
+}
+
+/// Instances are named by their head, or by their name with their head as
+/// the detail if they have one.
+fn instance_symbol(
+    name: Option<ast::InstanceName>,
+    head: Option<ast::InstanceHead>,
+    full_range: TextRange,
+    fixities: &Fixities,
+) -> Option<DocumentSymbol> {
+    let head = head?;
+    let class = head.class_name()?;
+    let arguments = head.arguments().map(|argument| Type::from_ast(&argument, fixities)).collect();
+    let printed = Type::application(Type::atom(class.syntax().text().to_string()), arguments);
+    let printed = printed.to_string();
+    match name.and_then(|name| name.name()) {
+        Some(name) => symbol(SymbolKind::Instance, Some(name), full_range, Some(printed)),
+        None => Some(DocumentSymbol {
+            name: printed,
+            detail: None,
+            kind: SymbolKind::Instance,
+            full_range,
+            focus_range: head.syntax().text_range(),
+            children: vec![],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+
+    use super::{document_symbols, workspace_symbols, DocumentSymbol};
+
+    #[test]
+    fn outline() {
+        let mut db = Database::new();
+        let source = "module Main where\n\ndata Maybe a = Just a | Nothing\n\nclass Show a where\n  show :: a -> String\n\ninstance Show Int where\n  show _ = \"\"\n\nanswer :: Int\nanswer = 1\n\nfact 0 = 1\nfact n = n\n";
+        db.set_file_text(FileId(0), source);
+
+        fn outline(symbol: &DocumentSymbol) -> String {
+            let mut text = symbol.name.clone();
+            if let Some(detail) = &symbol.detail {
+                text.push_str(&format!(" :: {detail}"));
+            }
+            if !symbol.children.is_empty() {
+                let children: Vec<_> = symbol.children.iter().map(outline).collect();
+                text.push_str(&format!(" [{}]", children.join(", ")));
+            }
+            text
+        }
+        let symbols = document_symbols(&db, FileId(0));
+        let outlines: Vec<_> = symbols.iter().map(outline).collect();
+        assert_eq!(
+            outlines,
+            [
+                "Maybe [Just, Nothing]",
+                "Show [show :: a -> String]",
+                "Show Int [show]",
+                "answer :: Int",
+                "fact",
+            ]
+        );
+        // Equations of a value are grouped with their signature.
+        let answer = &source[symbols[3].full_range];
+        assert!(answer.starts_with("answer ::") && answer.trim_end().ends_with("answer = 1"));
+    }
+
+    #[test]
+    fn search_workspace() {
+        let mut db = Database::new();
+        db.set_file_text(FileId(0), "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe a _ = a\n\nmaybe b _ _ = b\n");
+        db.set_file_text(FileId(1), "module Main where\n\nmain = 0\n");
+
+        let names = |query: &str| {
+            let symbols = workspace_symbols(&db, query);
+            symbols.into_iter().map(|symbol| symbol.name).collect::<Vec<_>>()
+        };
+        assert_eq!(names("maybe"), ["maybe", "Maybe", "fromMaybe"]);
+        assert_eq!(names("fM"), ["fromMaybe"]);
+        assert_eq!(names("ma"), ["main", "Maybe", "maybe", "fromMaybe"]);
+    }
+}
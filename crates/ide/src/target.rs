@@ -0,0 +1,251 @@
+//! What the name under the cursor refers to, which most features start from.
+
+use std::rc::Rc;
+
+use analysis::{Database, FileId, Lowered, ModuleId, Parse};
+use lowering::{
+    ast_id::AstIdMap,
+    hir::{BinderId, BodyId},
+    item_tree::{Item, ItemTree},
+    lower_module_name, lower_name,
+    name::{ModuleName, Name, QualifiedName},
+};
+use resolution::{
+    body::{ModuleScope, Resolution},
+    interface::{Definition, Namespace},
+    reference::{resolve_at_offset, Reference, ReferenceKind, Target},
+};
+use rowan::{ast::AstNode, Direction, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+/// A place in the workspace to navigate to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavigationTarget {
+    pub file: FileId,
+    /// The range of the whole definition.
+    pub full_range: TextRange,
+    /// The range of its name, which is where the cursor goes.
+    pub focus_range: TextRange,
+}
+
+/// A range within a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileRange {
+    pub file: FileId,
+    pub range: TextRange,
+}
+
+/// The name at `offset` in `file`, along with what it refers to. The name of
+/// a declaration refers to the declaration itself.
+pub(crate) fn reference_at(db: &Database, file: FileId, offset: TextSize) -> Option<Reference> {
+    FileResolver::new(db, file)?.reference_at(offset)
+}
+
+/// Resolves the names of a file, keeping what they're resolved against for
+/// features that resolve many of them.
+pub(crate) struct FileResolver<'a> {
+    db: &'a Database,
+    file: FileId,
+    parse: Rc<Parse>,
+    lowered: Rc<Lowered>,
+    scope: Rc<ModuleScope>,
+    trees: Vec<(ModuleName, Rc<ItemTree>)>,
+}
+
+impl<'a> FileResolver<'a> {
+    pub(crate) fn new(db: &'a Database, file: FileId) -> Option<FileResolver<'a>> {
+        let module = ModuleId::File(file);
+        let scope = db.scope(module)?;
+        let trees = visible_trees(db, module);
+        Some(FileResolver {
+            db,
+            file,
+            parse: db.parse(file),
+            lowered: db.lower(file),
+            scope,
+            trees,
+        })
+    }
+
+    pub(crate) fn reference_at(&self, offset: TextSize) -> Option<Reference> {
+        let trees = &self.trees;
+        let tree = |name| trees.iter().find(|(module, _)| *module == name).map(|(_, tree)| &**tree);
+        let Lowered { module, source_map } = &*self.lowered;
+        let reference =
+            resolve_at_offset(&self.parse.root, module, source_map, &self.scope, tree, offset);
+        reference
+            .or_else(|| declaration_at(self.db, self.file, offset))
+            .or_else(|| listed_name_at(self.db, self.file, offset))
+    }
+}
+
+/// The module that a definition seen from `file` belongs to.
+pub(crate) fn definition_module(
+    db: &Database,
+    file: FileId,
+    definition: Definition,
+) -> Option<ModuleId> {
+    db.resolve_module(ModuleId::File(file), definition.module)
+}
+
+/// The item tree of every module that `module` can see, by name.
+pub(crate) fn visible_trees(db: &Database, module: ModuleId) -> Vec<(ModuleName, Rc<ItemTree>)> {
+    let names = db.modules().keys().copied().collect::<Vec<_>>();
+    let trees = names.into_iter().filter_map(|name| {
+        let tree = db.module_tree(db.resolve_module(module, name)?)?;
+        Some((name, tree))
+    });
+    trees.collect()
+}
+
+/// The declaration whose name is at `offset`, including constructors and
+/// class members.
+fn declaration_at(db: &Database, file: FileId, offset: TextSize) -> Option<Reference> {
+    let parse = db.parse(file);
+    let token = parse
+        .root
+        .token_at_offset(offset)
+        .find(|token| token.parent().is_some_and(|parent| ast::Name::can_cast(parent.kind())))?;
+    let name = ast::Name::cast(token.parent()?)?;
+    let declaration = name.syntax().parent()?;
+    let namespaces: &[Namespace] = match declaration.kind() {
+        SyntaxKind::ValueDeclaration
+        | SyntaxKind::AnnotationDeclaration
+        | SyntaxKind::ForeignValueDeclaration
+        | SyntaxKind::ClassMember => &[Namespace::Value],
+        SyntaxKind::DataDeclaration
+        | SyntaxKind::NewtypeDeclaration
+        | SyntaxKind::TypeDeclaration
+        | SyntaxKind::ForeignDataDeclaration => &[Namespace::Type],
+        SyntaxKind::ClassDeclaration => &[Namespace::Class],
+        SyntaxKind::DataConstructor => &[Namespace::Constructor],
+        SyntaxKind::FixityDeclaration => &[Namespace::Operator, Namespace::TypeOperator],
+        _ => return None,
+    };
+    // Instance members and local bindings aren't declarations of the module.
+    if matches!(
+        declaration.kind(),
+        SyntaxKind::ValueDeclaration | SyntaxKind::AnnotationDeclaration
+    ) && !ast::Module::can_cast(declaration.parent()?.kind())
+    {
+        return None;
+    }
+    let scope = db.scope(ModuleId::File(file))?;
+    let name = lower_name(Some(name));
+    let (&namespace, &definition) = namespaces.iter().find_map(|namespace| {
+        Some((namespace, scope.declarations.names.get(*namespace).get(&name)?))
+    })?;
+    Some(Reference {
+        kind: ReferenceKind::Name(namespace),
+        range: token.text_range(),
+        target: Target::Global(definition),
+    })
+}
+
+/// A name in an import or export list, which refers to what the imported
+/// module exports, or what's in scope of the module that exports it.
+fn listed_name_at(db: &Database, file: FileId, offset: TextSize) -> Option<Reference> {
+    let parse = db.parse(file);
+    let token = parse
+        .root
+        .token_at_offset(offset)
+        .find(|token| token.parent().is_some_and(|parent| ast::NameRef::can_cast(parent.kind())))?;
+    let item = token.parent()?.parent()?;
+    let namespace = match item.kind() {
+        SyntaxKind::ImportValue | SyntaxKind::ExportValue => Namespace::Value,
+        SyntaxKind::ImportOperator | SyntaxKind::ExportOperator => Namespace::Operator,
+        SyntaxKind::ImportType | SyntaxKind::ExportType => Namespace::Type,
+        SyntaxKind::ImportTypeOperator | SyntaxKind::ExportTypeOperator => Namespace::TypeOperator,
+        SyntaxKind::ImportClass | SyntaxKind::ExportClass => Namespace::Class,
+        SyntaxKind::DataEnumerated => Namespace::Constructor,
+        _ => return None,
+    };
+    let name = Name::new(token.text());
+    let module = ModuleId::File(file);
+    let target = match token.parent_ancestors().find_map(ast::ImportDeclaration::cast) {
+        Some(import) => {
+            let imported = db.resolve_module(module, lower_module_name(&import.module_name()?))?;
+            let exports = db.exports(imported);
+            Target::Global(*exports.interface.names.get(namespace).get(&name)?)
+        }
+        None => match db.scope(module)?.resolve(namespace, QualifiedName::unqualified(name))? {
+            Resolution::Global(definition) => Target::Global(definition),
+            Resolution::Prim(name) => Target::Prim(name),
+            Resolution::Ambiguous(definitions) => Target::Ambiguous(definitions),
+            Resolution::Local(_) => return None,
+        },
+    };
+    Some(Reference { kind: ReferenceKind::Name(namespace), range: token.text_range(), target })
+}
+
+/// The syntax of a definition, which is only known for modules that were
+/// loaded from source. Values are found at their signature if they have
+/// one, and constructors and class members within their declarations.
+pub(crate) fn definition_node(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+) -> Option<(FileId, SyntaxNode)> {
+    let ModuleId::File(file) = module else { return None };
+    let tree = db.item_tree(file);
+    let parse = db.parse(file);
+    let pointer =
+        AstIdMap::from_module(&parse.module()).get_erased(tree.ast_id(definition.item)?)?;
+    let node = pointer.to_node(&parse.root);
+    let node = match (&tree.items[definition.item], definition.member) {
+        (Item::Data(_) | Item::Newtype(_), Some(index)) => {
+            node.children().filter(|node| node.kind() == SyntaxKind::DataConstructor).nth(index)?
+        }
+        (Item::Class(_), Some(index)) => {
+            node.children().filter(|node| node.kind() == SyntaxKind::ClassMember).nth(index)?
+        }
+        (Item::Value(item), _) => {
+            let signature = node.siblings(Direction::Prev).skip(1).find_map(|node| {
+                let annotation = ast::AnnotationDeclaration::cast(node)?;
+                (lower_name(annotation.name()) == item.name).then_some(annotation)
+            });
+            signature.map_or(node, |signature| signature.syntax().clone())
+        }
+        _ => node,
+    };
+    Some((file, node))
+}
+
+pub(crate) fn definition_target(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+) -> Option<NavigationTarget> {
+    let (file, node) = definition_node(db, module, definition)?;
+    Some(node_target(file, &node))
+}
+
+/// The binder that introduces a local.
+pub(crate) fn local_target(
+    db: &Database,
+    file: FileId,
+    body: BodyId,
+    binder: BinderId,
+) -> Option<NavigationTarget> {
+    let pointer = db.lower(file).source_map.body(body).binder_syntax(binder)?.clone();
+    let node = pointer.to_node(&db.parse(file).root);
+    let name = node.descendants().find(|node| node.kind() == SyntaxKind::Name);
+    let focus_range = name.map_or(node.text_range(), |name| name.text_range());
+    Some(NavigationTarget { file, full_range: node.text_range(), focus_range })
+}
+
+/// The header of a module, or the start of its file if it has none.
+pub(crate) fn module_target(db: &Database, file: FileId) -> NavigationTarget {
+    let empty = TextRange::empty(TextSize::from(0));
+    let Some(header) = db.parse(file).module().header() else {
+        return NavigationTarget { file, full_range: empty, focus_range: empty };
+    };
+    let focus_range = header.name().map_or(empty, |name| name.syntax().text_range());
+    NavigationTarget { file, full_range: header.syntax().text_range(), focus_range }
+}
+
+fn node_target(file: FileId, node: &SyntaxNode) -> NavigationTarget {
+    let name = node.children().find(|node| node.kind() == SyntaxKind::Name);
+    let focus_range = name.map_or(node.text_range(), |name| name.text_range());
+    NavigationTarget { file, full_range: node.text_range(), focus_range }
+}
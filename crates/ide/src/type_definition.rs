@@ -0,0 +1,221 @@
+//! Where the type of the value under the cursor is defined.
+
+use analysis::{Database, FileId, ModuleId};
+use lowering::{
+    hir::{Declaration, Type, TypeId},
+    item_tree::{Item, ItemTree},
+};
+use resolution::{
+    body::{ModuleScope, Resolution},
+    interface::{Definition, Namespace},
+    reference::Target,
+};
+use rowan::TextSize;
+use typecheck::ty::{Ty, TyId, Types};
+
+use crate::target::{definition_module, definition_target, reference_at, NavigationTarget};
+
+/// The declarations of the types that make up the type of the value at
+/// `offset`, in the order they're written in. Declared signatures are
+/// preferred over inferred types, since only they refer to synonyms.
+pub fn type_definition(db: &Database, file: FileId, offset: TextSize) -> Vec<NavigationTarget> {
+    let mut definitions = vec![];
+    if let Some(reference) = reference_at(db, file, offset) {
+        match reference.target {
+            Target::Global(definition) => {
+                if let Some(module) = definition_module(db, file, definition) {
+                    declared_types(db, module, definition, &mut definitions);
+                }
+            }
+            Target::Local { body, binder } => {
+                if let Some(types) = db.infer(file).get(body) {
+                    if let Some(ty) = types.binder(binder) {
+                        inferred_types(&types.types, ty, &mut definitions);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if definitions.is_empty() {
+        expression_types(db, file, offset, &mut definitions);
+    }
+
+    let mut targets: Vec<NavigationTarget> = vec![];
+    for definition in definitions {
+        let Some(module) = definition_module(db, file, definition) else { continue };
+        if let Some(target) = definition_target(db, module, definition) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    targets
+}
+
+/// The types in the signature of a definition, or the type that a
+/// constructor belongs to.
+fn declared_types(
+    db: &Database,
+    module: ModuleId,
+    definition: Definition,
+    definitions: &mut Vec<Definition>,
+) {
+    let (Some(tree), Some(scope)) = (db.module_tree(module), db.scope(module)) else { return };
+    let signature = match (&tree.items[definition.item], definition.member) {
+        (Item::Value(item), _) => item.signature,
+        (Item::ForeignValue(item), _) => Some(item.type_),
+        (Item::Class(item), Some(index)) => item.members.get(index).map(|member| member.type_),
+        (Item::Data(_) | Item::Newtype(_), Some(_)) => {
+            definitions.push(Definition { member: None, ..definition });
+            return;
+        }
+        _ => return,
+    };
+    match signature {
+        Some(signature) => signature_types(&tree, &scope, signature, definitions),
+        // Values without a signature have the type inferred for their body.
+        None => {
+            let (ModuleId::File(file), Item::Value(item)) = (module, &tree.items[definition.item])
+            else {
+                return;
+            };
+            let lowered = db.lower(file);
+            let body =
+                lowered.module.declarations.iter().find_map(|(_, declaration)| match declaration {
+                    Declaration::Value(value) if value.name == item.name => Some(value.body),
+                    _ => None,
+                });
+            let inferred = db.infer(file);
+            if let Some(types) = body.and_then(|body| inferred.get(body)) {
+                if let Some(ty) = types.ty {
+                    inferred_types(&types.types, ty, definitions);
+                }
+            }
+        }
+    }
+}
+
+/// The type of the innermost expression at `offset`.
+fn expression_types(
+    db: &Database,
+    file: FileId,
+    offset: TextSize,
+    definitions: &mut Vec<Definition>,
+) {
+    let parse = db.parse(file);
+    let lowered = db.lower(file);
+    let Some(token) = parse.root.token_at_offset(offset).find(|token| !token.kind().is_trivia())
+    else {
+        return;
+    };
+    for node in token.parent_ancestors() {
+        for (body, _) in lowered.module.bodies.iter() {
+            let Some(expr) = lowered.source_map.body(body).node_expr(&node) else { continue };
+            let inferred = db.infer(file);
+            let Some(types) = inferred.get(body) else { return };
+            if let Some(ty) = types.expr(expr) {
+                inferred_types(&types.types, ty, definitions);
+            }
+            return;
+        }
+    }
+}
+
+fn signature_types(
+    tree: &ItemTree,
+    scope: &ModuleScope,
+    id: TypeId,
+    definitions: &mut Vec<Definition>,
+) {
+    let mut go = |id| signature_types(tree, scope, id, definitions);
+    match &tree.types[id] {
+        Type::Forall { type_, .. } | Type::Kinded { type_, .. } => go(*type_),
+        // Constraints refer to classes rather than types.
+        Type::Constrained { type_, .. } => go(*type_),
+        Type::Arrow { argument, result } => {
+            go(*argument);
+            go(*result);
+        }
+        Type::OperatorChain { head, tail } => {
+            go(*head);
+            for &(_, type_) in tail {
+                go(type_);
+            }
+        }
+        Type::Application { function, arguments } => {
+            go(*function);
+            for &argument in arguments {
+                go(argument);
+            }
+        }
+        Type::Record(row) | Type::Row(row) => {
+            for &(_, type_) in &row.fields {
+                go(type_);
+            }
+            if let Some(tail) = row.tail {
+                go(tail);
+            }
+        }
+        &Type::Constructor(name) => {
+            if let Some(Resolution::Global(definition)) = scope.resolve(Namespace::Type, name) {
+                if !definitions.contains(&definition) {
+                    definitions.push(definition);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn inferred_types(types: &Types, ty: TyId, definitions: &mut Vec<Definition>) {
+    match &types[ty] {
+        &Ty::Application(function, argument) => {
+            inferred_types(types, function, definitions);
+            inferred_types(types, argument, definitions);
+        }
+        &Ty::Forall(_, _, ty) | &Ty::Constrained(_, ty) => inferred_types(types, ty, definitions),
+        Ty::Row(fields, tail) => {
+            for &(_, ty) in fields {
+                inferred_types(types, ty, definitions);
+            }
+            if let &Some(tail) = tail {
+                inferred_types(types, tail, definitions);
+            }
+        }
+        Ty::Constructor(constructor) => {
+            if let Some(definition) = constructor.definition {
+                if !definitions.contains(&definition) {
+                    definitions.push(definition);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis::{Database, FileId};
+    use rowan::TextSize;
+
+    use super::type_definition;
+
+    #[test]
+    fn go_to_type_definitions() {
+        let mut db = Database::new();
+        let source = "module Main where\n\ndata Maybe a = Just a | Nothing\n\nnewtype Name = Name String\n\ntype Names = Array Name\n\nnames :: Names\nnames = []\n\nfirst = Just (Name \"a\")\n\nmain = first\n";
+        db.set_file_text(FileId(0), source);
+
+        let type_definition_at = |text: &str| {
+            let offset = TextSize::from(source.rfind(text).unwrap() as u32);
+            let targets = type_definition(&db, FileId(0), offset);
+            targets.into_iter().map(|target| &source[target.focus_range]).collect::<Vec<_>>()
+        };
+        // Synonyms are only known from signatures.
+        assert_eq!(type_definition_at("names ="), ["Names"]);
+        assert_eq!(type_definition_at("first"), ["Maybe", "Name"]);
+        assert_eq!(type_definition_at("Just"), ["Maybe"]);
+        assert_eq!(type_definition_at("\"a\""), Vec::<&str>::new());
+    }
+}
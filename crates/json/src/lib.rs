@@ -0,0 +1,360 @@
+//! JSON values, as exchanged with the client and written by `purs`.
+//!
+//! Readers only need to pick a few fields out of what they're given, and
+//! the server only builds small responses, so rather than deriving types
+//! for every message, they're read and written as a [`Json`] tree. Objects
+//! keep their fields in order, such that responses are written the way they
+//! were built.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document, returning the byte offset of the first
+    /// unexpected character if it's malformed.
+    pub fn parse(source: &str) -> Result<Json, usize> {
+        let mut parser = Parser { source: source.as_bytes(), offset: 0 };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.offset == parser.source.len() {
+            Ok(value)
+        } else {
+            Err(parser.offset)
+        }
+    }
+
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// The field of an object, where a field that's missing is `null`.
+    pub fn get(&self, key: &str) -> &Json {
+        self.field(key).unwrap_or(&Json::Null)
+    }
+
+    /// The field of an object, if it has one by that name.
+    pub fn field(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => {
+                fields.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            &Json::Boolean(boolean) => Some(boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            &Json::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// The value of a number that's a non-negative integer, such as a line.
+    pub fn as_u32(&self) -> Option<u32> {
+        let number = self.as_f64()?;
+        (number.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&number))
+            .then_some(number as u32)
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        let number = self.as_f64()?;
+        (number.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(&number))
+            .then_some(number as i32)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(boolean: bool) -> Json {
+        Json::Boolean(boolean)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(string: &str) -> Json {
+        Json::String(string.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(string: String) -> Json {
+        Json::String(string)
+    }
+}
+
+impl From<i32> for Json {
+    fn from(number: i32) -> Json {
+        Json::Number(number.into())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(number: u32) -> Json {
+        Json::Number(number.into())
+    }
+}
+
+impl From<usize> for Json {
+    fn from(number: usize) -> Json {
+        Json::Number(number as f64)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(elements: Vec<Json>) -> Json {
+        Json::Array(elements)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+/// Writes the value compactly, as it's sent to the client.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Boolean(boolean) => write!(f, "{boolean}"),
+            Json::Number(number) if number.is_finite() => write!(f, "{number}"),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(string) => write_string(f, string),
+            Json::Array(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, string: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for character in string.chars() {
+        match character {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            character if character.is_control() => write!(f, "\\u{:04x}", character as u32)?,
+            character => write!(f, "{character}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn whitespace(&mut self) {
+        while self.source.get(self.offset).is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.whitespace();
+        self.source.get(self.offset).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), usize> {
+        if self.peek() != Some(byte) {
+            return Err(self.offset);
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, usize> {
+        if !self.source[self.offset..].starts_with(keyword.as_bytes()) {
+            return Err(self.offset);
+        }
+        self.offset += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, usize> {
+        match self.peek().ok_or(self.offset)? {
+            b'n' => self.keyword("null", Json::Null),
+            b't' => self.keyword("true", Json::Boolean(true)),
+            b'f' => self.keyword("false", Json::Boolean(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => {
+                self.offset += 1;
+                let mut elements = vec![];
+                if self.peek() == Some(b']') {
+                    self.offset += 1;
+                    return Ok(Json::Array(elements));
+                }
+                loop {
+                    elements.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b']')?;
+                Ok(Json::Array(elements))
+            }
+            b'{' => {
+                self.offset += 1;
+                let mut fields = vec![];
+                if self.peek() == Some(b'}') {
+                    self.offset += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.offset);
+                    }
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b'}')?;
+                Ok(Json::Object(fields))
+            }
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, usize> {
+        let start = self.offset;
+        let is_number = |byte: &u8| byte.is_ascii_digit() || b"+-.eE".contains(byte);
+        while self.source.get(self.offset).is_some_and(is_number) {
+            self.offset += 1;
+        }
+        let text = std::str::from_utf8(&self.source[start..self.offset]).map_err(|_| start)?;
+        text.parse().map(Json::Number).map_err(|_| start)
+    }
+
+    fn string(&mut self) -> Result<String, usize> {
+        self.expect(b'"')?;
+        let mut bytes = vec![];
+        loop {
+            let &byte = self.source.get(self.offset).ok_or(self.offset)?;
+            self.offset += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let &escape = self.source.get(self.offset).ok_or(self.offset)?;
+                    self.offset += 1;
+                    let character = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.offset - 1),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.offset)
+    }
+
+    /// Decodes the digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, usize> {
+        let high = self.code_unit()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        if !self.source[self.offset..].starts_with(b"\\u") {
+            return Ok(char::REPLACEMENT_CHARACTER);
+        }
+        self.offset += 2;
+        let low = self.code_unit()?;
+        let code_point = 0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+        Ok(char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn code_unit(&mut self) -> Result<u32, usize> {
+        let digits = self.source.get(self.offset..self.offset + 4).ok_or(self.offset)?;
+        let digits = std::str::from_utf8(digits).map_err(|_| self.offset)?;
+        let code_unit = u32::from_str_radix(digits, 16).map_err(|_| self.offset)?;
+        self.offset += 4;
+        Ok(code_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn write_what_was_read() {
+        let source = r#"{"id":1,"params":{"text":"a\n\"b\"\u0001","items":[true,null,-2.5]}}"#;
+        let json = Json::parse(source).unwrap();
+        assert_eq!(json.get("id").as_u32(), Some(1));
+        assert_eq!(json.get("params").get("text").as_str(), Some("a\n\"b\"\u{1}"));
+        assert!(json.get("missing").get("field").is_null());
+        assert_eq!(json.field("missing"), None);
+        assert_eq!(json.to_string(), source);
+    }
+}
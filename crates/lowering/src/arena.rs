@@ -0,0 +1,166 @@
+//! Typed arenas for HIR nodes.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// An index into an [`Arena`] of `T`.
+pub struct Idx<T> {
+    raw: u32,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Idx<T> {
+    pub fn from_raw(raw: u32) -> Idx<T> {
+        Idx { raw, phantom: PhantomData }
+    }
+
+    pub fn into_raw(self) -> u32 {
+        self.raw
+    }
+}
+
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Idx<T> {}
+
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for Idx<T> {}
+
+impl<T> PartialOrd for Idx<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Idx<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or("?");
+        write!(f, "Idx::<{}>({})", name, self.raw)
+    }
+}
+
+/// An append-only collection of `T`, indexed by [`Idx`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Arena<T> {
+    data: Vec<T>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena { data: Vec::new() }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn alloc(&mut self, value: T) -> Idx<T> {
+        let raw = self.data.len() as u32;
+        self.data.push(value);
+        Idx::from_raw(raw)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Idx<T>, &T)> {
+        self.data.iter().enumerate().map(|(index, value)| (Idx::from_raw(index as u32), value))
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+}
+
+impl<T> Index<Idx<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: Idx<T>) -> &T {
+        &self.data[index.raw as usize]
+    }
+}
+
+impl<T> IndexMut<Idx<T>> for Arena<T> {
+    fn index_mut(&mut self, index: Idx<T>) -> &mut T {
+        &mut self.data[index.raw as usize]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter().map(|(index, value)| (index.raw, value))).finish()
+    }
+}
+
+/// A map from the indices of an [`Arena`] to values of `V`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ArenaMap<K, V> {
+    data: Vec<Option<V>>,
+    phantom: PhantomData<fn(K)>,
+}
+
+impl<K, V> Default for ArenaMap<K, V> {
+    fn default() -> Self {
+        ArenaMap { data: Vec::new(), phantom: PhantomData }
+    }
+}
+
+impl<T, V> ArenaMap<Idx<T>, V> {
+    pub fn insert(&mut self, index: Idx<T>, value: V) {
+        let index = index.raw as usize;
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, || None);
+        }
+        self.data[index] = Some(value);
+    }
+
+    pub fn get(&self, index: Idx<T>) -> Option<&V> {
+        self.data.get(index.raw as usize).and_then(Option::as_ref)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Idx<T>, &V)> {
+        self.data
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((Idx::from_raw(index as u32), value.as_ref()?)))
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for ArenaMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.data.iter().enumerate();
+        f.debug_map().entries(entries.filter_map(|(i, v)| Some((i, v.as_ref()?)))).finish()
+    }
+}
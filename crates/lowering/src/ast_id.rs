@@ -0,0 +1,201 @@
+//! Stable identifiers for top-level declarations.
+//!
+//! An [`AstId`] identifies a declaration by its kind and its position among
+//! declarations of the same kind, e.g. the second data declaration in a file.
+//! Unlike byte offsets, these survive edits to unrelated declarations, and
+//! edits inside of declarations, which makes them suitable as keys for
+//! cross-file references.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use rowan::ast::{AstNode, AstPtr, SyntaxNodePtr};
+use rustc_hash::FxHashMap;
+use syntax::{ast, PureScript, SyntaxNode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AstIdKind {
+    Value,
+    Annotation,
+    KindSignature,
+    Data,
+    Newtype,
+    Type,
+    Class,
+    InstanceChain,
+    DeriveInstance,
+    ForeignData,
+    ForeignValue,
+    Fixity,
+}
+
+/// An [`AstId`] without the type of its node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErasedAstId {
+    pub kind: AstIdKind,
+    pub index: u32,
+}
+
+/// A stable identifier for a declaration of type `N`.
+pub struct AstId<N> {
+    erased: ErasedAstId,
+    phantom: PhantomData<fn() -> N>,
+}
+
+impl<N> AstId<N> {
+    pub fn erased(self) -> ErasedAstId {
+        self.erased
+    }
+}
+
+impl<N> Clone for AstId<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N> Copy for AstId<N> {}
+
+impl<N> PartialEq for AstId<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.erased == other.erased
+    }
+}
+
+impl<N> Eq for AstId<N> {}
+
+impl<N> Hash for AstId<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.erased.hash(state)
+    }
+}
+
+impl<N> fmt::Debug for AstId<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AstId({:?}, {})", self.erased.kind, self.erased.index)
+    }
+}
+
+/// Nodes that are assigned an [`AstId`].
+pub trait AstIdNode: AstNode<Language = PureScript> {
+    const KIND: AstIdKind;
+}
+
+macro_rules! ast_id_node {
+    ($($node:ident => $kind:ident),* $(,)?) => {$(
+        impl AstIdNode for ast::$node {
+            const KIND: AstIdKind = AstIdKind::$kind;
+        }
+    )*};
+}
+
+ast_id_node!(
+    ValueDeclaration => Value,
+    AnnotationDeclaration => Annotation,
+    KindSignatureDeclaration => KindSignature,
+    DataDeclaration => Data,
+    NewtypeDeclaration => Newtype,
+    TypeDeclaration => Type,
+    ClassDeclaration => Class,
+    InstanceChain => InstanceChain,
+    DeriveInstanceDeclaration => DeriveInstance,
+    ForeignDataDeclaration => ForeignData,
+    ForeignValueDeclaration => ForeignValue,
+    FixityDeclaration => Fixity,
+);
+
+/// Maps between the top-level declarations of a file and their [`AstId`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AstIdMap {
+    pointers: FxHashMap<ErasedAstId, SyntaxNodePtr<PureScript>>,
+    ids: FxHashMap<SyntaxNodePtr<PureScript>, ErasedAstId>,
+}
+
+impl AstIdMap {
+    pub fn from_module(module: &ast::Module) -> AstIdMap {
+        let mut map = AstIdMap::default();
+        let mut counts: FxHashMap<AstIdKind, u32> = FxHashMap::default();
+        for declaration in module.declarations() {
+            let kind = declaration_kind(&declaration);
+            let index = counts.entry(kind).or_default();
+            let id = ErasedAstId { kind, index: *index };
+            *index += 1;
+            let pointer = SyntaxNodePtr::new(declaration.syntax());
+            map.pointers.insert(id, pointer.clone());
+            map.ids.insert(pointer, id);
+        }
+        map
+    }
+
+    pub fn ast_id<N: AstIdNode>(&self, node: &N) -> Option<AstId<N>> {
+        let erased = self.erased_ast_id(node.syntax())?;
+        Some(AstId { erased, phantom: PhantomData })
+    }
+
+    pub fn erased_ast_id(&self, node: &SyntaxNode) -> Option<ErasedAstId> {
+        self.ids.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    pub fn get<N: AstIdNode>(&self, id: AstId<N>) -> AstPtr<N> {
+        self.pointers[&id.erased].clone().cast().unwrap()
+    }
+
+    pub fn get_erased(&self, id: ErasedAstId) -> Option<SyntaxNodePtr<PureScript>> {
+        self.pointers.get(&id).cloned()
+    }
+}
+
+/// Returns the kind of a declaration's [`AstId`].
+pub fn declaration_kind(declaration: &ast::Declaration) -> AstIdKind {
+    match declaration {
+        ast::Declaration::Value(_) => AstIdKind::Value,
+        ast::Declaration::Annotation(_) => AstIdKind::Annotation,
+        ast::Declaration::KindSignature(_) => AstIdKind::KindSignature,
+        ast::Declaration::Data(_) => AstIdKind::Data,
+        ast::Declaration::Newtype(_) => AstIdKind::Newtype,
+        ast::Declaration::Type(_) => AstIdKind::Type,
+        ast::Declaration::Class(_) => AstIdKind::Class,
+        ast::Declaration::InstanceChain(_) => AstIdKind::InstanceChain,
+        ast::Declaration::DeriveInstance(_) => AstIdKind::DeriveInstance,
+        ast::Declaration::ForeignData(_) => AstIdKind::ForeignData,
+        ast::Declaration::ForeignValue(_) => AstIdKind::ForeignValue,
+        ast::Declaration::Fixity(_) => AstIdKind::Fixity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{AstIdKind, AstIdMap};
+
+    fn data_ids(source: &str) -> Vec<(super::ErasedAstId, String)> {
+        let (node, _) = parsing::parse_module(source);
+        let module = ast::Module::cast(node).unwrap();
+        let map = AstIdMap::from_module(&module);
+        module
+            .declarations()
+            .filter_map(|declaration| match declaration {
+                ast::Declaration::Data(data) => {
+                    let id = map.ast_id(&data)?;
+                    let node = map.get(id).to_node(module.syntax());
+                    Some((id.erased(), node.name()?.syntax().to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unrelated_edits_preserve_ids() {
+        let before = data_ids("module Main where\n\ndata A = A\n\ndata B = B\n");
+        let after = data_ids("module Main where\n\ndata A = A\n\nf x = x\n\ng = 0\n\ndata B = B\n");
+        assert_eq!(before, after);
+        assert_eq!(before[1].0.kind, AstIdKind::Data);
+        assert_eq!(before[1].0.index, 1);
+    }
+}
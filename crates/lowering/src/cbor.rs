@@ -0,0 +1,300 @@
+//! A minimal CBOR decoder for the `externs.cbor` files that `purs` writes to
+//! its output directory.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cbor {
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    /// A value with a semantic tag, such as a big integer.
+    Tagged(u64, Box<Cbor>),
+    Boolean(bool),
+    Null,
+    Float(f64),
+}
+
+impl Cbor {
+    /// Decodes a CBOR item, returning the byte offset where decoding failed
+    /// if it's malformed or followed by anything else.
+    pub(crate) fn parse(source: &[u8]) -> Result<Cbor, usize> {
+        let mut decoder = Decoder { source, offset: 0 };
+        let value = decoder.value()?;
+        if decoder.offset == source.len() {
+            Ok(value)
+        } else {
+            Err(decoder.offset)
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Cbor]> {
+        match self {
+            Cbor::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_integer(&self) -> Option<i128> {
+        match self {
+            &Cbor::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    /// The constructor index and fields of a value encoded the way that
+    /// `Generic` instances of `Serialise` encode them: a list of the index
+    /// followed by the fields.
+    pub(crate) fn as_constructor(&self) -> Option<(i128, &[Cbor])> {
+        let (tag, fields) = self.as_array()?.split_first()?;
+        Some((tag.as_integer()?, fields))
+    }
+}
+
+/// What the value of an item's initial byte is once its major type is
+/// masked off, when it's followed by a break rather than a length.
+const INDEFINITE: u8 = 31;
+
+const BREAK: u8 = 0xff;
+
+struct Decoder<'a> {
+    source: &'a [u8],
+    offset: usize,
+}
+
+impl Decoder<'_> {
+    fn byte(&mut self) -> Result<u8, usize> {
+        let &byte = self.source.get(self.offset).ok_or(self.offset)?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, length: u64) -> Result<&[u8], usize> {
+        let end = usize::try_from(length).ok().and_then(|length| self.offset.checked_add(length));
+        let bytes = end.and_then(|end| self.source.get(self.offset..end)).ok_or(self.offset)?;
+        self.offset += bytes.len();
+        Ok(bytes)
+    }
+
+    /// The argument of an item, which follows its initial byte in as many
+    /// bytes as that says.
+    fn argument(&mut self, info: u8) -> Result<u64, usize> {
+        let size = match info {
+            0..=23 => return Ok(info.into()),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(self.offset - 1),
+        };
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().fold(0, |value, &byte| value << 8 | u64::from(byte)))
+    }
+
+    fn is_break(&mut self) -> bool {
+        if self.source.get(self.offset) == Some(&BREAK) {
+            self.offset += 1;
+            return true;
+        }
+        false
+    }
+
+    fn value(&mut self) -> Result<Cbor, usize> {
+        let start = self.offset;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        if info == INDEFINITE && (2..=5).contains(&major) {
+            return self.indefinite(major, start);
+        }
+        if major == 7 {
+            return self.simple(info, start);
+        }
+        let argument = self.argument(info)?;
+        match major {
+            0 => Ok(Cbor::Integer(argument.into())),
+            1 => Ok(Cbor::Integer(-1 - i128::from(argument))),
+            2 => Ok(Cbor::Bytes(self.take(argument)?.to_vec())),
+            3 => {
+                let text = std::str::from_utf8(self.take(argument)?).map_err(|_| start)?;
+                Ok(Cbor::Text(text.to_string()))
+            }
+            4 => {
+                let elements = (0..argument).map(|_| self.value());
+                Ok(Cbor::Array(elements.collect::<Result<_, _>>()?))
+            }
+            5 => {
+                let mut entries = vec![];
+                for _ in 0..argument {
+                    entries.push((self.value()?, self.value()?));
+                }
+                Ok(Cbor::Map(entries))
+            }
+            _ => Ok(Cbor::Tagged(argument, Box::new(self.value()?))),
+        }
+    }
+
+    /// Strings, arrays, and maps whose length isn't given, which end with a
+    /// break instead. Strings are made of chunks of definite length.
+    fn indefinite(&mut self, major: u8, start: usize) -> Result<Cbor, usize> {
+        let mut elements = vec![];
+        while !self.is_break() {
+            elements.push(self.value()?);
+        }
+        match major {
+            2 | 3 => {
+                let mut bytes = vec![];
+                for element in elements {
+                    match (major, element) {
+                        (2, Cbor::Bytes(chunk)) => bytes.extend(chunk),
+                        (3, Cbor::Text(chunk)) => bytes.extend(chunk.into_bytes()),
+                        _ => return Err(start),
+                    }
+                }
+                match major {
+                    2 => Ok(Cbor::Bytes(bytes)),
+                    _ => String::from_utf8(bytes).map(Cbor::Text).map_err(|_| start),
+                }
+            }
+            4 => Ok(Cbor::Array(elements)),
+            _ => {
+                if elements.len() % 2 != 0 {
+                    return Err(start);
+                }
+                let mut entries = vec![];
+                let mut elements = elements.into_iter();
+                while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                    entries.push((key, value));
+                }
+                Ok(Cbor::Map(entries))
+            }
+        }
+    }
+
+    fn simple(&mut self, info: u8, start: usize) -> Result<Cbor, usize> {
+        match info {
+            20 => Ok(Cbor::Boolean(false)),
+            21 => Ok(Cbor::Boolean(true)),
+            22 | 23 => Ok(Cbor::Null),
+            25 => {
+                let bits = self.argument(info)? as u16;
+                Ok(Cbor::Float(half(bits)))
+            }
+            26 => Ok(Cbor::Float(f32::from_bits(self.argument(info)? as u32).into())),
+            27 => Ok(Cbor::Float(f64::from_bits(self.argument(info)?))),
+            _ => Err(start),
+        }
+    }
+}
+
+/// Widens a half-precision float.
+fn half(bits: u16) -> f64 {
+    let sign = if bits >> 15 == 1 { -1.0 } else { 1.0 };
+    let exponent = i32::from(bits >> 10 & 0x1f);
+    let fraction = f64::from(bits & 0x3ff);
+    sign * match exponent {
+        0 => fraction * 2f64.powi(-24),
+        31 if fraction == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + fraction / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Cbor;
+
+    /// Encodes a value with the shortest arguments and definite lengths.
+    pub(crate) fn encode(value: &Cbor, bytes: &mut Vec<u8>) {
+        let head = |major: u8, argument: u64, bytes: &mut Vec<u8>| {
+            let major = major << 5;
+            match argument {
+                0..=23 => bytes.push(major | argument as u8),
+                24..=0xff => bytes.extend([major | 24, argument as u8]),
+                0x100..=0xffff => {
+                    bytes.push(major | 25);
+                    bytes.extend((argument as u16).to_be_bytes());
+                }
+                0x10000..=0xffff_ffff => {
+                    bytes.push(major | 26);
+                    bytes.extend((argument as u32).to_be_bytes());
+                }
+                _ => {
+                    bytes.push(major | 27);
+                    bytes.extend(argument.to_be_bytes());
+                }
+            }
+        };
+        match value {
+            &Cbor::Integer(integer) if integer >= 0 => head(0, integer as u64, bytes),
+            &Cbor::Integer(integer) => head(1, (-1 - integer) as u64, bytes),
+            Cbor::Bytes(value) => {
+                head(2, value.len() as u64, bytes);
+                bytes.extend(value);
+            }
+            Cbor::Text(text) => {
+                head(3, text.len() as u64, bytes);
+                bytes.extend(text.as_bytes());
+            }
+            Cbor::Array(elements) => {
+                head(4, elements.len() as u64, bytes);
+                for element in elements {
+                    encode(element, bytes);
+                }
+            }
+            Cbor::Map(entries) => {
+                head(5, entries.len() as u64, bytes);
+                for (key, value) in entries {
+                    encode(key, bytes);
+                    encode(value, bytes);
+                }
+            }
+            Cbor::Tagged(tag, value) => {
+                head(6, *tag, bytes);
+                encode(value, bytes);
+            }
+            Cbor::Boolean(false) => bytes.push(0xf4),
+            Cbor::Boolean(true) => bytes.push(0xf5),
+            Cbor::Null => bytes.push(0xf6),
+            Cbor::Float(value) => {
+                bytes.push(0xfb);
+                bytes.extend(value.to_bits().to_be_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn decode_items() {
+        // Examples from appendix A of RFC 8949.
+        let cases: [(&[u8], Cbor); 8] = [
+            (&[0x18, 0x64], Cbor::Integer(100)),
+            (&[0x39, 0x03, 0xe7], Cbor::Integer(-1000)),
+            (&[0x64, 0x49, 0x45, 0x54, 0x46], Cbor::Text("IETF".to_string())),
+            (&[0xf9, 0x3c, 0x00], Cbor::Float(1.0)),
+            (&[0xf5], Cbor::Boolean(true)),
+            (
+                &[0x9f, 0x01, 0x82, 0x02, 0x03, 0xff],
+                Cbor::Array(vec![
+                    Cbor::Integer(1),
+                    Cbor::Array(vec![Cbor::Integer(2), Cbor::Integer(3)]),
+                ]),
+            ),
+            (
+                &[0x7f, 0x65, 0x73, 0x74, 0x72, 0x65, 0x61, 0x64, 0x6d, 0x69, 0x6e, 0x67, 0xff],
+                Cbor::Text("streaming".to_string()),
+            ),
+            (
+                &[0xa1, 0x61, 0x61, 0x01],
+                Cbor::Map(vec![(Cbor::Text("a".to_string()), Cbor::Integer(1))]),
+            ),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(Cbor::parse(bytes), Ok(expected.clone()));
+            if !matches!(expected, Cbor::Float(_)) && bytes[0] & 0x1f != 31 {
+                let mut encoded = vec![];
+                encode(&expected, &mut encoded);
+                assert_eq!(encoded, bytes);
+            }
+        }
+        assert_eq!(Cbor::parse(&[0x82, 0x01]), Err(2));
+    }
+}
@@ -0,0 +1,93 @@
+//! Diagnostics reported during lowering.
+
+use rowan::ast::SyntaxNodePtr;
+use syntax::PureScript;
+
+use crate::name::Name;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoweringDiagnostic {
+    /// A `do` block that doesn't end with an expression.
+    InvalidDoFinalStatement { ptr: SyntaxNodePtr<PureScript> },
+    /// An equation with a different number of binders than the first
+    /// equation of its value.
+    ArityMismatch { ptr: SyntaxNodePtr<PureScript>, expected: usize, actual: usize },
+    /// An integer literal that doesn't fit an `Int`.
+    IntegerOutOfRange { ptr: SyntaxNodePtr<PureScript> },
+    /// A string or character literal with an invalid escape sequence.
+    InvalidEscape { ptr: SyntaxNodePtr<PureScript> },
+    /// A declaration with the same name as an earlier declaration in the
+    /// same namespace.
+    DuplicateDeclaration {
+        name: Name,
+        ptr: SyntaxNodePtr<PureScript>,
+        previous: SyntaxNodePtr<PureScript>,
+    },
+    /// A label that occurs more than once in the same record literal,
+    /// update, or binder.
+    DuplicateLabel {
+        name: Name,
+        ptr: SyntaxNodePtr<PureScript>,
+        previous: SyntaxNodePtr<PureScript>,
+    },
+    /// A type variable bound more than once by the same `forall` or
+    /// declaration.
+    DuplicateTypeVariable {
+        name: Name,
+        ptr: SyntaxNodePtr<PureScript>,
+        previous: SyntaxNodePtr<PureScript>,
+    },
+}
+
+impl LoweringDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            LoweringDiagnostic::InvalidDoFinalStatement { .. } => {
+                "the last statement in a do block must be an expression".to_string()
+            }
+            LoweringDiagnostic::ArityMismatch { expected, actual, .. } => {
+                let arguments = if *actual == 1 { "argument" } else { "arguments" };
+                format!("equation has {actual} {arguments}, but the first equation has {expected}")
+            }
+            LoweringDiagnostic::IntegerOutOfRange { .. } => {
+                "integer literal is out of range".to_string()
+            }
+            LoweringDiagnostic::InvalidEscape { .. } => "invalid escape sequence".to_string(),
+            LoweringDiagnostic::DuplicateDeclaration { name, .. } => {
+                format!("{name} is defined more than once")
+            }
+            LoweringDiagnostic::DuplicateLabel { name, .. } => {
+                format!("label {name} appears more than once")
+            }
+            LoweringDiagnostic::DuplicateTypeVariable { name, .. } => {
+                format!("type variable {name} is bound more than once")
+            }
+        }
+    }
+
+    /// The syntax node the diagnostic is reported at.
+    pub fn ptr(&self) -> &SyntaxNodePtr<PureScript> {
+        match self {
+            LoweringDiagnostic::InvalidDoFinalStatement { ptr }
+            | LoweringDiagnostic::ArityMismatch { ptr, .. }
+            | LoweringDiagnostic::IntegerOutOfRange { ptr }
+            | LoweringDiagnostic::InvalidEscape { ptr }
+            | LoweringDiagnostic::DuplicateDeclaration { ptr, .. }
+            | LoweringDiagnostic::DuplicateLabel { ptr, .. }
+            | LoweringDiagnostic::DuplicateTypeVariable { ptr, .. } => ptr,
+        }
+    }
+
+    /// A secondary location related to the diagnostic, with a message that
+    /// describes it.
+    pub fn related(&self) -> Option<(&SyntaxNodePtr<PureScript>, &'static str)> {
+        match self {
+            LoweringDiagnostic::DuplicateDeclaration { previous, .. }
+            | LoweringDiagnostic::DuplicateLabel { previous, .. }
+            | LoweringDiagnostic::DuplicateTypeVariable { previous, .. } => {
+                Some((previous, "previously defined here"))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,585 @@
+//! A binary encoding of item trees, such that they can be cached on disk.
+//!
+//! Names are written out as their text, since the indices they're interned
+//! at differ between runs. The encoding starts with a version, which has to
+//! be bumped whenever an item tree changes shape; trees encoded with another
+//! version don't decode, and are lowered again.
+
+use crate::{
+    arena::{Arena, Idx},
+    ast_id::{AstIdKind, ErasedAstId},
+    hir::{
+        Associativity, Constructor, DataMembers, Export, Fixity, FunctionalDependency, Import,
+        ImportItem, ImportList, InstanceHead, Row, Signature, Type, TypeVariable,
+    },
+    item_tree::{
+        ClassItem, DataItem, ForeignDataItem, ForeignValueItem, InstanceItem, Item, ItemTree,
+        NewtypeItem, SynonymItem, ValueItem,
+    },
+    name::{ModuleName, Name, QualifiedName},
+};
+
+const MAGIC: &[u8; 4] = b"PSIT";
+const VERSION: u32 = 1;
+
+impl ItemTree {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder { bytes: MAGIC.to_vec() };
+        VERSION.encode(&mut encoder);
+        self.name.encode(&mut encoder);
+        self.exports.encode(&mut encoder);
+        self.imports.encode(&mut encoder);
+        self.items.encode(&mut encoder);
+        self.types.encode(&mut encoder);
+        self.ast_ids.encode(&mut encoder);
+        encoder.bytes
+    }
+
+    /// Decodes an item tree, or returns `None` if the bytes are malformed or
+    /// encoded by another version.
+    pub fn decode(bytes: &[u8]) -> Option<ItemTree> {
+        let mut decoder = Decoder { bytes: bytes.strip_prefix(MAGIC)? };
+        if u32::decode(&mut decoder)? != VERSION {
+            return None;
+        }
+        let tree = ItemTree {
+            name: Decode::decode(&mut decoder)?,
+            exports: Decode::decode(&mut decoder)?,
+            imports: Decode::decode(&mut decoder)?,
+            items: Decode::decode(&mut decoder)?,
+            types: Decode::decode(&mut decoder)?,
+            ast_ids: Decode::decode(&mut decoder)?,
+        };
+        decoder.bytes.is_empty().then_some(tree)
+    }
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn tag(&mut self, tag: u8) {
+        self.bytes.push(tag);
+    }
+
+    fn variant(&mut self, tag: u8, fields: impl Encode) {
+        self.tag(tag);
+        fields.encode(self);
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn tag(&mut self) -> Option<u8> {
+        let (&tag, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        Some(tag)
+    }
+}
+
+trait Encode {
+    fn encode(&self, encoder: &mut Encoder);
+}
+
+trait Decode: Sized {
+    fn decode(decoder: &mut Decoder) -> Option<Self>;
+}
+
+/// Integers are encoded in as few bytes as they fit in, seven bits at a
+/// time, since most of them are small indices and lengths.
+impl Encode for u64 {
+    fn encode(&self, encoder: &mut Encoder) {
+        let mut value = *self;
+        while value >= 0x80 {
+            encoder.tag(value as u8 | 0x80);
+            value >>= 7;
+        }
+        encoder.tag(value as u8);
+    }
+}
+
+impl Decode for u64 {
+    fn decode(decoder: &mut Decoder) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = decoder.tag()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte < 0x80 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+macro_rules! integers {
+    ($($integer:ty)*) => {$(
+        impl Encode for $integer {
+            fn encode(&self, encoder: &mut Encoder) {
+                (*self as u64).encode(encoder);
+            }
+        }
+
+        impl Decode for $integer {
+            fn decode(decoder: &mut Decoder) -> Option<$integer> {
+                u64::decode(decoder)?.try_into().ok()
+            }
+        }
+    )*};
+}
+
+integers!(u8 u32 usize);
+
+/// Signed integers are zigzag encoded, such that small negative integers
+/// are small as well.
+impl Encode for i64 {
+    fn encode(&self, encoder: &mut Encoder) {
+        (((*self << 1) ^ (*self >> 63)) as u64).encode(encoder);
+    }
+}
+
+impl Decode for i64 {
+    fn decode(decoder: &mut Decoder) -> Option<i64> {
+        let value = u64::decode(decoder)?;
+        Some((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.tag(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(decoder: &mut Decoder) -> Option<bool> {
+        match decoder.tag()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.len().encode(encoder);
+        encoder.bytes.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.as_str().encode(encoder);
+    }
+}
+
+impl Decode for String {
+    fn decode(decoder: &mut Decoder) -> Option<String> {
+        let length = usize::decode(decoder)?;
+        if length > decoder.bytes.len() {
+            return None;
+        }
+        let (text, rest) = decoder.bytes.split_at(length);
+        decoder.bytes = rest;
+        String::from_utf8(text.to_vec()).ok()
+    }
+}
+
+impl Encode for Name {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.as_str().encode(encoder);
+    }
+}
+
+impl Decode for Name {
+    fn decode(decoder: &mut Decoder) -> Option<Name> {
+        Some(Name::new(&String::decode(decoder)?))
+    }
+}
+
+impl Encode for ModuleName {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.as_str().encode(encoder);
+    }
+}
+
+impl Decode for ModuleName {
+    fn decode(decoder: &mut Decoder) -> Option<ModuleName> {
+        Some(ModuleName::new(&String::decode(decoder)?))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, encoder: &mut Encoder) {
+        (**self).encode(encoder);
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            None => encoder.tag(0),
+            Some(value) => {
+                encoder.tag(1);
+                value.encode(encoder);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(decoder: &mut Decoder) -> Option<Option<T>> {
+        match decoder.tag()? {
+            0 => Some(None),
+            1 => Some(Some(T::decode(decoder)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.len().encode(encoder);
+        for element in self {
+            element.encode(encoder);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(decoder: &mut Decoder) -> Option<Vec<T>> {
+        let length = usize::decode(decoder)?;
+        // Every element takes at least a byte, which bounds the allocation
+        // for malformed lengths.
+        let mut elements = Vec::with_capacity(length.min(decoder.bytes.len()));
+        for _ in 0..length {
+            elements.push(T::decode(decoder)?);
+        }
+        Some(elements)
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.0.encode(encoder);
+        self.1.encode(encoder);
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(decoder: &mut Decoder) -> Option<(A, B)> {
+        Some((A::decode(decoder)?, B::decode(decoder)?))
+    }
+}
+
+impl<T> Encode for Idx<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.into_raw().encode(encoder);
+    }
+}
+
+impl<T> Decode for Idx<T> {
+    fn decode(decoder: &mut Decoder) -> Option<Idx<T>> {
+        Some(Idx::from_raw(u32::decode(decoder)?))
+    }
+}
+
+impl<T: Encode> Encode for Arena<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.len().encode(encoder);
+        for (_, value) in self.iter() {
+            value.encode(encoder);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Arena<T> {
+    fn decode(decoder: &mut Decoder) -> Option<Arena<T>> {
+        let mut arena = Arena::default();
+        for value in Vec::decode(decoder)? {
+            arena.alloc(value);
+        }
+        Some(arena)
+    }
+}
+
+/// Implements the encoding of structs field by field, in order.
+macro_rules! structs {
+    ($($name:ident { $($field:ident),* })*) => {$(
+        impl Encode for $name {
+            fn encode(&self, encoder: &mut Encoder) {
+                $(self.$field.encode(encoder);)*
+            }
+        }
+
+        impl Decode for $name {
+            fn decode(decoder: &mut Decoder) -> Option<$name> {
+                Some($name { $($field: Decode::decode(decoder)?),* })
+            }
+        }
+    )*};
+}
+
+structs! {
+    QualifiedName { qualifier, name }
+    ErasedAstId { kind, index }
+    Import { module, list, alias }
+    ImportList { hiding, items }
+    Signature { name, type_ }
+    TypeVariable { name, kind, visible }
+    Constructor { name, fields }
+    FunctionalDependency { determiners, determined }
+    InstanceHead { constraints, class, arguments }
+    Fixity { associativity, precedence, is_type, target, operator }
+    Row { fields, tail }
+    ValueItem { name, signature, equations }
+    DataItem { name, kind, variables, constructors }
+    NewtypeItem { name, kind, variables, constructor }
+    SynonymItem { name, kind, variables, type_ }
+    ClassItem { name, kind, constraints, variables, dependencies, members }
+    InstanceItem { name, head, members, chain_index, derived, newtype }
+    ForeignDataItem { name, kind }
+    ForeignValueItem { name, type_ }
+}
+
+/// Implements the encoding of enums without fields as their index.
+macro_rules! tags {
+    ($($name:ident { $($variant:ident),* })*) => {$(
+        impl Encode for $name {
+            fn encode(&self, encoder: &mut Encoder) {
+                let variants = [$($name::$variant),*];
+                let tag = variants.iter().position(|variant| variant == self).unwrap();
+                encoder.tag(tag as u8);
+            }
+        }
+
+        impl Decode for $name {
+            fn decode(decoder: &mut Decoder) -> Option<$name> {
+                let variants = [$($name::$variant),*];
+                variants.get(usize::from(decoder.tag()?)).copied()
+            }
+        }
+    )*};
+}
+
+tags! {
+    Associativity { Left, Right, None }
+    AstIdKind {
+        Value, Annotation, KindSignature, Data, Newtype, Type, Class, InstanceChain,
+        DeriveInstance, ForeignData, ForeignValue, Fixity
+    }
+}
+
+impl Encode for DataMembers {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            DataMembers::All => encoder.tag(0),
+            DataMembers::Enumerated(names) => {
+                encoder.tag(1);
+                names.encode(encoder);
+            }
+        }
+    }
+}
+
+impl Decode for DataMembers {
+    fn decode(decoder: &mut Decoder) -> Option<DataMembers> {
+        match decoder.tag()? {
+            0 => Some(DataMembers::All),
+            1 => Some(DataMembers::Enumerated(Decode::decode(decoder)?)),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for Export {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            Export::Value(name) => encoder.variant(0, name),
+            Export::Operator(name) => encoder.variant(1, name),
+            Export::Type(name, members) => encoder.variant(2, (name, members)),
+            Export::TypeOperator(name) => encoder.variant(3, name),
+            Export::Class(name) => encoder.variant(4, name),
+            Export::Module(name) => encoder.variant(5, name),
+        }
+    }
+}
+
+impl Decode for Export {
+    fn decode(decoder: &mut Decoder) -> Option<Export> {
+        Some(match decoder.tag()? {
+            0 => Export::Value(Decode::decode(decoder)?),
+            1 => Export::Operator(Decode::decode(decoder)?),
+            2 => Export::Type(Decode::decode(decoder)?, Decode::decode(decoder)?),
+            3 => Export::TypeOperator(Decode::decode(decoder)?),
+            4 => Export::Class(Decode::decode(decoder)?),
+            5 => Export::Module(Decode::decode(decoder)?),
+            _ => return None,
+        })
+    }
+}
+
+impl Encode for ImportItem {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            ImportItem::Value(name) => encoder.variant(0, name),
+            ImportItem::Operator(name) => encoder.variant(1, name),
+            ImportItem::Type(name, members) => encoder.variant(2, (name, members)),
+            ImportItem::TypeOperator(name) => encoder.variant(3, name),
+            ImportItem::Class(name) => encoder.variant(4, name),
+        }
+    }
+}
+
+impl Decode for ImportItem {
+    fn decode(decoder: &mut Decoder) -> Option<ImportItem> {
+        Some(match decoder.tag()? {
+            0 => ImportItem::Value(Decode::decode(decoder)?),
+            1 => ImportItem::Operator(Decode::decode(decoder)?),
+            2 => ImportItem::Type(Decode::decode(decoder)?, Decode::decode(decoder)?),
+            3 => ImportItem::TypeOperator(Decode::decode(decoder)?),
+            4 => ImportItem::Class(Decode::decode(decoder)?),
+            _ => return None,
+        })
+    }
+}
+
+impl Encode for Item {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            Item::Value(item) => encoder.variant(0, item),
+            Item::Data(item) => encoder.variant(1, item),
+            Item::Newtype(item) => encoder.variant(2, item),
+            Item::Synonym(item) => encoder.variant(3, item),
+            Item::Class(item) => encoder.variant(4, item),
+            Item::Instance(item) => encoder.variant(5, item),
+            Item::ForeignData(item) => encoder.variant(6, item),
+            Item::ForeignValue(item) => encoder.variant(7, item),
+            Item::Fixity(item) => encoder.variant(8, item),
+        }
+    }
+}
+
+impl Decode for Item {
+    fn decode(decoder: &mut Decoder) -> Option<Item> {
+        Some(match decoder.tag()? {
+            0 => Item::Value(Decode::decode(decoder)?),
+            1 => Item::Data(Decode::decode(decoder)?),
+            2 => Item::Newtype(Decode::decode(decoder)?),
+            3 => Item::Synonym(Decode::decode(decoder)?),
+            4 => Item::Class(Decode::decode(decoder)?),
+            5 => Item::Instance(Decode::decode(decoder)?),
+            6 => Item::ForeignData(Decode::decode(decoder)?),
+            7 => Item::ForeignValue(Decode::decode(decoder)?),
+            8 => Item::Fixity(Decode::decode(decoder)?),
+            _ => return None,
+        })
+    }
+}
+
+impl Encode for Type {
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            Type::Missing => encoder.tag(0),
+            Type::Forall { variables, type_ } => encoder.variant(1, (variables, type_)),
+            Type::Constrained { constraint, type_ } => encoder.variant(2, (constraint, type_)),
+            Type::Arrow { argument, result } => encoder.variant(3, (argument, result)),
+            Type::OperatorChain { head, tail } => encoder.variant(4, (head, tail)),
+            Type::Kinded { type_, kind } => encoder.variant(5, (type_, kind)),
+            Type::Application { function, arguments } => encoder.variant(6, (function, arguments)),
+            Type::Constructor(name) => encoder.variant(7, name),
+            Type::Variable(name) => encoder.variant(8, name),
+            Type::Wildcard => encoder.tag(9),
+            Type::Hole(name) => encoder.variant(10, name),
+            Type::String(text) => encoder.variant(11, text),
+            Type::Integer(value) => encoder.variant(12, value),
+            Type::Record(row) => encoder.variant(13, row),
+            Type::Row(row) => encoder.variant(14, row),
+            Type::OperatorName(name) => encoder.variant(15, name),
+            Type::Function => encoder.tag(16),
+            Type::Constraints(constraints) => encoder.variant(17, constraints),
+        }
+    }
+}
+
+impl Decode for Type {
+    fn decode(decoder: &mut Decoder) -> Option<Type> {
+        Some(match decoder.tag()? {
+            0 => Type::Missing,
+            1 => Type::Forall {
+                variables: Decode::decode(decoder)?,
+                type_: Decode::decode(decoder)?,
+            },
+            2 => Type::Constrained {
+                constraint: Decode::decode(decoder)?,
+                type_: Decode::decode(decoder)?,
+            },
+            3 => {
+                Type::Arrow { argument: Decode::decode(decoder)?, result: Decode::decode(decoder)? }
+            }
+            4 => Type::OperatorChain {
+                head: Decode::decode(decoder)?,
+                tail: Decode::decode(decoder)?,
+            },
+            5 => Type::Kinded { type_: Decode::decode(decoder)?, kind: Decode::decode(decoder)? },
+            6 => Type::Application {
+                function: Decode::decode(decoder)?,
+                arguments: Decode::decode(decoder)?,
+            },
+            7 => Type::Constructor(Decode::decode(decoder)?),
+            8 => Type::Variable(Decode::decode(decoder)?),
+            9 => Type::Wildcard,
+            10 => Type::Hole(Decode::decode(decoder)?),
+            11 => Type::String(Decode::decode(decoder)?),
+            12 => Type::Integer(Decode::decode(decoder)?),
+            13 => Type::Record(Decode::decode(decoder)?),
+            14 => Type::Row(Decode::decode(decoder)?),
+            15 => Type::OperatorName(Decode::decode(decoder)?),
+            16 => Type::Function,
+            17 => Type::Constraints(Decode::decode(decoder)?),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use crate::item_tree::ItemTree;
+
+    #[test]
+    fn round_trip_item_trees() {
+        let source = r#"module Main (T(..), class C, module M, (+)) where
+
+import Data.Maybe (Maybe(..)) as M
+import Prelude hiding (add)
+
+data T a = A a | B { x :: Int, y :: "label" }
+newtype N = N (forall @a. a -> P 2)
+type S = (c :: Int | r)
+class C a b | a -> b where
+  c :: a -> b
+instance cInt :: C Int String where
+  c _ = ""
+derive newtype instance Show N
+foreign import data F :: Type
+foreign import f :: F
+infixl 6 add as +
+
+add = 0
+"#;
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let tree = ItemTree::lower(&ast::Module::cast(node).unwrap());
+        let bytes = tree.encode();
+        assert_eq!(ItemTree::decode(&bytes), Some(tree));
+        assert_eq!(ItemTree::decode(&bytes[..bytes.len() - 1]), None);
+    }
+}
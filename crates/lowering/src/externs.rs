@@ -0,0 +1,1330 @@
+//! Item trees for modules that `purs` has already compiled.
+//!
+//! Libraries in the package set are compiled by `purs` into its output
+//! directory, where each module gets an `externs.cbor` and, when docs are
+//! generated, a `docs.json`. The externs are a serialization of the
+//! compiler's internal types, which change between compiler versions, so
+//! they're only read when they were written by a compiler whose encoding is
+//! known, and `docs.json` is read instead otherwise. Both have the signature
+//! of every exported value and the declaration of every exported type and
+//! class, which is all that modules depending on it see.
+//!
+//! Externs are translated into the shape of `docs.json`, such that both are
+//! read the same way. Types in `docs.json` are fully qualified. Names
+//! qualified by the module itself or by `Prim` are lowered unqualified, and
+//! every other module they mention is imported under its own name, such that
+//! the types resolve the same way that they would in source.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use json::Json;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    cbor::Cbor,
+    hir::{
+        Associativity, Constructor, Fixity, FunctionalDependency, Import, InstanceHead, Row,
+        Signature, Type, TypeId, TypeVariable,
+    },
+    item_tree::{
+        ClassItem, DataItem, ForeignDataItem, InstanceItem, Item, ItemTree, NewtypeItem,
+        SynonymItem, ValueItem,
+    },
+    name::{ModuleName, Name, QualifiedName},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternsError {
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    /// The file isn't valid JSON, at the given byte offset.
+    Json {
+        offset: usize,
+    },
+    /// The file isn't valid CBOR, at the given byte offset.
+    Cbor {
+        offset: usize,
+    },
+    /// The externs were written by a compiler whose encoding isn't known.
+    Version {
+        version: String,
+    },
+    /// The file is JSON, but not in the shape of a `docs.json`, e.g. when
+    /// it was written by an unsupported compiler version.
+    Shape {
+        context: &'static str,
+    },
+}
+
+impl ExternsError {
+    pub fn message(&self) -> String {
+        match self {
+            ExternsError::Io { path, message } => {
+                format!("couldn't read {}: {message}", path.display())
+            }
+            ExternsError::Json { offset } => format!("invalid JSON at byte {offset}"),
+            ExternsError::Cbor { offset } => format!("invalid CBOR at byte {offset}"),
+            ExternsError::Version { version } => {
+                format!("written by purs {version}, whose externs can't be read")
+            }
+            ExternsError::Shape { context } => format!("unexpected shape of {context}"),
+        }
+    }
+}
+
+/// The item trees read from the output directory of `purs`, along with the
+/// files that couldn't be read, which are skipped.
+#[derive(Debug, Default)]
+pub struct Output {
+    pub trees: Vec<ItemTree>,
+    pub errors: Vec<(PathBuf, ExternsError)>,
+}
+
+/// Reads the modules in the output directory of `purs`, from their
+/// `externs.cbor`, or from their `docs.json` if the externs can't be read.
+/// An output directory that doesn't exist yet has no modules.
+pub fn load_output(output: &Path) -> Output {
+    let mut loaded = Output::default();
+    let entries = match fs::read_dir(output) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return loaded,
+        Err(error) => {
+            loaded.errors.push((output.to_path_buf(), io(output, error)));
+            return loaded;
+        }
+    };
+    let mut directories: Vec<_> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    directories.sort();
+    for directory in directories {
+        let (externs, docs) = (directory.join("externs.cbor"), directory.join("docs.json"));
+        let read_externs = externs.is_file().then(|| {
+            let source = fs::read(&externs).map_err(|error| io(&externs, error))?;
+            read_externs(&source)
+        });
+        let error = match read_externs {
+            Some(Ok(tree)) => {
+                loaded.trees.push(tree);
+                continue;
+            }
+            Some(Err(error)) => Some((externs, error)),
+            None => None,
+        };
+        let read_docs = docs.is_file().then(|| {
+            let source = fs::read_to_string(&docs).map_err(|error| io(&docs, error))?;
+            read_docs(&source)
+        });
+        match (read_docs, error) {
+            (Some(Ok(tree)), _) => loaded.trees.push(tree),
+            (Some(Err(error)), _) => loaded.errors.push((docs, error)),
+            (None, Some(error)) => loaded.errors.push(error),
+            (None, None) => {}
+        }
+    }
+    loaded
+}
+
+fn io(path: &Path, error: std::io::Error) -> ExternsError {
+    ExternsError::Io { path: path.to_path_buf(), message: error.to_string() }
+}
+
+/// Builds the [`ItemTree`] of a module from its `docs.json`.
+pub fn read_docs(source: &str) -> Result<ItemTree, ExternsError> {
+    let json = Json::parse(source).map_err(|offset| ExternsError::Json { offset })?;
+    let name = json.field("name").and_then(Json::as_str).ok_or(shape("the module name"))?;
+    let mut reader =
+        Reader { module: ModuleName::new(name), tree: ItemTree::default(), imports: vec![] };
+    let declarations =
+        json.field("declarations").and_then(Json::as_array).ok_or(shape("the declarations"))?;
+    for declaration in declarations {
+        reader.declaration(declaration)?;
+    }
+    Ok(reader.finish())
+}
+
+/// The prefix of the versions of `purs` whose externs can be read.
+const EXTERNS_VERSION: &str = "0.15.";
+
+/// Builds the [`ItemTree`] of a module from its `externs.cbor`, which is an
+/// `ExternsFile` encoded by the `Generic` instances of `Serialise`: each
+/// value is a list of its constructor's index followed by its fields, and
+/// every type starts with its source annotation.
+pub fn read_externs(source: &[u8]) -> Result<ItemTree, ExternsError> {
+    let cbor = Cbor::parse(source).map_err(|offset| ExternsError::Cbor { offset })?;
+    let [version, name, _exports, _imports, fixities, type_fixities, declarations, ..] =
+        fields(&cbor, "an externs file")?
+    else {
+        return Err(shape("an externs file"));
+    };
+    let version = text(version)?;
+    if !version.starts_with(EXTERNS_VERSION) {
+        return Err(ExternsError::Version { version: version.to_string() });
+    }
+    let module = ModuleName::new(text(name)?);
+    let mut reader = Reader { module, tree: ItemTree::default(), imports: vec![] };
+
+    // Every type is declared with its kind, and synonyms and classes are
+    // declared again with their definitions. Classes also declare the type
+    // of their dictionary, whose name has a `$` in it.
+    let declarations = list(declarations, "the declarations")?;
+    let mut kinds = FxHashMap::default();
+    let mut newtypes = vec![];
+    let mut classes = vec![];
+    for declaration in declarations {
+        match declaration.as_constructor() {
+            Some((0, [name, kind, _])) => {
+                kinds.insert(text(name)?, object([("kind", externs_type(kind)?)]));
+            }
+            Some((2, [_, origin, type_, ..])) if tag(origin) == Some(1) => {
+                newtypes.push(text(type_)?);
+            }
+            Some((4, [name, ..])) => classes.push(text(name)?),
+            _ => {}
+        }
+    }
+    for declaration in declarations {
+        let (tag, fields) = declaration.as_constructor().ok_or(shape("a declaration"))?;
+        let declaration = match (tag, fields) {
+            (0, [name, _, type_kind]) => {
+                let title = text(name)?;
+                if title.contains('$') || classes.contains(&title) {
+                    continue;
+                }
+                let kind = kinds.get(title).cloned().unwrap_or(Json::Null);
+                match type_kind.as_constructor() {
+                    Some((0, type_kind)) => {
+                        externs_data(title, kind, type_kind, newtypes.contains(&title))?
+                    }
+                    Some((2, _)) => object([
+                        ("title", Json::String(title.to_string())),
+                        ("info", object([("declType", string_json("externData"))])),
+                        ("kind", kind),
+                    ]),
+                    _ => continue,
+                }
+            }
+            (1, [name, arguments, type_]) => {
+                let title = text(name)?;
+                let info = object([
+                    ("declType", string_json("typeSynonym")),
+                    ("arguments", externs_variables(arguments)?),
+                    ("type", externs_type(type_)?),
+                ]);
+                let kind = kinds.get(title).cloned().unwrap_or(Json::Null);
+                object([("title", string_json(title)), ("info", info), ("kind", kind)])
+            }
+            (3, [name, type_]) => {
+                let info =
+                    object([("declType", string_json("value")), ("type", externs_type(type_)?)]);
+                object([("title", string_json(text(name)?)), ("info", info)])
+            }
+            (4, [name, arguments, members, constraints, dependencies, ..]) => {
+                let title = text(name)?;
+                externs_class(
+                    title,
+                    kinds.get(title).cloned(),
+                    arguments,
+                    members,
+                    constraints,
+                    dependencies,
+                )?
+            }
+            (5, [class, name, _, _, types, constraints, ..]) => {
+                let mut head = tagged("TypeConstructor", qualified_name(class)?);
+                for argument in list(types, "an instance head")? {
+                    head = tagged("TypeApp", Json::Array(vec![head, externs_type(argument)?]));
+                }
+                let constraints = match maybe(constraints)? {
+                    Some(constraints) => externs_constraints(constraints)?,
+                    None => Json::Array(vec![]),
+                };
+                let info = object([("dependencies", constraints), ("type", head)]);
+                let instance = reader.instance(Name::new(text(name)?), &info)?;
+                reader.tree.items.alloc(Item::Instance(instance));
+                continue;
+            }
+            _ => continue,
+        };
+        reader.declaration(&declaration)?;
+    }
+
+    for (fixities, is_type) in [(fixities, false), (type_fixities, true)] {
+        for fixity in list(fixities, "the fixities")? {
+            reader.declaration(&externs_fixity(fixity, is_type)?)?;
+        }
+    }
+    Ok(reader.finish())
+}
+
+/// A data type or newtype from the kind of type it's declared as, which
+/// lists its variables and constructors. Older compilers don't say whether
+/// it's a newtype there, but its constructor does.
+fn externs_data(
+    title: &str,
+    kind: Json,
+    type_kind: &[Cbor],
+    is_newtype: bool,
+) -> Result<Json, ExternsError> {
+    let (is_newtype, arguments, constructors) = match type_kind {
+        [declaration, arguments, constructors] => {
+            (tag(declaration) == Some(1), arguments, constructors)
+        }
+        [arguments, constructors] => (is_newtype, arguments, constructors),
+        _ => return Err(shape("a data declaration")),
+    };
+    let mut children = vec![];
+    for constructor in list(constructors, "the constructors")? {
+        let [name, fields] = tuple(constructor)?;
+        let fields = list(fields, "a constructor")?.iter().map(externs_type);
+        let info = object([
+            ("declType", string_json("dataConstructor")),
+            ("arguments", Json::Array(fields.collect::<Result<_, _>>()?)),
+        ]);
+        children.push(object([("title", string_json(text(name)?)), ("info", info)]));
+    }
+    let info = object([
+        ("declType", string_json("data")),
+        ("dataDeclType", string_json(if is_newtype { "newtype" } else { "data" })),
+        ("typeArguments", externs_variables(arguments)?),
+    ]);
+    Ok(object([
+        ("title", string_json(title)),
+        ("info", info),
+        ("kind", kind),
+        ("children", Json::Array(children)),
+    ]))
+}
+
+/// A class, whose functional dependencies refer to its variables by their
+/// position.
+fn externs_class(
+    title: &str,
+    kind: Option<Json>,
+    arguments: &Cbor,
+    members: &Cbor,
+    constraints: &Cbor,
+    dependencies: &Cbor,
+) -> Result<Json, ExternsError> {
+    let variables = list(arguments, "the class variables")?;
+    let variable = |index: &Cbor| -> Result<Json, ExternsError> {
+        let index = index.as_integer().and_then(|index| usize::try_from(index).ok());
+        let variable = index.and_then(|index| variables.get(index));
+        let [name, ..] =
+            variable.and_then(Cbor::as_array).ok_or(shape("a functional dependency"))?
+        else {
+            return Err(shape("a functional dependency"));
+        };
+        Ok(string_json(text(name)?))
+    };
+    let mut fundeps = vec![];
+    for dependency in list(dependencies, "the functional dependencies")? {
+        let [determiners, determined] = fields(dependency, "a functional dependency")? else {
+            return Err(shape("a functional dependency"));
+        };
+        let determiners = list(determiners, "a functional dependency")?.iter().map(variable);
+        let determined = list(determined, "a functional dependency")?.iter().map(variable);
+        fundeps.push(Json::Array(vec![
+            Json::Array(determiners.collect::<Result<_, _>>()?),
+            Json::Array(determined.collect::<Result<_, _>>()?),
+        ]));
+    }
+    let mut children = vec![];
+    for member in list(members, "the class members")? {
+        let [name, type_] = tuple(member)?;
+        let info =
+            object([("declType", string_json("typeClassMember")), ("type", externs_type(type_)?)]);
+        children.push(object([("title", string_json(text(name)?)), ("info", info)]));
+    }
+    let info = object([
+        ("declType", string_json("typeClass")),
+        ("arguments", externs_variables(arguments)?),
+        ("superclasses", externs_constraints(constraints)?),
+        ("fundeps", Json::Array(fundeps)),
+    ]);
+    Ok(object([
+        ("title", string_json(title)),
+        ("info", info),
+        ("kind", kind.unwrap_or(Json::Null)),
+        ("children", Json::Array(children)),
+    ]))
+}
+
+/// A fixity, whose alias is a type, or else a value or a constructor.
+fn externs_fixity(fixity: &Cbor, is_type: bool) -> Result<Json, ExternsError> {
+    let [associativity, precedence, operator, alias] = fields(fixity, "a fixity")? else {
+        return Err(shape("a fixity"));
+    };
+    let associativity = match tag(associativity) {
+        Some(0) => "infixl",
+        Some(1) => "infixr",
+        _ => "infix",
+    };
+    let precedence = precedence.as_integer().ok_or(shape("a fixity"))?;
+    let (module, target) = qualified(alias)?;
+    let (title, target) = match is_type {
+        true => {
+            (format!("type ({})", text(operator)?), object([("Left", string_json(text(target)?))]))
+        }
+        false => {
+            let side = match target.as_constructor() {
+                Some((0, [target])) => object([("Left", string_json(text(target)?))]),
+                Some((1, [target])) => object([("Right", string_json(text(target)?))]),
+                _ => return Err(shape("an alias")),
+            };
+            (format!("({})", text(operator)?), object([("Right", side)]))
+        }
+    };
+    let fixity = object([
+        ("associativity", string_json(associativity)),
+        ("precedence", Json::Number(precedence as f64)),
+    ]);
+    let info = object([
+        ("declType", string_json("alias")),
+        ("fixity", fixity),
+        ("alias", Json::Array(vec![module, target])),
+    ]);
+    Ok(object([("title", Json::String(title)), ("info", info)]))
+}
+
+fn externs_type(cbor: &Cbor) -> Result<Json, ExternsError> {
+    let (tag, fields) = cbor.as_constructor().ok_or(shape("a type"))?;
+    let fields = fields.get(1..).ok_or(shape("a type"))?;
+    let pair = |first, second| -> Result<Json, ExternsError> {
+        Ok(Json::Array(vec![externs_type(first)?, externs_type(second)?]))
+    };
+    let type_ = match (tag, fields) {
+        (1, [name]) => tagged("TypeVar", string_json(text(name)?)),
+        (2, [value]) => tagged("TypeLevelString", Json::String(string(value)?)),
+        (3, [value]) => {
+            tagged("TypeLevelInt", Json::Number(value.as_integer().ok_or(shape("a type"))? as f64))
+        }
+        (4, _) => tagged("TypeWildcard", Json::Null),
+        (5, [name]) => tagged("TypeConstructor", qualified_name(name)?),
+        (6, [name]) => tagged("TypeOp", qualified_name(name)?),
+        (7, [function, argument]) => tagged("TypeApp", pair(function, argument)?),
+        (8, [type_, kind]) => tagged("KindApp", pair(type_, kind)?),
+        // Compilers older than visible type applications don't write the
+        // visibility of a variable.
+        (9, [visibility, name, kind, body, _]) => {
+            externs_forall(tag_is(visibility, 0), name, kind, body)?
+        }
+        (9, [name, kind, body, _]) => externs_forall(false, name, kind, body)?,
+        (10, [constraint, body]) => tagged(
+            "ConstrainedType",
+            Json::Array(vec![externs_constraint(constraint)?, externs_type(body)?]),
+        ),
+        (12, []) => tagged("REmpty", Json::Null),
+        (13, [label, type_, rest]) => tagged(
+            "RCons",
+            Json::Array(vec![
+                Json::String(string(label)?),
+                externs_type(type_)?,
+                externs_type(rest)?,
+            ]),
+        ),
+        (14, [type_, kind]) => tagged("KindedType", pair(type_, kind)?),
+        (15, [operator, left, right]) => tagged(
+            "BinaryNoParensType",
+            Json::Array(vec![externs_type(operator)?, externs_type(left)?, externs_type(right)?]),
+        ),
+        (16, [type_]) => tagged("ParensInType", externs_type(type_)?),
+        // Unknowns and skolems only come up while checking.
+        _ => tagged("TUnknown", Json::Null),
+    };
+    Ok(type_)
+}
+
+fn externs_forall(
+    visible: bool,
+    name: &Cbor,
+    kind: &Cbor,
+    body: &Cbor,
+) -> Result<Json, ExternsError> {
+    let kind = match maybe(kind)? {
+        Some(kind) => externs_type(kind)?,
+        None => Json::Null,
+    };
+    let visibility = if visible { "TypeVarVisible" } else { "TypeVarInvisible" };
+    let contents = object([
+        ("identifier", string_json(text(name)?)),
+        ("kind", kind),
+        ("type", externs_type(body)?),
+        ("visibility", string_json(visibility)),
+    ]);
+    Ok(tagged("ForAll", contents))
+}
+
+fn externs_constraint(cbor: &Cbor) -> Result<Json, ExternsError> {
+    let [_, class, _, arguments, ..] = fields(cbor, "a constraint")? else {
+        return Err(shape("a constraint"));
+    };
+    let arguments = list(arguments, "a constraint")?.iter().map(externs_type);
+    Ok(object([
+        ("constraintClass", qualified_name(class)?),
+        ("constraintArgs", Json::Array(arguments.collect::<Result<_, _>>()?)),
+    ]))
+}
+
+fn externs_constraints(cbor: &Cbor) -> Result<Json, ExternsError> {
+    let constraints = list(cbor, "the constraints")?.iter().map(externs_constraint);
+    Ok(Json::Array(constraints.collect::<Result<_, _>>()?))
+}
+
+/// Type variables, each a tuple of its name and kind, along with its role
+/// for data types.
+fn externs_variables(cbor: &Cbor) -> Result<Json, ExternsError> {
+    let variables = list(cbor, "the type variables")?.iter().map(|variable| {
+        let [name, kind, ..] = variable.as_array().ok_or(shape("a type variable"))? else {
+            return Err(shape("a type variable"));
+        };
+        let kind = match maybe(kind)? {
+            Some(kind) => externs_type(kind)?,
+            None => Json::Null,
+        };
+        Ok(Json::Array(vec![string_json(text(name)?), kind]))
+    });
+    Ok(Json::Array(variables.collect::<Result<_, _>>()?))
+}
+
+/// A qualified name in the shape of `docs.json`, e.g.
+/// `[["Data", "Maybe"], "Maybe"]`.
+fn qualified_name(cbor: &Cbor) -> Result<Json, ExternsError> {
+    let (module, name) = qualified(cbor)?;
+    Ok(Json::Array(vec![module, string_json(text(name)?)]))
+}
+
+/// The module of a qualified name, as its segments, and the name itself.
+/// Names are qualified by their module or by where they're declared, and
+/// by an optional module in older compilers.
+fn qualified(cbor: &Cbor) -> Result<(Json, &Cbor), ExternsError> {
+    let [by, name] = fields(cbor, "a qualified name")? else {
+        return Err(shape("a qualified name"));
+    };
+    let module = match (by.as_constructor(), by.as_array()) {
+        (Some((1, [module])), _) | (None, Some([module])) => Some(text(module)?),
+        _ => None,
+    };
+    let segments = module.into_iter().flat_map(|module| module.split('.')).map(string_json);
+    Ok((Json::Array(segments.collect()), name))
+}
+
+/// The fields of a value, after its constructor's index.
+fn fields<'a>(cbor: &'a Cbor, context: &'static str) -> Result<&'a [Cbor], ExternsError> {
+    Ok(cbor.as_constructor().ok_or(shape(context))?.1)
+}
+
+fn tag(cbor: &Cbor) -> Option<i128> {
+    Some(cbor.as_constructor()?.0)
+}
+
+fn tag_is(cbor: &Cbor, index: i128) -> bool {
+    tag(cbor) == Some(index)
+}
+
+fn list<'a>(cbor: &'a Cbor, context: &'static str) -> Result<&'a [Cbor], ExternsError> {
+    cbor.as_array().ok_or(shape(context))
+}
+
+fn tuple(cbor: &Cbor) -> Result<[&Cbor; 2], ExternsError> {
+    match cbor.as_array() {
+        Some([first, second]) => Ok([first, second]),
+        _ => Err(shape("a pair")),
+    }
+}
+
+/// A `Maybe`, which is written as a list of none or one value.
+fn maybe(cbor: &Cbor) -> Result<Option<&Cbor>, ExternsError> {
+    match cbor.as_array() {
+        Some([]) => Ok(None),
+        Some([value]) => Ok(Some(value)),
+        _ => Err(shape("an optional value")),
+    }
+}
+
+/// The text of a name, which newtypes such as `ProperName` and
+/// constructors such as `Ident` wrap.
+fn text(cbor: &Cbor) -> Result<&str, ExternsError> {
+    match (cbor, cbor.as_constructor()) {
+        (Cbor::Text(text), _) => Ok(text),
+        (_, Some((0, [inner]))) => text(inner),
+        _ => Err(shape("a name")),
+    }
+}
+
+/// A `PSString`, which is a list of UTF-16 code units, such as a label or
+/// a type-level string.
+fn string(cbor: &Cbor) -> Result<String, ExternsError> {
+    let units = cbor.as_array().unwrap_or_default();
+    let units: Option<Vec<_>> =
+        units.iter().map(|unit| u16::try_from(unit.as_integer()?).ok()).collect();
+    match (cbor, units, cbor.as_constructor()) {
+        (Cbor::Text(text), ..) => Ok(text.clone()),
+        (_, Some(units), _) => Ok(String::from_utf16_lossy(&units)),
+        (_, _, Some((0, [inner]))) => string(inner),
+        _ => Err(shape("a string")),
+    }
+}
+
+fn object<const N: usize>(fields: [(&str, Json); N]) -> Json {
+    Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn tagged(tag: &str, contents: Json) -> Json {
+    object([("tag", string_json(tag)), ("contents", contents)])
+}
+
+fn string_json(text: &str) -> Json {
+    Json::String(text.to_string())
+}
+
+fn shape(context: &'static str) -> ExternsError {
+    ExternsError::Shape { context }
+}
+
+struct Reader {
+    module: ModuleName,
+    tree: ItemTree,
+    /// The other modules that types refer to.
+    imports: Vec<ModuleName>,
+}
+
+impl Reader {
+    fn declaration(&mut self, declaration: &Json) -> Result<(), ExternsError> {
+        let title = declaration.field("title").and_then(Json::as_str).ok_or(shape("a title"))?;
+        let info = declaration.field("info").ok_or(shape("a declaration"))?;
+        let children = declaration.field("children").and_then(Json::as_array).unwrap_or(&[]);
+        let kind = match declaration.field("kind") {
+            Some(kind) if kind != &Json::Null => {
+                Some(self.type_(kind.field("kind").unwrap_or(kind))?)
+            }
+            _ => None,
+        };
+        let name = Name::new(title);
+        let declaration_type = info.field("declType").and_then(Json::as_str).unwrap_or_default();
+
+        let item = match declaration_type {
+            "value" => {
+                let signature = Some(self.field_type(info, "type")?);
+                Item::Value(ValueItem { name, signature, equations: 0 })
+            }
+            "data" => {
+                let variables = self.variables(info.field("typeArguments"))?;
+                let mut constructors = vec![];
+                for child in children {
+                    let info = child.field("info").ok_or(shape("a constructor"))?;
+                    if info.field("declType").and_then(Json::as_str) != Some("dataConstructor") {
+                        continue;
+                    }
+                    let title =
+                        child.field("title").and_then(Json::as_str).ok_or(shape("a title"))?;
+                    let arguments = info.field("arguments").and_then(Json::as_array).unwrap_or(&[]);
+                    let fields = arguments.iter().map(|argument| self.type_(argument));
+                    let fields = fields.collect::<Result<_, _>>()?;
+                    constructors.push(Constructor { name: Name::new(title), fields });
+                }
+                match info.field("dataDeclType").and_then(Json::as_str) {
+                    Some("newtype") if constructors.len() == 1 => {
+                        let constructor = constructors.remove(0);
+                        Item::Newtype(NewtypeItem { name, kind, variables, constructor })
+                    }
+                    _ => Item::Data(DataItem { name, kind, variables, constructors }),
+                }
+            }
+            "typeSynonym" => {
+                let variables = self.variables(info.field("arguments"))?;
+                let type_ = self.field_type(info, "type")?;
+                Item::Synonym(SynonymItem { name, kind, variables, type_ })
+            }
+            "typeClass" => {
+                let variables = self.variables(info.field("arguments"))?;
+                let superclasses =
+                    info.field("superclasses").and_then(Json::as_array).unwrap_or(&[]);
+                let constraints = superclasses.iter().map(|constraint| self.constraint(constraint));
+                let constraints = constraints.collect::<Result<_, _>>()?;
+                let dependencies = info.field("fundeps").and_then(Json::as_array).unwrap_or(&[]);
+                let dependencies = dependencies.iter().map(dependency).collect::<Result<_, _>>()?;
+                let mut members = vec![];
+                for child in children {
+                    let Some(info) = child.field("info") else { continue };
+                    if info.field("declType").and_then(Json::as_str) != Some("typeClassMember") {
+                        continue;
+                    }
+                    let title =
+                        child.field("title").and_then(Json::as_str).ok_or(shape("a title"))?;
+                    let type_ = self.field_type(info, "type")?;
+                    members.push(Signature { name: Name::new(title), type_ });
+                }
+                Item::Class(ClassItem { name, kind, constraints, variables, dependencies, members })
+            }
+            "externData" => {
+                let kind = match kind {
+                    Some(kind) => kind,
+                    None => self.field_type(info, "kind")?,
+                };
+                Item::ForeignData(ForeignDataItem { name, kind })
+            }
+            "alias" => Item::Fixity(self.fixity(title, info)?),
+            _ => return Ok(()),
+        };
+        self.tree.items.alloc(item);
+
+        // Instances are listed under both their class and the types in their
+        // head, so each one is only added where it's first seen.
+        for child in children {
+            let Some(info) = child.field("info") else { continue };
+            if info.field("declType").and_then(Json::as_str) != Some("instance") {
+                continue;
+            }
+            let title = child.field("title").and_then(Json::as_str).ok_or(shape("a title"))?;
+            let name = Name::new(title);
+            let seen = self.tree.items.iter().any(
+                |(_, item)| matches!(item, Item::Instance(instance) if instance.name == Some(name)),
+            );
+            if !seen {
+                let instance = self.instance(name, info)?;
+                self.tree.items.alloc(Item::Instance(instance));
+            }
+        }
+        Ok(())
+    }
+
+    fn instance(&mut self, name: Name, info: &Json) -> Result<InstanceItem, ExternsError> {
+        let dependencies = info.field("dependencies").and_then(Json::as_array).unwrap_or(&[]);
+        let constraints = dependencies.iter().map(|constraint| self.constraint(constraint));
+        let constraints = constraints.collect::<Result<_, _>>()?;
+        // The head is a type: the class applied to the arguments.
+        let head = self.field_type(info, "type")?;
+        let (class, arguments) = match &self.tree.types[head] {
+            &Type::Constructor(class) => (class, vec![]),
+            Type::Application { function, arguments } => match self.tree.types[*function] {
+                Type::Constructor(class) => (class, arguments.clone()),
+                _ => return Err(shape("an instance head")),
+            },
+            _ => return Err(shape("an instance head")),
+        };
+        Ok(InstanceItem {
+            name: Some(name),
+            head: InstanceHead { constraints, class, arguments },
+            members: vec![],
+            chain_index: 0,
+            derived: false,
+            newtype: false,
+        })
+    }
+
+    fn fixity(&mut self, title: &str, info: &Json) -> Result<Fixity, ExternsError> {
+        let fixity = info.field("fixity").ok_or(shape("a fixity"))?;
+        let associativity = match fixity.field("associativity").and_then(Json::as_str) {
+            Some("infixl") => Associativity::Left,
+            Some("infixr") => Associativity::Right,
+            _ => Associativity::None,
+        };
+        let precedence = fixity.field("precedence").and_then(Json::as_f64).unwrap_or(9.0) as u8;
+        // The alias is a qualified name, whose name is `Left` for types, and
+        // `Right` of `Left` for values or `Right` of `Right` for constructors.
+        let alias = info.field("alias").and_then(Json::as_array).ok_or(shape("an alias"))?;
+        let [module, target] = alias else { return Err(shape("an alias")) };
+        let (target, is_type) = match (target.field("Left"), target.field("Right")) {
+            (Some(target), _) => (target, true),
+            (_, Some(target)) => {
+                (target.field("Left").or_else(|| target.field("Right")).unwrap_or(target), false)
+            }
+            _ => return Err(shape("an alias")),
+        };
+        let target = target.as_str().ok_or(shape("an alias"))?;
+        let target = self.qualified(module, target);
+        let operator =
+            title.trim_start_matches("type ").trim_start_matches('(').trim_end_matches(')');
+        Ok(Fixity {
+            associativity,
+            precedence: precedence.min(9),
+            is_type,
+            target,
+            operator: Name::new(operator),
+        })
+    }
+
+    fn variables(&mut self, variables: Option<&Json>) -> Result<Vec<TypeVariable>, ExternsError> {
+        let variables = variables.and_then(Json::as_array).unwrap_or(&[]);
+        let variables = variables.iter().map(|variable| {
+            let [name, kind] = variable.as_array().ok_or(shape("a type variable"))? else {
+                return Err(shape("a type variable"));
+            };
+            let name = Name::new(name.as_str().ok_or(shape("a type variable"))?);
+            let kind = match kind {
+                Json::Null => None,
+                kind => Some(self.type_(kind)?),
+            };
+            Ok(TypeVariable { name, kind, visible: false })
+        });
+        variables.collect()
+    }
+
+    fn field_type(&mut self, info: &Json, field: &'static str) -> Result<TypeId, ExternsError> {
+        self.type_(info.field(field).ok_or(shape("a type"))?)
+    }
+
+    /// A constraint, which is lowered like a type: its class applied to its
+    /// arguments.
+    fn constraint(&mut self, constraint: &Json) -> Result<TypeId, ExternsError> {
+        let class = constraint.field("constraintClass").ok_or(shape("a constraint"))?;
+        let class = self.qualified_json(class)?;
+        let class = self.alloc(Type::Constructor(class));
+        let arguments = constraint.field("constraintArgs").and_then(Json::as_array).unwrap_or(&[]);
+        if arguments.is_empty() {
+            return Ok(class);
+        }
+        let arguments = arguments.iter().map(|argument| self.type_(argument));
+        let arguments = arguments.collect::<Result<_, _>>()?;
+        Ok(self.alloc(Type::Application { function: class, arguments }))
+    }
+
+    fn type_(&mut self, json: &Json) -> Result<TypeId, ExternsError> {
+        let tag = json.field("tag").and_then(Json::as_str).ok_or(shape("a type"))?;
+        let contents = json.field("contents").unwrap_or(&Json::Null);
+        let type_ = match tag {
+            "TypeVar" => Type::Variable(Name::new(contents.as_str().ok_or(shape("a type"))?)),
+            "TypeLevelString" => {
+                Type::String(contents.as_str().ok_or(shape("a type"))?.to_string())
+            }
+            "TypeLevelInt" => Type::Integer(contents.as_f64().ok_or(shape("a type"))? as i64),
+            "TypeWildcard" => Type::Wildcard,
+            "TypeConstructor" => {
+                let name = self.qualified_json(contents)?;
+                match name {
+                    QualifiedName { qualifier: None, name } if name.as_str() == "Function" => {
+                        Type::Function
+                    }
+                    name => Type::Constructor(name),
+                }
+            }
+            "TypeOp" => Type::OperatorName(self.qualified_json(contents)?),
+            "TypeApp" => return self.application(json),
+            "KindApp" | "KindedType" => {
+                let [type_, kind] = pair(contents)?;
+                let type_ = self.type_(type_)?;
+                if tag == "KindApp" {
+                    return Ok(type_);
+                }
+                Type::Kinded { type_, kind: self.type_(kind)? }
+            }
+            "ParensInType" => return self.type_(contents),
+            "ForAll" => {
+                let mut variables = vec![];
+                let mut json = json;
+                while json.field("tag").and_then(Json::as_str) == Some("ForAll") {
+                    let contents = json.field("contents").ok_or(shape("a forall"))?;
+                    // Older compilers write a tuple of the variable, its kind,
+                    // and the body, rather than an object.
+                    let (name, kind, body, visible) = match contents {
+                        Json::Array(contents) => match &contents[..] {
+                            [name, kind, body, ..] => (name, kind, body, false),
+                            [name, body] => (name, &Json::Null, body, false),
+                            _ => return Err(shape("a forall")),
+                        },
+                        contents => (
+                            contents.field("identifier").ok_or(shape("a forall"))?,
+                            contents.field("kind").unwrap_or(&Json::Null),
+                            contents.field("type").ok_or(shape("a forall"))?,
+                            contents.field("visibility").and_then(Json::as_str)
+                                == Some("TypeVarVisible"),
+                        ),
+                    };
+                    let name = Name::new(name.as_str().ok_or(shape("a forall"))?);
+                    let kind = match kind {
+                        Json::Null => None,
+                        kind => Some(self.type_(kind)?),
+                    };
+                    variables.push(TypeVariable { name, kind, visible });
+                    json = body;
+                }
+                Type::Forall { variables, type_: self.type_(json)? }
+            }
+            "ConstrainedType" => {
+                let [constraint, type_] = pair(contents)?;
+                let constraint = self.constraint(constraint)?;
+                Type::Constrained { constraint, type_: self.type_(type_)? }
+            }
+            "REmpty" | "RCons" => Type::Row(self.row(json)?),
+            "BinaryNoParensType" => {
+                let [operator, left, right] = contents.as_array().ok_or(shape("a type"))? else {
+                    return Err(shape("a type"));
+                };
+                let head = self.type_(left)?;
+                let operator = match operator.field("contents") {
+                    Some(operator) => self.qualified_json(operator)?,
+                    None => return Err(shape("a type operator")),
+                };
+                Type::OperatorChain { head, tail: vec![(operator, self.type_(right)?)] }
+            }
+            _ => Type::Missing,
+        };
+        Ok(self.alloc(type_))
+    }
+
+    /// Flattens nested applications, turning applications of `Function` and
+    /// `Record` into their syntactic forms.
+    fn application(&mut self, json: &Json) -> Result<TypeId, ExternsError> {
+        let mut arguments = vec![];
+        let mut function = json;
+        while function.field("tag").and_then(Json::as_str) == Some("TypeApp") {
+            let [inner, argument] = pair(function.field("contents").unwrap_or(&Json::Null))?;
+            arguments.push(argument);
+            function = inner;
+        }
+        arguments.reverse();
+
+        let function = self.type_(function)?;
+        match (&self.tree.types[function], &arguments[..]) {
+            (Type::Function, [argument, result]) => {
+                let argument = self.type_(argument)?;
+                let result = self.type_(result)?;
+                return Ok(self.alloc(Type::Arrow { argument, result }));
+            }
+            (Type::Constructor(name), [row])
+                if name.qualifier.is_none() && name.name.as_str() == "Record" =>
+            {
+                let row = self.row(row)?;
+                return Ok(self.alloc(Type::Record(row)));
+            }
+            _ => {}
+        }
+        let arguments = arguments.into_iter().map(|argument| self.type_(argument));
+        let arguments = arguments.collect::<Result<_, _>>()?;
+        Ok(self.alloc(Type::Application { function, arguments }))
+    }
+
+    fn row(&mut self, mut json: &Json) -> Result<Row, ExternsError> {
+        let mut fields = vec![];
+        let tail = loop {
+            match json.field("tag").and_then(Json::as_str) {
+                Some("REmpty") => break None,
+                Some("RCons") => {
+                    let contents = json.field("contents").and_then(Json::as_array);
+                    let Some([label, type_, rest]) = contents else { return Err(shape("a row")) };
+                    let label = Name::new(label.as_str().ok_or(shape("a row"))?);
+                    fields.push((label, self.type_(type_)?));
+                    json = rest;
+                }
+                _ => break Some(self.type_(json)?),
+            }
+        };
+        Ok(Row { fields, tail })
+    }
+
+    /// A qualified name written as `[["Data", "Maybe"], "Maybe"]`.
+    fn qualified_json(&mut self, json: &Json) -> Result<QualifiedName, ExternsError> {
+        let [module, name] = pair(json)?;
+        let name = name.as_str().ok_or(shape("a qualified name"))?;
+        Ok(self.qualified(module, name))
+    }
+
+    fn qualified(&mut self, module: &Json, name: &str) -> QualifiedName {
+        let segments: Vec<_> =
+            module.as_array().unwrap_or(&[]).iter().filter_map(Json::as_str).collect();
+        let name = Name::new(name);
+        if segments.is_empty() || segments == ["Prim"] {
+            return QualifiedName { qualifier: None, name };
+        }
+        let module = ModuleName::new(&segments.join("."));
+        if module == self.module {
+            return QualifiedName { qualifier: None, name };
+        }
+        if !self.imports.contains(&module) {
+            self.imports.push(module);
+        }
+        QualifiedName { qualifier: Some(module), name }
+    }
+
+    fn alloc(&mut self, type_: Type) -> TypeId {
+        self.tree.types.alloc(type_)
+    }
+
+    fn finish(self) -> ItemTree {
+        let Reader { module, mut tree, imports } = self;
+        tree.name = Some(module);
+        tree.imports = imports
+            .into_iter()
+            .map(|module| Import { module, list: None, alias: Some(module) })
+            .collect();
+        tree
+    }
+}
+
+fn pair(json: &Json) -> Result<[&Json; 2], ExternsError> {
+    match json.as_array() {
+        Some([first, second]) => Ok([first, second]),
+        _ => Err(shape("a pair")),
+    }
+}
+
+fn dependency(json: &Json) -> Result<FunctionalDependency, ExternsError> {
+    let names = |json: &Json| -> Result<Vec<Name>, ExternsError> {
+        let names = json.as_array().ok_or(shape("a functional dependency"))?;
+        let names = names.iter().map(|name| name.as_str().map(Name::new));
+        names.collect::<Option<_>>().ok_or(shape("a functional dependency"))
+    };
+    let [determiners, determined] = pair(json)?;
+    Ok(FunctionalDependency { determiners: names(determiners)?, determined: names(determined)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{load_output, read_docs, read_externs, ExternsError};
+    use crate::{
+        cbor::{tests::encode, Cbor},
+        hir::{Type, TypeId},
+        item_tree::{Item, ItemTree},
+    };
+
+    fn items(tree: &ItemTree) -> Vec<String> {
+        tree.items
+            .iter()
+            .map(|(_, item)| match item {
+                Item::Data(data) => {
+                    let constructors: Vec<_> = data
+                        .constructors
+                        .iter()
+                        .map(|constructor| constructor.name.to_string())
+                        .collect();
+                    format!("data {} = {}", data.name, constructors.join(" | "))
+                }
+                Item::Class(class) => {
+                    let members: Vec<_> = class
+                        .members
+                        .iter()
+                        .map(|member| format!("{} :: {}", member.name, print(tree, member.type_)))
+                        .collect();
+                    format!("class {} where {}", class.name, members.join(", "))
+                }
+                Item::Instance(instance) => {
+                    let arguments: Vec<_> = instance
+                        .head
+                        .arguments
+                        .iter()
+                        .map(|&argument| print(tree, argument))
+                        .collect();
+                    format!("instance {} {}", instance.head.class, arguments.join(" "))
+                }
+                Item::Value(value) => {
+                    format!("{} :: {}", value.name, print(tree, value.signature.unwrap()))
+                }
+                Item::Fixity(fixity) => {
+                    format!("infix {} {} as {}", fixity.precedence, fixity.target, fixity.operator)
+                }
+                item => format!("{item:?}"),
+            })
+            .collect()
+    }
+
+    fn print(tree: &ItemTree, id: TypeId) -> String {
+        match &tree.types[id] {
+            Type::Constructor(name) | Type::OperatorName(name) => name.to_string(),
+            Type::Variable(name) => name.to_string(),
+            &Type::Arrow { argument, result } => {
+                format!("({} -> {})", print(tree, argument), print(tree, result))
+            }
+            Type::Application { function, arguments } => {
+                let arguments: Vec<_> =
+                    arguments.iter().map(|&argument| print(tree, argument)).collect();
+                format!("({} {})", print(tree, *function), arguments.join(" "))
+            }
+            Type::Forall { variables, type_ } => {
+                let variables: Vec<_> =
+                    variables.iter().map(|variable| variable.name.as_str()).collect();
+                format!("forall {}. {}", variables.join(" "), print(tree, *type_))
+            }
+            &Type::Constrained { constraint, type_ } => {
+                format!("{} => {}", print(tree, constraint), print(tree, type_))
+            }
+            Type::Record(row) => {
+                let fields: Vec<_> = row
+                    .fields
+                    .iter()
+                    .map(|&(label, field)| format!("{label} :: {}", print(tree, field)))
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            type_ => format!("{type_:?}"),
+        }
+    }
+
+    #[test]
+    fn read_docs_json() {
+        let source = r#"{
+  "name": "Data.Maybe",
+  "comments": null,
+  "declarations": [
+    {
+      "title": "Maybe",
+      "info": { "declType": "data", "dataDeclType": "data", "typeArguments": [["a", null]], "roles": ["Representational"] },
+      "kind": null,
+      "children": [
+        { "title": "Nothing", "info": { "declType": "dataConstructor", "arguments": [] }, "children": [] },
+        { "title": "Just", "info": { "declType": "dataConstructor", "arguments": [{ "annotation": [], "tag": "TypeVar", "contents": "a" }] }, "children": [] },
+        {
+          "title": "showMaybe",
+          "info": {
+            "declType": "instance",
+            "dependencies": [{ "constraintAnn": [], "constraintClass": [["Data", "Show"], "Show"], "constraintKindArgs": [], "constraintArgs": [{ "annotation": [], "tag": "TypeVar", "contents": "a" }], "constraintData": null }],
+            "type": { "annotation": [], "tag": "TypeApp", "contents": [
+              { "annotation": [], "tag": "TypeConstructor", "contents": [["Data", "Show"], "Show"] },
+              { "annotation": [], "tag": "TypeApp", "contents": [
+                { "annotation": [], "tag": "TypeConstructor", "contents": [["Data", "Maybe"], "Maybe"] },
+                { "annotation": [], "tag": "TypeVar", "contents": "a" }
+              ] }
+            ] }
+          },
+          "children": []
+        }
+      ]
+    },
+    {
+      "title": "fromMaybe",
+      "info": {
+        "declType": "value",
+        "type": { "annotation": [], "tag": "ForAll", "contents": { "identifier": "a", "kind": null, "skolem": null, "visibility": "TypeVarInvisible", "type": {
+          "annotation": [], "tag": "TypeApp", "contents": [
+            { "annotation": [], "tag": "TypeApp", "contents": [
+              { "annotation": [], "tag": "TypeConstructor", "contents": [["Prim"], "Function"] },
+              { "annotation": [], "tag": "TypeVar", "contents": "a" }
+            ] },
+            { "annotation": [], "tag": "TypeApp", "contents": [
+              { "annotation": [], "tag": "TypeConstructor", "contents": [["Prim"], "Record"] },
+              { "annotation": [], "tag": "RCons", "contents": ["value", { "annotation": [], "tag": "TypeConstructor", "contents": [["Prim"], "Int"] }, { "annotation": [], "tag": "REmpty" }] }
+            ] }
+          ]
+        } } }
+      },
+      "children": []
+    },
+    {
+      "title": "(<|>)",
+      "info": { "declType": "alias", "fixity": { "associativity": "infixl", "precedence": 3 }, "alias": [["Data", "Maybe"], { "Right": { "Left": "alt" } }] },
+      "children": []
+    }
+  ],
+  "reExports": []
+}"#;
+        let tree = read_docs(source).unwrap();
+        let imports: Vec<_> = tree.imports.iter().map(|import| import.module.to_string()).collect();
+        assert_eq!(imports, ["Data.Show"]);
+
+        assert_eq!(
+            items(&tree),
+            [
+                "data Maybe = Nothing | Just",
+                "instance Data.Show.Show (Maybe a)",
+                "fromMaybe :: forall a. (a -> { value :: Int })",
+                "infix 3 alt as <|>",
+            ]
+        );
+    }
+
+    /// An `ExternsFile` written by `version` with the given declarations,
+    /// built the way `Serialise` encodes each value.
+    fn externs_file(version: &str, module: &str, declarations: Vec<Cbor>) -> Vec<u8> {
+        let fixity = constructor(
+            0,
+            vec![
+                constructor(0, vec![]),
+                Cbor::Integer(3),
+                name("<|>"),
+                qualified(Some(module), constructor(0, vec![name("fromMaybe")])),
+            ],
+        );
+        let file = constructor(
+            0,
+            vec![
+                Cbor::Text(version.to_string()),
+                name(module),
+                Cbor::Array(vec![]),
+                Cbor::Array(vec![]),
+                Cbor::Array(vec![fixity]),
+                Cbor::Array(vec![]),
+                Cbor::Array(declarations),
+                Cbor::Null,
+            ],
+        );
+        let mut bytes = vec![];
+        encode(&file, &mut bytes);
+        bytes
+    }
+
+    fn constructor(tag: i128, fields: Vec<Cbor>) -> Cbor {
+        Cbor::Array([Cbor::Integer(tag)].into_iter().chain(fields).collect())
+    }
+
+    fn name(text: &str) -> Cbor {
+        constructor(0, vec![Cbor::Text(text.to_string())])
+    }
+
+    fn pair(first: Cbor, second: Cbor) -> Cbor {
+        Cbor::Array(vec![first, second])
+    }
+
+    fn qualified(module: Option<&str>, name_: Cbor) -> Cbor {
+        let by = match module {
+            Some(module) => constructor(1, vec![name(module)]),
+            None => constructor(0, vec![Cbor::Null]),
+        };
+        constructor(0, vec![by, name_])
+    }
+
+    fn type_(tag: i128, fields: Vec<Cbor>) -> Cbor {
+        constructor(tag, [Cbor::Array(vec![])].into_iter().chain(fields).collect())
+    }
+
+    fn variable(name: &str) -> Cbor {
+        type_(1, vec![Cbor::Text(name.to_string())])
+    }
+
+    fn type_constructor(module: &str, name_: &str) -> Cbor {
+        type_(5, vec![qualified(Some(module), name(name_))])
+    }
+
+    fn application(function: Cbor, argument: Cbor) -> Cbor {
+        type_(7, vec![function, argument])
+    }
+
+    fn function(argument: Cbor, result: Cbor) -> Cbor {
+        application(application(type_constructor("Prim", "Function"), argument), result)
+    }
+
+    #[test]
+    fn read_externs_cbor() {
+        let maybe = || application(type_constructor("Data.Maybe", "Maybe"), variable("a"));
+        let kind = function(type_constructor("Prim", "Type"), type_constructor("Prim", "Type"));
+        let declarations = vec![
+            constructor(
+                0,
+                vec![
+                    name("Maybe"),
+                    kind.clone(),
+                    constructor(
+                        0,
+                        vec![
+                            constructor(0, vec![]),
+                            Cbor::Array(vec![Cbor::Array(vec![
+                                Cbor::Text("a".to_string()),
+                                Cbor::Array(vec![]),
+                                constructor(0, vec![]),
+                            ])]),
+                            Cbor::Array(vec![
+                                pair(name("Nothing"), Cbor::Array(vec![])),
+                                pair(name("Just"), Cbor::Array(vec![variable("a")])),
+                            ]),
+                        ],
+                    ),
+                ],
+            ),
+            constructor(
+                3,
+                vec![
+                    name("fromMaybe"),
+                    type_(
+                        9,
+                        vec![
+                            constructor(1, vec![]),
+                            Cbor::Text("a".to_string()),
+                            Cbor::Array(vec![]),
+                            function(variable("a"), function(maybe(), variable("a"))),
+                            Cbor::Array(vec![]),
+                        ],
+                    ),
+                ],
+            ),
+            constructor(0, vec![name("Show"), kind.clone(), constructor(2, vec![])]),
+            constructor(0, vec![name("Show$Dict"), kind, constructor(1, vec![])]),
+            constructor(
+                4,
+                vec![
+                    name("Show"),
+                    Cbor::Array(vec![pair(Cbor::Text("a".to_string()), Cbor::Array(vec![]))]),
+                    Cbor::Array(vec![pair(
+                        name("show"),
+                        function(variable("a"), type_constructor("Prim", "String")),
+                    )]),
+                    Cbor::Array(vec![]),
+                    Cbor::Array(vec![]),
+                    Cbor::Boolean(false),
+                ],
+            ),
+            constructor(
+                5,
+                vec![
+                    qualified(Some("Data.Maybe"), name("Show")),
+                    name("showMaybe"),
+                    Cbor::Array(vec![]),
+                    Cbor::Array(vec![]),
+                    Cbor::Array(vec![maybe()]),
+                    Cbor::Array(vec![Cbor::Array(vec![])]),
+                    Cbor::Array(vec![]),
+                    Cbor::Integer(0),
+                ],
+            ),
+        ];
+        let tree = read_externs(&externs_file("0.15.15", "Data.Maybe", declarations)).unwrap();
+        assert!(tree.imports.is_empty());
+        assert_eq!(
+            items(&tree),
+            [
+                "data Maybe = Nothing | Just",
+                "fromMaybe :: forall a. (a -> ((Maybe a) -> a))",
+                "class Show where show :: (a -> String)",
+                "instance Show (Maybe a)",
+                "infix 3 fromMaybe as <|>",
+            ]
+        );
+
+        let old = externs_file("0.14.5", "Data.Maybe", vec![]);
+        assert_eq!(
+            read_externs(&old),
+            Err(ExternsError::Version { version: "0.14.5".to_string() })
+        );
+    }
+
+    #[test]
+    fn load_output_directory() {
+        let output = std::env::temp_dir().join(format!("lowering-output-{}", std::process::id()));
+        let write = |module: &str, file: &str, contents: &[u8]| {
+            fs::create_dir_all(output.join(module)).unwrap();
+            fs::write(output.join(module).join(file), contents).unwrap();
+        };
+        write("A", "externs.cbor", &externs_file("0.15.15", "A", vec![]));
+        write("B", "externs.cbor", &externs_file("0.14.5", "B", vec![]));
+        write("B", "docs.json", br#"{ "name": "B", "declarations": [] }"#);
+        write("C", "docs.json", b"{");
+        write("D", "externs.cbor", &externs_file("0.14.5", "D", vec![]));
+
+        let loaded = load_output(&output);
+        let trees: Vec<_> =
+            loaded.trees.iter().map(|tree| tree.name.unwrap().to_string()).collect();
+        assert_eq!(trees, ["A", "B"]);
+        let errors: Vec<_> = loaded
+            .errors
+            .iter()
+            .map(|(path, error)| {
+                (path.strip_prefix(&output).unwrap().to_path_buf(), error.message())
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                ("C/docs.json".into(), "invalid JSON at byte 1".to_string()),
+                (
+                    "D/externs.cbor".into(),
+                    "written by purs 0.14.5, whose externs can't be read".to_string()
+                ),
+            ]
+        );
+        fs::remove_dir_all(&output).unwrap();
+        assert!(load_output(&output).errors.is_empty());
+    }
+}
@@ -0,0 +1,431 @@
+//! The high-level intermediate representation.
+//!
+//! Declarations, expressions, binders, and types refer to each other through
+//! indices into the arenas owned by [`Module`] and each [`Body`]. Parentheses, trivia, and
+//! other syntactic details are not represented.
+
+pub use syntax::ast::Associativity;
+
+use crate::{
+    arena::{Arena, Idx},
+    name::{ModuleName, Name, QualifiedName},
+};
+
+pub type DeclarationId = Idx<Declaration>;
+pub type BodyId = Idx<Body>;
+pub type ExprId = Idx<Expr>;
+pub type BinderId = Idx<Binder>;
+pub type TypeId = Idx<Type>;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Module {
+    pub name: Option<ModuleName>,
+    /// `None` if the module exports everything.
+    pub exports: Option<Vec<Export>>,
+    pub imports: Vec<Import>,
+    pub declarations: Arena<Declaration>,
+    pub bodies: Arena<Body>,
+    pub types: Arena<Type>,
+}
+
+/// The body of a value, which owns the expressions and binders within it,
+/// such that edits to one body leave the indices in others intact.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Body {
+    pub exprs: Arena<Expr>,
+    pub binders: Arena<Binder>,
+    pub guarded: GuardedExpr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Export {
+    Value(Name),
+    Operator(Name),
+    Type(Name, Option<DataMembers>),
+    TypeOperator(Name),
+    Class(Name),
+    Module(ModuleName),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataMembers {
+    All,
+    Enumerated(Vec<Name>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub module: ModuleName,
+    pub list: Option<ImportList>,
+    pub alias: Option<ModuleName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportList {
+    pub hiding: bool,
+    pub items: Vec<ImportItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportItem {
+    Value(Name),
+    Operator(Name),
+    Type(Name, Option<DataMembers>),
+    TypeOperator(Name),
+    Class(Name),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Declaration {
+    Value(Value),
+    Signature(Signature),
+    KindSignature(KindSignature),
+    Data(Data),
+    Newtype(Newtype),
+    Synonym(Synonym),
+    Class(Class),
+    InstanceChain(Vec<Instance>),
+    Derive(Derive),
+    ForeignData(ForeignData),
+    ForeignValue(ForeignValue),
+    Fixity(Fixity),
+}
+
+/// A value declaration, with its equations combined into a single body that
+/// matches on the binders of each equation in turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    pub name: Name,
+    pub body: BodyId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: Name,
+    pub type_: TypeId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KindSignatureTarget {
+    Data,
+    Newtype,
+    Synonym,
+    Class,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindSignature {
+    pub target: KindSignatureTarget,
+    pub name: Name,
+    pub kind: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeVariable {
+    pub name: Name,
+    pub kind: Option<TypeId>,
+    /// Whether the variable is bound with `@` in a `forall`, such that it
+    /// can be instantiated with a visible type application.
+    pub visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Data {
+    pub name: Name,
+    pub variables: Vec<TypeVariable>,
+    pub constructors: Vec<Constructor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    pub name: Name,
+    pub fields: Vec<TypeId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Newtype {
+    pub name: Name,
+    pub variables: Vec<TypeVariable>,
+    pub constructor: Constructor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Synonym {
+    pub name: Name,
+    pub variables: Vec<TypeVariable>,
+    pub type_: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    /// Superclass constraints.
+    pub constraints: Vec<TypeId>,
+    pub name: Name,
+    pub variables: Vec<TypeVariable>,
+    pub dependencies: Vec<FunctionalDependency>,
+    pub members: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionalDependency {
+    pub determiners: Vec<Name>,
+    pub determined: Vec<Name>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    pub name: Option<Name>,
+    pub head: InstanceHead,
+    pub members: Vec<InstanceMember>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceHead {
+    pub constraints: Vec<TypeId>,
+    pub class: QualifiedName,
+    pub arguments: Vec<TypeId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstanceMember {
+    Value(Value),
+    Signature(Signature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derive {
+    pub newtype: bool,
+    pub name: Option<Name>,
+    pub head: InstanceHead,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignData {
+    pub name: Name,
+    pub kind: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignValue {
+    pub name: Name,
+    pub type_: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: u8,
+    /// Determines if this declares a type operator.
+    pub is_type: bool,
+    pub target: QualifiedName,
+    pub operator: Name,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Missing,
+    Typed {
+        expr: ExprId,
+        type_: TypeId,
+    },
+    /// A flat chain of operators, to be re-associated once fixity
+    /// information is available.
+    OperatorChain {
+        head: ExprId,
+        tail: Vec<(Operator, ExprId)>,
+    },
+    Negate(ExprId),
+    Application {
+        function: ExprId,
+        arguments: Vec<ExprId>,
+    },
+    /// A visible type application, e.g. `identity @Int`.
+    TypeApplication {
+        expr: ExprId,
+        type_: TypeId,
+    },
+    Variable(QualifiedName),
+    Constructor(QualifiedName),
+    OperatorName(QualifiedName),
+    Literal(Literal<ExprId>),
+    Hole(Name),
+    /// The placeholder in sections such as `(_ + 1)`.
+    Wildcard,
+    RecordAccess {
+        record: ExprId,
+        labels: Vec<Name>,
+    },
+    /// Record updates with nested updates expanded, e.g. `r { a { b = 1 } }`
+    /// becomes `r { a = r.a { b = 1 } }`.
+    RecordUpdate {
+        record: ExprId,
+        updates: Vec<(Name, ExprId)>,
+    },
+    IfThenElse {
+        condition: ExprId,
+        then: ExprId,
+        else_: ExprId,
+    },
+    Lambda {
+        binders: Vec<BinderId>,
+        body: ExprId,
+    },
+    Case {
+        scrutinees: Vec<ExprId>,
+        branches: Vec<CaseBranch>,
+    },
+    LetIn {
+        bindings: Vec<LetBinding>,
+        body: ExprId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operator {
+    Symbol(QualifiedName),
+    /// A function applied infix with backticks.
+    Infix(ExprId),
+}
+
+/// A `Number` literal, which compares by its representation so that the HIR
+/// can be `Eq`.
+#[derive(Debug, Clone, Copy)]
+pub struct Number(pub f64);
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Number {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal<I> {
+    Int(i32),
+    Number(Number),
+    String(String),
+    Char(char),
+    Boolean(bool),
+    Array(Vec<I>),
+    /// Records with puns expanded, e.g. `{ x }` becomes `{ x: x }`.
+    Record(Vec<(Name, I)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseBranch {
+    pub binders: Vec<BinderId>,
+    pub guarded: GuardedExpr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardedExpr {
+    Unconditional(ExprId),
+    Guarded(Vec<GuardedBranch>),
+    /// Guarded alternatives under bindings that scope over every guard,
+    /// desugared from a `where` clause. Unconditional expressions use an
+    /// [`Expr::LetIn`] instead.
+    LetIn {
+        bindings: Vec<LetBinding>,
+        branches: Vec<GuardedBranch>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardedBranch {
+    pub guards: Vec<Guard>,
+    pub expr: ExprId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Guard {
+    Boolean(ExprId),
+    Pattern(BinderId, ExprId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LetBinding {
+    /// A value, with its equations combined, named by a [`Binder::Variable`].
+    Value {
+        binder: BinderId,
+        guarded: GuardedExpr,
+    },
+    Signature(Signature),
+    Pattern {
+        binder: BinderId,
+        guarded: GuardedExpr,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Binder {
+    Missing,
+    Wildcard,
+    Variable(Name),
+    Named {
+        name: Name,
+        binder: BinderId,
+    },
+    Constructor {
+        name: QualifiedName,
+        arguments: Vec<BinderId>,
+    },
+    /// Negative numeric literals are folded into the literal.
+    Literal(Literal<BinderId>),
+    Typed {
+        binder: BinderId,
+        type_: TypeId,
+    },
+    OperatorChain {
+        head: BinderId,
+        tail: Vec<(QualifiedName, BinderId)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Missing,
+    Forall {
+        variables: Vec<TypeVariable>,
+        type_: TypeId,
+    },
+    Constrained {
+        constraint: TypeId,
+        type_: TypeId,
+    },
+    Arrow {
+        argument: TypeId,
+        result: TypeId,
+    },
+    OperatorChain {
+        head: TypeId,
+        tail: Vec<(QualifiedName, TypeId)>,
+    },
+    Kinded {
+        type_: TypeId,
+        kind: TypeId,
+    },
+    Application {
+        function: TypeId,
+        arguments: Vec<TypeId>,
+    },
+    Constructor(QualifiedName),
+    Variable(Name),
+    Wildcard,
+    Hole(Name),
+    String(String),
+    Integer(i64),
+    Record(Row),
+    Row(Row),
+    OperatorName(QualifiedName),
+    /// The function type constructor, `(->)`.
+    Function,
+    /// A parenthesized sequence of constraints, e.g. `(Show a, Eq a)`.
+    Constraints(Vec<TypeId>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub fields: Vec<(Name, TypeId)>,
+    pub tail: Option<TypeId>,
+}
@@ -0,0 +1,373 @@
+//! A summary of the items declared in a module.
+//!
+//! The [`ItemTree`] only contains the parts of a module that are visible to
+//! other modules: its header, and the skeletons of its declarations. Value
+//! bodies, instance member bodies, and local bindings are not represented,
+//! such that editing them produces an equal [`ItemTree`], and results that
+//! are derived from it don't have to be recomputed.
+
+use rowan::ast::AstNode;
+use rustc_hash::FxHashMap;
+use syntax::ast;
+
+use crate::{
+    arena::{Arena, Idx},
+    ast_id::{AstIdKind, AstIdMap, ErasedAstId},
+    hir::{
+        Constructor, Export, Fixity, FunctionalDependency, Import, InstanceHead,
+        KindSignatureTarget, Signature, Type, TypeId, TypeVariable,
+    },
+    lower::{self, Ctx},
+    name::{ModuleName, Name},
+};
+
+pub type ItemId = Idx<Item>;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ItemTree {
+    pub name: Option<ModuleName>,
+    /// `None` if the module exports everything.
+    pub exports: Option<Vec<Export>>,
+    pub imports: Vec<Import>,
+    pub items: Arena<Item>,
+    pub types: Arena<Type>,
+    pub(crate) ast_ids: Vec<ErasedAstId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    /// A value, which groups all of its equations with its signature.
+    Value(ValueItem),
+    Data(DataItem),
+    Newtype(NewtypeItem),
+    Synonym(SynonymItem),
+    Class(ClassItem),
+    Instance(InstanceItem),
+    ForeignData(ForeignDataItem),
+    ForeignValue(ForeignValueItem),
+    Fixity(Fixity),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueItem {
+    pub name: Name,
+    pub signature: Option<TypeId>,
+    pub equations: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataItem {
+    pub name: Name,
+    pub kind: Option<TypeId>,
+    pub variables: Vec<TypeVariable>,
+    pub constructors: Vec<Constructor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewtypeItem {
+    pub name: Name,
+    pub kind: Option<TypeId>,
+    pub variables: Vec<TypeVariable>,
+    pub constructor: Constructor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SynonymItem {
+    pub name: Name,
+    pub kind: Option<TypeId>,
+    pub variables: Vec<TypeVariable>,
+    pub type_: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassItem {
+    pub name: Name,
+    pub kind: Option<TypeId>,
+    pub constraints: Vec<TypeId>,
+    pub variables: Vec<TypeVariable>,
+    pub dependencies: Vec<FunctionalDependency>,
+    pub members: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceItem {
+    pub name: Option<Name>,
+    pub head: InstanceHead,
+    /// The names of the members defined by the instance.
+    pub members: Vec<Name>,
+    /// The position of the instance in its chain, starting from zero.
+    pub chain_index: usize,
+    pub derived: bool,
+    pub newtype: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignDataItem {
+    pub name: Name,
+    pub kind: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignValueItem {
+    pub name: Name,
+    pub type_: TypeId,
+}
+
+impl Item {
+    /// Returns `None` for instances without a name.
+    pub fn name(&self) -> Option<Name> {
+        match self {
+            Item::Value(item) => Some(item.name),
+            Item::Data(item) => Some(item.name),
+            Item::Newtype(item) => Some(item.name),
+            Item::Synonym(item) => Some(item.name),
+            Item::Class(item) => Some(item.name),
+            Item::Instance(item) => item.name,
+            Item::ForeignData(item) => Some(item.name),
+            Item::ForeignValue(item) => Some(item.name),
+            Item::Fixity(item) => Some(item.operator),
+        }
+    }
+}
+
+impl ItemTree {
+    pub fn lower(module: &ast::Module) -> ItemTree {
+        let ast_id_map = AstIdMap::from_module(module);
+        let mut collector = Collector {
+            ctx: Ctx::default(),
+            tree: ItemTree::default(),
+            values: FxHashMap::default(),
+            signatures: FxHashMap::default(),
+            kinds: FxHashMap::default(),
+            ast_id: ErasedAstId { kind: AstIdKind::Value, index: 0 },
+        };
+
+        if let Some(header) = module.header() {
+            collector.tree.name = header.name().map(|name| lower::lower_module_name(&name));
+            collector.tree.exports = lower::lower_exports(header.exports());
+            collector.tree.imports = lower::lower_imports(header.imports());
+        }
+
+        for declaration in module.declarations() {
+            let Some(ast_id) = ast_id_map.erased_ast_id(declaration.syntax()) else { continue };
+            collector.ast_id = ast_id;
+            collector.declaration(&declaration);
+        }
+
+        collector.finish()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, &Item)> {
+        self.items.iter()
+    }
+
+    /// Returns the [`ErasedAstId`] of the declaration that introduces an
+    /// item, which for values is their first equation. Items read from
+    /// [`externs`](crate::externs) have no syntax, and return `None`.
+    pub fn ast_id(&self, id: ItemId) -> Option<ErasedAstId> {
+        self.ast_ids.get(id.into_raw() as usize).copied()
+    }
+}
+
+/// Collects items, attaching signatures to the items they belong to.
+struct Collector {
+    ctx: Ctx,
+    tree: ItemTree,
+    values: FxHashMap<Name, ItemId>,
+    signatures: FxHashMap<Name, (TypeId, ErasedAstId)>,
+    kinds: FxHashMap<(Name, KindSignatureTarget), TypeId>,
+    /// The [`ErasedAstId`] of the current declaration.
+    ast_id: ErasedAstId,
+}
+
+impl Collector {
+    fn declaration(&mut self, declaration: &ast::Declaration) {
+        match declaration {
+            ast::Declaration::Value(value) => {
+                let name = lower::lower_name(value.name());
+                if let Some(&id) = self.values.get(&name) {
+                    if let Item::Value(item) = &mut self.tree.items[id] {
+                        item.equations += 1;
+                    }
+                } else {
+                    let signature = self.signatures.remove(&name).map(|(type_, _)| type_);
+                    let id = self.alloc(Item::Value(ValueItem { name, signature, equations: 1 }));
+                    self.values.insert(name, id);
+                }
+            }
+            ast::Declaration::Annotation(annotation) => {
+                let name = lower::lower_name(annotation.name());
+                let type_ = self.ctx.lower_type_opt(annotation.type_());
+                match self.values.get(&name).map(|&id| &mut self.tree.items[id]) {
+                    Some(Item::Value(item)) if item.signature.is_none() => {
+                        item.signature = Some(type_);
+                    }
+                    _ => {
+                        self.signatures.insert(name, (type_, self.ast_id));
+                    }
+                }
+            }
+            ast::Declaration::KindSignature(signature) => {
+                let target = lower::lower_kind_signature_target(signature);
+                let name = lower::lower_name(signature.name());
+                let kind = self.ctx.lower_type_opt(signature.kind());
+                self.kinds.insert((name, target), kind);
+            }
+            ast::Declaration::Data(data) => {
+                let name = lower::lower_name(data.name());
+                let kind = self.kinds.remove(&(name, KindSignatureTarget::Data));
+                let variables = self.ctx.lower_type_variables(data.variables());
+                let constructors = data
+                    .constructors()
+                    .map(|constructor| self.ctx.lower_constructor(&constructor))
+                    .collect();
+                self.alloc(Item::Data(DataItem { name, kind, variables, constructors }));
+            }
+            ast::Declaration::Newtype(newtype) => {
+                let name = lower::lower_name(newtype.name());
+                let kind = self.kinds.remove(&(name, KindSignatureTarget::Newtype));
+                let variables = self.ctx.lower_type_variables(newtype.variables());
+                let constructor = match newtype.constructor() {
+                    Some(constructor) => self.ctx.lower_constructor(&constructor),
+                    None => Constructor { name: Name::missing(), fields: vec![] },
+                };
+                self.alloc(Item::Newtype(NewtypeItem { name, kind, variables, constructor }));
+            }
+            ast::Declaration::Type(synonym) => {
+                let name = lower::lower_name(synonym.name());
+                let kind = self.kinds.remove(&(name, KindSignatureTarget::Synonym));
+                let variables = self.ctx.lower_type_variables(synonym.variables());
+                let type_ = self.ctx.lower_type_opt(synonym.type_());
+                self.alloc(Item::Synonym(SynonymItem { name, kind, variables, type_ }));
+            }
+            ast::Declaration::Class(class) => {
+                let name = lower::lower_name(class.name());
+                let kind = self.kinds.remove(&(name, KindSignatureTarget::Class));
+                let constraints = self.ctx.lower_constraints(class.constraints());
+                let variables = self.ctx.lower_type_variables(class.variables());
+                let dependencies = lower::lower_functional_dependencies(class);
+                let members = class
+                    .members()
+                    .map(|member| Signature {
+                        name: lower::lower_name(member.name()),
+                        type_: self.ctx.lower_type_opt(member.type_()),
+                    })
+                    .collect();
+                let class = ClassItem { name, kind, constraints, variables, dependencies, members };
+                self.alloc(Item::Class(class));
+            }
+            ast::Declaration::InstanceChain(chain) => {
+                for (chain_index, instance) in chain.instances().enumerate() {
+                    let name = instance.instance_name().map(|name| lower::lower_name(name.name()));
+                    let head = self.ctx.lower_instance_head(instance.head());
+                    let mut members: Vec<_> = instance
+                        .members()
+                        .filter_map(|member| match member {
+                            ast::InstanceMember::Value(value) => {
+                                Some(lower::lower_name(value.name()))
+                            }
+                            ast::InstanceMember::Annotation(_) => None,
+                        })
+                        .collect();
+                    members.dedup();
+                    self.alloc(Item::Instance(InstanceItem {
+                        name,
+                        head,
+                        members,
+                        chain_index,
+                        derived: false,
+                        newtype: false,
+                    }));
+                }
+            }
+            ast::Declaration::DeriveInstance(derive) => {
+                let name = derive.instance_name().map(|name| lower::lower_name(name.name()));
+                let head = self.ctx.lower_instance_head(derive.head());
+                self.alloc(Item::Instance(InstanceItem {
+                    name,
+                    head,
+                    members: vec![],
+                    chain_index: 0,
+                    derived: true,
+                    newtype: derive.is_newtype(),
+                }));
+            }
+            ast::Declaration::ForeignData(foreign) => {
+                let name = lower::lower_name(foreign.name());
+                let kind = self.ctx.lower_type_opt(foreign.kind());
+                self.alloc(Item::ForeignData(ForeignDataItem { name, kind }));
+            }
+            ast::Declaration::ForeignValue(foreign) => {
+                let name = lower::lower_name(foreign.name());
+                let type_ = self.ctx.lower_type_opt(foreign.type_());
+                self.alloc(Item::ForeignValue(ForeignValueItem { name, type_ }));
+            }
+            ast::Declaration::Fixity(fixity) => {
+                self.alloc(Item::Fixity(lower::lower_fixity(fixity)));
+            }
+        }
+    }
+
+    /// Signatures without a value are still visible to other modules e.g. for
+    /// error reporting, so they're kept as values without equations.
+    fn finish(mut self) -> ItemTree {
+        let mut signatures: Vec<_> = std::mem::take(&mut self.signatures).into_iter().collect();
+        signatures.sort_by_key(|(_, (type_, _))| *type_);
+        for (name, (signature, ast_id)) in signatures {
+            self.ast_id = ast_id;
+            self.alloc(Item::Value(ValueItem { name, signature: Some(signature), equations: 0 }));
+        }
+
+        let types = self.ctx.finish();
+        self.tree.types = types;
+        self.tree.items.shrink_to_fit();
+        self.tree
+    }
+
+    fn alloc(&mut self, item: Item) -> ItemId {
+        self.tree.ast_ids.push(self.ast_id);
+        self.tree.items.alloc(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{Item, ItemTree};
+    use crate::{
+        ast_id::{AstIdKind, ErasedAstId},
+        name::Name,
+    };
+
+    fn item_tree(source: &str) -> ItemTree {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        ItemTree::lower(&ast::Module::cast(node).unwrap())
+    }
+
+    #[test]
+    fn body_edits_preserve_item_tree() {
+        let before = item_tree("module Main where\n\nf :: Int -> Int\nf 0 = 1\nf x = x\n");
+        let after =
+            item_tree("module Main where\n\nf :: Int -> Int\nf 0 = 1\nf x = let y = x in y\n");
+        assert_eq!(before, after);
+
+        let (id, Item::Value(value)) = before.iter().next().unwrap() else { panic!() };
+        assert_eq!(value.name, Name::new("f"));
+        assert_eq!(before.ast_id(id), Some(ErasedAstId { kind: AstIdKind::Value, index: 0 }));
+        assert_eq!(value.equations, 2);
+        assert!(value.signature.is_some());
+
+        let changed = item_tree("module Main where\n\nf :: Int -> String\nf 0 = 1\nf x = x\n");
+        assert_ne!(before, changed);
+    }
+
+    #[test]
+    fn skipped_bodies_preserve_item_tree() {
+        let source = "module Main (f) where\n\nimport Prelude\n\nf :: Int -> Int\nf 0 = 1\nf x = x\n  where\n  y = 0\n\ninstance Show T where\n  show _ = \"T\"\n";
+        let (node, _) = parsing::parse_module_interface(source);
+        assert_eq!(ItemTree::lower(&ast::Module::cast(node).unwrap()), item_tree(source));
+    }
+}
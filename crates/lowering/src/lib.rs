@@ -0,0 +1,283 @@
+pub mod arena;
+pub mod ast_id;
+mod cbor;
+pub mod diagnostics;
+mod encoding;
+pub mod externs;
+pub mod hir;
+pub mod item_tree;
+mod literal;
+mod lower;
+pub mod name;
+pub mod pretty;
+pub mod scope;
+pub mod source_map;
+
+pub use lower::{
+    lower_export, lower_import, lower_import_item, lower_label, lower_module, lower_module_name,
+    lower_module_with_source_map, lower_name, lower_qualified_name,
+};
+
+#[cfg(test)]
+mod tests {
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use crate::{
+        diagnostics::LoweringDiagnostic,
+        hir::{Binder, Body, Declaration, Expr, GuardedExpr, Literal, Module, Operator, Type},
+        lower_module, lower_module_with_source_map,
+        name::{ModuleName, Name, QualifiedName},
+    };
+
+    fn bodies(module: &Module) -> Vec<&Body> {
+        let values = module.declarations.iter().filter_map(|(_, declaration)| match declaration {
+            Declaration::Value(value) => Some(&module.bodies[value.body]),
+            _ => None,
+        });
+        values.collect()
+    }
+
+    fn lower(source: &str) -> Module {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        lower_module(&ast::Module::cast(node).unwrap())
+    }
+
+    #[test]
+    fn lower_declarations() {
+        let module = lower(
+            "module Main where\n\nimport Data.Maybe (Maybe(..)) as M\n\ndata T a = A a | B\n\nf :: Int\nf = (x + 1) `div` 2\n",
+        );
+        assert_eq!(module.name, Some(ModuleName::new("Main")));
+        assert_eq!(module.imports[0].alias, Some(ModuleName::new("M")));
+
+        let declarations: Vec<_> = module.declarations.iter().map(|(_, d)| d).collect();
+        let Declaration::Data(data) = declarations[0] else { panic!() };
+        assert_eq!(data.name, Name::new("T"));
+        assert_eq!(data.constructors.len(), 2);
+        assert!(matches!(declarations[1], Declaration::Signature(_)));
+
+        assert!(matches!(declarations[2], Declaration::Value(_)));
+
+        let body = bodies(&module)[0];
+        let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+        let Expr::OperatorChain { head, tail } = &body.exprs[*expr] else { panic!() };
+        assert!(matches!(tail[..], [(Operator::Infix(_), _)]));
+        // Parentheses are not represented in the HIR.
+        assert!(matches!(body.exprs[*head], Expr::OperatorChain { .. }));
+    }
+
+    #[test]
+    fn desugar_do_notation() {
+        let source = "module Main where\n\nmain = do\n  x <- a\n  b\n  let y = x\n  c y\n";
+        let (node, _) = parsing::parse_module(source);
+        let (module, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+        assert!(source_map.diagnostics().is_empty());
+
+        let body = bodies(&module)[0];
+        let variable = |id| match &body.exprs[id] {
+            Expr::Variable(QualifiedName { name, .. }) => name.as_str(),
+            _ => panic!(),
+        };
+        let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+        let Expr::Application { function, arguments } = &body.exprs[*expr] else { panic!() };
+        assert_eq!(variable(*function), "bind");
+        let Expr::Lambda { body: lambda, .. } = &body.exprs[arguments[1]] else { panic!() };
+        let Expr::Application { function, arguments } = &body.exprs[*lambda] else { panic!() };
+        assert_eq!(variable(*function), "discard");
+        let Expr::Lambda { body: lambda, .. } = &body.exprs[arguments[1]] else { panic!() };
+        assert!(matches!(body.exprs[*lambda], Expr::LetIn { .. }));
+
+        // The desugared block maps back to the `do` expression.
+        let (value, _) = module.bodies.iter().next().unwrap();
+        let ptr = source_map.body(value).expr_syntax(*expr).unwrap();
+        assert_eq!(ptr.kind(), syntax::SyntaxKind::ExpressionDo);
+    }
+
+    #[test]
+    fn desugar_ado_notation() {
+        let module = lower("module Main where\n\nmain = ado\n  x <- a\n  y <- b\n  in x\n");
+        let body = bodies(&module)[0];
+        let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+        let Expr::Application { function, arguments } = &body.exprs[*expr] else { panic!() };
+        assert!(
+            matches!(&body.exprs[*function], Expr::Variable(name) if name.name.as_str() == "apply")
+        );
+        let Expr::Application { function, .. } = &body.exprs[arguments[0]] else { panic!() };
+        assert!(
+            matches!(&body.exprs[*function], Expr::Variable(name) if name.name.as_str() == "map")
+        );
+    }
+
+    #[test]
+    fn desugar_sections() {
+        let module = lower(
+            "module Main where\n\na = (_ + 1)\nb = _.foo.bar\nc = _ { x = 1 }\nd = (x + 1)\n",
+        );
+        let bodies = bodies(&module);
+        for body in &bodies[..3] {
+            let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+            let Expr::Lambda { binders, body: lambda } = &body.exprs[*expr] else { panic!() };
+            assert_eq!(binders.len(), 1);
+            assert!(!matches!(body.exprs[*lambda], Expr::Lambda { .. }));
+        }
+        let GuardedExpr::Unconditional(expr) = &bodies[3].guarded else { panic!() };
+        assert!(matches!(bodies[3].exprs[*expr], Expr::OperatorChain { .. }));
+    }
+
+    #[test]
+    fn desugar_where_clauses() {
+        let module = lower("module Main where\n\nf = x\n  where\n  x = 0\n\ng | x = 0\n  | otherwise = 1\n  where\n  x = true\n");
+        let bodies = bodies(&module);
+        let GuardedExpr::Unconditional(expr) = &bodies[0].guarded else { panic!() };
+        assert!(matches!(bodies[0].exprs[*expr], Expr::LetIn { .. }));
+        // Bindings scope over every guard, so they can't be pushed into the branches.
+        let GuardedExpr::LetIn { bindings, branches } = &bodies[1].guarded else { panic!() };
+        assert_eq!((bindings.len(), branches.len()), (1, 2));
+    }
+
+    #[test]
+    fn combine_equations() {
+        let source = "module Main where\n\nf 0 y = y\nf x y = x\ng x = x\ng = 0\n";
+        let (node, _) = parsing::parse_module(source);
+        let (module, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+        assert_eq!(module.declarations.len(), 2);
+
+        let body = bodies(&module)[0];
+        let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+        let Expr::Lambda { binders, body: lambda } = &body.exprs[*expr] else { panic!() };
+        assert_eq!(binders.len(), 2);
+        let Expr::Case { scrutinees, branches } = &body.exprs[*lambda] else { panic!() };
+        assert_eq!((scrutinees.len(), branches.len()), (2, 2));
+
+        // The mismatched equation of `g` is left out.
+        let body = bodies(&module)[1];
+        let GuardedExpr::Unconditional(expr) = &body.guarded else { panic!() };
+        let Expr::Lambda { body: lambda, .. } = &body.exprs[*expr] else { panic!() };
+        let Expr::Case { branches, .. } = &body.exprs[*lambda] else { panic!() };
+        assert_eq!(branches.len(), 1);
+
+        let [LoweringDiagnostic::ArityMismatch { expected: 1, actual: 0, .. }] =
+            source_map.diagnostics()
+        else {
+            panic!("{:?}", source_map.diagnostics())
+        };
+    }
+
+    #[test]
+    fn desugar_records() {
+        let module =
+            lower("module Main where\n\na = { x, \"y z\": 0 }\nb = (f r) { a { b = 1 }, c = 2 }\n");
+        let bodies = bodies(&module);
+        let GuardedExpr::Unconditional(expr) = &bodies[0].guarded else { panic!() };
+        let Expr::Literal(Literal::Record(fields)) = &bodies[0].exprs[*expr] else { panic!() };
+        assert_eq!(fields[0].0, Name::new("x"));
+        let pun = &bodies[0].exprs[fields[0].1];
+        assert!(matches!(pun, Expr::Variable(name) if name.name == fields[0].0));
+        assert_eq!(fields[1].0, Name::new("y z"));
+
+        // The record is bound once, and nested updates refer to it by name.
+        let exprs = &bodies[1].exprs;
+        let GuardedExpr::Unconditional(expr) = &bodies[1].guarded else { panic!() };
+        let Expr::LetIn { body, .. } = &exprs[*expr] else { panic!() };
+        let Expr::RecordUpdate { updates, .. } = &exprs[*body] else { panic!() };
+        let Expr::RecordUpdate { record, .. } = &exprs[updates[0].1] else { panic!() };
+        let Expr::RecordAccess { labels, .. } = &exprs[*record] else { panic!() };
+        assert_eq!(labels, &[Name::new("a")]);
+    }
+
+    #[test]
+    fn source_map_covers_every_node() {
+        let source = "module Main where\n\nf :: forall a. Array a -> Int\nf [x] | x > 0 = do\n  y <- g { x }\n  pure (_ + y)\nf _ = 0\n";
+        let (node, _) = parsing::parse_module(source);
+        let (module, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+        let (id, body) = module.bodies.iter().next().unwrap();
+        let body_source_map = source_map.body(id);
+        for (id, expr) in body.exprs.iter() {
+            assert!(matches!(expr, Expr::Missing) || body_source_map.expr_syntax(id).is_some());
+        }
+        for (id, binder) in body.binders.iter() {
+            let syntax = body_source_map.binder_syntax(id);
+            assert!(matches!(binder, Binder::Missing) || syntax.is_some());
+        }
+        for (id, type_) in module.types.iter() {
+            let ptr = source_map.type_ptr(id);
+            assert!(matches!(type_, Type::Missing) || ptr.is_some());
+        }
+
+        let (id, _) = body.binders.iter().find(|(_, b)| matches!(b, Binder::Literal(_))).unwrap();
+        let range = body_source_map.binder_ptr(id).unwrap().syntax_node_ptr().text_range();
+        assert_eq!(&source[range], "[x]");
+    }
+
+    #[test]
+    fn canonicalize_literals() {
+        let source =
+            "module Main where\n\na = [0xFF, 2147483648, 'x', \"\\q\", -2147483648, -2147483649]\n";
+        let (node, _) = parsing::parse_module(source);
+        let (module, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+        let literals: Vec<_> = bodies(&module)[0]
+            .exprs
+            .iter()
+            .filter_map(|(_, expr)| match expr {
+                Expr::Literal(Literal::Array(_)) => None,
+                Expr::Literal(literal) => Some(literal.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            literals,
+            [
+                Literal::Int(255),
+                Literal::Int(0),
+                Literal::Char('x'),
+                Literal::String("\\q".into()),
+                Literal::Int(i32::MIN),
+                Literal::Int(0)
+            ]
+        );
+        assert!(matches!(
+            source_map.diagnostics(),
+            [
+                LoweringDiagnostic::IntegerOutOfRange { .. },
+                LoweringDiagnostic::InvalidEscape { .. },
+                LoweringDiagnostic::IntegerOutOfRange { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn report_duplicates() {
+        let source = "module Main where\n\nf 0 = 0\nf x = x\ndata T = A | B\nf = 1\ndata U = A\n\ng :: forall a a. { x :: a, \"x\" :: a }\ng = { y: 0, \"y\": 1 }\n";
+        let (node, _) = parsing::parse_module(source);
+        let (_, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+
+        let diagnostics: Vec<_> = source_map
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                let (previous, _) = diagnostic.related().unwrap();
+                let ptr = diagnostic.ptr().text_range();
+                (diagnostic.message(), &source[ptr], previous.text_range().start() < ptr.start())
+            })
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("f is defined more than once".to_string(), "f", true),
+                ("A is defined more than once".to_string(), "A", true),
+                ("type variable a is bound more than once".to_string(), "a", true),
+                ("label y appears more than once".to_string(), "\"y\"", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_duplicate_labels_in_rows() {
+        let source = "module Main where\n\ntype R = ( a :: Int, a :: String )\n\nf :: { a :: Int, a :: String } -> Int\nf _ = 0\n";
+        let (node, _) = parsing::parse_module(source);
+        let (_, source_map) = lower_module_with_source_map(&ast::Module::cast(node).unwrap());
+        assert_eq!(source_map.diagnostics(), []);
+    }
+}
@@ -0,0 +1,109 @@
+//! Parsing and decoding of literal text.
+
+use syntax::{SyntaxKind, SyntaxToken};
+
+/// Parses an integer, e.g. `1_000` or `0xFF`, returning `None` on overflow.
+pub(crate) fn parse_integer(text: &str, negative: bool) -> Option<i64> {
+    let (digits, radix) = match text.strip_prefix("0x") {
+        Some(digits) => (digits, 16),
+        None => (text, 10),
+    };
+    let mut value: i64 = 0;
+    for digit in digits.chars().filter(|&c| c != '_') {
+        let digit = digit.to_digit(radix)? as i64;
+        value = value.checked_mul(radix as i64)?.checked_add(digit)?;
+    }
+    Some(if negative { -value } else { value })
+}
+
+/// Parses a number, e.g. `1_000.5` or `1.0e-10`.
+pub(crate) fn parse_number(text: &str, negative: bool) -> f64 {
+    let text: String = text.chars().filter(|&c| c != '_').collect();
+    let value = text.parse::<f64>().unwrap_or(f64::NAN);
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Returns the contents of a string token without its delimiters, and
+/// whether escapes should be decoded.
+pub(crate) fn string_contents(token: &SyntaxToken) -> (&str, bool) {
+    let text = token.text();
+    let (quote, escaped) = match token.kind() {
+        SyntaxKind::LiteralRawString => ("\"\"\"", false),
+        SyntaxKind::LiteralString => ("\"", true),
+        SyntaxKind::LiteralChar => ("'", true),
+        _ => return (text, false),
+    };
+    let contents = text.strip_prefix(quote).and_then(|text| text.strip_suffix(quote));
+    (contents.unwrap_or(text), escaped)
+}
+
+/// Decodes the escapes in the contents of a string or character literal,
+/// returning `None` for invalid escapes.
+///
+/// Besides the usual `\n`, `\r`, `\t`, `\"`, `\'`, and `\\`, PureScript
+/// supports hexadecimal escapes with up to six digits, e.g. `\x1F600`, and
+/// string gaps, where a backslash-delimited run of whitespace is skipped.
+/// Lone surrogates can't be represented, and are rejected.
+pub(crate) fn unescape(text: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => decoded.push('\n'),
+            'r' => decoded.push('\r'),
+            't' => decoded.push('\t'),
+            c @ ('"' | '\'' | '\\') => decoded.push(c),
+            'x' => {
+                let mut value = 0;
+                let mut digits = 0;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    if digits == 6 {
+                        break;
+                    }
+                    value = value * 16 + digit;
+                    digits += 1;
+                    chars.next();
+                }
+                if digits == 0 {
+                    return None;
+                }
+                decoded.push(char::from_u32(value)?);
+            }
+            c if c.is_whitespace() => loop {
+                match chars.next()? {
+                    '\\' => break,
+                    c if c.is_whitespace() => continue,
+                    _ => return None,
+                }
+            },
+            _ => return None,
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_integer, parse_number, unescape};
+
+    #[test]
+    fn literals() {
+        assert_eq!(parse_integer("1_000", false), Some(1000));
+        assert_eq!(parse_integer("0xFF", true), Some(-255));
+        assert_eq!(parse_integer("99999999999999999999", false), None);
+        assert_eq!(parse_number("1_0.5e-1", false), 1.05);
+
+        assert_eq!(unescape(r#"a\n\"\x1F600\t"#).as_deref(), Some("a\n\"\u{1F600}\t"));
+        assert_eq!(unescape("a\\\n   \\b").as_deref(), Some("ab"));
+        assert_eq!(unescape(r"\q"), None);
+        assert_eq!(unescape(r"\xD800"), None);
+    }
+}
@@ -0,0 +1,1439 @@
+//! Lowering from the typed AST into the HIR.
+
+use std::{collections::hash_map::Entry, hash::Hash, iter::Peekable};
+
+use rowan::ast::{AstNode, SyntaxNodePtr};
+use rustc_hash::FxHashMap;
+use syntax::{ast, PureScript, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use crate::{
+    arena::Arena,
+    diagnostics::LoweringDiagnostic,
+    hir::*,
+    literal,
+    name::{ModuleName, Name, QualifiedName},
+    source_map::{BodySourceMap, SourceMap},
+};
+
+/// Lowers a module into the HIR.
+pub fn lower_module(module: &ast::Module) -> Module {
+    lower_module_with_source_map(module).0
+}
+
+/// Lowers a module into the HIR, alongside a mapping back to its syntax.
+pub fn lower_module_with_source_map(module: &ast::Module) -> (Module, SourceMap) {
+    let mut ctx = Ctx::default();
+    let mut lowered = Module::default();
+
+    if let Some(header) = module.header() {
+        lowered.name = header.name().map(|name| lower_module_name(&name));
+        lowered.exports = lower_exports(header.exports());
+        lowered.imports = lower_imports(header.imports());
+    }
+    ctx.source_map.diagnostics.extend(duplicate_declarations(module));
+
+    let mut declarations = module.declarations().peekable();
+    while let Some(declaration) = declarations.next() {
+        let declaration = match Equation::from_declaration(&declaration) {
+            Some(equation) => {
+                let equations = equation.group(&mut declarations, Equation::from_declaration);
+                Declaration::Value(ctx.lower_value(equations))
+            }
+            None => ctx.lower_declaration(&declaration),
+        };
+        lowered.declarations.alloc(declaration);
+    }
+
+    lowered.declarations.shrink_to_fit();
+    let mut source_map = std::mem::take(&mut ctx.source_map);
+    source_map.shrink_to_fit();
+    lowered.bodies = std::mem::take(&mut ctx.bodies);
+    lowered.bodies.shrink_to_fit();
+    lowered.types = ctx.finish();
+    (lowered, source_map)
+}
+
+/// The namespaces of top-level declarations, where names must be unique.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Namespace {
+    Value,
+    Constructor,
+    Operator,
+    Type,
+    TypeOperator,
+    Class,
+}
+
+/// Reports declarations that share a name with an earlier declaration in the
+/// same namespace. Adjacent equations of a value are a single declaration.
+fn duplicate_declarations(module: &ast::Module) -> Vec<LoweringDiagnostic> {
+    let mut names = vec![];
+    let mut previous_value = None;
+    for declaration in module.declarations() {
+        let mut push = |namespace, name: Option<ast::Name>| {
+            if let Some(name) = name {
+                let key = (namespace, lower_name(Some(name.clone())));
+                names.push((key, SyntaxNodePtr::new(name.syntax())));
+            }
+        };
+        match &declaration {
+            ast::Declaration::Value(value) => {
+                let name = lower_name(value.name());
+                if previous_value != Some(name) {
+                    push(Namespace::Value, value.name());
+                }
+                previous_value = Some(name);
+                continue;
+            }
+            ast::Declaration::ForeignValue(foreign) => push(Namespace::Value, foreign.name()),
+            ast::Declaration::Data(data) => {
+                push(Namespace::Type, data.name());
+                for constructor in data.constructors() {
+                    push(Namespace::Constructor, constructor.name());
+                }
+            }
+            ast::Declaration::Newtype(newtype) => {
+                push(Namespace::Type, newtype.name());
+                if let Some(constructor) = newtype.constructor() {
+                    push(Namespace::Constructor, constructor.name());
+                }
+            }
+            ast::Declaration::Type(synonym) => push(Namespace::Type, synonym.name()),
+            ast::Declaration::ForeignData(foreign) => push(Namespace::Type, foreign.name()),
+            ast::Declaration::Class(class) => {
+                push(Namespace::Class, class.name());
+                for member in class.members() {
+                    push(Namespace::Value, member.name());
+                }
+            }
+            ast::Declaration::Fixity(fixity) => {
+                let namespace =
+                    if fixity.is_type() { Namespace::TypeOperator } else { Namespace::Operator };
+                push(namespace, fixity.operator());
+            }
+            _ => {}
+        }
+        previous_value = None;
+    }
+    duplicates(names)
+        .map(|((_, name), ptr, previous)| LoweringDiagnostic::DuplicateDeclaration {
+            name,
+            ptr,
+            previous,
+        })
+        .collect()
+}
+
+/// Finds keys that occur more than once, alongside the pointers to each later
+/// occurrence and to the first occurrence.
+fn duplicates<K: Hash + Eq + Clone>(
+    items: impl IntoIterator<Item = (K, SyntaxNodePtr<PureScript>)>,
+) -> impl Iterator<Item = (K, SyntaxNodePtr<PureScript>, SyntaxNodePtr<PureScript>)> {
+    let mut seen: FxHashMap<K, SyntaxNodePtr<PureScript>> = FxHashMap::default();
+    items.into_iter().filter_map(move |(key, ptr)| match seen.entry(key) {
+        Entry::Occupied(entry) => Some((entry.key().clone(), ptr, entry.get().clone())),
+        Entry::Vacant(entry) => {
+            entry.insert(ptr);
+            None
+        }
+    })
+}
+
+/// A single equation of a value, before equations are combined.
+struct Equation {
+    name: Name,
+    binders: Vec<ast::Binder>,
+    guarded: Option<ast::GuardedExpression>,
+    syntax: SyntaxNode,
+}
+
+impl Equation {
+    fn from_declaration(declaration: &ast::Declaration) -> Option<Equation> {
+        let ast::Declaration::Value(value) = declaration else { return None };
+        let binders = value.binders().collect();
+        Some(Equation::new(value.name(), binders, value.guarded_expression(), value.syntax()))
+    }
+
+    fn from_let_binding(binding: &ast::LetBinding) -> Option<Equation> {
+        let ast::LetBinding::Name(value) = binding else { return None };
+        let binders = value.binders().collect();
+        Some(Equation::new(value.name(), binders, value.guarded_expression(), value.syntax()))
+    }
+
+    fn from_instance_member(member: &ast::InstanceMember) -> Option<Equation> {
+        let ast::InstanceMember::Value(value) = member else { return None };
+        let binders = value.binders().collect();
+        Some(Equation::new(value.name(), binders, value.guarded_expression(), value.syntax()))
+    }
+
+    fn new(
+        name: Option<ast::Name>,
+        binders: Vec<ast::Binder>,
+        guarded: Option<ast::GuardedExpression>,
+        syntax: &SyntaxNode,
+    ) -> Equation {
+        Equation { name: lower_name(name), binders, guarded, syntax: syntax.clone() }
+    }
+
+    /// Collects `self` and the equations that follow it with the same name.
+    fn group<T>(
+        self,
+        rest: &mut Peekable<impl Iterator<Item = T>>,
+        equation: impl Fn(&T) -> Option<Equation>,
+    ) -> Vec<Equation> {
+        let mut equations = vec![self];
+        while let Some(next) = rest.peek().and_then(&equation) {
+            if next.name != equations[0].name {
+                break;
+            }
+            rest.next();
+            equations.push(next);
+        }
+        equations
+    }
+}
+
+/// Lowers declarations into arenas. Types are shared between the items of a
+/// single module, while expressions and binders belong to the body of the
+/// value currently being lowered.
+#[derive(Default)]
+pub(crate) struct Ctx {
+    bodies: Arena<Body>,
+    exprs: Arena<Expr>,
+    binders: Arena<Binder>,
+    types: Arena<Type>,
+    source_map: SourceMap,
+    body_source_map: BodySourceMap,
+    /// Counter for names introduced through desugaring, which use a `$`
+    /// prefix that can't appear in source code.
+    fresh_names: u32,
+}
+
+impl Ctx {
+    pub(crate) fn finish(mut self) -> Arena<Type> {
+        self.types.shrink_to_fit();
+        self.types
+    }
+
+    /// Lowers the equations of a value into a new body.
+    fn lower_value(&mut self, equations: Vec<Equation>) -> Value {
+        let name = equations[0].name;
+        self.fresh_names = 0;
+        let guarded = self.lower_equations(equations);
+
+        let mut exprs = std::mem::take(&mut self.exprs);
+        let mut binders = std::mem::take(&mut self.binders);
+        let mut source_map = std::mem::take(&mut self.body_source_map);
+        exprs.shrink_to_fit();
+        binders.shrink_to_fit();
+        source_map.shrink_to_fit();
+
+        let body = self.bodies.alloc(Body { exprs, binders, guarded });
+        self.source_map.insert_body(body, source_map);
+        Value { name, body }
+    }
+
+    fn alloc_expr(&mut self, expr: Expr) -> ExprId {
+        self.exprs.alloc(expr)
+    }
+
+    /// Allocates an expression synthesized while desugaring `node`.
+    fn alloc_desugared_expr(&mut self, expr: Expr, node: &SyntaxNode) -> ExprId {
+        let id = self.exprs.alloc(expr);
+        self.body_source_map.insert_desugared_expr(id, node);
+        id
+    }
+
+    /// Lowers a literal token, where `negative` folds a preceding minus sign
+    /// into numeric literals. Diagnostics are reported at `syntax`.
+    fn lower_literal<I>(
+        &mut self,
+        token: &SyntaxToken,
+        negative: bool,
+        syntax: &SyntaxNode,
+    ) -> Option<Literal<I>> {
+        let text = token.text();
+        let literal = match token.kind() {
+            SyntaxKind::LiteralInteger => {
+                let value = literal::parse_integer(text, negative);
+                Literal::Int(
+                    self.check_integer(value.and_then(|value| value.try_into().ok()), syntax),
+                )
+            }
+            SyntaxKind::LiteralNumber => {
+                Literal::Number(Number(literal::parse_number(text, negative)))
+            }
+            SyntaxKind::LiteralString | SyntaxKind::LiteralRawString => {
+                Literal::String(self.lower_string(token, syntax))
+            }
+            SyntaxKind::LiteralChar => {
+                let string = self.lower_string(token, syntax);
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Literal::Char(c),
+                    _ => {
+                        let ptr = SyntaxNodePtr::new(syntax);
+                        self.source_map.diagnostics.push(LoweringDiagnostic::InvalidEscape { ptr });
+                        Literal::Char(char::REPLACEMENT_CHARACTER)
+                    }
+                }
+            }
+            SyntaxKind::LiteralTrue => Literal::Boolean(true),
+            SyntaxKind::LiteralFalse => Literal::Boolean(false),
+            _ => return None,
+        };
+        Some(literal)
+    }
+
+    /// Reports integers that have overflowed, which become `0`.
+    fn check_integer<T: Default>(&mut self, value: Option<T>, syntax: &SyntaxNode) -> T {
+        value.unwrap_or_else(|| {
+            let ptr = SyntaxNodePtr::new(syntax);
+            self.source_map.diagnostics.push(LoweringDiagnostic::IntegerOutOfRange { ptr });
+            T::default()
+        })
+    }
+
+    /// Decodes a string token, keeping the text as written if it has invalid
+    /// escapes.
+    fn lower_string(&mut self, token: &SyntaxToken, syntax: &SyntaxNode) -> String {
+        let (text, escaped) = literal::string_contents(token);
+        if !escaped {
+            return text.to_string();
+        }
+        literal::unescape(text).unwrap_or_else(|| {
+            let ptr = SyntaxNodePtr::new(syntax);
+            self.source_map.diagnostics.push(LoweringDiagnostic::InvalidEscape { ptr });
+            text.to_string()
+        })
+    }
+
+    fn alloc_binder(&mut self, binder: Binder) -> BinderId {
+        self.binders.alloc(binder)
+    }
+
+    /// Allocates a binder synthesized while desugaring `node`.
+    fn alloc_desugared_binder(&mut self, binder: Binder, node: &SyntaxNode) -> BinderId {
+        let id = self.binders.alloc(binder);
+        self.body_source_map.insert_desugared_binder(id, node);
+        id
+    }
+
+    fn alloc_type(&mut self, type_: Type) -> TypeId {
+        self.types.alloc(type_)
+    }
+
+    fn check_labels(&mut self, labels: impl Iterator<Item = Option<ast::Label>>) {
+        let labels = labels
+            .flatten()
+            .map(|label| (lower_label(Some(label.clone())), SyntaxNodePtr::new(label.syntax())));
+        let diagnostics = duplicates(labels).map(|(name, ptr, previous)| {
+            LoweringDiagnostic::DuplicateLabel { name, ptr, previous }
+        });
+        self.source_map.diagnostics.extend(diagnostics);
+    }
+
+    fn lower_declaration(&mut self, declaration: &ast::Declaration) -> Declaration {
+        match declaration {
+            ast::Declaration::Value(_) => {
+                let equation = Equation::from_declaration(declaration).unwrap();
+                Declaration::Value(self.lower_value(vec![equation]))
+            }
+            ast::Declaration::Annotation(annotation) => {
+                Declaration::Signature(self.lower_annotation(annotation))
+            }
+            ast::Declaration::KindSignature(signature) => {
+                let target = lower_kind_signature_target(signature);
+                let name = lower_name(signature.name());
+                let kind = self.lower_type_opt(signature.kind());
+                Declaration::KindSignature(KindSignature { target, name, kind })
+            }
+            ast::Declaration::Data(data) => {
+                let name = lower_name(data.name());
+                let variables = self.lower_type_variables(data.variables());
+                let constructors =
+                    data.constructors().map(|constructor| self.lower_constructor(&constructor));
+                let constructors = constructors.collect();
+                Declaration::Data(Data { name, variables, constructors })
+            }
+            ast::Declaration::Newtype(newtype) => {
+                let name = lower_name(newtype.name());
+                let variables = self.lower_type_variables(newtype.variables());
+                let constructor = match newtype.constructor() {
+                    Some(constructor) => self.lower_constructor(&constructor),
+                    None => Constructor { name: Name::missing(), fields: vec![] },
+                };
+                Declaration::Newtype(Newtype { name, variables, constructor })
+            }
+            ast::Declaration::Type(synonym) => {
+                let name = lower_name(synonym.name());
+                let variables = self.lower_type_variables(synonym.variables());
+                let type_ = self.lower_type_opt(synonym.type_());
+                Declaration::Synonym(Synonym { name, variables, type_ })
+            }
+            ast::Declaration::Class(class) => {
+                let constraints = self.lower_constraints(class.constraints());
+                let name = lower_name(class.name());
+                let variables = self.lower_type_variables(class.variables());
+                let dependencies = lower_functional_dependencies(class);
+                let members = class
+                    .members()
+                    .map(|member| Signature {
+                        name: lower_name(member.name()),
+                        type_: self.lower_type_opt(member.type_()),
+                    })
+                    .collect();
+                Declaration::Class(Class { constraints, name, variables, dependencies, members })
+            }
+            ast::Declaration::InstanceChain(chain) => {
+                let instances = chain.instances().map(|instance| {
+                    let name = instance.instance_name().map(|name| lower_name(name.name()));
+                    let head = self.lower_instance_head(instance.head());
+                    let mut ast_members = instance.members().peekable();
+                    let mut members = vec![];
+                    while let Some(member) = ast_members.next() {
+                        if let Some(equation) = Equation::from_instance_member(&member) {
+                            let equations =
+                                equation.group(&mut ast_members, Equation::from_instance_member);
+                            members.push(InstanceMember::Value(self.lower_value(equations)));
+                        } else if let ast::InstanceMember::Annotation(annotation) = member {
+                            let signature = self.lower_annotation(&annotation);
+                            members.push(InstanceMember::Signature(signature));
+                        }
+                    }
+                    Instance { name, head, members }
+                });
+                Declaration::InstanceChain(instances.collect())
+            }
+            ast::Declaration::DeriveInstance(derive) => {
+                let newtype = derive.is_newtype();
+                let name = derive.instance_name().map(|name| lower_name(name.name()));
+                let head = self.lower_instance_head(derive.head());
+                Declaration::Derive(Derive { newtype, name, head })
+            }
+            ast::Declaration::ForeignData(foreign) => {
+                let name = lower_name(foreign.name());
+                let kind = self.lower_type_opt(foreign.kind());
+                Declaration::ForeignData(ForeignData { name, kind })
+            }
+            ast::Declaration::ForeignValue(foreign) => {
+                let name = lower_name(foreign.name());
+                let type_ = self.lower_type_opt(foreign.type_());
+                Declaration::ForeignValue(ForeignValue { name, type_ })
+            }
+            ast::Declaration::Fixity(fixity) => Declaration::Fixity(lower_fixity(fixity)),
+        }
+    }
+
+    /// Combines the equations of a value into a single body, e.g.
+    ///
+    /// ```purescript
+    /// f 0 = 1
+    /// f n = n
+    /// ```
+    ///
+    /// becomes `f = \$0 -> case $0 of 0 -> 1; n -> n`. An equation with a
+    /// different number of binders than the first is reported and left out,
+    /// rather than checked against binders it doesn't have.
+    fn lower_equations(&mut self, equations: Vec<Equation>) -> GuardedExpr {
+        if let [equation] = &equations[..] {
+            if equation.binders.is_empty() {
+                return self.lower_guarded_opt(equation.guarded.clone());
+            }
+        }
+
+        let arity = equations[0].binders.len();
+        let branches = equations
+            .iter()
+            .filter_map(|equation| {
+                let actual = equation.binders.len();
+                if actual != arity {
+                    let ptr = SyntaxNodePtr::new(&equation.syntax);
+                    let diagnostic =
+                        LoweringDiagnostic::ArityMismatch { ptr, expected: arity, actual };
+                    self.source_map.diagnostics.push(diagnostic);
+                    return None;
+                }
+                let binders =
+                    equation.binders.iter().map(|binder| self.lower_binder(binder)).collect();
+                let guarded = self.lower_guarded_opt(equation.guarded.clone());
+                Some(CaseBranch { binders, guarded })
+            })
+            .collect();
+
+        let syntax = &equations[0].syntax;
+        let names: Vec<_> = (0..arity).map(|_| self.fresh_name()).collect();
+        let scrutinees = names
+            .iter()
+            .map(|&name| {
+                let variable = Expr::Variable(QualifiedName::unqualified(name));
+                self.alloc_desugared_expr(variable, syntax)
+            })
+            .collect();
+        let case = self.alloc_desugared_expr(Expr::Case { scrutinees, branches }, syntax);
+        if arity == 0 {
+            return GuardedExpr::Unconditional(case);
+        }
+
+        let binders = names
+            .into_iter()
+            .map(|name| self.alloc_desugared_binder(Binder::Variable(name), syntax))
+            .collect();
+        let lambda = self.alloc_desugared_expr(Expr::Lambda { binders, body: case }, syntax);
+        GuardedExpr::Unconditional(lambda)
+    }
+
+    fn lower_annotation(&mut self, annotation: &ast::AnnotationDeclaration) -> Signature {
+        let name = lower_name(annotation.name());
+        let type_ = self.lower_type_opt(annotation.type_());
+        Signature { name, type_ }
+    }
+
+    pub(crate) fn lower_constructor(&mut self, constructor: &ast::DataConstructor) -> Constructor {
+        let name = lower_name(constructor.name());
+        let fields = constructor.fields().map(|field| self.lower_type(&field)).collect();
+        Constructor { name, fields }
+    }
+
+    pub(crate) fn lower_type_variables(
+        &mut self,
+        variables: impl Iterator<Item = ast::TypeVariableBinding>,
+    ) -> Vec<TypeVariable> {
+        let variables: Vec<_> = variables.collect();
+        let names = variables.iter().filter_map(|variable| {
+            let name = variable.name()?;
+            Some((lower_name(Some(name.clone())), SyntaxNodePtr::new(name.syntax())))
+        });
+        let diagnostics = duplicates(names).map(|(name, ptr, previous)| {
+            LoweringDiagnostic::DuplicateTypeVariable { name, ptr, previous }
+        });
+        self.source_map.diagnostics.extend(diagnostics);
+        variables
+            .iter()
+            .map(|variable| TypeVariable {
+                name: lower_name(variable.name()),
+                kind: variable.kind().map(|kind| self.lower_type(&kind)),
+                visible: variable.is_visible(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn lower_constraints(
+        &mut self,
+        constraints: Option<ast::Constraints>,
+    ) -> Vec<TypeId> {
+        constraints
+            .into_iter()
+            .flat_map(|constraints| constraints.types())
+            .map(|constraint| self.lower_type(&constraint))
+            .collect()
+    }
+
+    pub(crate) fn lower_instance_head(&mut self, head: Option<ast::InstanceHead>) -> InstanceHead {
+        let Some(head) = head else {
+            let class = QualifiedName::unqualified(Name::missing());
+            return InstanceHead { constraints: vec![], class, arguments: vec![] };
+        };
+        let constraints = self.lower_constraints(head.constraints());
+        let class = head
+            .class_name()
+            .map(|name| lower_qualified_name(&name))
+            .unwrap_or_else(|| QualifiedName::unqualified(Name::missing()));
+        let arguments = head.arguments().map(|argument| self.lower_type(&argument)).collect();
+        InstanceHead { constraints, class, arguments }
+    }
+
+    /// Lowers a guarded expression, desugaring its `where` clause into `let`
+    /// bindings.
+    fn lower_guarded_opt(&mut self, guarded: Option<ast::GuardedExpression>) -> GuardedExpr {
+        match guarded {
+            Some(ast::GuardedExpression::Unconditional(unconditional)) => {
+                let expr = self.lower_expr_opt(unconditional.expression());
+                let syntax = unconditional.syntax();
+                GuardedExpr::Unconditional(self.lower_where(
+                    expr,
+                    unconditional.where_clause(),
+                    syntax,
+                ))
+            }
+            Some(ast::GuardedExpression::Guarded(guarded)) => {
+                let branches = guarded
+                    .branches()
+                    .map(|branch| {
+                        let guards =
+                            branch.guards().map(|guard| self.lower_guard(&guard)).collect();
+                        let expr = self.lower_expr_opt(branch.expression());
+                        GuardedBranch { guards, expr }
+                    })
+                    .collect();
+                match guarded.where_clause() {
+                    Some(where_clause) => {
+                        let bindings = self.lower_let_bindings(where_clause.bindings());
+                        GuardedExpr::LetIn { bindings, branches }
+                    }
+                    None => GuardedExpr::Guarded(branches),
+                }
+            }
+            None => GuardedExpr::Unconditional(self.alloc_expr(Expr::Missing)),
+        }
+    }
+
+    fn lower_guard(&mut self, guard: &ast::PatternGuard) -> Guard {
+        let binder = guard.binder().map(|binder| self.lower_binder(&binder));
+        let expr = self.lower_expr_opt(guard.expression());
+        match binder {
+            Some(binder) => Guard::Pattern(binder, expr),
+            None => Guard::Boolean(expr),
+        }
+    }
+
+    /// Wraps `expr` in the bindings of a `where` clause.
+    fn lower_where(
+        &mut self,
+        expr: ExprId,
+        where_clause: Option<ast::WhereClause>,
+        syntax: &SyntaxNode,
+    ) -> ExprId {
+        let Some(where_clause) = where_clause else { return expr };
+        let bindings = self.lower_let_bindings(where_clause.bindings());
+        self.alloc_desugared_expr(Expr::LetIn { bindings, body: expr }, syntax)
+    }
+
+    fn lower_let_bindings(
+        &mut self,
+        bindings: impl Iterator<Item = ast::LetBinding>,
+    ) -> Vec<LetBinding> {
+        let mut bindings = bindings.peekable();
+        let mut lowered = vec![];
+        while let Some(binding) = bindings.next() {
+            if let Some(equation) = Equation::from_let_binding(&binding) {
+                let equations = equation.group(&mut bindings, Equation::from_let_binding);
+                let binder = Binder::Variable(equations[0].name);
+                let binder = self.alloc_binder(binder);
+                self.body_source_map.insert_binder(binder, &equations[0].syntax);
+                let guarded = self.lower_equations(equations);
+                lowered.push(LetBinding::Value { binder, guarded });
+                continue;
+            }
+            let binding = match binding {
+                ast::LetBinding::Name(_) => continue,
+                ast::LetBinding::Signature(signature) => {
+                    let name = lower_name(signature.name());
+                    let type_ = self.lower_type_opt(signature.type_());
+                    LetBinding::Signature(Signature { name, type_ })
+                }
+                ast::LetBinding::Pattern(pattern) => {
+                    let binder = self.lower_binder_opt(pattern.binder());
+                    let expr = self.lower_expr_opt(pattern.expression());
+                    let expr = self.lower_where(expr, pattern.where_clause(), pattern.syntax());
+                    let guarded = GuardedExpr::Unconditional(expr);
+                    LetBinding::Pattern { binder, guarded }
+                }
+            };
+            lowered.push(binding);
+        }
+        lowered
+    }
+
+    fn lower_expr_opt(&mut self, expr: Option<ast::Expression>) -> ExprId {
+        match expr {
+            Some(expr) => self.lower_expr(&expr),
+            None => self.alloc_expr(Expr::Missing),
+        }
+    }
+
+    /// Lowers an application, where each type argument applies the
+    /// expression to its left, e.g. `f x @Int y` becomes `((f x) @Int) y`.
+    fn lower_application(
+        &mut self,
+        application: &ast::ExpressionApplication,
+        syntax: &SyntaxNode,
+    ) -> Expr {
+        let mut function = self.lower_expr_opt(application.head());
+        let mut arguments = vec![];
+        let mut applied = None;
+        for argument in application.arguments() {
+            if let Some(id) = applied.take() {
+                function = self.alloc_desugared_expr(id, syntax);
+            }
+            let ast::Expression::TypeArgument(argument) = argument else {
+                arguments.push(self.lower_expr(&argument));
+                continue;
+            };
+            if !arguments.is_empty() {
+                let arguments = std::mem::take(&mut arguments);
+                function =
+                    self.alloc_desugared_expr(Expr::Application { function, arguments }, syntax);
+            }
+            let type_ = self.lower_type_opt(argument.type_());
+            applied = Some(Expr::TypeApplication { expr: function, type_ });
+        }
+        match applied {
+            Some(applied) => applied,
+            None => Expr::Application { function, arguments },
+        }
+    }
+
+    fn lower_expr(&mut self, expr: &ast::Expression) -> ExprId {
+        let syntax = expr.syntax();
+        let expr = match expr {
+            ast::Expression::OperatorChain(_) | ast::Expression::InfixChain(_) => {
+                self.lower_chain(expr, None)
+            }
+            ast::Expression::Typed(typed) => {
+                let expr = self.lower_expr_opt(typed.expression());
+                let type_ = self.lower_type_opt(typed.type_());
+                Expr::Typed { expr, type_ }
+            }
+            // A negated literal is checked against the range of `Int` with
+            // its sign, since `-2147483648` fits while `2147483648` doesn't.
+            ast::Expression::Negate(negate) => {
+                let literal = match negate.expression() {
+                    Some(ast::Expression::Literal(literal)) => literal.token().filter(|token| {
+                        matches!(
+                            token.kind(),
+                            SyntaxKind::LiteralInteger | SyntaxKind::LiteralNumber
+                        )
+                    }),
+                    _ => None,
+                };
+                match literal.and_then(|token| self.lower_literal(&token, true, syntax)) {
+                    Some(literal) => Expr::Literal(literal),
+                    None => Expr::Negate(self.lower_expr_opt(negate.expression())),
+                }
+            }
+            ast::Expression::Application(application) => {
+                self.lower_application(application, syntax)
+            }
+            // Type arguments are only lowered as part of an application.
+            ast::Expression::TypeArgument(_) => Expr::Missing,
+            ast::Expression::Variable(variable) => match variable.name() {
+                Some(name) => Expr::Variable(lower_qualified_name(&name)),
+                None => Expr::Missing,
+            },
+            ast::Expression::Constructor(constructor) => match constructor.name() {
+                Some(name) => Expr::Constructor(lower_qualified_name(&name)),
+                None => Expr::Missing,
+            },
+            ast::Expression::OperatorName(operator) => match operator.name() {
+                Some(name) => Expr::OperatorName(lower_qualified_name(&name)),
+                None => Expr::Missing,
+            },
+            ast::Expression::Literal(literal) => {
+                match literal.token().and_then(|token| self.lower_literal(&token, false, syntax)) {
+                    Some(literal) => Expr::Literal(literal),
+                    None => Expr::Missing,
+                }
+            }
+            ast::Expression::Array(array) => {
+                let elements = array.elements().map(|element| self.lower_expr(&element)).collect();
+                Expr::Literal(Literal::Array(elements))
+            }
+            ast::Expression::Record(record) => {
+                self.check_labels(record.fields().map(|field| record_item_label(&field)));
+                let fields = record
+                    .fields()
+                    .map(|field| match field {
+                        ast::RecordItem::Field(field) => {
+                            (lower_label(field.label()), self.lower_expr_opt(field.expression()))
+                        }
+                        ast::RecordItem::Pun(pun) => {
+                            let label = lower_label(pun.label());
+                            let variable = Expr::Variable(QualifiedName::unqualified(label));
+                            (label, self.alloc_desugared_expr(variable, pun.syntax()))
+                        }
+                    })
+                    .collect();
+                Expr::Literal(Literal::Record(fields))
+            }
+            ast::Expression::Parenthesized(parenthesized) => match parenthesized.expression() {
+                Some(
+                    chain @ (ast::Expression::OperatorChain(_) | ast::Expression::InfixChain(_)),
+                ) => {
+                    let mut binders = vec![];
+                    let body = self.lower_chain(&chain, Some(&mut binders));
+                    let body = self.alloc_expr(body);
+                    self.body_source_map.insert_expr(body, chain.syntax());
+                    if binders.is_empty() {
+                        return body;
+                    }
+                    Expr::Lambda { binders, body }
+                }
+                expression => return self.lower_expr_opt(expression),
+            },
+            ast::Expression::Hole(hole) => match hole.token() {
+                Some(token) => Expr::Hole(Name::new(token.text().trim_start_matches('?'))),
+                None => Expr::Missing,
+            },
+            ast::Expression::Wildcard(_) => Expr::Wildcard,
+            ast::Expression::RecordAccess(access) => {
+                let mut binders = vec![];
+                let record = self.lower_section_operand(access.expression(), &mut binders);
+                let labels = access.labels().map(|label| lower_label(Some(label))).collect();
+                self.wrap_section(Expr::RecordAccess { record, labels }, binders, syntax)
+            }
+            ast::Expression::RecordUpdate(update) => {
+                let mut binders = vec![];
+                let record = self.lower_section_operand(update.expression(), &mut binders);
+                let update = self.lower_record_update(record, update);
+                self.wrap_section(update, binders, syntax)
+            }
+            ast::Expression::IfThenElse(if_then_else) => {
+                let condition = self.lower_expr_opt(if_then_else.condition());
+                let then = self.lower_expr_opt(if_then_else.then());
+                let else_ = self.lower_expr_opt(if_then_else.else_());
+                Expr::IfThenElse { condition, then, else_ }
+            }
+            ast::Expression::Lambda(lambda) => {
+                let binders = lambda.binders().map(|binder| self.lower_binder(&binder)).collect();
+                let body = self.lower_expr_opt(lambda.body());
+                Expr::Lambda { binders, body }
+            }
+            ast::Expression::Case(case) => {
+                let scrutinees =
+                    case.scrutinees().map(|scrutinee| self.lower_expr(&scrutinee)).collect();
+                let branches = case
+                    .branches()
+                    .map(|branch| {
+                        let binders =
+                            branch.binders().map(|binder| self.lower_binder(&binder)).collect();
+                        let guarded = self.lower_guarded_opt(branch.guarded_expression());
+                        CaseBranch { binders, guarded }
+                    })
+                    .collect();
+                Expr::Case { scrutinees, branches }
+            }
+            ast::Expression::LetIn(let_in) => {
+                let bindings = self.lower_let_bindings(let_in.bindings());
+                let body = self.lower_expr_opt(let_in.expression());
+                Expr::LetIn { bindings, body }
+            }
+            ast::Expression::Do(do_) => {
+                let id = self.lower_do(do_);
+                self.body_source_map.insert_expr(id, syntax);
+                return id;
+            }
+            ast::Expression::Ado(ado) => {
+                let id = self.lower_ado(ado);
+                self.body_source_map.insert_expr(id, syntax);
+                return id;
+            }
+        };
+        let id = self.alloc_expr(expr);
+        self.body_source_map.insert_expr(id, syntax);
+        id
+    }
+
+    /// Lowers an operator chain. Within a `section`, wildcard operands are
+    /// replaced with fresh variables, whose binders are collected.
+    fn lower_chain(
+        &mut self,
+        chain: &ast::Expression,
+        section: Option<&mut Vec<BinderId>>,
+    ) -> Expr {
+        let mut operands = vec![];
+        let mut operators = vec![];
+        self.lower_expr_chain(chain, &mut operands, &mut operators, section);
+        let mut operands = operands.into_iter();
+        let Some(head) = operands.next() else { return Expr::Missing };
+        let tail = operators.into_iter().zip(operands).collect();
+        Expr::OperatorChain { head, tail }
+    }
+
+    /// Flattens nested operator and infix chains, as both kinds of operators
+    /// share one precedence table.
+    fn lower_expr_chain(
+        &mut self,
+        chain: &ast::Expression,
+        operands: &mut Vec<ExprId>,
+        operators: &mut Vec<Operator>,
+        mut section: Option<&mut Vec<BinderId>>,
+    ) {
+        for child in chain.syntax().children() {
+            if let Some(operator) = ast::QualifiedName::cast(child.clone()) {
+                operators.push(Operator::Symbol(lower_qualified_name(&operator)));
+            } else if let Some(tick) = ast::ExpressionTick::cast(child.clone()) {
+                operators.push(Operator::Infix(self.lower_expr_opt(tick.expression())));
+            } else if let Some(operand) = ast::Expression::cast(child) {
+                match (operand, section.as_deref_mut()) {
+                    (
+                        operand @ (ast::Expression::OperatorChain(_)
+                        | ast::Expression::InfixChain(_)),
+                        section,
+                    ) => self.lower_expr_chain(&operand, operands, operators, section),
+                    (operand, Some(binders)) => {
+                        operands.push(self.lower_section_operand(Some(operand), binders))
+                    }
+                    (operand, None) => operands.push(self.lower_expr(&operand)),
+                }
+            }
+        }
+        // Recovered chains may end with a dangling operator.
+        operators.truncate(operands.len().saturating_sub(1));
+    }
+
+    /// Lowers the operand of a section, where a wildcard stands for a fresh
+    /// argument, e.g. the `_` in `_.label`.
+    fn lower_section_operand(
+        &mut self,
+        expr: Option<ast::Expression>,
+        binders: &mut Vec<BinderId>,
+    ) -> ExprId {
+        let Some(ast::Expression::Wildcard(wildcard)) = expr else {
+            return self.lower_expr_opt(expr);
+        };
+        let name = self.fresh_name();
+        binders.push(self.alloc_desugared_binder(Binder::Variable(name), wildcard.syntax()));
+        let variable = Expr::Variable(QualifiedName::unqualified(name));
+        self.alloc_desugared_expr(variable, wildcard.syntax())
+    }
+
+    /// Wraps a section in a lambda over its arguments, if it has any.
+    fn wrap_section(&mut self, expr: Expr, binders: Vec<BinderId>, syntax: &SyntaxNode) -> Expr {
+        if binders.is_empty() {
+            return expr;
+        }
+        let body = self.alloc_desugared_expr(expr, syntax);
+        Expr::Lambda { binders, body }
+    }
+
+    /// Lowers an update of `record`, where nested updates need to refer to
+    /// the record again, so it's bound to a fresh name if it isn't already
+    /// a variable.
+    fn lower_record_update(
+        &mut self,
+        record: ExprId,
+        update: &ast::ExpressionRecordUpdate,
+    ) -> Expr {
+        let is_nested =
+            update.updates().any(|update| matches!(update, ast::RecordUpdate::Branch(_)));
+        let (name, binding) = match &self.exprs[record] {
+            Expr::Variable(name) => (*name, None),
+            _ if is_nested => {
+                let name = self.fresh_name();
+                (QualifiedName::unqualified(name), Some(name))
+            }
+            // The name is only referred to by nested updates.
+            _ => (QualifiedName::unqualified(Name::missing()), None),
+        };
+        let updates = self.lower_record_updates(name, &[], update.updates());
+        let Some(binding) = binding else { return Expr::RecordUpdate { record, updates } };
+
+        let syntax = update.syntax();
+        let variable = self.alloc_desugared_expr(Expr::Variable(name), syntax);
+        let body =
+            self.alloc_desugared_expr(Expr::RecordUpdate { record: variable, updates }, syntax);
+        let binder = self.alloc_desugared_binder(Binder::Variable(binding), syntax);
+        let guarded = GuardedExpr::Unconditional(record);
+        Expr::LetIn { bindings: vec![LetBinding::Value { binder, guarded }], body }
+    }
+
+    /// Lowers the updates to the field at `path` of `record`.
+    fn lower_record_updates(
+        &mut self,
+        record: QualifiedName,
+        path: &[Name],
+        updates: impl Iterator<Item = ast::RecordUpdate>,
+    ) -> Vec<(Name, ExprId)> {
+        let updates: Vec<_> = updates.collect();
+        self.check_labels(updates.iter().map(record_update_label));
+        updates
+            .into_iter()
+            .map(|update| match update {
+                ast::RecordUpdate::Leaf(leaf) => {
+                    (lower_label(leaf.label()), self.lower_expr_opt(leaf.expression()))
+                }
+                ast::RecordUpdate::Branch(branch) => {
+                    let label = lower_label(branch.label());
+                    let path = [path, &[label]].concat();
+                    let syntax = branch.syntax();
+                    let variable = self.alloc_desugared_expr(Expr::Variable(record), syntax);
+                    let access = Expr::RecordAccess { record: variable, labels: path.clone() };
+                    let access = self.alloc_desugared_expr(access, syntax);
+                    let updates = self.lower_record_updates(record, &path, branch.updates());
+                    let update = Expr::RecordUpdate { record: access, updates };
+                    (label, self.alloc_desugared_expr(update, syntax))
+                }
+            })
+            .collect()
+    }
+
+    /// Desugars a `do` block into applications of `bind` and `discard`.
+    ///
+    /// * `x <- e; rest` becomes `bind e (\x -> rest)`
+    /// * `e; rest` becomes `discard e (\_ -> rest)`
+    /// * `let bindings; rest` becomes `let bindings in rest`
+    fn lower_do(&mut self, do_: &ast::ExpressionDo) -> ExprId {
+        let mut statements: Vec<_> = do_.statements().collect();
+        let mut rest = match statements.last() {
+            Some(ast::DoStatement::Discard(discard)) => {
+                let expression = discard.expression();
+                statements.pop();
+                self.lower_expr_opt(expression)
+            }
+            last => {
+                let node = last.map_or(do_.syntax(), |last| last.syntax());
+                let ptr = SyntaxNodePtr::new(node);
+                self.source_map
+                    .diagnostics
+                    .push(LoweringDiagnostic::InvalidDoFinalStatement { ptr });
+                self.alloc_expr(Expr::Missing)
+            }
+        };
+        for statement in statements.iter().rev() {
+            let syntax = statement.syntax();
+            rest = match statement {
+                ast::DoStatement::Bind(bind) => {
+                    let expr = self.lower_expr_opt(bind.expression());
+                    let binder = self.lower_binder_opt(bind.binder());
+                    self.desugar_bind("bind", expr, binder, rest, syntax)
+                }
+                ast::DoStatement::Discard(discard) => {
+                    let expr = self.lower_expr_opt(discard.expression());
+                    let binder = self.alloc_desugared_binder(Binder::Wildcard, syntax);
+                    self.desugar_bind("discard", expr, binder, rest, syntax)
+                }
+                ast::DoStatement::Let(let_) => {
+                    let bindings = self.lower_let_bindings(let_.bindings());
+                    self.alloc_desugared_expr(Expr::LetIn { bindings, body: rest }, syntax)
+                }
+            };
+        }
+        rest
+    }
+
+    /// Creates `function expr (\binder -> body)`.
+    fn desugar_bind(
+        &mut self,
+        function: &str,
+        expr: ExprId,
+        binder: BinderId,
+        body: ExprId,
+        syntax: &SyntaxNode,
+    ) -> ExprId {
+        let function = self.alloc_desugared_variable(function, syntax);
+        let lambda =
+            self.alloc_desugared_expr(Expr::Lambda { binders: vec![binder], body }, syntax);
+        self.alloc_desugared_expr(
+            Expr::Application { function, arguments: vec![expr, lambda] },
+            syntax,
+        )
+    }
+
+    /// Desugars an `ado` block into applications of `map` and `apply`.
+    ///
+    /// The body is wrapped in one lambda per statement, which is then mapped
+    /// over the first statement and applied to the rest, e.g. `ado x <- a;
+    /// y <- b in e` becomes `apply (map (\x -> \y -> e) a) b`. An empty block
+    /// becomes `pure e`.
+    fn lower_ado(&mut self, ado: &ast::ExpressionAdo) -> ExprId {
+        let mut arguments = vec![];
+        let mut statements = vec![];
+        for statement in ado.statements() {
+            match &statement {
+                ast::DoStatement::Bind(bind) => {
+                    arguments.push(self.lower_expr_opt(bind.expression()));
+                    statements
+                        .push((statement.clone(), Some(self.lower_binder_opt(bind.binder()))));
+                }
+                ast::DoStatement::Discard(discard) => {
+                    arguments.push(self.lower_expr_opt(discard.expression()));
+                    statements.push((
+                        statement.clone(),
+                        Some(self.alloc_desugared_binder(Binder::Wildcard, statement.syntax())),
+                    ));
+                }
+                ast::DoStatement::Let(_) => statements.push((statement.clone(), None)),
+            }
+        }
+
+        let mut function = self.lower_expr_opt(ado.expression());
+        for (statement, binder) in statements.iter().rev() {
+            let syntax = statement.syntax();
+            function = match (statement, binder) {
+                (ast::DoStatement::Let(let_), _) => {
+                    let bindings = self.lower_let_bindings(let_.bindings());
+                    self.alloc_desugared_expr(Expr::LetIn { bindings, body: function }, syntax)
+                }
+                (_, binder) => {
+                    let binders = binder.iter().copied().collect();
+                    self.alloc_desugared_expr(Expr::Lambda { binders, body: function }, syntax)
+                }
+            };
+        }
+
+        let syntax = ado.syntax();
+        let mut arguments = arguments.into_iter();
+        let Some(first) = arguments.next() else {
+            let pure = self.alloc_desugared_variable("pure", syntax);
+            let arguments = vec![function];
+            return self
+                .alloc_desugared_expr(Expr::Application { function: pure, arguments }, syntax);
+        };
+        let map = self.alloc_desugared_variable("map", syntax);
+        let application = Expr::Application { function: map, arguments: vec![function, first] };
+        let mut result = self.alloc_desugared_expr(application, syntax);
+        for argument in arguments {
+            let apply = self.alloc_desugared_variable("apply", syntax);
+            let arguments = vec![result, argument];
+            result =
+                self.alloc_desugared_expr(Expr::Application { function: apply, arguments }, syntax);
+        }
+        result
+    }
+
+    fn fresh_name(&mut self) -> Name {
+        let name = Name::new(&format!("${}", self.fresh_names));
+        self.fresh_names += 1;
+        name
+    }
+
+    fn alloc_desugared_variable(&mut self, name: &str, syntax: &SyntaxNode) -> ExprId {
+        let name = QualifiedName::unqualified(Name::new(name));
+        self.alloc_desugared_expr(Expr::Variable(name), syntax)
+    }
+
+    fn lower_binder_opt(&mut self, binder: Option<ast::Binder>) -> BinderId {
+        match binder {
+            Some(binder) => self.lower_binder(&binder),
+            None => self.alloc_binder(Binder::Missing),
+        }
+    }
+
+    fn lower_binder(&mut self, binder: &ast::Binder) -> BinderId {
+        let syntax = binder.syntax();
+        let binder = match binder {
+            ast::Binder::Wildcard(_) => Binder::Wildcard,
+            ast::Binder::Variable(variable) => Binder::Variable(lower_name(variable.name())),
+            ast::Binder::Named(named) => {
+                let name = lower_name(named.name());
+                let binder = self.lower_binder_opt(named.binder());
+                Binder::Named { name, binder }
+            }
+            ast::Binder::Constructor(constructor) => match constructor.name() {
+                Some(name) => {
+                    let name = lower_qualified_name(&name);
+                    let arguments = constructor
+                        .arguments()
+                        .map(|argument| self.lower_binder(&argument))
+                        .collect();
+                    Binder::Constructor { name, arguments }
+                }
+                None => Binder::Missing,
+            },
+            ast::Binder::Literal(literal) => {
+                match literal.token().and_then(|token| self.lower_literal(&token, false, syntax)) {
+                    Some(literal) => Binder::Literal(literal),
+                    None => Binder::Missing,
+                }
+            }
+            ast::Binder::Negative(negative) => {
+                match negative.token().and_then(|token| self.lower_literal(&token, true, syntax)) {
+                    Some(literal) => Binder::Literal(literal),
+                    None => Binder::Missing,
+                }
+            }
+            ast::Binder::Array(array) => {
+                let elements = array.elements().map(|element| self.lower_binder(&element));
+                Binder::Literal(Literal::Array(elements.collect()))
+            }
+            ast::Binder::Record(record) => {
+                self.check_labels(record.fields().map(|field| record_item_label(&field)));
+                let fields = record
+                    .fields()
+                    .map(|field| match field {
+                        ast::RecordItem::Field(field) => {
+                            (lower_label(field.label()), self.lower_binder_opt(field.binder()))
+                        }
+                        ast::RecordItem::Pun(pun) => {
+                            let label = lower_label(pun.label());
+                            (
+                                label,
+                                self.alloc_desugared_binder(Binder::Variable(label), pun.syntax()),
+                            )
+                        }
+                    })
+                    .collect();
+                Binder::Literal(Literal::Record(fields))
+            }
+            ast::Binder::Parenthesized(parenthesized) => {
+                return self.lower_binder_opt(parenthesized.binder());
+            }
+            ast::Binder::Typed(typed) => {
+                let binder = self.lower_binder_opt(typed.binder());
+                let type_ = self.lower_type_opt(typed.type_());
+                Binder::Typed { binder, type_ }
+            }
+            ast::Binder::OperatorChain(chain) => {
+                let mut operands = chain.operands().map(|operand| self.lower_binder(&operand));
+                let Some(head) = operands.next() else { return self.alloc_binder(Binder::Missing) };
+                let operands: Vec<_> = operands.collect();
+                let operators = chain.operators().map(|operator| lower_qualified_name(&operator));
+                let tail = operators.zip(operands).collect();
+                Binder::OperatorChain { head, tail }
+            }
+        };
+        let id = self.alloc_binder(binder);
+        self.body_source_map.insert_binder(id, syntax);
+        id
+    }
+
+    pub(crate) fn lower_type_opt(&mut self, type_: Option<ast::Type>) -> TypeId {
+        match type_ {
+            Some(type_) => self.lower_type(&type_),
+            None => self.alloc_type(Type::Missing),
+        }
+    }
+
+    pub(crate) fn lower_type(&mut self, type_: &ast::Type) -> TypeId {
+        let syntax = type_.syntax();
+        let type_ = match type_ {
+            ast::Type::Forall(forall) => {
+                let variables = self.lower_type_variables(forall.variables());
+                let type_ = self.lower_type_opt(forall.type_());
+                Type::Forall { variables, type_ }
+            }
+            ast::Type::Constrained(constrained) => {
+                let constraint = self.lower_type_opt(constrained.constraint());
+                let type_ = self.lower_type_opt(constrained.type_());
+                Type::Constrained { constraint, type_ }
+            }
+            ast::Type::Constraints(constraints) => {
+                let constraints = constraints.constraints().map(|c| self.lower_type(&c)).collect();
+                Type::Constraints(constraints)
+            }
+            ast::Type::Arrow(arrow) => {
+                let argument = self.lower_type_opt(arrow.argument());
+                let result = self.lower_type_opt(arrow.result());
+                Type::Arrow { argument, result }
+            }
+            ast::Type::OperatorChain(chain) => {
+                let mut operands = chain.operands().map(|operand| self.lower_type(&operand));
+                let Some(head) = operands.next() else { return self.alloc_type(Type::Missing) };
+                let operands: Vec<_> = operands.collect();
+                let operators = chain.operators().map(|operator| lower_qualified_name(&operator));
+                let tail = operators.zip(operands).collect();
+                Type::OperatorChain { head, tail }
+            }
+            ast::Type::Kinded(kinded) => {
+                let type_ = self.lower_type_opt(kinded.type_());
+                let kind = self.lower_type_opt(kinded.kind());
+                Type::Kinded { type_, kind }
+            }
+            ast::Type::Application(application) => {
+                let function = self.lower_type_opt(application.head());
+                let arguments =
+                    application.arguments().map(|argument| self.lower_type(&argument)).collect();
+                Type::Application { function, arguments }
+            }
+            ast::Type::Constructor(constructor) => match constructor.name() {
+                Some(name) => Type::Constructor(lower_qualified_name(&name)),
+                None => Type::Missing,
+            },
+            ast::Type::Variable(variable) => match variable.name_ref() {
+                Some(name) => Type::Variable(lower_name_ref(&name)),
+                None => Type::Missing,
+            },
+            ast::Type::Wildcard(_) => Type::Wildcard,
+            ast::Type::Hole(hole) => match hole.token() {
+                Some(token) => Type::Hole(Name::new(token.text().trim_start_matches('?'))),
+                None => Type::Missing,
+            },
+            ast::Type::String(string) => match string.token() {
+                Some(token) => Type::String(self.lower_string(&token, syntax)),
+                None => Type::Missing,
+            },
+            ast::Type::Integer(integer) => match integer.token() {
+                Some(token) => {
+                    let (text, negative) = match token.text().strip_prefix('-') {
+                        Some(text) => (text, true),
+                        None => (token.text(), false),
+                    };
+                    let value = literal::parse_integer(text, negative);
+                    Type::Integer(self.check_integer(value, syntax))
+                }
+                None => Type::Missing,
+            },
+            ast::Type::Record(record) => {
+                Type::Record(self.lower_row(record.fields(), record.tail()))
+            }
+            ast::Type::Row(row) => Type::Row(self.lower_row(row.fields(), row.tail())),
+            ast::Type::Parenthesized(parenthesized) => {
+                return self.lower_type_opt(parenthesized.type_());
+            }
+            ast::Type::OperatorName(operator) => match operator.name() {
+                Some(name) => Type::OperatorName(lower_qualified_name(&name)),
+                None => Type::Missing,
+            },
+            ast::Type::ArrowName(_) => Type::Function,
+        };
+        let id = self.alloc_type(type_);
+        self.source_map.insert_type(id, syntax);
+        id
+    }
+
+    fn lower_row(
+        &mut self,
+        fields: impl Iterator<Item = ast::RowField>,
+        tail: Option<ast::RowTail>,
+    ) -> Row {
+        // Rows can repeat labels, unlike the records that are built from
+        // them.
+        let fields = fields
+            .map(|field| (lower_label(field.label()), self.lower_type_opt(field.type_())))
+            .collect();
+        let tail = tail.map(|tail| self.lower_type_opt(tail.type_()));
+        Row { fields, tail }
+    }
+}
+
+pub(crate) fn lower_kind_signature_target(
+    signature: &ast::KindSignatureDeclaration,
+) -> KindSignatureTarget {
+    match signature.keyword().map(|keyword| keyword.kind()) {
+        Some(SyntaxKind::NewtypeKw) => KindSignatureTarget::Newtype,
+        Some(SyntaxKind::TypeKw) => KindSignatureTarget::Synonym,
+        Some(SyntaxKind::ClassKw) => KindSignatureTarget::Class,
+        _ => KindSignatureTarget::Data,
+    }
+}
+
+pub(crate) fn lower_functional_dependencies(
+    class: &ast::ClassDeclaration,
+) -> Vec<FunctionalDependency> {
+    let dependencies = class.functional_dependencies();
+    let dependencies =
+        dependencies.into_iter().flat_map(|dependencies| dependencies.dependencies());
+    dependencies
+        .map(|dependency| FunctionalDependency {
+            determiners: dependency.determiners().map(|name| lower_name_ref(&name)).collect(),
+            determined: dependency.determined().map(|name| lower_name_ref(&name)).collect(),
+        })
+        .collect()
+}
+
+pub(crate) fn lower_fixity(fixity: &ast::FixityDeclaration) -> Fixity {
+    let associativity = fixity.associativity().unwrap_or(Associativity::Left);
+    let precedence = fixity
+        .precedence()
+        .and_then(|token| token.text().parse::<u8>().ok())
+        .map_or(9, |precedence| precedence.min(9));
+    let is_type = fixity.is_type();
+    let target = fixity
+        .target()
+        .map(|target| lower_qualified_name(&target))
+        .unwrap_or_else(|| QualifiedName::unqualified(Name::missing()));
+    let operator = lower_name(fixity.operator());
+    Fixity { associativity, precedence, is_type, target, operator }
+}
+
+pub(crate) fn lower_exports(exports: Option<ast::ExportList>) -> Option<Vec<Export>> {
+    exports.map(|exports| exports.items().filter_map(|item| lower_export(&item)).collect())
+}
+
+pub(crate) fn lower_imports(imports: impl Iterator<Item = ast::ImportDeclaration>) -> Vec<Import> {
+    imports.filter_map(|import| lower_import(&import)).collect()
+}
+
+pub fn lower_export(item: &ast::ExportItem) -> Option<Export> {
+    let export = match item {
+        ast::ExportItem::Value(value) => Export::Value(lower_name_ref(&value.name_ref()?)),
+        ast::ExportItem::Operator(operator) => {
+            Export::Operator(lower_name_ref(&operator.name_ref()?))
+        }
+        ast::ExportItem::Type(type_) => Export::Type(
+            lower_name_ref(&type_.name_ref()?),
+            type_.data_members().map(|members| lower_data_members(&members)),
+        ),
+        ast::ExportItem::TypeOperator(operator) => {
+            Export::TypeOperator(lower_name_ref(&operator.name_ref()?))
+        }
+        ast::ExportItem::Class(class) => Export::Class(lower_name_ref(&class.name_ref()?)),
+        ast::ExportItem::Module(module) => {
+            Export::Module(lower_module_name(&module.module_name()?))
+        }
+    };
+    Some(export)
+}
+
+pub fn lower_import(import: &ast::ImportDeclaration) -> Option<Import> {
+    let module = lower_module_name(&import.module_name()?);
+    let list = import.import_list().map(|list| ImportList {
+        hiding: list.hiding(),
+        items: list.items().filter_map(|item| lower_import_item(&item)).collect(),
+    });
+    let alias = import.alias().and_then(|alias| alias.module_name());
+    let alias = alias.map(|alias| lower_module_name(&alias));
+    Some(Import { module, list, alias })
+}
+
+pub fn lower_import_item(item: &ast::ImportItem) -> Option<ImportItem> {
+    let item = match item {
+        ast::ImportItem::Value(value) => ImportItem::Value(lower_name_ref(&value.name_ref()?)),
+        ast::ImportItem::Operator(operator) => {
+            ImportItem::Operator(lower_name_ref(&operator.name_ref()?))
+        }
+        ast::ImportItem::Type(type_) => ImportItem::Type(
+            lower_name_ref(&type_.name_ref()?),
+            type_.data_members().map(|members| lower_data_members(&members)),
+        ),
+        ast::ImportItem::TypeOperator(operator) => {
+            ImportItem::TypeOperator(lower_name_ref(&operator.name_ref()?))
+        }
+        ast::ImportItem::Class(class) => ImportItem::Class(lower_name_ref(&class.name_ref()?)),
+    };
+    Some(item)
+}
+
+fn lower_data_members(members: &ast::DataMembers) -> DataMembers {
+    match members {
+        ast::DataMembers::All(_) => DataMembers::All,
+        ast::DataMembers::Enumerated(enumerated) => DataMembers::Enumerated(
+            enumerated.constructors().map(|constructor| lower_name_ref(&constructor)).collect(),
+        ),
+    }
+}
+
+pub fn lower_module_name(name: &ast::ModuleName) -> ModuleName {
+    let segments: Vec<_> = name.segments().map(|segment| segment.text().to_string()).collect();
+    ModuleName::new(&segments.join("."))
+}
+
+pub fn lower_qualified_name(name: &ast::QualifiedName) -> QualifiedName {
+    let qualifier = name.qualifier().map(|qualifier| lower_module_name(&qualifier));
+    let name = name.token().map_or_else(Name::missing, |token| Name::new(token.text()));
+    QualifiedName { qualifier, name }
+}
+
+pub fn lower_name(name: Option<ast::Name>) -> Name {
+    name.and_then(|name| name.token()).map_or_else(Name::missing, |token| Name::new(token.text()))
+}
+
+pub(crate) fn lower_name_ref(name: &ast::NameRef) -> Name {
+    name.token().map_or_else(Name::missing, |token| Name::new(token.text()))
+}
+
+fn record_item_label(item: &ast::RecordItem) -> Option<ast::Label> {
+    match item {
+        ast::RecordItem::Field(field) => field.label(),
+        ast::RecordItem::Pun(pun) => pun.label(),
+    }
+}
+
+fn record_update_label(update: &ast::RecordUpdate) -> Option<ast::Label> {
+    match update {
+        ast::RecordUpdate::Leaf(leaf) => leaf.label(),
+        ast::RecordUpdate::Branch(branch) => branch.label(),
+    }
+}
+
+/// Lowers a label, which may be quoted, e.g. `{ "a b": 0 }`. Labels with
+/// invalid escapes keep their text as written.
+pub fn lower_label(label: Option<ast::Label>) -> Name {
+    let Some(token) = label.and_then(|label| label.token()) else { return Name::missing() };
+    let (text, escaped) = literal::string_contents(&token);
+    match escaped.then(|| literal::unescape(text)).flatten() {
+        Some(text) => Name::new(&text),
+        None => Name::new(text),
+    }
+}
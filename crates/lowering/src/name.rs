@@ -0,0 +1,113 @@
+//! Interned names.
+
+use std::{
+    fmt,
+    sync::{Mutex, OnceLock},
+};
+
+use rustc_hash::FxHashMap;
+
+/// An interned identifier, operator, or label.
+///
+/// Interned strings live for the rest of the program, such that a [`Name`]
+/// is `Copy` and can be compared and hashed as an integer.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Name(u32);
+
+#[derive(Default)]
+struct Interner {
+    map: FxHashMap<&'static str, u32>,
+    strings: Vec<&'static str>,
+}
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Name {
+    pub fn new(text: &str) -> Name {
+        let mut interner = interner().lock().unwrap();
+        if let Some(&index) = interner.map.get(text) {
+            return Name(index);
+        }
+        let text: &'static str = Box::leak(text.into());
+        let index = interner.strings.len() as u32;
+        interner.strings.push(text);
+        interner.map.insert(text, index);
+        Name(index)
+    }
+
+    /// A placeholder for names missing from erroneous syntax.
+    pub fn missing() -> Name {
+        Name::new("[missing name]")
+    }
+
+    pub fn as_str(self) -> &'static str {
+        interner().lock().unwrap().strings[self.0 as usize]
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A module name such as `Data.Maybe`, interned as a whole.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModuleName(Name);
+
+impl ModuleName {
+    pub fn new(text: &str) -> ModuleName {
+        ModuleName(Name::new(text))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0.as_str()
+    }
+
+    pub fn segments(self) -> impl Iterator<Item = &'static str> {
+        self.as_str().split('.')
+    }
+}
+
+impl fmt::Debug for ModuleName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for ModuleName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A possibly-qualified name such as `Maybe.Just` or `map`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QualifiedName {
+    pub qualifier: Option<ModuleName>,
+    pub name: Name,
+}
+
+impl QualifiedName {
+    pub fn unqualified(name: Name) -> QualifiedName {
+        QualifiedName { qualifier: None, name }
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(qualifier) = self.qualifier {
+            write!(f, "{}.", qualifier)?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
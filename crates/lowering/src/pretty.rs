@@ -0,0 +1,625 @@
+//! A deterministic rendering of the HIR, for snapshot tests.
+//!
+//! The output resembles PureScript, but shows the desugared form of each
+//! body: binders carry their index, e.g. `x#3`, nested scopes are indented,
+//! and every compound subexpression is parenthesized.
+
+use std::fmt::Write;
+
+use crate::{
+    arena::Arena,
+    hir::*,
+    name::{ModuleName, Name},
+};
+
+/// Renders each declaration of a module, separated by blank lines.
+pub fn print_module(module: &Module) -> String {
+    let mut printer = Printer::new(Some(module), &module.types);
+    printer.header();
+    for (_, declaration) in module.declarations.iter() {
+        printer.out.push('\n');
+        printer.declaration(declaration);
+        printer.out.push('\n');
+    }
+    printer.out
+}
+
+/// Renders a value declaration, including its body.
+pub fn print_value(module: &Module, value: &Value) -> String {
+    let mut printer = Printer::new(Some(module), &module.types);
+    printer.value(value);
+    printer.out
+}
+
+/// Renders a type from any arena, such as the types of an item tree.
+pub fn print_type(types: &Arena<Type>, id: TypeId) -> String {
+    let mut printer = Printer::new(None, types);
+    printer.type_(id);
+    printer.out
+}
+
+struct Printer<'a> {
+    /// The module that declarations are printed from, which is absent when
+    /// only printing types.
+    module: Option<&'a Module>,
+    types: &'a Arena<Type>,
+    /// The body that expressions and binders are printed from.
+    body: Option<&'a Body>,
+    out: String,
+    indent: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(module: Option<&'a Module>, types: &'a Arena<Type>) -> Printer<'a> {
+        Printer { module, types, body: None, out: String::new(), indent: 0 }
+    }
+
+    fn module(&self) -> &'a Module {
+        self.module.expect("declarations are only printed within modules")
+    }
+
+    fn body(&self) -> &'a Body {
+        self.body.expect("expressions are only printed within bodies")
+    }
+
+    fn newline(&mut self) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn indented(&mut self, f: impl FnOnce(&mut Self)) {
+        self.indent += 1;
+        f(self);
+        self.indent -= 1;
+    }
+
+    fn list<T>(&mut self, items: &[T], separator: &str, mut f: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.out.push_str(separator);
+            }
+            f(self, item);
+        }
+    }
+
+    fn header(&mut self) {
+        let module = self.module();
+        let name = module.name.map_or("[missing name]", ModuleName::as_str);
+        write!(self.out, "module {name}").unwrap();
+        if let Some(exports) = &module.exports {
+            self.out.push_str(" (");
+            self.list(exports, ", ", |p, export| match export {
+                Export::Value(name) => write!(p.out, "{name}").unwrap(),
+                Export::Operator(name) => write!(p.out, "({name})").unwrap(),
+                Export::Type(name, members) => p.type_members(*name, members.as_ref()),
+                Export::TypeOperator(name) => write!(p.out, "type ({name})").unwrap(),
+                Export::Class(name) => write!(p.out, "class {name}").unwrap(),
+                Export::Module(name) => write!(p.out, "module {name}").unwrap(),
+            });
+            self.out.push(')');
+        }
+        self.out.push('\n');
+
+        for import in &module.imports {
+            write!(self.out, "import {}", import.module).unwrap();
+            if let Some(list) = &import.list {
+                self.out.push_str(if list.hiding { " hiding (" } else { " (" });
+                self.list(&list.items, ", ", |p, item| match item {
+                    ImportItem::Value(name) => write!(p.out, "{name}").unwrap(),
+                    ImportItem::Operator(name) => write!(p.out, "({name})").unwrap(),
+                    ImportItem::Type(name, members) => p.type_members(*name, members.as_ref()),
+                    ImportItem::TypeOperator(name) => write!(p.out, "type ({name})").unwrap(),
+                    ImportItem::Class(name) => write!(p.out, "class {name}").unwrap(),
+                });
+                self.out.push(')');
+            }
+            if let Some(alias) = import.alias {
+                write!(self.out, " as {alias}").unwrap();
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn type_members(&mut self, name: Name, members: Option<&DataMembers>) {
+        write!(self.out, "{name}").unwrap();
+        match members {
+            Some(DataMembers::All) => self.out.push_str("(..)"),
+            Some(DataMembers::Enumerated(constructors)) => {
+                self.out.push('(');
+                self.list(constructors, ", ", |p, name| write!(p.out, "{name}").unwrap());
+                self.out.push(')');
+            }
+            None => {}
+        }
+    }
+
+    fn declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::Value(value) => self.value(value),
+            Declaration::Signature(signature) => self.signature(signature),
+            Declaration::KindSignature(signature) => {
+                let keyword = match signature.target {
+                    KindSignatureTarget::Data => "data",
+                    KindSignatureTarget::Newtype => "newtype",
+                    KindSignatureTarget::Synonym => "type",
+                    KindSignatureTarget::Class => "class",
+                };
+                write!(self.out, "{keyword} {} :: ", signature.name).unwrap();
+                self.type_(signature.kind);
+            }
+            Declaration::Data(data) => {
+                write!(self.out, "data {}", data.name).unwrap();
+                self.type_variables(&data.variables);
+                for (index, constructor) in data.constructors.iter().enumerate() {
+                    self.out.push_str(if index == 0 { " = " } else { " | " });
+                    self.constructor(constructor);
+                }
+            }
+            Declaration::Newtype(newtype) => {
+                write!(self.out, "newtype {}", newtype.name).unwrap();
+                self.type_variables(&newtype.variables);
+                self.out.push_str(" = ");
+                self.constructor(&newtype.constructor);
+            }
+            Declaration::Synonym(synonym) => {
+                write!(self.out, "type {}", synonym.name).unwrap();
+                self.type_variables(&synonym.variables);
+                self.out.push_str(" = ");
+                self.type_(synonym.type_);
+            }
+            Declaration::Class(class) => {
+                self.out.push_str("class ");
+                self.constraints(&class.constraints, " <= ");
+                write!(self.out, "{}", class.name).unwrap();
+                self.type_variables(&class.variables);
+                for (index, dependency) in class.dependencies.iter().enumerate() {
+                    self.out.push_str(if index == 0 { " | " } else { ", " });
+                    self.list(&dependency.determiners, " ", |p, n| write!(p.out, "{n}").unwrap());
+                    self.out.push_str(" -> ");
+                    self.list(&dependency.determined, " ", |p, n| write!(p.out, "{n}").unwrap());
+                }
+                if !class.members.is_empty() {
+                    self.out.push_str(" where");
+                    self.indented(|p| {
+                        for member in &class.members {
+                            p.newline();
+                            p.signature(member);
+                        }
+                    });
+                }
+            }
+            Declaration::InstanceChain(instances) => {
+                for (index, instance) in instances.iter().enumerate() {
+                    if index > 0 {
+                        self.newline();
+                        self.out.push_str("else ");
+                    }
+                    self.out.push_str("instance ");
+                    self.instance_head(instance.name, &instance.head);
+                    if !instance.members.is_empty() {
+                        self.out.push_str(" where");
+                        self.indented(|p| {
+                            for member in &instance.members {
+                                p.newline();
+                                match member {
+                                    InstanceMember::Value(value) => p.value(value),
+                                    InstanceMember::Signature(signature) => p.signature(signature),
+                                }
+                            }
+                        });
+                    }
+                }
+            }
+            Declaration::Derive(derive) => {
+                self.out.push_str(if derive.newtype {
+                    "derive newtype instance "
+                } else {
+                    "derive instance "
+                });
+                self.instance_head(derive.name, &derive.head);
+            }
+            Declaration::ForeignData(foreign) => {
+                write!(self.out, "foreign import data {} :: ", foreign.name).unwrap();
+                self.type_(foreign.kind);
+            }
+            Declaration::ForeignValue(foreign) => {
+                write!(self.out, "foreign import {} :: ", foreign.name).unwrap();
+                self.type_(foreign.type_);
+            }
+            Declaration::Fixity(fixity) => {
+                let keyword = match fixity.associativity {
+                    Associativity::Left => "infixl",
+                    Associativity::Right => "infixr",
+                    Associativity::None => "infix",
+                };
+                let type_ = if fixity.is_type { "type " } else { "" };
+                write!(
+                    self.out,
+                    "{keyword} {} {type_}{} as {}",
+                    fixity.precedence, fixity.target, fixity.operator
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        let body = &self.module().bodies[value.body];
+        self.body = Some(body);
+        write!(self.out, "{}", value.name).unwrap();
+        self.guarded(&body.guarded, "=");
+        self.body = None;
+    }
+
+    fn signature(&mut self, signature: &Signature) {
+        write!(self.out, "{} :: ", signature.name).unwrap();
+        self.type_(signature.type_);
+    }
+
+    fn constructor(&mut self, constructor: &Constructor) {
+        write!(self.out, "{}", constructor.name).unwrap();
+        for &field in &constructor.fields {
+            self.out.push(' ');
+            self.type_atom(field);
+        }
+    }
+
+    fn type_variables(&mut self, variables: &[TypeVariable]) {
+        for variable in variables {
+            self.out.push_str(if variable.visible { " @" } else { " " });
+            match variable.kind {
+                Some(kind) => {
+                    write!(self.out, "({} :: ", variable.name).unwrap();
+                    self.type_(kind);
+                    self.out.push(')');
+                }
+                None => write!(self.out, "{}", variable.name).unwrap(),
+            }
+        }
+    }
+
+    fn constraints(&mut self, constraints: &[TypeId], arrow: &str) {
+        if constraints.is_empty() {
+            return;
+        }
+        self.out.push('(');
+        self.list(constraints, ", ", |p, &constraint| p.type_(constraint));
+        self.out.push(')');
+        self.out.push_str(arrow);
+    }
+
+    fn instance_head(&mut self, name: Option<Name>, head: &InstanceHead) {
+        if let Some(name) = name {
+            write!(self.out, "{name} :: ").unwrap();
+        }
+        self.constraints(&head.constraints, " => ");
+        write!(self.out, "{}", head.class).unwrap();
+        for &argument in &head.arguments {
+            self.out.push(' ');
+            self.type_atom(argument);
+        }
+    }
+
+    fn guarded(&mut self, guarded: &GuardedExpr, separator: &str) {
+        match guarded {
+            GuardedExpr::Unconditional(expr) => {
+                write!(self.out, " {separator} ").unwrap();
+                self.expr(*expr);
+            }
+            GuardedExpr::Guarded(branches) => {
+                self.indented(|p| p.guarded_branches(branches, separator));
+            }
+            GuardedExpr::LetIn { bindings, branches } => self.indented(|p| {
+                p.newline();
+                p.out.push_str("let");
+                p.indented(|p| p.let_bindings(bindings));
+                p.newline();
+                p.out.push_str("in");
+                p.indented(|p| p.guarded_branches(branches, separator));
+            }),
+        }
+    }
+
+    fn guarded_branches(&mut self, branches: &[GuardedBranch], separator: &str) {
+        for branch in branches {
+            self.newline();
+            self.out.push_str("| ");
+            self.list(&branch.guards, ", ", |p, guard| match guard {
+                Guard::Boolean(expr) => p.expr(*expr),
+                Guard::Pattern(binder, expr) => {
+                    p.binder(*binder);
+                    p.out.push_str(" <- ");
+                    p.expr(*expr);
+                }
+            });
+            write!(self.out, " {separator} ").unwrap();
+            self.expr(branch.expr);
+        }
+    }
+
+    fn let_bindings(&mut self, bindings: &[LetBinding]) {
+        for binding in bindings {
+            self.newline();
+            match binding {
+                LetBinding::Value { binder, guarded } => {
+                    self.binder(*binder);
+                    self.guarded(guarded, "=");
+                }
+                LetBinding::Signature(signature) => self.signature(signature),
+                LetBinding::Pattern { binder, guarded } => {
+                    self.binder(*binder);
+                    self.guarded(guarded, "=");
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self, id: ExprId) {
+        match &self.body().exprs[id] {
+            Expr::Missing => self.out.push_str("<missing>"),
+            Expr::Typed { expr, type_ } => {
+                self.expr_atom(*expr);
+                self.out.push_str(" :: ");
+                self.type_(*type_);
+            }
+            Expr::OperatorChain { head, tail } => {
+                self.expr_atom(*head);
+                for (operator, operand) in tail {
+                    match operator {
+                        Operator::Symbol(name) => write!(self.out, " {name} ").unwrap(),
+                        Operator::Infix(function) => {
+                            self.out.push_str(" `");
+                            self.expr(*function);
+                            self.out.push_str("` ");
+                        }
+                    }
+                    self.expr_atom(*operand);
+                }
+            }
+            Expr::Negate(expr) => {
+                self.out.push('-');
+                self.expr_atom(*expr);
+            }
+            Expr::Application { function, arguments } => {
+                self.expr_atom(*function);
+                for &argument in arguments {
+                    self.out.push(' ');
+                    self.expr_atom(argument);
+                }
+            }
+            Expr::TypeApplication { expr, type_ } => {
+                self.expr_atom(*expr);
+                self.out.push_str(" @");
+                self.type_atom(*type_);
+            }
+            Expr::Variable(name) | Expr::Constructor(name) => write!(self.out, "{name}").unwrap(),
+            Expr::OperatorName(name) => write!(self.out, "({name})").unwrap(),
+            Expr::Literal(literal) => self.literal(literal, Self::expr),
+            Expr::Hole(name) => write!(self.out, "?{name}").unwrap(),
+            Expr::Wildcard => self.out.push('_'),
+            Expr::RecordAccess { record, labels } => {
+                self.expr_atom(*record);
+                for label in labels {
+                    write!(self.out, ".{label}").unwrap();
+                }
+            }
+            Expr::RecordUpdate { record, updates } => {
+                self.expr_atom(*record);
+                self.out.push_str(" { ");
+                self.list(updates, ", ", |p, (label, expr)| {
+                    write!(p.out, "{label} = ").unwrap();
+                    p.expr(*expr);
+                });
+                self.out.push_str(" }");
+            }
+            Expr::IfThenElse { condition, then, else_ } => {
+                self.out.push_str("if ");
+                self.expr(*condition);
+                self.out.push_str(" then ");
+                self.expr(*then);
+                self.out.push_str(" else ");
+                self.expr(*else_);
+            }
+            Expr::Lambda { binders, body } => {
+                self.out.push('\\');
+                self.list(binders, " ", |p, &binder| p.binder_atom(binder));
+                self.out.push_str(" -> ");
+                self.expr(*body);
+            }
+            Expr::Case { scrutinees, branches } => {
+                self.out.push_str("case ");
+                self.list(scrutinees, ", ", |p, &scrutinee| p.expr(scrutinee));
+                self.out.push_str(" of");
+                self.indented(|p| {
+                    for branch in branches {
+                        p.newline();
+                        p.list(&branch.binders, ", ", |p, &binder| p.binder(binder));
+                        p.guarded(&branch.guarded, "->");
+                    }
+                });
+            }
+            Expr::LetIn { bindings, body } => self.indented(|p| {
+                p.newline();
+                p.out.push_str("let");
+                p.indented(|p| p.let_bindings(bindings));
+                p.newline();
+                p.out.push_str("in ");
+                p.expr(*body);
+            }),
+        }
+    }
+
+    fn expr_atom(&mut self, id: ExprId) {
+        let atomic = matches!(
+            self.body().exprs[id],
+            Expr::Missing
+                | Expr::Variable(_)
+                | Expr::Constructor(_)
+                | Expr::OperatorName(_)
+                | Expr::Literal(_)
+                | Expr::Hole(_)
+                | Expr::Wildcard
+                | Expr::RecordAccess { .. }
+        );
+        self.parenthesize(!atomic, |p| p.expr(id));
+    }
+
+    fn literal<I: Copy>(&mut self, literal: &Literal<I>, mut f: impl FnMut(&mut Self, I)) {
+        match literal {
+            Literal::Int(value) => write!(self.out, "{value}").unwrap(),
+            Literal::Number(Number(value)) => write!(self.out, "{value:?}").unwrap(),
+            Literal::String(value) => write!(self.out, "{value:?}").unwrap(),
+            Literal::Char(value) => write!(self.out, "{value:?}").unwrap(),
+            Literal::Boolean(value) => write!(self.out, "{value}").unwrap(),
+            Literal::Array(elements) => {
+                self.out.push('[');
+                self.list(elements, ", ", |p, &element| f(p, element));
+                self.out.push(']');
+            }
+            Literal::Record(fields) => {
+                self.out.push_str("{ ");
+                self.list(fields, ", ", |p, &(label, value)| {
+                    write!(p.out, "{label}: ").unwrap();
+                    f(p, value);
+                });
+                self.out.push_str(" }");
+            }
+        }
+    }
+
+    fn binder(&mut self, id: BinderId) {
+        match &self.body().binders[id] {
+            Binder::Missing => self.out.push_str("<missing>"),
+            Binder::Wildcard => self.out.push('_'),
+            Binder::Variable(name) => write!(self.out, "{name}#{}", id.into_raw()).unwrap(),
+            Binder::Named { name, binder } => {
+                write!(self.out, "{name}#{}@", id.into_raw()).unwrap();
+                self.binder_atom(*binder);
+            }
+            Binder::Constructor { name, arguments } => {
+                write!(self.out, "{name}").unwrap();
+                for &argument in arguments {
+                    self.out.push(' ');
+                    self.binder_atom(argument);
+                }
+            }
+            Binder::Literal(literal) => self.literal(literal, Self::binder),
+            Binder::Typed { binder, type_ } => {
+                self.binder_atom(*binder);
+                self.out.push_str(" :: ");
+                self.type_(*type_);
+            }
+            Binder::OperatorChain { head, tail } => {
+                self.binder_atom(*head);
+                for (operator, operand) in tail {
+                    write!(self.out, " {operator} ").unwrap();
+                    self.binder_atom(*operand);
+                }
+            }
+        }
+    }
+
+    fn binder_atom(&mut self, id: BinderId) {
+        let compound = match &self.body().binders[id] {
+            Binder::Constructor { arguments, .. } => !arguments.is_empty(),
+            Binder::Typed { .. } | Binder::OperatorChain { .. } => true,
+            _ => false,
+        };
+        self.parenthesize(compound, |p| p.binder(id));
+    }
+
+    fn type_(&mut self, id: TypeId) {
+        match &self.types[id] {
+            Type::Missing => self.out.push_str("<missing>"),
+            Type::Forall { variables, type_ } => {
+                self.out.push_str("forall");
+                self.type_variables(variables);
+                self.out.push_str(". ");
+                self.type_(*type_);
+            }
+            Type::Constrained { constraint, type_ } => {
+                self.type_atom(*constraint);
+                self.out.push_str(" => ");
+                self.type_(*type_);
+            }
+            Type::Arrow { argument, result } => {
+                self.type_atom(*argument);
+                self.out.push_str(" -> ");
+                self.type_(*result);
+            }
+            Type::OperatorChain { head, tail } => {
+                self.type_atom(*head);
+                for (operator, operand) in tail {
+                    write!(self.out, " {operator} ").unwrap();
+                    self.type_atom(*operand);
+                }
+            }
+            Type::Kinded { type_, kind } => {
+                self.type_atom(*type_);
+                self.out.push_str(" :: ");
+                self.type_(*kind);
+            }
+            Type::Application { function, arguments } => {
+                self.type_atom(*function);
+                for &argument in arguments {
+                    self.out.push(' ');
+                    self.type_atom(argument);
+                }
+            }
+            Type::Constructor(name) => write!(self.out, "{name}").unwrap(),
+            Type::Variable(name) => write!(self.out, "{name}").unwrap(),
+            Type::Wildcard => self.out.push('_'),
+            Type::Hole(name) => write!(self.out, "?{name}").unwrap(),
+            Type::String(value) => write!(self.out, "{value:?}").unwrap(),
+            Type::Integer(value) => write!(self.out, "{value}").unwrap(),
+            Type::Record(row) => self.row(row, "{ ", " }"),
+            Type::Row(row) => self.row(row, "( ", " )"),
+            Type::OperatorName(name) => write!(self.out, "({name})").unwrap(),
+            Type::Function => self.out.push_str("(->)"),
+            Type::Constraints(constraints) => {
+                self.out.push('(');
+                self.list(constraints, ", ", |p, &constraint| p.type_(constraint));
+                self.out.push(')');
+            }
+        }
+    }
+
+    fn type_atom(&mut self, id: TypeId) {
+        let compound = matches!(
+            self.types[id],
+            Type::Forall { .. }
+                | Type::Constrained { .. }
+                | Type::Arrow { .. }
+                | Type::OperatorChain { .. }
+                | Type::Kinded { .. }
+                | Type::Application { .. }
+        );
+        self.parenthesize(compound, |p| p.type_(id));
+    }
+
+    fn row(&mut self, row: &Row, open: &str, close: &str) {
+        self.out.push_str(open);
+        self.list(&row.fields, ", ", |p, &(label, type_)| {
+            write!(p.out, "{label} :: ").unwrap();
+            p.type_(type_);
+        });
+        if let Some(tail) = row.tail {
+            self.out.push_str(" | ");
+            self.type_(tail);
+        }
+        self.out.push_str(close);
+    }
+
+    fn parenthesize(&mut self, parenthesize: bool, f: impl FnOnce(&mut Self)) {
+        if parenthesize {
+            self.out.push('(');
+        }
+        f(self);
+        if parenthesize {
+            self.out.push(')');
+        }
+    }
+}
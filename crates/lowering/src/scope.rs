@@ -0,0 +1,273 @@
+//! Scopes of the names bound within a body.
+
+use crate::{
+    arena::{Arena, ArenaMap, Idx},
+    hir::*,
+    name::Name,
+};
+
+pub type ScopeId = Idx<Scope>;
+
+/// A scope introduced by a lambda, case branch, pattern guard, or `let`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Scope {
+    pub parent: Option<ScopeId>,
+    pub entries: Vec<ScopeEntry>,
+}
+
+/// A name bound by a [`Binder::Variable`] or [`Binder::Named`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeEntry {
+    pub name: Name,
+    pub binder: BinderId,
+}
+
+/// Maps each expression of a body to the scope it's in.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScopeTree {
+    scopes: Arena<Scope>,
+    scope_by_expr: ArenaMap<ExprId, ScopeId>,
+}
+
+impl ScopeTree {
+    pub fn new(body: &Body) -> ScopeTree {
+        let mut scopes = ScopeTree { scopes: Arena::default(), scope_by_expr: ArenaMap::default() };
+        let root = scopes.scopes.alloc(Scope { parent: None, entries: vec![] });
+        scopes.guarded(body, &body.guarded, root);
+        scopes.scopes.shrink_to_fit();
+        scopes.scope_by_expr.shrink_to_fit();
+        scopes
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ScopeId, &Scope)> + '_ {
+        self.scopes.iter()
+    }
+
+    pub fn scope_for_expr(&self, expr: ExprId) -> Option<ScopeId> {
+        self.scope_by_expr.get(expr).copied()
+    }
+
+    /// The scopes enclosing `scope`, starting with itself.
+    pub fn scope_chain(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), |&scope| self.scopes[scope].parent)
+    }
+
+    /// The names visible from `expr`, innermost first, such that shadowed
+    /// names come after the names shadowing them.
+    pub fn visible(&self, expr: ExprId) -> impl Iterator<Item = &ScopeEntry> + '_ {
+        let scopes =
+            self.scope_for_expr(expr).into_iter().flat_map(|scope| self.scope_chain(scope));
+        scopes.flat_map(|scope| self.scopes[scope].entries.iter().rev())
+    }
+
+    /// Resolves an unqualified name from `expr` to the binder it refers to.
+    pub fn resolve(&self, expr: ExprId, name: Name) -> Option<BinderId> {
+        self.visible(expr).find(|entry| entry.name == name).map(|entry| entry.binder)
+    }
+
+    fn child(&mut self, parent: ScopeId) -> ScopeId {
+        self.scopes.alloc(Scope { parent: Some(parent), entries: vec![] })
+    }
+
+    fn bind(&mut self, body: &Body, scope: ScopeId, binder: BinderId) {
+        match &body.binders[binder] {
+            Binder::Missing | Binder::Wildcard => {}
+            &Binder::Variable(name) => self.scopes[scope].entries.push(ScopeEntry { name, binder }),
+            &Binder::Named { name, binder: inner } => {
+                self.scopes[scope].entries.push(ScopeEntry { name, binder });
+                self.bind(body, scope, inner);
+            }
+            Binder::Constructor { arguments, .. } => {
+                for &argument in arguments {
+                    self.bind(body, scope, argument);
+                }
+            }
+            Binder::Literal(Literal::Array(elements)) => {
+                for &element in elements {
+                    self.bind(body, scope, element);
+                }
+            }
+            Binder::Literal(Literal::Record(fields)) => {
+                for &(_, field) in fields {
+                    self.bind(body, scope, field);
+                }
+            }
+            Binder::Literal(_) => {}
+            &Binder::Typed { binder, .. } => self.bind(body, scope, binder),
+            Binder::OperatorChain { head, tail } => {
+                self.bind(body, scope, *head);
+                for &(_, operand) in tail {
+                    self.bind(body, scope, operand);
+                }
+            }
+        }
+    }
+
+    /// Bindings in `let` are mutually recursive, so all of them are visible
+    /// from every binding.
+    fn let_bindings(&mut self, body: &Body, bindings: &[LetBinding], scope: ScopeId) -> ScopeId {
+        let scope = self.child(scope);
+        for binding in bindings {
+            match binding {
+                LetBinding::Value { binder, .. } | LetBinding::Pattern { binder, .. } => {
+                    self.bind(body, scope, *binder)
+                }
+                LetBinding::Signature(_) => {}
+            }
+        }
+        for binding in bindings {
+            match binding {
+                LetBinding::Value { guarded, .. } | LetBinding::Pattern { guarded, .. } => {
+                    self.guarded(body, guarded, scope)
+                }
+                LetBinding::Signature(_) => {}
+            }
+        }
+        scope
+    }
+
+    fn guarded(&mut self, body: &Body, guarded: &GuardedExpr, scope: ScopeId) {
+        match guarded {
+            GuardedExpr::Unconditional(expr) => self.expr(body, *expr, scope),
+            GuardedExpr::Guarded(branches) => self.guarded_branches(body, branches, scope),
+            GuardedExpr::LetIn { bindings, branches } => {
+                let scope = self.let_bindings(body, bindings, scope);
+                self.guarded_branches(body, branches, scope);
+            }
+        }
+    }
+
+    fn guarded_branches(&mut self, body: &Body, branches: &[GuardedBranch], scope: ScopeId) {
+        for branch in branches {
+            let mut scope = scope;
+            for guard in &branch.guards {
+                match *guard {
+                    Guard::Boolean(expr) => self.expr(body, expr, scope),
+                    // Pattern guards bind names for later guards and the branch.
+                    Guard::Pattern(binder, expr) => {
+                        self.expr(body, expr, scope);
+                        scope = self.child(scope);
+                        self.bind(body, scope, binder);
+                    }
+                }
+            }
+            self.expr(body, branch.expr, scope);
+        }
+    }
+
+    fn expr(&mut self, body: &Body, expr: ExprId, scope: ScopeId) {
+        self.scope_by_expr.insert(expr, scope);
+        match &body.exprs[expr] {
+            Expr::Missing
+            | Expr::Variable(_)
+            | Expr::Constructor(_)
+            | Expr::OperatorName(_)
+            | Expr::Hole(_)
+            | Expr::Wildcard => {}
+            Expr::Typed { expr, .. }
+            | Expr::TypeApplication { expr, .. }
+            | Expr::Negate(expr)
+            | Expr::RecordAccess { record: expr, .. } => self.expr(body, *expr, scope),
+            Expr::OperatorChain { head, tail } => {
+                self.expr(body, *head, scope);
+                for (operator, operand) in tail {
+                    if let Operator::Infix(function) = operator {
+                        self.expr(body, *function, scope);
+                    }
+                    self.expr(body, *operand, scope);
+                }
+            }
+            Expr::Application { function, arguments } => {
+                self.expr(body, *function, scope);
+                for &argument in arguments {
+                    self.expr(body, argument, scope);
+                }
+            }
+            Expr::Literal(Literal::Array(elements)) => {
+                for &element in elements {
+                    self.expr(body, element, scope);
+                }
+            }
+            Expr::Literal(Literal::Record(fields)) => {
+                for &(_, field) in fields {
+                    self.expr(body, field, scope);
+                }
+            }
+            Expr::Literal(_) => {}
+            Expr::RecordUpdate { record, updates } => {
+                self.expr(body, *record, scope);
+                for &(_, update) in updates {
+                    self.expr(body, update, scope);
+                }
+            }
+            Expr::IfThenElse { condition, then, else_ } => {
+                self.expr(body, *condition, scope);
+                self.expr(body, *then, scope);
+                self.expr(body, *else_, scope);
+            }
+            Expr::Lambda { binders, body: lambda } => {
+                let scope = self.child(scope);
+                for &binder in binders {
+                    self.bind(body, scope, binder);
+                }
+                self.expr(body, *lambda, scope);
+            }
+            Expr::Case { scrutinees, branches } => {
+                for &scrutinee in scrutinees {
+                    self.expr(body, scrutinee, scope);
+                }
+                for branch in branches {
+                    let scope = self.child(scope);
+                    for &binder in &branch.binders {
+                        self.bind(body, scope, binder);
+                    }
+                    self.guarded(body, &branch.guarded, scope);
+                }
+            }
+            Expr::LetIn { bindings, body: let_body } => {
+                let scope = self.let_bindings(body, bindings, scope);
+                self.expr(body, *let_body, scope);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::ScopeTree;
+    use crate::{
+        hir::{Binder, Declaration, Expr},
+        lower_module,
+        name::Name,
+    };
+
+    #[test]
+    fn where_bindings_are_visible_from_guards() {
+        let source = "module Main where\n\nf x | Just y <- g x, y > low = y\n  where\n  low = 0\n";
+        let (node, _) = parsing::parse_module(source);
+        let module = lower_module(&ast::Module::cast(node).unwrap());
+        let (_, Declaration::Value(value)) = module.declarations.iter().next().unwrap() else {
+            panic!()
+        };
+        let body = &module.bodies[value.body];
+        let scopes = ScopeTree::new(body);
+
+        let (guard, _) = body
+            .exprs
+            .iter()
+            .find(|(_, expr)| matches!(expr, Expr::Variable(name) if name.name.as_str() == "low"))
+            .unwrap();
+        let visible: Vec<_> = scopes.visible(guard).map(|entry| entry.name.as_str()).collect();
+        assert_eq!(visible, ["y", "low", "x", "$0"]);
+
+        let binder = scopes.resolve(guard, Name::new("x")).unwrap();
+        assert_eq!(body.binders[binder], Binder::Variable(Name::new("x")));
+    }
+}
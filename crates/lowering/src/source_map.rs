@@ -0,0 +1,151 @@
+//! Mappings from the HIR back to the syntax it was lowered from.
+
+use rowan::ast::{AstNode, AstPtr, SyntaxNodePtr};
+use rustc_hash::FxHashMap;
+use syntax::{ast, PureScript, SyntaxNode};
+
+use crate::{
+    arena::{ArenaMap, Idx},
+    diagnostics::LoweringDiagnostic,
+    hir::{BinderId, BodyId, ExprId, TypeId},
+};
+
+/// Maps lowered types and bodies to their syntax, and vice versa.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    bodies: ArenaMap<BodyId, BodySourceMap>,
+    types: Mapping<TypeId>,
+    pub(crate) diagnostics: Vec<LoweringDiagnostic>,
+}
+
+/// Maps the expressions and binders of a body to their syntax, and vice
+/// versa.
+///
+/// Nodes synthesized through desugaring map to the syntax they were
+/// desugared from, e.g. the `bind` in a desugared `do` block maps to its
+/// statement. Only the outermost node is mapped from the syntax.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BodySourceMap {
+    exprs: Mapping<ExprId>,
+    binders: Mapping<BinderId>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Mapping<I> {
+    to_syntax: ArenaMap<I, SyntaxNodePtr<PureScript>>,
+    from_syntax: FxHashMap<SyntaxNodePtr<PureScript>, I>,
+}
+
+impl<I> Default for Mapping<I> {
+    fn default() -> Self {
+        Mapping { to_syntax: ArenaMap::default(), from_syntax: FxHashMap::default() }
+    }
+}
+
+impl<T> Mapping<Idx<T>> {
+    fn insert(&mut self, id: Idx<T>, node: &SyntaxNode) {
+        let ptr = SyntaxNodePtr::new(node);
+        self.to_syntax.insert(id, ptr.clone());
+        self.from_syntax.insert(ptr, id);
+    }
+
+    fn insert_desugared(&mut self, id: Idx<T>, node: &SyntaxNode) {
+        self.to_syntax.insert(id, SyntaxNodePtr::new(node));
+    }
+
+    fn ptr<N: AstNode<Language = PureScript>>(&self, id: Idx<T>) -> Option<AstPtr<N>> {
+        self.to_syntax.get(id)?.clone().cast()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.to_syntax.shrink_to_fit();
+        self.from_syntax.shrink_to_fit();
+    }
+}
+
+impl SourceMap {
+    pub fn body(&self, id: BodyId) -> &BodySourceMap {
+        self.bodies.get(id).expect("bodies are always mapped")
+    }
+
+    pub fn type_syntax(&self, id: TypeId) -> Option<&SyntaxNodePtr<PureScript>> {
+        self.types.to_syntax.get(id)
+    }
+
+    pub fn type_ptr(&self, id: TypeId) -> Option<AstPtr<ast::Type>> {
+        self.types.ptr(id)
+    }
+
+    pub fn node_type(&self, node: &SyntaxNode) -> Option<TypeId> {
+        self.types.from_syntax.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    pub fn diagnostics(&self) -> &[LoweringDiagnostic] {
+        &self.diagnostics
+    }
+
+    pub(crate) fn insert_body(&mut self, id: BodyId, source_map: BodySourceMap) {
+        self.bodies.insert(id, source_map);
+    }
+
+    pub(crate) fn insert_type(&mut self, id: TypeId, node: &SyntaxNode) {
+        self.types.insert(id, node);
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.bodies.shrink_to_fit();
+        self.types.shrink_to_fit();
+        self.diagnostics.shrink_to_fit();
+    }
+}
+
+impl BodySourceMap {
+    /// The syntax an expression was lowered or desugared from.
+    pub fn expr_syntax(&self, id: ExprId) -> Option<&SyntaxNodePtr<PureScript>> {
+        self.exprs.to_syntax.get(id)
+    }
+
+    /// The expression an expression was lowered from, which is `None` for
+    /// expressions desugared from other syntax.
+    pub fn expr_ptr(&self, id: ExprId) -> Option<AstPtr<ast::Expression>> {
+        self.exprs.ptr(id)
+    }
+
+    pub fn node_expr(&self, node: &SyntaxNode) -> Option<ExprId> {
+        self.exprs.from_syntax.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    pub fn binder_syntax(&self, id: BinderId) -> Option<&SyntaxNodePtr<PureScript>> {
+        self.binders.to_syntax.get(id)
+    }
+
+    pub fn binder_ptr(&self, id: BinderId) -> Option<AstPtr<ast::Binder>> {
+        self.binders.ptr(id)
+    }
+
+    pub fn node_binder(&self, node: &SyntaxNode) -> Option<BinderId> {
+        self.binders.from_syntax.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    pub(crate) fn insert_expr(&mut self, id: ExprId, node: &SyntaxNode) {
+        self.exprs.insert(id, node);
+    }
+
+    /// Maps a synthesized expression to its syntax, without mapping back.
+    pub(crate) fn insert_desugared_expr(&mut self, id: ExprId, node: &SyntaxNode) {
+        self.exprs.insert_desugared(id, node);
+    }
+
+    pub(crate) fn insert_binder(&mut self, id: BinderId, node: &SyntaxNode) {
+        self.binders.insert(id, node);
+    }
+
+    pub(crate) fn insert_desugared_binder(&mut self, id: BinderId, node: &SyntaxNode) {
+        self.binders.insert_desugared(id, node);
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.exprs.shrink_to_fit();
+        self.binders.shrink_to_fit();
+    }
+}
@@ -0,0 +1,41 @@
+//! Snapshot tests for lowering.
+//!
+//! Each `tests/fixtures/*.purs` file is lowered and pretty-printed, and then
+//! compared against the `.hir` file next to it. Run with `UPDATE_SNAPSHOTS=1`
+//! to write the current output instead.
+
+use std::{env, fs, path::Path};
+
+use rowan::ast::AstNode;
+use syntax::ast;
+
+#[test]
+fn snapshots() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+    let mut paths: Vec<_> = fs::read_dir(&fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "purs"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let mut mismatched = vec![];
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        let (node, _) = parsing::parse_module(&source);
+        let module = lowering::lower_module(&ast::Module::cast(node).unwrap());
+        let actual = lowering::pretty::print_module(&module);
+
+        let snapshot = path.with_extension("hir");
+        if update {
+            fs::write(&snapshot, &actual).unwrap();
+        } else if fs::read_to_string(&snapshot).ok().as_deref() != Some(actual.as_str()) {
+            eprintln!("--- {}\n{actual}", snapshot.display());
+            mismatched.push(snapshot);
+        }
+    }
+    assert!(mismatched.is_empty(), "mismatched snapshots: {mismatched:?}");
+}
@@ -0,0 +1,130 @@
+//! Builds the syntax tree from [`Lexed`] and [`Output`].
+//!
+//! The parser only sees significant tokens; trivia is reinserted here. Trivia
+//! that precedes a node is attached to its parent, such that nodes start and
+//! end with significant tokens.
+
+use std::mem;
+
+use rowan::{GreenNode, GreenNodeBuilder, TextRange, TextSize};
+use syntax::SyntaxKind;
+
+use crate::{
+    lexer::Lexed,
+    output::{Event, Output},
+    ParseError, ParseErrorKind,
+};
+
+struct Builder<'l, 'a> {
+    lexed: &'l Lexed<'a>,
+    index: usize,
+    depth: usize,
+    builder: GreenNodeBuilder<'static>,
+    errors: Vec<ParseError>,
+}
+
+impl<'l, 'a> Builder<'l, 'a> {
+    fn new(lexed: &'l Lexed<'a>) -> Builder<'l, 'a> {
+        let index = 0;
+        let depth = 0;
+        let builder = GreenNodeBuilder::new();
+        let errors = lexed
+            .errors()
+            .iter()
+            .map(|error| {
+                let index = error.index as usize;
+                let range = token_range(lexed, index);
+                ParseError { kind: ParseErrorKind::Lexer, message: error.message.clone(), range }
+            })
+            .collect();
+        Builder { lexed, index, depth, builder, errors }
+    }
+
+    fn trivia(&mut self) {
+        while self.index < self.lexed.len() && self.lexed.kind(self.index).is_trivia() {
+            self.token();
+        }
+    }
+
+    fn token(&mut self) {
+        let kind = self.lexed.kind(self.index);
+        let text = self.lexed.text(self.index);
+        self.builder.token(kind.into(), text);
+        self.index += 1;
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        if self.depth > 0 {
+            self.trivia();
+        }
+        self.builder.start_node(kind.into());
+        self.depth += 1;
+    }
+
+    fn significant(&mut self) {
+        self.trivia();
+        self.token();
+    }
+
+    fn finish(&mut self) {
+        if self.depth == 1 {
+            while self.index < self.lexed.len() {
+                self.token();
+            }
+        }
+        self.builder.finish_node();
+        self.depth -= 1;
+    }
+
+    fn error(&mut self, kind: ParseErrorKind, message: String) {
+        let mut index = self.index;
+        while index < self.lexed.len() && self.lexed.kind(index).is_trivia() {
+            index += 1;
+        }
+        let range = token_range(self.lexed, index);
+        self.errors.push(ParseError { kind, message, range });
+    }
+}
+
+fn token_range(lexed: &Lexed, index: usize) -> TextRange {
+    let start = TextSize::from(lexed.offset(index.min(lexed.len())));
+    let end = TextSize::from(lexed.offset((index + 1).min(lexed.len())));
+    TextRange::new(start, end)
+}
+
+pub(crate) fn build(lexed: &Lexed, output: Output) -> (GreenNode, Vec<ParseError>) {
+    let mut builder = Builder::new(lexed);
+    let mut events = output.events;
+    let mut forward_parents = vec![];
+
+    for index in 0..events.len() {
+        match mem::replace(&mut events[index], Event::Tombstone) {
+            Event::Start { kind, forward_parent } => {
+                forward_parents.push(kind);
+                let mut current = index;
+                let mut forward_parent = forward_parent;
+                while let Some(offset) = forward_parent {
+                    current += offset;
+                    forward_parent = match mem::replace(&mut events[current], Event::Tombstone) {
+                        Event::Start { kind, forward_parent } => {
+                            forward_parents.push(kind);
+                            forward_parent
+                        }
+                        _ => unreachable!(),
+                    };
+                }
+                for kind in forward_parents.drain(..).rev() {
+                    builder.start(kind);
+                }
+            }
+            Event::Token => builder.significant(),
+            Event::Finish => builder.finish(),
+            Event::Error { kind, message } => builder.error(kind, message),
+            Event::Tombstone => (),
+        }
+    }
+
+    let mut errors = builder.errors;
+    errors.sort_by_key(|error| error.range.start());
+    (builder.builder.finish(), errors)
+}
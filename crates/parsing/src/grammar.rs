@@ -0,0 +1,204 @@
+//! The grammar for PureScript, written as recursive descent rules over the
+//! [`Parser`].
+//!
+//! Rules that parse an optional construct return an [`Option`] with the
+//! [`CompletedMarker`] for the node, such that callers can wrap it e.g. for
+//! left-associative applications.
+
+mod binders;
+mod declarations;
+mod expressions;
+mod module;
+mod types;
+
+use syntax::SyntaxKind;
+
+use crate::parser::{CompletedMarker, Parser};
+
+pub(crate) use module::module;
+
+/// Entry point for a standalone expression.
+pub(crate) fn expression_entry(p: &mut Parser) {
+    standalone(p, |p| {
+        if expressions::expression(p).is_none() {
+            p.error("expected an expression");
+        }
+    });
+}
+
+/// Entry point for a standalone type.
+pub(crate) fn type_entry(p: &mut Parser) {
+    standalone(p, |p| {
+        if types::type_(p).is_none() {
+            p.error("expected a type");
+        }
+    });
+}
+
+fn standalone(p: &mut Parser, rule: impl FnOnce(&mut Parser)) {
+    let marker = p.start();
+    if p.layout_start() {
+        rule(p);
+        p.layout_end();
+    }
+    if !p.at_eof() {
+        p.error("unexpected token");
+        let error = p.start();
+        while !p.at_eof() {
+            p.bump();
+        }
+        error.complete(p, SyntaxKind::Error);
+    }
+    marker.complete(p, SyntaxKind::Module);
+}
+
+/// Parses a binding occurrence of a name.
+fn name(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    marker.complete(p, SyntaxKind::Name)
+}
+
+/// Parses an unqualified reference to a name.
+fn name_ref(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    marker.complete(p, SyntaxKind::NameRef)
+}
+
+/// Parses a binding occurrence of a name, or emits an error.
+fn expect_name(p: &mut Parser, kind: SyntaxKind) -> bool {
+    if p.at(kind) {
+        name(p);
+        true
+    } else {
+        p.expect(kind);
+        false
+    }
+}
+
+/// Parses a binding occurrence of an operator, or emits an error.
+fn expect_operator_name(p: &mut Parser) -> bool {
+    if p.at_operator() {
+        name(p);
+        true
+    } else {
+        p.expect(SyntaxKind::Operator);
+        false
+    }
+}
+
+/// Determines if a token can be used as a record label.
+pub(crate) fn is_label(kind: SyntaxKind) -> bool {
+    use SyntaxKind::*;
+    matches!(
+        kind,
+        Lower
+            | LiteralString
+            | LiteralRawString
+            | LiteralTrue
+            | LiteralFalse
+            | AdoKw
+            | AsKw
+            | CaseKw
+            | ClassKw
+            | DataKw
+            | DeriveKw
+            | DoKw
+            | ElseKw
+            | ForallKw
+            | ForeignKw
+            | HidingKw
+            | IfKw
+            | ImportKw
+            | InKw
+            | InfixKw
+            | InfixlKw
+            | InfixrKw
+            | InstanceKw
+            | LetKw
+            | ModuleKw
+            | NewtypeKw
+            | OfKw
+            | ThenKw
+            | TypeKw
+            | WhereKw
+    )
+}
+
+fn label(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    marker.complete(p, SyntaxKind::Label)
+}
+
+/// Parses a module name e.g. `Data.Maybe`.
+fn module_name(p: &mut Parser) -> bool {
+    if !p.at(SyntaxKind::Upper) {
+        p.error("expected a module name");
+        return false;
+    }
+    let marker = p.start();
+    p.bump();
+    while p.nth(0) == SyntaxKind::Period
+        && p.nth(1) == SyntaxKind::Upper
+        && p.nth_joint(0)
+        && p.nth_joint(1)
+    {
+        p.bump();
+        p.bump();
+    }
+    marker.complete(p, SyntaxKind::ModuleName);
+    true
+}
+
+/// Parses a possibly-qualified name e.g. `Data.Maybe.Just` or `M.fromMaybe`.
+///
+/// The current token must be the first segment of the name, and qualified
+/// names must not contain any trivia between segments.
+fn qualified_name(p: &mut Parser) -> CompletedMarker {
+    let segments = qualified_segments(p);
+    let marker = p.start();
+    if segments > 0 {
+        let module_name = p.start();
+        for segment in 0..segments {
+            if segment > 0 {
+                p.bump();
+            }
+            p.bump();
+        }
+        module_name.complete(p, SyntaxKind::ModuleName);
+        p.bump();
+    }
+    p.bump();
+    marker.complete(p, SyntaxKind::QualifiedName)
+}
+
+/// Returns the number of module name segments in a qualified name.
+fn qualified_segments(p: &Parser) -> usize {
+    let mut segments = 0;
+    loop {
+        let offset = segments * 2;
+        let qualifies = p.nth(offset) == SyntaxKind::Upper
+            && p.nth(offset + 1) == SyntaxKind::Period
+            && p.nth_joint(offset + 1)
+            && p.nth_joint(offset + 2)
+            && matches!(p.nth(offset + 2), SyntaxKind::Upper | SyntaxKind::Lower);
+        if !qualifies {
+            break segments;
+        }
+        segments += 1;
+    }
+}
+
+/// Returns the kind of the final segment in a qualified name.
+fn qualified_name_end(p: &Parser) -> SyntaxKind {
+    p.nth(qualified_segments(p) * 2)
+}
+
+/// Parses a qualified name wrapping an operator token.
+fn qualified_operator(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    marker.complete(p, SyntaxKind::QualifiedName)
+}
@@ -0,0 +1,143 @@
+use syntax::SyntaxKind;
+
+use super::{is_label, label, name, qualified_name, qualified_operator, types};
+use crate::parser::{CompletedMarker, Parser};
+
+/// Parses `binder :: Type`.
+pub(crate) fn binder(p: &mut Parser) -> Option<CompletedMarker> {
+    let binder = binder_operator_chain(p)?;
+    if p.at(SyntaxKind::Colon2) {
+        let marker = binder.precede(p);
+        p.bump();
+        if types::type_(p).is_none() {
+            p.error("expected a type");
+        }
+        return Some(marker.complete(p, SyntaxKind::BinderTyped));
+    }
+    Some(binder)
+}
+
+pub(crate) fn expect_binder(p: &mut Parser) {
+    if binder(p).is_none() {
+        p.error_recover("expected a binder");
+    }
+}
+
+/// Parses `x : xs`, to be re-associated once fixity information is available.
+fn binder_operator_chain(p: &mut Parser) -> Option<CompletedMarker> {
+    let first = binder_constructor(p)?;
+    if !p.at_operator() {
+        return Some(first);
+    }
+    let marker = first.precede(p);
+    while p.at_operator() {
+        qualified_operator(p);
+        if binder_constructor(p).is_none() {
+            p.error("expected a binder");
+            break;
+        }
+    }
+    Some(marker.complete(p, SyntaxKind::BinderOperatorChain))
+}
+
+/// Parses `Just x`, or negative literals such as `-1`.
+fn binder_constructor(p: &mut Parser) -> Option<CompletedMarker> {
+    if p.at(SyntaxKind::Minus)
+        && matches!(p.nth(1), SyntaxKind::LiteralInteger | SyntaxKind::LiteralNumber)
+    {
+        let marker = p.start();
+        p.bump();
+        p.bump();
+        return Some(marker.complete(p, SyntaxKind::BinderNegative));
+    }
+    if p.at(SyntaxKind::Upper) {
+        let marker = p.start();
+        qualified_name(p);
+        while at_binder_atom(p) {
+            binder_atom(p);
+        }
+        return Some(marker.complete(p, SyntaxKind::BinderConstructor));
+    }
+    if at_binder_atom(p) {
+        return Some(binder_atom(p));
+    }
+    None
+}
+
+pub(crate) fn at_binder_atom(p: &Parser) -> bool {
+    p.at_any(&[
+        SyntaxKind::Underscore,
+        SyntaxKind::Lower,
+        SyntaxKind::Upper,
+        SyntaxKind::LiteralInteger,
+        SyntaxKind::LiteralNumber,
+        SyntaxKind::LiteralString,
+        SyntaxKind::LiteralRawString,
+        SyntaxKind::LiteralChar,
+        SyntaxKind::LiteralTrue,
+        SyntaxKind::LiteralFalse,
+        SyntaxKind::LeftBrace,
+        SyntaxKind::LeftBracket,
+        SyntaxKind::LeftParenthesis,
+    ])
+}
+
+pub(crate) fn binder_atom(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    let kind = match p.current() {
+        SyntaxKind::Underscore => {
+            p.bump();
+            SyntaxKind::BinderWildcard
+        }
+        SyntaxKind::Lower if p.nth(1) == SyntaxKind::At => {
+            name(p);
+            p.bump();
+            if at_binder_atom(p) {
+                binder_atom(p);
+            } else {
+                p.error("expected a binder");
+            }
+            SyntaxKind::BinderNamed
+        }
+        SyntaxKind::Lower => {
+            name(p);
+            SyntaxKind::BinderVariable
+        }
+        SyntaxKind::Upper => {
+            qualified_name(p);
+            SyntaxKind::BinderConstructor
+        }
+        SyntaxKind::LeftBrace => {
+            p.separated(SyntaxKind::LeftBrace, SyntaxKind::RightBrace, expect_binder);
+            SyntaxKind::BinderArray
+        }
+        SyntaxKind::LeftBracket => {
+            p.separated(SyntaxKind::LeftBracket, SyntaxKind::RightBracket, record_field);
+            SyntaxKind::BinderRecord
+        }
+        SyntaxKind::LeftParenthesis => {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, expect_binder);
+            SyntaxKind::BinderParenthesized
+        }
+        _ => {
+            p.bump();
+            SyntaxKind::BinderLiteral
+        }
+    };
+    marker.complete(p, kind)
+}
+
+fn record_field(p: &mut Parser) {
+    if !is_label(p.current()) {
+        p.error_recover("expected a record field");
+        return;
+    }
+    let marker = p.start();
+    label(p);
+    if p.eat(SyntaxKind::Colon) {
+        expect_binder(p);
+        marker.complete(p, SyntaxKind::RecordField);
+    } else {
+        marker.complete(p, SyntaxKind::RecordPun);
+    }
+}
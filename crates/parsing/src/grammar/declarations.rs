@@ -0,0 +1,275 @@
+use syntax::SyntaxKind;
+
+use super::{
+    binders, expect_name, expect_operator_name, expressions, name, name_ref, qualified_name, types,
+};
+use crate::parser::Parser;
+
+pub(crate) fn declaration(p: &mut Parser) {
+    match p.current() {
+        SyntaxKind::DataKw | SyntaxKind::NewtypeKw | SyntaxKind::TypeKw | SyntaxKind::ClassKw
+            if p.nth(1) == SyntaxKind::Upper && p.nth(2) == SyntaxKind::Colon2 =>
+        {
+            kind_signature_declaration(p)
+        }
+        SyntaxKind::DataKw => data_declaration(p),
+        SyntaxKind::NewtypeKw => newtype_declaration(p),
+        SyntaxKind::TypeKw => type_declaration(p),
+        SyntaxKind::ClassKw => class_declaration(p),
+        SyntaxKind::InstanceKw => instance_chain(p),
+        SyntaxKind::DeriveKw => derive_declaration(p),
+        SyntaxKind::ForeignKw => foreign_declaration(p),
+        SyntaxKind::InfixlKw | SyntaxKind::InfixrKw | SyntaxKind::InfixKw => fixity_declaration(p),
+        SyntaxKind::Lower if p.nth(1) == SyntaxKind::Colon2 => annotation_declaration(p),
+        SyntaxKind::Lower => value_declaration(p),
+        _ => {
+            p.error("expected a declaration");
+            let error = p.start();
+            while !p.at_layout_boundary() {
+                p.bump();
+            }
+            error.complete(p, SyntaxKind::Error);
+        }
+    }
+}
+
+/// Parses `name :: Type`.
+pub(crate) fn annotation_declaration(p: &mut Parser) {
+    let marker = p.start();
+    name(p);
+    p.expect(SyntaxKind::Colon2);
+    expect_type(p);
+    marker.complete(p, SyntaxKind::AnnotationDeclaration);
+}
+
+/// Parses `name binders = expression`, including guards and `where`.
+pub(crate) fn value_declaration(p: &mut Parser) {
+    let marker = p.start();
+    name(p);
+    while binders::at_binder_atom(p) {
+        binders::binder_atom(p);
+    }
+    if p.skips_bodies() {
+        // The body is indented past the declaration, so it ends where the
+        // next declaration starts.
+        let body = p.start();
+        while !p.at_layout_boundary() {
+            p.bump();
+        }
+        body.complete(p, SyntaxKind::SkippedBody);
+    } else {
+        expressions::guarded(p, SyntaxKind::Equal);
+    }
+    marker.complete(p, SyntaxKind::ValueDeclaration);
+}
+
+fn kind_signature_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.bump();
+    name(p);
+    p.expect(SyntaxKind::Colon2);
+    expect_type(p);
+    marker.complete(p, SyntaxKind::KindSignatureDeclaration);
+}
+
+fn data_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::DataKw);
+    expect_name(p, SyntaxKind::Upper);
+    type_variable_bindings(p);
+    if p.eat(SyntaxKind::Equal) {
+        loop {
+            data_constructor(p);
+            if !p.eat(SyntaxKind::Pipe) {
+                break;
+            }
+        }
+    }
+    marker.complete(p, SyntaxKind::DataDeclaration);
+}
+
+fn newtype_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::NewtypeKw);
+    expect_name(p, SyntaxKind::Upper);
+    type_variable_bindings(p);
+    p.expect(SyntaxKind::Equal);
+    data_constructor(p);
+    marker.complete(p, SyntaxKind::NewtypeDeclaration);
+}
+
+fn data_constructor(p: &mut Parser) {
+    let marker = p.start();
+    expect_name(p, SyntaxKind::Upper);
+    while types::at_type_atom(p) {
+        types::type_atom(p);
+    }
+    marker.complete(p, SyntaxKind::DataConstructor);
+}
+
+fn type_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::TypeKw);
+    expect_name(p, SyntaxKind::Upper);
+    type_variable_bindings(p);
+    p.expect(SyntaxKind::Equal);
+    expect_type(p);
+    marker.complete(p, SyntaxKind::TypeDeclaration);
+}
+
+fn class_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::ClassKw);
+
+    if p.scan_for(SyntaxKind::LeftThickArrow, &[SyntaxKind::WhereKw, SyntaxKind::Pipe]) {
+        constraints(p, SyntaxKind::LeftThickArrow);
+    }
+
+    expect_name(p, SyntaxKind::Upper);
+    type_variable_bindings(p);
+
+    if p.at(SyntaxKind::Pipe) {
+        let dependencies = p.start();
+        p.bump();
+        loop {
+            let dependency = p.start();
+            while p.at(SyntaxKind::Lower) {
+                name_ref(p);
+            }
+            p.expect(SyntaxKind::RightArrow);
+            while p.at(SyntaxKind::Lower) {
+                name_ref(p);
+            }
+            dependency.complete(p, SyntaxKind::FunctionalDependency);
+            if !p.eat(SyntaxKind::Comma) {
+                break;
+            }
+        }
+        dependencies.complete(p, SyntaxKind::FunctionalDependencies);
+    }
+
+    if p.eat(SyntaxKind::WhereKw) {
+        p.layout_block(|p| {
+            let member = p.start();
+            if expect_name(p, SyntaxKind::Lower) {
+                p.expect(SyntaxKind::Colon2);
+                expect_type(p);
+            }
+            member.complete(p, SyntaxKind::ClassMember);
+        });
+    }
+
+    marker.complete(p, SyntaxKind::ClassDeclaration);
+}
+
+/// Parses superclass or instance constraints followed by `arrow`.
+fn constraints(p: &mut Parser, arrow: SyntaxKind) {
+    let marker = p.start();
+    if p.at(SyntaxKind::LeftParenthesis) && types::at_parenthesized_tuple(p) {
+        p.separated(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, expect_type);
+    } else {
+        types::type_application(p);
+    }
+    p.expect(arrow);
+    marker.complete(p, SyntaxKind::Constraints);
+}
+
+fn instance_chain(p: &mut Parser) {
+    let marker = p.start();
+    instance_declaration(p);
+    while p.eat_across_separator(SyntaxKind::ElseKw) {
+        instance_declaration(p);
+    }
+    marker.complete(p, SyntaxKind::InstanceChain);
+}
+
+fn instance_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::InstanceKw);
+    instance_head(p);
+    if p.eat(SyntaxKind::WhereKw) {
+        p.layout_block(|p| match p.current() {
+            SyntaxKind::Lower if p.nth(1) == SyntaxKind::Colon2 => annotation_declaration(p),
+            SyntaxKind::Lower => value_declaration(p),
+            _ => p.error_recover("expected an instance member"),
+        });
+    }
+    marker.complete(p, SyntaxKind::InstanceDeclaration);
+}
+
+/// Parses `name :: Constraints => Class Arguments`.
+fn instance_head(p: &mut Parser) {
+    if p.at(SyntaxKind::Lower) && p.nth(1) == SyntaxKind::Colon2 {
+        let instance_name = p.start();
+        name(p);
+        p.bump();
+        instance_name.complete(p, SyntaxKind::InstanceName);
+    }
+
+    let marker = p.start();
+    if p.scan_for(SyntaxKind::RightThickArrow, &[SyntaxKind::WhereKw, SyntaxKind::Equal]) {
+        constraints(p, SyntaxKind::RightThickArrow);
+    }
+    if p.at(SyntaxKind::Upper) {
+        qualified_name(p);
+    } else {
+        p.error("expected a class name");
+    }
+    while types::at_type_atom(p) {
+        types::type_atom(p);
+    }
+    marker.complete(p, SyntaxKind::InstanceHead);
+}
+
+fn derive_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::DeriveKw);
+    p.eat(SyntaxKind::NewtypeKw);
+    p.expect(SyntaxKind::InstanceKw);
+    instance_head(p);
+    marker.complete(p, SyntaxKind::DeriveInstanceDeclaration);
+}
+
+fn foreign_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::ForeignKw);
+    p.expect(SyntaxKind::ImportKw);
+    let kind = if p.eat(SyntaxKind::DataKw) {
+        expect_name(p, SyntaxKind::Upper);
+        SyntaxKind::ForeignDataDeclaration
+    } else {
+        expect_name(p, SyntaxKind::Lower);
+        SyntaxKind::ForeignValueDeclaration
+    };
+    p.expect(SyntaxKind::Colon2);
+    expect_type(p);
+    marker.complete(p, kind);
+}
+
+/// Parses `infixl 4 map as <$>`.
+fn fixity_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.bump();
+    p.expect(SyntaxKind::LiteralInteger);
+    p.eat(SyntaxKind::TypeKw);
+    if p.at(SyntaxKind::Upper) || p.at(SyntaxKind::Lower) {
+        qualified_name(p);
+    } else {
+        p.error("expected a name");
+    }
+    p.expect(SyntaxKind::AsKw);
+    expect_operator_name(p);
+    marker.complete(p, SyntaxKind::FixityDeclaration);
+}
+
+fn type_variable_bindings(p: &mut Parser) {
+    while types::at_type_variable_binding(p) {
+        types::type_variable_binding(p);
+    }
+}
+
+fn expect_type(p: &mut Parser) {
+    if types::type_(p).is_none() {
+        p.error("expected a type");
+    }
+}
@@ -0,0 +1,488 @@
+use syntax::SyntaxKind;
+
+use super::{
+    binders, is_label, label, name, qualified_name, qualified_name_end, qualified_operator, types,
+};
+use crate::parser::{CompletedMarker, Parser};
+
+/// Parses `expression :: Type`.
+pub(crate) fn expression(p: &mut Parser) -> Option<CompletedMarker> {
+    let expression = expression_operator_chain(p)?;
+    if p.at(SyntaxKind::Colon2) {
+        let marker = expression.precede(p);
+        p.bump();
+        if types::type_(p).is_none() {
+            p.error("expected a type");
+        }
+        return Some(marker.complete(p, SyntaxKind::ExpressionTyped));
+    }
+    Some(expression)
+}
+
+fn expect_expression(p: &mut Parser) {
+    if expression(p).is_none() {
+        p.error("expected an expression");
+    }
+}
+
+/// Parses `a + b * c` as a flat chain, to be re-associated once fixity
+/// information is available.
+fn expression_operator_chain(p: &mut Parser) -> Option<CompletedMarker> {
+    let first = expression_infix_chain(p)?;
+    if !p.at_operator() {
+        return Some(first);
+    }
+    let marker = first.precede(p);
+    while p.at_operator() {
+        qualified_operator(p);
+        if expression_infix_chain(p).is_none() {
+            p.error("expected an expression");
+            break;
+        }
+    }
+    Some(marker.complete(p, SyntaxKind::ExpressionOperatorChain))
+}
+
+/// Parses ``a `div` b``.
+fn expression_infix_chain(p: &mut Parser) -> Option<CompletedMarker> {
+    let first = expression_negate(p)?;
+    if !p.at(SyntaxKind::Backtick) {
+        return Some(first);
+    }
+    let marker = first.precede(p);
+    while p.at(SyntaxKind::Backtick) {
+        let tick = p.start();
+        p.bump();
+        if expression_negate(p).is_none() {
+            p.error("expected an expression");
+        }
+        p.expect(SyntaxKind::Backtick);
+        tick.complete(p, SyntaxKind::ExpressionTick);
+        if expression_negate(p).is_none() {
+            p.error("expected an expression");
+            break;
+        }
+    }
+    Some(marker.complete(p, SyntaxKind::ExpressionInfixChain))
+}
+
+fn expression_negate(p: &mut Parser) -> Option<CompletedMarker> {
+    if p.at(SyntaxKind::Minus) {
+        let marker = p.start();
+        p.bump();
+        if expression_negate(p).is_none() {
+            p.error("expected an expression");
+        }
+        return Some(marker.complete(p, SyntaxKind::ExpressionNegate));
+    }
+    expression_application(p)
+}
+
+fn expression_application(p: &mut Parser) -> Option<CompletedMarker> {
+    let head = expression_block(p)?;
+    if !at_argument(p) {
+        return Some(head);
+    }
+    let marker = head.precede(p);
+    while at_argument(p) {
+        if p.at(SyntaxKind::At) {
+            type_argument(p);
+        } else {
+            expression_block(p);
+        }
+    }
+    Some(marker.complete(p, SyntaxKind::ExpressionApplication))
+}
+
+/// Parses a visible type argument, e.g. `@Int`.
+fn type_argument(p: &mut Parser) {
+    let marker = p.start();
+    p.bump();
+    if types::at_type_atom(p) {
+        types::type_atom(p);
+    } else {
+        p.error("expected a type");
+    }
+    marker.complete(p, SyntaxKind::ExpressionTypeArgument);
+}
+
+fn at_argument(p: &Parser) -> bool {
+    at_expression_atom(p)
+        || p.at_any(&[
+            SyntaxKind::At,
+            SyntaxKind::IfKw,
+            SyntaxKind::LetKw,
+            SyntaxKind::Backslash,
+            SyntaxKind::CaseKw,
+            SyntaxKind::DoKw,
+            SyntaxKind::AdoKw,
+        ])
+}
+
+fn expression_block(p: &mut Parser) -> Option<CompletedMarker> {
+    match p.current() {
+        SyntaxKind::IfKw => Some(if_then_else(p)),
+        SyntaxKind::LetKw => Some(let_in(p)),
+        SyntaxKind::Backslash => Some(lambda(p)),
+        SyntaxKind::CaseKw => Some(case_of(p)),
+        SyntaxKind::DoKw => Some(do_block(p)),
+        SyntaxKind::AdoKw => Some(ado_block(p)),
+        _ => expression_record_update(p),
+    }
+}
+
+fn if_then_else(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    expect_expression(p);
+    if p.eat_across_separator(SyntaxKind::ThenKw) {
+        expect_expression(p);
+    } else {
+        p.expect(SyntaxKind::ThenKw);
+    }
+    if p.eat_across_separator(SyntaxKind::ElseKw) {
+        expect_expression(p);
+    } else {
+        p.expect(SyntaxKind::ElseKw);
+    }
+    marker.complete(p, SyntaxKind::ExpressionIfThenElse)
+}
+
+fn let_in(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    p.layout_block(let_binding);
+    if p.eat_across_separator(SyntaxKind::InKw) {
+        expect_expression(p);
+    } else {
+        p.expect(SyntaxKind::InKw);
+    }
+    marker.complete(p, SyntaxKind::ExpressionLetIn)
+}
+
+fn lambda(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    if !binders::at_binder_atom(p) {
+        p.error("expected a binder");
+    }
+    while binders::at_binder_atom(p) {
+        binders::binder_atom(p);
+    }
+    p.expect(SyntaxKind::RightArrow);
+    expect_expression(p);
+    marker.complete(p, SyntaxKind::ExpressionLambda)
+}
+
+fn case_of(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    loop {
+        expect_expression(p);
+        if !p.eat(SyntaxKind::Comma) {
+            break;
+        }
+    }
+    p.expect(SyntaxKind::OfKw);
+    p.layout_block(|p| {
+        let branch = p.start();
+        loop {
+            binders::expect_binder(p);
+            if !p.eat(SyntaxKind::Comma) {
+                break;
+            }
+        }
+        guarded(p, SyntaxKind::RightArrow);
+        branch.complete(p, SyntaxKind::CaseBranch);
+    });
+    marker.complete(p, SyntaxKind::ExpressionCase)
+}
+
+fn do_block(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    p.layout_block(do_statement);
+    marker.complete(p, SyntaxKind::ExpressionDo)
+}
+
+fn ado_block(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    p.bump();
+    if !p.at(SyntaxKind::InKw) {
+        p.layout_block(do_statement);
+    }
+    if p.eat_across_separator(SyntaxKind::InKw) {
+        expect_expression(p);
+    } else {
+        p.expect(SyntaxKind::InKw);
+    }
+    marker.complete(p, SyntaxKind::ExpressionAdo)
+}
+
+/// Tokens that cannot appear in the binder of a do statement, used to stop
+/// scanning for `<-` early.
+const BIND_STOP: &[SyntaxKind] = &[
+    SyntaxKind::DoKw,
+    SyntaxKind::AdoKw,
+    SyntaxKind::CaseKw,
+    SyntaxKind::OfKw,
+    SyntaxKind::LetKw,
+    SyntaxKind::WhereKw,
+    SyntaxKind::IfKw,
+    SyntaxKind::Backslash,
+    SyntaxKind::Equal,
+];
+
+fn do_statement(p: &mut Parser) {
+    let marker = p.start();
+    if p.at(SyntaxKind::LetKw) && !p.scan_for(SyntaxKind::InKw, &[]) {
+        p.bump();
+        p.layout_block(let_binding);
+        marker.complete(p, SyntaxKind::DoLet);
+    } else if p.scan_for(SyntaxKind::LeftArrow, BIND_STOP) {
+        binders::expect_binder(p);
+        p.expect(SyntaxKind::LeftArrow);
+        expect_expression(p);
+        marker.complete(p, SyntaxKind::DoBind);
+    } else if expression(p).is_some() {
+        marker.complete(p, SyntaxKind::DoDiscard);
+    } else {
+        marker.abandon(p);
+        p.error_recover("expected a statement");
+    }
+}
+
+/// Parses a binding in `let` or `where`.
+pub(crate) fn let_binding(p: &mut Parser) {
+    match p.current() {
+        SyntaxKind::Lower if p.nth(1) == SyntaxKind::Colon2 => {
+            let marker = p.start();
+            name(p);
+            p.bump();
+            if types::type_(p).is_none() {
+                p.error("expected a type");
+            }
+            marker.complete(p, SyntaxKind::LetBindingSignature);
+        }
+        SyntaxKind::Lower if p.nth(1) != SyntaxKind::At => {
+            let marker = p.start();
+            name(p);
+            while binders::at_binder_atom(p) {
+                binders::binder_atom(p);
+            }
+            guarded(p, SyntaxKind::Equal);
+            marker.complete(p, SyntaxKind::LetBindingName);
+        }
+        _ if binders::at_binder_atom(p) => {
+            let marker = p.start();
+            binders::expect_binder(p);
+            p.expect(SyntaxKind::Equal);
+            expect_expression(p);
+            where_clause(p);
+            marker.complete(p, SyntaxKind::LetBindingPattern);
+        }
+        _ => p.error_recover("expected a binding"),
+    }
+}
+
+/// Parses the right-hand side of a declaration or case branch, where
+/// `separator` is either `=` or `->`.
+pub(crate) fn guarded(p: &mut Parser, separator: SyntaxKind) {
+    if p.at(SyntaxKind::Pipe) {
+        let marker = p.start();
+        while p.at(SyntaxKind::Pipe) {
+            let branch = p.start();
+            p.bump();
+            loop {
+                pattern_guard(p, separator);
+                if !p.eat(SyntaxKind::Comma) {
+                    break;
+                }
+            }
+            p.expect(separator);
+            expect_expression(p);
+            branch.complete(p, SyntaxKind::GuardedBranch);
+        }
+        where_clause(p);
+        marker.complete(p, SyntaxKind::Guarded);
+    } else {
+        let marker = p.start();
+        p.expect(separator);
+        expect_expression(p);
+        where_clause(p);
+        marker.complete(p, SyntaxKind::Unconditional);
+    }
+}
+
+fn pattern_guard(p: &mut Parser, separator: SyntaxKind) {
+    let marker = p.start();
+    if p.scan_for(SyntaxKind::LeftArrow, &[SyntaxKind::Comma, separator, SyntaxKind::Pipe]) {
+        binders::expect_binder(p);
+        p.expect(SyntaxKind::LeftArrow);
+    }
+    expect_expression(p);
+    marker.complete(p, SyntaxKind::PatternGuard);
+}
+
+fn where_clause(p: &mut Parser) {
+    if p.at(SyntaxKind::WhereKw) {
+        let marker = p.start();
+        p.bump();
+        p.layout_block(let_binding);
+        marker.complete(p, SyntaxKind::WhereClause);
+    }
+}
+
+/// Parses `record { field = value }`.
+fn expression_record_update(p: &mut Parser) -> Option<CompletedMarker> {
+    let mut expression = expression_record_access(p)?;
+    while at_record_update(p) {
+        let marker = expression.precede(p);
+        record_updates(p);
+        expression = marker.complete(p, SyntaxKind::ExpressionRecordUpdate);
+    }
+    Some(expression)
+}
+
+fn at_record_update(p: &Parser) -> bool {
+    p.at(SyntaxKind::LeftBracket)
+        && is_label(p.nth(1))
+        && matches!(p.nth(2), SyntaxKind::Equal | SyntaxKind::LeftBracket)
+}
+
+fn record_updates(p: &mut Parser) {
+    p.separated(SyntaxKind::LeftBracket, SyntaxKind::RightBracket, |p| {
+        if !is_label(p.current()) {
+            p.error_recover("expected a record update");
+            return;
+        }
+        let marker = p.start();
+        label(p);
+        if p.at(SyntaxKind::LeftBracket) {
+            record_updates(p);
+            marker.complete(p, SyntaxKind::RecordUpdateBranch);
+        } else {
+            p.expect(SyntaxKind::Equal);
+            expect_expression(p);
+            marker.complete(p, SyntaxKind::RecordUpdateLeaf);
+        }
+    });
+}
+
+/// Parses `record.field.field`. A period right after the record starts an
+/// access even without a label, as it does while one is being typed.
+fn expression_record_access(p: &mut Parser) -> Option<CompletedMarker> {
+    let atom = expression_atom(p)?;
+    let at_access = |p: &Parser| p.at(SyntaxKind::Period) && (is_label(p.nth(1)) || p.nth_joint(0));
+    if !at_access(p) {
+        return Some(atom);
+    }
+    let marker = atom.precede(p);
+    while at_access(p) {
+        p.bump();
+        // The next token may be a label on the next line, in another layout
+        // item.
+        if !is_label(p.current()) {
+            p.error("expected a label");
+            break;
+        }
+        label(p);
+    }
+    Some(marker.complete(p, SyntaxKind::ExpressionRecordAccess))
+}
+
+pub(crate) fn at_expression_atom(p: &Parser) -> bool {
+    p.at_any(&[
+        SyntaxKind::Lower,
+        SyntaxKind::Upper,
+        SyntaxKind::LiteralInteger,
+        SyntaxKind::LiteralNumber,
+        SyntaxKind::LiteralString,
+        SyntaxKind::LiteralRawString,
+        SyntaxKind::LiteralChar,
+        SyntaxKind::LiteralTrue,
+        SyntaxKind::LiteralFalse,
+        SyntaxKind::LeftBrace,
+        SyntaxKind::LeftBracket,
+        SyntaxKind::LeftParenthesis,
+        SyntaxKind::Hole,
+        SyntaxKind::Underscore,
+    ])
+}
+
+fn expression_atom(p: &mut Parser) -> Option<CompletedMarker> {
+    let marker = p.start();
+    let kind = match p.current() {
+        SyntaxKind::Lower | SyntaxKind::Upper => {
+            let is_constructor = qualified_name_end(p) == SyntaxKind::Upper;
+            qualified_name(p);
+            if is_constructor {
+                SyntaxKind::ExpressionConstructor
+            } else {
+                SyntaxKind::ExpressionVariable
+            }
+        }
+        SyntaxKind::LiteralInteger
+        | SyntaxKind::LiteralNumber
+        | SyntaxKind::LiteralString
+        | SyntaxKind::LiteralRawString
+        | SyntaxKind::LiteralChar
+        | SyntaxKind::LiteralTrue
+        | SyntaxKind::LiteralFalse => {
+            p.bump();
+            SyntaxKind::ExpressionLiteral
+        }
+        SyntaxKind::LeftBrace => {
+            p.separated(SyntaxKind::LeftBrace, SyntaxKind::RightBrace, expect_expression);
+            SyntaxKind::ExpressionArray
+        }
+        SyntaxKind::LeftBracket => {
+            p.separated(SyntaxKind::LeftBracket, SyntaxKind::RightBracket, record_field);
+            SyntaxKind::ExpressionRecord
+        }
+        SyntaxKind::LeftParenthesis
+            if p.nth(1).is_operator() && p.nth(2) == SyntaxKind::RightParenthesis =>
+        {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+                qualified_operator(p);
+            });
+            SyntaxKind::ExpressionOperatorName
+        }
+        SyntaxKind::LeftParenthesis => {
+            p.delimited(
+                SyntaxKind::LeftParenthesis,
+                SyntaxKind::RightParenthesis,
+                expect_expression,
+            );
+            SyntaxKind::ExpressionParenthesized
+        }
+        SyntaxKind::Hole => {
+            p.bump();
+            SyntaxKind::ExpressionHole
+        }
+        SyntaxKind::Underscore => {
+            p.bump();
+            SyntaxKind::ExpressionWildcard
+        }
+        _ => {
+            marker.abandon(p);
+            return None;
+        }
+    };
+    Some(marker.complete(p, kind))
+}
+
+fn record_field(p: &mut Parser) {
+    if !is_label(p.current()) {
+        p.error_recover("expected a record field");
+        return;
+    }
+    let marker = p.start();
+    label(p);
+    if p.eat(SyntaxKind::Colon) {
+        expect_expression(p);
+        marker.complete(p, SyntaxKind::RecordField);
+    } else {
+        marker.complete(p, SyntaxKind::RecordPun);
+    }
+}
@@ -0,0 +1,222 @@
+use syntax::SyntaxKind;
+
+use super::{declarations, module_name, name_ref};
+use crate::parser::Parser;
+
+pub(crate) fn module(p: &mut Parser) {
+    let module = p.start();
+
+    let header = p.start();
+    if p.at(SyntaxKind::ModuleKw) {
+        p.bump();
+        module_name(p);
+        if p.at(SyntaxKind::LeftParenthesis) {
+            export_list(p);
+        }
+        p.expect(SyntaxKind::WhereKw);
+    } else {
+        p.error("expected a module header");
+    }
+
+    let block = p.layout_start();
+    let mut more = block;
+    if block {
+        while p.at(SyntaxKind::ImportKw) {
+            import_declaration(p);
+            if !item_end(p) {
+                more = false;
+                break;
+            }
+        }
+    }
+    header.complete(p, SyntaxKind::ModuleHeader);
+
+    while more && !p.at_any(&[SyntaxKind::LayoutEnd, SyntaxKind::EndOfFile]) {
+        if p.at(SyntaxKind::ImportKw) {
+            p.error("imports must appear before declarations");
+            import_declaration(p);
+        } else {
+            declarations::declaration(p);
+        }
+        more = item_end(p);
+    }
+
+    if block {
+        p.layout_end();
+    }
+
+    if !p.at_eof() {
+        p.error("unexpected token");
+        let error = p.start();
+        while !p.at_eof() {
+            p.bump();
+        }
+        error.complete(p, SyntaxKind::Error);
+    }
+
+    module.complete(p, SyntaxKind::Module);
+}
+
+/// Consumes the end of a top-level item, wrapping any leftover tokens in
+/// an error node. Returns `false` if there are no more items.
+fn item_end(p: &mut Parser) -> bool {
+    if p.layout_separator() {
+        return true;
+    }
+    if p.at_any(&[SyntaxKind::LayoutEnd, SyntaxKind::EndOfFile]) {
+        return false;
+    }
+    p.error("unexpected token");
+    let error = p.start();
+    while !p.at_layout_boundary() {
+        p.bump();
+    }
+    error.complete(p, SyntaxKind::Error);
+    p.layout_separator()
+}
+
+fn export_list(p: &mut Parser) {
+    let marker = p.start();
+    p.separated(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, export_item);
+    marker.complete(p, SyntaxKind::ExportList);
+}
+
+fn export_item(p: &mut Parser) {
+    let marker = p.start();
+    let kind = match p.current() {
+        SyntaxKind::Lower => {
+            name_ref(p);
+            SyntaxKind::ExportValue
+        }
+        SyntaxKind::LeftParenthesis => {
+            operator_ref(p);
+            SyntaxKind::ExportOperator
+        }
+        SyntaxKind::Upper => {
+            name_ref(p);
+            if p.at(SyntaxKind::LeftParenthesis) {
+                data_members(p);
+            }
+            SyntaxKind::ExportType
+        }
+        SyntaxKind::TypeKw => {
+            p.bump();
+            operator_ref(p);
+            SyntaxKind::ExportTypeOperator
+        }
+        SyntaxKind::ClassKw => {
+            p.bump();
+            if p.at(SyntaxKind::Upper) {
+                name_ref(p);
+            } else {
+                p.expect(SyntaxKind::Upper);
+            }
+            SyntaxKind::ExportClass
+        }
+        SyntaxKind::ModuleKw => {
+            p.bump();
+            module_name(p);
+            SyntaxKind::ExportModule
+        }
+        _ => {
+            marker.abandon(p);
+            p.error_recover("expected an export item");
+            return;
+        }
+    };
+    marker.complete(p, kind);
+}
+
+fn import_declaration(p: &mut Parser) {
+    let marker = p.start();
+    p.expect(SyntaxKind::ImportKw);
+    module_name(p);
+
+    if p.at(SyntaxKind::LeftParenthesis) || p.at(SyntaxKind::HidingKw) {
+        let list = p.start();
+        p.eat(SyntaxKind::HidingKw);
+        p.separated(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, import_item);
+        list.complete(p, SyntaxKind::ImportList);
+    }
+
+    if p.at(SyntaxKind::AsKw) {
+        let alias = p.start();
+        p.bump();
+        module_name(p);
+        alias.complete(p, SyntaxKind::ImportAlias);
+    }
+
+    marker.complete(p, SyntaxKind::ImportDeclaration);
+}
+
+fn import_item(p: &mut Parser) {
+    let marker = p.start();
+    let kind = match p.current() {
+        SyntaxKind::Lower => {
+            name_ref(p);
+            SyntaxKind::ImportValue
+        }
+        SyntaxKind::LeftParenthesis => {
+            operator_ref(p);
+            SyntaxKind::ImportOperator
+        }
+        SyntaxKind::Upper => {
+            name_ref(p);
+            if p.at(SyntaxKind::LeftParenthesis) {
+                data_members(p);
+            }
+            SyntaxKind::ImportType
+        }
+        SyntaxKind::TypeKw => {
+            p.bump();
+            operator_ref(p);
+            SyntaxKind::ImportTypeOperator
+        }
+        SyntaxKind::ClassKw => {
+            p.bump();
+            if p.at(SyntaxKind::Upper) {
+                name_ref(p);
+            } else {
+                p.expect(SyntaxKind::Upper);
+            }
+            SyntaxKind::ImportClass
+        }
+        _ => {
+            marker.abandon(p);
+            p.error_recover("expected an import item");
+            return;
+        }
+    };
+    marker.complete(p, kind);
+}
+
+/// Parses `(+)` in import and export lists.
+fn operator_ref(p: &mut Parser) {
+    p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+        if p.at_operator() {
+            name_ref(p);
+        } else {
+            p.error("expected an operator");
+        }
+    });
+}
+
+/// Parses `(..)` or `(A, B)` after a type name.
+fn data_members(p: &mut Parser) {
+    let marker = p.start();
+    if p.nth(1) == SyntaxKind::Period2 {
+        p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+            p.bump();
+        });
+        marker.complete(p, SyntaxKind::DataAll);
+    } else {
+        p.separated(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+            if p.at(SyntaxKind::Upper) {
+                name_ref(p);
+            } else {
+                p.error_recover("expected a constructor name");
+            }
+        });
+        marker.complete(p, SyntaxKind::DataEnumerated);
+    }
+}
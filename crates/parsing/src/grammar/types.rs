@@ -0,0 +1,259 @@
+use syntax::SyntaxKind;
+
+use super::{is_label, label, name, name_ref, qualified_name, qualified_operator};
+use crate::parser::{CompletedMarker, Parser};
+
+/// Parses `Type :: Kind`.
+pub(crate) fn type_(p: &mut Parser) -> Option<CompletedMarker> {
+    let type_ = type_forall(p)?;
+    if p.at(SyntaxKind::Colon2) {
+        let marker = type_.precede(p);
+        p.bump();
+        expect_type(p);
+        return Some(marker.complete(p, SyntaxKind::TypeKinded));
+    }
+    Some(type_)
+}
+
+fn expect_type(p: &mut Parser) {
+    if type_(p).is_none() {
+        p.error("expected a type");
+    }
+}
+
+fn type_forall(p: &mut Parser) -> Option<CompletedMarker> {
+    if p.at(SyntaxKind::ForallKw) {
+        let marker = p.start();
+        p.bump();
+        if !at_forall_variable(p) {
+            p.error("expected a type variable");
+        }
+        while at_forall_variable(p) {
+            type_variable_binding(p);
+        }
+        p.expect(SyntaxKind::Period);
+        if type_forall(p).is_none() {
+            p.error("expected a type");
+        }
+        return Some(marker.complete(p, SyntaxKind::TypeForall));
+    }
+    type_arrow(p)
+}
+
+/// Parses `a -> b` and `Constraint => a`, which are right-associative.
+fn type_arrow(p: &mut Parser) -> Option<CompletedMarker> {
+    let argument = type_operator_chain(p)?;
+    let kind = match p.current() {
+        SyntaxKind::RightArrow => SyntaxKind::TypeArrow,
+        SyntaxKind::RightThickArrow => SyntaxKind::TypeConstrained,
+        _ => return Some(argument),
+    };
+    let marker = argument.precede(p);
+    p.bump();
+    if type_forall(p).is_none() {
+        p.error("expected a type");
+    }
+    Some(marker.complete(p, kind))
+}
+
+/// Parses `a /\ b`, to be re-associated once fixity information is available.
+fn type_operator_chain(p: &mut Parser) -> Option<CompletedMarker> {
+    let first = type_application(p)?;
+    if !p.at_operator() {
+        return Some(first);
+    }
+    let marker = first.precede(p);
+    while p.at_operator() {
+        qualified_operator(p);
+        if type_application(p).is_none() {
+            p.error("expected a type");
+            break;
+        }
+    }
+    Some(marker.complete(p, SyntaxKind::TypeOperatorChain))
+}
+
+pub(crate) fn type_application(p: &mut Parser) -> Option<CompletedMarker> {
+    if !at_type_atom(p) {
+        return None;
+    }
+    let head = type_atom(p);
+    if !at_type_atom(p) {
+        return Some(head);
+    }
+    let marker = head.precede(p);
+    while at_type_atom(p) {
+        type_atom(p);
+    }
+    Some(marker.complete(p, SyntaxKind::TypeApplication))
+}
+
+pub(crate) fn at_type_atom(p: &Parser) -> bool {
+    p.at_any(&[
+        SyntaxKind::Underscore,
+        SyntaxKind::Lower,
+        SyntaxKind::Upper,
+        SyntaxKind::LiteralString,
+        SyntaxKind::LiteralRawString,
+        SyntaxKind::LiteralInteger,
+        SyntaxKind::Hole,
+        SyntaxKind::LeftBracket,
+        SyntaxKind::LeftParenthesis,
+    ])
+}
+
+pub(crate) fn type_atom(p: &mut Parser) -> CompletedMarker {
+    let marker = p.start();
+    let kind = match p.current() {
+        SyntaxKind::Underscore => {
+            p.bump();
+            SyntaxKind::TypeWildcard
+        }
+        SyntaxKind::Lower => {
+            name_ref(p);
+            SyntaxKind::TypeVariable
+        }
+        SyntaxKind::Upper => {
+            qualified_name(p);
+            SyntaxKind::TypeConstructor
+        }
+        SyntaxKind::LiteralString | SyntaxKind::LiteralRawString => {
+            p.bump();
+            SyntaxKind::TypeString
+        }
+        SyntaxKind::LiteralInteger => {
+            p.bump();
+            SyntaxKind::TypeInteger
+        }
+        SyntaxKind::Hole => {
+            p.bump();
+            SyntaxKind::TypeHole
+        }
+        SyntaxKind::LeftBracket => {
+            p.delimited(SyntaxKind::LeftBracket, SyntaxKind::RightBracket, row);
+            SyntaxKind::TypeRecord
+        }
+        SyntaxKind::LeftParenthesis
+            if p.nth(1) == SyntaxKind::RightArrow && p.nth(2) == SyntaxKind::RightParenthesis =>
+        {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| p.bump());
+            SyntaxKind::TypeArrowName
+        }
+        SyntaxKind::LeftParenthesis
+            if p.nth(1).is_operator() && p.nth(2) == SyntaxKind::RightParenthesis =>
+        {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+                qualified_operator(p);
+            });
+            SyntaxKind::TypeOperatorName
+        }
+        SyntaxKind::LeftParenthesis if at_row(p) => {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, row);
+            SyntaxKind::TypeRow
+        }
+        SyntaxKind::LeftParenthesis if at_parenthesized_tuple(p) => {
+            p.separated(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, expect_type);
+            SyntaxKind::TypeConstraints
+        }
+        _ => {
+            p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, expect_type);
+            SyntaxKind::TypeParenthesized
+        }
+    };
+    marker.complete(p, kind)
+}
+
+/// Determines if `(` starts a row type, e.g. `(a :: Int | r)` or `()`.
+fn at_row(p: &Parser) -> bool {
+    match p.nth(1) {
+        SyntaxKind::RightParenthesis | SyntaxKind::Pipe => true,
+        kind => is_label(kind) && p.nth(2) == SyntaxKind::Colon2,
+    }
+}
+
+/// Determines if `(` starts a comma-separated sequence, e.g. `(Show a, Eq a)`.
+pub(crate) fn at_parenthesized_tuple(p: &Parser) -> bool {
+    let mut depth = 0usize;
+    let mut index = 1;
+    loop {
+        match p.nth(index) {
+            SyntaxKind::EndOfFile => return false,
+            SyntaxKind::LeftParenthesis | SyntaxKind::LeftBracket | SyntaxKind::LeftBrace => {
+                depth += 1
+            }
+            SyntaxKind::RightParenthesis | SyntaxKind::RightBracket | SyntaxKind::RightBrace => {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+            SyntaxKind::Comma if depth == 0 => return true,
+            _ => (),
+        }
+        index += 1;
+    }
+}
+
+/// Parses `label :: Type, label :: Type | tail`.
+fn row(p: &mut Parser) {
+    if is_label(p.current()) {
+        loop {
+            let marker = p.start();
+            label(p);
+            p.expect(SyntaxKind::Colon2);
+            expect_type(p);
+            marker.complete(p, SyntaxKind::RowField);
+            if !p.eat(SyntaxKind::Comma) {
+                break;
+            }
+            if !is_label(p.current()) {
+                p.error("expected a label");
+                break;
+            }
+        }
+    }
+    if p.at(SyntaxKind::Pipe) {
+        let marker = p.start();
+        p.bump();
+        expect_type(p);
+        marker.complete(p, SyntaxKind::RowTail);
+    }
+}
+
+pub(crate) fn at_type_variable_binding(p: &Parser) -> bool {
+    p.at(SyntaxKind::Lower)
+        || (p.at(SyntaxKind::LeftParenthesis)
+            && p.nth(1) == SyntaxKind::Lower
+            && p.nth(2) == SyntaxKind::Colon2)
+}
+
+/// The variables of a `forall` can be visible, e.g. `forall @a.`.
+fn at_forall_variable(p: &Parser) -> bool {
+    at_type_variable_binding(p) || p.at(SyntaxKind::At)
+}
+
+/// Parses `a` or `(a :: Kind)`, optionally preceded by `@` in a `forall`.
+pub(crate) fn type_variable_binding(p: &mut Parser) {
+    let marker = p.start();
+    p.eat(SyntaxKind::At);
+    if p.at(SyntaxKind::LeftParenthesis) {
+        p.delimited(SyntaxKind::LeftParenthesis, SyntaxKind::RightParenthesis, |p| {
+            type_variable_name(p);
+            p.expect(SyntaxKind::Colon2);
+            expect_type(p);
+        });
+    } else {
+        type_variable_name(p);
+    }
+    marker.complete(p, SyntaxKind::TypeVariableBinding);
+}
+
+/// Only a `@` is known to start a binding, so whatever follows it may not
+/// be a name, such as the `.` of `forall @.` or the end of the line.
+fn type_variable_name(p: &mut Parser) {
+    if p.at(SyntaxKind::Lower) {
+        name(p);
+    } else {
+        p.error("expected a type variable");
+    }
+}
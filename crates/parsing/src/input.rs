@@ -2,8 +2,86 @@
 
 use syntax::SyntaxKind;
 
-#[allow(dead_code)]
+use crate::lexer::Lexed;
+
+/// A sequence of significant [`SyntaxKind`]s alongside their column offsets.
+///
+/// Whitespace and comments are omitted such that the parser can peek into
+/// the next token without skipping trivia. The parser recovers the layout
+/// of the source through the columns and the `line_start` flag of each
+/// token, while `joint` flags are used to glue qualified names together.
 pub struct Input {
     tokens: Vec<SyntaxKind>,
     columns: Vec<u32>,
+    line_start: Vec<bool>,
+    joint: Vec<bool>,
+}
+
+impl Input {
+    /// Computes the [`Input`] for some [`Lexed`].
+    pub fn from_lexed(lexed: &Lexed) -> Input {
+        let source = lexed.source();
+
+        let mut tokens = vec![];
+        let mut columns = vec![];
+        let mut line_start = vec![];
+        let mut joint = vec![];
+
+        let mut line_offset = 0;
+        let mut saw_newline = true;
+        let mut saw_trivia = true;
+
+        for index in 0..lexed.len() {
+            let kind = lexed.kind(index);
+            let offset = lexed.offset(index) as usize;
+
+            if kind.is_trivia() {
+                let text = lexed.text(index);
+                if let Some(position) = text.rfind('\n') {
+                    line_offset = offset + position + 1;
+                    saw_newline = true;
+                }
+                saw_trivia = true;
+                continue;
+            }
+
+            tokens.push(kind);
+            columns.push(source[line_offset..offset].chars().count() as u32);
+            line_start.push(saw_newline);
+            joint.push(!saw_trivia);
+
+            saw_newline = false;
+            saw_trivia = false;
+        }
+
+        Input { tokens, columns, line_start, joint }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the kind for an index, or [`SyntaxKind::EndOfFile`].
+    pub fn kind(&self, index: usize) -> SyntaxKind {
+        self.tokens.get(index).copied().unwrap_or(SyntaxKind::EndOfFile)
+    }
+
+    /// Returns the column for an index.
+    pub fn column(&self, index: usize) -> u32 {
+        self.columns.get(index).copied().unwrap_or(0)
+    }
+
+    /// Determines if the token is the first on its line.
+    pub fn is_line_start(&self, index: usize) -> bool {
+        self.line_start.get(index).copied().unwrap_or(true)
+    }
+
+    /// Determines if the token immediately follows the previous one.
+    pub fn is_joint(&self, index: usize) -> bool {
+        self.joint.get(index).copied().unwrap_or(false)
+    }
 }
@@ -15,10 +15,11 @@ pub struct Lexed<'a> {
     errors: Vec<LexError>,
 }
 
-#[derive(Debug)]
-struct LexError {
-    message: String,
-    index: u32,
+/// An error emitted for a token by the lexer, e.g. an unterminated string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub message: String,
+    pub index: u32,
 }
 
 impl<'a> Lexed<'a> {
@@ -60,6 +61,29 @@ impl<'a> Lexed<'a> {
         self.kinds.len() - 1
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the source text that was lexed.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the starting offset for an index.
+    ///
+    /// Unlike [`Lexed::kind`], this accepts `self.len()` as an index, which
+    /// returns the offset for the end of the source.
+    pub fn offset(&self, index: usize) -> u32 {
+        assert!(index <= self.len());
+        self.offsets[index]
+    }
+
+    /// Returns the errors emitted while lexing.
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
     /// Returns the kind for an index.
     pub fn kind(&self, index: usize) -> SyntaxKind {
         assert!(index < self.len());
@@ -110,6 +134,13 @@ impl<'a> Lexer<'a> {
         chars.next().unwrap_or(EOF_CHAR)
     }
 
+    fn third(&self) -> char {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next();
+        chars.next().unwrap_or(EOF_CHAR)
+    }
+
     fn take(&mut self) -> char {
         self.chars.next().unwrap_or(EOF_CHAR)
     }
@@ -133,12 +164,17 @@ impl<'a> Lexer<'a> {
             '}' => self.take_single(SyntaxKind::RightBracket),
             '[' => self.take_single(SyntaxKind::LeftBrace),
             ']' => self.take_single(SyntaxKind::RightBrace),
+            ',' => self.take_single(SyntaxKind::Comma),
+            '`' => self.take_single(SyntaxKind::Backtick),
 
             '\'' => self.take_char(),
+            '"' if self.second() == '"' && self.third() == '"' => self.take_raw_string(),
             '"' => self.take_string(),
 
+            '?' if is_identifier_start(self.second()) => self.take_hole(),
+
             identifier => {
-                if identifier.is_letter_lowercase() {
+                if identifier.is_letter_lowercase() || identifier == '_' {
                     self.take_lower()
                 } else if identifier.is_letter_uppercase() {
                     self.take_upper()
@@ -149,7 +185,7 @@ impl<'a> Lexer<'a> {
                 } else if identifier.is_ascii_digit() {
                     self.take_integer_or_number()
                 } else {
-                    todo!("Unknown token!")
+                    self.take_unknown()
                 }
             }
         }
@@ -165,22 +201,34 @@ impl<'a> Lexer<'a> {
     #[inline]
     fn take_lower(&mut self) -> (SyntaxKind, usize, Option<&str>) {
         let offset = self.consumed();
-        self.take_while(|c| c.is_letter());
+        self.take_while(is_identifier_continue);
         let end_offset = self.consumed();
         let kind = match &self.source[offset..end_offset] {
+            "_" => SyntaxKind::Underscore,
+            "ado" => SyntaxKind::AdoKw,
             "as" => SyntaxKind::AsKw,
+            "case" => SyntaxKind::CaseKw,
             "class" => SyntaxKind::ClassKw,
             "data" => SyntaxKind::DataKw,
             "derive" => SyntaxKind::DeriveKw,
+            "do" => SyntaxKind::DoKw,
+            "else" => SyntaxKind::ElseKw,
             "false" => SyntaxKind::LiteralFalse,
+            "forall" => SyntaxKind::ForallKw,
             "foreign" => SyntaxKind::ForeignKw,
+            "hiding" => SyntaxKind::HidingKw,
+            "if" => SyntaxKind::IfKw,
             "import" => SyntaxKind::ImportKw,
+            "in" => SyntaxKind::InKw,
             "infix" => SyntaxKind::InfixKw,
             "infixl" => SyntaxKind::InfixlKw,
             "infixr" => SyntaxKind::InfixrKw,
             "instance" => SyntaxKind::InstanceKw,
+            "let" => SyntaxKind::LetKw,
             "module" => SyntaxKind::ModuleKw,
             "newtype" => SyntaxKind::NewtypeKw,
+            "of" => SyntaxKind::OfKw,
+            "then" => SyntaxKind::ThenKw,
             "true" => SyntaxKind::LiteralTrue,
             "type" => SyntaxKind::TypeKw,
             "where" => SyntaxKind::WhereKw,
@@ -192,7 +240,7 @@ impl<'a> Lexer<'a> {
     #[inline]
     fn take_upper(&mut self) -> (SyntaxKind, usize, Option<&str>) {
         let offset = self.consumed();
-        self.take_while(|c| c.is_letter());
+        self.take_while(is_identifier_continue);
         (SyntaxKind::Upper, offset, None)
     }
 
@@ -204,23 +252,39 @@ impl<'a> Lexer<'a> {
         let kind = match &self.source[offset..offset_end] {
             "=" => SyntaxKind::Equal,
             ":" => SyntaxKind::Colon,
-            "::" => SyntaxKind::Colon2,
+            "::" | "∷" => SyntaxKind::Colon2,
             "." => SyntaxKind::Period,
             ".." => SyntaxKind::Period2,
-            "<-" => SyntaxKind::LeftArrow,
-            "->" => SyntaxKind::RightArrow,
-            "<=" => SyntaxKind::LeftThickArrow,
-            "=>" => SyntaxKind::RightThickArrow,
+            "|" => SyntaxKind::Pipe,
+            "\\" => SyntaxKind::Backslash,
+            "@" => SyntaxKind::At,
+            "-" => SyntaxKind::Minus,
+            "<-" | "←" => SyntaxKind::LeftArrow,
+            "->" | "→" => SyntaxKind::RightArrow,
+            "<=" | "⇐" => SyntaxKind::LeftThickArrow,
+            "=>" | "⇒" => SyntaxKind::RightThickArrow,
+            "∀" => SyntaxKind::ForallKw,
             _ => SyntaxKind::Operator,
         };
         (kind, offset, None)
     }
 
+    #[inline]
+    fn take_hole(&mut self) -> (SyntaxKind, usize, Option<&str>) {
+        let offset = self.consumed();
+        assert_eq!(self.take(), '?');
+        self.take_while(is_identifier_continue);
+        (SyntaxKind::Hole, offset, None)
+    }
+
     #[inline]
     fn take_char(&mut self) -> (SyntaxKind, usize, Option<&str>) {
         let offset = self.consumed();
         assert_eq!(self.take(), '\'');
-        self.take();
+        if self.take() == '\\' {
+            self.take();
+            self.take_while(|c| c != '\'' && c != '\n');
+        }
         if self.first() == '\'' {
             self.take();
             (SyntaxKind::LiteralChar, offset, None)
@@ -233,19 +297,64 @@ impl<'a> Lexer<'a> {
     fn take_string(&mut self) -> (SyntaxKind, usize, Option<&str>) {
         let offset = self.consumed();
         assert_eq!(self.take(), '"');
-        self.take_while(|c| c != '"');
-        if self.first() == '"' {
+        loop {
+            match self.first() {
+                '"' => {
+                    self.take();
+                    break (SyntaxKind::LiteralString, offset, None);
+                }
+                '\\' => {
+                    self.take();
+                    self.take();
+                }
+                '\n' => break (SyntaxKind::Error, offset, Some("invalid string literal")),
+                _ if self.is_eof() => {
+                    break (SyntaxKind::Error, offset, Some("invalid string literal"))
+                }
+                _ => {
+                    self.take();
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn take_raw_string(&mut self) -> (SyntaxKind, usize, Option<&str>) {
+        let offset = self.consumed();
+        for _ in 0..3 {
+            assert_eq!(self.take(), '"');
+        }
+        loop {
+            if self.is_eof() {
+                break (SyntaxKind::Error, offset, Some("invalid raw string literal"));
+            }
+            if self.first() == '"' && self.second() == '"' && self.third() == '"' {
+                self.take();
+                self.take();
+                self.take();
+                // `""""a""""` => the closing delimiter absorbs extra quotes
+                self.take_while(|c| c == '"');
+                break (SyntaxKind::LiteralRawString, offset, None);
+            }
             self.take();
-            (SyntaxKind::LiteralString, offset, None)
-        } else {
-            (SyntaxKind::Error, offset, Some("invalid string literal"))
         }
     }
-    
+
     #[inline]
     fn take_integer_or_number(&mut self) -> (SyntaxKind, usize, Option<&str>) {
         let offset = self.consumed();
-        self.take_while(|c| c.is_ascii_digit());
+
+        // `0xFF` => [LiteralInteger]
+        if self.first() == '0' && self.second() == 'x' && self.third().is_ascii_hexdigit() {
+            self.take();
+            self.take();
+            self.take_while(|c| c.is_ascii_hexdigit());
+            return (SyntaxKind::LiteralInteger, offset, None);
+        }
+
+        self.take_while(|c| c.is_ascii_digit() || c == '_');
+
+        let mut kind = SyntaxKind::LiteralInteger;
 
         if self.first() == '.' {
             // `1..x` => [LiteralInteger, Period2, Lower]
@@ -256,16 +365,37 @@ impl<'a> Lexer<'a> {
             // `1.2` => [LiteralNumber]
             if self.second().is_ascii_digit() {
                 assert_eq!(self.take(), '.');
-                self.take_while(|c| c.is_ascii_digit());
-                return (SyntaxKind::LiteralNumber, offset, None);
+                self.take_while(|c| c.is_ascii_digit() || c == '_');
+                kind = SyntaxKind::LiteralNumber;
+            } else {
+                // `1.` => [Error]
+                assert_eq!(self.take(), '.');
+                return (SyntaxKind::Error, offset, Some("invalid number literal"));
             }
+        }
 
-            // `1.` => [Error]
-            assert_eq!(self.take(), '.');
-            return (SyntaxKind::Error, offset, Some("invalid number literal"));
+        // `1e10`, `1.0e-10` => [LiteralNumber]
+        if self.first() == 'e' {
+            let sign = matches!(self.second(), '+' | '-');
+            let digit = if sign { self.third() } else { self.second() };
+            if digit.is_ascii_digit() {
+                self.take();
+                if sign {
+                    self.take();
+                }
+                self.take_while(|c| c.is_ascii_digit());
+                kind = SyntaxKind::LiteralNumber;
+            }
         }
 
-        return (SyntaxKind::LiteralInteger, offset, None);
+        (kind, offset, None)
+    }
+
+    #[inline]
+    fn take_unknown(&mut self) -> (SyntaxKind, usize, Option<&str>) {
+        let offset = self.consumed();
+        self.take();
+        (SyntaxKind::Error, offset, Some("unknown token"))
     }
 
     #[inline]
@@ -307,6 +437,9 @@ impl<'a> Lexer<'a> {
             if level == 0 {
                 break;
             }
+            if self.is_eof() {
+                return (SyntaxKind::Error, offset, Some("unterminated block comment"));
+            }
             self.take();
         }
         (SyntaxKind::BlockComment, offset, None)
@@ -314,11 +447,40 @@ impl<'a> Lexer<'a> {
 }
 
 fn is_operator(c: char) -> bool {
-    c.is_symbol() || c.is_punctuation()
+    matches!(
+        c,
+        ':' | '!'
+            | '#'
+            | '$'
+            | '%'
+            | '&'
+            | '*'
+            | '+'
+            | '.'
+            | '/'
+            | '<'
+            | '='
+            | '>'
+            | '?'
+            | '@'
+            | '\\'
+            | '^'
+            | '|'
+            | '-'
+            | '~'
+    ) || (!c.is_ascii() && c.is_symbol())
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_letter() || c == '_'
+}
+
+fn is_identifier_continue(c: char) -> bool {
+    c.is_letter() || c.is_number() || c == '_' || c == '\''
 }
 
 /// Lexes a `&str` into [`Lexed`].
-pub fn lex(source: &str) -> Lexed {
+pub fn lex(source: &str) -> Lexed<'_> {
     let mut lexer = Lexer::new(source);
     let mut lexed = Lexed::new(source);
     loop {
@@ -339,3 +501,25 @@ fn lexer_test() {
     dbg!(lexed.offsets);
     dbg!(lexed.errors);
 }
+
+#[test]
+fn lexer_operator_sections() {
+    let lexed = lex("(+) x' _y ?hole 0xFF 1e3 \"a\\\"b\"");
+    let kinds: Vec<_> =
+        (0..lexed.len()).map(|index| lexed.kind(index)).filter(|kind| !kind.is_trivia()).collect();
+    assert_eq!(
+        kinds,
+        [
+            SyntaxKind::LeftParenthesis,
+            SyntaxKind::Operator,
+            SyntaxKind::RightParenthesis,
+            SyntaxKind::Lower,
+            SyntaxKind::Lower,
+            SyntaxKind::Hole,
+            SyntaxKind::LiteralInteger,
+            SyntaxKind::LiteralNumber,
+            SyntaxKind::LiteralString,
+        ]
+    );
+    assert!(lexed.errors().is_empty());
+}
@@ -1,3 +1,183 @@
+mod builder;
+mod grammar;
 pub mod input;
 pub mod lexer;
 pub mod output;
+mod parser;
+
+use rowan::TextRange;
+use syntax::SyntaxNode;
+
+/// An error emitted by the lexer or the parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub message: String,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// A token that can't be lexed, e.g. an unterminated string.
+    Lexer,
+    /// A token that's expected before the end of a layout block, which is
+    /// usually a token that's indented too little.
+    Layout,
+    Parser,
+}
+
+impl ParseErrorKind {
+    /// A stable name for the kind of error, which editors show as its code.
+    pub fn code(self) -> &'static str {
+        match self {
+            ParseErrorKind::Lexer => "lexer-error",
+            ParseErrorKind::Layout => "layout-error",
+            ParseErrorKind::Parser => "parse-error",
+        }
+    }
+}
+
+/// Parses a PureScript module into a lossless syntax tree.
+///
+/// Parsing never fails; erroneous fragments are wrapped in
+/// [`SyntaxKind::Error`](syntax::SyntaxKind::Error) nodes and reported in
+/// the returned errors.
+pub fn parse_module(source: &str) -> (SyntaxNode, Vec<ParseError>) {
+    parse_with(source, false, grammar::module)
+}
+
+/// Parses the interface of a PureScript module, skipping the bodies of its
+/// values and instance members.
+///
+/// Skipped bodies are kept as [`SyntaxKind::SkippedBody`](syntax::SyntaxKind::SkippedBody)
+/// nodes, such that the tree is still lossless, and they end at the next
+/// layout boundary of the declaration. Only errors outside of the bodies
+/// are reported.
+pub fn parse_module_interface(source: &str) -> (SyntaxNode, Vec<ParseError>) {
+    parse_with(source, true, grammar::module)
+}
+
+/// Parses a standalone expression, wrapped in a module node.
+pub fn parse_expression(source: &str) -> (SyntaxNode, Vec<ParseError>) {
+    parse_with(source, false, grammar::expression_entry)
+}
+
+/// Parses a standalone type, wrapped in a module node.
+pub fn parse_type(source: &str) -> (SyntaxNode, Vec<ParseError>) {
+    parse_with(source, false, grammar::type_entry)
+}
+
+fn parse_with(
+    source: &str,
+    skip_bodies: bool,
+    rule: fn(&mut parser::Parser),
+) -> (SyntaxNode, Vec<ParseError>) {
+    let lexed = lexer::lex(source);
+    let input = input::Input::from_lexed(&lexed);
+    let mut parser = parser::Parser::new(&input, skip_bodies);
+    rule(&mut parser);
+    let (green, errors) = builder::build(&lexed, parser.finish());
+    (SyntaxNode::new_root(green), errors)
+}
+
+#[test]
+fn typed_ast_children() {
+    use syntax::ast::{self, AstNode};
+
+    let source = "module Main (main) where\n\nimport Prelude\nimport Data.Maybe (Maybe(..)) as M\n\nmain :: Effect Unit\nmain = do\n  x <- pure 1\n  log \"hi\"\n";
+    let (node, errors) = parse_module(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(node.to_string(), source);
+
+    let module = ast::Module::cast(node).unwrap();
+    let header = module.header().unwrap();
+    let imports: Vec<_> = header
+        .imports()
+        .filter_map(|import| import.module_name())
+        .map(|name| name.syntax().to_string())
+        .collect();
+    assert_eq!(imports, ["Prelude", "Data.Maybe"]);
+
+    let declarations: Vec<_> = module.declarations().collect();
+    assert!(matches!(declarations[0], ast::Declaration::Annotation(_)));
+    let ast::Declaration::Value(value) = &declarations[1] else { panic!() };
+    let Some(ast::GuardedExpression::Unconditional(unconditional)) = value.guarded_expression()
+    else {
+        panic!()
+    };
+    let Some(ast::Expression::Do(block)) = unconditional.expression() else { panic!() };
+    let statements: Vec<_> = block.statements().collect();
+    assert!(matches!(statements[0], ast::DoStatement::Bind(_)));
+    assert!(matches!(statements[1], ast::DoStatement::Discard(_)));
+}
+
+#[test]
+fn skip_value_bodies() {
+    use syntax::SyntaxKind;
+
+    let source = "module Main where\n\nf :: Int\nf x | x > 0 = case x of\n  _ -> (\nf _ = 1 +\n\ninstance Show T where\n  show _ = \"T\"\n";
+    let (node, errors) = parse_module_interface(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(node.to_string(), source);
+    let skipped: Vec<_> = node
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::SkippedBody)
+        .map(|node| node.to_string())
+        .collect();
+    assert_eq!(skipped, ["| x > 0 = case x of\n  _ -> (", "= 1 +", "= \"T\""]);
+}
+
+#[test]
+fn unparse_reassociates_operators() {
+    use syntax::{
+        ast::{self, AstNode},
+        unparse::{Expression, Fixities},
+    };
+
+    let source = "module Main where\n\ninfixl 6 add as +\ninfixl 7 mul as *\n\nvalue = (a + b) * c `div` d + f (g x) (-1)\n";
+    let (node, errors) = parse_module(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let module = ast::Module::cast(node).unwrap();
+    let fixities = Fixities::from_module(&module);
+    let value = module
+        .declarations()
+        .find_map(|declaration| match declaration {
+            ast::Declaration::Value(value) => Some(value),
+            _ => None,
+        })
+        .unwrap();
+    let Some(ast::GuardedExpression::Unconditional(unconditional)) = value.guarded_expression()
+    else {
+        panic!()
+    };
+    let expression = Expression::from_ast(&unconditional.expression().unwrap(), &fixities);
+    assert_eq!(expression.to_string(), "(a + b) * c `div` d + f (g x) (-1)");
+}
+
+#[test]
+fn recover_from_visible_forall_variables() {
+    for source in [
+        "module Main where\n\nty :: forall @\n",
+        "module Main where\n\nty :: forall @",
+        "module Main where\n\nty :: forall @. a\n",
+        "module Main where\n\nty :: forall @(. a\n",
+        "module Main where\n\nty :: forall a @\nx = 1\n",
+    ] {
+        let (node, errors) = parse_module(source);
+        assert_eq!(node.to_string(), source);
+        let messages: Vec<_> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert!(messages.contains(&"expected a type variable"), "{messages:?}");
+    }
+    // The `.` isn't taken as the name of the variable, so the rest of the
+    // type still parses.
+    let (node, errors) = parse_module("module Main where\n\nty :: forall @ . a\n");
+    let messages: Vec<_> = errors.iter().map(|error| error.message.as_str()).collect();
+    assert_eq!(messages, ["expected a type variable"]);
+    let bindings: Vec<_> = node
+        .descendants()
+        .filter(|node| node.kind() == syntax::SyntaxKind::TypeVariableBinding)
+        .map(|node| node.to_string())
+        .collect();
+    assert_eq!(bindings, ["@"]);
+}
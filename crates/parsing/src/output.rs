@@ -1,3 +1,35 @@
 //! The output type for the parser.
 
-pub struct Output {}
+use syntax::SyntaxKind;
+
+use crate::ParseErrorKind;
+
+/// An event emitted by the parser, describing a depth-first traversal of
+/// the syntax tree to be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Starts a node. `forward_parent` is a relative offset into the events
+    /// for a node that wraps this one, which is used to implement
+    /// [`CompletedMarker::precede`](crate::parser::CompletedMarker::precede).
+    Start { kind: SyntaxKind, forward_parent: Option<usize> },
+    /// Consumes the next significant token from the input.
+    Token,
+    /// Finishes the current node.
+    Finish,
+    /// Reports an error at the next significant token.
+    Error { kind: ParseErrorKind, message: String },
+    /// Placeholder for abandoned markers.
+    Tombstone,
+}
+
+/// A stream of [`Event`]s.
+#[derive(Debug, Default)]
+pub struct Output {
+    pub(crate) events: Vec<Event>,
+}
+
+impl Output {
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+}
@@ -0,0 +1,472 @@
+//! The event-based parser and its layout algorithm.
+//!
+//! Rather than inserting layout tokens into the [`Input`], the parser keeps
+//! a stack of layout contexts that it consults when peeking at the current
+//! token. When the current token begins a new line at the column of the
+//! innermost block, it's reported as [`SyntaxKind::LayoutSeparator`], and
+//! as [`SyntaxKind::LayoutEnd`] if it's further to the left. Delimiters
+//! such as parentheses mask the enclosing blocks, such that expressions
+//! can be split across lines freely.
+
+use syntax::SyntaxKind;
+
+use crate::{
+    input::Input,
+    output::{Event, Output},
+    ParseErrorKind,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// An indentation-sensitive block, e.g. the statements in `do`.
+    Block { column: u32, cleared: usize },
+    /// A delimited context, e.g. the contents of `(` and `)`.
+    Delimiter,
+}
+
+pub(crate) struct Parser<'i> {
+    input: &'i Input,
+    index: usize,
+    events: Vec<Event>,
+    layouts: Vec<Layout>,
+    /// Whether the bodies of values are skipped rather than parsed.
+    skip_bodies: bool,
+}
+
+impl<'i> Parser<'i> {
+    pub(crate) fn new(input: &'i Input, skip_bodies: bool) -> Parser<'i> {
+        Parser { input, index: 0, events: vec![], layouts: vec![], skip_bodies }
+    }
+
+    pub(crate) fn skips_bodies(&self) -> bool {
+        self.skip_bodies
+    }
+
+    pub(crate) fn finish(self) -> Output {
+        Output { events: self.events }
+    }
+
+    /// Returns the current token, taking layout into account.
+    pub(crate) fn current(&self) -> SyntaxKind {
+        let kind = self.input.kind(self.index);
+        if kind == SyntaxKind::EndOfFile || !self.input.is_line_start(self.index) {
+            return kind;
+        }
+
+        let column = self.input.column(self.index);
+        let root = self.layouts.iter().find_map(|layout| match layout {
+            Layout::Block { column, .. } => Some(*column),
+            Layout::Delimiter => None,
+        });
+
+        let mut delimited = false;
+        for layout in self.layouts.iter().rev() {
+            match *layout {
+                Layout::Delimiter => delimited = true,
+                Layout::Block { column: block, cleared } => {
+                    if delimited {
+                        // Delimiters mask the enclosing blocks, but a token that
+                        // dedents past them is more likely a missing delimiter.
+                        if column < block || Some(column) <= root {
+                            return SyntaxKind::LayoutEnd;
+                        }
+                        return kind;
+                    }
+                    if cleared == self.index {
+                        return kind;
+                    }
+                    if column < block {
+                        return SyntaxKind::LayoutEnd;
+                    }
+                    if column == block {
+                        return SyntaxKind::LayoutSeparator;
+                    }
+                    return kind;
+                }
+            }
+        }
+
+        kind
+    }
+
+    /// Returns the n-th token ahead, ignoring layout.
+    pub(crate) fn nth(&self, n: usize) -> SyntaxKind {
+        self.input.kind(self.index + n)
+    }
+
+    /// Determines if the n-th token ahead immediately follows the one before it.
+    pub(crate) fn nth_joint(&self, n: usize) -> bool {
+        self.input.is_joint(self.index + n)
+    }
+
+    pub(crate) fn at(&self, kind: SyntaxKind) -> bool {
+        self.current() == kind
+    }
+
+    pub(crate) fn at_any(&self, kinds: &[SyntaxKind]) -> bool {
+        kinds.contains(&self.current())
+    }
+
+    pub(crate) fn at_eof(&self) -> bool {
+        self.at(SyntaxKind::EndOfFile)
+    }
+
+    /// Determines if the parser is at the end of a layout item.
+    pub(crate) fn at_layout_boundary(&self) -> bool {
+        self.at_any(&[SyntaxKind::LayoutSeparator, SyntaxKind::LayoutEnd, SyntaxKind::EndOfFile])
+    }
+
+    /// Determines if the parser is at an operator, including contextual
+    /// operators such as `:` and `..`.
+    pub(crate) fn at_operator(&self) -> bool {
+        self.current().is_operator()
+    }
+
+    /// Consumes the current token.
+    pub(crate) fn bump(&mut self) {
+        assert!(
+            !matches!(
+                self.current(),
+                SyntaxKind::EndOfFile | SyntaxKind::LayoutSeparator | SyntaxKind::LayoutEnd
+            ),
+            "cannot bump virtual tokens"
+        );
+        self.events.push(Event::Token);
+        self.index += 1;
+    }
+
+    /// Consumes the current token if it's of the given kind.
+    pub(crate) fn eat(&mut self, kind: SyntaxKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the current token if it's of the given kind, even when it
+    /// would otherwise be read as a layout separator. This is used for tokens
+    /// like `then` and `else` that are allowed to align with do statements.
+    pub(crate) fn eat_across_separator(&mut self, kind: SyntaxKind) -> bool {
+        if self.nth(0) == kind && self.at(SyntaxKind::LayoutSeparator) {
+            self.events.push(Event::Token);
+            self.index += 1;
+            if let Some(Layout::Block { cleared, .. }) = self.layouts.last_mut() {
+                *cleared = self.index;
+            }
+            return true;
+        }
+        self.eat(kind)
+    }
+
+    /// Consumes the current token if it's of the given kind, or emits an error.
+    pub(crate) fn expect(&mut self, kind: SyntaxKind) -> bool {
+        if self.eat(kind) {
+            true
+        } else {
+            self.error(format!("expected {}", describe(kind)));
+            false
+        }
+    }
+
+    pub(crate) fn error(&mut self, message: impl Into<String>) {
+        // Errors at the end of a layout item are where a token that's
+        // indented too little cut the item short.
+        let kind = if matches!(self.current(), SyntaxKind::LayoutSeparator | SyntaxKind::LayoutEnd)
+        {
+            ParseErrorKind::Layout
+        } else {
+            ParseErrorKind::Parser
+        };
+        self.events.push(Event::Error { kind, message: message.into() });
+    }
+
+    /// Emits an error and wraps the current token in an [`SyntaxKind::Error`]
+    /// node, unless the parser is at a layout boundary.
+    pub(crate) fn error_recover(&mut self, message: impl Into<String>) {
+        self.error(message);
+        if !self.at_layout_boundary() {
+            let marker = self.start();
+            self.bump();
+            marker.complete(self, SyntaxKind::Error);
+        }
+    }
+
+    pub(crate) fn start(&mut self) -> Marker {
+        let index = self.events.len();
+        self.events.push(Event::Tombstone);
+        Marker { index, completed: false }
+    }
+}
+
+impl<'i> Parser<'i> {
+    /// Starts a layout block at the current token.
+    ///
+    /// Returns `false` if the block is empty, i.e. the current token is not
+    /// indented past the enclosing block. No block is pushed in that case.
+    pub(crate) fn layout_start(&mut self) -> bool {
+        if self.at_layout_boundary() {
+            return false;
+        }
+        let column = self.input.column(self.index);
+        self.layouts.push(Layout::Block { column, cleared: self.index });
+        true
+    }
+
+    /// Consumes a layout separator, if present.
+    pub(crate) fn layout_separator(&mut self) -> bool {
+        if self.at(SyntaxKind::LayoutSeparator) {
+            if let Some(Layout::Block { cleared, .. }) = self.layouts.last_mut() {
+                *cleared = self.index;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn layout_end(&mut self) {
+        let layout = self.layouts.pop();
+        assert!(matches!(layout, Some(Layout::Block { .. })), "unbalanced layout");
+    }
+
+    /// Parses a layout block of items, e.g. the statements in `do`.
+    ///
+    /// Items are delimited by layout separators. Tokens that an item failed
+    /// to consume are wrapped in error nodes until the next separator. For
+    /// nested blocks, a token that cannot continue the item instead closes
+    /// the block such that the enclosing rule can consume it, e.g. `in`.
+    pub(crate) fn layout_block(&mut self, mut item: impl FnMut(&mut Parser)) {
+        if !self.layout_start() {
+            return;
+        }
+        let nested = self.layouts.len() > 1;
+        loop {
+            if self.at_any(CLOSING) {
+                break;
+            }
+
+            let before = self.index;
+            item(self);
+
+            if self.layout_separator() {
+                continue;
+            }
+            if self.at_any(&[SyntaxKind::LayoutEnd, SyntaxKind::EndOfFile]) {
+                break;
+            }
+            if nested && self.index != before {
+                break;
+            }
+
+            self.error("unexpected token");
+            let marker = self.start();
+            while !self.at_layout_boundary() {
+                self.bump();
+            }
+            marker.complete(self, SyntaxKind::Error);
+            if !self.layout_separator() {
+                break;
+            }
+        }
+        self.layout_end();
+    }
+
+    /// Parses the contents of a delimited context e.g. `(` and `)`.
+    ///
+    /// The opening token is consumed by this function, which also expects
+    /// the closing token.
+    pub(crate) fn delimited(
+        &mut self,
+        open: SyntaxKind,
+        close: SyntaxKind,
+        inner: impl FnOnce(&mut Parser),
+    ) {
+        self.expect(open);
+        self.layouts.push(Layout::Delimiter);
+        inner(self);
+        let layout = self.layouts.pop();
+        assert!(matches!(layout, Some(Layout::Delimiter)), "unbalanced layout");
+        self.expect(close);
+    }
+
+    /// Parses a comma-separated sequence within delimiters.
+    pub(crate) fn separated(
+        &mut self,
+        open: SyntaxKind,
+        close: SyntaxKind,
+        mut element: impl FnMut(&mut Parser),
+    ) {
+        self.delimited(open, close, |p| {
+            if p.at(close) {
+                return;
+            }
+            loop {
+                let before = p.index;
+                element(p);
+                if p.eat(SyntaxKind::Comma) {
+                    continue;
+                }
+                if p.at(close) || p.at_layout_boundary() {
+                    break;
+                }
+                if p.index == before {
+                    p.error_recover("unexpected token");
+                } else {
+                    p.error(format!(
+                        "expected {} or {}",
+                        describe(SyntaxKind::Comma),
+                        describe(close)
+                    ));
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Scans ahead for a token before the end of the current item, outside of
+    /// any delimiters. Scanning stops at any of the `stop` tokens.
+    pub(crate) fn scan_for(&self, target: SyntaxKind, stop: &[SyntaxKind]) -> bool {
+        let boundary = self.layouts.iter().rev().find_map(|layout| match layout {
+            Layout::Block { column, .. } => Some(*column),
+            Layout::Delimiter => None,
+        });
+
+        let mut depth = 0usize;
+        let mut index = self.index;
+        loop {
+            let kind = self.input.kind(index);
+            if kind == SyntaxKind::EndOfFile {
+                return false;
+            }
+            if index != self.index && self.input.is_line_start(index) {
+                if let Some(boundary) = boundary {
+                    if self.input.column(index) <= boundary && depth == 0 {
+                        return false;
+                    }
+                }
+            }
+            match kind {
+                SyntaxKind::LeftParenthesis | SyntaxKind::LeftBracket | SyntaxKind::LeftBrace => {
+                    depth += 1
+                }
+                SyntaxKind::RightParenthesis
+                | SyntaxKind::RightBracket
+                | SyntaxKind::RightBrace => {
+                    if depth == 0 {
+                        return false;
+                    }
+                    depth -= 1;
+                }
+                _ if depth == 0 && kind == target => return true,
+                _ if depth == 0 && stop.contains(&kind) => return false,
+                _ => (),
+            }
+            index += 1;
+        }
+    }
+}
+
+/// Tokens that close a nested layout block even after a separator.
+const CLOSING: &[SyntaxKind] = &[
+    SyntaxKind::InKw,
+    SyntaxKind::ThenKw,
+    SyntaxKind::ElseKw,
+    SyntaxKind::OfKw,
+    SyntaxKind::RightParenthesis,
+    SyntaxKind::RightBracket,
+    SyntaxKind::RightBrace,
+    SyntaxKind::Comma,
+];
+
+pub(crate) struct Marker {
+    index: usize,
+    completed: bool,
+}
+
+impl Marker {
+    pub(crate) fn complete(mut self, p: &mut Parser, kind: SyntaxKind) -> CompletedMarker {
+        self.completed = true;
+        match &mut p.events[self.index] {
+            event @ Event::Tombstone => *event = Event::Start { kind, forward_parent: None },
+            _ => unreachable!(),
+        }
+        p.events.push(Event::Finish);
+        CompletedMarker { index: self.index }
+    }
+
+    pub(crate) fn abandon(mut self, p: &mut Parser) {
+        self.completed = true;
+        if self.index == p.events.len() - 1 {
+            p.events.pop();
+        }
+    }
+}
+
+impl Drop for Marker {
+    fn drop(&mut self) {
+        if !self.completed && !std::thread::panicking() {
+            panic!("marker must be completed or abandoned");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompletedMarker {
+    index: usize,
+}
+
+impl CompletedMarker {
+    /// Starts a new node that wraps this completed one.
+    pub(crate) fn precede(self, p: &mut Parser) -> Marker {
+        let marker = p.start();
+        match &mut p.events[self.index] {
+            Event::Start { forward_parent, .. } => {
+                *forward_parent = Some(marker.index - self.index);
+            }
+            _ => unreachable!(),
+        }
+        marker
+    }
+}
+
+/// Describes a token for error messages.
+pub(crate) fn describe(kind: SyntaxKind) -> &'static str {
+    match kind {
+        SyntaxKind::ModuleKw => "'module'",
+        SyntaxKind::WhereKw => "'where'",
+        SyntaxKind::ImportKw => "'import'",
+        SyntaxKind::AsKw => "'as'",
+        SyntaxKind::CaseKw => "'case'",
+        SyntaxKind::OfKw => "'of'",
+        SyntaxKind::LetKw => "'let'",
+        SyntaxKind::InKw => "'in'",
+        SyntaxKind::IfKw => "'if'",
+        SyntaxKind::ThenKw => "'then'",
+        SyntaxKind::ElseKw => "'else'",
+        SyntaxKind::InstanceKw => "'instance'",
+        SyntaxKind::Upper => "a proper name",
+        SyntaxKind::Lower => "an identifier",
+        SyntaxKind::Operator => "an operator",
+        SyntaxKind::LiteralInteger => "an integer",
+        SyntaxKind::Equal => "'='",
+        SyntaxKind::Period => "'.'",
+        SyntaxKind::Colon => "':'",
+        SyntaxKind::Colon2 => "'::'",
+        SyntaxKind::Comma => "','",
+        SyntaxKind::Pipe => "'|'",
+        SyntaxKind::Backtick => "'`'",
+        SyntaxKind::LeftArrow => "'<-'",
+        SyntaxKind::RightArrow => "'->'",
+        SyntaxKind::RightThickArrow => "'=>'",
+        SyntaxKind::LeftThickArrow => "'<='",
+        SyntaxKind::LeftParenthesis => "'('",
+        SyntaxKind::RightParenthesis => "')'",
+        SyntaxKind::LeftBracket => "'{'",
+        SyntaxKind::RightBracket => "'}'",
+        SyntaxKind::LeftBrace => "'['",
+        SyntaxKind::RightBrace => "']'",
+        _ => "a token",
+    }
+}
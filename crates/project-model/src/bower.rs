@@ -0,0 +1,118 @@
+//! Projects whose dependencies are installed by Bower, configured by a
+//! `bower.json`.
+//!
+//! Bower installs every dependency, including transitive ones, directly
+//! under `bower_components`, each with the `purescript-` prefix that the
+//! Bower registry requires. Their sources are already on disk, so they're
+//! read as libraries without resolving any versions.
+
+use std::{fs, path::Path};
+
+use crate::{read_error, yaml::Yaml, Package, ProjectError, ProjectWorkspace, TestConfig};
+
+pub const CONFIG: &str = "bower.json";
+
+const COMPONENTS: &str = "bower_components";
+const PREFIX: &str = "purescript-";
+
+pub fn load(path: &Path) -> Result<ProjectWorkspace, ProjectError> {
+    let source = fs::read_to_string(path).map_err(|error| read_error(path, error))?;
+    let mut workspace = parse(path, &source)?;
+
+    let components = workspace.root.join(COMPONENTS);
+    let mut directories: Vec<_> = fs::read_dir(&components)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|directory| directory.is_dir())
+        .collect();
+    directories.sort();
+    for directory in directories {
+        let config = fs::read_to_string(directory.join(CONFIG)).ok();
+        let library =
+            config.and_then(|source| read_package(&directory, &Yaml::parse(&source).ok()?));
+        let library = library.unwrap_or_else(|| {
+            let name = directory.file_name().unwrap_or_default().to_string_lossy();
+            Package {
+                name: name.strip_prefix(PREFIX).unwrap_or(&name).to_string(),
+                root: directory.clone(),
+                sources: vec!["src/**/*.purs".to_string()],
+                dependencies: vec![],
+                test: None,
+            }
+        });
+        workspace.libraries.push(Package { test: None, ..library });
+    }
+    Ok(workspace)
+}
+
+/// Reads the package declared by the configuration at `path`, without its
+/// installed dependencies.
+pub fn parse(path: &Path, source: &str) -> Result<ProjectWorkspace, ProjectError> {
+    let yaml = Yaml::parse(source)
+        .map_err(|line| ProjectError::Syntax { path: path.to_path_buf(), line })?;
+    let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let package = read_package(&root, &yaml)
+        .ok_or(ProjectError::Shape { path: path.to_path_buf(), context: "the package" })?;
+    Ok(ProjectWorkspace {
+        output: root.join("output"),
+        root,
+        packages: vec![package],
+        libraries: vec![],
+        package_set: None,
+    })
+}
+
+/// Dependencies are a mapping from their names to their versions, and the
+/// tests are run with the development dependencies as well, from the
+/// `Test.Main` that Pulp expects by default.
+fn read_package(root: &Path, yaml: &Yaml) -> Option<Package> {
+    let name = yaml.get("name")?.as_str()?;
+    let dependencies = |field| match yaml.get(field) {
+        None | Some(Yaml::Null) => Some(vec![]),
+        Some(Yaml::Mapping(entries)) => Some(
+            entries
+                .iter()
+                .map(|(name, _)| name.strip_prefix(PREFIX).unwrap_or(name).to_string())
+                .collect(),
+        ),
+        Some(_) => None,
+    };
+    let test = TestConfig {
+        main: "Test.Main".to_string(),
+        sources: vec!["test/**/*.purs".to_string()],
+        dependencies: dependencies("devDependencies")?,
+    };
+    Some(Package {
+        name: name.strip_prefix(PREFIX).unwrap_or(name).to_string(),
+        root: root.to_path_buf(),
+        sources: vec!["src/**/*.purs".to_string()],
+        dependencies: dependencies("dependencies")?,
+        test: Some(test),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::parse;
+
+    #[test]
+    fn read_bower_json() {
+        let source = r#"{
+  "name": "purescript-example",
+  "license": ["MIT"],
+  "dependencies": {
+    "purescript-prelude": "^6.0.0",
+    "purescript-effect": "^4.0.0"
+  },
+  "devDependencies": { "purescript-spec": "^7.0.0" }
+}"#;
+        let workspace = parse(Path::new("/project/bower.json"), source).unwrap();
+        let [package] = workspace.packages.as_slice() else { panic!() };
+        assert_eq!(package.name, "example");
+        assert_eq!(package.dependencies, ["prelude", "effect"]);
+        assert_eq!(package.test.as_ref().unwrap().dependencies, ["spec"]);
+    }
+}
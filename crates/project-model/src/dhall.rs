@@ -0,0 +1,330 @@
+//! A minimal Dhall evaluator for legacy Spago configurations.
+//!
+//! A `spago.dhall` is a record of text and lists of text, which may be built
+//! from `let` bindings, local imports, record merges with `//`, and list
+//! appends with `#`. Anything else, such as a remote import or a function,
+//! evaluates to [`Dhall::Opaque`], which is only an error if a field that's
+//! needed turns out to be opaque.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Dhall {
+    Text(String),
+    List(Vec<Dhall>),
+    Record(Vec<(String, Dhall)>),
+    Opaque,
+}
+
+impl Dhall {
+    /// Evaluates a Dhall expression, resolving imports through `import`,
+    /// and returning the byte offset of the first unexpected character if
+    /// it's malformed or outside of the supported subset.
+    pub(crate) fn evaluate(
+        source: &str,
+        import: &mut dyn FnMut(&str) -> Dhall,
+    ) -> Result<Dhall, usize> {
+        let mut evaluator = Evaluator { source, offset: 0, bindings: vec![], import };
+        let value = evaluator.expression()?;
+        evaluator.whitespace();
+        if evaluator.offset == source.len() {
+            Ok(value)
+        } else {
+            Err(evaluator.offset)
+        }
+    }
+
+    pub(crate) fn get(&self, field: &str) -> Option<&Dhall> {
+        match self {
+            Dhall::Record(fields) => fields.iter().find(|(name, _)| name == field).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_text(&self) -> Option<&str> {
+        match self {
+            Dhall::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Dhall]> {
+        match self {
+            Dhall::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+struct Evaluator<'a, 'i> {
+    source: &'a str,
+    offset: usize,
+    bindings: Vec<(&'a str, Dhall)>,
+    import: &'i mut dyn FnMut(&str) -> Dhall,
+}
+
+impl<'a> Evaluator<'a, '_> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.offset += rest.len() - trimmed.len();
+            if trimmed.starts_with("--") {
+                self.offset += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("{-") {
+                self.offset += trimmed.find("-}").map_or(trimmed.len(), |end| end + 2);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.whitespace();
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), usize> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.offset)
+        }
+    }
+
+    /// Eats a keyword, which must not be the start of a longer identifier.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.whitespace();
+        let rest = self.rest();
+        let is_keyword = rest.starts_with(keyword)
+            && !rest[keyword.len()..]
+                .starts_with(|c: char| c.is_alphanumeric() || "_-/".contains(c));
+        if is_keyword {
+            self.offset += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        self.whitespace();
+        let rest = self.rest();
+        if !rest.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return None;
+        }
+        let end =
+            rest.find(|c: char| !(c.is_alphanumeric() || "_-/".contains(c))).unwrap_or(rest.len());
+        self.offset += end;
+        Some(&rest[..end])
+    }
+
+    fn expression(&mut self) -> Result<Dhall, usize> {
+        if self.keyword("let") {
+            let depth = self.bindings.len();
+            let name = self.identifier().ok_or(self.offset)?;
+            if self.eat(":") {
+                self.skip_type();
+            }
+            self.expect("=")?;
+            let value = self.expression()?;
+            self.bindings.push((name, value));
+            // Bindings can follow each other without an `in` between them.
+            if !self.keyword("in") && !self.rest().starts_with("let") {
+                return Err(self.offset);
+            }
+            let body = self.expression();
+            self.bindings.truncate(depth);
+            return body;
+        }
+        let mut value = self.operand()?;
+        loop {
+            if self.eat("//") || self.eat("⫽") {
+                let right = self.operand()?;
+                value = match (value, right) {
+                    (Dhall::Record(mut left), Dhall::Record(right)) => {
+                        for (name, field) in right {
+                            left.retain(|(existing, _)| *existing != name);
+                            left.push((name, field));
+                        }
+                        Dhall::Record(left)
+                    }
+                    _ => Dhall::Opaque,
+                };
+            } else if self.eat("#") {
+                let right = self.operand()?;
+                value = match (value, right) {
+                    (Dhall::List(mut left), Dhall::List(right)) => {
+                        left.extend(right);
+                        Dhall::List(left)
+                    }
+                    _ => Dhall::Opaque,
+                };
+            } else if self.eat(":") {
+                self.skip_type();
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Skips a type annotation such as `List Text`, which doesn't change the
+    /// value.
+    fn skip_type(&mut self) {
+        while self.identifier().is_some() {}
+    }
+
+    /// A primary expression, along with the fields selected from it.
+    fn operand(&mut self) -> Result<Dhall, usize> {
+        let mut value = self.primary()?;
+        while self.rest().starts_with('.') && !self.rest().starts_with("..") {
+            self.offset += 1;
+            let field = self.identifier().ok_or(self.offset)?;
+            value = value.get(field).cloned().unwrap_or(Dhall::Opaque);
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<Dhall, usize> {
+        self.whitespace();
+        let rest = self.rest();
+        if self.eat("(") {
+            let value = self.expression()?;
+            self.expect(")")?;
+            Ok(value)
+        } else if self.eat("{") {
+            let mut fields = vec![];
+            if self.eat("=") {
+                self.expect("}")?;
+                return Ok(Dhall::Record(fields));
+            }
+            while !self.eat("}") {
+                let name = self.identifier().ok_or(self.offset)?.to_string();
+                let value = if self.eat("=") {
+                    self.expression()?
+                } else {
+                    // A record type, or a field punned from a binding.
+                    self.expect(":").map(|_| self.skip_type()).ok();
+                    self.lookup(&name)
+                };
+                fields.push((name, value));
+                if !self.eat(",") {
+                    self.expect("}")?;
+                    break;
+                }
+            }
+            Ok(Dhall::Record(fields))
+        } else if self.eat("[") {
+            let mut elements = vec![];
+            while !self.eat("]") {
+                elements.push(self.expression()?);
+                if !self.eat(",") {
+                    self.expect("]")?;
+                    break;
+                }
+            }
+            Ok(Dhall::List(elements))
+        } else if rest.starts_with('"') {
+            self.text()
+        } else if ["./", "../", "/", "~/", "http://", "https://", "env:"]
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+        {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let end = rest[..end].find([',', ')', ']', '}']).unwrap_or(end);
+            self.offset += end;
+            // Imports may be pinned by their hash, which doesn't change what
+            // they refer to.
+            if self.keyword("sha256") {
+                self.expect(":")?;
+                let hash = self.rest();
+                self.offset += hash.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(hash.len());
+            }
+            Ok((self.import)(&rest[..end]))
+        } else if let Some(name) = self.identifier() {
+            Ok(self.lookup(name))
+        } else {
+            Err(self.offset)
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Dhall {
+        let binding = self.bindings.iter().rev().find(|(binding, _)| *binding == name);
+        binding.map_or(Dhall::Opaque, |(_, value)| value.clone())
+    }
+
+    fn text(&mut self) -> Result<Dhall, usize> {
+        self.offset += 1;
+        let mut text = String::new();
+        let mut characters = self.rest().char_indices();
+        while let Some((index, character)) = characters.next() {
+            match character {
+                '"' => {
+                    self.offset += index + 1;
+                    return Ok(Dhall::Text(text));
+                }
+                '\\' => match characters.next().ok_or(self.offset + index)?.1 {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    escaped => text.push(escaped),
+                },
+                '$' if self.rest()[index..].starts_with("${") => {
+                    // Interpolation can't be evaluated without the values
+                    // it refers to.
+                    return Err(self.offset + index);
+                }
+                character => text.push(character),
+            }
+        }
+        Err(self.source.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dhall;
+
+    fn text(value: &str) -> Dhall {
+        Dhall::Text(value.to_string())
+    }
+
+    #[test]
+    fn evaluate_spago_dhall() {
+        let source = r#"
+{- The test configuration extends the main one. -}
+let conf = ./spago.dhall
+
+in  conf
+  // { sources = conf.sources # [ "test/**/*.purs" ]
+     , dependencies = conf.dependencies # [ "spec" ] : List Text
+     , packages = https://example.com/packages.dhall sha256:0123abcd
+     }
+"#;
+        let mut import = |path: &str| {
+            match path {
+            "./spago.dhall" => Dhall::evaluate(
+                r#"{ name = "example", dependencies = [ "prelude" ], sources = [ "src/**/*.purs" ] }"#,
+                &mut |_| Dhall::Opaque,
+            )
+            .unwrap(),
+            _ => Dhall::Opaque,
+        }
+        };
+        let value = Dhall::evaluate(source, &mut import).unwrap();
+        assert_eq!(value.get("name"), Some(&text("example")));
+        assert_eq!(
+            value.get("sources"),
+            Some(&Dhall::List(vec![text("src/**/*.purs"), text("test/**/*.purs")]))
+        );
+        assert_eq!(
+            value.get("dependencies"),
+            Some(&Dhall::List(vec![text("prelude"), text("spec")]))
+        );
+        assert_eq!(value.get("packages"), Some(&Dhall::Opaque));
+    }
+}
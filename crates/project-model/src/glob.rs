@@ -0,0 +1,84 @@
+//! Matching source globs against paths.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Whether a `/`-separated relative path matches a glob, where `*` and `?`
+/// match within a segment and `**` matches any number of segments. Leading
+/// `./` segments are ignored on both sides.
+pub(crate) fn matches(glob: &str, path: &str) -> bool {
+    matches_segments(&segments(glob), &segments(path))
+}
+
+fn segments(text: &str) -> Vec<&str> {
+    text.split('/').filter(|&segment| !matches!(segment, "" | ".")).collect()
+}
+
+fn matches_segments(glob: &[&str], path: &[&str]) -> bool {
+    match (glob.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            matches_segments(rest, path) || (!path.is_empty() && matches_segments(glob, &path[1..]))
+        }
+        (Some((segment, glob)), Some((name, path))) => {
+            matches_segment(segment.as_bytes(), name.as_bytes()) && matches_segments(glob, path)
+        }
+        _ => false,
+    }
+}
+
+fn matches_segment(segment: &[u8], name: &[u8]) -> bool {
+    match (segment.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches_segment(rest, name)
+                || (!name.is_empty() && matches_segment(segment, &name[1..]))
+        }
+        (Some((b'?', segment)), Some((_, name))) => matches_segment(segment, name),
+        (Some((expected, segment)), Some((actual, name))) => {
+            expected == actual && matches_segment(segment, name)
+        }
+        _ => false,
+    }
+}
+
+/// Every file under `directory` that matches one of the globs, in order.
+/// Directories that can't be read are skipped.
+pub(crate) fn expand(directory: &Path, globs: &[String]) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else { continue };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(directory) else { continue };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if globs.iter().any(|glob| matches(glob, &relative)) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn match_source_globs() {
+        assert!(matches("src/**/*.purs", "src/Main.purs"));
+        assert!(matches("src/**/*.purs", "src/Data/Maybe.purs"));
+        assert!(matches("./src/**/*.purs", "src/Data/Maybe.purs"));
+        assert!(!matches("src/**/*.purs", "test/Main.purs"));
+        assert!(!matches("src/**/*.purs", "src/Main.js"));
+        assert!(matches("test/Test?.purs", "test/Test1.purs"));
+    }
+}
@@ -0,0 +1,219 @@
+//! The layout of a project: its packages, where their sources are, and what
+//! they depend on.
+//!
+//! The layout is read from the configuration of the build tool, and turned
+//! into the source roots whose files are loaded into the [`Vfs`].
+
+pub mod bower;
+mod dhall;
+mod glob;
+mod packages;
+pub mod spago;
+pub mod spago_dhall;
+mod watch;
+mod yaml;
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use vfs::{FileId, Vfs};
+
+pub use crate::watch::{WatchChanges, Watcher};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectError {
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    /// The configuration can't be parsed, at the given line.
+    Syntax {
+        path: PathBuf,
+        line: usize,
+    },
+    /// The configuration is valid, but not in the expected shape.
+    Shape {
+        path: PathBuf,
+        context: &'static str,
+    },
+    /// No configuration was found in the directory or its ancestors.
+    NotFound {
+        directory: PathBuf,
+    },
+}
+
+impl ProjectError {
+    pub fn message(&self) -> String {
+        match self {
+            ProjectError::Io { path, message } => {
+                format!("couldn't read {}: {message}", path.display())
+            }
+            ProjectError::Syntax { path, line } => {
+                format!("invalid syntax in {} at line {line}", path.display())
+            }
+            ProjectError::Shape { path, context } => {
+                format!("unexpected shape of {context} in {}", path.display())
+            }
+            ProjectError::NotFound { directory } => {
+                format!("no project configuration found in {}", directory.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectWorkspace {
+    /// The directory of the configuration that declares the workspace.
+    pub root: PathBuf,
+    pub packages: Vec<Package>,
+    /// The dependencies whose sources are already on disk.
+    pub libraries: Vec<Package>,
+    pub package_set: Option<PackageSet>,
+    /// Where the compiler writes its output, relative to which the externs
+    /// of compiled modules are found.
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub root: PathBuf,
+    pub sources: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub test: Option<TestConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestConfig {
+    pub main: String,
+    pub sources: Vec<String>,
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSet {
+    /// A version of the package set published to the registry.
+    Registry(String),
+    Url(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceRootKind {
+    Source,
+    Test,
+    /// The sources of a dependency, which aren't edited.
+    Library,
+}
+
+/// A directory of source files that belong to a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    pub package: String,
+    pub kind: SourceRootKind,
+    pub directory: PathBuf,
+    /// Globs relative to the directory.
+    pub globs: Vec<String>,
+}
+
+/// The names of the files that configure a project or pin its dependencies,
+/// a change to any of which means it has to be discovered again.
+pub const CONFIGS: [&str; 4] = [spago::CONFIG, spago_dhall::CONFIG, bower::CONFIG, "spago.lock"];
+
+/// Finds the project that `start` is in, from the closest directory with a
+/// `spago.yaml` that declares a workspace, a `spago.dhall`, or a
+/// `bower.json`, in that order of preference.
+pub fn discover(start: &Path) -> Result<ProjectWorkspace, ProjectError> {
+    for directory in start.ancestors() {
+        let config = directory.join(spago::CONFIG);
+        if spago::is_workspace(&config) {
+            return spago::load(&config);
+        }
+        let config = directory.join(spago_dhall::CONFIG);
+        if config.is_file() {
+            return spago_dhall::load(&config);
+        }
+        let config = directory.join(bower::CONFIG);
+        if config.is_file() {
+            return bower::load(&config);
+        }
+    }
+    Err(ProjectError::NotFound { directory: start.to_path_buf() })
+}
+
+pub(crate) fn read_error(path: &Path, error: io::Error) -> ProjectError {
+    ProjectError::Io { path: path.to_path_buf(), message: error.to_string() }
+}
+
+impl ProjectWorkspace {
+    /// The dependencies of the packages in the workspace that aren't
+    /// installed, which have to be installed before they can be resolved.
+    pub fn missing_dependencies(&self) -> Vec<&str> {
+        let mut missing = vec![];
+        for package in &self.packages {
+            let tests = package.test.iter().flat_map(|test| &test.dependencies);
+            for dependency in package.dependencies.iter().chain(tests) {
+                let installed = self.libraries.iter().any(|library| library.name == *dependency)
+                    || self.packages.iter().any(|package| package.name == *dependency);
+                if !installed && !missing.contains(&dependency.as_str()) {
+                    missing.push(dependency);
+                }
+            }
+        }
+        missing
+    }
+
+    /// Whether the workspace is built with `spago`, since it's configured
+    /// by a `spago.yaml` or a `spago.dhall`, rather than with `purs` alone.
+    pub fn is_spago(&self) -> bool {
+        [spago::CONFIG, spago_dhall::CONFIG].iter().any(|config| self.root.join(config).is_file())
+    }
+
+    pub fn source_roots(&self) -> Vec<SourceRoot> {
+        let mut roots = vec![];
+        for package in &self.packages {
+            roots.push(SourceRoot {
+                package: package.name.clone(),
+                kind: SourceRootKind::Source,
+                directory: package.root.clone(),
+                globs: package.sources.clone(),
+            });
+            if let Some(test) = &package.test {
+                roots.push(SourceRoot {
+                    package: package.name.clone(),
+                    kind: SourceRootKind::Test,
+                    directory: package.root.clone(),
+                    globs: test.sources.clone(),
+                });
+            }
+        }
+        for library in &self.libraries {
+            roots.push(SourceRoot {
+                package: library.name.clone(),
+                kind: SourceRootKind::Library,
+                directory: library.root.clone(),
+                globs: library.sources.clone(),
+            });
+        }
+        roots
+    }
+}
+
+impl SourceRoot {
+    pub fn files(&self) -> Vec<PathBuf> {
+        glob::expand(&self.directory, &self.globs)
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.directory).is_ok_and(|relative| {
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            self.globs.iter().any(|glob| glob::matches(glob, &relative))
+        })
+    }
+
+    /// Reads every file of the source root into the [`Vfs`].
+    pub fn load(&self, vfs: &mut Vfs) -> io::Result<Vec<FileId>> {
+        self.files().iter().map(|path| vfs.load(path)).collect()
+    }
+}
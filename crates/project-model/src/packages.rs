@@ -0,0 +1,131 @@
+//! The sources of dependencies that Spago has installed.
+//!
+//! Spago installs the whole closure of the dependencies of a workspace into
+//! its `.spago` directory, so every package found there is a library of the
+//! workspace. Registry packages are installed as `.spago/p/<name>-<version>`,
+//! while packages from Git, and every package of the legacy Spago, are
+//! installed as `<name>/<version>` instead.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{spago, Package};
+
+const INSTALLED: &str = ".spago";
+const REGISTRY: &str = "p";
+
+/// Every package installed for the workspace at `root`, by name. Where
+/// several versions of a package are installed, the latest is used.
+pub(crate) fn installed(root: &Path) -> Vec<Package> {
+    let directory = root.join(INSTALLED);
+    let mut packages: Vec<Package> = vec![];
+    let mut candidates = vec![];
+    for entry in directories(&directory) {
+        if entry.file_name().is_some_and(|name| name == REGISTRY) {
+            candidates.extend(directories(&entry));
+        } else {
+            candidates.push(entry);
+        }
+    }
+    for candidate in candidates {
+        let name = candidate.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let installed = if candidate.join("src").is_dir() {
+            vec![(split_version(&name).to_string(), candidate)]
+        } else {
+            directories(&candidate).into_iter().map(|version| (name.clone(), version)).collect()
+        };
+        for (name, root) in installed {
+            let package = read_package(&name, &root);
+            match packages.iter_mut().find(|package| package.name == name) {
+                Some(existing) if version(&existing.root) < version(&package.root) => {
+                    *existing = package
+                }
+                Some(_) => {}
+                None => packages.push(package),
+            }
+        }
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+/// A registry package is installed with its version, which starts at the
+/// last dash that's followed by a digit.
+fn split_version(name: &str) -> &str {
+    let version = name
+        .match_indices('-')
+        .rev()
+        .find(|&(index, _)| name[index + 1..].starts_with(|c: char| c.is_ascii_digit()));
+    version.map_or(name, |(index, _)| &name[..index])
+}
+
+/// The numbers in the version a package was installed at, which order its
+/// versions.
+fn version(root: &Path) -> Vec<u64> {
+    let name = root.file_name().unwrap_or_default().to_string_lossy();
+    name.split(|c: char| !c.is_ascii_digit()).filter_map(|number| number.parse().ok()).collect()
+}
+
+/// The package installed at `root`, whose dependencies are read from its own
+/// `spago.yaml` if it has one. Libraries are never tested, and their sources
+/// are always in `src`.
+fn read_package(name: &str, root: &Path) -> Package {
+    let dependencies = fs::read_to_string(root.join(spago::CONFIG))
+        .ok()
+        .and_then(|source| spago::parse_package(root, &source))
+        .map_or(vec![], |package| package.dependencies);
+    Package {
+        name: name.to_string(),
+        root: root.to_path_buf(),
+        sources: vec!["src/**/*.purs".to_string()],
+        dependencies,
+        test: None,
+    }
+}
+
+fn directories(directory: &Path) -> Vec<PathBuf> {
+    let mut directories: Vec<_> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    directories.sort();
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::installed;
+
+    #[test]
+    fn find_installed_packages() {
+        let root = std::env::temp_dir().join(format!("project-model-{}", std::process::id()));
+        for directory in [
+            ".spago/p/prelude-6.0.0/src",
+            ".spago/p/prelude-6.0.10/src",
+            ".spago/p/prelude-6.0.9/src",
+            ".spago/p/web-html-4.1.0/src",
+            ".spago/p/forked/4d3a1e/src",
+            ".spago/effect/v4.0.0/src",
+        ] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        fs::write(
+            root.join(".spago/p/web-html-4.1.0/spago.yaml"),
+            "package:\n  name: web-html\n  dependencies:\n    - prelude\n",
+        )
+        .unwrap();
+
+        let packages = installed(&root);
+        let names: Vec<_> = packages.iter().map(|package| package.name.as_str()).collect();
+        assert_eq!(names, ["effect", "forked", "prelude", "web-html"]);
+        assert!(packages[2].root.ends_with("prelude-6.0.10"));
+        assert_eq!(packages[3].dependencies, ["prelude"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
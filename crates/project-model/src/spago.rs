@@ -0,0 +1,174 @@
+//! Projects built with Spago, configured by a `spago.yaml`.
+//!
+//! The workspace is declared by the `spago.yaml` that has a `workspace`
+//! section, and each package by one with a `package` section. Spago doesn't
+//! configure where sources are; packages keep them in `src` and their tests
+//! in `test`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    packages, read_error, yaml::Yaml, Package, PackageSet, ProjectError, ProjectWorkspace,
+    TestConfig,
+};
+
+pub const CONFIG: &str = "spago.yaml";
+
+const SOURCES: &str = "src/**/*.purs";
+const TEST_SOURCES: &str = "test/**/*.purs";
+
+/// Whether the configuration at `path` declares a workspace, rather than
+/// only a package within one.
+pub fn is_workspace(path: &Path) -> bool {
+    let Ok(source) = fs::read_to_string(path) else { return false };
+    Yaml::parse(&source).is_ok_and(|yaml| yaml.get("workspace").is_some())
+}
+
+/// Reads the workspace declared by the configuration at `path`, along with
+/// the packages in its subdirectories and the packages that are installed
+/// for it.
+pub fn load(path: &Path) -> Result<ProjectWorkspace, ProjectError> {
+    let source = fs::read_to_string(path).map_err(|error| read_error(path, error))?;
+    let mut workspace = parse(path, &source)?;
+    let nested = workspace.packages.len();
+    let mut pending = subdirectories(&workspace.root, &workspace.output);
+    while let Some(directory) = pending.pop() {
+        let config = directory.join(CONFIG);
+        if let Ok(source) = fs::read_to_string(&config) {
+            let package = parse_package(&directory, &source)
+                .ok_or(ProjectError::Shape { path: config, context: "the package" })?;
+            workspace.packages.push(package);
+        }
+        pending.extend(subdirectories(&directory, &workspace.output));
+    }
+    workspace.packages[nested..].sort_by(|a, b| a.root.cmp(&b.root));
+    workspace.libraries = packages::installed(&workspace.root);
+    Ok(workspace)
+}
+
+/// The directories that packages may be in, which excludes hidden ones such
+/// as `.spago`, and the ones that tools write to.
+fn subdirectories(directory: &Path, output: &Path) -> Vec<PathBuf> {
+    let entries = fs::read_dir(directory).into_iter().flatten().filter_map(Result::ok);
+    entries
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path != output)
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            !name.starts_with('.') && name != "node_modules"
+        })
+        .collect()
+}
+
+/// Reads the package declared by a configuration, if it declares one.
+pub(crate) fn parse_package(root: &Path, source: &str) -> Option<Package> {
+    read_package(root, Yaml::parse(source).ok()?.get("package")?)
+}
+
+/// Reads the workspace declared by the configuration at `path`.
+pub fn parse(path: &Path, source: &str) -> Result<ProjectWorkspace, ProjectError> {
+    let yaml = Yaml::parse(source)
+        .map_err(|line| ProjectError::Syntax { path: path.to_path_buf(), line })?;
+    let shape = |context| ProjectError::Shape { path: path.to_path_buf(), context };
+    let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+    let workspace = yaml.get("workspace").ok_or(shape("the workspace"))?;
+    let package_set = match workspace.get("packageSet") {
+        None | Some(Yaml::Null) => None,
+        Some(set) => Some(match (set.get("registry"), set.get("url")) {
+            (Some(Yaml::String(version)), _) => PackageSet::Registry(version.clone()),
+            (_, Some(Yaml::String(url))) => PackageSet::Url(url.clone()),
+            _ => return Err(shape("the package set")),
+        }),
+    };
+    let output = match workspace.get("buildOpts").and_then(|options| options.get("output")) {
+        None | Some(Yaml::Null) => "output",
+        Some(output) => output.as_str().ok_or(shape("the output directory"))?,
+    };
+
+    let mut packages = vec![];
+    if let Some(package) = yaml.get("package") {
+        packages.push(read_package(&root, package).ok_or(shape("the package"))?);
+    }
+    let output = root.join(output);
+    Ok(ProjectWorkspace { root, packages, libraries: vec![], package_set, output })
+}
+
+fn read_package(root: &Path, package: &Yaml) -> Option<Package> {
+    let name = package.get("name")?.as_str()?.to_string();
+    let dependencies = read_dependencies(package.get("dependencies"))?;
+    let test = match package.get("test") {
+        None => None,
+        Some(test) => Some(TestConfig {
+            main: test.get("main")?.as_str()?.to_string(),
+            sources: vec![TEST_SOURCES.to_string()],
+            dependencies: read_dependencies(test.get("dependencies"))?,
+        }),
+    };
+    let sources = vec![SOURCES.to_string()];
+    Some(Package { name, root: root.to_path_buf(), sources, dependencies, test })
+}
+
+/// Dependencies are listed by name, or as a mapping from their name to the
+/// range of versions they're constrained to.
+fn read_dependencies(dependencies: Option<&Yaml>) -> Option<Vec<String>> {
+    let Some(dependencies) = dependencies else { return Some(vec![]) };
+    dependencies
+        .as_sequence()?
+        .iter()
+        .map(|dependency| match dependency {
+            Yaml::String(name) => Some(name.clone()),
+            Yaml::Mapping(entries) => match entries.as_slice() {
+                [(name, _)] => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::parse;
+    use crate::{PackageSet, SourceRootKind};
+
+    #[test]
+    fn read_spago_yaml() {
+        let source = r#"
+package:
+  name: example
+  dependencies:
+    - prelude
+    - effect: ">=4.0.0 <5.0.0"
+  test:
+    main: Test.Main
+    dependencies:
+      - spec
+workspace:
+  packageSet:
+    registry: 41.5.0
+  buildOpts:
+    output: build
+"#;
+        let workspace = parse(Path::new("/project/spago.yaml"), source).unwrap();
+        assert_eq!(workspace.package_set, Some(PackageSet::Registry("41.5.0".to_string())));
+        assert_eq!(workspace.output, Path::new("/project/build"));
+
+        let [package] = workspace.packages.as_slice() else { panic!() };
+        assert_eq!(package.name, "example");
+        assert_eq!(package.dependencies, ["prelude", "effect"]);
+        assert_eq!(package.test.as_ref().unwrap().main, "Test.Main");
+
+        let roots = workspace.source_roots();
+        let kinds: Vec<_> = roots.iter().map(|root| root.kind).collect();
+        assert_eq!(kinds, [SourceRootKind::Source, SourceRootKind::Test]);
+        assert!(roots[0].contains(Path::new("/project/src/Data/Example.purs")));
+        assert!(!roots[0].contains(Path::new("/project/test/Main.purs")));
+        assert!(roots[1].contains(Path::new("/project/test/Main.purs")));
+    }
+}
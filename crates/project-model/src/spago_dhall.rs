@@ -0,0 +1,78 @@
+//! Projects built with the legacy Spago, configured by a `spago.dhall`.
+//!
+//! The configuration lists the globs of its sources, which usually include
+//! the tests, so there's no separate test source root. Local imports are
+//! evaluated such that a configuration can extend another, but the package
+//! set is a remote import and is left opaque.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{dhall::Dhall, packages, read_error, Package, ProjectError, ProjectWorkspace};
+
+pub const CONFIG: &str = "spago.dhall";
+
+pub fn load(path: &Path) -> Result<ProjectWorkspace, ProjectError> {
+    let source = fs::read_to_string(path).map_err(|error| read_error(path, error))?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut importing = vec![path.to_path_buf()];
+    let value = evaluate(path, &source, directory, &mut importing)?;
+    let mut workspace = read(path, &value)?;
+    workspace.libraries = packages::installed(&workspace.root);
+    Ok(workspace)
+}
+
+/// Evaluates a configuration, reading the local files it imports relative
+/// to `directory`. Imports that can't be read, or that import themselves,
+/// are opaque.
+fn evaluate(
+    path: &Path,
+    source: &str,
+    directory: &Path,
+    importing: &mut Vec<PathBuf>,
+) -> Result<Dhall, ProjectError> {
+    let mut import = |import: &str| {
+        if !import.starts_with('.') {
+            return Dhall::Opaque;
+        }
+        let path = directory.join(import);
+        let Ok(source) = fs::read_to_string(&path) else { return Dhall::Opaque };
+        if importing.contains(&path) {
+            return Dhall::Opaque;
+        }
+        importing.push(path.clone());
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let value = evaluate(&path, &source, directory, importing).unwrap_or(Dhall::Opaque);
+        importing.pop();
+        value
+    };
+    Dhall::evaluate(source, &mut import).map_err(|offset| {
+        let line = 1 + source[..offset].matches('\n').count();
+        ProjectError::Syntax { path: path.to_path_buf(), line }
+    })
+}
+
+fn read(path: &Path, value: &Dhall) -> Result<ProjectWorkspace, ProjectError> {
+    let shape = |context| ProjectError::Shape { path: path.to_path_buf(), context };
+    let texts = |field, context| -> Result<Vec<String>, ProjectError> {
+        let elements = value.get(field).and_then(Dhall::as_list).ok_or(shape(context))?;
+        let texts = elements.iter().map(|element| element.as_text().map(str::to_string));
+        texts.collect::<Option<_>>().ok_or(shape(context))
+    };
+
+    let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let name = value.get("name").and_then(Dhall::as_text).ok_or(shape("the name"))?;
+    let dependencies = texts("dependencies", "the dependencies")?;
+    let sources = texts("sources", "the sources")?;
+    let package =
+        Package { name: name.to_string(), root: root.clone(), sources, dependencies, test: None };
+    Ok(ProjectWorkspace {
+        output: root.join("output"),
+        root,
+        packages: vec![package],
+        libraries: vec![],
+        package_set: None,
+    })
+}
@@ -0,0 +1,187 @@
+//! Noticing changes that are made to a project outside of the editor.
+//!
+//! The editor only tells the analyzer about the documents it has open, so
+//! switching branches or running a formatter over the project would leave
+//! the rest of the files stale. The [`Watcher`] scans what the project is
+//! made of for changes to their modification time or size each time it's
+//! polled, reading the files that changed into the [`Vfs`] such that they
+//! reach the database like any other change.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use vfs::Vfs;
+
+use crate::{read_error, ProjectError, ProjectWorkspace, SourceRoot, CONFIGS};
+
+/// When a file was last modified, and how large it was then, which tells
+/// apart most edits made within the resolution of the clock.
+type Stamp = Option<(SystemTime, u64)>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WatchChanges {
+    /// A configuration changed, or dependencies were installed, so the
+    /// project has to be discovered again.
+    pub project: bool,
+    /// The compiler wrote new output.
+    pub output: bool,
+    /// Source files that changed but couldn't be read, which are only read
+    /// again once they change again.
+    pub errors: Vec<ProjectError>,
+}
+
+#[derive(Debug)]
+pub struct Watcher {
+    roots: Vec<SourceRoot>,
+    files: FxHashMap<PathBuf, Stamp>,
+    /// Configurations, and the directories dependencies are installed in,
+    /// whose stamps are compared as a whole.
+    configs: Vec<(PathBuf, Stamp)>,
+    output: PathBuf,
+    outputs: FxHashMap<PathBuf, Stamp>,
+}
+
+impl Watcher {
+    /// Starts watching the project as it is on disk, which is expected to
+    /// have been loaded already.
+    pub fn new(workspace: &ProjectWorkspace) -> Watcher {
+        let mut configs = vec![];
+        for installed in [".spago", ".spago/p", "bower_components"] {
+            configs.push(workspace.root.join(installed));
+        }
+        let roots = workspace.packages.iter().map(|package| &package.root);
+        for root in std::iter::once(&workspace.root).chain(roots) {
+            for name in CONFIGS {
+                let path = root.join(name);
+                if !configs.contains(&path) {
+                    configs.push(path);
+                }
+            }
+        }
+
+        let roots = workspace.source_roots();
+        let files = roots.iter().flat_map(SourceRoot::files).map(|path| {
+            let stamp = stamp(&path);
+            (path, stamp)
+        });
+        Watcher {
+            files: files.collect(),
+            configs: configs.into_iter().map(|path| (path.clone(), stamp(&path))).collect(),
+            outputs: outputs(&workspace.output),
+            output: workspace.output.clone(),
+            roots,
+        }
+    }
+
+    /// Reads the source files that were created, modified or deleted since
+    /// the last poll into the [`Vfs`], and reports whether the project or
+    /// its output changed.
+    pub fn poll(&mut self, vfs: &mut Vfs) -> WatchChanges {
+        let mut changes = WatchChanges::default();
+        let mut seen = FxHashSet::default();
+        for path in self.roots.iter().flat_map(SourceRoot::files) {
+            let stamp = stamp(&path);
+            if self.files.get(&path) != Some(&stamp) {
+                if let Err(error) = vfs.load(&path) {
+                    changes.errors.push(read_error(&path, error));
+                }
+                self.files.insert(path.clone(), stamp);
+            }
+            seen.insert(path);
+        }
+        let deleted: Vec<_> =
+            self.files.keys().filter(|path| !seen.contains(*path)).cloned().collect();
+        for path in deleted {
+            vfs.set_disk_contents(&path, None);
+            self.files.remove(&path);
+        }
+
+        for (path, before) in &mut self.configs {
+            let after = stamp(path);
+            if *before != after {
+                *before = after;
+                changes.project = true;
+            }
+        }
+        let outputs = outputs(&self.output);
+        if outputs != self.outputs {
+            self.outputs = outputs;
+            changes.output = true;
+        }
+        changes
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The compiler writes the externs of each module into a directory of its
+/// own, so only their stamps are compared.
+fn outputs(output: &Path) -> FxHashMap<PathBuf, Stamp> {
+    let modules = fs::read_dir(output).into_iter().flatten().filter_map(Result::ok);
+    let externs = modules.map(|module| module.path().join("externs.cbor"));
+    externs.map(|path| (path.clone(), stamp(&path))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use vfs::{ChangeKind, Vfs};
+
+    use super::{WatchChanges, Watcher};
+
+    #[test]
+    fn poll_for_changes() {
+        let root = std::env::temp_dir().join(format!("project-model-watch-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("spago.yaml"), "package:\n  name: example\nworkspace: {}\n").unwrap();
+        fs::write(root.join("src/Main.purs"), "module Main where\n").unwrap();
+        fs::write(root.join("src/Util.purs"), "module Util where\n").unwrap();
+
+        let workspace = crate::discover(&root).unwrap();
+        let mut vfs = Vfs::default();
+        for source_root in workspace.source_roots() {
+            source_root.load(&mut vfs).unwrap();
+        }
+        vfs.take_changes();
+        let mut watcher = Watcher::new(&workspace);
+        assert_eq!(watcher.poll(&mut vfs), WatchChanges::default());
+
+        fs::write(root.join("src/Main.purs"), "module Main where\n\nmain = 0\n").unwrap();
+        fs::remove_file(root.join("src/Util.purs")).unwrap();
+        fs::write(root.join("src/Test.purs"), "module Test where\n").unwrap();
+        fs::create_dir_all(root.join("output/Main")).unwrap();
+        fs::write(root.join("output/Main/externs.cbor"), "").unwrap();
+        let changes = watcher.poll(&mut vfs);
+        assert_eq!(changes, WatchChanges { project: false, output: true, errors: vec![] });
+
+        let mut kinds: Vec<_> = vfs
+            .take_changes()
+            .into_iter()
+            .map(|change| {
+                let path = vfs.file_path(change.file_id);
+                (path.file_name().unwrap().to_str().unwrap().to_string(), change.kind)
+            })
+            .collect();
+        kinds.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            kinds,
+            [
+                ("Main.purs".to_string(), ChangeKind::Modified),
+                ("Test.purs".to_string(), ChangeKind::Created),
+                ("Util.purs".to_string(), ChangeKind::Deleted),
+            ]
+        );
+
+        fs::write(root.join("spago.yaml"), "package:\n  name: renamed\nworkspace: {}\n").unwrap();
+        assert!(watcher.poll(&mut vfs).project);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,379 @@
+//! A minimal YAML parser for project configuration files.
+//!
+//! Only the subset that Spago writes and documents is supported: block
+//! mappings and sequences nested by indentation, plain and quoted scalars,
+//! and flow collections on a single line, or documents written entirely in
+//! flow style such as JSON. Scalars are kept as strings, since
+//! a configuration only ever interprets them in context.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Yaml {
+    Null,
+    String(String),
+    Sequence(Vec<Yaml>),
+    Mapping(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    /// Parses a YAML document, returning the line of the first unexpected
+    /// construct if it's malformed or outside of the supported subset.
+    pub(crate) fn parse(source: &str) -> Result<Yaml, usize> {
+        if source.trim_start().starts_with(['{', '[']) {
+            // JSON is the subset of YAML written entirely in flow style, and
+            // may span several lines.
+            let mut flow = Flow { text: source, offset: 0 };
+            let value = flow.value();
+            flow.whitespace();
+            return match value {
+                Ok(value) if flow.offset == source.len() => Ok(value),
+                _ => Err(1 + source[..flow.offset].matches('\n').count()),
+            };
+        }
+        let mut lines = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let line = strip_comment(line).trim_end();
+            let content = line.trim_start();
+            if content.is_empty() || content == "---" {
+                continue;
+            }
+            lines.push(Line { number: index + 1, indent: line.len() - content.len(), content });
+        }
+        let mut parser = Parser { lines, index: 0 };
+        if parser.lines.is_empty() {
+            return Ok(Yaml::Null);
+        }
+        let value = parser.block(0)?;
+        match parser.lines.get(parser.index) {
+            Some(line) => Err(line.number),
+            None => Ok(value),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Yaml> {
+        match self {
+            Yaml::Mapping(entries) => {
+                entries.iter().find(|(entry, _)| entry == key).map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// The elements of a sequence, where a missing value counts as empty.
+    pub(crate) fn as_sequence(&self) -> Option<&[Yaml]> {
+        match self {
+            Yaml::Sequence(elements) => Some(elements),
+            Yaml::Null => Some(&[]),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn current(&self) -> Option<Line<'a>> {
+        self.lines.get(self.index).copied()
+    }
+
+    /// Parses the block that starts at the current line, which must be
+    /// indented by more than `parent`.
+    fn block(&mut self, parent: usize) -> Result<Yaml, usize> {
+        let Some(line) = self.current() else { return Ok(Yaml::Null) };
+        if line.indent < parent {
+            return Ok(Yaml::Null);
+        }
+        if is_sequence_item(line.content) {
+            self.sequence(line.indent)
+        } else if split_key(line.content).is_some() {
+            self.mapping(line.indent)
+        } else {
+            self.index += 1;
+            scalar(line.content).map_err(|_| line.number)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Yaml, usize> {
+        let mut elements = vec![];
+        while let Some(line) = self.current() {
+            if line.indent != indent || !is_sequence_item(line.content) {
+                break;
+            }
+            let rest = line.content[1..].trim_start();
+            if rest.is_empty() {
+                self.index += 1;
+                elements.push(self.nested(indent)?);
+            } else if split_key(rest).is_some() {
+                // A mapping that starts on the same line as its dash, whose
+                // keys line up with the first one.
+                let offset = line.content.len() - rest.len();
+                self.lines[self.index] = Line { indent: indent + offset, content: rest, ..line };
+                elements.push(self.mapping(indent + offset)?);
+            } else {
+                self.index += 1;
+                elements.push(scalar(rest).map_err(|_| line.number)?);
+            }
+        }
+        Ok(Yaml::Sequence(elements))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Yaml, usize> {
+        let mut entries = vec![];
+        while let Some(line) = self.current() {
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent || is_sequence_item(line.content) {
+                return Err(line.number);
+            }
+            let (key, rest) = split_key(line.content).ok_or(line.number)?;
+            let key = match scalar(key).map_err(|_| line.number)? {
+                Yaml::String(key) => key,
+                _ => return Err(line.number),
+            };
+            self.index += 1;
+            let value = if !rest.is_empty() {
+                scalar(rest).map_err(|_| line.number)?
+            } else if self
+                .current()
+                .is_some_and(|next| next.indent == indent && is_sequence_item(next.content))
+            {
+                // Sequences may be indented as far as the key they belong to.
+                self.sequence(indent)?
+            } else {
+                self.nested(indent)?
+            };
+            entries.push((key, value));
+        }
+        Ok(Yaml::Mapping(entries))
+    }
+
+    /// The block nested under a line indented by `indent`, if there is one.
+    fn nested(&mut self, indent: usize) -> Result<Yaml, usize> {
+        match self.current() {
+            Some(next) if next.indent > indent => self.block(next.indent),
+            _ => Ok(Yaml::Null),
+        }
+    }
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Splits `key: value` at the colon that ends the key, if there is one.
+fn split_key(content: &str) -> Option<(&str, &str)> {
+    if content.starts_with(['[', '{']) {
+        return None;
+    }
+    let end = match content.chars().next()? {
+        quote @ ('"' | '\'') => content[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let colon = content[end..]
+        .match_indices(':')
+        .map(|(index, _)| end + index)
+        .find(|&index| content[index + 1..].is_empty() || content[index + 1..].starts_with(' '))?;
+    Some((content[..colon].trim_end(), content[colon + 1..].trim()))
+}
+
+/// Removes a comment, which starts at a `#` after whitespace outside of
+/// quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, character) in line.char_indices() {
+        match (quote, character) {
+            (None, '#') if previous.is_whitespace() => return &line[..index],
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            _ => {}
+        }
+        previous = character;
+    }
+    line
+}
+
+fn scalar(text: &str) -> Result<Yaml, ()> {
+    let mut flow = Flow { text, offset: 0 };
+    let value = flow.value()?;
+    flow.whitespace();
+    if flow.offset == text.len() {
+        Ok(value)
+    } else {
+        Err(())
+    }
+}
+
+/// A scalar or a flow collection.
+struct Flow<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl Flow<'_> {
+    fn whitespace(&mut self) {
+        let rest = &self.text[self.offset..];
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.whitespace();
+        self.text[self.offset..].chars().next()
+    }
+
+    fn value(&mut self) -> Result<Yaml, ()> {
+        match self.peek() {
+            None => Ok(Yaml::Null),
+            Some('[') => {
+                self.offset += 1;
+                let mut elements = vec![];
+                while self.peek().ok_or(())? != ']' {
+                    elements.push(self.value()?);
+                    if self.peek() == Some(',') {
+                        self.offset += 1;
+                    }
+                }
+                self.offset += 1;
+                Ok(Yaml::Sequence(elements))
+            }
+            Some('{') => {
+                self.offset += 1;
+                let mut entries = vec![];
+                while self.peek().ok_or(())? != '}' {
+                    let Yaml::String(key) = self.value()? else { return Err(()) };
+                    let value = if self.peek() == Some(':') {
+                        self.offset += 1;
+                        self.value()?
+                    } else {
+                        Yaml::Null
+                    };
+                    entries.push((key, value));
+                    if self.peek() == Some(',') {
+                        self.offset += 1;
+                    }
+                }
+                self.offset += 1;
+                Ok(Yaml::Mapping(entries))
+            }
+            Some(quote @ ('"' | '\'')) => self.quoted(quote),
+            Some(_) => {
+                let rest = &self.text[self.offset..];
+                let end = rest
+                    .char_indices()
+                    .find(|&(index, character)| {
+                        matches!(character, ',' | ']' | '}')
+                            || (character == ':' && rest[index + 1..].starts_with([' ', ',']))
+                    })
+                    .map_or(rest.len(), |(index, _)| index);
+                if end == 0 {
+                    return Err(());
+                }
+                // Outside of a flow collection, the rest of the line is the
+                // scalar.
+                let end = if self.offset == 0 && !rest[end..].trim().is_empty() {
+                    rest.len()
+                } else {
+                    end
+                };
+                self.offset += end;
+                Ok(match rest[..end].trim() {
+                    "~" | "null" => Yaml::Null,
+                    plain => Yaml::String(plain.to_string()),
+                })
+            }
+        }
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<Yaml, ()> {
+        self.offset += 1;
+        let mut string = String::new();
+        let mut characters = self.text[self.offset..].char_indices();
+        while let Some((index, character)) = characters.next() {
+            match character {
+                '\'' if quote == '\'' => {
+                    if self.text[self.offset + index + 1..].starts_with('\'') {
+                        characters.next();
+                        string.push('\'');
+                    } else {
+                        self.offset += index + 1;
+                        return Ok(Yaml::String(string));
+                    }
+                }
+                '"' if quote == '"' => {
+                    self.offset += index + 1;
+                    return Ok(Yaml::String(string));
+                }
+                '\\' if quote == '"' => match characters.next().ok_or(())?.1 {
+                    'n' => string.push('\n'),
+                    't' => string.push('\t'),
+                    escaped => string.push(escaped),
+                },
+                character => string.push(character),
+            }
+        }
+        Err(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Yaml;
+
+    fn string(value: &str) -> Yaml {
+        Yaml::String(value.to_string())
+    }
+
+    #[test]
+    fn parse_spago_subset() {
+        let source = r#"
+package:
+  name: example # a comment
+  dependencies:
+  - prelude
+  - effect: ">=4.0.0 <5.0.0"
+  test:
+    main: 'Test.Main'
+    dependencies: [ spec, "spec-discovery" ]
+workspace:
+  extraPackages: {}
+"#;
+        let yaml = Yaml::parse(source).unwrap();
+        let package = yaml.get("package").unwrap();
+        assert_eq!(package.get("name"), Some(&string("example")));
+        assert_eq!(
+            package.get("dependencies"),
+            Some(&Yaml::Sequence(vec![
+                string("prelude"),
+                Yaml::Mapping(vec![("effect".to_string(), string(">=4.0.0 <5.0.0"))]),
+            ]))
+        );
+        let test = package.get("test").unwrap();
+        assert_eq!(test.get("main"), Some(&string("Test.Main")));
+        assert_eq!(
+            test.get("dependencies"),
+            Some(&Yaml::Sequence(vec![string("spec"), string("spec-discovery")]))
+        );
+        assert_eq!(
+            yaml.get("workspace").unwrap().get("extraPackages"),
+            Some(&Yaml::Mapping(vec![]))
+        );
+        assert_eq!(Yaml::parse("package:\n  name: a\n    extra: b\n"), Err(3));
+    }
+}
@@ -0,0 +1,234 @@
+//! Resolution of the names within value bodies.
+
+use lowering::{
+    arena::ArenaMap,
+    hir::{Binder, BinderId, Body, Expr, ExprId, Operator},
+    item_tree::ItemTree,
+    name::{ModuleName, Name, QualifiedName},
+    scope::ScopeTree,
+};
+
+use crate::{
+    imports::Imports,
+    interface::{Definition, Interface, Namespace},
+    prim,
+};
+
+/// The names in scope at the top level of a module: its own declarations,
+/// followed by its imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleScope {
+    pub declarations: Interface,
+    pub imports: Imports,
+}
+
+impl ModuleScope {
+    pub fn new(tree: &ItemTree, imports: Imports) -> ModuleScope {
+        ModuleScope { declarations: Interface::local(tree), imports }
+    }
+
+    /// The definitions a name could refer to. Declarations shadow imports,
+    /// and qualified names only refer to imports.
+    pub fn lookup(
+        &self,
+        qualifier: Option<ModuleName>,
+        namespace: Namespace,
+        name: Name,
+    ) -> Vec<Definition> {
+        if qualifier.is_none() {
+            if let Some(&definition) = self.declarations.names.get(namespace).get(&name) {
+                return vec![definition];
+            }
+        }
+        self.imports.lookup(qualifier, namespace, name).to_vec()
+    }
+
+    /// Returns `None` if the name doesn't resolve to anything. Names that
+    /// aren't declared or imported may still refer to the implicitly
+    /// imported `Prim` module.
+    pub fn resolve(&self, namespace: Namespace, name: QualifiedName) -> Option<Resolution> {
+        let mut definitions = self.lookup(name.qualifier, namespace, name.name);
+        match definitions.len() {
+            0 if name.qualifier.is_none() && prim::contains(namespace, name.name) => {
+                Some(Resolution::Prim(name.name))
+            }
+            0 => None,
+            1 => Some(Resolution::Global(definitions.remove(0))),
+            _ => Some(Resolution::Ambiguous(definitions)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// A name bound within the body.
+    Local(BinderId),
+    Global(Definition),
+    /// A type or class built into the compiler.
+    Prim(Name),
+    /// A name imported from several modules, which refer to different
+    /// definitions.
+    Ambiguous(Vec<Definition>),
+}
+
+/// The resolutions of the names in a body, which includes variables,
+/// constructors, and operators in both expressions and binders.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BodyResolution {
+    scopes: ScopeTree,
+    exprs: ArenaMap<ExprId, Resolution>,
+    binders: ArenaMap<BinderId, Resolution>,
+    /// The resolutions of the operators in each operator chain.
+    operators: ArenaMap<ExprId, Vec<Option<Resolution>>>,
+    binder_operators: ArenaMap<BinderId, Vec<Option<Resolution>>>,
+}
+
+impl BodyResolution {
+    pub fn new(body: &Body, scope: &ModuleScope) -> BodyResolution {
+        let scopes = ScopeTree::new(body);
+        let mut exprs = ArenaMap::default();
+        let mut operators = ArenaMap::default();
+        for (id, expr) in body.exprs.iter() {
+            let resolution = match expr {
+                &Expr::Variable(name) => {
+                    let local = match name.qualifier {
+                        None => scopes.resolve(id, name.name).map(Resolution::Local),
+                        Some(_) => None,
+                    };
+                    local.or_else(|| scope.resolve(Namespace::Value, name))
+                }
+                &Expr::Constructor(name) => scope.resolve(Namespace::Constructor, name),
+                &Expr::OperatorName(name) => scope.resolve(Namespace::Operator, name),
+                Expr::OperatorChain { tail, .. } => {
+                    let resolutions = tail.iter().map(|(operator, _)| match operator {
+                        &Operator::Symbol(name) => scope.resolve(Namespace::Operator, name),
+                        Operator::Infix(_) => None,
+                    });
+                    operators.insert(id, resolutions.collect());
+                    None
+                }
+                _ => None,
+            };
+            if let Some(resolution) = resolution {
+                exprs.insert(id, resolution);
+            }
+        }
+
+        let mut binders = ArenaMap::default();
+        let mut binder_operators = ArenaMap::default();
+        for (id, binder) in body.binders.iter() {
+            match binder {
+                &Binder::Constructor { name, .. } => {
+                    if let Some(resolution) = scope.resolve(Namespace::Constructor, name) {
+                        binders.insert(id, resolution);
+                    }
+                }
+                Binder::OperatorChain { tail, .. } => {
+                    let resolutions =
+                        tail.iter().map(|&(name, _)| scope.resolve(Namespace::Operator, name));
+                    binder_operators.insert(id, resolutions.collect());
+                }
+                _ => {}
+            }
+        }
+
+        exprs.shrink_to_fit();
+        binders.shrink_to_fit();
+        operators.shrink_to_fit();
+        binder_operators.shrink_to_fit();
+        BodyResolution { scopes, exprs, binders, operators, binder_operators }
+    }
+
+    pub fn scopes(&self) -> &ScopeTree {
+        &self.scopes
+    }
+
+    /// Returns `None` for expressions that aren't names, and for names that
+    /// don't resolve to anything.
+    pub fn resolution(&self, expr: ExprId) -> Option<&Resolution> {
+        self.exprs.get(expr)
+    }
+
+    /// Returns `None` for binders that aren't constructors, and for
+    /// constructors that don't resolve to anything.
+    pub fn binder_resolution(&self, binder: BinderId) -> Option<&Resolution> {
+        self.binders.get(binder)
+    }
+
+    /// The resolution of the operator at `index` in the tail of an operator
+    /// chain expression.
+    pub fn operator_resolution(&self, chain: ExprId, index: usize) -> Option<&Resolution> {
+        self.operators.get(chain)?.get(index)?.as_ref()
+    }
+
+    /// The resolution of the operator at `index` in the tail of an operator
+    /// chain binder.
+    pub fn binder_operator_resolution(&self, chain: BinderId, index: usize) -> Option<&Resolution> {
+        self.binder_operators.get(chain)?.get(index)?.as_ref()
+    }
+
+    /// Resolves `name` to the binder it refers to from `expr`, without
+    /// falling back to the module scope, e.g. for completions.
+    pub fn resolve_local(&self, expr: ExprId, name: Name) -> Option<BinderId> {
+        self.scopes.resolve(expr, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{
+        hir::{Binder, Declaration, Expr},
+        item_tree::ItemTree,
+        lower_module,
+        name::{ModuleName, Name},
+    };
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{BodyResolution, ModuleScope, Resolution};
+    use crate::{imports::Imports, interface::Interface};
+
+    #[test]
+    fn locals_shadow_module_scope() {
+        let prelude = "module Prelude where\n\nbind = 0\nlog = 0\npure = 0\n";
+        let (node, _) = parsing::parse_module(prelude);
+        let prelude = Interface::local(&ItemTree::lower(&ast::Module::cast(node).unwrap()));
+
+        let source =
+            "module Main where\n\nimport Prelude\n\nx = 0\n\nmain = do\n  x <- pure x\n  log x\n";
+        let (node, _) = parsing::parse_module(source);
+        let node = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&node);
+        let module = lower_module(&node);
+        let imports = Imports::resolve(&tree, |module| {
+            (module == ModuleName::new("Prelude")).then_some(&prelude)
+        });
+        let scope = ModuleScope::new(&tree, imports);
+
+        let (_, Declaration::Value(main)) = module.declarations.iter().nth(1).unwrap() else {
+            panic!()
+        };
+        let body = &module.bodies[main.body];
+        let resolution = BodyResolution::new(body, &scope);
+
+        let resolutions = |variable| {
+            let variables = body.exprs.iter().filter_map(|(id, expr)| match expr {
+                Expr::Variable(name) if name.name == Name::new(variable) => {
+                    resolution.resolution(id)
+                }
+                _ => None,
+            });
+            variables.collect::<Vec<_>>()
+        };
+        // The `x` in `log x` is bound by the `do` statement, and the one in
+        // `pure x` is the top-level `x`.
+        let [Resolution::Local(binder), Resolution::Global(global)] = resolutions("x")[..] else {
+            panic!("{:?}", resolutions("x"))
+        };
+        assert_eq!(body.binders[*binder], Binder::Variable(Name::new("x")));
+        assert_eq!(global.module, ModuleName::new("Main"));
+
+        let [Resolution::Global(bind)] = resolutions("bind")[..] else { panic!() };
+        assert_eq!(bind.module, ModuleName::new("Prelude"));
+    }
+}
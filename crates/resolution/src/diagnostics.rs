@@ -0,0 +1,231 @@
+//! Diagnostics for names that don't resolve.
+
+use lowering::{
+    hir::{Binder, Declaration, Expr, Module, Operator, Type},
+    name::{ModuleName, QualifiedName},
+    source_map::SourceMap,
+};
+use rowan::ast::SyntaxNodePtr;
+use syntax::PureScript;
+
+use crate::{
+    body::{BodyResolution, ModuleScope, Resolution},
+    interface::Namespace,
+    types::TypeResolution,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionDiagnostic {
+    /// A name that doesn't refer to anything in scope.
+    UnresolvedName { namespace: Namespace, name: QualifiedName, ptr: SyntaxNodePtr<PureScript> },
+    /// A name that refers to definitions imported from several modules.
+    AmbiguousName {
+        namespace: Namespace,
+        name: QualifiedName,
+        modules: Vec<ModuleName>,
+        ptr: SyntaxNodePtr<PureScript>,
+    },
+}
+
+impl ResolutionDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            ResolutionDiagnostic::UnresolvedName { namespace, name, .. } => {
+                format!("unknown {} {name}", describe(*namespace))
+            }
+            ResolutionDiagnostic::AmbiguousName { namespace, name, modules, .. } => {
+                let modules: Vec<_> = modules.iter().map(|module| module.as_str()).collect();
+                format!(
+                    "{} {name} is ambiguous, it could refer to any of {}",
+                    describe(*namespace),
+                    modules.join(", ")
+                )
+            }
+        }
+    }
+
+    pub fn ptr(&self) -> &SyntaxNodePtr<PureScript> {
+        match self {
+            ResolutionDiagnostic::UnresolvedName { ptr, .. }
+            | ResolutionDiagnostic::AmbiguousName { ptr, .. } => ptr,
+        }
+    }
+}
+
+fn describe(namespace: Namespace) -> &'static str {
+    match namespace {
+        Namespace::Value => "value",
+        Namespace::Constructor => "constructor",
+        Namespace::Operator => "operator",
+        Namespace::Type => "type",
+        Namespace::TypeOperator => "type operator",
+        Namespace::Class => "class",
+    }
+}
+
+/// Reports the names in a module that don't resolve to exactly one
+/// definition, in every body and type.
+pub fn check_names(
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+) -> Vec<ResolutionDiagnostic> {
+    let mut diagnostics = vec![];
+    for_each_name(module, source_map, scope, |namespace, name, resolution, ptr| {
+        let Some(ptr) = ptr.cloned() else { return };
+        match resolution {
+            None => {
+                diagnostics.push(ResolutionDiagnostic::UnresolvedName { namespace, name, ptr });
+            }
+            Some(Resolution::Ambiguous(definitions)) => {
+                let modules = definitions.iter().map(|definition| definition.module).collect();
+                diagnostics.push(ResolutionDiagnostic::AmbiguousName {
+                    namespace,
+                    name,
+                    modules,
+                    ptr,
+                });
+            }
+            Some(_) => {}
+        }
+    });
+    diagnostics
+}
+
+/// Calls `f` with every name a module refers to, along with its resolution
+/// and the syntax it was lowered from. Names in declarations, such as the
+/// class of an instance, aren't mapped to syntax.
+pub(crate) fn for_each_name(
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    mut f: impl FnMut(Namespace, QualifiedName, Option<&Resolution>, Option<&SyntaxNodePtr<PureScript>>),
+) {
+    for (id, body) in module.bodies.iter() {
+        let resolution = BodyResolution::new(body, scope);
+        let source_map = source_map.body(id);
+        for (id, expr) in body.exprs.iter() {
+            let ptr = source_map.expr_syntax(id);
+            let (namespace, name) = match expr {
+                &Expr::Variable(name) => (Namespace::Value, name),
+                &Expr::Constructor(name) => (Namespace::Constructor, name),
+                &Expr::OperatorName(name) => (Namespace::Operator, name),
+                Expr::OperatorChain { tail, .. } => {
+                    for (index, (operator, _)) in tail.iter().enumerate() {
+                        if let &Operator::Symbol(name) = operator {
+                            let resolution = resolution.operator_resolution(id, index);
+                            f(Namespace::Operator, name, resolution, ptr);
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            f(namespace, name, resolution.resolution(id), ptr);
+        }
+        for (id, binder) in body.binders.iter() {
+            let ptr = source_map.binder_syntax(id);
+            match binder {
+                &Binder::Constructor { name, .. } => {
+                    f(Namespace::Constructor, name, resolution.binder_resolution(id), ptr);
+                }
+                Binder::OperatorChain { tail, .. } => {
+                    for (index, &(name, _)) in tail.iter().enumerate() {
+                        let resolution = resolution.binder_operator_resolution(id, index);
+                        f(Namespace::Operator, name, resolution, ptr);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let resolution = TypeResolution::new(&module.types, scope);
+    for (id, type_) in module.types.iter() {
+        let ptr = source_map.type_syntax(id);
+        match type_ {
+            &Type::Constructor(name) => {
+                let namespace = resolution.namespace(id).unwrap_or(Namespace::Type);
+                f(namespace, name, resolution.resolution(id), ptr);
+            }
+            &Type::OperatorName(name) => {
+                f(Namespace::TypeOperator, name, resolution.resolution(id), ptr);
+            }
+            Type::OperatorChain { tail, .. } => {
+                for (index, &(name, _)) in tail.iter().enumerate() {
+                    let resolution = resolution.operator_resolution(id, index);
+                    f(Namespace::TypeOperator, name, resolution, ptr);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut declaration = |namespace, name: QualifiedName| {
+        f(namespace, name, scope.resolve(namespace, name).as_ref(), None);
+    };
+    for (_, item) in module.declarations.iter() {
+        match item {
+            Declaration::InstanceChain(instances) => {
+                for instance in instances {
+                    declaration(Namespace::Class, instance.head.class);
+                }
+            }
+            Declaration::Derive(derive) => declaration(Namespace::Class, derive.head.class),
+            Declaration::Fixity(fixity) => {
+                let namespace = if fixity.is_type {
+                    Namespace::Type
+                } else if fixity.target.name.as_str().starts_with(char::is_uppercase) {
+                    Namespace::Constructor
+                } else {
+                    Namespace::Value
+                };
+                declaration(namespace, fixity.target);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, lower_module_with_source_map, name::ModuleName};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_names;
+    use crate::{body::ModuleScope, imports::Imports, interface::Interface};
+
+    #[test]
+    fn report_unresolved_names() {
+        let prelude = "module Prelude where\n\ndata Maybe a = Just a | Nothing\n";
+        let (node, _) = parsing::parse_module(prelude);
+        let prelude = Interface::local(&ItemTree::lower(&ast::Module::cast(node).unwrap()));
+
+        let source = "module Main where\n\nimport Prelude as P\n\nf :: P.Maybe Int -> Maybe Unknown\nf (P.Just x) = x + missing\nf Nothing = P.Nothing\n";
+        let (node, _) = parsing::parse_module(source);
+        let node = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&node);
+        let (module, source_map) = lower_module_with_source_map(&node);
+        let imports = Imports::resolve(&tree, |module| {
+            (module == ModuleName::new("Prelude")).then_some(&prelude)
+        });
+        let scope = ModuleScope::new(&tree, imports);
+
+        let mut diagnostics: Vec<_> = check_names(&module, &source_map, &scope)
+            .iter()
+            .map(|diagnostic| (diagnostic.message(), &source[diagnostic.ptr().text_range()]))
+            .collect();
+        diagnostics.sort();
+        assert_eq!(
+            diagnostics,
+            [
+                ("unknown constructor Nothing".to_string(), "Nothing"),
+                ("unknown operator +".to_string(), "x + missing"),
+                ("unknown type Maybe".to_string(), "Maybe"),
+                ("unknown type Unknown".to_string(), "Unknown"),
+                ("unknown value missing".to_string(), "missing"),
+            ]
+        );
+    }
+}
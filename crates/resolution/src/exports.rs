@@ -0,0 +1,318 @@
+//! Resolution of export lists into the interface of a module.
+
+use lowering::{
+    hir::{DataMembers, Export},
+    item_tree::ItemTree,
+    name::{ModuleName, Name},
+};
+
+use crate::{
+    imports::{self, Imports},
+    interface::{Definition, Interface, Names, Namespace},
+};
+
+/// The interface of a module, as determined by its export list.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Exports {
+    pub interface: Interface,
+    diagnostics: Vec<ExportDiagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportDiagnostic {
+    /// An exported name that is neither declared nor imported.
+    UnknownName { namespace: Namespace, name: Name },
+    /// A constructor listed with a type that it doesn't belong to.
+    UnknownConstructor { type_: Name, name: Name },
+    /// A `module M` export where `M` is neither the module itself nor the
+    /// name or alias of an import.
+    UnknownModule { module: ModuleName },
+    /// A name exported with a different definition than it was exported
+    /// with before, by the `module` export if it's `Some`.
+    Conflict { namespace: Namespace, name: Name, module: Option<ModuleName> },
+}
+
+impl ExportDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            ExportDiagnostic::UnknownName { name, .. } => {
+                format!("cannot export {name}, which is not defined or imported")
+            }
+            ExportDiagnostic::UnknownConstructor { type_, name } => {
+                format!("{name} is not a constructor of {type_}")
+            }
+            ExportDiagnostic::UnknownModule { module } => {
+                format!("cannot export module {module}, which is not imported")
+            }
+            ExportDiagnostic::Conflict { name, .. } => {
+                format!("{name} is exported with more than one definition")
+            }
+        }
+    }
+}
+
+impl Exports {
+    /// Resolves the export list of a module, where `interface` returns the
+    /// interface of each module in the workspace for `module` re-exports.
+    pub fn resolve<'a>(
+        tree: &ItemTree,
+        imports: &Imports,
+        interface: impl Fn(ModuleName) -> Option<&'a Interface>,
+    ) -> Exports {
+        let local = Interface::local(tree);
+        let Some(exports) = &tree.exports else {
+            return Exports { interface: local, diagnostics: vec![] };
+        };
+
+        let mut result = Exports::default();
+        for export in exports {
+            let (namespace, name, members) = match export {
+                Export::Module(module) if Some(*module) == tree.name => {
+                    result.export_all(&local.names, &local, *module);
+                    continue;
+                }
+                Export::Module(module) => {
+                    result.export_module(tree, *module, &interface);
+                    continue;
+                }
+                Export::Value(name) => (Namespace::Value, *name, None),
+                Export::Operator(name) => (Namespace::Operator, *name, None),
+                Export::Type(name, members) => (Namespace::Type, *name, members.as_ref()),
+                Export::TypeOperator(name) => (Namespace::TypeOperator, *name, None),
+                Export::Class(name) => (Namespace::Class, *name, None),
+            };
+
+            // Declarations shadow imports of the same name.
+            let definition = match local.names.get(namespace).get(&name) {
+                Some(&definition) => definition,
+                None => match imports.lookup(None, namespace, name).first() {
+                    Some(&definition) => definition,
+                    None => {
+                        result.diagnostics.push(ExportDiagnostic::UnknownName { namespace, name });
+                        continue;
+                    }
+                },
+            };
+            result.export(namespace, name, definition, None);
+            if namespace != Namespace::Type {
+                continue;
+            }
+
+            let constructors = if local.names.types.get(&name) == Some(&definition) {
+                local.type_constructors.get(&name).cloned().unwrap_or_default()
+            } else {
+                let imported = imports.unqualified.constructors.iter();
+                let imported =
+                    imported.filter_map(|(&name, definitions)| Some((name, *definitions.first()?)));
+                constructors_of(definition, imported)
+            };
+            let exported = match members {
+                None => vec![],
+                Some(DataMembers::All) => constructors,
+                Some(DataMembers::Enumerated(names)) => {
+                    let (exported, unknown): (Vec<Name>, Vec<Name>) =
+                        names.iter().partition(|name| constructors.contains(name));
+                    for constructor in unknown {
+                        let type_ = name;
+                        let name = constructor;
+                        result
+                            .diagnostics
+                            .push(ExportDiagnostic::UnknownConstructor { type_, name });
+                    }
+                    exported
+                }
+            };
+            for &constructor in &exported {
+                let definition =
+                    local.names.constructors.get(&constructor).or_else(|| {
+                        imports.lookup(None, Namespace::Constructor, constructor).first()
+                    });
+                if let Some(&definition) = definition {
+                    result.export(Namespace::Constructor, constructor, definition, None);
+                }
+            }
+            result.export_constructors(name, exported);
+        }
+
+        result
+    }
+
+    pub fn diagnostics(&self) -> &[ExportDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Re-exports every name brought into scope by the imports of `module`,
+    /// which is either the name of an unaliased import or an alias.
+    fn export_module<'a>(
+        &mut self,
+        tree: &ItemTree,
+        module: ModuleName,
+        interface: impl Fn(ModuleName) -> Option<&'a Interface>,
+    ) {
+        let mut found = false;
+        for import in &tree.imports {
+            if import.alias.unwrap_or(import.module) != module {
+                continue;
+            }
+            found = true;
+            if let Some(interface) = interface(import.module) {
+                // Diagnostics for the import itself are reported by `Imports`.
+                let names = imports::imported_names(import, interface, &mut vec![]);
+                self.export_all(&names, interface, module);
+            }
+        }
+        if !found {
+            self.diagnostics.push(ExportDiagnostic::UnknownModule { module });
+        }
+    }
+
+    /// Exports `names` for the `module` export, along with the constructors
+    /// they include for each type in `interface`.
+    fn export_all(&mut self, names: &Names<Definition>, interface: &Interface, module: ModuleName) {
+        for namespace in Namespace::ALL {
+            for (&name, &definition) in names.get(namespace) {
+                self.export(namespace, name, definition, Some(module));
+            }
+        }
+        for &type_ in names.types.keys() {
+            let available = interface.type_constructors.get(&type_).map_or(&[][..], Vec::as_slice);
+            let constructors =
+                available.iter().filter(|name| names.constructors.contains_key(name));
+            self.export_constructors(type_, constructors.copied());
+        }
+    }
+
+    /// Exports a single name, unless it's already exported with another
+    /// definition, in which case the first one is kept.
+    fn export(
+        &mut self,
+        namespace: Namespace,
+        name: Name,
+        definition: Definition,
+        module: Option<ModuleName>,
+    ) {
+        let exported = self.interface.names.get_mut(namespace);
+        match exported.get(&name) {
+            Some(&previous) if previous != definition => {
+                self.diagnostics.push(ExportDiagnostic::Conflict { namespace, name, module });
+            }
+            Some(_) => {}
+            None => {
+                exported.insert(name, definition);
+            }
+        }
+    }
+
+    fn export_constructors(&mut self, type_: Name, constructors: impl IntoIterator<Item = Name>) {
+        let exported = self.interface.type_constructors.entry(type_).or_default();
+        for constructor in constructors {
+            if !exported.contains(&constructor) {
+                exported.push(constructor);
+            }
+        }
+    }
+}
+
+/// The constructors among `constructors` that belong to the type
+/// `definition`, in the order they're declared.
+fn constructors_of(
+    definition: Definition,
+    constructors: impl Iterator<Item = (Name, Definition)>,
+) -> Vec<Name> {
+    let mut constructors: Vec<_> = constructors
+        .filter(|(_, constructor)| {
+            constructor.module == definition.module && constructor.item == definition.item
+        })
+        .collect();
+    constructors.sort_by_key(|(_, constructor)| constructor.member);
+    constructors.into_iter().map(|(name, _)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::name::{ModuleName, Name};
+
+    use super::{ExportDiagnostic, Exports};
+    use crate::{
+        imports::Imports,
+        interface::{Interface, Namespace},
+        tests::item_tree,
+    };
+
+    #[test]
+    fn resolve_export_lists() {
+        let maybe = item_tree(
+            "module Data.Maybe (Maybe(..), fromMaybe) where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 0\nhidden = 0\n",
+        );
+        let maybe_exports = Exports::resolve(&maybe, &Imports::default(), |_| None);
+        let names = |interface: &Interface, namespace| {
+            let mut names: Vec<_> =
+                interface.names.get(namespace).keys().map(|name| name.as_str()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&maybe_exports.interface, Namespace::Value), ["fromMaybe"]);
+        assert_eq!(names(&maybe_exports.interface, Namespace::Constructor), ["Just", "Nothing"]);
+
+        let main = item_tree(
+            "module Main (module Exports, Maybe(Just, Nope), T, module Main, missing, module Nope) where\n\nimport Data.Maybe (Maybe(..))\nimport Data.Maybe (fromMaybe) as Exports\n\ndata T = T\nmain = 0\n",
+        );
+        let lookup =
+            |module| (module == ModuleName::new("Data.Maybe")).then_some(&maybe_exports.interface);
+        let imports = Imports::resolve(&main, lookup);
+        let exports = Exports::resolve(&main, &imports, lookup);
+
+        let interface = &exports.interface;
+        assert_eq!(names(interface, Namespace::Value), ["fromMaybe", "main"]);
+        assert_eq!(names(interface, Namespace::Type), ["Maybe", "T"]);
+        // Constructors of `T` are exported through `module Main`.
+        assert_eq!(names(interface, Namespace::Constructor), ["Just", "T"]);
+        assert_eq!(interface.type_constructors[&Name::new("Maybe")], [Name::new("Just")]);
+        assert_eq!(
+            exports.diagnostics(),
+            [
+                ExportDiagnostic::UnknownConstructor {
+                    type_: Name::new("Maybe"),
+                    name: Name::new("Nope")
+                },
+                ExportDiagnostic::UnknownName {
+                    namespace: Namespace::Value,
+                    name: Name::new("missing")
+                },
+                ExportDiagnostic::UnknownModule { module: ModuleName::new("Nope") },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_conflicting_exports() {
+        let a = item_tree("module A where\n\nx = 0\n");
+        let b = item_tree("module B where\n\nx = 0\ny = 0\n");
+        let a_exports = Exports::resolve(&a, &Imports::default(), |_| None);
+        let b_exports = Exports::resolve(&b, &Imports::default(), |_| None);
+        let main =
+            item_tree("module Main (module A, module B, y) where\n\nimport A\nimport B\n\ny = 0\n");
+        let lookup = |module| match module {
+            module if module == ModuleName::new("A") => Some(&a_exports.interface),
+            module if module == ModuleName::new("B") => Some(&b_exports.interface),
+            _ => None,
+        };
+        let imports = Imports::resolve(&main, lookup);
+        let exports = Exports::resolve(&main, &imports, lookup);
+        assert_eq!(
+            exports.diagnostics(),
+            [
+                ExportDiagnostic::Conflict {
+                    namespace: Namespace::Value,
+                    name: Name::new("x"),
+                    module: Some(ModuleName::new("B")),
+                },
+                ExportDiagnostic::Conflict {
+                    namespace: Namespace::Value,
+                    name: Name::new("y"),
+                    module: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Re-association of operator chains by the fixities of their operators.
+//!
+//! Lowering keeps operator chains flat, since the fixity of an operator is
+//! only known once it's resolved, possibly to a declaration in another
+//! module. The trees built here nest the operands of each chain the way the
+//! compiler does.
+
+use lowering::{
+    arena::{Arena, ArenaMap},
+    hir::{Associativity, Binder, BinderId, Body, Expr, ExprId, Module, Operator, Type, TypeId},
+    item_tree::{Item, ItemTree},
+    name::ModuleName,
+    source_map::SourceMap,
+};
+use rowan::ast::SyntaxNodePtr;
+use rustc_hash::FxHashMap;
+use syntax::PureScript;
+
+use crate::{
+    body::{BodyResolution, ModuleScope, Resolution},
+    interface::{Definition, Names},
+    types::TypeResolution,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: u8,
+}
+
+impl Fixity {
+    /// The fixity of functions applied with backticks, and of operators that
+    /// don't resolve to anything.
+    pub const DEFAULT: Fixity = Fixity { associativity: Associativity::Left, precedence: 9 };
+}
+
+/// The fixities of every operator in scope in a module, keyed by the fixity
+/// declaration they resolve to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Fixities {
+    fixities: FxHashMap<Definition, Fixity>,
+}
+
+impl Fixities {
+    /// Collects the fixities of the operators in `scope`, where `tree`
+    /// returns the [`ItemTree`] of each module in the workspace, including
+    /// the module itself.
+    pub fn new<'a>(
+        scope: &ModuleScope,
+        tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+    ) -> Fixities {
+        let declared =
+            [&scope.declarations.names.operators, &scope.declarations.names.type_operators];
+        let declared = declared.into_iter().flat_map(|names| names.values());
+        let imported = std::iter::once(&scope.imports.unqualified)
+            .chain(scope.imports.qualified.values())
+            .flat_map(|names: &Names<Vec<Definition>>| {
+                names.operators.values().chain(names.type_operators.values()).flatten()
+            });
+
+        let mut fixities = FxHashMap::default();
+        for &definition in declared.chain(imported) {
+            let Some(tree) = tree(definition.module) else { continue };
+            if let Item::Fixity(fixity) = &tree.items[definition.item] {
+                let fixity =
+                    Fixity { associativity: fixity.associativity, precedence: fixity.precedence };
+                fixities.insert(definition, fixity);
+            }
+        }
+        Fixities { fixities }
+    }
+
+    pub fn fixity(&self, resolution: Option<&Resolution>) -> Fixity {
+        match resolution {
+            Some(Resolution::Global(definition)) => {
+                self.fixities.get(definition).copied().unwrap_or(Fixity::DEFAULT)
+            }
+            _ => Fixity::DEFAULT,
+        }
+    }
+}
+
+/// An operator chain nested by fixity. Operands and operators are referred
+/// to by their position in the chain: operand `0` is its head, and operator
+/// `i` is followed by operand `i + 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperatorTree {
+    Operand(usize),
+    Operator { index: usize, left: Box<OperatorTree>, right: Box<OperatorTree> },
+}
+
+/// Two adjacent operators of the same precedence that can't be associated
+/// without parentheses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub left: usize,
+    pub right: usize,
+    /// Both operators are non-associative, rather than of different
+    /// associativities.
+    pub non_associative: bool,
+}
+
+/// Re-associates a chain with operators of the given fixities. Conflicting
+/// operators are associated to the left, like operators of the same
+/// associativity would be, and reported.
+pub fn associate(fixities: &[Fixity]) -> (OperatorTree, Vec<Conflict>) {
+    let mut conflicts = vec![];
+    let mut operands = vec![OperatorTree::Operand(0)];
+    let mut operators: Vec<usize> = vec![];
+
+    let reduce = |operands: &mut Vec<OperatorTree>, index| {
+        let right = operands.pop().unwrap();
+        let left = operands.pop().unwrap();
+        operands.push(OperatorTree::Operator {
+            index,
+            left: Box::new(left),
+            right: Box::new(right),
+        });
+    };
+
+    for (index, fixity) in fixities.iter().enumerate() {
+        while let Some(&top) = operators.last() {
+            let previous = fixities[top];
+            let reduces = if previous.precedence != fixity.precedence {
+                previous.precedence > fixity.precedence
+            } else {
+                match (previous.associativity, fixity.associativity) {
+                    (Associativity::Left, Associativity::Left) => true,
+                    (Associativity::Right, Associativity::Right) => false,
+                    (left, right) => {
+                        let non_associative =
+                            left == Associativity::None && right == Associativity::None;
+                        conflicts.push(Conflict { left: top, right: index, non_associative });
+                        true
+                    }
+                }
+            };
+            if !reduces {
+                break;
+            }
+            operators.pop();
+            reduce(&mut operands, top);
+        }
+        operators.push(index);
+        operands.push(OperatorTree::Operand(index + 1));
+    }
+    while let Some(index) = operators.pop() {
+        reduce(&mut operands, index);
+    }
+    (operands.pop().unwrap(), conflicts)
+}
+
+/// The re-associated operator chains of a body, or of the types of a module.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Chains {
+    exprs: ArenaMap<ExprId, (OperatorTree, Vec<Conflict>)>,
+    binders: ArenaMap<BinderId, (OperatorTree, Vec<Conflict>)>,
+    types: ArenaMap<TypeId, (OperatorTree, Vec<Conflict>)>,
+}
+
+impl Chains {
+    pub fn body(body: &Body, resolution: &BodyResolution, fixities: &Fixities) -> Chains {
+        let mut chains = Chains::default();
+        for (id, expr) in body.exprs.iter() {
+            if let Expr::OperatorChain { tail, .. } = expr {
+                let operators =
+                    tail.iter().enumerate().map(|(index, (operator, _))| match operator {
+                        Operator::Symbol(_) => {
+                            fixities.fixity(resolution.operator_resolution(id, index))
+                        }
+                        Operator::Infix(_) => Fixity::DEFAULT,
+                    });
+                chains.exprs.insert(id, associate(&operators.collect::<Vec<_>>()));
+            }
+        }
+        for (id, binder) in body.binders.iter() {
+            if let Binder::OperatorChain { tail, .. } = binder {
+                let operators = (0..tail.len())
+                    .map(|index| fixities.fixity(resolution.binder_operator_resolution(id, index)));
+                chains.binders.insert(id, associate(&operators.collect::<Vec<_>>()));
+            }
+        }
+        chains
+    }
+
+    pub fn types(types: &Arena<Type>, resolution: &TypeResolution, fixities: &Fixities) -> Chains {
+        let mut chains = Chains::default();
+        for (id, type_) in types.iter() {
+            if let Type::OperatorChain { tail, .. } = type_ {
+                let operators = (0..tail.len())
+                    .map(|index| fixities.fixity(resolution.operator_resolution(id, index)));
+                chains.types.insert(id, associate(&operators.collect::<Vec<_>>()));
+            }
+        }
+        chains
+    }
+
+    pub fn expr(&self, chain: ExprId) -> Option<&OperatorTree> {
+        self.exprs.get(chain).map(|(tree, _)| tree)
+    }
+
+    pub fn binder(&self, chain: BinderId) -> Option<&OperatorTree> {
+        self.binders.get(chain).map(|(tree, _)| tree)
+    }
+
+    pub fn type_(&self, chain: TypeId) -> Option<&OperatorTree> {
+        self.types.get(chain).map(|(tree, _)| tree)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixityDiagnostic {
+    /// The names of the conflicting operators, where functions applied with
+    /// backticks are named by their syntax.
+    pub operators: (String, String),
+    pub non_associative: bool,
+    /// The operator chain the operators are in.
+    pub ptr: SyntaxNodePtr<PureScript>,
+}
+
+impl FixityDiagnostic {
+    pub fn message(&self) -> String {
+        let (left, right) = &self.operators;
+        if self.non_associative {
+            format!(
+                "{left} and {right} are non-associative, and can't be chained without parentheses"
+            )
+        } else {
+            format!("{left} and {right} have the same precedence but different associativity, and can't be chained without parentheses")
+        }
+    }
+}
+
+/// Reports the operators in a module that can't be chained, in every body
+/// and type.
+pub fn check_fixities(
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    fixities: &Fixities,
+) -> Vec<FixityDiagnostic> {
+    let mut diagnostics = vec![];
+    let mut report = |conflicts: &[Conflict],
+                      name: &dyn Fn(usize) -> String,
+                      ptr: Option<&SyntaxNodePtr<PureScript>>| {
+        let Some(ptr) = ptr else { return };
+        for conflict in conflicts {
+            diagnostics.push(FixityDiagnostic {
+                operators: (name(conflict.left), name(conflict.right)),
+                non_associative: conflict.non_associative,
+                ptr: ptr.clone(),
+            });
+        }
+    };
+
+    for (id, body) in module.bodies.iter() {
+        let resolution = BodyResolution::new(body, scope);
+        let chains = Chains::body(body, &resolution, fixities);
+        let source_map = source_map.body(id);
+        for (chain, (_, conflicts)) in chains.exprs.iter() {
+            let Expr::OperatorChain { tail, .. } = &body.exprs[chain] else { continue };
+            let name = |index: usize| match &tail[index].0 {
+                Operator::Symbol(name) => name.to_string(),
+                &Operator::Infix(function) => match &body.exprs[function] {
+                    Expr::Variable(name) => format!("`{name}`"),
+                    _ => "an infix function".to_string(),
+                },
+            };
+            report(conflicts, &name, source_map.expr_syntax(chain));
+        }
+        for (chain, (_, conflicts)) in chains.binders.iter() {
+            let Binder::OperatorChain { tail, .. } = &body.binders[chain] else { continue };
+            let name = |index: usize| tail[index].0.to_string();
+            report(conflicts, &name, source_map.binder_syntax(chain));
+        }
+    }
+
+    let resolution = TypeResolution::new(&module.types, scope);
+    let chains = Chains::types(&module.types, &resolution, fixities);
+    for (chain, (_, conflicts)) in chains.types.iter() {
+        let Type::OperatorChain { tail, .. } = &module.types[chain] else { continue };
+        let name = |index: usize| tail[index].0.to_string();
+        report(conflicts, &name, source_map.type_syntax(chain));
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::hir::Associativity;
+
+    use super::{associate, Conflict, Fixity, OperatorTree};
+
+    fn fixity(associativity: Associativity, precedence: u8) -> Fixity {
+        Fixity { associativity, precedence }
+    }
+
+    fn pretty(tree: &OperatorTree) -> String {
+        match tree {
+            OperatorTree::Operand(index) => index.to_string(),
+            OperatorTree::Operator { index, left, right } => {
+                format!("({} {index} {})", pretty(left), pretty(right))
+            }
+        }
+    }
+
+    #[test]
+    fn associate_by_fixity() {
+        let plus = fixity(Associativity::Left, 6);
+        let times = fixity(Associativity::Left, 7);
+        let cons = fixity(Associativity::Right, 6);
+        let equals = fixity(Associativity::None, 4);
+
+        let (tree, conflicts) = associate(&[plus, times, plus, plus]);
+        assert_eq!(pretty(&tree), "(((0 0 (1 1 2)) 2 3) 3 4)");
+        assert_eq!(conflicts, []);
+
+        let (tree, _) = associate(&[cons, cons, times]);
+        assert_eq!(pretty(&tree), "(0 0 (1 1 (2 2 3)))");
+
+        let (tree, conflicts) = associate(&[equals, equals]);
+        assert_eq!(pretty(&tree), "((0 0 1) 1 2)");
+        assert_eq!(conflicts, [Conflict { left: 0, right: 1, non_associative: true }]);
+
+        let (_, conflicts) = associate(&[plus, cons]);
+        assert_eq!(conflicts, [Conflict { left: 0, right: 1, non_associative: false }]);
+    }
+}
@@ -0,0 +1,385 @@
+//! The graph of modules in a workspace.
+//!
+//! The [`ModuleGraph`] is built from the headers of each module's
+//! [`ItemTree`], which makes it independent of edits to declarations. Its
+//! reverse edges determine which modules have to be re-resolved when the
+//! interface of a module changes.
+
+use std::collections::VecDeque;
+
+use lowering::{
+    arena::{Arena, Idx},
+    item_tree::ItemTree,
+    lower_module_name,
+    name::ModuleName,
+};
+use rowan::ast::{AstNode, SyntaxNodePtr};
+use rustc_hash::FxHashMap;
+use syntax::{ast, PureScript};
+
+use crate::prim;
+
+pub type ModuleId = Idx<ModuleNode>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleNode {
+    pub name: ModuleName,
+    /// The modules imported by this module, without duplicates.
+    pub imports: Vec<ModuleId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphDiagnostic {
+    /// A module with the same name as a module that was seen before, which
+    /// is left out of the graph.
+    DuplicateModule { name: ModuleName },
+    /// An import of a module that isn't in the workspace.
+    UnknownModule { module: ModuleName, import: ModuleName },
+    /// Modules that import each other, in the order they import each other.
+    Cycle { modules: Vec<ModuleName> },
+}
+
+impl GraphDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            GraphDiagnostic::DuplicateModule { name } => {
+                format!("module {name} is defined more than once")
+            }
+            GraphDiagnostic::UnknownModule { import, .. } => {
+                format!("module {import} could not be found")
+            }
+            GraphDiagnostic::Cycle { modules } => {
+                let mut modules: Vec<_> = modules.iter().map(|module| module.as_str()).collect();
+                modules.push(modules[0]);
+                format!("modules form an import cycle: {}", modules.join(" -> "))
+            }
+        }
+    }
+}
+
+/// An import statement that is part of an import cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCycle {
+    /// The importing module, followed by the shortest path of imports that
+    /// leads back to it.
+    pub path: Vec<ModuleName>,
+    pub ptr: SyntaxNodePtr<PureScript>,
+}
+
+impl ImportCycle {
+    pub fn message(&self) -> String {
+        let path: Vec<_> = self.path.iter().map(|module| module.as_str()).collect();
+        format!("this import forms a cycle: {}", path.join(" -> "))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ModuleGraph {
+    modules: Arena<ModuleNode>,
+    by_name: FxHashMap<ModuleName, ModuleId>,
+    dependents: Vec<Vec<ModuleId>>,
+    cycles: Vec<Vec<ModuleId>>,
+    diagnostics: Vec<GraphDiagnostic>,
+}
+
+impl ModuleGraph {
+    /// Builds the graph from the item trees of every module in a workspace.
+    /// Modules without a header are skipped.
+    pub fn new<'a>(trees: impl IntoIterator<Item = &'a ItemTree>) -> ModuleGraph {
+        let mut graph = ModuleGraph::default();
+
+        let mut headers = vec![];
+        for tree in trees {
+            let Some(name) = tree.name else { continue };
+            if graph.by_name.contains_key(&name) {
+                graph.diagnostics.push(GraphDiagnostic::DuplicateModule { name });
+                continue;
+            }
+            let id = graph.modules.alloc(ModuleNode { name, imports: vec![] });
+            graph.by_name.insert(name, id);
+            headers.push((id, tree));
+        }
+
+        graph.dependents = vec![vec![]; graph.modules.len()];
+        for (id, tree) in headers {
+            let module = graph.modules[id].name;
+            let mut imports = vec![];
+            for import in &tree.imports {
+                match graph.by_name.get(&import.module) {
+                    Some(&import) if !imports.contains(&import) => imports.push(import),
+                    Some(_) => {}
+                    None if prim::is_prim_module(import.module) => {}
+                    None => graph
+                        .diagnostics
+                        .push(GraphDiagnostic::UnknownModule { module, import: import.module }),
+                }
+            }
+            for &import in &imports {
+                graph.dependents[import.into_raw() as usize].push(id);
+            }
+            graph.modules[id].imports = imports;
+        }
+
+        graph.cycles = Tarjan::run(&graph.modules);
+        for cycle in &graph.cycles {
+            let modules = cycle.iter().map(|&id| graph.modules[id].name).collect();
+            graph.diagnostics.push(GraphDiagnostic::Cycle { modules });
+        }
+
+        graph
+    }
+
+    pub fn id(&self, name: ModuleName) -> Option<ModuleId> {
+        self.by_name.get(&name).copied()
+    }
+
+    pub fn module(&self, id: ModuleId) -> &ModuleNode {
+        &self.modules[id]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ModuleId, &ModuleNode)> {
+        self.modules.iter()
+    }
+
+    pub fn dependencies(&self, id: ModuleId) -> &[ModuleId] {
+        &self.modules[id].imports
+    }
+
+    /// The modules that import `id` directly.
+    pub fn reverse_dependencies(&self, id: ModuleId) -> &[ModuleId] {
+        &self.dependents[id.into_raw() as usize]
+    }
+
+    /// The modules that import `id` directly or through other modules, in
+    /// the order they're reached from `id`. This doesn't include `id`, unless
+    /// it's part of a cycle.
+    pub fn transitive_reverse_dependencies(&self, id: ModuleId) -> Vec<ModuleId> {
+        let mut seen = vec![false; self.modules.len()];
+        let mut result = vec![];
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            for &dependent in self.reverse_dependencies(id) {
+                let seen = &mut seen[dependent.into_raw() as usize];
+                if !*seen {
+                    *seen = true;
+                    result.push(dependent);
+                    stack.push(dependent);
+                }
+            }
+        }
+        result
+    }
+
+    /// Groups of modules that import each other, including modules that
+    /// import themselves.
+    pub fn cycles(&self) -> &[Vec<ModuleId>] {
+        &self.cycles
+    }
+
+    pub fn diagnostics(&self) -> &[GraphDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// The shortest path of imports from `from` to `to`, including both.
+    /// Only modules in the same cycle as `from` are followed.
+    pub fn cycle_path(&self, from: ModuleId, to: ModuleId) -> Option<Vec<ModuleId>> {
+        let cycle = self.cycles.iter().find(|cycle| cycle.contains(&from))?;
+        let mut previous: FxHashMap<ModuleId, ModuleId> = FxHashMap::default();
+        let mut queue = VecDeque::from([from]);
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![to];
+                while let Some(&id) = previous.get(path.last().unwrap()) {
+                    path.push(id);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &import in self.dependencies(id) {
+                if cycle.contains(&import) && import != from && !previous.contains_key(&import) {
+                    previous.insert(import, id);
+                    queue.push_back(import);
+                }
+            }
+        }
+        None
+    }
+
+    /// The import statements of `root` that lead back to it, such that each
+    /// of them can be reported where it's written. Modules in a cycle can't
+    /// be resolved in order, so these are reported in place of the names
+    /// that fail to resolve because of it.
+    pub fn import_cycles(&self, root: &ast::Module) -> Vec<ImportCycle> {
+        let Some(header) = root.header() else { return vec![] };
+        let Some(id) = header.name().and_then(|name| self.id(lower_module_name(&name))) else {
+            return vec![];
+        };
+
+        let mut cycles = vec![];
+        for import in header.imports() {
+            let Some(module) = import.module_name() else { continue };
+            let Some(imported) = self.id(lower_module_name(&module)) else { continue };
+            // A module importing itself is also a cycle, of length one.
+            let path = if imported == id {
+                self.dependencies(id).contains(&id).then(|| vec![id])
+            } else {
+                self.cycle_path(imported, id)
+            };
+            let Some(path) = path else { continue };
+            let path = std::iter::once(id).chain(path).map(|id| self.modules[id].name);
+            let ptr = SyntaxNodePtr::new(import.syntax());
+            cycles.push(ImportCycle { path: path.collect(), ptr });
+        }
+        cycles
+    }
+}
+
+/// Tarjan's strongly connected components algorithm, keeping only the
+/// components that form a cycle.
+struct Tarjan<'a> {
+    modules: &'a Arena<ModuleNode>,
+    index: Vec<Option<u32>>,
+    low_link: Vec<u32>,
+    on_stack: Vec<bool>,
+    stack: Vec<ModuleId>,
+    next: u32,
+    cycles: Vec<Vec<ModuleId>>,
+}
+
+impl Tarjan<'_> {
+    fn run(modules: &Arena<ModuleNode>) -> Vec<Vec<ModuleId>> {
+        let mut tarjan = Tarjan {
+            modules,
+            index: vec![None; modules.len()],
+            low_link: vec![0; modules.len()],
+            on_stack: vec![false; modules.len()],
+            stack: vec![],
+            next: 0,
+            cycles: vec![],
+        };
+        for (id, _) in modules.iter() {
+            if tarjan.index[id.into_raw() as usize].is_none() {
+                tarjan.visit(id);
+            }
+        }
+        tarjan.cycles
+    }
+
+    fn visit(&mut self, id: ModuleId) {
+        let raw = id.into_raw() as usize;
+        self.index[raw] = Some(self.next);
+        self.low_link[raw] = self.next;
+        self.next += 1;
+        self.stack.push(id);
+        self.on_stack[raw] = true;
+
+        for &import in &self.modules[id].imports {
+            let import_raw = import.into_raw() as usize;
+            match self.index[import_raw] {
+                None => {
+                    self.visit(import);
+                    self.low_link[raw] = self.low_link[raw].min(self.low_link[import_raw]);
+                }
+                Some(index) if self.on_stack[import_raw] => {
+                    self.low_link[raw] = self.low_link[raw].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_link[raw]) == self.index[raw] {
+            let mut component = vec![];
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack[member.into_raw() as usize] = false;
+                component.push(member);
+                if member == id {
+                    break;
+                }
+            }
+            if component.len() > 1 || self.modules[id].imports.contains(&id) {
+                component.reverse();
+                self.cycles.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, name::ModuleName};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{GraphDiagnostic, ImportCycle, ModuleGraph};
+    use crate::tests::item_tree;
+
+    #[test]
+    fn reverse_dependencies_and_cycles() {
+        let trees = [
+            item_tree("module A where\n\nimport B\nimport Prim.Row\n"),
+            item_tree("module B where\n\nimport C\nimport Missing\n"),
+            item_tree("module C where\n\nimport B\n"),
+            item_tree("module D where\n\nimport A\nimport A as A\n"),
+        ];
+        let graph = ModuleGraph::new(&trees);
+        let id = |name| graph.id(ModuleName::new(name)).unwrap();
+
+        assert_eq!(graph.dependencies(id("D")), [id("A")]);
+        assert_eq!(graph.reverse_dependencies(id("B")), [id("A"), id("C")]);
+        assert_eq!(
+            graph.transitive_reverse_dependencies(id("C")),
+            [id("B"), id("A"), id("C"), id("D")]
+        );
+        assert_eq!(graph.cycles(), [vec![id("B"), id("C")]]);
+        assert_eq!(graph.cycle_path(id("C"), id("B")), Some(vec![id("C"), id("B")]));
+        assert_eq!(graph.cycle_path(id("A"), id("B")), None);
+
+        let modules = |names: &[&str]| names.iter().map(|name| ModuleName::new(name)).collect();
+        assert_eq!(
+            graph.diagnostics(),
+            [
+                GraphDiagnostic::UnknownModule {
+                    module: ModuleName::new("B"),
+                    import: ModuleName::new("Missing")
+                },
+                GraphDiagnostic::Cycle { modules: modules(&["B", "C"]) },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_cycles_at_imports() {
+        let sources = [
+            "module A where\n\nimport B\nimport D\n",
+            "module B where\n\nimport C\n",
+            "module C where\n\nimport A\nimport C\n",
+            "module D where\n",
+        ];
+        let roots: Vec<_> = sources
+            .iter()
+            .map(|source| ast::Module::cast(parsing::parse_module(source).0).unwrap())
+            .collect();
+        let trees: Vec<_> = roots.iter().map(ItemTree::lower).collect();
+        let graph = ModuleGraph::new(&trees);
+
+        let cycles = |index: usize| {
+            let cycles = graph.import_cycles(&roots[index]);
+            let cycles = cycles.iter().map(|cycle: &ImportCycle| {
+                (cycle.message(), &sources[index][cycle.ptr.text_range()])
+            });
+            cycles.collect::<Vec<_>>()
+        };
+        assert_eq!(
+            cycles(0),
+            [("this import forms a cycle: A -> B -> C -> A".to_string(), "import B")]
+        );
+        assert_eq!(
+            cycles(2),
+            [
+                ("this import forms a cycle: C -> A -> B -> C".to_string(), "import A"),
+                ("this import forms a cycle: C -> C".to_string(), "import C"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Resolution of import declarations against the interfaces of the modules
+//! they import.
+
+use lowering::{
+    hir::{DataMembers, Import, ImportItem},
+    item_tree::ItemTree,
+    name::{ModuleName, Name},
+};
+use rustc_hash::FxHashMap;
+
+use crate::interface::{Definition, Interface, Names, Namespace};
+
+/// The names brought into scope by the imports of a module. Names can be
+/// imported from several modules, in which case they're ambiguous unless all
+/// of them refer to the same definition.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Imports {
+    pub unqualified: Names<Vec<Definition>>,
+    /// Names imported through `import M as Alias`, keyed by the alias.
+    pub qualified: FxHashMap<ModuleName, Names<Vec<Definition>>>,
+    /// The modules imported under each alias.
+    pub aliases: FxHashMap<ModuleName, Vec<ModuleName>>,
+    diagnostics: Vec<ImportDiagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportDiagnostic {
+    /// An imported or hidden name that the module doesn't export.
+    UnknownName { module: ModuleName, namespace: Namespace, name: Name },
+    /// A constructor listed with a type that it doesn't belong to.
+    UnknownConstructor { module: ModuleName, type_: Name, name: Name },
+}
+
+impl ImportDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            ImportDiagnostic::UnknownName { module, name, .. } => {
+                format!("module {module} does not export {name}")
+            }
+            ImportDiagnostic::UnknownConstructor { type_, name, .. } => {
+                format!("{name} is not a constructor of {type_}")
+            }
+        }
+    }
+}
+
+impl Imports {
+    /// Resolves the imports of a module, where `interface` returns the
+    /// interface of each module in the workspace. Imports of modules that
+    /// don't exist are reported by the
+    /// [`ModuleGraph`](crate::graph::ModuleGraph) and skipped here.
+    pub fn resolve<'a>(
+        tree: &ItemTree,
+        interface: impl Fn(ModuleName) -> Option<&'a Interface>,
+    ) -> Imports {
+        let mut imports = Imports::default();
+        for import in &tree.imports {
+            if let Some(interface) = interface(import.module) {
+                imports.import(import, interface);
+            }
+        }
+        imports
+    }
+
+    /// The definitions an unqualified or qualified name could refer to.
+    pub fn lookup(
+        &self,
+        qualifier: Option<ModuleName>,
+        namespace: Namespace,
+        name: Name,
+    ) -> &[Definition] {
+        let names = match qualifier {
+            Some(qualifier) => self.qualified.get(&qualifier),
+            None => Some(&self.unqualified),
+        };
+        let definitions = names.and_then(|names| names.get(namespace).get(&name));
+        definitions.map_or(&[], Vec::as_slice)
+    }
+
+    pub fn diagnostics(&self) -> &[ImportDiagnostic] {
+        &self.diagnostics
+    }
+
+    fn import(&mut self, import: &Import, interface: &Interface) {
+        let imported = imported_names(import, interface, &mut self.diagnostics);
+        let names = match import.alias {
+            Some(alias) => {
+                let modules = self.aliases.entry(alias).or_default();
+                if !modules.contains(&import.module) {
+                    modules.push(import.module);
+                }
+                self.qualified.entry(alias).or_default()
+            }
+            None => &mut self.unqualified,
+        };
+        for namespace in Namespace::ALL {
+            for (&name, &definition) in imported.get(namespace) {
+                let definitions = names.get_mut(namespace).entry(name).or_default();
+                if !definitions.contains(&definition) {
+                    definitions.push(definition);
+                }
+            }
+        }
+    }
+}
+
+/// The names brought into scope by a single import.
+pub(crate) fn imported_names(
+    import: &Import,
+    interface: &Interface,
+    diagnostics: &mut Vec<ImportDiagnostic>,
+) -> Names<Definition> {
+    let mut imported = Names::<Definition>::default();
+    match &import.list {
+        None => imported = interface.names.clone(),
+        Some(list) if list.hiding => {
+            imported = interface.names.clone();
+            for item in &list.items {
+                for (namespace, name) in items(import.module, interface, item, diagnostics) {
+                    imported.get_mut(namespace).remove(&name);
+                }
+            }
+        }
+        Some(list) => {
+            for item in &list.items {
+                for (namespace, name) in items(import.module, interface, item, diagnostics) {
+                    let definition = interface.names.get(namespace)[&name];
+                    imported.get_mut(namespace).insert(name, definition);
+                }
+            }
+        }
+    }
+    imported
+}
+
+/// The names an import item refers to, which for types includes their listed
+/// constructors. Names that aren't exported are reported and left out.
+pub(crate) fn items(
+    module: ModuleName,
+    interface: &Interface,
+    item: &ImportItem,
+    diagnostics: &mut Vec<ImportDiagnostic>,
+) -> Vec<(Namespace, Name)> {
+    let (namespace, name, members) = match item {
+        ImportItem::Value(name) => (Namespace::Value, *name, None),
+        ImportItem::Operator(name) => (Namespace::Operator, *name, None),
+        ImportItem::Type(name, members) => (Namespace::Type, *name, members.as_ref()),
+        ImportItem::TypeOperator(name) => (Namespace::TypeOperator, *name, None),
+        ImportItem::Class(name) => (Namespace::Class, *name, None),
+    };
+    if !interface.names.get(namespace).contains_key(&name) {
+        diagnostics.push(ImportDiagnostic::UnknownName { module, namespace, name });
+        return vec![];
+    }
+
+    let mut items = vec![(namespace, name)];
+    let constructors = interface.type_constructors.get(&name).map_or(&[][..], Vec::as_slice);
+    match members {
+        None => {}
+        Some(DataMembers::All) => {
+            items.extend(constructors.iter().map(|&name| (Namespace::Constructor, name)));
+        }
+        Some(DataMembers::Enumerated(names)) => {
+            for &constructor in names {
+                if constructors.contains(&constructor) {
+                    items.push((Namespace::Constructor, constructor));
+                } else {
+                    let type_ = name;
+                    let name = constructor;
+                    diagnostics.push(ImportDiagnostic::UnknownConstructor { module, type_, name });
+                }
+            }
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::name::{ModuleName, Name};
+
+    use super::{ImportDiagnostic, Imports};
+    use crate::{
+        interface::{Interface, Namespace},
+        tests::item_tree,
+    };
+
+    #[test]
+    fn resolve_import_lists() {
+        let maybe = item_tree(
+            "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 0\n\nclass Functor f where\n  map :: Int\n\ninfixl 4 map as <$>\n",
+        );
+        let interface = Interface::local(&maybe);
+        let main = item_tree(
+            "module Main where\n\nimport Data.Maybe (Maybe(Just, Nope), class Functor, (<$>), missing)\nimport Data.Maybe hiding (Maybe(..)) as M\n",
+        );
+        let imports = Imports::resolve(&main, |module| {
+            (module == ModuleName::new("Data.Maybe")).then_some(&interface)
+        });
+
+        let unqualified = |namespace, name| imports.lookup(None, namespace, Name::new(name)).len();
+        assert_eq!(unqualified(Namespace::Type, "Maybe"), 1);
+        assert_eq!(unqualified(Namespace::Constructor, "Just"), 1);
+        assert_eq!(unqualified(Namespace::Constructor, "Nothing"), 0);
+        assert_eq!(unqualified(Namespace::Class, "Functor"), 1);
+        assert_eq!(unqualified(Namespace::Operator, "<$>"), 1);
+        // Importing a class doesn't import its members.
+        assert_eq!(unqualified(Namespace::Value, "map"), 0);
+
+        let alias = Some(ModuleName::new("M"));
+        let qualified = |namespace, name| imports.lookup(alias, namespace, Name::new(name)).len();
+        assert_eq!(qualified(Namespace::Type, "Maybe"), 0);
+        assert_eq!(qualified(Namespace::Constructor, "Just"), 0);
+        assert_eq!(qualified(Namespace::Value, "fromMaybe"), 1);
+        assert_eq!(qualified(Namespace::Value, "map"), 1);
+
+        let module = ModuleName::new("Data.Maybe");
+        assert_eq!(
+            imports.diagnostics(),
+            [
+                ImportDiagnostic::UnknownConstructor {
+                    module,
+                    type_: Name::new("Maybe"),
+                    name: Name::new("Nope")
+                },
+                ImportDiagnostic::UnknownName {
+                    module,
+                    namespace: Namespace::Value,
+                    name: Name::new("missing")
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Resolution of instance members to the class members they implement, and
+//! checks of the modules instances are declared in.
+
+use lowering::{
+    arena::Arena,
+    ast_id::AstIdMap,
+    hir::{FunctionalDependency, Type, TypeId, TypeVariable},
+    item_tree::{ClassItem, Item, ItemTree},
+    lower_name, lower_qualified_name,
+    name::{ModuleName, Name, QualifiedName},
+};
+use rowan::ast::{AstNode, SyntaxNodePtr};
+use rustc_hash::FxHashSet;
+use syntax::{ast, PureScript};
+
+use crate::{
+    body::{ModuleScope, Resolution},
+    interface::{Definition, Namespace},
+    types::TypeResolution,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstanceDiagnostic {
+    /// A member that the class of the instance doesn't declare.
+    UnknownMember { class: QualifiedName, name: Name, ptr: SyntaxNodePtr<PureScript> },
+    /// Class members without an equation in the instance, reported at the
+    /// head of the instance.
+    MissingMembers { class: QualifiedName, names: Vec<Name>, ptr: SyntaxNodePtr<PureScript> },
+}
+
+impl InstanceDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            InstanceDiagnostic::UnknownMember { class, name, .. } => {
+                format!("{name} is not a member of class {class}")
+            }
+            InstanceDiagnostic::MissingMembers { class, names, .. } => {
+                let names: Vec<_> = names.iter().map(|name| name.as_str()).collect();
+                format!("instance of {class} is missing {}", names.join(", "))
+            }
+        }
+    }
+
+    pub fn ptr(&self) -> &SyntaxNodePtr<PureScript> {
+        match self {
+            InstanceDiagnostic::UnknownMember { ptr, .. }
+            | InstanceDiagnostic::MissingMembers { ptr, .. } => ptr,
+        }
+    }
+}
+
+/// An instance declared in a module that defines neither its class nor a
+/// type in its head, which the compiler rejects. `homes` are the modules it
+/// could be declared in instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanWarning {
+    pub class: QualifiedName,
+    pub homes: Vec<ModuleName>,
+    pub ptr: SyntaxNodePtr<PureScript>,
+}
+
+impl OrphanWarning {
+    pub fn message(&self) -> String {
+        let homes: Vec<_> = self.homes.iter().map(|module| module.as_str()).collect();
+        format!(
+            "orphan instance of {}, which can be declared in {} instead, or on a newtype wrapper",
+            self.class,
+            homes.join(" or ")
+        )
+    }
+
+    pub fn ptr(&self) -> &SyntaxNodePtr<PureScript> {
+        &self.ptr
+    }
+}
+
+/// Resolves the class of an instance, where `tree` returns the [`ItemTree`]
+/// of each module in the workspace, including the module itself.
+pub fn resolve_class<'a>(
+    scope: &ModuleScope,
+    class: QualifiedName,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+) -> Option<(Definition, &'a ClassItem)> {
+    let Some(Resolution::Global(definition)) = scope.resolve(Namespace::Class, class) else {
+        return None;
+    };
+    match &tree(definition.module)?.items[definition.item] {
+        Item::Class(item) => Some((definition, item)),
+        _ => None,
+    }
+}
+
+/// The positions of the variables of a class in its functional dependencies.
+pub fn dependencies(
+    variables: &[TypeVariable],
+    dependencies: &[FunctionalDependency],
+) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let positions = |names: &[Name]| -> Vec<usize> {
+        let positions = names
+            .iter()
+            .filter_map(|&name| variables.iter().position(|variable| variable.name == name));
+        positions.collect()
+    };
+    let dependencies = dependencies
+        .iter()
+        .map(|dependency| (positions(&dependency.determiners), positions(&dependency.determined)));
+    dependencies.collect()
+}
+
+/// The positions determined by `known` through `dependencies`, including
+/// `known` itself.
+fn closure(dependencies: &[(Vec<usize>, Vec<usize>)], known: &[usize]) -> Vec<usize> {
+    let mut closure = known.to_vec();
+    loop {
+        let mut progress = false;
+        for (determiners, determined) in dependencies {
+            if determiners.iter().all(|position| closure.contains(position)) {
+                for &position in determined {
+                    if !closure.contains(&position) {
+                        closure.push(position);
+                        progress = true;
+                    }
+                }
+            }
+        }
+        if !progress {
+            return closure;
+        }
+    }
+}
+
+/// The smallest sets of arguments that determine every argument of a class,
+/// other than the set of every argument.
+pub fn covering_sets(class: &ClassItem) -> Vec<Vec<usize>> {
+    let arity = class.variables.len();
+    let dependencies = dependencies(&class.variables, &class.dependencies);
+    // Classes rarely have more than a few parameters, so every subset is
+    // tried, smallest first.
+    let mut subsets: Vec<Vec<usize>> = (0..1u32 << arity.min(16))
+        .map(|mask| (0..arity).filter(|&position| mask & (1 << position) != 0).collect())
+        .collect();
+    subsets.sort_by_key(|subset| subset.len());
+    let mut covering: Vec<Vec<usize>> = vec![];
+    for subset in subsets {
+        if subset.len() == arity || closure(&dependencies, &subset).len() < arity {
+            continue;
+        }
+        if !covering.iter().any(|smaller| smaller.iter().all(|position| subset.contains(position)))
+        {
+            covering.push(subset);
+        }
+    }
+    covering
+}
+
+/// The class member an instance member implements, e.g. for going to its
+/// definition.
+pub fn resolve_member<'a>(
+    scope: &ModuleScope,
+    class: QualifiedName,
+    member: Name,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+) -> Option<Definition> {
+    let (definition, class) = resolve_class(scope, class, tree)?;
+    let index = class.members.iter().position(|signature| signature.name == member)?;
+    Some(Definition { member: Some(index), ..definition })
+}
+
+/// Reports instance members that aren't part of their class, and class
+/// members that instances don't define. Instances of classes that don't
+/// resolve are skipped, since their names are reported already.
+pub fn check_instances<'a>(
+    root: &ast::Module,
+    scope: &ModuleScope,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+) -> Vec<InstanceDiagnostic> {
+    let mut diagnostics = vec![];
+    let chains = root.declarations().filter_map(|declaration| match declaration {
+        ast::Declaration::InstanceChain(chain) => Some(chain),
+        _ => None,
+    });
+    for instance in chains.flat_map(|chain| chain.instances()) {
+        let Some(head) = instance.head() else { continue };
+        let Some(class) = head.class_name().map(|name| lower_qualified_name(&name)) else {
+            continue;
+        };
+        let Some((_, item)) = resolve_class(scope, class, &tree) else { continue };
+
+        let mut defined = FxHashSet::default();
+        for member in instance.members() {
+            let (name_node, is_equation) = match &member {
+                ast::InstanceMember::Value(value) => (value.name(), true),
+                ast::InstanceMember::Annotation(annotation) => (annotation.name(), false),
+            };
+            let Some(name_node) = name_node else { continue };
+            let name = lower_name(Some(name_node.clone()));
+            if !item.members.iter().any(|signature| signature.name == name) {
+                // Equations of the same member are only reported once.
+                if defined.insert(name) {
+                    let ptr = SyntaxNodePtr::new(name_node.syntax());
+                    diagnostics.push(InstanceDiagnostic::UnknownMember { class, name, ptr });
+                }
+            } else if is_equation {
+                defined.insert(name);
+            }
+        }
+
+        let missing: Vec<_> = item
+            .members
+            .iter()
+            .map(|signature| signature.name)
+            .filter(|name| !defined.contains(name))
+            .collect();
+        if !missing.is_empty() {
+            let ptr = SyntaxNodePtr::new(head.syntax());
+            diagnostics.push(InstanceDiagnostic::MissingMembers { class, names: missing, ptr });
+        }
+    }
+    diagnostics
+}
+
+/// Reports the instances of module `name`, derived or not, that are orphans.
+/// Like the compiler, an instance belongs in the module of its class, or in
+/// a module that defines a type in the arguments of every covering set of
+/// its class's functional dependencies.
+pub fn check_orphans<'a>(
+    root: &ast::Module,
+    name: ModuleName,
+    scope: &ModuleScope,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+) -> Vec<OrphanWarning> {
+    let mut warnings = vec![];
+    let Some(module) = tree(name) else { return warnings };
+    let resolution = TypeResolution::new(&module.types, scope);
+    let ast_ids = AstIdMap::from_module(root);
+    for (id, item) in module.iter() {
+        let Item::Instance(instance) = item else { continue };
+        let head = &instance.head;
+        let Some((definition, class)) = resolve_class(scope, head.class, &tree) else { continue };
+        if definition.module == name || head.arguments.len() != class.variables.len() {
+            continue;
+        }
+
+        let mut covering = covering_sets(class);
+        if covering.is_empty() {
+            covering.push((0..head.arguments.len()).collect());
+        }
+        let mut homes: Option<Vec<ModuleName>> = None;
+        for set in covering {
+            let mut modules = vec![];
+            for position in set {
+                type_modules(&module.types, &resolution, head.arguments[position], &mut modules);
+            }
+            homes = Some(match homes {
+                Some(homes) => homes.into_iter().filter(|home| modules.contains(home)).collect(),
+                None => modules,
+            });
+        }
+        let mut homes = homes.unwrap_or_default();
+        if homes.contains(&name) {
+            continue;
+        }
+
+        let Some(ptr) = module.ast_id(id).and_then(|id| ast_ids.get_erased(id)) else { continue };
+        let node = ptr.to_node(root.syntax());
+        let syntax = match ast::InstanceChain::cast(node.clone()) {
+            Some(chain) => chain
+                .instances()
+                .nth(instance.chain_index)
+                .and_then(|declaration| declaration.head()),
+            None => ast::DeriveInstanceDeclaration::cast(node).and_then(|derive| derive.head()),
+        };
+        let ptr = syntax.map_or(ptr, |head| SyntaxNodePtr::new(head.syntax()));
+        homes.retain(|&home| home != definition.module);
+        homes.insert(0, definition.module);
+        warnings.push(OrphanWarning { class: head.class, homes, ptr });
+    }
+    warnings
+}
+
+/// The modules of the type constructors and type operators in a type, in
+/// order.
+fn type_modules(
+    types: &Arena<Type>,
+    resolution: &TypeResolution,
+    id: TypeId,
+    modules: &mut Vec<ModuleName>,
+) {
+    let mut push = |resolution: Option<&Resolution>| {
+        if let Some(&Resolution::Global(definition)) = resolution {
+            if !modules.contains(&definition.module) {
+                modules.push(definition.module);
+            }
+        }
+    };
+    match &types[id] {
+        Type::Constructor(_) | Type::OperatorName(_) => push(resolution.resolution(id)),
+        Type::OperatorChain { head, tail } => {
+            for index in 0..tail.len() {
+                push(resolution.operator_resolution(id, index));
+            }
+            type_modules(types, resolution, *head, modules);
+            for &(_, operand) in tail {
+                type_modules(types, resolution, operand, modules);
+            }
+        }
+        &Type::Forall { type_, .. } | &Type::Kinded { type_, .. } => {
+            type_modules(types, resolution, type_, modules);
+        }
+        &Type::Constrained { constraint, type_ } => {
+            type_modules(types, resolution, constraint, modules);
+            type_modules(types, resolution, type_, modules);
+        }
+        &Type::Arrow { argument, result } => {
+            type_modules(types, resolution, argument, modules);
+            type_modules(types, resolution, result, modules);
+        }
+        Type::Application { function, arguments } => {
+            type_modules(types, resolution, *function, modules);
+            for &argument in arguments {
+                type_modules(types, resolution, argument, modules);
+            }
+        }
+        Type::Record(row) | Type::Row(row) => {
+            for &(_, field) in &row.fields {
+                type_modules(types, resolution, field, modules);
+            }
+            if let Some(tail) = row.tail {
+                type_modules(types, resolution, tail, modules);
+            }
+        }
+        Type::Constraints(constraints) => {
+            for &constraint in constraints {
+                type_modules(types, resolution, constraint, modules);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, name::ModuleName};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{check_instances, check_orphans};
+    use crate::{body::ModuleScope, imports::Imports, interface::Interface};
+
+    #[test]
+    fn report_unknown_and_missing_members() {
+        let source = "module Main where\n\nclass Show a where\n  show :: a -> String\n  showList :: a -> String\n\ninstance Show Int where\n  show _ = \"\"\n  show _ = \"\"\n  display _ = \"\"\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+
+        let diagnostics = check_instances(&root, &scope, |module| {
+            (module == ModuleName::new("Main")).then_some(&tree)
+        });
+        let diagnostics: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.message(), &source[diagnostic.ptr().text_range()]))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("display is not a member of class Show".to_string(), "display"),
+                ("instance of Show is missing showList".to_string(), "Show Int"),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_orphan_instances() {
+        let parse = |source: &str| {
+            let (node, errors) = parsing::parse_module(source);
+            assert!(errors.is_empty(), "{errors:?}");
+            ast::Module::cast(node).unwrap()
+        };
+        let classes = ItemTree::lower(&parse(
+            "module Data.Classes where\n\nclass Show a\n\nclass Newtype t a | t -> a\n",
+        ));
+        let maybe =
+            ItemTree::lower(&parse("module Data.Maybe where\n\ndata Maybe a = Nothing | Just a\n"));
+        let library = [classes, maybe];
+        let interfaces: Vec<_> = library.iter().map(Interface::local).collect();
+        let source = "module Main where\n\nimport Data.Classes\nimport Data.Maybe\n\ndata T = T\n\ninstance Show T\ninstance Show (Maybe Int)\ninstance Newtype T String\ninstance Newtype String T\nderive instance Show (Maybe T)\n";
+        let root = parse(source);
+        let tree = ItemTree::lower(&root);
+        let imports = Imports::resolve(&tree, |module| {
+            let index = library.iter().position(|tree| tree.name == Some(module))?;
+            Some(&interfaces[index])
+        });
+        let scope = ModuleScope::new(&tree, imports);
+
+        let main = ModuleName::new("Main");
+        let warnings = check_orphans(&root, main, &scope, |module| {
+            if module == main {
+                Some(&tree)
+            } else {
+                library.iter().find(|tree| tree.name == Some(module))
+            }
+        });
+        let warnings: Vec<_> = warnings
+            .iter()
+            .map(|warning| (warning.message(), &source[warning.ptr().text_range()]))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                (
+                    "orphan instance of Show, which can be declared in Data.Classes or Data.Maybe instead, or on a newtype wrapper".to_string(),
+                    "Show (Maybe Int)"
+                ),
+                (
+                    "orphan instance of Newtype, which can be declared in Data.Classes instead, or on a newtype wrapper".to_string(),
+                    "Newtype String T"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! The names a module makes available to other modules.
+
+use lowering::{
+    item_tree::{Item, ItemId, ItemTree},
+    name::{ModuleName, Name},
+};
+use rustc_hash::FxHashMap;
+
+/// The namespaces names are resolved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Values, foreign values, and class members.
+    Value,
+    Constructor,
+    Operator,
+    /// Data types, newtypes, synonyms, and foreign data.
+    Type,
+    TypeOperator,
+    Class,
+}
+
+impl Namespace {
+    pub const ALL: [Namespace; 6] = [
+        Namespace::Value,
+        Namespace::Constructor,
+        Namespace::Operator,
+        Namespace::Type,
+        Namespace::TypeOperator,
+        Namespace::Class,
+    ];
+}
+
+/// A declaration, or a constructor or class member within one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Definition {
+    pub module: ModuleName,
+    pub item: ItemId,
+    /// The index of the constructor or class member within the item.
+    pub member: Option<usize>,
+}
+
+/// A map from names to `T` for each [`Namespace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Names<T> {
+    pub values: FxHashMap<Name, T>,
+    pub constructors: FxHashMap<Name, T>,
+    pub operators: FxHashMap<Name, T>,
+    pub types: FxHashMap<Name, T>,
+    pub type_operators: FxHashMap<Name, T>,
+    pub classes: FxHashMap<Name, T>,
+}
+
+impl<T> Default for Names<T> {
+    fn default() -> Self {
+        Names {
+            values: FxHashMap::default(),
+            constructors: FxHashMap::default(),
+            operators: FxHashMap::default(),
+            types: FxHashMap::default(),
+            type_operators: FxHashMap::default(),
+            classes: FxHashMap::default(),
+        }
+    }
+}
+
+impl<T> Names<T> {
+    pub fn get(&self, namespace: Namespace) -> &FxHashMap<Name, T> {
+        match namespace {
+            Namespace::Value => &self.values,
+            Namespace::Constructor => &self.constructors,
+            Namespace::Operator => &self.operators,
+            Namespace::Type => &self.types,
+            Namespace::TypeOperator => &self.type_operators,
+            Namespace::Class => &self.classes,
+        }
+    }
+
+    pub fn get_mut(&mut self, namespace: Namespace) -> &mut FxHashMap<Name, T> {
+        match namespace {
+            Namespace::Value => &mut self.values,
+            Namespace::Constructor => &mut self.constructors,
+            Namespace::Operator => &mut self.operators,
+            Namespace::Type => &mut self.types,
+            Namespace::TypeOperator => &mut self.type_operators,
+            Namespace::Class => &mut self.classes,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub names: Names<Definition>,
+    /// The constructors available with each type, which are only imported
+    /// along with their type, e.g. `Maybe(..)`.
+    pub type_constructors: FxHashMap<Name, Vec<Name>>,
+}
+
+impl Interface {
+    /// Every item declared in a module, which is what a module without an
+    /// export list exports.
+    pub fn local(tree: &ItemTree) -> Interface {
+        let module = module_name(tree);
+        let mut interface = Interface::default();
+        for (item, kind) in tree.iter() {
+            let definition = Definition { module, item, member: None };
+            let mut insert = |namespace, name, definition| {
+                interface.names.get_mut(namespace).insert(name, definition);
+            };
+            match kind {
+                Item::Value(value) => insert(Namespace::Value, value.name, definition),
+                Item::ForeignValue(value) => insert(Namespace::Value, value.name, definition),
+                Item::Data(data) => {
+                    insert(Namespace::Type, data.name, definition);
+                    let constructors = data.constructors.iter().map(|constructor| constructor.name);
+                    for (index, name) in constructors.clone().enumerate() {
+                        let definition = Definition { member: Some(index), ..definition };
+                        insert(Namespace::Constructor, name, definition);
+                    }
+                    interface.type_constructors.insert(data.name, constructors.collect());
+                }
+                Item::Newtype(newtype) => {
+                    insert(Namespace::Type, newtype.name, definition);
+                    let name = newtype.constructor.name;
+                    let constructor = Definition { member: Some(0), ..definition };
+                    insert(Namespace::Constructor, name, constructor);
+                    interface.type_constructors.insert(newtype.name, vec![name]);
+                }
+                Item::Synonym(synonym) => insert(Namespace::Type, synonym.name, definition),
+                Item::ForeignData(data) => insert(Namespace::Type, data.name, definition),
+                Item::Class(class) => {
+                    insert(Namespace::Class, class.name, definition);
+                    for (index, member) in class.members.iter().enumerate() {
+                        let definition = Definition { member: Some(index), ..definition };
+                        insert(Namespace::Value, member.name, definition);
+                    }
+                }
+                // Instances are always exported, but can't be referred to.
+                Item::Instance(_) => {}
+                Item::Fixity(fixity) => {
+                    let namespace =
+                        if fixity.is_type { Namespace::TypeOperator } else { Namespace::Operator };
+                    insert(namespace, fixity.operator, definition);
+                }
+            }
+        }
+        interface
+    }
+}
+
+pub(crate) fn module_name(tree: &ItemTree) -> ModuleName {
+    tree.name.unwrap_or_else(|| ModuleName::new("[missing module]"))
+}
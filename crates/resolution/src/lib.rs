@@ -0,0 +1,27 @@
+pub mod body;
+pub mod diagnostics;
+pub mod exports;
+pub mod fixity;
+pub mod graph;
+pub mod imports;
+pub mod instances;
+pub mod interface;
+pub mod prim;
+pub mod reference;
+pub mod shadowing;
+pub mod types;
+pub mod unused;
+
+#[cfg(test)]
+mod tests {
+    use lowering::item_tree::ItemTree;
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    /// The item tree of a module, which has to parse without errors.
+    pub(crate) fn item_tree(source: &str) -> ItemTree {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        ItemTree::lower(&ast::Module::cast(node).unwrap())
+    }
+}
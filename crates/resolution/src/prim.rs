@@ -0,0 +1,187 @@
+//! The `Prim` module, which is built into the compiler and implicitly
+//! imported by every module.
+
+use lowering::{
+    hir::{FunctionalDependency, Type, TypeId, TypeVariable},
+    item_tree::{ClassItem, ForeignDataItem, Item, ItemTree},
+    name::{ModuleName, Name, QualifiedName},
+};
+
+use crate::interface::Namespace;
+
+const TYPES: &[&str] = &[
+    "Array",
+    "Boolean",
+    "Char",
+    "Constraint",
+    "Function",
+    "Int",
+    "Number",
+    "Record",
+    "Row",
+    "String",
+    "Symbol",
+    "Type",
+];
+
+const CLASSES: &[&str] = &["Partial"];
+
+/// The names that `Prim` defines in `namespace`.
+pub fn names(namespace: Namespace) -> &'static [&'static str] {
+    match namespace {
+        Namespace::Type => TYPES,
+        Namespace::Class => CLASSES,
+        _ => &[],
+    }
+}
+
+/// Determines if `Prim` defines `name` in `namespace`.
+pub fn contains(namespace: Namespace, name: Name) -> bool {
+    names(namespace).contains(&name.as_str())
+}
+
+/// `Prim` and its submodules, e.g. `Prim.Row`, are not part of the workspace.
+pub fn is_prim_module(name: ModuleName) -> bool {
+    name.segments().next() == Some("Prim")
+}
+
+/// The submodules of `Prim` that define classes and types of their own, as
+/// item trees such that workspaces can include them like any other module.
+/// Their classes have no instances, since they're solved by the type checker.
+pub fn modules() -> Vec<ItemTree> {
+    let mut boolean = Builder::new("Prim.Boolean");
+    for name in ["True", "False"] {
+        let kind = boolean.constructor("Boolean");
+        boolean.data(name, kind);
+    }
+
+    let mut ordering = Builder::new("Prim.Ordering");
+    let kind = ordering.constructor("Type");
+    ordering.data("Ordering", kind);
+    for name in ["LT", "EQ", "GT"] {
+        let kind = ordering.constructor("Ordering");
+        ordering.data(name, kind);
+    }
+
+    let mut row = Builder::new("Prim.Row");
+    row.class(
+        "Union",
+        &["left", "right", "union"],
+        &[
+            (&["left", "right"], &["union"]),
+            (&["right", "union"], &["left"]),
+            (&["union", "left"], &["right"]),
+        ],
+    );
+    row.class("Nub", &["original", "nubbed"], &[(&["original"], &["nubbed"])]);
+    row.class("Lacks", &["label", "row"], &[]);
+    row.class(
+        "Cons",
+        &["label", "a", "tail", "row"],
+        &[(&["label", "a", "tail"], &["row"]), (&["label", "row"], &["a", "tail"])],
+    );
+
+    let mut row_list = Builder::new("Prim.RowList");
+    let kind = row_list.arrow(&["Type"], "Type");
+    row_list.data("RowList", kind);
+    let k = row_list.variable("k");
+    let list = row_list.applied("RowList", k);
+    let symbol = row_list.constructor("Symbol");
+    let cons = row_list.function(&[symbol, k, list], list);
+    let cons = row_list.forall("k", cons);
+    row_list.data("Cons", cons);
+    let nil = row_list.forall("k", list);
+    row_list.data("Nil", nil);
+    row_list.class("RowToList", &["row", "list"], &[(&["row"], &["list"])]);
+
+    let mut symbol = Builder::new("Prim.Symbol");
+    symbol.class(
+        "Append",
+        &["left", "right", "appended"],
+        &[
+            (&["left", "right"], &["appended"]),
+            (&["right", "appended"], &["left"]),
+            (&["appended", "left"], &["right"]),
+        ],
+    );
+    symbol.class("Compare", &["left", "right", "ordering"], &[(&["left", "right"], &["ordering"])]);
+    symbol.class(
+        "Cons",
+        &["head", "tail", "symbol"],
+        &[(&["head", "tail"], &["symbol"]), (&["symbol"], &["head", "tail"])],
+    );
+
+    [boolean, ordering, row, row_list, symbol].into_iter().map(|builder| builder.tree).collect()
+}
+
+/// Builds the item tree of a `Prim` submodule, whose types refer to `Prim`
+/// and to the module itself.
+struct Builder {
+    tree: ItemTree,
+}
+
+impl Builder {
+    fn new(name: &str) -> Builder {
+        let mut tree = ItemTree::default();
+        tree.name = Some(ModuleName::new(name));
+        Builder { tree }
+    }
+
+    fn constructor(&mut self, name: &str) -> TypeId {
+        let name = QualifiedName { qualifier: None, name: Name::new(name) };
+        self.tree.types.alloc(Type::Constructor(name))
+    }
+
+    fn variable(&mut self, name: &str) -> TypeId {
+        self.tree.types.alloc(Type::Variable(Name::new(name)))
+    }
+
+    fn applied(&mut self, function: &str, argument: TypeId) -> TypeId {
+        let function = self.constructor(function);
+        self.tree.types.alloc(Type::Application { function, arguments: vec![argument] })
+    }
+
+    fn function(&mut self, arguments: &[TypeId], result: TypeId) -> TypeId {
+        let mut ty = result;
+        for &argument in arguments.iter().rev() {
+            ty = self.tree.types.alloc(Type::Arrow { argument, result: ty });
+        }
+        ty
+    }
+
+    fn arrow(&mut self, arguments: &[&str], result: &str) -> TypeId {
+        let arguments: Vec<_> = arguments.iter().map(|name| self.constructor(name)).collect();
+        let result = self.constructor(result);
+        self.function(&arguments, result)
+    }
+
+    fn forall(&mut self, variable: &str, type_: TypeId) -> TypeId {
+        let variables =
+            vec![TypeVariable { name: Name::new(variable), kind: None, visible: false }];
+        self.tree.types.alloc(Type::Forall { variables, type_ })
+    }
+
+    fn data(&mut self, name: &str, kind: TypeId) {
+        self.tree.items.alloc(Item::ForeignData(ForeignDataItem { name: Name::new(name), kind }));
+    }
+
+    fn class(&mut self, name: &str, variables: &[&str], dependencies: &[(&[&str], &[&str])]) {
+        let names = |names: &[&str]| names.iter().map(|&name| Name::new(name)).collect();
+        let variables = variables.iter().map(|&name| TypeVariable {
+            name: Name::new(name),
+            kind: None,
+            visible: false,
+        });
+        let dependencies = dependencies.iter().map(|(determiners, determined)| {
+            FunctionalDependency { determiners: names(determiners), determined: names(determined) }
+        });
+        self.tree.items.alloc(Item::Class(ClassItem {
+            name: Name::new(name),
+            kind: None,
+            constraints: vec![],
+            variables: variables.collect(),
+            dependencies: dependencies.collect(),
+            members: vec![],
+        }));
+    }
+}
@@ -0,0 +1,284 @@
+//! Classification of the name under the cursor, which hover, go to
+//! definition, and highlights are built on.
+
+use lowering::{
+    hir::{Binder, BinderId, BodyId, Expr, Module, Operator, Type},
+    item_tree::ItemTree,
+    lower_label, lower_module_name, lower_name, lower_qualified_name,
+    name::{ModuleName, Name, QualifiedName},
+    source_map::SourceMap,
+};
+use rowan::{ast::AstNode, TextRange, TextSize};
+use syntax::{ast, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use crate::{
+    body::{BodyResolution, ModuleScope, Resolution},
+    instances,
+    interface::{Definition, Namespace},
+    types::TypeResolution,
+};
+
+/// A name in the source, along with what it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    /// The range of the token the name was found at.
+    pub range: TextRange,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Name(Namespace),
+    /// A module name or alias, e.g. a qualifier.
+    Module,
+    Label,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A binder within a body, which includes the binder itself.
+    Local {
+        body: BodyId,
+        binder: BinderId,
+    },
+    Global(Definition),
+    Prim(Name),
+    Ambiguous(Vec<Definition>),
+    /// The modules imported under an alias, or the module named.
+    Modules(Vec<ModuleName>),
+    /// Labels aren't resolved without types, so only their name is known.
+    Label(Name),
+}
+
+/// Finds the name at `offset` in `root`, the syntax `module` was lowered
+/// from, where `tree` returns the [`ItemTree`] of each module in the
+/// workspace for instance members. Returns `None` if there's no name at
+/// `offset`, or if it doesn't resolve to anything.
+pub fn resolve_at_offset<'a>(
+    root: &SyntaxNode,
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+    offset: TextSize,
+) -> Option<Reference> {
+    // Prefer names over the punctuation next to them, e.g. in `f(x)`.
+    let token = root
+        .token_at_offset(offset)
+        .filter(|token| !token.kind().is_trivia())
+        .max_by_key(|token| is_name(token.kind()))?;
+    let range = token.text_range();
+    let reference = |kind, target| Some(Reference { kind, range, target });
+
+    if let Some(definition) = instance_member(&token, scope, tree) {
+        return reference(ReferenceKind::Name(Namespace::Value), Target::Global(definition));
+    }
+
+    for node in token.parent_ancestors() {
+        if let Some(label) = ast::Label::cast(node.clone()) {
+            return reference(ReferenceKind::Label, Target::Label(lower_label(Some(label))));
+        }
+        if let Some(name) = ast::ModuleName::cast(node.clone()) {
+            let name = lower_module_name(&name);
+            let modules = scope.imports.aliases.get(&name).cloned().unwrap_or_else(|| vec![name]);
+            return reference(ReferenceKind::Module, Target::Modules(modules));
+        }
+
+        for (id, body) in module.bodies.iter() {
+            let body_map = source_map.body(id);
+            if let Some(expr) = body_map.node_expr(&node) {
+                let resolution = BodyResolution::new(body, scope);
+                let (namespace, resolved) = match &body.exprs[expr] {
+                    Expr::Variable(_) => (Namespace::Value, resolution.resolution(expr)),
+                    Expr::Constructor(_) => (Namespace::Constructor, resolution.resolution(expr)),
+                    Expr::OperatorName(_) => (Namespace::Operator, resolution.resolution(expr)),
+                    Expr::OperatorChain { tail, .. } => {
+                        let index = operator_index(
+                            &token,
+                            tail.iter().map(|(operator, _)| match operator {
+                                &Operator::Symbol(name) => Some(name),
+                                Operator::Infix(_) => None,
+                            }),
+                        )?;
+                        (Namespace::Operator, resolution.operator_resolution(expr, index))
+                    }
+                    _ => return None,
+                };
+                let target = target(id, resolved?.clone());
+                return reference(ReferenceKind::Name(namespace), target);
+            }
+            if let Some(binder) = body_map.node_binder(&node) {
+                let resolution = BodyResolution::new(body, scope);
+                let (namespace, resolved) = match &body.binders[binder] {
+                    Binder::Variable(_) | Binder::Named { .. } => {
+                        (Namespace::Value, Resolution::Local(binder))
+                    }
+                    Binder::Constructor { .. } => {
+                        (Namespace::Constructor, resolution.binder_resolution(binder)?.clone())
+                    }
+                    Binder::OperatorChain { tail, .. } => {
+                        let index =
+                            operator_index(&token, tail.iter().map(|&(name, _)| Some(name)))?;
+                        let resolved = resolution.binder_operator_resolution(binder, index)?;
+                        (Namespace::Operator, resolved.clone())
+                    }
+                    _ => return None,
+                };
+                return reference(ReferenceKind::Name(namespace), target(id, resolved));
+            }
+        }
+
+        if let Some(type_) = source_map.node_type(&node) {
+            let resolution = TypeResolution::new(&module.types, scope);
+            let (namespace, resolved) = match &module.types[type_] {
+                Type::Constructor(_) => {
+                    let namespace = resolution.namespace(type_).unwrap_or(Namespace::Type);
+                    (namespace, resolution.resolution(type_))
+                }
+                Type::OperatorName(_) => (Namespace::TypeOperator, resolution.resolution(type_)),
+                Type::OperatorChain { tail, .. } => {
+                    let index = operator_index(&token, tail.iter().map(|&(name, _)| Some(name)))?;
+                    (Namespace::TypeOperator, resolution.operator_resolution(type_, index))
+                }
+                _ => return None,
+            };
+            // Bodies aren't involved in resolving types, so locals can't
+            // occur here.
+            let target = match resolved?.clone() {
+                Resolution::Global(definition) => Target::Global(definition),
+                Resolution::Prim(name) => Target::Prim(name),
+                Resolution::Ambiguous(definitions) => Target::Ambiguous(definitions),
+                Resolution::Local(_) => return None,
+            };
+            return reference(ReferenceKind::Name(namespace), target);
+        }
+    }
+    None
+}
+
+/// The class member implemented by an instance member, if `token` is the
+/// name of one.
+fn instance_member<'a>(
+    token: &SyntaxToken,
+    scope: &ModuleScope,
+    tree: impl Fn(ModuleName) -> Option<&'a ItemTree>,
+) -> Option<Definition> {
+    let name = ast::Name::cast(token.parent()?)?;
+    let member = name.syntax().parent()?;
+    if !ast::ValueDeclaration::can_cast(member.kind())
+        && !ast::AnnotationDeclaration::can_cast(member.kind())
+    {
+        return None;
+    }
+    let instance = ast::InstanceDeclaration::cast(member.parent()?)?;
+    let class = lower_qualified_name(&instance.head()?.class_name()?);
+    instances::resolve_member(scope, class, lower_name(Some(name)), tree)
+}
+
+fn is_name(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::Lower | SyntaxKind::Upper | SyntaxKind::Operator)
+}
+
+fn target(body: BodyId, resolution: Resolution) -> Target {
+    match resolution {
+        Resolution::Local(binder) => Target::Local { body, binder },
+        Resolution::Global(definition) => Target::Global(definition),
+        Resolution::Prim(name) => Target::Prim(name),
+        Resolution::Ambiguous(definitions) => Target::Ambiguous(definitions),
+    }
+}
+
+/// The index of the operator `token` belongs to within the tail of an
+/// operator chain. Chains are flattened during lowering, so operators are
+/// matched by name, which resolves the same wherever it occurs.
+fn operator_index(
+    token: &SyntaxToken,
+    mut operators: impl Iterator<Item = Option<QualifiedName>>,
+) -> Option<usize> {
+    let name = token.parent_ancestors().find_map(ast::QualifiedName::cast)?;
+    let name = lower_qualified_name(&name);
+    operators.position(|operator| operator == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{
+        hir::Binder,
+        item_tree::ItemTree,
+        lower_module_with_source_map,
+        name::{ModuleName, Name},
+    };
+    use rowan::{ast::AstNode, TextSize};
+    use syntax::ast;
+
+    use super::{resolve_at_offset, Reference, ReferenceKind, Target};
+    use crate::{
+        body::ModuleScope,
+        imports::Imports,
+        interface::{Interface, Namespace},
+    };
+
+    #[test]
+    fn resolve_names_at_offsets() {
+        let prelude = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n";
+        let (node, _) = parsing::parse_module(prelude);
+        let prelude = Interface::local(&ItemTree::lower(&ast::Module::cast(node).unwrap()));
+
+        let source = "module Main where\n\nimport Data.Maybe as M\n\nf :: M.Maybe Int -> Int\nf (M.Just x) = { label: x }.label\n\nclass Show a where\n  show :: a -> Int\n\ninstance Show Int where\n  show _ = 0\n";
+        let (node, _) = parsing::parse_module(source);
+        let root = ast::Module::cast(node.clone()).unwrap();
+        let tree = ItemTree::lower(&root);
+        let (module, source_map) = lower_module_with_source_map(&root);
+        let imports = Imports::resolve(&tree, |module| {
+            (module == ModuleName::new("Data.Maybe")).then_some(&prelude)
+        });
+        let scope = ModuleScope::new(&tree, imports);
+
+        let resolve = |text: &str, nth: usize| {
+            let (offset, _) = source.match_indices(text).nth(nth).unwrap();
+            let offset = TextSize::from(offset as u32);
+            let tree = |module| (module == ModuleName::new("Main")).then_some(&tree);
+            resolve_at_offset(&node, &module, &source_map, &scope, tree, offset)
+        };
+
+        let Some(Reference { kind, target: Target::Global(maybe), .. }) = resolve("Maybe Int", 0)
+        else {
+            panic!()
+        };
+        assert_eq!(kind, ReferenceKind::Name(Namespace::Type));
+        assert_eq!(maybe.module, ModuleName::new("Data.Maybe"));
+
+        let Some(Reference { target: Target::Modules(modules), .. }) = resolve("M.Just", 0) else {
+            panic!()
+        };
+        assert_eq!(modules, [ModuleName::new("Data.Maybe")]);
+
+        let Some(Reference { kind, target: Target::Global(just), .. }) = resolve("Just x", 0)
+        else {
+            panic!()
+        };
+        assert_eq!(kind, ReferenceKind::Name(Namespace::Constructor));
+        assert_eq!(just.member, Some(0));
+
+        // The `x` in the record refers to the binder in the arguments.
+        let Some(Reference { target: Target::Local { body, binder }, .. }) = resolve("x }", 0)
+        else {
+            panic!()
+        };
+        assert_eq!(module.bodies[body].binders[binder], Binder::Variable(Name::new("x")));
+        assert_eq!(resolve("x)", 0).unwrap().target, Target::Local { body, binder });
+
+        let label = resolve("label", 1).unwrap();
+        assert_eq!(
+            (label.kind, label.target),
+            (ReferenceKind::Label, Target::Label(Name::new("label")))
+        );
+        // Instance members refer to the members of their class.
+        let Some(Reference { target: Target::Global(show), .. }) = resolve("show _", 0) else {
+            panic!()
+        };
+        assert_eq!((show.module, show.member), (ModuleName::new("Main"), Some(0)));
+        assert_eq!(resolve("::", 0), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Warnings for names bound in a body that hide another name.
+
+use lowering::{hir::Module, name::Name, source_map::SourceMap};
+use rowan::ast::SyntaxNodePtr;
+use syntax::PureScript;
+
+use crate::{
+    body::{BodyResolution, ModuleScope},
+    interface::{Definition, Namespace},
+};
+
+/// Which kinds of shadowing to warn about. Shadowing is idiomatic in a lot of
+/// code, so every warning is disabled by default and enabled per workspace.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowingConfig {
+    /// Binders that shadow a binder of an enclosing scope.
+    pub locals: bool,
+    /// Binders that shadow a declaration or an unqualified import.
+    pub globals: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShadowingWarning {
+    Local {
+        name: Name,
+        ptr: SyntaxNodePtr<PureScript>,
+        previous: SyntaxNodePtr<PureScript>,
+    },
+    /// The shadowed definition may be in another module, so it's up to the
+    /// caller to find its syntax.
+    Global {
+        name: Name,
+        ptr: SyntaxNodePtr<PureScript>,
+        definition: Definition,
+    },
+}
+
+impl ShadowingWarning {
+    pub fn message(&self) -> String {
+        match self {
+            ShadowingWarning::Local { name, .. } => format!("{name} shadows an enclosing binding"),
+            ShadowingWarning::Global { name, definition, .. } => {
+                format!("{name} shadows {}.{name}", definition.module)
+            }
+        }
+    }
+
+    pub fn ptr(&self) -> &SyntaxNodePtr<PureScript> {
+        match self {
+            ShadowingWarning::Local { ptr, .. } | ShadowingWarning::Global { ptr, .. } => ptr,
+        }
+    }
+}
+
+/// Reports the binders in every body of `module` that shadow another name,
+/// as enabled by `config`. Binders introduced by desugaring have no syntax
+/// and are never reported.
+pub fn check_shadowing(
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    config: ShadowingConfig,
+) -> Vec<ShadowingWarning> {
+    let mut warnings = vec![];
+    if !config.locals && !config.globals {
+        return warnings;
+    }
+
+    for (id, body) in module.bodies.iter() {
+        let resolution = BodyResolution::new(body, scope);
+        let scopes = resolution.scopes();
+        let source_map = source_map.body(id);
+        for (_, current) in scopes.iter() {
+            for entry in &current.entries {
+                let Some(ptr) = source_map.binder_syntax(entry.binder).cloned() else { continue };
+                let name = entry.name;
+
+                let enclosing = current.parent.into_iter().flat_map(|parent| {
+                    scopes
+                        .scope_chain(parent)
+                        .flat_map(|parent| scopes.scope(parent).entries.iter().rev())
+                });
+                let previous = enclosing
+                    .filter(|previous| previous.name == name)
+                    .find_map(|previous| source_map.binder_syntax(previous.binder));
+                if let Some(previous) = previous {
+                    if config.locals {
+                        let previous = previous.clone();
+                        warnings.push(ShadowingWarning::Local { name, ptr, previous });
+                    }
+                    continue;
+                }
+
+                if config.globals {
+                    if let Some(&definition) = scope.lookup(None, Namespace::Value, name).first() {
+                        warnings.push(ShadowingWarning::Global { name, ptr, definition });
+                    }
+                }
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, lower_module_with_source_map};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::{check_shadowing, ShadowingConfig};
+    use crate::{body::ModuleScope, imports::Imports};
+
+    #[test]
+    fn report_shadowing_when_enabled() {
+        let source = "module Main where\n\nx = 0\n\nf x = \\y -> case y of\n  y -> 0\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let (module, source_map) = lower_module_with_source_map(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+
+        let check = |config| {
+            let warnings = check_shadowing(&module, &source_map, &scope, config);
+            let warnings = warnings.iter().map(|warning| {
+                let range = warning.ptr().text_range();
+                (warning.message(), u32::from(range.start()))
+            });
+            warnings.collect::<Vec<_>>()
+        };
+        assert_eq!(check(ShadowingConfig::default()), []);
+        assert_eq!(
+            check(ShadowingConfig { locals: true, globals: true }),
+            [
+                ("x shadows Main.x".to_string(), 28),
+                ("y shadows an enclosing binding".to_string(), 50),
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Resolution of the names within types.
+
+use lowering::{
+    arena::{Arena, ArenaMap},
+    hir::{Type, TypeId},
+};
+
+use crate::{
+    body::{ModuleScope, Resolution},
+    interface::Namespace,
+};
+
+/// The resolutions of the constructors and operators in the types of a
+/// module. Type variables are bound by their `forall`, and aren't resolved.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TypeResolution {
+    /// Constructors may resolve to either a type or a class, so the namespace
+    /// each name was found in is kept along with its resolution.
+    types: ArenaMap<TypeId, (Namespace, Resolution)>,
+    /// The resolutions of the operators in each operator chain.
+    operators: ArenaMap<TypeId, Vec<Option<Resolution>>>,
+}
+
+impl TypeResolution {
+    pub fn new(types: &Arena<Type>, scope: &ModuleScope) -> TypeResolution {
+        let mut resolution = TypeResolution::default();
+        for (id, type_) in types.iter() {
+            match type_ {
+                // Constraints are parsed as types, so classes are resolved
+                // where types are.
+                &Type::Constructor(name) => {
+                    let resolved = [Namespace::Type, Namespace::Class]
+                        .into_iter()
+                        .find_map(|namespace| Some((namespace, scope.resolve(namespace, name)?)));
+                    if let Some(resolved) = resolved {
+                        resolution.types.insert(id, resolved);
+                    }
+                }
+                &Type::OperatorName(name) => {
+                    if let Some(resolved) = scope.resolve(Namespace::TypeOperator, name) {
+                        resolution.types.insert(id, (Namespace::TypeOperator, resolved));
+                    }
+                }
+                Type::OperatorChain { tail, .. } => {
+                    let resolved =
+                        tail.iter().map(|&(name, _)| scope.resolve(Namespace::TypeOperator, name));
+                    resolution.operators.insert(id, resolved.collect());
+                }
+                _ => {}
+            }
+        }
+        resolution.types.shrink_to_fit();
+        resolution.operators.shrink_to_fit();
+        resolution
+    }
+
+    /// Returns `None` for types that aren't names, and for names that don't
+    /// resolve to anything.
+    pub fn resolution(&self, type_: TypeId) -> Option<&Resolution> {
+        self.types.get(type_).map(|(_, resolution)| resolution)
+    }
+
+    /// The namespace a name was resolved in, which for constructors is
+    /// either [`Namespace::Type`] or [`Namespace::Class`].
+    pub fn namespace(&self, type_: TypeId) -> Option<Namespace> {
+        self.types.get(type_).map(|&(namespace, _)| namespace)
+    }
+
+    /// The resolution of the operator at `index` in the tail of an operator
+    /// chain.
+    pub fn operator_resolution(&self, chain: TypeId, index: usize) -> Option<&Resolution> {
+        self.operators.get(chain)?.get(index)?.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{hir::Type, item_tree::ItemTree, lower_module, name::Name};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::TypeResolution;
+    use crate::{
+        body::{ModuleScope, Resolution},
+        imports::Imports,
+        interface::Namespace,
+    };
+
+    #[test]
+    fn separate_namespaces() {
+        let source = "module Main where\n\ndata T = T\n\nclass C\n\nf :: C => T -> T\nf T = T\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let node = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&node);
+        let module = lower_module(&node);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let resolution = TypeResolution::new(&module.types, &scope);
+
+        let constructors = module.types.iter().filter_map(|(id, type_)| match type_ {
+            Type::Constructor(name) => {
+                let Some(Resolution::Global(definition)) = resolution.resolution(id) else {
+                    panic!()
+                };
+                Some((name.name.as_str(), resolution.namespace(id)?, definition.member))
+            }
+            _ => None,
+        });
+        let mut constructors: Vec<_> = constructors.collect();
+        constructors.dedup();
+        assert_eq!(constructors, [("C", Namespace::Class, None), ("T", Namespace::Type, None)]);
+        // The constructor `T` is a member of the data declaration, unlike the
+        // type of the same name.
+        let [constructor] = &scope.lookup(None, Namespace::Constructor, Name::new("T"))[..] else {
+            panic!()
+        };
+        assert_eq!(constructor.member, Some(0));
+    }
+}
@@ -0,0 +1,262 @@
+//! Detection of imports that nothing in a module refers to.
+
+use lowering::{
+    hir::{DataMembers, Export, Import, ImportItem, Module},
+    lower_import, lower_import_item,
+    name::{ModuleName, Name},
+    source_map::SourceMap,
+};
+use rowan::ast::{AstPtr, SyntaxNodePtr};
+use rustc_hash::FxHashSet;
+use syntax::{ast, PureScript};
+
+use crate::{
+    body::{ModuleScope, Resolution},
+    diagnostics::for_each_name,
+    imports,
+    interface::{Definition, Interface, Namespace},
+};
+
+/// An import, or part of one, that can be removed without changing what any
+/// name in the module refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnusedImport {
+    /// An unqualified import that no name is resolved through.
+    Import { module: ModuleName, ptr: AstPtr<ast::ImportDeclaration> },
+    /// A qualified import that no name under its alias is resolved through.
+    Alias { alias: ModuleName, ptr: AstPtr<ast::ImportDeclaration> },
+    /// An item in an explicit import list that's never referred to, while
+    /// the rest of the import is used.
+    Item { name: Name, ptr: AstPtr<ast::ImportItem> },
+}
+
+impl UnusedImport {
+    pub fn message(&self) -> String {
+        match self {
+            UnusedImport::Import { module, .. } => format!("the import of {module} is unused"),
+            UnusedImport::Alias { alias, .. } => format!("the alias {alias} is unused"),
+            UnusedImport::Item { name, .. } => format!("{name} is imported but never used"),
+        }
+    }
+
+    /// The syntax to remove to fix the diagnostic, which is either the whole
+    /// import declaration or a single item.
+    pub fn ptr(&self) -> SyntaxNodePtr<PureScript> {
+        match self {
+            UnusedImport::Import { ptr, .. } | UnusedImport::Alias { ptr, .. } => {
+                ptr.syntax_node_ptr()
+            }
+            UnusedImport::Item { ptr, .. } => ptr.syntax_node_ptr(),
+        }
+    }
+}
+
+/// The definitions a module refers to, keyed by the qualifier they're
+/// referred to with.
+type Usages = FxHashSet<(Option<ModuleName>, Namespace, Definition)>;
+
+/// Reports the imports in `root` that nothing in `module` refers to, where
+/// `interface` returns the interface of each module in the workspace.
+/// Imports of modules that aren't in the workspace are never reported, and
+/// neither are empty import lists, which are written on purpose.
+pub fn check_unused_imports<'a>(
+    root: &ast::Module,
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    interface: impl Fn(ModuleName) -> Option<&'a Interface>,
+) -> Vec<UnusedImport> {
+    let usages = usages(module, source_map, scope);
+    let exported_modules: Vec<_> = module
+        .exports
+        .iter()
+        .flatten()
+        .filter_map(|export| match export {
+            &Export::Module(module) => Some(module),
+            _ => None,
+        })
+        .collect();
+
+    let mut unused = vec![];
+    let Some(header) = root.header() else { return unused };
+    for declaration in header.imports() {
+        let Some(import) = lower_import(&declaration) else { continue };
+        let Some(interface) = interface(import.module) else { continue };
+        if exported_modules.contains(&import.alias.unwrap_or(import.module)) {
+            continue;
+        }
+
+        let imported = imports::imported_names(&import, interface, &mut vec![]);
+        let is_used =
+            |namespace, definition| usages.contains(&(import.alias, namespace, definition));
+        let used = Namespace::ALL.into_iter().any(|namespace| {
+            imported.get(namespace).values().any(|&definition| is_used(namespace, definition))
+        });
+        if !used {
+            let ptr = AstPtr::new(&declaration);
+            let is_empty =
+                import.list.as_ref().is_some_and(|list| !list.hiding && list.items.is_empty());
+            match import.alias {
+                _ if is_empty => {}
+                Some(alias) => unused.push(UnusedImport::Alias { alias, ptr }),
+                None => unused.push(UnusedImport::Import { module: import.module, ptr }),
+            }
+            continue;
+        }
+
+        // Hidden names aren't in scope at all, so only explicit lists have
+        // items that can be unused.
+        let Some(list) = declaration.import_list().filter(|list| !list.hiding()) else { continue };
+        for item in list.items() {
+            let Some(lowered) = lower_import_item(&item) else { continue };
+            let names = imports::items(import.module, interface, &lowered, &mut vec![]);
+            // Unknown names are reported by `Imports` already.
+            let is_item_used = names.iter().any(|&(namespace, name)| {
+                imported.get(namespace).get(&name).is_some_and(|&item| is_used(namespace, item))
+            });
+            if names.is_empty() || is_item_used {
+                continue;
+            }
+            let name = names[0].1;
+            unused.push(UnusedImport::Item { name, ptr: AstPtr::new(&item) });
+        }
+    }
+    unused
+}
+
+/// The items that an explicit list would need for `import` to keep bringing
+/// every name that `module` refers to through it into scope. A constructor
+/// is listed with its type, as `T(..)` when every constructor is used.
+pub fn used_items(
+    module: &Module,
+    source_map: &SourceMap,
+    scope: &ModuleScope,
+    import: &Import,
+    interface: &Interface,
+) -> Vec<ImportItem> {
+    let usages = usages(module, source_map, scope);
+    let imported = imports::imported_names(import, interface, &mut vec![]);
+    let mut items = vec![];
+    let mut constructors: Vec<(Name, Vec<Name>)> = vec![];
+    for namespace in Namespace::ALL {
+        for (&name, &definition) in imported.get(namespace) {
+            if !usages.contains(&(import.alias, namespace, definition)) {
+                continue;
+            }
+            let item = match namespace {
+                Namespace::Value => ImportItem::Value(name),
+                Namespace::Operator => ImportItem::Operator(name),
+                Namespace::Type => ImportItem::Type(name, None),
+                Namespace::TypeOperator => ImportItem::TypeOperator(name),
+                Namespace::Class => ImportItem::Class(name),
+                Namespace::Constructor => {
+                    let type_constructors = interface.type_constructors.iter();
+                    let mut types = type_constructors.filter(|(_, names)| names.contains(&name));
+                    let Some((&type_, _)) = types.next() else { continue };
+                    match constructors.iter_mut().find(|(other, _)| *other == type_) {
+                        Some((_, names)) => names.push(name),
+                        None => constructors.push((type_, vec![name])),
+                    }
+                    continue;
+                }
+            };
+            items.push(item);
+        }
+    }
+    for (type_, names) in constructors {
+        let all = &interface.type_constructors[&type_];
+        let members = if all.iter().all(|name| names.contains(name)) {
+            DataMembers::All
+        } else {
+            DataMembers::Enumerated(names)
+        };
+        items.retain(|item| *item != ImportItem::Type(type_, None));
+        items.push(ImportItem::Type(type_, Some(members)));
+    }
+    items
+}
+
+fn usages(module: &Module, source_map: &SourceMap, scope: &ModuleScope) -> Usages {
+    let mut usages = Usages::default();
+    for_each_name(module, source_map, scope, |namespace, name, resolution, _| {
+        let definitions = match resolution {
+            Some(Resolution::Global(definition)) => std::slice::from_ref(definition),
+            Some(Resolution::Ambiguous(definitions)) => definitions.as_slice(),
+            _ => return,
+        };
+        for &definition in definitions {
+            usages.insert((name.qualifier, namespace, definition));
+        }
+    });
+
+    // Exported names are resolved against declarations before imports, the
+    // same as in `Exports`.
+    let mut export = |namespace, name: Name| {
+        if scope.declarations.names.get(namespace).contains_key(&name) {
+            return;
+        }
+        for &definition in scope.imports.lookup(None, namespace, name) {
+            usages.insert((None, namespace, definition));
+        }
+    };
+    for item in module.exports.iter().flatten() {
+        match item {
+            &Export::Value(name) => export(Namespace::Value, name),
+            &Export::Operator(name) => export(Namespace::Operator, name),
+            Export::Type(name, members) => {
+                export(Namespace::Type, *name);
+                if let Some(DataMembers::Enumerated(constructors)) = members {
+                    for &constructor in constructors {
+                        export(Namespace::Constructor, constructor);
+                    }
+                }
+            }
+            &Export::TypeOperator(name) => export(Namespace::TypeOperator, name),
+            &Export::Class(name) => export(Namespace::Class, name),
+            Export::Module(_) => {}
+        }
+    }
+    usages
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, lower_module_with_source_map, name::ModuleName};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_unused_imports;
+    use crate::{body::ModuleScope, imports::Imports, interface::Interface};
+
+    #[test]
+    fn report_unused_imports() {
+        let maybe = "module Data.Maybe where\n\ndata Maybe a = Just a | Nothing\n\nfromMaybe = 0\nisJust = 0\n\nclass Default a\n";
+        let (node, _) = parsing::parse_module(maybe);
+        let maybe = Interface::local(&ItemTree::lower(&ast::Module::cast(node).unwrap()));
+
+        let source = "module Main where\n\nimport Data.Maybe (Maybe(..), fromMaybe, isJust, class Default)\nimport Data.Maybe as M\nimport Data.Maybe as N\nimport Data.Maybe ()\nimport Data.Maybe hiding (isJust)\nimport Missing\n\nf :: Default Int => Maybe Int\nf = M.fromMaybe Nothing\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let (module, source_map) = lower_module_with_source_map(&root);
+        let interface = |module| (module == ModuleName::new("Data.Maybe")).then_some(&maybe);
+        let scope = ModuleScope::new(&tree, Imports::resolve(&tree, interface));
+
+        let unused = check_unused_imports(&root, &module, &source_map, &scope, interface);
+        let unused: Vec<_> = unused
+            .iter()
+            .map(|unused| (unused.message(), &source[unused.ptr().text_range()]))
+            .collect();
+        // `Maybe` and `Nothing` are brought into scope by both the first and
+        // the last import, so both count as used.
+        assert_eq!(
+            unused,
+            [
+                ("fromMaybe is imported but never used".to_string(), "fromMaybe"),
+                ("isJust is imported but never used".to_string(), "isJust"),
+                ("the alias N is unused".to_string(), "import Data.Maybe as N"),
+            ]
+        );
+    }
+}
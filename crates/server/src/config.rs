@@ -0,0 +1,141 @@
+//! The settings of the client that change how features behave.
+
+use ide::{
+    CodeActionConfig, CodeLensConfig, ImportGrouping, ImportStyle, InlayHintsConfig, Severity,
+    ShadowingConfig,
+};
+use json::Json;
+
+/// Settings are read from the `initializationOptions` of the `initialize`
+/// request, and again from each `workspace/didChangeConfiguration`, where
+/// settings that are missing keep their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    pub diagnostics: DiagnosticsConfig,
+    pub inlay_hints: InlayHintsConfig,
+    pub code_lens: CodeLensConfig,
+    pub code_actions: CodeActionConfig,
+    pub formatter: Formatter,
+    pub build: BuildConfig,
+}
+
+/// Which diagnostics are published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticsConfig {
+    pub enabled: bool,
+    /// The codes of the kinds of diagnostics that aren't published, such
+    /// as `wildcard-import`.
+    pub disabled: Vec<String>,
+    pub shadowing: ShadowingConfig,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> DiagnosticsConfig {
+        DiagnosticsConfig { enabled: true, disabled: vec![], shadowing: ShadowingConfig::default() }
+    }
+}
+
+impl DiagnosticsConfig {
+    pub fn is_enabled(&self, code: &str) -> bool {
+        self.enabled && !self.disabled.iter().any(|disabled| disabled == code)
+    }
+}
+
+/// What formats documents, which depends on what the workspace uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Formatter {
+    /// The formatter of `ide`, which only normalizes whitespace.
+    #[default]
+    Builtin,
+    /// A `purs-tidy` binary, run in the root of the workspace such that it
+    /// reads the settings there.
+    PursTidy { path: String },
+}
+
+/// Where the binaries that build the workspace are, which are looked up on
+/// the `PATH` by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildConfig {
+    pub purs: String,
+    pub spago: String,
+}
+
+impl Default for BuildConfig {
+    fn default() -> BuildConfig {
+        BuildConfig { purs: "purs".to_string(), spago: "spago".to_string() }
+    }
+}
+
+impl Config {
+    pub fn from_json(options: &Json) -> Config {
+        let mut config = Config::default();
+        let diagnostics = options.get("diagnostics");
+        set(&mut config.diagnostics.enabled, diagnostics.get("enabled"));
+        if let Some(disabled) = diagnostics.get("disabled").as_array() {
+            let disabled = disabled.iter().filter_map(Json::as_str).map(str::to_string);
+            config.diagnostics.disabled = disabled.collect();
+        }
+        let shadowing = diagnostics.get("shadowing");
+        let shadowing_config = &mut config.diagnostics.shadowing;
+        set(&mut shadowing_config.locals, shadowing.get("locals"));
+        set(&mut shadowing_config.globals, shadowing.get("globals"));
+        if let Some(severity) = shadowing.get("severity").as_str().and_then(severity) {
+            shadowing_config.severity = severity;
+        }
+        if let Some(allowed) = shadowing.get("allowed").as_array() {
+            let allowed = allowed.iter().filter_map(Json::as_str).map(str::to_string);
+            shadowing_config.allowed = allowed.collect();
+        }
+        let hints = options.get("inlayHints");
+        let inlay_hints = &mut config.inlay_hints;
+        set(&mut inlay_hints.declarations, hints.get("declarations"));
+        set(&mut inlay_hints.bindings, hints.get("bindings"));
+        set(&mut inlay_hints.binds, hints.get("binds"));
+        let lenses = options.get("codeLens");
+        let code_lens = &mut config.code_lens;
+        set(&mut code_lens.signatures, lenses.get("signatures"));
+        set(&mut code_lens.references, lenses.get("references"));
+        let actions = options.get("codeActions");
+        config.code_actions.import_style = match actions.get("importStyle").as_str() {
+            Some("qualified") => ImportStyle::Qualified,
+            Some("open") => ImportStyle::Open,
+            _ => ImportStyle::Item,
+        };
+        let import_layout = &mut config.code_actions.import_layout;
+        import_layout.grouping = match actions.get("importGroups").as_str() {
+            Some("origin") => ImportGrouping::Origin,
+            _ => ImportGrouping::Qualification,
+        };
+        set(&mut import_layout.align_aliases, actions.get("alignImportAliases"));
+        import_layout.max_width = actions.get("importListWidth").as_u32();
+        let formatting = options.get("formatting");
+        if formatting.get("formatter").as_str() == Some("purs-tidy") {
+            let path = formatting.get("pursTidyPath").as_str().unwrap_or("purs-tidy");
+            config.formatter = Formatter::PursTidy { path: path.to_string() };
+        }
+        let build = options.get("build");
+        if let Some(purs) = build.get("pursPath").as_str() {
+            config.build.purs = purs.to_string();
+        }
+        if let Some(spago) = build.get("spagoPath").as_str() {
+            config.build.spago = spago.to_string();
+        }
+        config
+    }
+}
+
+fn severity(name: &str) -> Option<Severity> {
+    match name {
+        "error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "information" => Some(Severity::Information),
+        "hint" => Some(Severity::Hint),
+        _ => None,
+    }
+}
+
+fn set(setting: &mut bool, value: &Json) {
+    if let Some(value) = value.as_bool() {
+        *setting = value;
+    }
+}
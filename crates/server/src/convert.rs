@@ -0,0 +1,336 @@
+//! Conversions between the types of the protocol and those of the analyzer.
+
+use std::path::{Path, PathBuf};
+
+use ide::{
+    CodeActionKind, CompletionItem, CompletionKind, Diagnostic, DocumentSymbol, FileRange,
+    FoldingKind, FoldingRange, SemanticToken, SemanticTokenKind, Severity, SignatureHelp,
+    SymbolKind, TextEdit,
+};
+use json::Json;
+use rowan::{TextRange, TextSize};
+
+use crate::line_index::{LineCol, LineIndex, PositionEncoding};
+
+/// The path of a `file` URI, decoding escaped characters.
+pub fn path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let digits = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(digits, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // Windows paths are written as `/c:/...`.
+    let windows = path.get(2..3) == Some(":") && path.starts_with('/');
+    Some(PathBuf::from(if windows { &path[1..] } else { &path[..] }))
+}
+
+/// The `file` URI of a path, escaping the characters that aren't allowed in
+/// one.
+pub fn uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+pub fn offset(index: &LineIndex, position: &Json) -> Option<TextSize> {
+    let line = position.get("line").as_u32()?;
+    let col = position.get("character").as_u32()?;
+    index.offset(index.to_utf8(LineCol { line, col }))
+}
+
+pub fn text_range(index: &LineIndex, range: &Json) -> Option<TextRange> {
+    let start = offset(index, range.get("start"))?;
+    let end = offset(index, range.get("end"))?;
+    (start <= end).then(|| TextRange::new(start, end))
+}
+
+pub fn position(index: &LineIndex, offset: TextSize) -> Json {
+    let LineCol { line, col } = index.to_encoded(index.line_col(offset));
+    Json::object([("line", line.into()), ("character", col.into())])
+}
+
+pub fn range(index: &LineIndex, range: TextRange) -> Json {
+    Json::object([("start", position(index, range.start())), ("end", position(index, range.end()))])
+}
+
+/// A diagnostic, with `location` converting the ranges of its related
+/// information, which can be in other files.
+pub fn diagnostic(
+    index: &LineIndex,
+    diagnostic: Diagnostic,
+    location: impl Fn(FileRange) -> Json,
+) -> Json {
+    let severity: u32 = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Information => 3,
+        Severity::Hint => 4,
+    };
+    let mut fields = vec![
+        ("range", range(index, diagnostic.range)),
+        ("severity", severity.into()),
+        ("code", diagnostic.code.into()),
+        ("source", "purescript-analyzer".into()),
+        ("message", diagnostic.message.into()),
+    ];
+    if diagnostic.unnecessary {
+        fields.push(("tags", vec![Json::from(1u32)].into()));
+    }
+    if !diagnostic.related.is_empty() {
+        let related = diagnostic.related.into_iter().map(|(range, message)| {
+            Json::object([("location", location(range)), ("message", message.into())])
+        });
+        fields.push(("relatedInformation", related.collect::<Vec<_>>().into()));
+    }
+    Json::object(fields)
+}
+
+pub fn symbol_kind(kind: SymbolKind) -> Json {
+    let kind: u32 = match kind {
+        SymbolKind::Value | SymbolKind::ForeignValue => 12,
+        SymbolKind::Data => 10,
+        SymbolKind::Newtype | SymbolKind::ForeignData => 23,
+        SymbolKind::Synonym => 26,
+        SymbolKind::Constructor => 22,
+        SymbolKind::Class => 11,
+        SymbolKind::Member => 6,
+        SymbolKind::Instance => 19,
+        SymbolKind::Operator => 25,
+    };
+    kind.into()
+}
+
+pub fn document_symbol(index: &LineIndex, symbol: DocumentSymbol) -> Json {
+    let children = symbol.children.into_iter().map(|child| document_symbol(index, child));
+    let mut fields = vec![
+        ("name", symbol.name.into()),
+        ("kind", symbol_kind(symbol.kind)),
+        ("range", range(index, symbol.full_range)),
+        ("selectionRange", range(index, symbol.focus_range)),
+        ("children", children.collect::<Vec<_>>().into()),
+    ];
+    if let Some(detail) = symbol.detail {
+        fields.push(("detail", detail.into()));
+    }
+    Json::object(fields)
+}
+
+/// A folding range by its lines, which folds away the lines after its
+/// first one.
+pub fn folding_range(index: &LineIndex, range: FoldingRange) -> Json {
+    let mut fields = vec![
+        ("startLine", index.line_col(range.range.start()).line.into()),
+        ("endLine", index.line_col(range.range.end()).line.into()),
+    ];
+    let kind = match range.kind {
+        FoldingKind::Imports => Some("imports"),
+        FoldingKind::Comment => Some("comment"),
+        FoldingKind::Region => Some("region"),
+        FoldingKind::Code => None,
+    };
+    if let Some(kind) = kind {
+        fields.push(("kind", kind.into()));
+    }
+    Json::object(fields)
+}
+
+pub fn completion_item(index: &LineIndex, item: CompletionItem) -> Json {
+    let kind: u32 = match item.kind {
+        CompletionKind::Local => 6,
+        CompletionKind::Field => 5,
+        CompletionKind::Module => 9,
+        CompletionKind::Snippet => 15,
+        CompletionKind::Declaration(kind) => match kind {
+            SymbolKind::Value | SymbolKind::ForeignValue => 3,
+            SymbolKind::Constructor => 4,
+            SymbolKind::Data => 13,
+            SymbolKind::Newtype | SymbolKind::ForeignData => 22,
+            SymbolKind::Synonym => 25,
+            SymbolKind::Class => 8,
+            SymbolKind::Member => 2,
+            SymbolKind::Instance => 12,
+            SymbolKind::Operator => 24,
+        },
+    };
+    let mut fields = vec![("label", item.label.as_str().into()), ("kind", kind.into())];
+    if let Some(detail) = item.detail {
+        fields.push(("detail", detail.into()));
+    }
+    if let Some(documentation) = item.documentation {
+        let markdown = Json::object([("kind", "markdown".into()), ("value", documentation.into())]);
+        fields.push(("documentation", markdown));
+    }
+    match (item.replace, item.insert_text) {
+        (Some(range), insert_text) => {
+            let edit = TextEdit::replace(range, insert_text.unwrap_or(item.label));
+            fields.push(("textEdit", text_edit(index, edit)));
+        }
+        (None, Some(insert_text)) => fields.push(("insertText", insert_text.into())),
+        (None, None) => {}
+    }
+    if item.snippet {
+        fields.push(("insertTextFormat", 2.into()));
+    }
+    if let Some(filter_text) = item.filter_text {
+        fields.push(("filterText", filter_text.into()));
+    }
+    if let Some(module) = item.import {
+        let description = Json::object([("description", module.as_str().into())]);
+        fields.push(("labelDetails", description));
+    }
+    if !item.additional_edits.is_empty() {
+        let edits = item.additional_edits.into_iter().map(|edit| text_edit(index, edit));
+        fields.push(("additionalTextEdits", edits.collect::<Vec<_>>().into()));
+    }
+    Json::object(fields)
+}
+
+/// A signature with its parameters as offsets into its label, which are
+/// counted in the same units as positions are.
+pub fn signature_help(help: SignatureHelp, encoding: PositionEncoding) -> Json {
+    let encoded = |offset: TextSize| encoding.len(&help.label[..usize::from(offset)]);
+    let parameters = help.parameters.iter().map(|range| {
+        let offsets: Vec<Json> = vec![encoded(range.start()).into(), encoded(range.end()).into()];
+        Json::object([("label", offsets.into())])
+    });
+    let mut signature = vec![
+        ("label", help.label.as_str().into()),
+        ("parameters", parameters.collect::<Vec<_>>().into()),
+    ];
+    if let Some(documentation) = help.documentation {
+        let markdown = Json::object([("kind", "markdown".into()), ("value", documentation.into())]);
+        signature.push(("documentation", markdown));
+    }
+    Json::object([
+        ("signatures", vec![Json::object(signature)].into()),
+        ("activeSignature", 0.into()),
+        ("activeParameter", help.active_parameter.into()),
+    ])
+}
+
+pub fn text_edit(index: &LineIndex, edit: TextEdit) -> Json {
+    Json::object([("range", range(index, edit.range)), ("newText", edit.new_text.into())])
+}
+
+/// The kinds of code actions that the server offers.
+pub const CODE_ACTION_KINDS: [&str; 6] = [
+    "quickfix",
+    "refactor.rewrite",
+    "refactor.extract",
+    "refactor.inline",
+    "refactor.move",
+    "source.organizeImports",
+];
+
+pub fn code_action_kind(kind: CodeActionKind) -> &'static str {
+    match kind {
+        CodeActionKind::QuickFix => "quickfix",
+        CodeActionKind::RefactorRewrite => "refactor.rewrite",
+        CodeActionKind::RefactorExtract => "refactor.extract",
+        CodeActionKind::RefactorInline => "refactor.inline",
+        CodeActionKind::RefactorMove => "refactor.move",
+        CodeActionKind::SourceOrganizeImports => "source.organizeImports",
+    }
+}
+
+/// The token types of the legend, which tokens refer to by index.
+pub const SEMANTIC_TOKEN_TYPES: [&str; 13] = [
+    "namespace",
+    "type",
+    "interface",
+    "enumMember",
+    "typeParameter",
+    "function",
+    "variable",
+    "property",
+    "operator",
+    "keyword",
+    "comment",
+    "string",
+    "number",
+];
+
+/// Encodes tokens as the protocol expects them: five integers per token,
+/// with its line and start relative to the token before it. Tokens that
+/// span several lines, such as block comments, are split at each line.
+pub fn semantic_tokens(index: &LineIndex, text: &str, tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = vec![];
+    let mut last = LineCol { line: 0, col: 0 };
+    for token in tokens {
+        let kind = semantic_token_type(token.kind);
+        let mut start = usize::from(token.range.start());
+        for line in text[token.range].split('\n') {
+            let end = start + line.trim_end_matches('\r').len();
+            let from = index.to_encoded(index.line_col(TextSize::from(start as u32)));
+            let to = index.to_encoded(index.line_col(TextSize::from(end as u32)));
+            if to.col > from.col {
+                let col = if from.line == last.line { from.col - last.col } else { from.col };
+                data.extend([from.line - last.line, col, to.col - from.col, kind, 0]);
+                last = from;
+            }
+            start += line.len() + 1;
+        }
+    }
+    data
+}
+
+fn semantic_token_type(kind: SemanticTokenKind) -> u32 {
+    match kind {
+        SemanticTokenKind::Module => 0,
+        SemanticTokenKind::Type => 1,
+        SemanticTokenKind::Class => 2,
+        SemanticTokenKind::Constructor => 3,
+        SemanticTokenKind::TypeVariable => 4,
+        SemanticTokenKind::Value => 5,
+        SemanticTokenKind::Local => 6,
+        SemanticTokenKind::Label => 7,
+        SemanticTokenKind::Operator => 8,
+        SemanticTokenKind::Keyword => 9,
+        SemanticTokenKind::Comment => 10,
+        SemanticTokenKind::String => 11,
+        SemanticTokenKind::Number => 12,
+    }
+}
+
+/// The edit that turns the tokens that were sent last into the new ones,
+/// which keeps what they start and end with in common. Tokens are relative
+/// to the one before them, so editing the text only changes the tokens
+/// around the edit, rather than every token after it.
+pub fn semantic_tokens_edits(old: &[u32], new: &[u32]) -> Json {
+    if old == new {
+        return Json::Array(vec![]);
+    }
+    let prefix = common_tokens(old.chunks(5), new.chunks(5));
+    let suffix = common_tokens(old[prefix..].rchunks(5), new[prefix..].rchunks(5));
+    let data = new[prefix..new.len() - suffix].iter().map(|&integer| integer.into());
+    let edit = Json::object([
+        ("start", prefix.into()),
+        ("deleteCount", (old.len() - prefix - suffix).into()),
+        ("data", data.collect::<Vec<_>>().into()),
+    ]);
+    Json::Array(vec![edit])
+}
+
+/// The number of integers of the tokens that two encodings start with.
+fn common_tokens<'a>(
+    old: impl Iterator<Item = &'a [u32]>,
+    new: impl Iterator<Item = &'a [u32]>,
+) -> usize {
+    old.zip(new).take_while(|(old, new)| old == new).count() * 5
+}
@@ -0,0 +1,10 @@
+//! The language server, which speaks the Language Server Protocol to the
+//! editor and answers its requests from the analysis database.
+
+pub mod config;
+pub mod convert;
+pub mod line_index;
+pub mod protocol;
+mod server;
+
+pub use crate::server::{run, Server};
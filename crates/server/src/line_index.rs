@@ -0,0 +1,214 @@
+//! Converting between offsets into a file and the positions of the client.
+//!
+//! The analyzer works in byte offsets, while the client counts lines and,
+//! by default, the UTF-16 code units within a line. The [`LineIndex`] of a
+//! file knows where each line starts and where the characters that take
+//! more than one byte are, such that lines without any only need a lookup.
+//! Clients that count bytes as well don't need the characters at all.
+
+use rowan::TextSize;
+use rustc_hash::FxHashMap;
+
+/// A line and the byte offset within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// The units that the client counts the columns of positions in, which is
+/// agreed on when the server is initialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    pub fn from_name(name: &str) -> Option<PositionEncoding> {
+        match name {
+            "utf-8" => Some(PositionEncoding::Utf8),
+            "utf-16" => Some(PositionEncoding::Utf16),
+            "utf-32" => Some(PositionEncoding::Utf32),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PositionEncoding::Utf8 => "utf-8",
+            PositionEncoding::Utf16 => "utf-16",
+            PositionEncoding::Utf32 => "utf-32",
+        }
+    }
+
+    /// The encoding to use with a client that supports `offered`: UTF-8 if
+    /// it's among them, since positions are then byte offsets like those of
+    /// the analyzer, and otherwise the first that the server knows. Clients
+    /// that don't say only support UTF-16.
+    pub fn negotiate<'a>(offered: impl IntoIterator<Item = &'a str>) -> PositionEncoding {
+        let offered: Vec<_> = offered.into_iter().filter_map(PositionEncoding::from_name).collect();
+        if offered.contains(&PositionEncoding::Utf8) {
+            return PositionEncoding::Utf8;
+        }
+        offered.first().copied().unwrap_or_default()
+    }
+
+    /// The length of `text` in the units of the encoding.
+    pub fn len(self, text: &str) -> usize {
+        match self {
+            PositionEncoding::Utf8 => text.len(),
+            PositionEncoding::Utf16 => text.encode_utf16().count(),
+            PositionEncoding::Utf32 => text.chars().count(),
+        }
+    }
+
+    /// The units taken by a character of `bytes` bytes.
+    fn units(self, bytes: u32) -> u32 {
+        match self {
+            PositionEncoding::Utf8 => bytes,
+            // Characters of four bytes are outside of the basic plane, which
+            // is the only case that takes two code units.
+            PositionEncoding::Utf16 if bytes == 4 => 2,
+            PositionEncoding::Utf16 | PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// A character that takes more than one byte, by its range within its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    start: u32,
+    end: u32,
+}
+
+impl WideChar {
+    /// The bytes that the character takes beyond its length in `encoding`.
+    fn extra(self, encoding: PositionEncoding) -> u32 {
+        let bytes = self.end - self.start;
+        bytes - encoding.units(bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The offset that each line starts at, in order.
+    starts: Vec<TextSize>,
+    wide: FxHashMap<u32, Vec<WideChar>>,
+    len: TextSize,
+    encoding: PositionEncoding,
+}
+
+impl LineIndex {
+    pub fn new(text: &str, encoding: PositionEncoding) -> LineIndex {
+        let is_byte_encoding = encoding == PositionEncoding::Utf8;
+        let mut starts = vec![TextSize::from(0)];
+        let mut wide = FxHashMap::default();
+        let mut line_wide = vec![];
+        let mut line_start = 0;
+        for (offset, character) in text.char_indices() {
+            let offset = offset as u32;
+            if character == '\n' {
+                if !line_wide.is_empty() {
+                    wide.insert(starts.len() as u32 - 1, std::mem::take(&mut line_wide));
+                }
+                line_start = offset + 1;
+                starts.push(TextSize::from(line_start));
+            } else if character.len_utf8() > 1 && !is_byte_encoding {
+                let start = offset - line_start;
+                line_wide.push(WideChar { start, end: start + character.len_utf8() as u32 });
+            }
+        }
+        if !line_wide.is_empty() {
+            wide.insert(starts.len() as u32 - 1, line_wide);
+        }
+        LineIndex { starts, wide, len: TextSize::of(text), encoding }
+    }
+
+    pub fn len(&self) -> TextSize {
+        self.len
+    }
+
+    /// The line and column of an offset, which is clamped to the end of the
+    /// file.
+    pub fn line_col(&self, offset: TextSize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        LineCol { line: line as u32, col: u32::from(offset - self.starts[line]) }
+    }
+
+    /// The offset of a line and column, or `None` if the line is past the
+    /// end of the file. Columns past the end of a line are clamped to it.
+    pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
+        let start = *self.starts.get(line_col.line as usize)?;
+        let end = self
+            .starts
+            .get(line_col.line as usize + 1)
+            .map_or(self.len, |&next| next - TextSize::from(1));
+        Some((start + TextSize::from(line_col.col)).min(end))
+    }
+
+    pub fn encoding(&self) -> PositionEncoding {
+        self.encoding
+    }
+
+    /// Converts a column in bytes to one in the units of the encoding.
+    pub fn to_encoded(&self, line_col: LineCol) -> LineCol {
+        let mut col = line_col.col;
+        for &wide in self.wide.get(&line_col.line).into_iter().flatten() {
+            if wide.end <= line_col.col {
+                col -= wide.extra(self.encoding);
+            }
+        }
+        LineCol { line: line_col.line, col }
+    }
+
+    /// Converts a column in the units of the encoding to one in bytes, where
+    /// a column within a character is moved to its end.
+    pub fn to_utf8(&self, line_col: LineCol) -> LineCol {
+        let mut col = line_col.col;
+        for &wide in self.wide.get(&line_col.line).into_iter().flatten() {
+            if wide.start >= col {
+                break;
+            }
+            col += wide.extra(self.encoding);
+        }
+        LineCol { line: line_col.line, col }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rowan::TextSize;
+
+    use super::{LineCol, LineIndex, PositionEncoding};
+
+    #[test]
+    fn convert_positions() {
+        let text = "module Main where\n\nx = \"é𝔸\" -- ok\n";
+        let offset = TextSize::from(text.find("--").unwrap() as u32);
+        for (encoding, col) in [
+            (PositionEncoding::Utf8, 13),
+            (PositionEncoding::Utf16, 10),
+            (PositionEncoding::Utf32, 9),
+        ] {
+            let index = LineIndex::new(text, encoding);
+            let line_col = index.line_col(offset);
+            assert_eq!(line_col, LineCol { line: 2, col: 13 });
+            let encoded = index.to_encoded(line_col);
+            assert_eq!(encoded, LineCol { line: 2, col });
+            assert_eq!(index.offset(index.to_utf8(encoded)), Some(offset));
+        }
+
+        let index = LineIndex::new(text, PositionEncoding::Utf16);
+        assert_eq!(index.line_col(index.len()), LineCol { line: 3, col: 0 });
+        assert_eq!(index.offset(LineCol { line: 0, col: 80 }), Some(TextSize::from(17)));
+        assert_eq!(index.offset(LineCol { line: 4, col: 0 }), None);
+
+        assert_eq!(PositionEncoding::negotiate(["utf-16", "utf-8"]), PositionEncoding::Utf8);
+        assert_eq!(PositionEncoding::negotiate(["utf-32", "utf-16"]), PositionEncoding::Utf32);
+        assert_eq!(PositionEncoding::negotiate([]), PositionEncoding::Utf16);
+    }
+}
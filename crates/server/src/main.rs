@@ -0,0 +1,22 @@
+//! Serves a single client over standard input and output.
+//!
+//! ```text
+//! cargo run --release --bin purescript-analyzer
+//! ```
+
+use std::{
+    io::{self, BufReader},
+    process,
+};
+
+fn main() {
+    match server::run(BufReader::new(io::stdin()), io::stdout().lock()) {
+        Ok(true) => {}
+        // The client exited without shutting the server down first.
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! JSON-RPC messages, framed by a `Content-Length` header as the Language
+//! Server Protocol sends them over standard input and output.
+
+use std::io::{self, BufRead, Write};
+
+use json::Json;
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const SERVER_NOT_INITIALIZED: i32 = -32002;
+pub const REQUEST_CANCELLED: i32 = -32800;
+pub const CONTENT_MODIFIED: i32 = -32801;
+pub const REQUEST_FAILED: i32 = -32803;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl ResponseError {
+    pub fn new(code: i32, message: impl Into<String>) -> ResponseError {
+        ResponseError { code, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Request { id: Json, method: String, params: Json },
+    Response { id: Json, result: Result<Json, ResponseError> },
+    Notification { method: String, params: Json },
+}
+
+impl Message {
+    pub fn notification(method: &str, params: Json) -> Message {
+        Message::Notification { method: method.to_string(), params }
+    }
+
+    /// Reads the next message, or `None` once the input is closed. A message
+    /// that isn't valid JSON-RPC is an error of kind
+    /// [`io::ErrorKind::InvalidData`], after which the next one can be read.
+    pub fn read(input: &mut impl BufRead) -> io::Result<Option<Message>> {
+        let mut length = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("Content-Length") {
+                    length = value.trim().parse().ok();
+                }
+            }
+        }
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let length = length.ok_or_else(|| invalid("missing Content-Length"))?;
+        let mut content = vec![0; length];
+        input.read_exact(&mut content)?;
+        let content = String::from_utf8(content).map_err(|_| invalid("content isn't UTF-8"))?;
+        let json = Json::parse(&content).map_err(|_| invalid("content isn't JSON"))?;
+        Message::from_json(json).map(Some).ok_or_else(|| invalid("content isn't JSON-RPC"))
+    }
+
+    pub fn write(&self, output: &mut impl Write) -> io::Result<()> {
+        let content = self.to_json().to_string();
+        write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+        output.flush()
+    }
+
+    fn from_json(json: Json) -> Option<Message> {
+        let Json::Object(mut fields) = json else { return None };
+        let mut take = |key: &str| {
+            let index = fields.iter().position(|(name, _)| name == key)?;
+            Some(fields.swap_remove(index).1)
+        };
+        let id = take("id");
+        let method = take("method");
+        let params = take("params").unwrap_or(Json::Null);
+        match (id, method) {
+            (Some(id), Some(Json::String(method))) => Some(Message::Request { id, method, params }),
+            (None, Some(Json::String(method))) => Some(Message::Notification { method, params }),
+            (Some(id), None) => {
+                let result = match take("error") {
+                    Some(error) => Err(ResponseError {
+                        code: error.get("code").as_i32()?,
+                        message: error.get("message").as_str()?.to_string(),
+                    }),
+                    None => Ok(take("result").unwrap_or(Json::Null)),
+                };
+                Some(Message::Response { id, result })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        let version = ("jsonrpc", Json::from("2.0"));
+        match self {
+            Message::Request { id, method, params } => Json::object([
+                version,
+                ("id", id.clone()),
+                ("method", method.as_str().into()),
+                ("params", params.clone()),
+            ]),
+            Message::Response { id, result: Ok(result) } => {
+                Json::object([version, ("id", id.clone()), ("result", result.clone())])
+            }
+            Message::Response { id, result: Err(error) } => Json::object([
+                version,
+                ("id", id.clone()),
+                (
+                    "error",
+                    Json::object([
+                        ("code", error.code.into()),
+                        ("message", error.message.as_str().into()),
+                    ]),
+                ),
+            ]),
+            Message::Notification { method, params } => Json::object([
+                version,
+                ("method", method.as_str().into()),
+                ("params", params.clone()),
+            ]),
+        }
+    }
+}
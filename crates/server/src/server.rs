@@ -0,0 +1,1277 @@
+//! The state of the server, and how it responds to each message.
+//!
+//! Messages are handled one at a time on the main thread, which owns the
+//! database. Documents the client has open are overlays in the [`Vfs`],
+//! so every edit reaches the database as a change to the file's text.
+//!
+//! The thread that reads messages cancels the request being handled when
+//! the document changes or the client cancels it, which is answered with
+//! `ContentModified` or `RequestCancelled` respectively.
+
+mod handlers;
+mod progress;
+
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    rc::Rc,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use analysis::{Cancelled, Canceller, Database, FileId, IndexConfig};
+use json::Json;
+use project_model::{ProjectWorkspace, Watcher};
+use rowan::TextSize;
+use rustc_hash::{FxHashMap, FxHashSet};
+use vfs::{ChangeKind, Vfs};
+
+use self::{
+    handlers::{APPLY_EDIT, BUILD},
+    progress::Reporter,
+};
+use crate::{
+    config::Config,
+    convert,
+    line_index::{LineIndex, PositionEncoding},
+    protocol::{
+        Message, ResponseError, CONTENT_MODIFIED, INVALID_PARAMS, INVALID_REQUEST,
+        METHOD_NOT_FOUND, REQUEST_CANCELLED, SERVER_NOT_INITIALIZED,
+    },
+};
+
+/// Content changes are sent as edits to a range of the document, rather
+/// than as the whole document, per `TextDocumentSyncKind.Incremental`.
+const INCREMENTAL: u32 = 2;
+
+/// Watched files that were deleted, per `FileChangeType.Deleted`.
+const DELETED: u32 = 3;
+
+/// How long to wait after an edit before publishing diagnostics, such that
+/// they aren't recomputed on every keystroke.
+const DIAGNOSTICS_DELAY: Duration = Duration::from_millis(200);
+
+/// How often the project is polled for changes made outside of the editor
+/// when the client can't be asked to watch files.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Uninitialized,
+    Running,
+    ShutDown,
+}
+
+/// What the client can do beyond what every client supports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ClientCapabilities {
+    /// Whether the client can show progress that it didn't ask for.
+    work_done_progress: bool,
+    /// Whether the client can be asked to watch files.
+    watch_files: bool,
+    /// Whether the client can be asked to request the inlay hints and code
+    /// lenses of its documents again.
+    refresh_inlay_hints: bool,
+    refresh_code_lenses: bool,
+    /// Whether the client pulls diagnostics rather than having them
+    /// published, and can be asked to pull them again.
+    pull_diagnostics: bool,
+    refresh_diagnostics: bool,
+    /// Whether completions can insert snippets.
+    snippets: bool,
+}
+
+impl ClientCapabilities {
+    fn from_json(capabilities: &Json) -> ClientCapabilities {
+        let workspace = capabilities.get("workspace");
+        let completion_item =
+            capabilities.get("textDocument").get("completion").get("completionItem");
+        let is_set = |value: &Json| value.as_bool().unwrap_or(false);
+        ClientCapabilities {
+            work_done_progress: is_set(capabilities.get("window").get("workDoneProgress")),
+            watch_files: is_set(workspace.get("didChangeWatchedFiles").get("dynamicRegistration")),
+            refresh_inlay_hints: is_set(workspace.get("inlayHint").get("refreshSupport")),
+            refresh_code_lenses: is_set(workspace.get("codeLens").get("refreshSupport")),
+            pull_diagnostics: *capabilities.get("textDocument").get("diagnostic") != Json::Null,
+            refresh_diagnostics: is_set(workspace.get("diagnostics").get("refreshSupport")),
+            snippets: is_set(completion_item.get("snippetSupport")),
+        }
+    }
+}
+
+/// A document the client has open.
+#[derive(Debug)]
+struct Document {
+    version: i32,
+    line_index: Rc<LineIndex>,
+    /// The semantic tokens last sent for the document along with their
+    /// result id, which deltas are computed from. They're replaced by
+    /// requests, which only borrow the server.
+    semantic_tokens: RefCell<Option<(u32, Vec<u32>)>>,
+}
+
+/// The requests the client cancelled, which are shared with the thread
+/// that reads messages such that it can cancel the one being handled.
+#[derive(Debug, Default)]
+struct Cancellations {
+    running: Option<Json>,
+    /// Requests are cancelled by their id, which is forgotten once the
+    /// notification that cancelled them is handled.
+    cancelled: Vec<Json>,
+}
+
+impl Cancellations {
+    /// Cancels the request being handled if `message` makes it stale or
+    /// asks for it to be cancelled.
+    fn read(cancellations: &Mutex<Cancellations>, canceller: &Canceller, message: &Message) {
+        let Message::Notification { method, params } = message else { return };
+        let mut cancellations = cancellations.lock().unwrap();
+        match method.as_str() {
+            "textDocument/didChange" if cancellations.running.is_some() => canceller.cancel(),
+            "$/cancelRequest" => {
+                let id = params.get("id").clone();
+                if cancellations.running.as_ref() == Some(&id) {
+                    canceller.cancel();
+                }
+                cancellations.cancelled.push(id);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct Server {
+    db: Database,
+    vfs: Vfs,
+    state: State,
+    exited: bool,
+    root: Option<PathBuf>,
+    project: Option<ProjectWorkspace>,
+    config: Config,
+    capabilities: ClientCapabilities,
+    /// Whether the client was asked to watch files.
+    watching: bool,
+    /// What the project looked like when it was last polled, if the client
+    /// can't be asked to watch files, along with when to poll it next.
+    watcher: Option<(Watcher, Instant)>,
+    /// Whether the project is yet to be loaded, which [`run`] does between
+    /// messages so that its progress can be written as it's made.
+    loading: bool,
+    /// How the columns of positions are counted, as agreed with the client.
+    encoding: PositionEncoding,
+    documents: FxHashMap<FileId, Document>,
+    /// Documents whose diagnostics are out of date, to be published once
+    /// the client has stopped editing for a moment.
+    stale: FxHashSet<FileId>,
+    deadline: Option<Instant>,
+    outgoing: Vec<Message>,
+    /// The number of requests sent to the client, which is the id of the
+    /// next one.
+    requests: i32,
+    cancellations: Arc<Mutex<Cancellations>>,
+}
+
+/// Serves the client until it exits, returning whether it shut the server
+/// down first.
+///
+/// Messages are read on a thread of their own, such that the server can
+/// wait for the next one with a timeout while work is pending.
+pub fn run(mut input: impl BufRead + Send + 'static, mut output: impl Write) -> io::Result<bool> {
+    let mut server = Server::new();
+    let cancellations = server.cancellations.clone();
+    let canceller = server.db.canceller();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        let message = match Message::read(&mut input) {
+            Ok(Some(message)) => {
+                Cancellations::read(&cancellations, &canceller, &message);
+                Ok(message)
+            }
+            Ok(None) => break,
+            Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                eprintln!("ignoring a malformed message: {error}");
+                continue;
+            }
+            Err(error) => Err(error),
+        };
+        let failed = message.is_err();
+        if sender.send(message).is_err() || failed {
+            break;
+        }
+    });
+
+    while !server.exited {
+        let poll = server.watcher.as_ref().map(|(_, poll)| *poll);
+        let wake = server.deadline.into_iter().chain(poll).min();
+        let message = match wake {
+            Some(wake) => {
+                match receiver.recv_timeout(wake.saturating_duration_since(Instant::now())) {
+                    Ok(message) => Some(message),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match receiver.recv() {
+                Ok(message) => Some(message),
+                Err(_) => break,
+            },
+        };
+        match message {
+            Some(message) => server.handle(message?),
+            None => server.wake(),
+        }
+        for message in server.outgoing.drain(..) {
+            message.write(&mut output)?;
+        }
+        if server.loading {
+            server.load_project(&mut |message| message.write(&mut output))?;
+        }
+    }
+    Ok(server.state == State::ShutDown)
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server {
+            db: Database::new(),
+            vfs: Vfs::default(),
+            state: State::Uninitialized,
+            exited: false,
+            root: None,
+            project: None,
+            config: Config::default(),
+            capabilities: ClientCapabilities::default(),
+            watching: false,
+            watcher: None,
+            loading: false,
+            encoding: PositionEncoding::default(),
+            documents: FxHashMap::default(),
+            stale: FxHashSet::default(),
+            deadline: None,
+            outgoing: vec![],
+            requests: 0,
+            cancellations: Arc::default(),
+        }
+    }
+
+    pub fn handle(&mut self, message: Message) {
+        match message {
+            Message::Request { id, method, params } => {
+                let result = self.on_request(&id, &method, params);
+                self.outgoing.push(Message::Response { id, result });
+            }
+            Message::Notification { method, params } => self.on_notification(&method, params),
+            // Requests are only sent for their effect, such as applying an
+            // edit, so what they respond with isn't waited on.
+            Message::Response { .. } => {}
+        }
+    }
+
+    fn send_request(&mut self, method: &str, params: Json) {
+        let id = self.request_id();
+        self.outgoing.push(Message::Request { id, method: method.to_string(), params });
+    }
+
+    fn request_id(&mut self) -> Json {
+        let id = Json::from(self.requests);
+        self.requests += 1;
+        id
+    }
+
+    fn on_request(&mut self, id: &Json, method: &str, params: Json) -> Result<Json, ResponseError> {
+        match (self.state, method) {
+            (State::Uninitialized, "initialize") => {
+                self.state = State::Running;
+                Ok(self.initialize(&params))
+            }
+            (State::Uninitialized, _) => {
+                Err(ResponseError::new(SERVER_NOT_INITIALIZED, "the server isn't initialized"))
+            }
+            (_, "shutdown") => {
+                self.state = State::ShutDown;
+                Ok(Json::Null)
+            }
+            (State::ShutDown, _) => {
+                Err(ResponseError::new(INVALID_REQUEST, "the server was shut down"))
+            }
+            (_, "workspace/executeCommand") => self.execute_command(&params),
+            _ => self.on_query(id, method, params),
+        }
+    }
+
+    /// Handles a request that only reads the database, such that it can be
+    /// cancelled while it's running.
+    fn on_query(&mut self, id: &Json, method: &str, params: Json) -> Result<Json, ResponseError> {
+        let cancelled = || ResponseError::new(REQUEST_CANCELLED, "the request was cancelled");
+        if self.cancellations.lock().unwrap().cancelled.contains(id) {
+            return Err(cancelled());
+        }
+        self.cancellations.lock().unwrap().running = Some(id.clone());
+        let result = self.db.catch_cancelled(|_| self.on_read(method, params));
+        // The flag is cleared while the lock is held, such that the reader
+        // can't set it again for a request that's already done.
+        let mut cancellations = self.cancellations.lock().unwrap();
+        cancellations.running = None;
+        self.db.clear_cancelled();
+        match result {
+            Ok(result) => result,
+            Err(Cancelled) if cancellations.cancelled.contains(id) => Err(cancelled()),
+            Err(Cancelled) => Err(ResponseError::new(CONTENT_MODIFIED, "the document changed")),
+        }
+    }
+
+    fn on_read(&self, method: &str, params: Json) -> Result<Json, ResponseError> {
+        match method {
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/completion" => self.completion(&params),
+            "textDocument/signatureHelp" => self.signature_help(&params),
+            "textDocument/definition" => self.definition(&params),
+            "textDocument/typeDefinition" => self.type_definition(&params),
+            "textDocument/implementation" => self.implementation(&params),
+            "textDocument/references" => self.references(&params),
+            "textDocument/documentHighlight" => self.document_highlight(&params),
+            "textDocument/inlayHint" => self.inlay_hint(&params),
+            "inlayHint/resolve" => self.resolve_inlay_hint(params),
+            "textDocument/codeAction" => self.code_action(&params),
+            "textDocument/codeLens" => self.code_lens(&params),
+            "textDocument/formatting" => self.formatting(&params),
+            "textDocument/rangeFormatting" => self.range_formatting(&params),
+            "textDocument/onTypeFormatting" => self.on_type_formatting(&params),
+            "codeLens/resolve" => self.resolve_code_lens(params),
+            "textDocument/documentLink" => self.document_link(&params),
+            "documentLink/resolve" => self.resolve_document_link(params),
+            "textDocument/prepareRename" => self.prepare_rename(&params),
+            "textDocument/rename" => self.rename(&params),
+            "textDocument/foldingRange" => self.folding_range(&params),
+            "textDocument/selectionRange" => self.selection_range(&params),
+            "textDocument/semanticTokens/full" => self.semantic_tokens(&params),
+            "textDocument/semanticTokens/full/delta" => self.semantic_tokens_delta(&params),
+            "textDocument/documentSymbol" => self.document_symbol(&params),
+            "workspace/symbol" => self.workspace_symbol(&params),
+            "textDocument/diagnostic" => self.document_diagnostic(&params),
+            "workspace/diagnostic" => self.workspace_diagnostic(&params),
+            _ => Err(ResponseError::new(METHOD_NOT_FOUND, format!("unknown method {method}"))),
+        }
+    }
+
+    fn on_notification(&mut self, method: &str, params: Json) {
+        let result = match method {
+            "exit" => {
+                self.exited = true;
+                Ok(())
+            }
+            "$/cancelRequest" => {
+                let id = params.get("id");
+                self.cancellations.lock().unwrap().cancelled.retain(|cancelled| cancelled != id);
+                Ok(())
+            }
+            _ if self.state != State::Running => Ok(()),
+            "initialized" => {
+                self.loading = true;
+                self.outgoing.push(progress::status(false));
+                Ok(())
+            }
+            "textDocument/didOpen" => self.did_open(&params),
+            "textDocument/didChange" => self.did_change(&params),
+            "textDocument/didClose" => self.did_close(&params),
+            "workspace/didChangeWatchedFiles" => {
+                self.did_change_watched_files(&params);
+                Ok(())
+            }
+            "workspace/didChangeConfiguration" => {
+                self.did_change_configuration(&params);
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(error) = result {
+            eprintln!("couldn't handle {method}: {}", error.message);
+        }
+    }
+
+    fn initialize(&mut self, params: &Json) -> Json {
+        let folder = params.get("workspaceFolders").as_array().and_then(|folders| folders.first());
+        let uri = params.get("rootUri").as_str().or(folder.and_then(|f| f.get("uri").as_str()));
+        self.root = uri
+            .and_then(convert::path)
+            .or_else(|| params.get("rootPath").as_str().map(PathBuf::from));
+        self.config = Config::from_json(params.get("initializationOptions"));
+        let encodings = params.get("capabilities").get("general").get("positionEncodings");
+        let encodings = encodings.as_array().unwrap_or_default();
+        self.encoding = PositionEncoding::negotiate(encodings.iter().filter_map(Json::as_str));
+        self.capabilities = ClientCapabilities::from_json(params.get("capabilities"));
+
+        let sync = Json::object([("openClose", true.into()), ("change", INCREMENTAL.into())]);
+        // Arguments are separated by spaces, so help is asked for after each.
+        let signature_help = Json::object([("triggerCharacters", vec![" ".into()].into())]);
+        let legend = Json::object([
+            ("tokenTypes", convert::SEMANTIC_TOKEN_TYPES.map(Json::from).to_vec().into()),
+            ("tokenModifiers", Json::Array(vec![])),
+        ]);
+        let semantic_tokens =
+            Json::object([("legend", legend), ("full", Json::object([("delta", true.into())]))]);
+        let code_action_kinds = convert::CODE_ACTION_KINDS.map(Json::from).to_vec().into();
+        let [first, more @ ..] = ide::on_type_formatting::TRIGGER_CHARACTERS.map(String::from);
+        let on_type_formatting = Json::object([
+            ("firstTriggerCharacter", first.into()),
+            ("moreTriggerCharacter", more.map(Json::from).to_vec().into()),
+        ]);
+        let capabilities = Json::object([
+            ("positionEncoding", self.encoding.name().into()),
+            ("textDocumentSync", sync),
+            ("hoverProvider", true.into()),
+            ("completionProvider", Json::object([("triggerCharacters", vec![".".into()].into())])),
+            ("signatureHelpProvider", signature_help),
+            ("definitionProvider", true.into()),
+            ("typeDefinitionProvider", true.into()),
+            ("implementationProvider", true.into()),
+            ("referencesProvider", true.into()),
+            ("documentHighlightProvider", true.into()),
+            ("codeActionProvider", Json::object([("codeActionKinds", code_action_kinds)])),
+            ("codeLensProvider", Json::object([("resolveProvider", true.into())])),
+            ("documentLinkProvider", Json::object([("resolveProvider", true.into())])),
+            (
+                "executeCommandProvider",
+                Json::object([("commands", vec![APPLY_EDIT.into(), BUILD.into()].into())]),
+            ),
+            ("inlayHintProvider", Json::object([("resolveProvider", true.into())])),
+            ("documentFormattingProvider", true.into()),
+            ("documentRangeFormattingProvider", true.into()),
+            ("documentOnTypeFormattingProvider", on_type_formatting),
+            ("renameProvider", Json::object([("prepareProvider", true.into())])),
+            ("documentSymbolProvider", true.into()),
+            ("foldingRangeProvider", true.into()),
+            ("selectionRangeProvider", true.into()),
+            ("semanticTokensProvider", semantic_tokens),
+            ("workspaceSymbolProvider", true.into()),
+            (
+                "diagnosticProvider",
+                Json::object([
+                    ("identifier", "purescript-analyzer".into()),
+                    ("interFileDependencies", true.into()),
+                    ("workspaceDiagnostics", true.into()),
+                ]),
+            ),
+        ]);
+        let info = Json::object([
+            ("name", "purescript-analyzer".into()),
+            ("version", env!("CARGO_PKG_VERSION").into()),
+        ]);
+        Json::object([("capabilities", capabilities), ("serverInfo", info)])
+    }
+
+    /// Loads and indexes the project that the root of the workspace is in,
+    /// writing its progress with `write` as it goes, and tells the client
+    /// that the server is ready once it's done.
+    fn load_project(&mut self, write: progress::Write) -> io::Result<()> {
+        self.loading = false;
+        let project = self.root.as_deref().and_then(|root| match project_model::discover(root) {
+            Ok(project) => Some(project),
+            Err(error) => {
+                eprintln!("{}", error.message());
+                None
+            }
+        });
+        if let Some(project) = project {
+            let id = self.capabilities.work_done_progress.then(|| self.request_id());
+            let mut reporter = Reporter::begin(write, id);
+            let config =
+                IndexConfig { cache: Some(project.output.join(".analyzer")), ..Default::default() };
+            let mut progress = |progress| reporter.report(progress);
+            match self.db.load_project(&project, &mut self.vfs, &config, &mut progress) {
+                Ok(skipped) => {
+                    for (path, error) in skipped {
+                        eprintln!("skipped {}: {}", path.display(), error.message());
+                    }
+                }
+                Err(error) => eprintln!("couldn't load the project: {error}"),
+            }
+            reporter.end()?;
+            if !self.capabilities.watch_files {
+                self.watcher = Some((Watcher::new(&project), Instant::now() + POLL_INTERVAL));
+            } else if !self.watching {
+                self.watching = true;
+                let method = "client/registerCapability".to_string();
+                let params = watchers(&project);
+                write(Message::Request { id: self.request_id(), method, params })?;
+            }
+            self.project = Some(project);
+        }
+        write(progress::status(true))
+    }
+
+    fn did_open(&mut self, params: &Json) -> Result<(), ResponseError> {
+        let document = params.get("textDocument");
+        let path = document_path(document)?;
+        let text = document.get("text").as_str().ok_or_else(|| invalid("missing text"))?;
+        let version = document.get("version").as_i32().unwrap_or_default();
+        let file = self.vfs.set_overlay(&path, Some(text.to_string()));
+        let line_index = Rc::new(LineIndex::new(text, self.encoding));
+        self.documents
+            .insert(file, Document { version, line_index, semantic_tokens: RefCell::default() });
+        self.apply_changes();
+        self.schedule_diagnostics(file);
+        Ok(())
+    }
+
+    fn did_change(&mut self, params: &Json) -> Result<(), ResponseError> {
+        let document = params.get("textDocument");
+        let path = document_path(document)?;
+        let file = self.vfs.file_id(&path).ok_or_else(|| invalid("the document isn't open"))?;
+        let mut text = self.vfs.contents(file).unwrap_or_default().to_string();
+        let encoding = self.encoding;
+        let Some(open) = self.documents.get_mut(&file) else {
+            return Err(invalid("the document isn't open"));
+        };
+        let changes = params.get("contentChanges").as_array().unwrap_or_default();
+        // Each change applies to the document as the previous one left it.
+        for change in changes {
+            let replacement = change.get("text").as_str().ok_or_else(|| invalid("missing text"))?;
+            match change.get("range") {
+                Json::Null => text = replacement.to_string(),
+                range => {
+                    let range = convert::text_range(&open.line_index, range)
+                        .ok_or_else(|| invalid("the range is outside of the document"))?;
+                    text.replace_range(std::ops::Range::<usize>::from(range), replacement);
+                }
+            }
+            open.line_index = Rc::new(LineIndex::new(&text, encoding));
+        }
+        open.version = document.get("version").as_i32().unwrap_or(open.version);
+        self.vfs.set_overlay(&path, Some(text));
+        self.apply_changes();
+        self.schedule_diagnostics(file);
+        Ok(())
+    }
+
+    fn did_close(&mut self, params: &Json) -> Result<(), ResponseError> {
+        let path = document_path(params.get("textDocument"))?;
+        let file = self.vfs.set_overlay(&path, None);
+        self.documents.remove(&file);
+        self.stale.remove(&file);
+        self.apply_changes();
+        // Diagnostics are only published for open documents, so the client
+        // would otherwise keep showing the last ones.
+        let params =
+            Json::object([("uri", convert::uri(&path).into()), ("diagnostics", vec![].into())]);
+        self.outgoing.push(Message::notification("textDocument/publishDiagnostics", params));
+        Ok(())
+    }
+
+    /// Replaces the settings with those the client changed them to, under
+    /// the `purescript-analyzer` section if they're sent with it. Features
+    /// read the settings each time they're asked for, so only those that
+    /// the client keeps are asked for again: hints and lenses if the client
+    /// can be asked to, and the diagnostics of open documents.
+    fn did_change_configuration(&mut self, params: &Json) {
+        let settings = params.get("settings");
+        let section = settings.get("purescript-analyzer");
+        let config = Config::from_json(if *section == Json::Null { settings } else { section });
+        let before = std::mem::replace(&mut self.config, config);
+        if before.inlay_hints != self.config.inlay_hints && self.capabilities.refresh_inlay_hints {
+            self.send_request("workspace/inlayHint/refresh", Json::Null);
+        }
+        if before.code_lens != self.config.code_lens && self.capabilities.refresh_code_lenses {
+            self.send_request("workspace/codeLens/refresh", Json::Null);
+        }
+        if before.diagnostics != self.config.diagnostics {
+            let open: Vec<_> = self.documents.keys().copied().collect();
+            for file in open {
+                self.schedule_diagnostics(file);
+            }
+        }
+    }
+
+    /// Brings the files that changed outside of the editor up to date, such
+    /// as when switching branches, by reading each source file that was
+    /// created or changed and dropping each that was deleted. A change to a
+    /// configuration loads the project again, since its packages or their
+    /// dependencies may have changed with it, while new output from the
+    /// compiler reads the modules compiled there again. Files that can't be
+    /// read are logged and skipped, keeping the rest of the changes.
+    fn did_change_watched_files(&mut self, params: &Json) {
+        let Some(project) = &self.project else { return };
+        let roots = project.source_roots();
+        let mut compiled = false;
+        let changes = params.get("changes").as_array().unwrap_or_default();
+        for change in changes {
+            let Some(path) = change.get("uri").as_str().and_then(convert::path) else { continue };
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if project_model::CONFIGS.contains(&name) {
+                self.loading = true;
+                continue;
+            }
+            if path.starts_with(&project.output) {
+                compiled |= matches!(name, "externs.cbor" | "docs.json");
+                continue;
+            }
+            if path.extension().is_none_or(|extension| extension != "purs") {
+                continue;
+            }
+            if change.get("type").as_u32() == Some(DELETED) {
+                if self.vfs.file_id(&path).is_some() {
+                    self.vfs.set_disk_contents(&path, None);
+                }
+                continue;
+            }
+            // Files outside of the source roots, such as those that the
+            // compiler copies into its output, aren't part of the project.
+            if !roots.iter().any(|root| root.contains(&path)) {
+                continue;
+            }
+            if let Err(error) = self.vfs.load(&path) {
+                eprintln!("couldn't read {}: {error}", path.display());
+            }
+        }
+        if compiled {
+            self.load_output();
+        }
+        self.apply_external_changes();
+    }
+
+    /// Does what's due once no message arrived in time: polling the project
+    /// for changes, or publishing diagnostics.
+    fn wake(&mut self) {
+        let now = Instant::now();
+        if self.watcher.as_ref().is_some_and(|(_, poll)| *poll <= now) {
+            self.poll_project();
+        }
+        if self.deadline.is_some_and(|deadline| deadline <= now) {
+            self.publish_diagnostics();
+        }
+    }
+
+    /// Polls the project for the changes made outside of the editor, for
+    /// clients that can't be asked to watch files, and applies them like
+    /// those the client reports.
+    fn poll_project(&mut self) {
+        let Some((watcher, poll)) = &mut self.watcher else { return };
+        *poll = Instant::now() + POLL_INTERVAL;
+        let changes = watcher.poll(&mut self.vfs);
+        for error in changes.errors {
+            eprintln!("{}", error.message());
+        }
+        if changes.output {
+            self.load_output();
+        }
+        self.loading |= changes.project;
+        self.apply_external_changes();
+    }
+
+    /// Reads the modules compiled into the output directory again.
+    fn load_output(&mut self) {
+        let Some(project) = &self.project else { return };
+        for (path, error) in self.db.load_output(&project.output) {
+            eprintln!("skipped {}: {}", path.display(), error.message());
+        }
+    }
+
+    /// Applies the changes that were made outside of the editor, adding the
+    /// files that were created to the package of their source root, and
+    /// schedules the diagnostics of the open documents, which may depend on
+    /// any of them.
+    fn apply_external_changes(&mut self) {
+        if self.loading {
+            self.outgoing.push(progress::status(false));
+        }
+        let changes = self.vfs.take_changes();
+        if let Some(project) = &self.project {
+            for change in &changes {
+                let file = change.file_id;
+                if change.kind == ChangeKind::Created && self.db.package(file).is_none() {
+                    self.db.add_project_file(project, file, self.vfs.file_path(file));
+                }
+            }
+        }
+        self.db.apply_changes(&self.vfs, changes);
+        let open: Vec<_> = self.documents.keys().copied().collect();
+        for file in open {
+            self.schedule_diagnostics(file);
+        }
+    }
+
+    /// Publishes the diagnostics of a document once the client has stopped
+    /// editing it for a moment, starting over with every edit.
+    fn schedule_diagnostics(&mut self, file: FileId) {
+        self.stale.insert(file);
+        self.deadline = Some(Instant::now() + DIAGNOSTICS_DELAY);
+    }
+
+    /// Publishes the diagnostics of the stale documents, or asks a client
+    /// that pulls them to pull them again, since diagnostics depend on the
+    /// other files of the project.
+    fn publish_diagnostics(&mut self) {
+        self.deadline = None;
+        if self.capabilities.pull_diagnostics {
+            self.stale.clear();
+            if self.capabilities.refresh_diagnostics {
+                self.send_request("workspace/diagnostic/refresh", Json::Null);
+            }
+            return;
+        }
+        let mut stale: Vec<_> = self.stale.drain().collect();
+        stale.sort();
+        for file in stale {
+            let Some(document) = self.documents.get(&file) else { continue };
+            let params = Json::object([
+                ("uri", convert::uri(self.vfs.file_path(file)).into()),
+                ("version", document.version.into()),
+                ("diagnostics", self.diagnostics(file).into()),
+            ]);
+            self.outgoing.push(Message::notification("textDocument/publishDiagnostics", params));
+        }
+    }
+
+    /// The line index of a file, which is kept for open documents and built
+    /// on demand for the rest.
+    fn line_index(&self, file: FileId) -> Rc<LineIndex> {
+        match self.documents.get(&file) {
+            Some(document) => document.line_index.clone(),
+            None => {
+                let text = self.db.file_text(file).unwrap_or_default();
+                Rc::new(LineIndex::new(&text, self.encoding))
+            }
+        }
+    }
+
+    /// The file of the `textDocument` of a request.
+    fn document_file(&self, params: &Json) -> Result<FileId, ResponseError> {
+        let path = document_path(params.get("textDocument"))?;
+        self.vfs.file_id(&path).ok_or_else(|| invalid("the document isn't known"))
+    }
+
+    /// The file and offset of a `TextDocumentPositionParams`.
+    fn file_position(
+        &self,
+        params: &Json,
+    ) -> Result<(FileId, TextSize, Rc<LineIndex>), ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let offset = convert::offset(&line_index, params.get("position"))
+            .ok_or_else(|| invalid("the position is outside of the document"))?;
+        Ok((file, offset, line_index))
+    }
+
+    fn apply_changes(&mut self) {
+        let changes = self.vfs.take_changes();
+        self.db.apply_changes(&self.vfs, changes);
+    }
+}
+
+impl Default for Server {
+    fn default() -> Server {
+        Server::new()
+    }
+}
+
+/// Asks the client to tell the server about changes to source files, to the
+/// configurations of the project, and to the modules compiled into its
+/// output directory.
+fn watchers(project: &ProjectWorkspace) -> Json {
+    let configs = format!("**/{{{}}}", project_model::CONFIGS.join(","));
+    let output = format!("{}/*/{{externs.cbor,docs.json}}", project.output.display());
+    let watchers = ["**/*.purs".to_string(), configs, output]
+        .map(|glob| Json::object([("globPattern", glob.into())]));
+    let registration = Json::object([
+        ("id", "purescript-analyzer/watchedFiles".into()),
+        ("method", "workspace/didChangeWatchedFiles".into()),
+        ("registerOptions", Json::object([("watchers", watchers.to_vec().into())])),
+    ]);
+    Json::object([("registrations", vec![registration].into())])
+}
+
+fn document_path(document: &Json) -> Result<PathBuf, ResponseError> {
+    let uri = document.get("uri").as_str().ok_or_else(|| invalid("missing uri"))?;
+    convert::path(uri).ok_or_else(|| invalid(format!("{uri} isn't a file")))
+}
+
+fn invalid(message: impl Into<String>) -> ResponseError {
+    ResponseError::new(INVALID_PARAMS, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use analysis::ModuleId;
+    use json::Json;
+
+    use crate::protocol::{Message, CONTENT_MODIFIED, REQUEST_CANCELLED};
+
+    use super::{Cancellations, Server};
+
+    fn notify(server: &mut Server, method: &str, params: &str) {
+        server.handle(Message::notification(method, Json::parse(params).unwrap()));
+    }
+
+    fn request(server: &mut Server, method: &str, params: &str) -> Json {
+        let params = Json::parse(params).unwrap();
+        server.handle(Message::Request { id: Json::from(2), method: method.into(), params });
+        let Some(Message::Response { result: Ok(result), .. }) = server.outgoing.pop() else {
+            panic!()
+        };
+        result
+    }
+
+    #[test]
+    fn apply_incremental_changes() {
+        let mut server = Server::new();
+        let id = Json::from(1);
+        server.handle(Message::Request { id, method: "initialize".into(), params: Json::Null });
+        notify(
+            &mut server,
+            "textDocument/didOpen",
+            r#"{"textDocument":{"uri":"file:///project/src/Ma%C3%AFn.purs","version":1,"text":"module Main where\n\nx = \"𝔸\" + y\n"}}"#,
+        );
+        // The second change is relative to the first, and counts the
+        // character outside of the basic plane as two code units.
+        notify(
+            &mut server,
+            "textDocument/didChange",
+            r#"{"textDocument":{"uri":"file:///project/src/Ma%C3%AFn.purs","version":2},"contentChanges":[
+                {"range":{"start":{"line":2,"character":0},"end":{"line":2,"character":1}},"text":"value"},
+                {"range":{"start":{"line":2,"character":15},"end":{"line":2,"character":16}},"text":"z"}
+            ]}"#,
+        );
+        let file = server.vfs.file_id(Path::new("/project/src/Maïn.purs")).unwrap();
+        assert_eq!(
+            server.db.file_text(file).as_deref(),
+            Some("module Main where\n\nvalue = \"𝔸\" + z\n")
+        );
+        assert_eq!(server.documents[&file].version, 2);
+
+        let id = Json::from(2);
+        let params = Json::parse(
+            r#"{"textDocument":{"uri":"file:///project/src/Ma%C3%AFn.purs"},"position":{"line":2,"character":2}}"#,
+        )
+        .unwrap();
+        server.handle(Message::Request { id, method: "textDocument/hover".into(), params });
+        let Some(Message::Response { result: Ok(hover), .. }) = server.outgoing.pop() else {
+            panic!()
+        };
+        let value = hover.get("contents").get("value").as_str().unwrap();
+        assert!(value.starts_with("```purescript\nvalue :: "), "{value}");
+
+        notify(
+            &mut server,
+            "textDocument/didClose",
+            r#"{"textDocument":{"uri":"file:///project/src/Ma%C3%AFn.purs"}}"#,
+        );
+        assert_eq!(server.db.file_text(file), None);
+    }
+
+    #[test]
+    fn semantic_tokens_delta() {
+        let mut server = Server::new();
+        let id = Json::from(1);
+        server.handle(Message::Request { id, method: "initialize".into(), params: Json::Null });
+        notify(
+            &mut server,
+            "textDocument/didOpen",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs","version":1,"text":"module Main where\n\nx = 1\n\ny = x\n"}}"#,
+        );
+        let full = request(
+            &mut server,
+            "textDocument/semanticTokens/full",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs"}}"#,
+        );
+        assert_eq!(full.get("resultId").as_str(), Some("0"));
+        let data: Vec<_> = full.get("data").as_array().unwrap().iter().map(Json::as_u32).collect();
+        // `module`, `Main`, `where`, `x`, `1`, `y`, and `x`, five integers each.
+        assert_eq!(data.len(), 35);
+        assert_eq!(data[15..20], [Some(2), Some(0), Some(1), Some(5), Some(0)]);
+
+        notify(
+            &mut server,
+            "textDocument/didChange",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs","version":2},"contentChanges":[
+                {"range":{"start":{"line":2,"character":4},"end":{"line":2,"character":5}},"text":"10"}
+            ]}"#,
+        );
+        let delta = request(
+            &mut server,
+            "textDocument/semanticTokens/full/delta",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs"},"previousResultId":"0"}"#,
+        );
+        assert_eq!(delta.get("resultId").as_str(), Some("1"));
+        // Only the length of the literal changes.
+        assert_eq!(
+            delta.get("edits"),
+            &Json::parse(r#"[{"start":20,"deleteCount":5,"data":[0,4,2,12,0]}]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn report_loading_progress() {
+        let root = std::env::temp_dir().join(format!("server-progress-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("bower.json"), r#"{"name": "purescript-app"}"#).unwrap();
+        std::fs::write(root.join("src/Main.purs"), "module Main where\n\nmain = 0\n").unwrap();
+
+        let mut server = Server::new();
+        let params = Json::object([
+            ("rootUri", crate::convert::uri(&root).into()),
+            ("capabilities", Json::parse(r#"{"window":{"workDoneProgress":true}}"#).unwrap()),
+        ]);
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        notify(&mut server, "initialized", "{}");
+        assert!(server.loading);
+        let mut written = vec![];
+        server
+            .load_project(&mut |message| {
+                written.push(message);
+                Ok(())
+            })
+            .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let methods: Vec<_> = written
+            .iter()
+            .map(|message| match message {
+                Message::Request { method, .. } => method.clone(),
+                Message::Notification { method, params } if method == "$/progress" => {
+                    let value = params.get("value");
+                    let kind = value.get("kind").as_str().unwrap();
+                    match value.get("message").as_str() {
+                        Some(message) => format!("{kind} {message}"),
+                        None => kind.to_string(),
+                    }
+                }
+                Message::Notification { method, params } => {
+                    format!("{method} {}", params.get("ready").as_bool().unwrap())
+                }
+                Message::Response { .. } => panic!(),
+            })
+            .collect();
+        assert_eq!(
+            methods,
+            [
+                "window/workDoneProgress/create",
+                "begin",
+                "report 1 files in 1 packages",
+                "report 1/1 (Main)",
+                "report resolving modules",
+                "end",
+                "purescript-analyzer/status true",
+            ]
+        );
+    }
+
+    #[test]
+    fn reload_watched_files() {
+        let root = std::env::temp_dir().join(format!("server-watch-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("bower.json"), r#"{"name": "purescript-app"}"#).unwrap();
+        std::fs::write(root.join("src/Main.purs"), "module Main where\n\nmain = 0\n").unwrap();
+
+        let mut server = Server::new();
+        let capabilities =
+            r#"{"workspace":{"didChangeWatchedFiles":{"dynamicRegistration":true}}}"#;
+        let params = Json::object([
+            ("rootUri", crate::convert::uri(&root).into()),
+            ("capabilities", Json::parse(capabilities).unwrap()),
+        ]);
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        notify(&mut server, "initialized", "{}");
+        let mut written = vec![];
+        server
+            .load_project(&mut |message| {
+                written.push(message);
+                Ok(())
+            })
+            .unwrap();
+        let registration = written.iter().find_map(|message| match message {
+            Message::Request { method, params, .. } if method == "client/registerCapability" => {
+                Some(params)
+            }
+            _ => None,
+        });
+        let watchers = registration.unwrap().get("registrations").as_array().unwrap()[0]
+            .get("registerOptions")
+            .get("watchers")
+            .as_array()
+            .unwrap();
+        let globs: Vec<_> =
+            watchers.iter().map(|watcher| watcher.get("globPattern").as_str().unwrap()).collect();
+        let output = format!("{}/*/{{externs.cbor,docs.json}}", root.join("output").display());
+        assert!(globs.contains(&output.as_str()));
+
+        std::fs::write(root.join("src/Util.purs"), "module Util where\n").unwrap();
+        std::fs::remove_file(root.join("src/Main.purs")).unwrap();
+        let changes = Json::object([(
+            "changes",
+            vec![
+                // Deleted again before the change was reported.
+                Json::object([
+                    ("uri", crate::convert::uri(&root.join("src/Gone.purs")).into()),
+                    ("type", 1.into()),
+                ]),
+                Json::object([
+                    ("uri", crate::convert::uri(&root.join("src/Util.purs")).into()),
+                    ("type", 1.into()),
+                ]),
+                Json::object([
+                    ("uri", crate::convert::uri(&root.join("src/Main.purs")).into()),
+                    ("type", 3.into()),
+                ]),
+            ]
+            .into(),
+        )]);
+        server.handle(Message::notification("workspace/didChangeWatchedFiles", changes));
+        let util = server.vfs.file_id(&root.join("src/Util.purs")).unwrap();
+        let main = server.vfs.file_id(&root.join("src/Main.purs")).unwrap();
+        assert_eq!(server.db.file_text(util).as_deref(), Some("module Util where\n"));
+        assert!(server.db.package(util).is_some());
+        assert_eq!(server.db.file_text(main), None);
+        assert!(!server.loading);
+
+        std::fs::create_dir_all(root.join("output/Lib")).unwrap();
+        std::fs::write(root.join("output/Lib/docs.json"), r#"{"name": "Lib", "declarations": []}"#)
+            .unwrap();
+        let changes = Json::object([(
+            "changes",
+            vec![Json::object([
+                ("uri", crate::convert::uri(&root.join("output/Lib/docs.json")).into()),
+                ("type", 1.into()),
+            ])]
+            .into(),
+        )]);
+        server.handle(Message::notification("workspace/didChangeWatchedFiles", changes));
+        let modules = server.db.visible_modules(ModuleId::File(util));
+        assert!(modules.iter().any(|(name, module)| {
+            name.as_str() == "Lib" && matches!(module, ModuleId::Compiled(_))
+        }));
+
+        let changes = Json::object([(
+            "changes",
+            vec![Json::object([
+                ("uri", crate::convert::uri(&root.join("bower.json")).into()),
+                ("type", 2.into()),
+            ])]
+            .into(),
+        )]);
+        server.handle(Message::notification("workspace/didChangeWatchedFiles", changes));
+        assert!(server.loading);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn poll_without_watched_files() {
+        let root = std::env::temp_dir().join(format!("server-poll-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("bower.json"), r#"{"name": "purescript-app"}"#).unwrap();
+        std::fs::write(root.join("src/Main.purs"), "module Main where\n\nmain = 0\n").unwrap();
+
+        let mut server = Server::new();
+        let params = Json::object([
+            ("rootUri", crate::convert::uri(&root).into()),
+            ("capabilities", Json::object([])),
+        ]);
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        notify(&mut server, "initialized", "{}");
+        server.load_project(&mut |_| Ok(())).unwrap();
+        assert!(!server.watching);
+        assert!(server.watcher.is_some());
+
+        std::fs::write(root.join("src/Util.purs"), "module Util where\n").unwrap();
+        std::fs::write(root.join("src/Main.purs"), "module Main where\n\nmain = 10\n").unwrap();
+        std::fs::create_dir_all(root.join("output/Lib")).unwrap();
+        std::fs::write(root.join("output/Lib/docs.json"), r#"{"name": "Lib", "declarations": []}"#)
+            .unwrap();
+        server.poll_project();
+        let util = server.vfs.file_id(&root.join("src/Util.purs")).unwrap();
+        let main = server.vfs.file_id(&root.join("src/Main.purs")).unwrap();
+        assert_eq!(server.db.file_text(util).as_deref(), Some("module Util where\n"));
+        assert!(server.db.package(util).is_some());
+        assert_eq!(server.db.file_text(main).as_deref(), Some("module Main where\n\nmain = 10\n"));
+        let modules = server.db.visible_modules(ModuleId::File(main));
+        assert!(modules.iter().any(|(name, module)| {
+            name.as_str() == "Lib" && matches!(module, ModuleId::Compiled(_))
+        }));
+        assert!(!server.loading);
+
+        std::fs::write(root.join("bower.json"), r#"{"name": "purescript-renamed"}"#).unwrap();
+        server.poll_project();
+        assert!(server.loading);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn build_with_configured_purs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("server-build-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("bower.json"), r#"{"name": "purescript-app"}"#).unwrap();
+        std::fs::write(root.join("src/Main.purs"), "module Main where\n").unwrap();
+        // Stands in for `purs compile`, writing a module into the output
+        // directory that follows `--output`.
+        let purs = root.join("bin/purs");
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        let script = "#!/bin/sh\necho \"$@\" > arguments\nmkdir -p \"$3/Lib\"\necho '{\"name\": \"Lib\", \"declarations\": []}' > \"$3/Lib/docs.json\"\necho 'compiled with warnings' >&2\nexit 1\n";
+        std::fs::write(&purs, script).unwrap();
+        std::fs::set_permissions(&purs, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut server = Server::new();
+        let options =
+            Json::object([("build", Json::object([("pursPath", purs.to_str().unwrap().into())]))]);
+        let params = Json::object([
+            ("rootUri", crate::convert::uri(&root).into()),
+            ("capabilities", Json::object([])),
+            ("initializationOptions", options),
+        ]);
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        notify(&mut server, "initialized", "{}");
+        server.load_project(&mut |_| Ok(())).unwrap();
+
+        let params = Json::object([("command", "purescript-analyzer.build".into())]);
+        let method = "workspace/executeCommand".to_string();
+        server.handle(Message::Request { id: Json::from(2), method, params });
+        let Some(Message::Response { result: Err(error), .. }) = server.outgoing.last() else {
+            panic!("{:?}", server.outgoing.last());
+        };
+        assert_eq!(error.message, "compiled with warnings");
+        let arguments = std::fs::read_to_string(root.join("arguments")).unwrap();
+        let output = root.join("output");
+        let sources = root.join("src/**/*.purs");
+        assert!(arguments.starts_with(&format!("compile --output {}", output.display())));
+        assert!(arguments.contains(&sources.display().to_string()));
+        let main = server.vfs.file_id(&root.join("src/Main.purs")).unwrap();
+        let modules = server.db.visible_modules(ModuleId::File(main));
+        assert!(modules.iter().any(|(name, module)| {
+            name.as_str() == "Lib" && matches!(module, ModuleId::Compiled(_))
+        }));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn change_configuration() {
+        let mut server = Server::new();
+        let params = Json::parse(
+            r#"{"capabilities":{"workspace":{"inlayHint":{"refreshSupport":true}}},"initializationOptions":{"inlayHints":{"binds":false},"build":{"pursPath":"/opt/purs","spagoPath":"/opt/spago"}}}"#,
+        )
+        .unwrap();
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        assert!(!server.config.inlay_hints.binds);
+        assert_eq!(server.config.build.purs, "/opt/purs");
+        assert_eq!(server.config.build.spago, "/opt/spago");
+        server.outgoing.clear();
+
+        notify(
+            &mut server,
+            "workspace/didChangeConfiguration",
+            r#"{"settings":{"purescript-analyzer":{"diagnostics":{"disabled":["wildcard-import"],"shadowing":{"locals":true,"severity":"hint","allowed":["x"]}}}}}"#,
+        );
+        // Settings that aren't sent are back to their defaults.
+        assert!(server.config.inlay_hints.binds);
+        assert_eq!(server.config.build, crate::config::BuildConfig::default());
+        assert!(!server.config.diagnostics.is_enabled("wildcard-import"));
+        assert!(server.config.diagnostics.is_enabled("unknown-member"));
+        let shadowing = &server.config.diagnostics.shadowing;
+        assert!(shadowing.locals && !shadowing.globals);
+        assert_eq!(shadowing.severity, ide::Severity::Hint);
+        assert_eq!(shadowing.allowed, ["x"]);
+        let methods: Vec<_> = server
+            .outgoing
+            .iter()
+            .map(|message| match message {
+                Message::Request { method, .. } => method.as_str(),
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(methods, ["workspace/inlayHint/refresh"]);
+    }
+
+    #[test]
+    fn pull_diagnostics() {
+        let mut server = Server::new();
+        let params = Json::parse(
+            r#"{"capabilities":{"textDocument":{"diagnostic":{}},"workspace":{"diagnostics":{"refreshSupport":true}}}}"#,
+        )
+        .unwrap();
+        server.handle(Message::Request { id: Json::from(1), method: "initialize".into(), params });
+        notify(
+            &mut server,
+            "textDocument/didOpen",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs","version":1,"text":"module Main where\n\nx = \"unterminated\n"}}"#,
+        );
+        let full = request(
+            &mut server,
+            "textDocument/diagnostic",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs"}}"#,
+        );
+        assert_eq!(full.get("kind").as_str(), Some("full"));
+        assert_eq!(full.get("items").as_array().map(<[Json]>::len), Some(1));
+        let result_id = full.get("resultId").as_str().unwrap().to_string();
+        let unchanged = request(
+            &mut server,
+            "textDocument/diagnostic",
+            &format!(
+                r#"{{"textDocument":{{"uri":"file:///project/src/Main.purs"}},"previousResultId":"{result_id}"}}"#
+            ),
+        );
+        assert_eq!(unchanged.get("kind").as_str(), Some("unchanged"));
+        assert_eq!(unchanged.get("items"), &Json::Null);
+
+        let workspace = request(
+            &mut server,
+            "workspace/diagnostic",
+            &format!(
+                r#"{{"previousResultIds":[{{"uri":"file:///project/src/Main.purs","value":"{result_id}"}}]}}"#
+            ),
+        );
+        let [report] = workspace.get("items").as_array().unwrap() else { panic!() };
+        assert_eq!(report.get("kind").as_str(), Some("unchanged"));
+        assert_eq!(report.get("version").as_u32(), Some(1));
+
+        // Diagnostics aren't published to a client that pulls them, which
+        // is asked to pull them again instead.
+        server.outgoing.clear();
+        server.publish_diagnostics();
+        let [Message::Request { method, .. }] = &server.outgoing[..] else { panic!() };
+        assert_eq!(method, "workspace/diagnostic/refresh");
+    }
+
+    #[test]
+    fn cancel_requests() {
+        let mut server = Server::new();
+        request(&mut server, "initialize", "{}");
+        notify(
+            &mut server,
+            "textDocument/didOpen",
+            r#"{"textDocument":{"uri":"file:///project/src/Main.purs","version":1,"text":"module Main where\n\nx = 1\n"}}"#,
+        );
+        let hover = |server: &mut Server, id: i32| {
+            let params = Json::parse(
+                r#"{"textDocument":{"uri":"file:///project/src/Main.purs"},"position":{"line":2,"character":0}}"#,
+            )
+            .unwrap();
+            let id = Json::from(id);
+            server.handle(Message::Request { id, method: "textDocument/hover".into(), params });
+            match server.outgoing.pop() {
+                Some(Message::Response { result, .. }) => result.map_err(|error| error.code),
+                _ => panic!(),
+            }
+        };
+
+        // A request that's cancelled before it's handled isn't run.
+        let canceller = server.db.canceller();
+        let cancel = Message::notification("$/cancelRequest", Json::parse(r#"{"id":3}"#).unwrap());
+        Cancellations::read(&server.cancellations, &canceller, &cancel);
+        assert_eq!(hover(&mut server, 3), Err(REQUEST_CANCELLED));
+        server.handle(cancel);
+        assert!(server.cancellations.lock().unwrap().cancelled.is_empty());
+
+        // A request whose queries are cancelled by an edit is answered as
+        // such, and the next one runs as usual.
+        canceller.cancel();
+        assert_eq!(hover(&mut server, 4), Err(CONTENT_MODIFIED));
+        assert!(hover(&mut server, 5).is_ok());
+    }
+}
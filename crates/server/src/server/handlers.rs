@@ -0,0 +1,595 @@
+//! The requests for the features of the editor, which convert between the
+//! protocol and the `ide` crate.
+
+use std::{
+    env,
+    hash::{Hash, Hasher},
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use analysis::FileId;
+use ide::{
+    CodeLensKind, CompletionConfig, FileRange, HighlightKind, NavigationTarget, TextEdit,
+    WorkspaceEdit,
+};
+use json::Json;
+use rustc_hash::{FxHashMap, FxHasher};
+
+use crate::{
+    config::Formatter,
+    convert,
+    protocol::{ResponseError, REQUEST_FAILED},
+};
+
+use super::{invalid, Server};
+
+/// Asks the client to apply the workspace edit that's its argument.
+pub(super) const APPLY_EDIT: &str = "purescript-analyzer.applyEdit";
+
+/// Builds the workspace, and reads the modules it compiled.
+pub(super) const BUILD: &str = "purescript-analyzer.build";
+
+impl Server {
+    pub(super) fn hover(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, line_index) = self.file_position(params)?;
+        let Some(hover) = ide::hover(&self.db, file, offset) else { return Ok(Json::Null) };
+        let contents =
+            Json::object([("kind", "markdown".into()), ("value", hover.markdown.into())]);
+        Ok(Json::object([
+            ("contents", contents),
+            ("range", convert::range(&line_index, hover.range)),
+        ]))
+    }
+
+    pub(super) fn completion(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, line_index) = self.file_position(params)?;
+        let config = CompletionConfig { snippets: self.capabilities.snippets };
+        let items = ide::completions(&self.db, file, offset, config);
+        let items = items.into_iter().map(|item| convert::completion_item(&line_index, item));
+        Ok(Json::object([
+            ("isIncomplete", false.into()),
+            ("items", items.collect::<Vec<_>>().into()),
+        ]))
+    }
+
+    pub(super) fn signature_help(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let help = ide::signature_help(&self.db, file, offset);
+        Ok(help.map_or(Json::Null, |help| convert::signature_help(help, self.encoding)))
+    }
+
+    pub(super) fn definition(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let targets = ide::definition(&self.db, file, offset);
+        Ok(self.locations(targets))
+    }
+
+    pub(super) fn type_definition(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let targets = ide::type_definition(&self.db, file, offset);
+        Ok(self.locations(targets))
+    }
+
+    pub(super) fn implementation(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let targets = ide::implementation(&self.db, file, offset);
+        Ok(self.locations(targets))
+    }
+
+    pub(super) fn references(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let include_declaration =
+            params.get("context").get("includeDeclaration").as_bool().unwrap_or(false);
+        let references = ide::references(&self.db, file, offset, include_declaration);
+        Ok(self.file_locations(references))
+    }
+
+    pub(super) fn document_highlight(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, line_index) = self.file_position(params)?;
+        let highlights = ide::document_highlights(&self.db, file, offset);
+        let highlights = highlights.into_iter().map(|highlight| {
+            let kind = match highlight.kind {
+                HighlightKind::Read => 2,
+                HighlightKind::Write => 3,
+            };
+            Json::object([
+                ("range", convert::range(&line_index, highlight.range)),
+                ("kind", kind.into()),
+            ])
+        });
+        Ok(highlights.collect::<Vec<_>>().into())
+    }
+
+    /// The hints of a range, which leave their tooltips to be resolved
+    /// once they're shown.
+    pub(super) fn inlay_hint(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let range = convert::text_range(&line_index, params.get("range"))
+            .ok_or_else(|| invalid("the range is outside of the document"))?;
+        let hints = ide::inlay_hints(&self.db, file, range, self.config.inlay_hints);
+        let hints = hints.into_iter().map(|hint| {
+            // The data of a hint is the position of its name, as it's
+            // passed to hover.
+            let data = Json::object([
+                ("textDocument", params.get("textDocument").clone()),
+                ("position", convert::position(&line_index, hint.target)),
+            ]);
+            Json::object([
+                ("position", convert::position(&line_index, hint.offset)),
+                ("label", hint.label.into()),
+                ("kind", 1.into()),
+                ("paddingLeft", true.into()),
+                ("data", data),
+            ])
+        });
+        Ok(hints.collect::<Vec<_>>().into())
+    }
+
+    /// Adds the hover of the name a hint belongs to as its tooltip.
+    pub(super) fn resolve_inlay_hint(&self, hint: Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(hint.get("data"))?;
+        let Some(hover) = ide::hover(&self.db, file, offset) else { return Ok(hint) };
+        let Json::Object(mut fields) = hint else {
+            return Err(invalid("the hint isn't an object"));
+        };
+        let tooltip = Json::object([("kind", "markdown".into()), ("value", hover.markdown.into())]);
+        fields.push(("tooltip".to_string(), tooltip));
+        Ok(Json::Object(fields))
+    }
+
+    pub(super) fn code_action(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let range = convert::text_range(&line_index, params.get("range"))
+            .ok_or_else(|| invalid("the range is outside of the document"))?;
+        let actions = ide::code_actions(&self.db, file, range, self.config.code_actions);
+        // Editors ask for only some kinds, such as organizing imports on save,
+        // where `source` asks for every kind under it.
+        let only = params.get("context").get("only").as_array();
+        let is_requested = |kind: &str| {
+            only.is_none_or(|only| {
+                only.iter().filter_map(Json::as_str).any(|requested| {
+                    kind == requested || kind.starts_with(&format!("{requested}."))
+                })
+            })
+        };
+        let actions = actions.into_iter().filter_map(|action| {
+            let kind = convert::code_action_kind(action.kind);
+            if !is_requested(kind) {
+                return None;
+            }
+            Some(Json::object([
+                ("title", action.title.into()),
+                ("kind", kind.into()),
+                ("edit", self.workspace_edit(action.edit)),
+            ]))
+        });
+        Ok(actions.collect::<Vec<_>>().into())
+    }
+
+    /// The lenses of a document, where those of references are resolved
+    /// once they're shown.
+    pub(super) fn code_lens(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let lenses = ide::code_lenses(&self.db, file, self.config.code_lens);
+        let lenses = lenses.into_iter().map(|lens| {
+            let range = ("range", convert::range(&line_index, lens.range));
+            match lens.kind {
+                CodeLensKind::AddSignature { signature, edit } => {
+                    let edit =
+                        self.workspace_edit(WorkspaceEdit { files: vec![(file, vec![edit])] });
+                    let command = Json::object([
+                        ("title", format!("Add signature: {signature}").into()),
+                        ("command", APPLY_EDIT.into()),
+                        ("arguments", vec![edit].into()),
+                    ]);
+                    Json::object([range, ("command", command)])
+                }
+                CodeLensKind::References => {
+                    let data = Json::object([
+                        ("textDocument", params.get("textDocument").clone()),
+                        ("position", convert::position(&line_index, lens.range.start())),
+                    ]);
+                    Json::object([range, ("data", data)])
+                }
+            }
+        });
+        Ok(lenses.collect::<Vec<_>>().into())
+    }
+
+    /// Counts the references of the name a lens is above, showing them
+    /// when the lens is clicked.
+    pub(super) fn resolve_code_lens(&self, lens: Json) -> Result<Json, ResponseError> {
+        let data = lens.get("data");
+        let (file, offset, _) = self.file_position(data)?;
+        let references = ide::references(&self.db, file, offset, false);
+        let title = match references.len() {
+            1 => "1 reference".to_string(),
+            count => format!("{count} references"),
+        };
+        let arguments = vec![
+            data.get("textDocument").get("uri").clone(),
+            data.get("position").clone(),
+            self.file_locations(references),
+        ];
+        let command = Json::object([
+            ("title", title.into()),
+            ("command", "editor.action.showReferences".into()),
+            ("arguments", arguments.into()),
+        ]);
+        Ok(Json::object([("range", lens.get("range").clone()), ("command", command)]))
+    }
+
+    /// The links of a document, whose targets are resolved once they're
+    /// followed.
+    pub(super) fn document_link(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let links = ide::document_links(&self.db, file).into_iter().map(|range| {
+            let data = Json::object([
+                ("textDocument", params.get("textDocument").clone()),
+                ("position", convert::position(&line_index, range.start())),
+            ]);
+            Json::object([("range", convert::range(&line_index, range)), ("data", data)])
+        });
+        Ok(links.collect::<Vec<_>>().into())
+    }
+
+    /// Adds the file of the module a link imports as its target.
+    pub(super) fn resolve_document_link(&self, link: Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(link.get("data"))?;
+        let Some(target) = ide::document_link_target(&self.db, file, offset) else {
+            return Ok(link);
+        };
+        let Json::Object(mut fields) = link else {
+            return Err(invalid("the link isn't an object"));
+        };
+        fields.push(("target".to_string(), convert::uri(self.vfs.file_path(target)).into()));
+        Ok(Json::Object(fields))
+    }
+
+    /// Runs a command of a lens or an action, which asks the client to
+    /// apply the edit that it carries.
+    pub(super) fn execute_command(&mut self, params: &Json) -> Result<Json, ResponseError> {
+        let command = params.get("command").as_str().unwrap_or_default();
+        let argument = params.get("arguments").as_array().and_then(|arguments| arguments.first());
+        match (command, argument) {
+            (APPLY_EDIT, Some(edit)) => {
+                self.send_request("workspace/applyEdit", Json::object([("edit", edit.clone())]));
+                Ok(Json::Null)
+            }
+            (BUILD, _) => self.build(),
+            _ => Err(invalid(format!("unknown command {command}"))),
+        }
+    }
+
+    /// Builds the workspace with `spago build` if spago configures it, or
+    /// else by compiling the globs of its source roots with `purs compile`,
+    /// in the root of the workspace. The compiled modules are read again
+    /// even if the build fails, since the modules that compiled are still
+    /// written, and the failure is answered with what the build printed.
+    fn build(&mut self) -> Result<Json, ResponseError> {
+        let Some(project) = &self.project else {
+            return Err(ResponseError::new(REQUEST_FAILED, "the project isn't loaded"));
+        };
+        let build = &self.config.build;
+        let mut command = if project.is_spago() {
+            let mut command = Command::new(&build.spago);
+            command.arg("build");
+            // spago runs the `purs` on the `PATH`, so the directory of the
+            // one that's configured goes first.
+            let directory = Path::new(&build.purs).parent();
+            if let Some(directory) = directory.filter(|directory| !directory.as_os_str().is_empty())
+            {
+                let path = env::var_os("PATH").unwrap_or_default();
+                let paths = std::iter::once(directory.to_path_buf()).chain(env::split_paths(&path));
+                if let Ok(path) = env::join_paths(paths) {
+                    command.env("PATH", path);
+                }
+            }
+            command
+        } else {
+            let mut command = Command::new(&build.purs);
+            command.arg("compile").arg("--output").arg(&project.output);
+            for root in project.source_roots() {
+                command.args(root.globs.iter().map(|glob| root.directory.join(glob)));
+            }
+            command
+        };
+        let program = command.get_program().to_string_lossy().into_owned();
+        command.current_dir(&project.root).stdin(Stdio::null());
+        // The output is read rather than inherited, since the server's own
+        // output is where it writes its messages.
+        let output = command.output().map_err(|error| {
+            ResponseError::new(REQUEST_FAILED, format!("couldn't run {program}: {error}"))
+        })?;
+        self.load_output();
+        self.apply_external_changes();
+        if !output.status.success() {
+            let printed = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(ResponseError::new(REQUEST_FAILED, printed));
+        }
+        Ok(Json::Null)
+    }
+
+    pub(super) fn formatting(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let edits = self.format(file)?;
+        Ok(self.text_edits(file, edits))
+    }
+
+    /// Formats the whole document, and keeps the edits that touch the range.
+    pub(super) fn range_formatting(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let range = convert::text_range(&self.line_index(file), params.get("range"))
+            .ok_or_else(|| invalid("the range is outside of the document"))?;
+        let edits = ide::edits_in_range(self.format(file)?, range);
+        Ok(self.text_edits(file, edits))
+    }
+
+    pub(super) fn on_type_formatting(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let typed = params.get("ch").as_str().and_then(|typed| typed.chars().next());
+        let edit = typed.and_then(|typed| ide::on_type_formatting(&self.db, file, offset, typed));
+        Ok(self.text_edits(file, edit.into_iter().collect()))
+    }
+
+    pub(super) fn prepare_rename(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, line_index) = self.file_position(params)?;
+        let range = ide::prepare_rename(&self.db, file, offset)
+            .map_err(|error| ResponseError::new(REQUEST_FAILED, error.to_string()))?;
+        Ok(convert::range(&line_index, range))
+    }
+
+    pub(super) fn rename(&self, params: &Json) -> Result<Json, ResponseError> {
+        let (file, offset, _) = self.file_position(params)?;
+        let new_name = params.get("newName").as_str().unwrap_or_default();
+        let edit = ide::rename(&self.db, file, offset, new_name)
+            .map_err(|error| ResponseError::new(REQUEST_FAILED, error.to_string()))?;
+        Ok(self.workspace_edit(edit))
+    }
+
+    pub(super) fn folding_range(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let ranges = ide::folding_ranges(&self.db, file);
+        let ranges = ranges.into_iter().map(|range| convert::folding_range(&line_index, range));
+        Ok(ranges.collect::<Vec<_>>().into())
+    }
+
+    /// The ranges around each position, as a list from the innermost that
+    /// links to the one around it.
+    pub(super) fn selection_range(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let positions = params.get("positions").as_array().unwrap_or_default();
+        let ranges = positions.iter().map(|position| {
+            let offset = convert::offset(&line_index, position)
+                .ok_or_else(|| invalid("the position is outside of the document"))?;
+            let ranges = ide::selection_ranges(&self.db, file, offset);
+            let linked = ranges.into_iter().rev().fold(Json::Null, |parent, range| {
+                let range = ("range", convert::range(&line_index, range));
+                match parent {
+                    Json::Null => Json::object([range]),
+                    parent => Json::object([range, ("parent", parent)]),
+                }
+            });
+            Ok(linked)
+        });
+        Ok(ranges.collect::<Result<Vec<_>, _>>()?.into())
+    }
+
+    pub(super) fn semantic_tokens(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let (result_id, data) = self.encode_semantic_tokens(file);
+        Ok(semantic_tokens(result_id, ("data", integers(&data))))
+    }
+
+    /// The edits to the tokens that were last sent for the document, or all
+    /// of them if those aren't the ones the client has.
+    pub(super) fn semantic_tokens_delta(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let previous = params.get("previousResultId").as_str();
+        let old = self.documents.get(&file).and_then(|document| {
+            let sent = document.semantic_tokens.borrow();
+            sent.as_ref()
+                .filter(|(result_id, _)| previous == Some(result_id.to_string().as_str()))
+                .map(|(_, data)| data.clone())
+        });
+        let (result_id, data) = self.encode_semantic_tokens(file);
+        Ok(match old {
+            Some(old) => {
+                semantic_tokens(result_id, ("edits", convert::semantic_tokens_edits(&old, &data)))
+            }
+            None => semantic_tokens(result_id, ("data", integers(&data))),
+        })
+    }
+
+    pub(super) fn document_symbol(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let line_index = self.line_index(file);
+        let symbols = ide::document_symbols(&self.db, file);
+        let symbols =
+            symbols.into_iter().map(|symbol| convert::document_symbol(&line_index, symbol));
+        Ok(symbols.collect::<Vec<_>>().into())
+    }
+
+    pub(super) fn workspace_symbol(&self, params: &Json) -> Result<Json, ResponseError> {
+        let query = params.get("query").as_str().unwrap_or_default();
+        let symbols = ide::workspace_symbols(&self.db, query).into_iter().map(|symbol| {
+            let line_index = self.line_index(symbol.target.file);
+            let location = Json::object([
+                ("uri", convert::uri(self.vfs.file_path(symbol.target.file)).into()),
+                ("range", convert::range(&line_index, symbol.target.focus_range)),
+            ]);
+            Json::object([
+                ("name", symbol.name.into()),
+                ("kind", convert::symbol_kind(symbol.kind)),
+                ("location", location),
+                ("containerName", symbol.module.as_str().into()),
+            ])
+        });
+        Ok(symbols.collect::<Vec<_>>().into())
+    }
+
+    /// The diagnostics of a document for clients that pull them, reported
+    /// as unchanged if they're the ones the client has from before.
+    pub(super) fn document_diagnostic(&self, params: &Json) -> Result<Json, ResponseError> {
+        let file = self.document_file(params)?;
+        let previous = params.get("previousResultId").as_str();
+        Ok(Json::object(diagnostic_report(self.diagnostics(file), previous)))
+    }
+
+    /// The diagnostics of every file of the project that isn't part of a
+    /// dependency, open or not, so errors are listed without having to open
+    /// the files they're in.
+    pub(super) fn workspace_diagnostic(&self, params: &Json) -> Result<Json, ResponseError> {
+        let previous = params.get("previousResultIds").as_array().unwrap_or_default();
+        let previous: FxHashMap<_, _> = previous
+            .iter()
+            .filter_map(|id| Some((id.get("uri").as_str()?, id.get("value").as_str()?)))
+            .collect();
+        let mut files = self.db.files();
+        files.retain(|&file| !self.db.is_library(file));
+        files.sort();
+        let reports = files.into_iter().map(|file| {
+            let uri = convert::uri(self.vfs.file_path(file));
+            let version = self.documents.get(&file).map_or(Json::Null, |d| d.version.into());
+            let report =
+                diagnostic_report(self.diagnostics(file), previous.get(uri.as_str()).copied());
+            Json::object([("uri", uri.into()), ("version", version)].into_iter().chain(report))
+        });
+        Ok(Json::object([("items", reports.collect::<Vec<_>>().into())]))
+    }
+
+    /// The diagnostics of a file, without those the settings turn off.
+    pub(super) fn diagnostics(&self, file: FileId) -> Vec<Json> {
+        let line_index = self.line_index(file);
+        let diagnostics = ide::diagnostics(&self.db, file, &self.config.diagnostics.shadowing);
+        let diagnostics = diagnostics.into_iter();
+        let diagnostics =
+            diagnostics.filter(|diagnostic| self.config.diagnostics.is_enabled(diagnostic.code));
+        let location = |range| self.file_location(range);
+        diagnostics
+            .map(|diagnostic| convert::diagnostic(&line_index, diagnostic, location))
+            .collect()
+    }
+
+    fn file_location(&self, FileRange { file, range }: FileRange) -> Json {
+        let line_index = self.line_index(file);
+        Json::object([
+            ("uri", convert::uri(self.vfs.file_path(file)).into()),
+            ("range", convert::range(&line_index, range)),
+        ])
+    }
+
+    fn file_locations(&self, ranges: Vec<FileRange>) -> Json {
+        let locations = ranges.into_iter().map(|range| self.file_location(range));
+        locations.collect::<Vec<_>>().into()
+    }
+
+    /// The locations of navigation targets, at their names.
+    fn locations(&self, targets: Vec<NavigationTarget>) -> Json {
+        let locations = targets.into_iter().map(|target| {
+            let line_index = self.line_index(target.file);
+            Json::object([
+                ("uri", convert::uri(self.vfs.file_path(target.file)).into()),
+                ("range", convert::range(&line_index, target.focus_range)),
+            ])
+        });
+        locations.collect::<Vec<_>>().into()
+    }
+
+    /// The edits that format a document with the formatter of the workspace.
+    fn format(&self, file: FileId) -> Result<Vec<TextEdit>, ResponseError> {
+        let text = self.db.file_text(file).unwrap_or_default();
+        let formatted = match &self.config.formatter {
+            Formatter::Builtin => ide::format(&self.db, file).ok_or_else(|| {
+                ResponseError::new(REQUEST_FAILED, "documents with syntax errors aren't formatted")
+            })?,
+            Formatter::PursTidy { path } => self
+                .purs_tidy(path, &text)
+                .map_err(|error| ResponseError::new(REQUEST_FAILED, error))?,
+        };
+        Ok(ide::text_edits(&text, &formatted))
+    }
+
+    /// Runs `purs-tidy format` on `text`, which it reads from its input.
+    fn purs_tidy(&self, path: &str, text: &str) -> Result<String, String> {
+        let mut command = Command::new(path);
+        command.arg("format").stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        if let Some(root) = &self.root {
+            command.current_dir(root);
+        }
+        let mut child = command.spawn().map_err(|error| format!("couldn't run {path}: {error}"))?;
+        // The input is written from another thread, since a large document
+        // would otherwise fill the pipe before its output is read.
+        let mut stdin = child.stdin.take().unwrap();
+        let text = text.to_string();
+        let writer = thread::spawn(move || stdin.write_all(text.as_bytes()));
+        let output = child.wait_with_output().map_err(|error| error.to_string())?;
+        let _ = writer.join();
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        String::from_utf8(output.stdout).map_err(|error| error.to_string())
+    }
+
+    fn text_edits(&self, file: FileId, edits: Vec<TextEdit>) -> Json {
+        let line_index = self.line_index(file);
+        let edits = edits.into_iter().map(|edit| convert::text_edit(&line_index, edit));
+        edits.collect::<Vec<_>>().into()
+    }
+
+    /// The edits of each file, keyed by the URIs of the files.
+    fn workspace_edit(&self, edit: WorkspaceEdit) -> Json {
+        let changes = edit.files.into_iter().map(|(file, edits)| {
+            let line_index = self.line_index(file);
+            let edits = edits.into_iter().map(|edit| convert::text_edit(&line_index, edit));
+            (convert::uri(self.vfs.file_path(file)), edits.collect::<Vec<_>>().into())
+        });
+        Json::object([("changes", Json::Object(changes.collect()))])
+    }
+
+    /// Encodes the semantic tokens of a file, keeping them under a new
+    /// result id if the document is open.
+    fn encode_semantic_tokens(&self, file: FileId) -> (Option<u32>, Vec<u32>) {
+        let line_index = self.line_index(file);
+        let text = self.db.file_text(file).unwrap_or_default();
+        let tokens = ide::semantic_tokens(&self.db, file);
+        let data = convert::semantic_tokens(&line_index, &text, &tokens);
+        let Some(document) = self.documents.get(&file) else { return (None, data) };
+        let mut sent = document.semantic_tokens.borrow_mut();
+        let result_id = sent.as_ref().map_or(0, |(result_id, _)| result_id + 1);
+        *sent = Some((result_id, data.clone()));
+        (Some(result_id), data)
+    }
+}
+
+fn semantic_tokens(result_id: Option<u32>, tokens: (&str, Json)) -> Json {
+    let result_id = result_id.map(|result_id| ("resultId", result_id.to_string().into()));
+    Json::object(result_id.into_iter().chain([tokens]))
+}
+
+/// A report of `items`, whose result id is a hash of them, such that the
+/// report is only sent in full when they've changed since `previous`.
+fn diagnostic_report(items: Vec<Json>, previous: Option<&str>) -> Vec<(&'static str, Json)> {
+    let items = Json::from(items);
+    let mut hasher = FxHasher::default();
+    items.to_string().hash(&mut hasher);
+    let result_id = format!("{:x}", hasher.finish());
+    if previous == Some(result_id.as_str()) {
+        vec![("kind", "unchanged".into()), ("resultId", result_id.into())]
+    } else {
+        vec![("kind", "full".into()), ("resultId", result_id.into()), ("items", items)]
+    }
+}
+
+fn integers(data: &[u32]) -> Json {
+    data.iter().map(|&integer| integer.into()).collect::<Vec<Json>>().into()
+}
@@ -0,0 +1,101 @@
+//! Reporting the progress of loading the project to the client.
+//!
+//! The project is loaded before the next message is handled, so progress is
+//! written to the client as it's made rather than queued with responses,
+//! which would only be written once loading is done.
+
+use std::io;
+
+use analysis::IndexProgress;
+use json::Json;
+
+use crate::protocol::Message;
+
+/// The token of the progress of loading the project.
+const TOKEN: &str = "purescript-analyzer/loading";
+
+pub(super) type Write<'a> = &'a mut dyn FnMut(Message) -> io::Result<()>;
+
+pub(super) struct Reporter<'a> {
+    write: Write<'a>,
+    /// Whether the client can show progress that it didn't ask for.
+    enabled: bool,
+    /// The last percentage that was reported, such that files are only
+    /// reported once their share of the whole has grown.
+    percentage: Option<u32>,
+    /// The first error that writing a message failed with, after which
+    /// nothing else is written.
+    result: io::Result<()>,
+}
+
+impl<'a> Reporter<'a> {
+    /// Asks the client to create the progress with the request `id`, and
+    /// begins it. Progress is only reported to clients that can create it,
+    /// which is when there's an `id` to ask with.
+    pub(super) fn begin(write: Write<'a>, id: Option<Json>) -> Reporter<'a> {
+        let enabled = id.is_some();
+        let mut reporter = Reporter { write, enabled, percentage: None, result: Ok(()) };
+        if let Some(id) = id {
+            let params = Json::object([("token", TOKEN.into())]);
+            let method = "window/workDoneProgress/create".to_string();
+            reporter.write(Message::Request { id, method, params });
+        }
+        reporter.progress(Json::object([
+            ("kind", "begin".into()),
+            ("title", "Indexing".into()),
+            ("percentage", 0.into()),
+        ]));
+        reporter
+    }
+
+    pub(super) fn report(&mut self, progress: IndexProgress) {
+        let (message, percentage) = match progress {
+            IndexProgress::Loaded { packages, files } => {
+                (format!("{files} files in {packages} packages"), 0)
+            }
+            IndexProgress::Parsed { indexed, total, module } => {
+                let percentage = (indexed * 100 / total.max(1)) as u32;
+                if self.percentage == Some(percentage) {
+                    return;
+                }
+                let message = match module {
+                    Some(module) => format!("{indexed}/{total} ({module})"),
+                    None => format!("{indexed}/{total}"),
+                };
+                (message, percentage)
+            }
+            IndexProgress::Resolving => ("resolving modules".to_string(), 100),
+        };
+        self.percentage = Some(percentage);
+        self.progress(Json::object([
+            ("kind", "report".into()),
+            ("message", message.into()),
+            ("percentage", percentage.into()),
+        ]));
+    }
+
+    pub(super) fn end(mut self) -> io::Result<()> {
+        self.progress(Json::object([("kind", "end".into())]));
+        self.result
+    }
+
+    fn progress(&mut self, value: Json) {
+        if self.enabled {
+            let params = Json::object([("token", TOKEN.into()), ("value", value)]);
+            self.write(Message::notification("$/progress", params));
+        }
+    }
+
+    fn write(&mut self, message: Message) {
+        if self.result.is_ok() {
+            self.result = (self.write)(message);
+        }
+    }
+}
+
+/// Tells the client whether the server has loaded the project. Requests
+/// made while it's loading wait until it's done, so this lets the client
+/// show that results are still to come rather than that there are none.
+pub(super) fn status(ready: bool) -> Message {
+    Message::notification("purescript-analyzer/status", Json::object([("ready", ready.into())]))
+}
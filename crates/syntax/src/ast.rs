@@ -1,49 +1,163 @@
-use rowan::ast::AstNode;
+//! The typed layer over the untyped syntax tree.
+//!
+//! Each node is a zero-cost wrapper over a [`SyntaxNode`], obtained through
+//! [`AstNode::cast`]. Accessors are lazy: sequences of children are exposed
+//! as [`AstChildren`] iterators rather than collected into vectors, such that
+//! walking a large module doesn't allocate.
 
-use crate::{PureScript, SyntaxKind, SyntaxNode, SyntaxToken};
+mod binders;
+mod declarations;
+mod expressions;
+mod module;
+mod names;
+mod types;
 
-#[derive(Debug)]
-pub struct ModuleName {
-    syntax: SyntaxNode,
-}
+use std::marker::PhantomData;
+
+pub use rowan::ast::{AstNode, AstPtr};
+
+pub use binders::*;
+pub use declarations::*;
+pub use expressions::*;
+pub use module::*;
+pub use names::*;
+pub use types::*;
 
-impl ModuleName {
-    /// Invariant: [`SyntaxToken::kind()`] is always [`SyntaxKind::Upper`].
-    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
-        self.syntax.children_with_tokens().filter_map(|element| {
-            element.into_token().and_then(|token| {
-                if matches!(token.kind(), SyntaxKind::Upper) {
-                    Some(token)
+use crate::{PureScript, SyntaxKind, SyntaxNode, SyntaxNodeChildren, SyntaxToken};
+
+/// Defines a struct wrapping a [`SyntaxNode`] of the same [`SyntaxKind`].
+macro_rules! create_ast_node {
+    ($($(#[$meta:meta])* $kind:ident;)*) => {$(
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $kind {
+            syntax: $crate::SyntaxNode,
+        }
+
+        impl $crate::ast::AstNode for $kind {
+            type Language = $crate::PureScript;
+
+            fn can_cast(kind: $crate::SyntaxKind) -> bool
+            where
+                Self: Sized,
+            {
+                matches!(kind, $crate::SyntaxKind::$kind)
+            }
+
+            fn cast(node: $crate::SyntaxNode) -> Option<Self>
+            where
+                Self: Sized,
+            {
+                if <Self as $crate::ast::AstNode>::can_cast(node.kind()) {
+                    Some(Self { syntax: node })
                 } else {
                     None
                 }
-            })
-        })
-    }
+            }
+
+            fn syntax(&self) -> &$crate::SyntaxNode {
+                &self.syntax
+            }
+        }
+    )*};
 }
 
-impl AstNode for ModuleName {
-    type Language = PureScript;
+/// Defines an enum over nodes defined through [`create_ast_node`].
+macro_rules! create_ast_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($kind:ident)),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant($kind),)*
+        }
 
-    fn can_cast(kind: SyntaxKind) -> bool
-    where
-        Self: Sized,
-    {
-        matches!(kind, SyntaxKind::ModuleName)
-    }
+        impl $crate::ast::AstNode for $name {
+            type Language = $crate::PureScript;
 
-    fn cast(node: SyntaxNode) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        if Self::can_cast(node.kind()) {
-            Some(Self { syntax: node })
-        } else {
-            None
+            fn can_cast(kind: $crate::SyntaxKind) -> bool
+            where
+                Self: Sized,
+            {
+                matches!(kind, $($crate::SyntaxKind::$kind)|*)
+            }
+
+            fn cast(node: $crate::SyntaxNode) -> Option<Self>
+            where
+                Self: Sized,
+            {
+                match node.kind() {
+                    $($crate::SyntaxKind::$kind => <$kind as $crate::ast::AstNode>::cast(node).map(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &$crate::SyntaxNode {
+                match self {
+                    $(Self::$variant(node) => $crate::ast::AstNode::syntax(node),)*
+                }
+            }
         }
+    };
+}
+
+pub(crate) use create_ast_enum;
+pub(crate) use create_ast_node;
+
+/// A lazy iterator over the children of a node that can be cast to `N`.
+#[derive(Debug, Clone)]
+pub struct AstChildren<N> {
+    inner: SyntaxNodeChildren,
+    phantom: PhantomData<N>,
+}
+
+impl<N: AstNode<Language = PureScript>> AstChildren<N> {
+    pub fn new(parent: &SyntaxNode) -> AstChildren<N> {
+        AstChildren { inner: parent.children(), phantom: PhantomData }
     }
+}
+
+impl<N: AstNode<Language = PureScript>> Iterator for AstChildren<N> {
+    type Item = N;
 
-    fn syntax(&self) -> &SyntaxNode {
-        &self.syntax
+    fn next(&mut self) -> Option<N> {
+        self.inner.find_map(N::cast)
     }
 }
+
+/// Returns the first child that can be cast to `N`.
+pub(crate) fn child<N: AstNode<Language = PureScript>>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+/// Returns the children that can be cast to `N`.
+pub(crate) fn children<N: AstNode<Language = PureScript>>(parent: &SyntaxNode) -> AstChildren<N> {
+    AstChildren::new(parent)
+}
+
+/// Returns the first child that can be cast to `N` after a token of a kind.
+pub(crate) fn child_after<N: AstNode<Language = PureScript>>(
+    parent: &SyntaxNode,
+    kind: SyntaxKind,
+) -> Option<N> {
+    parent
+        .children_with_tokens()
+        .skip_while(|element| element.kind() != kind)
+        .filter_map(|element| element.into_node())
+        .find_map(N::cast)
+}
+
+/// Returns the first token of a kind.
+pub(crate) fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == kind)
+}
+
+/// Returns the first significant token.
+pub(crate) fn significant_token(parent: &SyntaxNode) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| !token.kind().is_trivia())
+}
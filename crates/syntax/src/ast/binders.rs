@@ -0,0 +1,118 @@
+use super::{
+    child, children, create_ast_enum, create_ast_node, significant_token, AstChildren, Name,
+    QualifiedName, RecordItem, Type,
+};
+use crate::SyntaxToken;
+
+create_ast_node! {
+    BinderWildcard;
+    BinderVariable;
+    /// A named binder, e.g. `x@(Just _)`.
+    BinderNamed;
+    BinderConstructor;
+    BinderLiteral;
+    BinderNegative;
+    BinderArray;
+    BinderRecord;
+    BinderParenthesized;
+    BinderTyped;
+    /// A flat chain of operators, e.g. `x : xs`.
+    BinderOperatorChain;
+}
+
+create_ast_enum! {
+    Binder {
+        Wildcard(BinderWildcard),
+        Variable(BinderVariable),
+        Named(BinderNamed),
+        Constructor(BinderConstructor),
+        Literal(BinderLiteral),
+        Negative(BinderNegative),
+        Array(BinderArray),
+        Record(BinderRecord),
+        Parenthesized(BinderParenthesized),
+        Typed(BinderTyped),
+        OperatorChain(BinderOperatorChain),
+    }
+}
+
+impl BinderVariable {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+}
+
+impl BinderNamed {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+}
+
+impl BinderConstructor {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+
+    pub fn arguments(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+}
+
+impl BinderLiteral {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl BinderNegative {
+    /// The literal following the minus sign.
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.syntax
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| !token.kind().is_trivia())
+            .nth(1)
+    }
+}
+
+impl BinderArray {
+    pub fn elements(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+}
+
+impl BinderRecord {
+    pub fn fields(&self) -> AstChildren<RecordItem> {
+        children(&self.syntax)
+    }
+}
+
+impl BinderParenthesized {
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+}
+
+impl BinderTyped {
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl BinderOperatorChain {
+    pub fn operands(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+
+    pub fn operators(&self) -> AstChildren<QualifiedName> {
+        children(&self.syntax)
+    }
+}
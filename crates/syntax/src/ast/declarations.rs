@@ -0,0 +1,318 @@
+use super::{
+    child, children, create_ast_enum, create_ast_node, significant_token, token, AstChildren,
+    AstNode, Binder, GuardedExpression, Name, NameRef, QualifiedName, Type, TypeVariableBinding,
+};
+use crate::{SyntaxKind, SyntaxToken};
+
+create_ast_node! {
+    ValueDeclaration;
+    AnnotationDeclaration;
+    KindSignatureDeclaration;
+    DataDeclaration;
+    DataConstructor;
+    NewtypeDeclaration;
+    TypeDeclaration;
+    ClassDeclaration;
+    /// Superclass or instance constraints, including the arrow.
+    Constraints;
+    ClassMember;
+    FunctionalDependencies;
+    FunctionalDependency;
+    /// A sequence of instances separated by `else`.
+    InstanceChain;
+    InstanceDeclaration;
+    InstanceHead;
+    InstanceName;
+    DeriveInstanceDeclaration;
+    ForeignDataDeclaration;
+    ForeignValueDeclaration;
+    FixityDeclaration;
+}
+
+create_ast_enum! {
+    Declaration {
+        Value(ValueDeclaration),
+        Annotation(AnnotationDeclaration),
+        KindSignature(KindSignatureDeclaration),
+        Data(DataDeclaration),
+        Newtype(NewtypeDeclaration),
+        Type(TypeDeclaration),
+        Class(ClassDeclaration),
+        InstanceChain(InstanceChain),
+        DeriveInstance(DeriveInstanceDeclaration),
+        ForeignData(ForeignDataDeclaration),
+        ForeignValue(ForeignValueDeclaration),
+        Fixity(FixityDeclaration),
+    }
+}
+
+create_ast_enum! {
+    InstanceMember {
+        Value(ValueDeclaration),
+        Annotation(AnnotationDeclaration),
+    }
+}
+
+impl ValueDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn binders(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+
+    pub fn guarded_expression(&self) -> Option<GuardedExpression> {
+        child(&self.syntax)
+    }
+}
+
+impl AnnotationDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl KindSignatureDeclaration {
+    /// One of `data`, `newtype`, `type`, or `class`.
+    pub fn keyword(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn kind(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl DataDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn variables(&self) -> AstChildren<TypeVariableBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn constructors(&self) -> AstChildren<DataConstructor> {
+        children(&self.syntax)
+    }
+}
+
+impl DataConstructor {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn fields(&self) -> AstChildren<Type> {
+        children(&self.syntax)
+    }
+}
+
+impl NewtypeDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn variables(&self) -> AstChildren<TypeVariableBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn constructor(&self) -> Option<DataConstructor> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn variables(&self) -> AstChildren<TypeVariableBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ClassDeclaration {
+    pub fn constraints(&self) -> Option<Constraints> {
+        child(&self.syntax)
+    }
+
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn variables(&self) -> AstChildren<TypeVariableBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn functional_dependencies(&self) -> Option<FunctionalDependencies> {
+        child(&self.syntax)
+    }
+
+    pub fn members(&self) -> AstChildren<ClassMember> {
+        children(&self.syntax)
+    }
+}
+
+impl Constraints {
+    pub fn types(&self) -> AstChildren<Type> {
+        children(&self.syntax)
+    }
+}
+
+impl ClassMember {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl FunctionalDependencies {
+    pub fn dependencies(&self) -> AstChildren<FunctionalDependency> {
+        children(&self.syntax)
+    }
+}
+
+impl FunctionalDependency {
+    /// The variables to the left of the arrow.
+    pub fn determiners(&self) -> impl Iterator<Item = NameRef> {
+        self.syntax
+            .children_with_tokens()
+            .take_while(|element| element.kind() != SyntaxKind::RightArrow)
+            .filter_map(|element| element.into_node())
+            .filter_map(NameRef::cast)
+    }
+
+    /// The variables to the right of the arrow.
+    pub fn determined(&self) -> impl Iterator<Item = NameRef> {
+        self.syntax
+            .children_with_tokens()
+            .skip_while(|element| element.kind() != SyntaxKind::RightArrow)
+            .filter_map(|element| element.into_node())
+            .filter_map(NameRef::cast)
+    }
+}
+
+impl InstanceChain {
+    pub fn instances(&self) -> AstChildren<InstanceDeclaration> {
+        children(&self.syntax)
+    }
+}
+
+impl InstanceDeclaration {
+    pub fn instance_name(&self) -> Option<InstanceName> {
+        child(&self.syntax)
+    }
+
+    pub fn head(&self) -> Option<InstanceHead> {
+        child(&self.syntax)
+    }
+
+    pub fn members(&self) -> AstChildren<InstanceMember> {
+        children(&self.syntax)
+    }
+}
+
+impl InstanceName {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+}
+
+impl InstanceHead {
+    pub fn constraints(&self) -> Option<Constraints> {
+        child(&self.syntax)
+    }
+
+    pub fn class_name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+
+    pub fn arguments(&self) -> AstChildren<Type> {
+        children(&self.syntax)
+    }
+}
+
+impl DeriveInstanceDeclaration {
+    pub fn is_newtype(&self) -> bool {
+        token(&self.syntax, SyntaxKind::NewtypeKw).is_some()
+    }
+
+    pub fn instance_name(&self) -> Option<InstanceName> {
+        child(&self.syntax)
+    }
+
+    pub fn head(&self) -> Option<InstanceHead> {
+        child(&self.syntax)
+    }
+}
+
+impl ForeignDataDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn kind(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ForeignValueDeclaration {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+impl FixityDeclaration {
+    pub fn associativity(&self) -> Option<Associativity> {
+        self.syntax.children_with_tokens().find_map(|element| match element.kind() {
+            SyntaxKind::InfixlKw => Some(Associativity::Left),
+            SyntaxKind::InfixrKw => Some(Associativity::Right),
+            SyntaxKind::InfixKw => Some(Associativity::None),
+            _ => None,
+        })
+    }
+
+    pub fn precedence(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, SyntaxKind::LiteralInteger)
+    }
+
+    /// Determines if this is a fixity declaration for a type operator.
+    pub fn is_type(&self) -> bool {
+        token(&self.syntax, SyntaxKind::TypeKw).is_some()
+    }
+
+    pub fn target(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+
+    pub fn operator(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+}
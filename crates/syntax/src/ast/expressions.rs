@@ -0,0 +1,464 @@
+use super::{
+    child, child_after, children, create_ast_enum, create_ast_node, significant_token, AstChildren,
+    Binder, Label, Name, QualifiedName, Type,
+};
+use crate::{SyntaxKind, SyntaxToken};
+
+create_ast_node! {
+    /// A flat chain of operators, e.g. `a + b * c`.
+    ExpressionOperatorChain;
+    /// A flat chain of infix applications, e.g. ``a `div` b``.
+    ExpressionInfixChain;
+    ExpressionTick;
+    ExpressionTyped;
+    ExpressionNegate;
+    ExpressionApplication;
+    /// A visible type argument, e.g. the `@Int` in `identity @Int`.
+    ExpressionTypeArgument;
+    ExpressionVariable;
+    ExpressionConstructor;
+    ExpressionOperatorName;
+    ExpressionLiteral;
+    ExpressionArray;
+    ExpressionRecord;
+    ExpressionParenthesized;
+    ExpressionHole;
+    ExpressionWildcard;
+    ExpressionRecordAccess;
+    ExpressionRecordUpdate;
+    ExpressionIfThenElse;
+    ExpressionLambda;
+    ExpressionCase;
+    ExpressionLetIn;
+    ExpressionDo;
+    ExpressionAdo;
+    RecordField;
+    RecordPun;
+    RecordUpdateLeaf;
+    RecordUpdateBranch;
+    CaseBranch;
+    Unconditional;
+    Guarded;
+    GuardedBranch;
+    PatternGuard;
+    WhereClause;
+    DoBind;
+    DoLet;
+    DoDiscard;
+    LetBindingName;
+    LetBindingSignature;
+    LetBindingPattern;
+}
+
+create_ast_enum! {
+    Expression {
+        OperatorChain(ExpressionOperatorChain),
+        InfixChain(ExpressionInfixChain),
+        Typed(ExpressionTyped),
+        Negate(ExpressionNegate),
+        Application(ExpressionApplication),
+        TypeArgument(ExpressionTypeArgument),
+        Variable(ExpressionVariable),
+        Constructor(ExpressionConstructor),
+        OperatorName(ExpressionOperatorName),
+        Literal(ExpressionLiteral),
+        Array(ExpressionArray),
+        Record(ExpressionRecord),
+        Parenthesized(ExpressionParenthesized),
+        Hole(ExpressionHole),
+        Wildcard(ExpressionWildcard),
+        RecordAccess(ExpressionRecordAccess),
+        RecordUpdate(ExpressionRecordUpdate),
+        IfThenElse(ExpressionIfThenElse),
+        Lambda(ExpressionLambda),
+        Case(ExpressionCase),
+        LetIn(ExpressionLetIn),
+        Do(ExpressionDo),
+        Ado(ExpressionAdo),
+    }
+}
+
+create_ast_enum! {
+    /// The right-hand side of a declaration, binding, or case branch.
+    GuardedExpression {
+        Unconditional(Unconditional),
+        Guarded(Guarded),
+    }
+}
+
+create_ast_enum! {
+    /// A field in a record expression or binder.
+    RecordItem {
+        Field(RecordField),
+        Pun(RecordPun),
+    }
+}
+
+create_ast_enum! {
+    RecordUpdate {
+        Leaf(RecordUpdateLeaf),
+        Branch(RecordUpdateBranch),
+    }
+}
+
+create_ast_enum! {
+    DoStatement {
+        Bind(DoBind),
+        Let(DoLet),
+        Discard(DoDiscard),
+    }
+}
+
+create_ast_enum! {
+    /// A binding in `let` or `where`.
+    LetBinding {
+        Name(LetBindingName),
+        Signature(LetBindingSignature),
+        Pattern(LetBindingPattern),
+    }
+}
+
+impl ExpressionOperatorChain {
+    pub fn operands(&self) -> AstChildren<Expression> {
+        children(&self.syntax)
+    }
+
+    pub fn operators(&self) -> AstChildren<QualifiedName> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionInfixChain {
+    pub fn operands(&self) -> AstChildren<Expression> {
+        children(&self.syntax)
+    }
+
+    pub fn ticks(&self) -> AstChildren<ExpressionTick> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionTick {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionTyped {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionNegate {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionApplication {
+    pub fn head(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn arguments(&self) -> impl Iterator<Item = Expression> {
+        children(&self.syntax).skip(1)
+    }
+}
+
+impl ExpressionTypeArgument {
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionVariable {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionConstructor {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionOperatorName {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionLiteral {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl ExpressionArray {
+    pub fn elements(&self) -> AstChildren<Expression> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionRecord {
+    pub fn fields(&self) -> AstChildren<RecordItem> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionParenthesized {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionHole {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl ExpressionRecordAccess {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn labels(&self) -> AstChildren<Label> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionRecordUpdate {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn updates(&self) -> AstChildren<RecordUpdate> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionIfThenElse {
+    pub fn condition(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn then(&self) -> Option<Expression> {
+        child_after(&self.syntax, SyntaxKind::ThenKw)
+    }
+
+    pub fn else_(&self) -> Option<Expression> {
+        child_after(&self.syntax, SyntaxKind::ElseKw)
+    }
+}
+
+impl ExpressionLambda {
+    pub fn binders(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionCase {
+    pub fn scrutinees(&self) -> AstChildren<Expression> {
+        children(&self.syntax)
+    }
+
+    pub fn branches(&self) -> AstChildren<CaseBranch> {
+        children(&self.syntax)
+    }
+}
+
+impl CaseBranch {
+    pub fn binders(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+
+    pub fn guarded_expression(&self) -> Option<GuardedExpression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionLetIn {
+    pub fn bindings(&self) -> AstChildren<LetBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl ExpressionDo {
+    pub fn statements(&self) -> AstChildren<DoStatement> {
+        children(&self.syntax)
+    }
+}
+
+impl ExpressionAdo {
+    pub fn statements(&self) -> AstChildren<DoStatement> {
+        children(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl RecordField {
+    pub fn label(&self) -> Option<Label> {
+        child(&self.syntax)
+    }
+
+    /// The value of the field in a record expression.
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    /// The value of the field in a record binder.
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+}
+
+impl RecordPun {
+    pub fn label(&self) -> Option<Label> {
+        child(&self.syntax)
+    }
+}
+
+impl RecordUpdateLeaf {
+    pub fn label(&self) -> Option<Label> {
+        child(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl RecordUpdateBranch {
+    pub fn label(&self) -> Option<Label> {
+        child(&self.syntax)
+    }
+
+    pub fn updates(&self) -> AstChildren<RecordUpdate> {
+        children(&self.syntax)
+    }
+}
+
+impl Unconditional {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.syntax)
+    }
+}
+
+impl Guarded {
+    pub fn branches(&self) -> AstChildren<GuardedBranch> {
+        children(&self.syntax)
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.syntax)
+    }
+}
+
+impl GuardedBranch {
+    pub fn guards(&self) -> AstChildren<PatternGuard> {
+        children(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl PatternGuard {
+    /// Returns `None` for boolean guards.
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl WhereClause {
+    pub fn bindings(&self) -> AstChildren<LetBinding> {
+        children(&self.syntax)
+    }
+}
+
+impl DoBind {
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl DoLet {
+    pub fn bindings(&self) -> AstChildren<LetBinding> {
+        children(&self.syntax)
+    }
+}
+
+impl DoDiscard {
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+}
+
+impl LetBindingName {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn binders(&self) -> AstChildren<Binder> {
+        children(&self.syntax)
+    }
+
+    pub fn guarded_expression(&self) -> Option<GuardedExpression> {
+        child(&self.syntax)
+    }
+}
+
+impl LetBindingSignature {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl LetBindingPattern {
+    pub fn binder(&self) -> Option<Binder> {
+        child(&self.syntax)
+    }
+
+    pub fn expression(&self) -> Option<Expression> {
+        child(&self.syntax)
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.syntax)
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    child, children, create_ast_enum, create_ast_node, token, AstChildren, Declaration, ModuleName,
+    NameRef,
+};
+use crate::SyntaxKind;
+
+create_ast_node! {
+    Module;
+    ModuleHeader;
+    ExportList;
+    ExportValue;
+    ExportOperator;
+    ExportType;
+    ExportTypeOperator;
+    ExportClass;
+    ExportModule;
+    ImportDeclaration;
+    ImportList;
+    ImportValue;
+    ImportOperator;
+    ImportType;
+    ImportTypeOperator;
+    ImportClass;
+    ImportAlias;
+    DataAll;
+    DataEnumerated;
+}
+
+create_ast_enum! {
+    ExportItem {
+        Value(ExportValue),
+        Operator(ExportOperator),
+        Type(ExportType),
+        TypeOperator(ExportTypeOperator),
+        Class(ExportClass),
+        Module(ExportModule),
+    }
+}
+
+create_ast_enum! {
+    ImportItem {
+        Value(ImportValue),
+        Operator(ImportOperator),
+        Type(ImportType),
+        TypeOperator(ImportTypeOperator),
+        Class(ImportClass),
+    }
+}
+
+create_ast_enum! {
+    /// The constructors exported or imported with a type.
+    DataMembers {
+        All(DataAll),
+        Enumerated(DataEnumerated),
+    }
+}
+
+impl Module {
+    pub fn header(&self) -> Option<ModuleHeader> {
+        child(&self.syntax)
+    }
+
+    pub fn declarations(&self) -> AstChildren<Declaration> {
+        children(&self.syntax)
+    }
+}
+
+impl ModuleHeader {
+    pub fn name(&self) -> Option<ModuleName> {
+        child(&self.syntax)
+    }
+
+    /// Returns `None` if the module exports everything.
+    pub fn exports(&self) -> Option<ExportList> {
+        child(&self.syntax)
+    }
+
+    pub fn imports(&self) -> AstChildren<ImportDeclaration> {
+        children(&self.syntax)
+    }
+}
+
+impl ExportList {
+    pub fn items(&self) -> AstChildren<ExportItem> {
+        children(&self.syntax)
+    }
+}
+
+macro_rules! name_ref_accessor {
+    ($($node:ident),*) => {$(
+        impl $node {
+            pub fn name_ref(&self) -> Option<NameRef> {
+                child(&self.syntax)
+            }
+        }
+    )*};
+}
+
+name_ref_accessor!(
+    ExportValue,
+    ExportOperator,
+    ExportTypeOperator,
+    ExportClass,
+    ImportValue,
+    ImportOperator,
+    ImportTypeOperator,
+    ImportClass
+);
+
+impl ExportType {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        child(&self.syntax)
+    }
+
+    pub fn data_members(&self) -> Option<DataMembers> {
+        child(&self.syntax)
+    }
+}
+
+impl ExportModule {
+    pub fn module_name(&self) -> Option<ModuleName> {
+        child(&self.syntax)
+    }
+}
+
+impl ImportDeclaration {
+    pub fn module_name(&self) -> Option<ModuleName> {
+        child(&self.syntax)
+    }
+
+    pub fn import_list(&self) -> Option<ImportList> {
+        child(&self.syntax)
+    }
+
+    pub fn alias(&self) -> Option<ImportAlias> {
+        child(&self.syntax)
+    }
+}
+
+impl ImportList {
+    pub fn hiding(&self) -> bool {
+        token(&self.syntax, SyntaxKind::HidingKw).is_some()
+    }
+
+    pub fn items(&self) -> AstChildren<ImportItem> {
+        children(&self.syntax)
+    }
+}
+
+impl ImportType {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        child(&self.syntax)
+    }
+
+    pub fn data_members(&self) -> Option<DataMembers> {
+        child(&self.syntax)
+    }
+}
+
+impl ImportAlias {
+    pub fn module_name(&self) -> Option<ModuleName> {
+        child(&self.syntax)
+    }
+}
+
+impl DataEnumerated {
+    pub fn constructors(&self) -> AstChildren<NameRef> {
+        children(&self.syntax)
+    }
+}
@@ -0,0 +1,60 @@
+use super::{child, create_ast_node, significant_token};
+use crate::{SyntaxKind, SyntaxToken};
+
+create_ast_node! {
+    /// A binding occurrence of a name, e.g. in declarations and binders.
+    Name;
+    /// An unqualified reference to a name, e.g. in import lists.
+    NameRef;
+    /// A record label, which can also be a string or a keyword.
+    Label;
+    QualifiedName;
+    ModuleName;
+}
+
+impl Name {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl NameRef {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl Label {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl QualifiedName {
+    pub fn qualifier(&self) -> Option<ModuleName> {
+        child(&self.syntax)
+    }
+
+    /// The unqualified name, i.e. the final segment.
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.syntax
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| !token.kind().is_trivia() && token.kind() != SyntaxKind::Period)
+    }
+}
+
+impl ModuleName {
+    /// Invariant: [`SyntaxToken::kind()`] is always [`SyntaxKind::Upper`].
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.syntax.children_with_tokens().filter_map(|element| {
+            element.into_token().and_then(|token| {
+                if matches!(token.kind(), SyntaxKind::Upper) {
+                    Some(token)
+                } else {
+                    None
+                }
+            })
+        })
+    }
+}
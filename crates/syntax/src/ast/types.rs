@@ -0,0 +1,216 @@
+use super::{
+    child, child_after, children, create_ast_enum, create_ast_node, significant_token, AstChildren,
+    Label, Name, NameRef, QualifiedName,
+};
+use crate::{SyntaxKind, SyntaxToken};
+
+create_ast_node! {
+    TypeForall;
+    /// A constrained type, e.g. `Show a => a -> String`.
+    TypeConstrained;
+    /// A parenthesized sequence of constraints, e.g. `(Show a, Eq a)`.
+    TypeConstraints;
+    TypeArrow;
+    /// A flat chain of operators, e.g. `a /\ b`.
+    TypeOperatorChain;
+    TypeKinded;
+    TypeApplication;
+    TypeConstructor;
+    TypeVariable;
+    TypeWildcard;
+    TypeHole;
+    TypeString;
+    TypeInteger;
+    TypeRecord;
+    TypeRow;
+    TypeParenthesized;
+    TypeOperatorName;
+    /// The function type constructor, `(->)`.
+    TypeArrowName;
+    RowField;
+    RowTail;
+    TypeVariableBinding;
+}
+
+create_ast_enum! {
+    Type {
+        Forall(TypeForall),
+        Constrained(TypeConstrained),
+        Constraints(TypeConstraints),
+        Arrow(TypeArrow),
+        OperatorChain(TypeOperatorChain),
+        Kinded(TypeKinded),
+        Application(TypeApplication),
+        Constructor(TypeConstructor),
+        Variable(TypeVariable),
+        Wildcard(TypeWildcard),
+        Hole(TypeHole),
+        String(TypeString),
+        Integer(TypeInteger),
+        Record(TypeRecord),
+        Row(TypeRow),
+        Parenthesized(TypeParenthesized),
+        OperatorName(TypeOperatorName),
+        ArrowName(TypeArrowName),
+    }
+}
+
+impl TypeForall {
+    pub fn variables(&self) -> AstChildren<TypeVariableBinding> {
+        children(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeConstrained {
+    pub fn constraint(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child_after(&self.syntax, SyntaxKind::RightThickArrow)
+    }
+}
+
+impl TypeConstraints {
+    pub fn constraints(&self) -> AstChildren<Type> {
+        children(&self.syntax)
+    }
+}
+
+impl TypeArrow {
+    pub fn argument(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn result(&self) -> Option<Type> {
+        child_after(&self.syntax, SyntaxKind::RightArrow)
+    }
+}
+
+impl TypeOperatorChain {
+    pub fn operands(&self) -> AstChildren<Type> {
+        children(&self.syntax)
+    }
+
+    pub fn operators(&self) -> AstChildren<QualifiedName> {
+        children(&self.syntax)
+    }
+}
+
+impl TypeKinded {
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn kind(&self) -> Option<Type> {
+        child_after(&self.syntax, SyntaxKind::Colon2)
+    }
+}
+
+impl TypeApplication {
+    pub fn head(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn arguments(&self) -> impl Iterator<Item = Type> {
+        children(&self.syntax).skip(1)
+    }
+}
+
+impl TypeConstructor {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeVariable {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeHole {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl TypeString {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl TypeInteger {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_token(&self.syntax)
+    }
+}
+
+impl TypeRecord {
+    pub fn fields(&self) -> AstChildren<RowField> {
+        children(&self.syntax)
+    }
+
+    pub fn tail(&self) -> Option<RowTail> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeRow {
+    pub fn fields(&self) -> AstChildren<RowField> {
+        children(&self.syntax)
+    }
+
+    pub fn tail(&self) -> Option<RowTail> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeParenthesized {
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeOperatorName {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.syntax)
+    }
+}
+
+impl RowField {
+    pub fn label(&self) -> Option<Label> {
+        child(&self.syntax)
+    }
+
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl RowTail {
+    pub fn type_(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl TypeVariableBinding {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.syntax)
+    }
+
+    /// Whether the variable is bound with `@`, such that it can be
+    /// instantiated with a visible type application.
+    pub fn is_visible(&self) -> bool {
+        significant_token(&self.syntax).is_some_and(|token| token.kind() == SyntaxKind::At)
+    }
+
+    pub fn kind(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
@@ -1,4 +1,5 @@
 pub mod ast;
+pub mod unparse;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u16)]
@@ -13,23 +14,51 @@ pub enum SyntaxKind {
     WhereKw,
 
     ExportList,
+    ExportValue,
+    ExportOperator,
+    ExportType,
+    ExportTypeOperator,
+    ExportClass,
+    ExportModule,
+
     ImportList,
+    ImportValue,
+    ImportOperator,
+    ImportType,
+    ImportTypeOperator,
+    ImportClass,
+    ImportAlias,
+
+    DataAll,
+    DataEnumerated,
 
     ImportDeclaration,
     ImportKw,
     AsKw,
+    HidingKw,
 
+    Name,
+    NameRef,
+    Label,
     QualifiedName,
     ModuleName,
     Upper,
     Lower,
     Operator,
+    Hole,
 
     Equal,
+    Minus,
     Period,
     Period2,
     Colon,
     Colon2,
+    Comma,
+    Pipe,
+    Backslash,
+    Backtick,
+    At,
+    Underscore,
     LeftArrow,
     RightArrow,
     LeftThickArrow,
@@ -43,18 +72,112 @@ pub enum SyntaxKind {
 
     LiteralChar,
     LiteralString,
+    LiteralRawString,
     LiteralInteger,
     LiteralNumber,
     LiteralTrue,
     LiteralFalse,
 
-    Type,
-    Pattern,
+    CaseKw,
+    OfKw,
+    LetKw,
+    InKw,
+    IfKw,
+    ThenKw,
+    ElseKw,
+    DoKw,
+    AdoKw,
+    ForallKw,
+
+    ExpressionOperatorChain,
+    ExpressionInfixChain,
+    ExpressionTick,
+    ExpressionTyped,
+    ExpressionNegate,
+    ExpressionApplication,
+    ExpressionTypeArgument,
+    ExpressionVariable,
+    ExpressionConstructor,
+    ExpressionOperatorName,
+    ExpressionLiteral,
+    ExpressionArray,
+    ExpressionRecord,
+    ExpressionParenthesized,
+    ExpressionHole,
+    ExpressionWildcard,
+    ExpressionRecordAccess,
+    ExpressionRecordUpdate,
+    ExpressionIfThenElse,
+    ExpressionLambda,
+    ExpressionCase,
+    ExpressionLetIn,
+    ExpressionDo,
+    ExpressionAdo,
+
+    RecordField,
+    RecordPun,
+    RecordUpdateLeaf,
+    RecordUpdateBranch,
+
+    CaseBranch,
+    Unconditional,
+    Guarded,
+    GuardedBranch,
+    PatternGuard,
+    WhereClause,
+    /// The unparsed tokens of a value's body, from a module that was parsed
+    /// only for its interface.
+    SkippedBody,
+
+    DoBind,
+    DoLet,
+    DoDiscard,
+
+    LetBindingName,
+    LetBindingSignature,
+    LetBindingPattern,
+
+    BinderWildcard,
+    BinderVariable,
+    BinderNamed,
+    BinderConstructor,
+    BinderLiteral,
+    BinderNegative,
+    BinderArray,
+    BinderRecord,
+    BinderParenthesized,
+    BinderTyped,
+    BinderOperatorChain,
+
+    TypeForall,
+    TypeConstrained,
+    TypeConstraints,
+    TypeArrow,
+    TypeOperatorChain,
+    TypeKinded,
+    TypeApplication,
+    TypeConstructor,
+    TypeVariable,
+    TypeWildcard,
+    TypeHole,
+    TypeString,
+    TypeInteger,
+    TypeRecord,
+    TypeRow,
+    TypeParenthesized,
+    TypeOperatorName,
+    TypeArrowName,
+
+    RowField,
+    RowTail,
+    TypeVariableBinding,
 
     ValueDeclaration,
     AnnotationDeclaration,
+    KindSignatureDeclaration,
 
     DataDeclaration,
+    DataConstructor,
     DataKw,
 
     NewtypeDeclaration,
@@ -64,9 +187,16 @@ pub enum SyntaxKind {
     TypeKw,
 
     ClassDeclaration,
+    Constraints,
+    ClassMember,
+    FunctionalDependencies,
+    FunctionalDependency,
     ClassKw,
 
+    InstanceChain,
     InstanceDeclaration,
+    InstanceHead,
+    InstanceName,
     InstanceKw,
 
     DeriveInstanceDeclaration,
@@ -81,6 +211,11 @@ pub enum SyntaxKind {
     InfixrKw,
     InfixKw,
 
+    /// Virtual tokens emitted by the parser's layout algorithm. These never
+    /// appear in the syntax tree.
+    LayoutSeparator,
+    LayoutEnd,
+
     Error,
     EndOfFile,
 }
@@ -114,7 +249,48 @@ pub type SyntaxElement = rowan::SyntaxElement<PureScript>;
 
 impl SyntaxKind {
     pub fn is_contextual_operator(&self) -> bool {
-        matches!(self, Self::Colon | Self::Period2 | Self::LeftThickArrow)
+        matches!(self, Self::Colon | Self::Period2 | Self::LeftThickArrow | Self::Minus)
+    }
+
+    pub fn is_operator(&self) -> bool {
+        matches!(self, Self::Operator) || self.is_contextual_operator()
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Self::ModuleKw
+                | Self::WhereKw
+                | Self::ImportKw
+                | Self::AsKw
+                | Self::HidingKw
+                | Self::CaseKw
+                | Self::OfKw
+                | Self::LetKw
+                | Self::InKw
+                | Self::IfKw
+                | Self::ThenKw
+                | Self::ElseKw
+                | Self::DoKw
+                | Self::AdoKw
+                | Self::ForallKw
+                | Self::DataKw
+                | Self::NewtypeKw
+                | Self::TypeKw
+                | Self::ClassKw
+                | Self::InstanceKw
+                | Self::DeriveKw
+                | Self::ForeignKw
+                | Self::InfixlKw
+                | Self::InfixrKw
+                | Self::InfixKw
+                | Self::LiteralTrue
+                | Self::LiteralFalse
+        )
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace | Self::LineComment | Self::BlockComment)
     }
 }
 
@@ -168,17 +344,17 @@ mod tests {
 
         let module_name = purescript_module
             .children()
-            .nth(0)
+            .next()
             .unwrap()
             .children()
-            .nth(0)
+            .next()
             .and_then(ast::ModuleName::cast)
             .unwrap();
 
         let rust_module = SyntaxNode::new_root(
             module_name
                 .segments()
-                .nth(0)
+                .next()
                 .unwrap()
                 .replace_with(rowan::GreenToken::new(SyntaxKind::Upper.into(), "Rust")),
         );
@@ -0,0 +1,742 @@
+//! Rendering of expressions and types back into source text.
+//!
+//! Code actions that synthesize new code build an [`Expression`] or a
+//! [`Type`], either from scratch or from existing syntax through
+//! [`Expression::from_ast`] and [`Type::from_ast`]. Rendering then inserts
+//! only the parentheses required by precedence, using the [`Fixity`] that
+//! each operator carries; grouping in the original syntax is discarded.
+
+use std::{collections::HashMap, fmt};
+
+use rowan::ast::AstNode;
+
+use crate::{
+    ast::{self, Associativity},
+    SyntaxNode,
+};
+
+/// The fixity of an operator, e.g. `infixl 6`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: u8,
+}
+
+impl Fixity {
+    /// The fixity of operators without a fixity declaration, and of
+    /// functions applied infix with backticks.
+    pub const DEFAULT: Fixity = Fixity { associativity: Associativity::Left, precedence: 9 };
+
+    pub fn new(associativity: Associativity, precedence: u8) -> Fixity {
+        Fixity { associativity, precedence: precedence.min(9) }
+    }
+}
+
+/// Fixities of value and type operators in scope.
+#[derive(Debug, Clone, Default)]
+pub struct Fixities {
+    values: HashMap<String, Fixity>,
+    types: HashMap<String, Fixity>,
+}
+
+impl Fixities {
+    /// Collects the fixity declarations in a module.
+    pub fn from_module(module: &ast::Module) -> Fixities {
+        let mut fixities = Fixities::default();
+        for declaration in module.declarations() {
+            let ast::Declaration::Fixity(declaration) = declaration else { continue };
+            let Some(associativity) = declaration.associativity() else { continue };
+            let Some(precedence) =
+                declaration.precedence().and_then(|token| token.text().parse().ok())
+            else {
+                continue;
+            };
+            let Some(operator) = declaration.operator() else { continue };
+            let operator = operator.syntax().text().to_string();
+            let fixity = Fixity::new(associativity, precedence);
+            if declaration.is_type() {
+                fixities.insert_type(operator, fixity);
+            } else {
+                fixities.insert_value(operator, fixity);
+            }
+        }
+        fixities
+    }
+
+    pub fn insert_value(&mut self, operator: impl Into<String>, fixity: Fixity) {
+        self.values.insert(operator.into(), fixity);
+    }
+
+    pub fn insert_type(&mut self, operator: impl Into<String>, fixity: Fixity) {
+        self.types.insert(operator.into(), fixity);
+    }
+
+    pub fn value(&self, operator: &str) -> Fixity {
+        self.values.get(unqualified(operator)).copied().unwrap_or(Fixity::DEFAULT)
+    }
+
+    pub fn type_(&self, operator: &str) -> Fixity {
+        self.types.get(unqualified(operator)).copied().unwrap_or(Fixity::DEFAULT)
+    }
+}
+
+/// Strips the module qualifier from an operator, e.g. `M.<>` into `<>`.
+fn unqualified(operator: &str) -> &str {
+    let mut rest = operator;
+    while let Some((segment, tail)) = rest.split_once('.') {
+        if segment.is_empty() || !segment.starts_with(|c: char| c.is_uppercase()) || tail.is_empty()
+        {
+            break;
+        }
+        rest = tail;
+    }
+    rest
+}
+
+/// An expression to be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// Syntactically atomic text, such as names, literals, and holes.
+    Atom(String),
+    /// Text that is rendered verbatim but always parenthesized when nested,
+    /// such as `case` or `do` blocks.
+    Opaque(String),
+    Application(Box<Expression>, Vec<Expression>),
+    Binary(Box<Binary<Expression>>),
+    Negate(Box<Expression>),
+    Lambda(Vec<String>, Box<Expression>),
+    Typed(Box<Expression>, Type),
+    Array(Vec<Expression>),
+    /// Record fields, where fields without a value are puns.
+    Record(Vec<(String, Option<Expression>)>),
+    RecordAccess(Box<Expression>, Vec<String>),
+}
+
+/// A type to be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// Syntactically atomic text, such as names, records, and rows.
+    Atom(String),
+    Application(Box<Type>, Vec<Type>),
+    Binary(Box<Binary<Type>>),
+    Arrow(Box<Type>, Box<Type>),
+    Constrained(Box<Type>, Box<Type>),
+    Forall(Vec<String>, Box<Type>),
+    Kinded(Box<Type>, Box<Type>),
+}
+
+/// An operator application, e.g. `a + b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binary<T> {
+    /// The operator as written, or the function name for backtick operators.
+    pub operator: String,
+    pub fixity: Fixity,
+    pub lhs: T,
+    pub rhs: T,
+}
+
+impl Expression {
+    pub fn atom(text: impl Into<String>) -> Expression {
+        Expression::Atom(text.into())
+    }
+
+    pub fn application(head: Expression, arguments: Vec<Expression>) -> Expression {
+        if arguments.is_empty() {
+            return head;
+        }
+        match head {
+            Expression::Application(head, mut existing) => {
+                existing.extend(arguments);
+                Expression::Application(head, existing)
+            }
+            head => Expression::Application(Box::new(head), arguments),
+        }
+    }
+
+    pub fn binary(
+        operator: impl Into<String>,
+        fixity: Fixity,
+        lhs: Expression,
+        rhs: Expression,
+    ) -> Expression {
+        let operator = operator.into();
+        Expression::Binary(Box::new(Binary { operator, fixity, lhs, rhs }))
+    }
+
+    /// Converts syntax into an expression, re-associating operator chains
+    /// according to `fixities`.
+    pub fn from_ast(expression: &ast::Expression, fixities: &Fixities) -> Expression {
+        match expression {
+            ast::Expression::OperatorChain(_) | ast::Expression::InfixChain(_) => {
+                let mut operands = vec![];
+                let mut operators = vec![];
+                expression_chain(expression.syntax(), fixities, &mut operands, &mut operators);
+                associate(operands, operators, |operator, fixity, lhs, rhs| {
+                    Expression::binary(operator, fixity, lhs, rhs)
+                })
+            }
+            ast::Expression::Typed(typed) => match (typed.expression(), typed.type_()) {
+                (Some(expression), Some(type_)) => Expression::Typed(
+                    Box::new(Expression::from_ast(&expression, fixities)),
+                    Type::from_ast(&type_, fixities),
+                ),
+                _ => Expression::Opaque(text(typed.syntax())),
+            },
+            ast::Expression::Negate(negate) => match negate.expression() {
+                Some(expression) => {
+                    Expression::Negate(Box::new(Expression::from_ast(&expression, fixities)))
+                }
+                None => Expression::Opaque(text(negate.syntax())),
+            },
+            ast::Expression::Application(application) => match application.head() {
+                Some(head) => Expression::application(
+                    Expression::from_ast(&head, fixities),
+                    application
+                        .arguments()
+                        .map(|argument| Expression::from_ast(&argument, fixities))
+                        .collect(),
+                ),
+                None => Expression::Opaque(text(application.syntax())),
+            },
+            ast::Expression::Parenthesized(parenthesized) => match parenthesized.expression() {
+                Some(expression) => Expression::from_ast(&expression, fixities),
+                None => Expression::Atom(text(parenthesized.syntax())),
+            },
+            ast::Expression::Array(array) => Expression::Array(
+                array.elements().map(|element| Expression::from_ast(&element, fixities)).collect(),
+            ),
+            ast::Expression::Record(record) => {
+                let fields = record.fields().map(|field| match field {
+                    ast::RecordItem::Field(field) => Some((
+                        text(field.label()?.syntax()),
+                        Some(Expression::from_ast(&field.expression()?, fixities)),
+                    )),
+                    ast::RecordItem::Pun(pun) => Some((text(pun.label()?.syntax()), None)),
+                });
+                match fields.collect() {
+                    Some(fields) => Expression::Record(fields),
+                    None => Expression::Atom(text(record.syntax())),
+                }
+            }
+            ast::Expression::RecordAccess(access) => match access.expression() {
+                Some(expression) => Expression::RecordAccess(
+                    Box::new(Expression::from_ast(&expression, fixities)),
+                    access.labels().map(|label| text(label.syntax())).collect(),
+                ),
+                None => Expression::Opaque(text(access.syntax())),
+            },
+            ast::Expression::Lambda(lambda) => match lambda.body() {
+                Some(body) => Expression::Lambda(
+                    lambda.binders().map(|binder| text(binder.syntax())).collect(),
+                    Box::new(Expression::from_ast(&body, fixities)),
+                ),
+                None => Expression::Opaque(text(lambda.syntax())),
+            },
+            ast::Expression::Variable(_)
+            | ast::Expression::Constructor(_)
+            | ast::Expression::OperatorName(_)
+            | ast::Expression::Literal(_)
+            | ast::Expression::Hole(_)
+            | ast::Expression::Wildcard(_)
+            | ast::Expression::TypeArgument(_)
+            | ast::Expression::RecordUpdate(_) => Expression::Atom(text(expression.syntax())),
+            ast::Expression::IfThenElse(_)
+            | ast::Expression::Case(_)
+            | ast::Expression::LetIn(_)
+            | ast::Expression::Do(_)
+            | ast::Expression::Ado(_) => Expression::Opaque(text(expression.syntax())),
+        }
+    }
+
+    /// Whether the expression has to be parenthesized to be the left operand
+    /// of an operator of `fixity`.
+    pub fn needs_parentheses_before(&self, fixity: Fixity) -> bool {
+        operand_needs_parentheses(self.precedence(), self.as_binary(), fixity, Side::Left, 2)
+    }
+
+    /// Whether the expression has to be parenthesized to be the right
+    /// operand of an operator of `fixity`.
+    pub fn needs_parentheses_after(&self, fixity: Fixity) -> bool {
+        operand_needs_parentheses(self.precedence(), self.as_binary(), fixity, Side::Right, 2)
+    }
+
+    /// Whether the expression has to be parenthesized to be applied to
+    /// arguments.
+    pub fn needs_parentheses_applied(&self) -> bool {
+        self.precedence() < 13
+    }
+
+    /// Whether the expression has to be parenthesized to be the argument of
+    /// an application, or to have its fields accessed.
+    pub fn needs_parentheses_as_argument(&self) -> bool {
+        self.precedence() < 14
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Typed(..) => 0,
+            Expression::Opaque(_) | Expression::Lambda(..) => 1,
+            Expression::Binary(binary) => 2 + binary.fixity.precedence,
+            Expression::Negate(_) => 12,
+            Expression::Application(..) => 13,
+            Expression::Atom(_)
+            | Expression::Array(_)
+            | Expression::Record(_)
+            | Expression::RecordAccess(..) => 14,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&Binary<Expression>> {
+        match self {
+            Expression::Binary(binary) => Some(binary),
+            _ => None,
+        }
+    }
+}
+
+/// Flattens nested operator and infix chains into their operands and
+/// operators, as both kinds of operators share one precedence table.
+fn expression_chain(
+    node: &SyntaxNode,
+    fixities: &Fixities,
+    operands: &mut Vec<Expression>,
+    operators: &mut Vec<(String, Fixity)>,
+) {
+    for child in node.children() {
+        if let Some(operator) = ast::QualifiedName::cast(child.clone()) {
+            let operator = text(operator.syntax());
+            let fixity = fixities.value(&operator);
+            operators.push((operator, fixity));
+        } else if let Some(tick) = ast::ExpressionTick::cast(child.clone()) {
+            let operator = tick.expression().map_or_else(String::new, |e| text(e.syntax()));
+            operators.push((operator, Fixity::DEFAULT));
+        } else if let Some(operand) = ast::Expression::cast(child) {
+            match operand {
+                ast::Expression::OperatorChain(_) | ast::Expression::InfixChain(_) => {
+                    expression_chain(operand.syntax(), fixities, operands, operators)
+                }
+                operand => operands.push(Expression::from_ast(&operand, fixities)),
+            }
+        }
+    }
+}
+
+impl Type {
+    pub fn atom(text: impl Into<String>) -> Type {
+        Type::Atom(text.into())
+    }
+
+    pub fn application(head: Type, arguments: Vec<Type>) -> Type {
+        if arguments.is_empty() {
+            return head;
+        }
+        match head {
+            Type::Application(head, mut existing) => {
+                existing.extend(arguments);
+                Type::Application(head, existing)
+            }
+            head => Type::Application(Box::new(head), arguments),
+        }
+    }
+
+    pub fn arrow(argument: Type, result: Type) -> Type {
+        Type::Arrow(Box::new(argument), Box::new(result))
+    }
+
+    pub fn binary(operator: impl Into<String>, fixity: Fixity, lhs: Type, rhs: Type) -> Type {
+        let operator = operator.into();
+        Type::Binary(Box::new(Binary { operator, fixity, lhs, rhs }))
+    }
+
+    /// Converts syntax into a type, re-associating operator chains according
+    /// to `fixities`.
+    pub fn from_ast(type_: &ast::Type, fixities: &Fixities) -> Type {
+        let opaque = || Type::Atom(format!("({})", text(type_.syntax())));
+        match type_ {
+            ast::Type::Forall(forall) => match forall.type_() {
+                Some(inner) => Type::Forall(
+                    forall.variables().map(|variable| text(variable.syntax())).collect(),
+                    Box::new(Type::from_ast(&inner, fixities)),
+                ),
+                None => opaque(),
+            },
+            ast::Type::Constrained(constrained) => {
+                match (constrained.constraint(), constrained.type_()) {
+                    (Some(constraint), Some(inner)) => Type::Constrained(
+                        Box::new(Type::from_ast(&constraint, fixities)),
+                        Box::new(Type::from_ast(&inner, fixities)),
+                    ),
+                    _ => opaque(),
+                }
+            }
+            ast::Type::Arrow(arrow) => match (arrow.argument(), arrow.result()) {
+                (Some(argument), Some(result)) => Type::arrow(
+                    Type::from_ast(&argument, fixities),
+                    Type::from_ast(&result, fixities),
+                ),
+                _ => opaque(),
+            },
+            ast::Type::OperatorChain(chain) => {
+                let mut operands = vec![];
+                let mut operators = vec![];
+                for child in chain.syntax().children() {
+                    if let Some(operator) = ast::QualifiedName::cast(child.clone()) {
+                        let operator = text(operator.syntax());
+                        let fixity = fixities.type_(&operator);
+                        operators.push((operator, fixity));
+                    } else if let Some(operand) = ast::Type::cast(child) {
+                        operands.push(Type::from_ast(&operand, fixities));
+                    }
+                }
+                associate(operands, operators, |operator, fixity, lhs, rhs| {
+                    Type::binary(operator, fixity, lhs, rhs)
+                })
+            }
+            ast::Type::Kinded(kinded) => match (kinded.type_(), kinded.kind()) {
+                (Some(inner), Some(kind)) => Type::Kinded(
+                    Box::new(Type::from_ast(&inner, fixities)),
+                    Box::new(Type::from_ast(&kind, fixities)),
+                ),
+                _ => opaque(),
+            },
+            ast::Type::Application(application) => match application.head() {
+                Some(head) => Type::application(
+                    Type::from_ast(&head, fixities),
+                    application
+                        .arguments()
+                        .map(|argument| Type::from_ast(&argument, fixities))
+                        .collect(),
+                ),
+                None => opaque(),
+            },
+            ast::Type::Parenthesized(parenthesized) => match parenthesized.type_() {
+                Some(inner) => Type::from_ast(&inner, fixities),
+                None => Type::Atom(text(parenthesized.syntax())),
+            },
+            ast::Type::Constraints(_)
+            | ast::Type::Constructor(_)
+            | ast::Type::Variable(_)
+            | ast::Type::Wildcard(_)
+            | ast::Type::Hole(_)
+            | ast::Type::String(_)
+            | ast::Type::Integer(_)
+            | ast::Type::Record(_)
+            | ast::Type::Row(_)
+            | ast::Type::OperatorName(_)
+            | ast::Type::ArrowName(_) => Type::Atom(text(type_.syntax())),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Type::Kinded(..) => 0,
+            Type::Forall(..) => 1,
+            Type::Arrow(..) | Type::Constrained(..) => 2,
+            Type::Binary(binary) => 3 + binary.fixity.precedence,
+            Type::Application(..) => 13,
+            Type::Atom(_) => 14,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&Binary<Type>> {
+        match self {
+            Type::Binary(binary) => Some(binary),
+            _ => None,
+        }
+    }
+}
+
+/// Re-associates a flat chain of operands and operators.
+fn associate<T>(
+    operands: Vec<T>,
+    operators: Vec<(String, Fixity)>,
+    combine: impl Fn(String, Fixity, T, T) -> T,
+) -> T {
+    let mut operands = operands.into_iter();
+    let mut output = vec![operands.next().expect("chains have at least one operand")];
+    let mut pending: Vec<(String, Fixity)> = vec![];
+
+    let reduce = |output: &mut Vec<T>, (operator, fixity): (String, Fixity)| {
+        let rhs = output.pop().unwrap();
+        let lhs = output.pop().unwrap();
+        output.push(combine(operator, fixity, lhs, rhs));
+    };
+
+    for ((operator, fixity), operand) in operators.into_iter().zip(operands) {
+        while let Some((_, top)) = pending.last() {
+            let binds_tighter = top.precedence > fixity.precedence
+                || (top.precedence == fixity.precedence
+                    && fixity.associativity != Associativity::Right);
+            if !binds_tighter {
+                break;
+            }
+            let top = pending.pop().unwrap();
+            reduce(&mut output, top);
+        }
+        pending.push((operator, fixity));
+        output.push(operand);
+    }
+
+    while let Some(top) = pending.pop() {
+        reduce(&mut output, top);
+    }
+
+    output.pop().unwrap()
+}
+
+fn text(node: &SyntaxNode) -> String {
+    node.text().to_string()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Determines if an operand in a binary operator application must be
+/// parenthesized, given its `offset` in the precedence levels.
+fn operand_needs_parentheses<T>(
+    operand_precedence: u8,
+    operand: Option<&Binary<T>>,
+    parent: Fixity,
+    side: Side,
+    offset: u8,
+) -> bool {
+    match operand {
+        Some(operand) if operand.fixity.precedence == parent.precedence => {
+            let associates = operand.fixity.associativity == parent.associativity
+                && matches!(
+                    (parent.associativity, side),
+                    (Associativity::Left, Side::Left) | (Associativity::Right, Side::Right)
+                );
+            !associates
+        }
+        _ => operand_precedence < offset + parent.precedence,
+    }
+}
+
+fn write_operator(f: &mut fmt::Formatter<'_>, operator: &str) -> fmt::Result {
+    if operator.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        write!(f, "`{}`", operator)
+    } else {
+        write!(f, "{}", operator)
+    }
+}
+
+struct Parenthesized<'a, T>(&'a T, bool);
+
+impl<T: fmt::Display> fmt::Display for Parenthesized<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.1 {
+            write!(f, "({})", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+fn at_least<T: fmt::Display>(value: &T, precedence: u8, minimum: u8) -> Parenthesized<'_, T> {
+    Parenthesized(value, precedence < minimum)
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Atom(text) | Expression::Opaque(text) => write!(f, "{}", text),
+            Expression::Application(head, arguments) => {
+                write!(f, "{}", at_least(head.as_ref(), head.precedence(), 13))?;
+                for argument in arguments {
+                    write!(f, " {}", at_least(argument, argument.precedence(), 14))?;
+                }
+                Ok(())
+            }
+            Expression::Binary(binary) => {
+                let Binary { operator, fixity, lhs, rhs } = binary.as_ref();
+                let lhs_parentheses = operand_needs_parentheses(
+                    lhs.precedence(),
+                    lhs.as_binary(),
+                    *fixity,
+                    Side::Left,
+                    2,
+                );
+                let rhs_parentheses = operand_needs_parentheses(
+                    rhs.precedence(),
+                    rhs.as_binary(),
+                    *fixity,
+                    Side::Right,
+                    2,
+                );
+                write!(f, "{} ", Parenthesized(lhs, lhs_parentheses))?;
+                write_operator(f, operator)?;
+                write!(f, " {}", Parenthesized(rhs, rhs_parentheses))
+            }
+            Expression::Negate(expression) => {
+                // `- -x` would be lexed as a line comment.
+                let parentheses =
+                    expression.precedence() < 13 || matches!(**expression, Expression::Negate(_));
+                write!(f, "-{}", Parenthesized(expression.as_ref(), parentheses))
+            }
+            Expression::Lambda(binders, body) => {
+                write!(f, "\\{} -> {}", binders.join(" "), body)
+            }
+            Expression::Typed(expression, type_) => {
+                write!(
+                    f,
+                    "{} :: {}",
+                    at_least(expression.as_ref(), expression.precedence(), 2),
+                    type_
+                )
+            }
+            Expression::Array(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Expression::Record(fields) => {
+                if fields.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                write!(f, "{{ ")?;
+                for (index, (label, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match value {
+                        Some(value) => write!(f, "{}: {}", label, value)?,
+                        None => write!(f, "{}", label)?,
+                    }
+                }
+                write!(f, " }}")
+            }
+            Expression::RecordAccess(expression, labels) => {
+                write!(f, "{}", at_least(expression.as_ref(), expression.precedence(), 14))?;
+                for label in labels {
+                    write!(f, ".{}", label)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Atom(text) => write!(f, "{}", text),
+            Type::Application(head, arguments) => {
+                write!(f, "{}", at_least(head.as_ref(), head.precedence(), 13))?;
+                for argument in arguments {
+                    write!(f, " {}", at_least(argument, argument.precedence(), 14))?;
+                }
+                Ok(())
+            }
+            Type::Binary(binary) => {
+                let Binary { operator, fixity, lhs, rhs } = binary.as_ref();
+                let lhs_parentheses = operand_needs_parentheses(
+                    lhs.precedence(),
+                    lhs.as_binary(),
+                    *fixity,
+                    Side::Left,
+                    3,
+                );
+                let rhs_parentheses = operand_needs_parentheses(
+                    rhs.precedence(),
+                    rhs.as_binary(),
+                    *fixity,
+                    Side::Right,
+                    3,
+                );
+                write!(f, "{} ", Parenthesized(lhs, lhs_parentheses))?;
+                write_operator(f, operator)?;
+                write!(f, " {}", Parenthesized(rhs, rhs_parentheses))
+            }
+            Type::Arrow(argument, result) => write!(
+                f,
+                "{} -> {}",
+                at_least(argument.as_ref(), argument.precedence(), 3),
+                at_least(result.as_ref(), result.precedence(), 1)
+            ),
+            Type::Constrained(constraint, inner) => write!(
+                f,
+                "{} => {}",
+                at_least(constraint.as_ref(), constraint.precedence(), 3),
+                at_least(inner.as_ref(), inner.precedence(), 1)
+            ),
+            Type::Forall(variables, inner) => {
+                write!(f, "forall {}. {}", variables.join(" "), inner)
+            }
+            Type::Kinded(inner, kind) => {
+                write!(f, "{} :: {}", at_least(inner.as_ref(), inner.precedence(), 1), kind)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expression, Fixity, Type};
+    use crate::ast::Associativity;
+
+    fn add() -> Fixity {
+        Fixity::new(Associativity::Left, 6)
+    }
+
+    fn mul() -> Fixity {
+        Fixity::new(Associativity::Left, 7)
+    }
+
+    fn atom(text: &str) -> Expression {
+        Expression::atom(text)
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        let sum = Expression::binary("+", add(), atom("a"), atom("b"));
+        let product = Expression::binary("*", mul(), sum.clone(), atom("c"));
+        assert_eq!(product.to_string(), "(a + b) * c");
+
+        let product = Expression::binary("*", mul(), atom("b"), atom("c"));
+        let sum = Expression::binary("+", add(), atom("a"), product);
+        assert_eq!(sum.to_string(), "a + b * c");
+
+        let left = Expression::binary("-", add(), atom("a"), atom("b"));
+        assert_eq!(
+            Expression::binary("-", add(), left.clone(), atom("c")).to_string(),
+            "a - b - c"
+        );
+        assert_eq!(Expression::binary("-", add(), atom("c"), left).to_string(), "c - (a - b)");
+
+        let applied = Expression::application(atom("f"), vec![sum.clone(), atom("x")]);
+        assert_eq!(applied.to_string(), "f (a + b * c) x");
+
+        let negated = Expression::Negate(Box::new(Expression::Negate(Box::new(atom("x")))));
+        assert_eq!(negated.to_string(), "-(-x)");
+
+        let lambda = Expression::Lambda(vec!["x".into()], Box::new(atom("x")));
+        let typed = Expression::Typed(Box::new(lambda), Type::atom("Int"));
+        assert_eq!(typed.to_string(), "(\\x -> x) :: Int");
+    }
+
+    #[test]
+    fn minimal_parentheses_types() {
+        let function = Type::arrow(Type::atom("a"), Type::atom("b"));
+        let higher = Type::arrow(function.clone(), Type::atom("c"));
+        assert_eq!(higher.to_string(), "(a -> b) -> c");
+
+        let curried = Type::arrow(Type::atom("c"), function.clone());
+        assert_eq!(curried.to_string(), "c -> a -> b");
+
+        let applied = Type::application(Type::atom("Maybe"), vec![function]);
+        assert_eq!(applied.to_string(), "Maybe (a -> b)");
+
+        let forall = Type::Forall(vec!["a".into()], Box::new(Type::atom("a")));
+        assert_eq!(Type::arrow(forall.clone(), Type::atom("b")).to_string(), "(forall a. a) -> b");
+        assert_eq!(Type::arrow(Type::atom("b"), forall).to_string(), "b -> forall a. a");
+    }
+}
@@ -0,0 +1,484 @@
+//! Validation of `derive instance` declarations.
+//!
+//! Only the classes that the compiler knows how to derive can be derived,
+//! and only for the data types and newtypes of the module itself. The
+//! instances that a derived instance relies on, such as `Eq` for the fields
+//! of each constructor, are solved like the constraints of a body, against
+//! the context of the derived instance.
+
+use lowering::{
+    arena::Arena,
+    hir::{Body, Declaration, Derive, GuardedExpr, Module, TypeId, TypeVariable},
+    item_tree::{Item, ItemTree},
+    name::{ModuleName, Name},
+};
+use resolution::{
+    body::{BodyResolution, ModuleScope},
+    fixity::Chains,
+    instances::resolve_class,
+};
+
+use crate::{
+    infer::{Infer, Origin, TypeDiagnostic},
+    solve::Instances,
+    ty::{Constraint, Ty, TyId, TypeConstructor, Types},
+    Workspace,
+};
+
+/// The diagnostics of the derived instances of a module.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ModuleDerives {
+    pub types: Types,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl ModuleDerives {
+    pub fn diagnostics(&self) -> &[TypeDiagnostic] {
+        &self.diagnostics
+    }
+}
+
+/// The classes that can be derived, by the module that declares them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Eq,
+    Ord,
+    Functor,
+    Newtype,
+    Generic,
+}
+
+impl Strategy {
+    fn of(module: ModuleName, class: Name) -> Option<Strategy> {
+        let strategy = match (module.as_str(), class.as_str()) {
+            ("Data.Eq", "Eq") => Strategy::Eq,
+            ("Data.Ord", "Ord") => Strategy::Ord,
+            ("Data.Functor", "Functor") => Strategy::Functor,
+            ("Data.Newtype", "Newtype") => Strategy::Newtype,
+            ("Data.Generic.Rep", "Generic") => Strategy::Generic,
+            _ => return None,
+        };
+        Some(strategy)
+    }
+}
+
+pub fn check_derives(
+    workspace: &dyn Workspace,
+    name: ModuleName,
+    module: &Module,
+) -> ModuleDerives {
+    let Some(scope) = workspace.scope(name) else { return ModuleDerives::default() };
+    let Some(tree) = workspace.item_tree(name) else { return ModuleDerives::default() };
+    // Like kinds, derived instances have no expressions of their own.
+    let body = Body {
+        exprs: Arena::default(),
+        binders: Arena::default(),
+        guarded: GuardedExpr::Guarded(vec![]),
+    };
+    let instances = Instances::new(workspace);
+    let resolution = BodyResolution::new(&body, scope);
+    let infer =
+        Infer::new(workspace, &instances, module, &body, scope, resolution, Chains::default());
+    let mut derives = Derives { infer, name, scope, tree };
+    for (_, declaration) in module.declarations.iter() {
+        if let Declaration::Derive(derive) = declaration {
+            derives.derive(derive);
+        }
+    }
+
+    let mut infer = derives.infer;
+    let diagnostics = infer.take_diagnostics();
+    ModuleDerives { types: infer.types, diagnostics }
+}
+
+struct Derives<'a> {
+    infer: Infer<'a>,
+    name: ModuleName,
+    scope: &'a ModuleScope,
+    tree: &'a ItemTree,
+}
+
+/// A data type or newtype of the module that an instance is derived for.
+struct DataType<'a> {
+    name: Name,
+    newtype: bool,
+    variables: &'a [TypeVariable],
+    /// The fields of each constructor.
+    constructors: Vec<&'a [TypeId]>,
+}
+
+impl<'a> Derives<'a> {
+    fn derive(&mut self, derive: &Derive) {
+        let head = &derive.head;
+        let workspace = self.infer.workspace;
+        let tree = |module| workspace.item_tree(module);
+        let Some((definition, class)) = resolve_class(self.scope, head.class, tree) else {
+            return;
+        };
+        // Kinds report instances with the wrong number of arguments.
+        if head.arguments.len() != class.variables.len() || head.arguments.is_empty() {
+            return;
+        }
+
+        let (types, resolution) = self.infer.module_types.clone();
+        let arguments: Vec<_> = head
+            .arguments
+            .iter()
+            .map(|&argument| self.infer.convert(types, &resolution, argument))
+            .collect();
+        let mut givens = vec![];
+        for &constraint in &head.constraints {
+            givens.extend(self.infer.convert_constraints(types, &resolution, constraint));
+        }
+        let constraint = Constraint {
+            class: TypeConstructor { name: class.name, definition: Some(definition) },
+            arguments: arguments.clone(),
+        };
+
+        let start = self.infer.wanted.len();
+        let saved = std::mem::take(&mut self.infer.givens);
+        for given in givens {
+            self.infer.give(given);
+        }
+        if derive.newtype {
+            let last = head.arguments.len() - 1;
+            let origin = Origin::Type(head.arguments[last]);
+            self.derive_newtype(constraint, last, origin);
+        } else {
+            match Strategy::of(definition.module, class.name) {
+                Some(strategy) => {
+                    // `Newtype` and `Generic` are derived for their first
+                    // argument, and determine the others.
+                    let position = match strategy {
+                        Strategy::Newtype | Strategy::Generic => 0,
+                        _ => head.arguments.len() - 1,
+                    };
+                    let origin = Origin::Type(head.arguments[position]);
+                    self.derive_strategy(strategy, constraint, position, origin);
+                }
+                None => {
+                    let origin = Origin::Type(head.arguments[head.arguments.len() - 1]);
+                    self.infer
+                        .diagnostics
+                        .push(TypeDiagnostic::NotDerivable { origin, constraint });
+                }
+            }
+        }
+        self.infer.solve_constraints(start);
+        for (constraint, origin) in self.infer.wanted.split_off(start) {
+            self.infer.diagnostics.push(TypeDiagnostic::NoInstance { origin, constraint });
+        }
+        self.infer.givens = saved;
+    }
+
+    fn derive_strategy(
+        &mut self,
+        strategy: Strategy,
+        constraint: Constraint,
+        position: usize,
+        origin: Origin,
+    ) {
+        let ty = constraint.arguments[position];
+        let Some((data, arguments)) = self.data_type(ty) else {
+            self.infer.diagnostics.push(TypeDiagnostic::ExpectedTypeConstructor {
+                origin,
+                constraint,
+                ty,
+            });
+            return;
+        };
+        match strategy {
+            Strategy::Eq | Strategy::Ord => {
+                if arguments.len() != data.variables.len() {
+                    return;
+                }
+                let fields = self.fields(&data, &arguments);
+                for field in fields {
+                    self.want_fields(constraint.class, field, origin);
+                }
+            }
+            Strategy::Functor => {
+                let Some((last, variables)) = data.variables.split_last() else { return };
+                if arguments.len() != variables.len() {
+                    return;
+                }
+                let mut covariant = true;
+                for field in self.fields(&data, &[]) {
+                    covariant &= self.covariant(field, last.name);
+                }
+                if !covariant {
+                    self.infer.diagnostics.push(TypeDiagnostic::InvalidConstructorArgument {
+                        origin,
+                        class: constraint.class.name,
+                    });
+                }
+            }
+            Strategy::Newtype => {
+                if !data.newtype {
+                    let name = data.name;
+                    self.infer.diagnostics.push(TypeDiagnostic::NewtypeForData { origin, name });
+                    return;
+                }
+                if arguments.len() != data.variables.len() {
+                    return;
+                }
+                // The wrapped type is determined by the newtype, so a
+                // wildcard or the wrapped type itself is expected.
+                if let (Some(&field), Some(&wrapped)) =
+                    (self.fields(&data, &arguments).first(), constraint.arguments.get(1))
+                {
+                    self.infer.unify(wrapped, field, origin);
+                }
+            }
+            Strategy::Generic => {}
+        }
+    }
+
+    /// Derives an instance through the type that a newtype wraps, which may
+    /// be applied to fewer arguments than the newtype has variables, e.g.
+    /// `derive newtype instance Functor Wrapper` for a field of type
+    /// `Array a`.
+    fn derive_newtype(&mut self, mut constraint: Constraint, position: usize, origin: Origin) {
+        let ty = constraint.arguments[position];
+        let data = self.data_type(ty).filter(|(data, _)| data.newtype);
+        let Some((data, arguments)) = data else {
+            self.infer
+                .diagnostics
+                .push(TypeDiagnostic::InvalidNewtypeInstance { origin, constraint });
+            return;
+        };
+        if arguments.len() > data.variables.len() {
+            return;
+        }
+        let missing = &data.variables[arguments.len()..];
+        let mut bindings = self.bindings(data.variables, &arguments);
+        // The variables that aren't applied are kept as they are, and have
+        // to be the last arguments of the wrapped type.
+        for variable in missing {
+            let ty = self.infer.types.alloc(Ty::Variable(variable.name));
+            bindings.push((variable.name, ty));
+        }
+        let Some(&field) = data.constructors[0].first() else { return };
+        let field = self.infer.convert_tree_type(self.tree, self.name, field);
+        let mut wrapped = self.infer.substitute(field, &bindings);
+        for variable in missing.iter().rev() {
+            let stripped = match self.infer.types[wrapped] {
+                Ty::Application(function, argument)
+                    if self.infer.types[argument] == Ty::Variable(variable.name) =>
+                {
+                    Some(function)
+                }
+                _ => None,
+            };
+            match stripped {
+                Some(function)
+                    if missing.iter().all(|variable| !self.mentions(function, variable.name)) =>
+                {
+                    wrapped = function;
+                }
+                _ => {
+                    self.infer
+                        .diagnostics
+                        .push(TypeDiagnostic::InvalidNewtypeInstance { origin, constraint });
+                    return;
+                }
+            }
+        }
+        constraint.arguments[position] = wrapped;
+        self.infer.wanted.push((constraint, origin));
+    }
+
+    /// The data type or newtype of the module that `ty` applies, along with
+    /// its arguments.
+    fn data_type(&mut self, ty: TyId) -> Option<(DataType<'a>, Vec<TyId>)> {
+        let ty = self.infer.prune(ty);
+        let (constructor, arguments) = self.infer.types.spine(ty);
+        let Ty::Constructor(TypeConstructor { definition: Some(definition), .. }) =
+            self.infer.types[constructor]
+        else {
+            return None;
+        };
+        if definition.module != self.name {
+            return None;
+        }
+        let data = match &self.tree.items[definition.item] {
+            Item::Data(data) => DataType {
+                name: data.name,
+                newtype: false,
+                variables: &data.variables,
+                constructors: data.constructors.iter().map(|c| c.fields.as_slice()).collect(),
+            },
+            Item::Newtype(newtype) => DataType {
+                name: newtype.name,
+                newtype: true,
+                variables: &newtype.variables,
+                constructors: vec![newtype.constructor.fields.as_slice()],
+            },
+            _ => return None,
+        };
+        Some((data, arguments))
+    }
+
+    /// The fields of every constructor of `data`, with its variables
+    /// replaced by `arguments`, or left as they are where it has more
+    /// variables than arguments.
+    fn fields(&mut self, data: &DataType, arguments: &[TyId]) -> Vec<TyId> {
+        let bindings = self.bindings(data.variables, arguments);
+        let mut fields = vec![];
+        for &field in data.constructors.iter().flat_map(|fields| fields.iter()) {
+            let field = self.infer.convert_tree_type(self.tree, self.name, field);
+            fields.push(self.infer.substitute(field, &bindings));
+        }
+        fields
+    }
+
+    fn bindings(&self, variables: &[TypeVariable], arguments: &[TyId]) -> Vec<(Name, TyId)> {
+        variables.iter().map(|variable| variable.name).zip(arguments.iter().copied()).collect()
+    }
+
+    /// Wants an instance of `class` for a field, where records are compared
+    /// field by field, like the compiler.
+    fn want_fields(&mut self, class: TypeConstructor, field: TyId, origin: Origin) {
+        let field = self.infer.prune(field);
+        if let Ty::Application(record, row) = self.infer.types[field] {
+            if self.infer.types.is_record(record) {
+                if let Ty::Row(fields, None) = self.infer.types[row].clone() {
+                    for (_, field) in fields {
+                        self.want_fields(class, field, origin);
+                    }
+                    return;
+                }
+            }
+        }
+        let constraint = Constraint { class, arguments: vec![field] };
+        self.infer.wanted.push((constraint, origin));
+    }
+
+    /// Whether `variable` only appears in `ty` where a `Functor` can map
+    /// over it: as the last argument of a type constructor, as the result of
+    /// a function, or as the field of a record.
+    fn covariant(&mut self, ty: TyId, variable: Name) -> bool {
+        if !self.mentions(ty, variable) {
+            return true;
+        }
+        if let Some((argument, result)) = self.infer.types.as_function(ty) {
+            return !self.mentions(argument, variable) && self.covariant(result, variable);
+        }
+        match self.infer.types[ty].clone() {
+            Ty::Variable(name) => name == variable,
+            Ty::Application(record, row) if self.infer.types.is_record(record) => {
+                match self.infer.types[row].clone() {
+                    Ty::Row(fields, tail) => {
+                        let tail = tail.is_none_or(|tail| !self.mentions(tail, variable));
+                        tail && fields.into_iter().all(|(_, field)| self.covariant(field, variable))
+                    }
+                    _ => false,
+                }
+            }
+            Ty::Application(function, argument) => {
+                !self.mentions(function, variable) && self.covariant(argument, variable)
+            }
+            _ => false,
+        }
+    }
+
+    fn mentions(&mut self, ty: TyId, variable: Name) -> bool {
+        let ty = self.infer.prune(ty);
+        match self.infer.types[ty].clone() {
+            Ty::Variable(name) => name == variable,
+            Ty::Application(function, argument) => {
+                self.mentions(function, variable) || self.mentions(argument, variable)
+            }
+            Ty::Forall(name, _, body) => name != variable && self.mentions(body, variable),
+            Ty::Row(fields, tail) => {
+                fields.into_iter().any(|(_, field)| self.mentions(field, variable))
+                    || tail.is_some_and(|tail| self.mentions(tail, variable))
+            }
+            Ty::Constrained(constraint, body) => {
+                constraint.arguments.iter().any(|&argument| self.mentions(argument, variable))
+                    || self.mentions(body, variable)
+            }
+            Ty::Error | Ty::Unknown(_) | Ty::Skolem(..) | Ty::Constructor(_) | Ty::Symbol(_) => {
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{item_tree::ItemTree, lower_module, name::ModuleName};
+    use resolution::{body::ModuleScope, imports::Imports, interface::Interface};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_derives;
+    use crate::Workspace;
+
+    struct Modules(Vec<(ModuleName, ItemTree, ModuleScope)>);
+
+    impl Workspace for Modules {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            self.0.iter().find(|(name, ..)| *name == module).map(|(_, tree, _)| tree)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            self.0.iter().find(|(name, ..)| *name == module).map(|(.., scope)| scope)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            self.0.iter().map(|(name, ..)| *name).collect()
+        }
+    }
+
+    fn parse(source: &str) -> ast::Module {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        ast::Module::cast(node).unwrap()
+    }
+
+    #[test]
+    fn check_derived_instances() {
+        let prelude = [
+            "module Data.Eq where\n\nclass Eq a where\n  eq :: a -> a -> Boolean\n\ninstance Eq Int where\n  eq _ _ = true\n",
+            "module Data.Functor where\n\nclass Functor f where\n  map :: forall a b. (a -> b) -> f a -> f b\n\ninstance Functor Array where\n  map _ xs = xs\n",
+            "module Data.Newtype where\n\nclass Newtype t a | t -> a\n",
+            "module Data.Show where\n\nclass Show a\n",
+        ];
+        let source = "module Main where\n\nimport Data.Eq\nimport Data.Functor\nimport Data.Newtype\nimport Data.Show\n\ndata Pair a = Pair a { count :: Int }\n\nderive instance Eq a => Eq (Pair a)\n\ndata Compare = Compare (Int -> Int)\n\nderive instance Eq Compare\nderive instance Show Compare\nderive instance Eq String\n\ndata Box a = Box (Array a) a\n\nderive instance Functor Box\n\ndata Predicate a = Predicate (a -> Boolean)\n\nderive instance Functor Predicate\n\nnewtype Name = Name String\n\nderive instance Newtype Name _\nderive instance Newtype Compare _\n\nnewtype Wrap a = Wrap (Array a)\n\nderive newtype instance Functor Wrap\nderive newtype instance Eq (Wrap Int)\nderive newtype instance Eq (Pair Int)\n";
+
+        let roots: Vec<_> = prelude.iter().chain([&source]).map(|source| parse(source)).collect();
+        let trees: Vec<_> = roots.iter().map(ItemTree::lower).collect();
+        let interfaces: Vec<_> = trees.iter().map(Interface::local).collect();
+        let names: Vec<_> = trees.iter().map(|tree| tree.name.unwrap()).collect();
+        let modules = trees.into_iter().map(|tree| {
+            let imports = Imports::resolve(&tree, |module| {
+                let index = names.iter().position(|&name| name == module)?;
+                Some(&interfaces[index])
+            });
+            let scope = ModuleScope::new(&tree, imports);
+            (tree.name.unwrap(), tree, scope)
+        });
+        let workspace = Modules(modules.collect());
+
+        let main = lower_module(roots.last().unwrap());
+        let derives = check_derives(&workspace, ModuleName::new("Main"), &main);
+        let messages: Vec<_> = derives
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.message(&derives.types))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "no instance found for Eq (Int -> Int)",
+                "cannot derive a type class instance for Show Compare, since instances of this type class are not derivable",
+                "cannot derive the type class instance Eq String, because the type String is not of the required form T a_1 ... a_n, where T is a type constructor defined in the same module",
+                "one or more type variables are in positions that prevent Functor from being derived",
+                "cannot derive an instance of the Newtype class for non-newtype Compare",
+                "no instance found for Eq (Array Int)",
+                "cannot derive newtype instance for Eq (Pair Int), make sure this is a newtype",
+            ]
+        );
+    }
+}
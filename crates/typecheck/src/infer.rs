@@ -0,0 +1,2113 @@
+//! Bidirectional type inference over the bodies of a module.
+//!
+//! Expressions are checked against a type where one is known, such as the
+//! signature of a value, and inferred otherwise. Unknowns are solved through
+//! unification, and the unknowns of unannotated `let` bindings are
+//! generalized by level, such that bindings are polymorphic in their body.
+
+use std::rc::Rc;
+
+use lowering::{
+    arena::{Arena, ArenaMap},
+    hir::{
+        Binder, BinderId, Body, BodyId, Declaration, Expr, ExprId, Guard, GuardedBranch,
+        GuardedExpr, InstanceMember, LetBinding, Literal, Module, Operator, Row, Type, TypeId,
+    },
+    item_tree::{Item, ItemTree},
+    name::{ModuleName, Name, QualifiedName},
+};
+use resolution::{
+    body::{BodyResolution, ModuleScope, Resolution},
+    fixity::{Chains, Fixities, OperatorTree},
+    interface::{Definition, Namespace},
+    types::TypeResolution,
+};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    solve::Instances,
+    ty::{
+        Constraint, Ty, TyId, TypeConstructor, TypeDiff, Types, Unknown, UnknownNames, Visibility,
+    },
+    Workspace,
+};
+
+/// The syntax a type diagnostic is reported at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Expr(ExprId),
+    Binder(BinderId),
+    /// A type in the module, for kind errors.
+    Type(TypeId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDiagnostic {
+    Mismatch {
+        origin: Origin,
+        expected: TyId,
+        actual: TyId,
+    },
+    /// An unknown that would have to contain itself.
+    InfiniteType {
+        origin: Origin,
+        ty: TyId,
+    },
+    /// A constraint that no instance or given constraint satisfies,
+    /// reported where the name that introduced it is used.
+    NoInstance {
+        origin: Origin,
+        constraint: Constraint,
+    },
+    /// A constraint that instances from more than one chain match.
+    Overlapping {
+        origin: Origin,
+        constraint: Constraint,
+    },
+    /// A typed hole, with the values in scope that it could be replaced by,
+    /// such that a code action can offer to fill it in. Locals come first,
+    /// innermost first, followed by the module's own declarations and then
+    /// its imports.
+    Hole {
+        origin: Origin,
+        name: Name,
+        ty: TyId,
+        candidates: Vec<QualifiedName>,
+    },
+    /// A type argument applied to an expression whose type has no visible
+    /// `forall` left to instantiate with it.
+    NoVisibleVariable {
+        origin: Origin,
+        ty: TyId,
+    },
+    /// A type variable that was replaced by a skolem while checking an
+    /// expression against a `forall`, which an unknown from outside of the
+    /// expression was solved to.
+    EscapedSkolem {
+        origin: Origin,
+        name: Name,
+    },
+    /// A derived instance of a class that can't be derived.
+    NotDerivable {
+        origin: Origin,
+        constraint: Constraint,
+    },
+    /// A derived instance for a type that isn't a data type or newtype of
+    /// the module it's derived in.
+    ExpectedTypeConstructor {
+        origin: Origin,
+        constraint: Constraint,
+        ty: TyId,
+    },
+    /// An instance derived through a newtype for a type that isn't one, or
+    /// whose wrapped type can't be applied to the class.
+    InvalidNewtypeInstance {
+        origin: Origin,
+        constraint: Constraint,
+    },
+    /// A derived `Newtype` instance for a data type.
+    NewtypeForData {
+        origin: Origin,
+        name: Name,
+    },
+    /// A derived `Functor` instance for a type whose last variable appears
+    /// somewhere that it can't be mapped over.
+    InvalidConstructorArgument {
+        origin: Origin,
+        class: Name,
+    },
+    /// Variables in an argument of an instance head that the arguments
+    /// determining it through a functional dependency don't mention.
+    Uncovered {
+        origin: Origin,
+        class: Name,
+        variables: Vec<Name>,
+    },
+}
+
+impl TypeDiagnostic {
+    /// A stable name for the kind of diagnostic, which editors show as its
+    /// code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeDiagnostic::Mismatch { .. } => "type-mismatch",
+            TypeDiagnostic::InfiniteType { .. } => "infinite-type",
+            TypeDiagnostic::NoInstance { .. } => "no-instance",
+            TypeDiagnostic::Overlapping { .. } => "overlapping-instances",
+            TypeDiagnostic::Hole { .. } => "typed-hole",
+            TypeDiagnostic::NoVisibleVariable { .. } => "no-visible-type-variable",
+            TypeDiagnostic::EscapedSkolem { .. } => "escaped-skolem",
+            TypeDiagnostic::NotDerivable { .. } => "not-derivable",
+            TypeDiagnostic::ExpectedTypeConstructor { .. } => "expected-type-constructor",
+            TypeDiagnostic::InvalidNewtypeInstance { .. } => "invalid-newtype-instance",
+            TypeDiagnostic::NewtypeForData { .. } => "newtype-for-data",
+            TypeDiagnostic::InvalidConstructorArgument { .. } => "invalid-constructor-argument",
+            TypeDiagnostic::Uncovered { .. } => "uncovered-variables",
+        }
+    }
+
+    /// What went wrong, where a mismatch shows both types on lines of their
+    /// own with the parts where they differ underlined.
+    pub fn message(&self, types: &Types) -> String {
+        let unknowns = UnknownNames::default();
+        let describe = |ty| types.describe(ty, &unknowns);
+        let describe_constraint = |constraint| types.describe_constraint(constraint, &unknowns);
+        match self {
+            TypeDiagnostic::Mismatch { expected, actual, .. } => {
+                let TypeDiff { expected, actual } = types.diff(*expected, *actual);
+                let indent = " ".repeat("but found ".len());
+                format!(
+                    "expected  {}\nbut found {}",
+                    expected.underlined(&indent),
+                    actual.underlined(&indent)
+                )
+            }
+            TypeDiagnostic::InfiniteType { ty, .. } => {
+                format!("the type {} would be infinite", describe(*ty))
+            }
+            TypeDiagnostic::NoInstance { constraint, .. } => {
+                format!("no instance found for {}", describe_constraint(constraint))
+            }
+            TypeDiagnostic::Overlapping { constraint, .. } => {
+                format!("overlapping instances found for {}", describe_constraint(constraint))
+            }
+            TypeDiagnostic::Hole { name, ty, candidates, .. } => {
+                let ty = describe(*ty);
+                if candidates.is_empty() {
+                    return format!("hole ?{name} has the type {ty}");
+                }
+                let candidates: Vec<_> = candidates.iter().map(ToString::to_string).collect();
+                format!("hole ?{name} has the type {ty}, and could be {}", candidates.join(", "))
+            }
+            TypeDiagnostic::NoVisibleVariable { ty, .. } => {
+                format!("no visible type variable to apply in {}", describe(*ty))
+            }
+            TypeDiagnostic::EscapedSkolem { name, .. } => {
+                format!("the type variable {name} escapes its scope")
+            }
+            TypeDiagnostic::NotDerivable { constraint, .. } => format!(
+                "cannot derive a type class instance for {}, since instances of this type class are not derivable",
+                describe_constraint(constraint)
+            ),
+            TypeDiagnostic::ExpectedTypeConstructor { constraint, ty, .. } => format!(
+                "cannot derive the type class instance {}, because the type {} is not of the required form T a_1 ... a_n, where T is a type constructor defined in the same module",
+                describe_constraint(constraint),
+                describe(*ty)
+            ),
+            TypeDiagnostic::InvalidNewtypeInstance { constraint, .. } => format!(
+                "cannot derive newtype instance for {}, make sure this is a newtype",
+                describe_constraint(constraint)
+            ),
+            TypeDiagnostic::NewtypeForData { name, .. } => {
+                format!("cannot derive an instance of the Newtype class for non-newtype {name}")
+            }
+            TypeDiagnostic::InvalidConstructorArgument { class, .. } => format!(
+                "one or more type variables are in positions that prevent {class} from being derived"
+            ),
+            TypeDiagnostic::Uncovered { class, variables, .. } => {
+                let variables: Vec<_> = variables.iter().map(|name| name.as_str()).collect();
+                format!(
+                    "{} isn't determined by the functional dependencies of {class}",
+                    variables.join(", ")
+                )
+            }
+        }
+    }
+
+    pub fn origin(&self) -> Origin {
+        match self {
+            TypeDiagnostic::Mismatch { origin, .. }
+            | TypeDiagnostic::InfiniteType { origin, .. }
+            | TypeDiagnostic::NoInstance { origin, .. }
+            | TypeDiagnostic::Overlapping { origin, .. }
+            | TypeDiagnostic::Hole { origin, .. }
+            | TypeDiagnostic::NoVisibleVariable { origin, .. }
+            | TypeDiagnostic::EscapedSkolem { origin, .. }
+            | TypeDiagnostic::Uncovered { origin, .. }
+            | TypeDiagnostic::NotDerivable { origin, .. }
+            | TypeDiagnostic::ExpectedTypeConstructor { origin, .. }
+            | TypeDiagnostic::InvalidNewtypeInstance { origin, .. }
+            | TypeDiagnostic::NewtypeForData { origin, .. }
+            | TypeDiagnostic::InvalidConstructorArgument { origin, .. } => *origin,
+        }
+    }
+}
+
+/// The types of the expressions and binders in a body, with every solved
+/// unknown substituted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BodyTypes {
+    pub types: Types,
+    /// The type of the value the body belongs to.
+    pub ty: Option<TyId>,
+    exprs: ArenaMap<ExprId, TyId>,
+    /// The types that expressions whose own type is inferred were checked
+    /// against.
+    expected: ArenaMap<ExprId, TyId>,
+    binders: ArenaMap<BinderId, TyId>,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl BodyTypes {
+    pub fn expr(&self, expr: ExprId) -> Option<TyId> {
+        self.exprs.get(expr).copied()
+    }
+
+    /// The type that `expr` was checked against, which is known even where
+    /// the type of the expression itself isn't, such as when it applies a
+    /// name that isn't in scope.
+    pub fn expected(&self, expr: ExprId) -> Option<TyId> {
+        self.expected.get(expr).copied()
+    }
+
+    pub fn binder(&self, binder: BinderId) -> Option<TyId> {
+        self.binders.get(binder).copied()
+    }
+
+    pub fn diagnostics(&self) -> &[TypeDiagnostic] {
+        &self.diagnostics
+    }
+}
+
+/// Checks the body of every value in `module`, including instance members.
+pub fn check_module(
+    workspace: &dyn Workspace,
+    name: ModuleName,
+    module: &Module,
+) -> ArenaMap<BodyId, BodyTypes> {
+    let mut bodies = ArenaMap::default();
+    let Some(scope) = workspace.scope(name) else { return bodies };
+    let instances = Instances::new(workspace);
+    for (id, _) in module.bodies.iter() {
+        bodies.insert(id, check_body(workspace, &instances, module, scope, id));
+    }
+    bodies
+}
+
+fn check_body(
+    workspace: &dyn Workspace,
+    instances: &Instances,
+    module: &Module,
+    scope: &ModuleScope,
+    id: BodyId,
+) -> BodyTypes {
+    let (infer, signature) = Infer::body(workspace, instances, module, scope, id);
+    infer.check_body(signature)
+}
+
+pub struct Infer<'a> {
+    pub(crate) workspace: &'a dyn Workspace,
+    pub(crate) instances: &'a Instances,
+    pub(crate) body: &'a Body,
+    scope: &'a ModuleScope,
+    pub(crate) resolution: BodyResolution,
+    chains: Chains,
+    /// The types of annotations in the body, which are lowered into the
+    /// types of the module.
+    pub(crate) module_types: (&'a Arena<Type>, Rc<TypeResolution>),
+    /// The type resolutions of the item trees of other modules, to convert
+    /// the signatures of their values.
+    tree_types: FxHashMap<ModuleName, Rc<TypeResolution>>,
+
+    pub(crate) types: Types,
+    /// The solution and level of each unknown.
+    pub(crate) unknowns: Vec<(Option<TyId>, u32)>,
+    pub(crate) level: u32,
+    skolems: u32,
+    /// The skolems of the `forall`s that the expression being checked is
+    /// checked against, innermost last, which annotations within it can
+    /// refer to by name.
+    scoped: Vec<(Name, TyId)>,
+    /// The number of unknowns for wildcards in the signature of the body,
+    /// which are allowed to be solved to its skolems.
+    wildcards: usize,
+    locals: FxHashMap<BinderId, TyId>,
+    exprs: ArenaMap<ExprId, TyId>,
+    expected: ArenaMap<ExprId, TyId>,
+    binders: ArenaMap<BinderId, TyId>,
+    holes: Vec<ExprId>,
+    pub(crate) diagnostics: Vec<TypeDiagnostic>,
+    /// Constraints to be solved, and the constraints that are known to hold
+    /// in the expression being checked.
+    pub(crate) wanted: Vec<(Constraint, Origin)>,
+    pub(crate) givens: Vec<Constraint>,
+}
+
+impl<'a> Infer<'a> {
+    pub(crate) fn new(
+        workspace: &'a dyn Workspace,
+        instances: &'a Instances,
+        module: &'a Module,
+        body: &'a Body,
+        scope: &'a ModuleScope,
+        resolution: BodyResolution,
+        chains: Chains,
+    ) -> Infer<'a> {
+        let module_resolution = Rc::new(TypeResolution::new(&module.types, scope));
+        Infer {
+            workspace,
+            instances,
+            body,
+            scope,
+            resolution,
+            chains,
+            module_types: (&module.types, module_resolution),
+            tree_types: FxHashMap::default(),
+            types: Types::default(),
+            unknowns: vec![],
+            level: 0,
+            skolems: 0,
+            scoped: vec![],
+            wildcards: 0,
+            locals: FxHashMap::default(),
+            exprs: ArenaMap::default(),
+            expected: ArenaMap::default(),
+            binders: ArenaMap::default(),
+            holes: vec![],
+            diagnostics: vec![],
+            wanted: vec![],
+            givens: vec![],
+        }
+    }
+
+    /// Prepares to check a body, along with the signature it's checked
+    /// against, if it has one.
+    pub(crate) fn body(
+        workspace: &'a dyn Workspace,
+        instances: &'a Instances,
+        module: &'a Module,
+        scope: &'a ModuleScope,
+        id: BodyId,
+    ) -> (Infer<'a>, Option<TyId>) {
+        let body = &module.bodies[id];
+        let resolution = BodyResolution::new(body, scope);
+        let fixities = Fixities::new(scope, |module| workspace.item_tree(module));
+        let chains = Chains::body(body, &resolution, &fixities);
+        let mut infer = Infer::new(workspace, instances, module, body, scope, resolution, chains);
+
+        let signature = module.declarations.iter().find_map(|(_, declaration)| match declaration {
+            Declaration::Value(value) if value.body == id => {
+                let &definition = scope.declarations.names.values.get(&value.name)?;
+                Some(infer.value_signature(definition))
+            }
+            Declaration::InstanceChain(chain) => chain.iter().find_map(|instance| {
+                instance.members.iter().find_map(|member| match member {
+                    InstanceMember::Value(value) if value.body == id => {
+                        Some(infer.member_signature(scope, &instance.head, value.name))
+                    }
+                    _ => None,
+                })
+            }),
+            _ => None,
+        });
+        (infer, signature.flatten())
+    }
+
+    pub(crate) fn check_body(mut self, signature: Option<TyId>) -> BodyTypes {
+        self.wildcards = self.unknowns.len();
+        let ty = match signature {
+            Some(signature) => {
+                self.check_guarded(&self.body.guarded, signature);
+                signature
+            }
+            None => {
+                self.level += 1;
+                let ty = self.infer_guarded(&self.body.guarded);
+                self.level -= 1;
+                self.generalize(ty, 0)
+            }
+        };
+        self.finish(Some(ty))
+    }
+
+    fn finish(mut self, ty: Option<TyId>) -> BodyTypes {
+        self.solve_constraints(0);
+        for (constraint, origin) in std::mem::take(&mut self.wanted) {
+            self.diagnostics.push(TypeDiagnostic::NoInstance { origin, constraint });
+        }
+
+        let mut exprs = ArenaMap::default();
+        for (id, &ty) in self.exprs.clone().iter() {
+            exprs.insert(id, self.zonk(ty));
+        }
+        let mut expected = ArenaMap::default();
+        for (id, &ty) in self.expected.clone().iter() {
+            expected.insert(id, self.zonk(ty));
+        }
+        let mut binders = ArenaMap::default();
+        for (id, &ty) in self.binders.clone().iter() {
+            binders.insert(id, self.zonk(ty));
+        }
+        self.report_holes();
+        let diagnostics = self.take_diagnostics();
+        let ty = ty.map(|ty| self.zonk(ty));
+        exprs.shrink_to_fit();
+        expected.shrink_to_fit();
+        binders.shrink_to_fit();
+        BodyTypes { types: self.types, ty, exprs, expected, binders, diagnostics }
+    }
+
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<TypeDiagnostic> {
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+        for diagnostic in &mut diagnostics {
+            match diagnostic {
+                TypeDiagnostic::Mismatch { expected, actual, .. } => {
+                    *expected = self.zonk(*expected);
+                    *actual = self.zonk(*actual);
+                }
+                TypeDiagnostic::InfiniteType { ty, .. }
+                | TypeDiagnostic::Hole { ty, .. }
+                | TypeDiagnostic::NoVisibleVariable { ty, .. } => {
+                    *ty = self.zonk(*ty);
+                }
+                TypeDiagnostic::NoInstance { constraint, .. }
+                | TypeDiagnostic::Overlapping { constraint, .. }
+                | TypeDiagnostic::NotDerivable { constraint, .. }
+                | TypeDiagnostic::InvalidNewtypeInstance { constraint, .. } => {
+                    *constraint = self.zonk_constraint(constraint.clone());
+                }
+                TypeDiagnostic::ExpectedTypeConstructor { constraint, ty, .. } => {
+                    *constraint = self.zonk_constraint(constraint.clone());
+                    *ty = self.zonk(*ty);
+                }
+                TypeDiagnostic::EscapedSkolem { .. }
+                | TypeDiagnostic::Uncovered { .. }
+                | TypeDiagnostic::NewtypeForData { .. }
+                | TypeDiagnostic::InvalidConstructorArgument { .. } => {}
+            }
+        }
+        diagnostics
+    }
+
+    // Holes
+
+    /// Reports each hole once everything else in the body is solved, with
+    /// the values in scope whose type unifies with the type of the hole.
+    fn report_holes(&mut self) {
+        let scope = self.scope;
+        let mut globals: Vec<_> = scope
+            .declarations
+            .names
+            .values
+            .iter()
+            .map(|(&name, &definition)| (QualifiedName { qualifier: None, name }, definition))
+            .collect();
+        globals.sort_by_key(|(name, _)| name.name.as_str());
+        let mut imported = vec![];
+        let qualified =
+            scope.imports.qualified.iter().map(|(&module, names)| (Some(module), names));
+        for (qualifier, names) in
+            std::iter::once((None, &scope.imports.unqualified)).chain(qualified)
+        {
+            for (&name, definitions) in &names.values {
+                let shadowed =
+                    qualifier.is_none() && scope.declarations.names.values.contains_key(&name);
+                if let ([definition], false) = (definitions.as_slice(), shadowed) {
+                    imported.push((QualifiedName { qualifier, name }, *definition));
+                }
+            }
+        }
+        imported.sort_by_key(|(name, _)| name.to_string());
+        globals.extend(imported);
+
+        for hole in std::mem::take(&mut self.holes) {
+            let Expr::Hole(name) = self.body.exprs[hole] else { continue };
+            let Some(ty) = self.exprs.get(hole).copied() else { continue };
+            let ty = self.zonk(ty);
+            let mut candidates = vec![];
+            let mut locals: Vec<_> = vec![];
+            for entry in self.resolution.scopes().visible(hole) {
+                // Shadowed names come after the names shadowing them, and
+                // names generated by lowering can't be written.
+                let generated = entry.name.as_str().starts_with('$');
+                if !generated && !locals.iter().any(|&(name, _)| name == entry.name) {
+                    locals.push((entry.name, entry.binder));
+                }
+            }
+            for (name, binder) in locals {
+                let Some(&local) = self.locals.get(&binder) else { continue };
+                if self.fits(local, ty, hole) {
+                    candidates.push(QualifiedName { qualifier: None, name });
+                }
+            }
+            for &(name, definition) in &globals {
+                let global = self.definition_type(definition);
+                if !matches!(self.types[global], Ty::Error) && self.fits(global, ty, hole) {
+                    candidates.push(name);
+                }
+            }
+            let origin = Origin::Expr(hole);
+            self.diagnostics.push(TypeDiagnostic::Hole { origin, name, ty, candidates });
+        }
+    }
+
+    /// Whether a value of type `candidate` could replace the hole, without
+    /// solving any unknowns.
+    fn fits(&mut self, candidate: TyId, hole: TyId, id: ExprId) -> bool {
+        let (unknowns, wanted) = (self.unknowns.clone(), self.wanted.len());
+        let candidate = self.instantiate(candidate, Origin::Expr(id));
+        let fits = self.unify_types(candidate, hole).is_ok();
+        self.unknowns = unknowns;
+        self.wanted.truncate(wanted);
+        fits
+    }
+
+    // Unknowns and substitution
+
+    pub(crate) fn fresh(&mut self) -> TyId {
+        let unknown = self.unknowns.len() as Unknown;
+        self.unknowns.push((None, self.level));
+        self.types.alloc(Ty::Unknown(unknown))
+    }
+
+    /// Follows solved unknowns until a type that isn't one.
+    pub(crate) fn prune(&mut self, ty: TyId) -> TyId {
+        let Ty::Unknown(unknown) = self.types[ty] else { return ty };
+        match self.unknowns[unknown as usize].0 {
+            Some(solution) => {
+                let solution = self.prune(solution);
+                self.unknowns[unknown as usize].0 = Some(solution);
+                solution
+            }
+            None => ty,
+        }
+    }
+
+    /// Substitutes every solved unknown in `ty`.
+    pub(crate) fn zonk(&mut self, ty: TyId) -> TyId {
+        let ty = self.prune(ty);
+        let zonked = match self.types[ty].clone() {
+            Ty::Application(function, argument) => {
+                let (function_, argument_) = (self.zonk(function), self.zonk(argument));
+                if (function_, argument_) == (function, argument) {
+                    ty
+                } else {
+                    self.types.application(function_, argument_)
+                }
+            }
+            Ty::Forall(name, visibility, body) => {
+                let body_ = self.zonk(body);
+                if body_ == body {
+                    ty
+                } else {
+                    self.types.alloc(Ty::Forall(name, visibility, body_))
+                }
+            }
+            // Tails solved to rows are merged into the row.
+            Ty::Row(fields, tail) => {
+                let mut fields: Vec<_> =
+                    fields.into_iter().map(|(label, ty)| (label, self.zonk(ty))).collect();
+                let mut tail = tail.map(|tail| self.zonk(tail));
+                if let Some(Ty::Row(more, rest)) = tail.map(|tail| &self.types[tail]) {
+                    fields.extend(more.iter().copied());
+                    tail = *rest;
+                }
+                self.types.alloc(Ty::Row(fields, tail))
+            }
+            Ty::Constrained(constraint, body) => {
+                let constraint = self.zonk_constraint(constraint);
+                let body = self.zonk(body);
+                self.types.alloc(Ty::Constrained(constraint, body))
+            }
+            _ => ty,
+        };
+        if let Some(synonym) = self.types.synonym(ty).filter(|_| zonked != ty) {
+            let synonym = self.zonk(synonym);
+            self.types.expanded_from(zonked, synonym);
+        }
+        zonked
+    }
+
+    pub(crate) fn zonk_constraint(&mut self, constraint: Constraint) -> Constraint {
+        let arguments = constraint.arguments.iter().map(|&argument| self.zonk(argument));
+        Constraint { class: constraint.class, arguments: arguments.collect() }
+    }
+
+    /// Replaces the variables in `ty` with their bindings at once, such that
+    /// replacements are never substituted into themselves. Variables bound
+    /// by a `forall` within `ty` are left alone.
+    pub(crate) fn substitute(&mut self, ty: TyId, bindings: &[(Name, TyId)]) -> TyId {
+        let ty = self.prune(ty);
+        let substituted = match self.types[ty].clone() {
+            Ty::Variable(variable) => match bindings.iter().find(|&&(name, _)| name == variable) {
+                Some(&(_, replacement)) => replacement,
+                None => ty,
+            },
+            Ty::Application(function, argument) => {
+                let function = self.substitute(function, bindings);
+                let argument = self.substitute(argument, bindings);
+                self.types.application(function, argument)
+            }
+            Ty::Forall(variable, visibility, body) => {
+                let bindings: Vec<_> =
+                    bindings.iter().copied().filter(|&(name, _)| name != variable).collect();
+                let body = self.substitute(body, &bindings);
+                self.types.alloc(Ty::Forall(variable, visibility, body))
+            }
+            Ty::Row(fields, tail) => {
+                let fields = fields
+                    .into_iter()
+                    .map(|(label, ty)| (label, self.substitute(ty, bindings)))
+                    .collect();
+                let tail = tail.map(|tail| self.substitute(tail, bindings));
+                self.types.alloc(Ty::Row(fields, tail))
+            }
+            Ty::Constrained(constraint, body) => {
+                let constraint = self.substitute_constraint(&constraint, bindings);
+                let body = self.substitute(body, bindings);
+                self.types.alloc(Ty::Constrained(constraint, body))
+            }
+            _ => ty,
+        };
+        if let Some(synonym) = self.types.synonym(ty).filter(|_| substituted != ty) {
+            let synonym = self.substitute(synonym, bindings);
+            self.types.expanded_from(substituted, synonym);
+        }
+        substituted
+    }
+
+    pub(crate) fn substitute_constraint(
+        &mut self,
+        constraint: &Constraint,
+        bindings: &[(Name, TyId)],
+    ) -> Constraint {
+        let arguments = constraint
+            .arguments
+            .iter()
+            .map(|&argument| self.substitute(argument, bindings))
+            .collect();
+        Constraint { class: constraint.class, arguments }
+    }
+
+    /// Replaces the variables of the outer `forall`s of `ty` with unknowns,
+    /// and wants the constraints under them to be solved at `origin`.
+    pub(crate) fn instantiate(&mut self, mut ty: TyId, origin: Origin) -> TyId {
+        loop {
+            ty = self.prune(ty);
+            match self.types[ty].clone() {
+                Ty::Forall(name, _, body) => {
+                    let unknown = self.fresh();
+                    ty = self.substitute(body, &[(name, unknown)]);
+                }
+                Ty::Constrained(constraint, body) => {
+                    self.wanted.push((constraint, origin));
+                    ty = body;
+                }
+                _ => return ty,
+            }
+        }
+    }
+
+    /// Replaces the variables of the outer `forall`s of `ty` with skolems,
+    /// and gives the constraints under them. The skolems are scoped until
+    /// the caller truncates [`Infer::scoped`].
+    pub(crate) fn skolemize(&mut self, mut ty: TyId) -> TyId {
+        loop {
+            ty = self.prune(ty);
+            match self.types[ty].clone() {
+                Ty::Forall(name, _, body) => {
+                    self.skolems += 1;
+                    let skolem = self.types.alloc(Ty::Skolem(name, self.skolems));
+                    self.scoped.push((name, skolem));
+                    ty = self.substitute(body, &[(name, skolem)]);
+                }
+                Ty::Constrained(constraint, body) => {
+                    self.give(constraint);
+                    ty = body;
+                }
+                _ => return ty,
+            }
+        }
+    }
+
+    /// Quantifies over the unknowns in `ty` that were created at a deeper
+    /// level than the current one, and so can't be referred to from outside
+    /// of the binding being generalized. Constraints wanted since `wanted`
+    /// that can't be solved yet and mention those unknowns are added to the
+    /// type.
+    pub(crate) fn generalize(&mut self, ty: TyId, wanted: usize) -> TyId {
+        self.solve_constraints(wanted);
+        let ty = self.zonk(ty);
+        let mut unknowns = vec![];
+        self.collect_unknowns(ty, &mut unknowns);
+        unknowns.retain(|&unknown| self.unknowns[unknown as usize].1 > self.level);
+
+        let mut constraints = vec![];
+        let mut index = wanted;
+        while index < self.wanted.len() {
+            let mut mentioned = vec![];
+            for argument in self.wanted[index].0.arguments.clone() {
+                self.collect_unknowns(argument, &mut mentioned);
+            }
+            if mentioned.iter().any(|unknown| unknowns.contains(unknown)) {
+                constraints.push(self.wanted.remove(index).0);
+            } else {
+                index += 1;
+            }
+        }
+
+        let mut names = vec![];
+        for unknown in unknowns {
+            let name = Name::new(&format!("t{}", names.len()));
+            let variable = self.types.alloc(Ty::Variable(name));
+            self.unknowns[unknown as usize].0 = Some(variable);
+            names.push(name);
+        }
+        let mut ty = self.zonk(ty);
+        for constraint in constraints.into_iter().rev() {
+            let constraint = self.zonk_constraint(constraint);
+            ty = self.types.alloc(Ty::Constrained(constraint, ty));
+        }
+        for name in names.into_iter().rev() {
+            ty = self.types.alloc(Ty::Forall(name, Visibility::Invisible, ty));
+        }
+        ty
+    }
+
+    pub(crate) fn collect_unknowns(&mut self, ty: TyId, unknowns: &mut Vec<Unknown>) {
+        let ty = self.prune(ty);
+        match self.types[ty].clone() {
+            Ty::Unknown(unknown) if !unknowns.contains(&unknown) => unknowns.push(unknown),
+            Ty::Application(function, argument) => {
+                self.collect_unknowns(function, unknowns);
+                self.collect_unknowns(argument, unknowns);
+            }
+            Ty::Forall(_, _, body) => self.collect_unknowns(body, unknowns),
+            Ty::Row(fields, tail) => {
+                for ty in fields.into_iter().map(|(_, ty)| ty).chain(tail) {
+                    self.collect_unknowns(ty, unknowns);
+                }
+            }
+            Ty::Constrained(constraint, body) => {
+                for argument in constraint.arguments {
+                    self.collect_unknowns(argument, unknowns);
+                }
+                self.collect_unknowns(body, unknowns);
+            }
+            _ => {}
+        }
+    }
+
+    // Unification
+
+    /// Unifies the type of `origin` with the type it's expected to have,
+    /// reporting a mismatch if they don't unify.
+    pub(crate) fn unify(&mut self, actual: TyId, expected: TyId, origin: Origin) {
+        match self.unify_types(actual, expected) {
+            Ok(()) => {}
+            Err(UnifyError::Mismatch) => {
+                self.diagnostics.push(TypeDiagnostic::Mismatch { origin, expected, actual });
+            }
+            Err(UnifyError::Infinite(ty)) => {
+                self.diagnostics.push(TypeDiagnostic::InfiniteType { origin, ty });
+            }
+        }
+    }
+
+    pub(crate) fn unify_types(&mut self, left: TyId, right: TyId) -> Result<(), UnifyError> {
+        let (left, right) = (self.prune(left), self.prune(right));
+        if left == right {
+            return Ok(());
+        }
+        match (self.types[left].clone(), self.types[right].clone()) {
+            (Ty::Error, _) | (_, Ty::Error) => Ok(()),
+            (Ty::Unknown(unknown), _) => self.solve(unknown, right),
+            (_, Ty::Unknown(unknown)) => self.solve(unknown, left),
+            (Ty::Skolem(_, left), Ty::Skolem(_, right)) if left == right => Ok(()),
+            (Ty::Variable(left), Ty::Variable(right)) if left == right => Ok(()),
+            (Ty::Constructor(left), Ty::Constructor(right)) if left == right => Ok(()),
+            (Ty::Symbol(left), Ty::Symbol(right)) if left == right => Ok(()),
+            (
+                Ty::Application(left_function, left_argument),
+                Ty::Application(right_function, right_argument),
+            ) => {
+                self.unify_types(left_function, right_function)?;
+                self.unify_types(left_argument, right_argument)
+            }
+            (Ty::Forall(left_name, _, left_body), Ty::Forall(right_name, _, right_body)) => {
+                self.skolems += 1;
+                let skolem = self.types.alloc(Ty::Skolem(left_name, self.skolems));
+                let left = self.substitute(left_body, &[(left_name, skolem)]);
+                let right = self.substitute(right_body, &[(right_name, skolem)]);
+                self.unify_types(left, right)
+            }
+            (Ty::Row(..), _) | (_, Ty::Row(..)) => self.unify_rows(left, right),
+            (Ty::Constrained(left, left_body), Ty::Constrained(right, right_body))
+                if left.class == right.class && left.arguments.len() == right.arguments.len() =>
+            {
+                for (left, right) in left.arguments.into_iter().zip(right.arguments) {
+                    self.unify_types(left, right)?;
+                }
+                self.unify_types(left_body, right_body)
+            }
+            _ => Err(UnifyError::Mismatch),
+        }
+    }
+
+    /// Unifies the types of the labels both rows have, then extends the
+    /// tail of each row with the labels only the other one has. Labels that
+    /// repeat are paired up in order.
+    fn unify_rows(&mut self, left: TyId, right: TyId) -> Result<(), UnifyError> {
+        let (left_fields, left_tail) = self.flatten_row(left);
+        let (mut right_fields, right_tail) = self.flatten_row(right);
+        let mut left_only = vec![];
+        for (label, ty) in left_fields {
+            match right_fields.iter().position(|&(other, _)| other == label) {
+                Some(index) => {
+                    let (_, other) = right_fields.remove(index);
+                    self.unify_types(ty, other)?;
+                }
+                None => left_only.push((label, ty)),
+            }
+        }
+        let right_only = right_fields;
+
+        match (left_only.is_empty(), right_only.is_empty()) {
+            (true, true) => match (left_tail, right_tail) {
+                (None, None) => Ok(()),
+                (Some(left), Some(right)) => self.unify_types(left, right),
+                (Some(tail), None) | (None, Some(tail)) => {
+                    let empty = self.types.alloc(Ty::Row(vec![], None));
+                    self.unify_types(tail, empty)
+                }
+            },
+            (true, false) => self.extend_tail(left_tail, right_only, right_tail),
+            (false, true) => self.extend_tail(right_tail, left_only, left_tail),
+            (false, false) => {
+                if left_tail.is_some() && left_tail == right_tail {
+                    return Err(UnifyError::Mismatch);
+                }
+                let rest = self.fresh();
+                self.extend_tail(left_tail, right_only, Some(rest))?;
+                self.extend_tail(right_tail, left_only, Some(rest))
+            }
+        }
+    }
+
+    /// Solves the tail of a row to the labels it's missing, which requires
+    /// the row to be open.
+    fn extend_tail(
+        &mut self,
+        tail: Option<TyId>,
+        fields: Vec<(Name, TyId)>,
+        rest: Option<TyId>,
+    ) -> Result<(), UnifyError> {
+        let Some(tail) = tail else { return Err(UnifyError::Mismatch) };
+        match self.types[tail] {
+            Ty::Unknown(unknown) => {
+                let row = self.types.alloc(Ty::Row(fields, rest));
+                self.solve(unknown, row)
+            }
+            Ty::Error => Ok(()),
+            _ => Err(UnifyError::Mismatch),
+        }
+    }
+
+    /// Collects the labels of a row and of the rows in its tail, returning
+    /// the pruned tail that remains, if any.
+    fn flatten_row(&mut self, mut ty: TyId) -> (Vec<(Name, TyId)>, Option<TyId>) {
+        let mut fields = vec![];
+        loop {
+            ty = self.prune(ty);
+            match &self.types[ty] {
+                Ty::Row(more, tail) => {
+                    fields.extend(more.iter().copied());
+                    match *tail {
+                        Some(tail) => ty = tail,
+                        None => return (fields, None),
+                    }
+                }
+                _ => return (fields, Some(ty)),
+            }
+        }
+    }
+
+    fn solve(&mut self, unknown: Unknown, ty: TyId) -> Result<(), UnifyError> {
+        let level = self.unknowns[unknown as usize].1;
+        if self.occurs(unknown, level, ty) {
+            return Err(UnifyError::Infinite(ty));
+        }
+        self.unknowns[unknown as usize].0 = Some(ty);
+        Ok(())
+    }
+
+    /// Checks whether `unknown` occurs in `ty`, lowering the level of every
+    /// other unknown in `ty` to `level` on the way, since they now escape
+    /// into the scope of `unknown`.
+    fn occurs(&mut self, unknown: Unknown, level: u32, ty: TyId) -> bool {
+        let ty = self.prune(ty);
+        match self.types[ty].clone() {
+            Ty::Unknown(other) if other == unknown => true,
+            Ty::Unknown(other) => {
+                let other = &mut self.unknowns[other as usize].1;
+                *other = (*other).min(level);
+                false
+            }
+            Ty::Application(function, argument) => {
+                self.occurs(unknown, level, function) || self.occurs(unknown, level, argument)
+            }
+            Ty::Forall(_, _, body) => self.occurs(unknown, level, body),
+            Ty::Row(fields, tail) => {
+                let mut types = fields.into_iter().map(|(_, ty)| ty).chain(tail);
+                types.any(|ty| self.occurs(unknown, level, ty))
+            }
+            Ty::Constrained(constraint, body) => {
+                let mut types = constraint.arguments.into_iter().chain([body]);
+                types.any(|ty| self.occurs(unknown, level, ty))
+            }
+            _ => false,
+        }
+    }
+
+    /// Splits `ty` into the argument and result of a function, solving an
+    /// unknown into a function if needed.
+    pub(crate) fn expect_function(&mut self, ty: TyId, origin: Origin) -> (TyId, TyId) {
+        let ty = self.prune(ty);
+        if let Some(function) = self.types.as_function(ty) {
+            return function;
+        }
+        if let Ty::Error = self.types[ty] {
+            return (ty, ty);
+        }
+        let (argument, result) = (self.fresh(), self.fresh());
+        let function = self.types.function(argument, result);
+        self.unify(ty, function, origin);
+        (argument, result)
+    }
+
+    // Expressions
+
+    pub(crate) fn infer_expr(&mut self, id: ExprId) -> TyId {
+        let body = self.body;
+        let origin = Origin::Expr(id);
+        let ty = match &body.exprs[id] {
+            Expr::Missing => self.types.alloc(Ty::Error),
+            &Expr::Typed { expr, type_ } => {
+                let ty = self.convert_annotation(type_);
+                self.check_expr(expr, ty);
+                ty
+            }
+            Expr::OperatorChain { .. } => match self.chains.expr(id).cloned() {
+                Some(tree) => self.infer_expr_chain(id, &tree),
+                None => self.types.alloc(Ty::Error),
+            },
+            &Expr::Negate(expr) => self.infer_expr(expr),
+            Expr::Application { function, arguments } => {
+                let mut ty = self.infer_expr(*function);
+                for &argument in arguments {
+                    ty = self.instantiate(ty, origin);
+                    let (parameter, result) = self.expect_function(ty, origin);
+                    self.check_expr(argument, parameter);
+                    ty = result;
+                }
+                ty
+            }
+            &Expr::TypeApplication { expr, type_ } => {
+                let ty = self.infer_uninstantiated(expr);
+                let argument = self.convert_annotation(type_);
+                self.apply_type(ty, argument, origin)
+            }
+            Expr::Variable(_) | Expr::Constructor(_) | Expr::OperatorName(_) => {
+                let ty = self.infer_uninstantiated(id);
+                self.instantiate(ty, origin)
+            }
+            Expr::Literal(literal) => self.infer_literal(literal),
+            Expr::Hole(_) => {
+                self.holes.push(id);
+                self.fresh()
+            }
+            Expr::Wildcard => self.fresh(),
+            Expr::RecordAccess { record, labels } => {
+                let mut ty = self.infer_expr(*record);
+                for &label in labels {
+                    let (field, tail) = (self.fresh(), self.fresh());
+                    let expected = self.types.record(vec![(label, field)], Some(tail));
+                    let actual = self.instantiate(ty, Origin::Expr(*record));
+                    self.unify(actual, expected, Origin::Expr(*record));
+                    ty = field;
+                }
+                ty
+            }
+            Expr::RecordUpdate { record, updates } => {
+                // Updates may change the types of the fields they replace,
+                // but leave the rest of the record as it is.
+                let tail = self.fresh();
+                let before = updates.iter().map(|&(label, _)| (label, self.fresh())).collect();
+                let before = self.types.record(before, Some(tail));
+                self.check_expr(*record, before);
+                let after = updates.iter().map(|&(label, update)| (label, self.infer_expr(update)));
+                let after = after.collect();
+                self.types.record(after, Some(tail))
+            }
+            &Expr::IfThenElse { condition, then, else_ } => {
+                let boolean = self.types.prim("Boolean");
+                self.check_expr(condition, boolean);
+                let ty = self.infer_expr(then);
+                self.check_expr(else_, ty);
+                ty
+            }
+            Expr::Lambda { binders, body } => {
+                let parameters: Vec<_> = binders.iter().map(|_| self.fresh()).collect();
+                for (&binder, &parameter) in binders.iter().zip(&parameters) {
+                    self.check_binder(binder, parameter);
+                }
+                let mut ty = self.infer_expr(*body);
+                for &parameter in parameters.iter().rev() {
+                    ty = self.types.function(parameter, ty);
+                }
+                ty
+            }
+            Expr::Case { scrutinees, branches } => {
+                let scrutinees: Vec<_> =
+                    scrutinees.iter().map(|&scrutinee| self.infer_scrutinee(scrutinee)).collect();
+                let ty = self.fresh();
+                for branch in branches {
+                    for (&binder, &scrutinee) in branch.binders.iter().zip(&scrutinees) {
+                        self.check_binder(binder, scrutinee);
+                    }
+                    self.check_guarded(&branch.guarded, ty);
+                }
+                ty
+            }
+            Expr::LetIn { bindings, body } => {
+                self.let_bindings(bindings);
+                self.infer_expr(*body)
+            }
+        };
+        self.exprs.insert(id, ty);
+        ty
+    }
+
+    pub(crate) fn check_expr(&mut self, id: ExprId, expected: TyId) {
+        let body = self.body;
+        let expected = self.prune(expected);
+        if let Ty::Forall(..) | Ty::Constrained(..) = self.types[expected] {
+            self.check_skolemized(expected, Origin::Expr(id), |infer, skolemized| {
+                infer.check_expr(id, skolemized);
+            });
+            self.exprs.insert(id, expected);
+            return;
+        }
+
+        match &body.exprs[id] {
+            Expr::Lambda { binders, body } => {
+                let mut ty = expected;
+                for &binder in binders {
+                    let (parameter, result) = self.expect_function(ty, Origin::Expr(id));
+                    self.check_binder(binder, parameter);
+                    ty = result;
+                }
+                self.check_expr(*body, ty);
+            }
+            &Expr::IfThenElse { condition, then, else_ } => {
+                let boolean = self.types.prim("Boolean");
+                self.check_expr(condition, boolean);
+                self.check_expr(then, expected);
+                self.check_expr(else_, expected);
+            }
+            Expr::Case { scrutinees, branches } => {
+                let scrutinees: Vec<_> =
+                    scrutinees.iter().map(|&scrutinee| self.infer_scrutinee(scrutinee)).collect();
+                for branch in branches {
+                    for (&binder, &scrutinee) in branch.binders.iter().zip(&scrutinees) {
+                        self.check_binder(binder, scrutinee);
+                    }
+                    self.check_guarded(&branch.guarded, expected);
+                }
+            }
+            Expr::LetIn { bindings, body } => {
+                self.let_bindings(bindings);
+                self.check_expr(*body, expected);
+            }
+            _ => {
+                self.expected.insert(id, expected);
+                let ty = self.infer_expr(id);
+                let ty = self.instantiate(ty, Origin::Expr(id));
+                self.unify(ty, expected, Origin::Expr(id));
+                return;
+            }
+        }
+        self.exprs.insert(id, expected);
+    }
+
+    /// Infers the type of a scrutinee. A local isn't instantiated, since
+    /// equations match on their arguments through a `case`, and a binder
+    /// that's a polymorphic argument has to stay polymorphic.
+    fn infer_scrutinee(&mut self, id: ExprId) -> TyId {
+        if let Expr::Variable(_) = self.body.exprs[id] {
+            if let Some(&Resolution::Local(binder)) = self.resolution.resolution(id) {
+                if let Some(&ty) = self.locals.get(&binder) {
+                    self.exprs.insert(id, ty);
+                    return ty;
+                }
+            }
+        }
+        self.infer_expr(id)
+    }
+
+    /// Infers the type of an expression that a type is applied to, which
+    /// keeps the `forall`s of names rather than instantiating them.
+    fn infer_uninstantiated(&mut self, id: ExprId) -> TyId {
+        let namespace = match self.body.exprs[id] {
+            Expr::Variable(_) => Namespace::Value,
+            Expr::Constructor(_) => Namespace::Constructor,
+            Expr::OperatorName(_) => Namespace::Operator,
+            _ => return self.infer_expr(id),
+        };
+        let resolution = self.resolution.resolution(id).cloned();
+        let ty = self.resolution_type(namespace, resolution.as_ref());
+        self.exprs.insert(id, ty);
+        ty
+    }
+
+    /// Instantiates the first visible variable of `ty` with `argument`, and
+    /// the invisible variables before it with unknowns.
+    fn apply_type(&mut self, applied: TyId, argument: TyId, origin: Origin) -> TyId {
+        let mut ty = applied;
+        loop {
+            ty = self.prune(ty);
+            match self.types[ty].clone() {
+                Ty::Forall(name, Visibility::Visible, body) => {
+                    return self.substitute(body, &[(name, argument)]);
+                }
+                Ty::Forall(name, Visibility::Invisible, body) => {
+                    let unknown = self.fresh();
+                    ty = self.substitute(body, &[(name, unknown)]);
+                }
+                Ty::Constrained(constraint, body) => {
+                    self.wanted.push((constraint, origin));
+                    ty = body;
+                }
+                Ty::Error => return ty,
+                _ => {
+                    let ty = applied;
+                    self.diagnostics.push(TypeDiagnostic::NoVisibleVariable { origin, ty });
+                    return self.types.alloc(Ty::Error);
+                }
+            }
+        }
+    }
+
+    fn infer_expr_chain(&mut self, chain: ExprId, tree: &OperatorTree) -> TyId {
+        let Expr::OperatorChain { head, tail } = &self.body.exprs[chain] else { unreachable!() };
+        match tree {
+            &OperatorTree::Operand(0) => self.infer_expr(*head),
+            &OperatorTree::Operand(index) => self.infer_expr(tail[index - 1].1),
+            OperatorTree::Operator { index, left, right } => {
+                let ty = match &tail[*index].0 {
+                    Operator::Symbol(_) => {
+                        let resolution =
+                            self.resolution.operator_resolution(chain, *index).cloned();
+                        self.resolution_type(Namespace::Operator, resolution.as_ref())
+                    }
+                    &Operator::Infix(function) => self.infer_expr(function),
+                };
+                let mut ty = self.instantiate(ty, Origin::Expr(chain));
+                for operand in [left, right] {
+                    let (parameter, result) = self.expect_function(ty, Origin::Expr(chain));
+                    let argument = self.infer_expr_chain(chain, operand);
+                    let origin = match **operand {
+                        OperatorTree::Operand(0) => Origin::Expr(*head),
+                        OperatorTree::Operand(index) => Origin::Expr(tail[index - 1].1),
+                        OperatorTree::Operator { .. } => Origin::Expr(chain),
+                    };
+                    let argument = self.instantiate(argument, origin);
+                    self.unify(argument, parameter, origin);
+                    ty = self.instantiate(result, Origin::Expr(chain));
+                }
+                ty
+            }
+        }
+    }
+
+    fn infer_literal<I: Copy>(&mut self, literal: &Literal<I>) -> TyId
+    where
+        Self: CheckElement<I>,
+    {
+        match literal {
+            Literal::Int(_) => self.types.prim("Int"),
+            Literal::Number(_) => self.types.prim("Number"),
+            Literal::String(_) => self.types.prim("String"),
+            Literal::Char(_) => self.types.prim("Char"),
+            Literal::Boolean(_) => self.types.prim("Boolean"),
+            Literal::Array(elements) => {
+                let element = self.fresh();
+                for &item in elements {
+                    self.check_element(item, element);
+                }
+                let array = self.types.prim("Array");
+                self.types.application(array, element)
+            }
+            Literal::Record(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|&(label, field)| {
+                        let ty = self.fresh();
+                        self.check_element(field, ty);
+                        (label, ty)
+                    })
+                    .collect();
+                self.types.record(fields, None)
+            }
+        }
+    }
+
+    // Guarded expressions and bindings
+
+    pub(crate) fn infer_guarded(&mut self, guarded: &GuardedExpr) -> TyId {
+        let ty = self.fresh();
+        self.check_guarded(guarded, ty);
+        ty
+    }
+
+    /// Checks an expression against `expected` with its outer `forall`s
+    /// replaced by skolems. Constraints wanted within the expression are
+    /// solved while the constraints it's given are still in scope, and the
+    /// skolems are in scope of the annotations within it, as with
+    /// `ScopedTypeVariables`.
+    fn check_skolemized(
+        &mut self,
+        expected: TyId,
+        origin: Origin,
+        check: impl FnOnce(&mut Self, TyId),
+    ) {
+        let (wanted, givens, scoped) = (self.wanted.len(), self.givens.len(), self.scoped.len());
+        let (unknowns, skolems) = (self.unknowns.len(), self.skolems);
+        let skolemized = self.skolemize(expected);
+        check(self, skolemized);
+        self.solve_constraints(wanted);
+        self.givens.truncate(givens);
+        self.scoped.truncate(scoped);
+
+        // The skolems only exist within the expression, so unknowns from
+        // outside of it can't have been solved to them.
+        let mut escaped = vec![];
+        for unknown in self.wildcards..unknowns {
+            if let Some(solution) = self.unknowns[unknown].0 {
+                self.collect_skolems(solution, skolems, &mut escaped);
+            }
+        }
+        for name in escaped {
+            self.diagnostics.push(TypeDiagnostic::EscapedSkolem { origin, name });
+        }
+    }
+
+    /// Collects the names of the skolems in `ty` that were created after the
+    /// first `after` skolems.
+    fn collect_skolems(&mut self, ty: TyId, after: u32, skolems: &mut Vec<Name>) {
+        let ty = self.prune(ty);
+        match self.types[ty].clone() {
+            Ty::Skolem(name, skolem) if skolem > after && !skolems.contains(&name) => {
+                skolems.push(name);
+            }
+            Ty::Application(function, argument) => {
+                self.collect_skolems(function, after, skolems);
+                self.collect_skolems(argument, after, skolems);
+            }
+            Ty::Forall(_, _, body) => self.collect_skolems(body, after, skolems),
+            Ty::Row(fields, tail) => {
+                for ty in fields.into_iter().map(|(_, ty)| ty).chain(tail) {
+                    self.collect_skolems(ty, after, skolems);
+                }
+            }
+            Ty::Constrained(constraint, body) => {
+                for argument in constraint.arguments {
+                    self.collect_skolems(argument, after, skolems);
+                }
+                self.collect_skolems(body, after, skolems);
+            }
+            _ => {}
+        }
+    }
+
+    /// Converts an annotation within the body, where the variables of the
+    /// `forall`s being checked against refer to their skolems.
+    fn convert_annotation(&mut self, type_: TypeId) -> TyId {
+        let (types, resolution) = self.module_types.clone();
+        let ty = self.convert(types, &resolution, type_);
+        let scoped: Vec<_> = self.scoped.iter().rev().copied().collect();
+        self.substitute(ty, &scoped)
+    }
+
+    pub(crate) fn check_guarded(&mut self, guarded: &GuardedExpr, expected: TyId) {
+        let expected = self.prune(expected);
+        let first = match guarded {
+            GuardedExpr::Unconditional(_) => None,
+            GuardedExpr::Guarded(branches) | GuardedExpr::LetIn { branches, .. } => {
+                branches.first().map(|branch| branch.expr)
+            }
+        };
+        // Bindings from a `where` clause are checked before any branch, so
+        // the skolems have to be in scope for them too.
+        if let (Some(first), Ty::Forall(..) | Ty::Constrained(..)) = (first, &self.types[expected])
+        {
+            self.check_skolemized(expected, Origin::Expr(first), |infer, skolemized| {
+                infer.check_guarded(guarded, skolemized);
+            });
+            return;
+        }
+        match guarded {
+            &GuardedExpr::Unconditional(expr) => self.check_expr(expr, expected),
+            GuardedExpr::Guarded(branches) => self.check_branches(branches, expected),
+            GuardedExpr::LetIn { bindings, branches } => {
+                self.let_bindings(bindings);
+                self.check_branches(branches, expected);
+            }
+        }
+    }
+
+    fn check_branches(&mut self, branches: &[GuardedBranch], expected: TyId) {
+        for branch in branches {
+            for guard in &branch.guards {
+                match *guard {
+                    Guard::Boolean(expr) => {
+                        let boolean = self.types.prim("Boolean");
+                        self.check_expr(expr, boolean);
+                    }
+                    Guard::Pattern(binder, expr) => {
+                        let ty = self.infer_expr(expr);
+                        let ty = self.instantiate(ty, Origin::Expr(expr));
+                        self.check_binder(binder, ty);
+                    }
+                }
+            }
+            self.check_expr(branch.expr, expected);
+        }
+    }
+
+    /// Bindings in a `let` are mutually recursive, so every binding is in
+    /// scope with an unknown type before any of them are inferred. Bindings
+    /// without a signature are generalized once all of them are inferred.
+    fn let_bindings(&mut self, bindings: &[LetBinding]) {
+        let mut signatures = FxHashMap::default();
+        for binding in bindings {
+            if let LetBinding::Signature(signature) = binding {
+                let ty = self.convert_annotation(signature.type_);
+                signatures.insert(signature.name, ty);
+            }
+        }
+
+        let wanted = self.wanted.len();
+        self.level += 1;
+        let mut unannotated = vec![];
+        for binding in bindings {
+            if let LetBinding::Value { binder, .. } = binding {
+                let ty = match self.body.binders[*binder] {
+                    Binder::Variable(name) => signatures.get(&name).copied(),
+                    _ => None,
+                };
+                let ty = ty.unwrap_or_else(|| {
+                    unannotated.push(*binder);
+                    self.fresh()
+                });
+                self.locals.insert(*binder, ty);
+                self.binders.insert(*binder, ty);
+            }
+        }
+        for binding in bindings {
+            match binding {
+                LetBinding::Value { binder, guarded } => {
+                    let ty = self.locals[binder];
+                    if unannotated.contains(binder) {
+                        let inferred = self.infer_guarded(guarded);
+                        self.unify(inferred, ty, Origin::Binder(*binder));
+                    } else {
+                        self.check_guarded(guarded, ty);
+                    }
+                }
+                LetBinding::Pattern { binder, guarded } => {
+                    let ty = self.infer_guarded(guarded);
+                    self.check_binder(*binder, ty);
+                }
+                LetBinding::Signature(_) => {}
+            }
+        }
+        self.level -= 1;
+
+        for binder in unannotated {
+            let ty = self.generalize(self.locals[&binder], wanted);
+            self.locals.insert(binder, ty);
+            self.binders.insert(binder, ty);
+        }
+    }
+
+    // Binders
+
+    pub(crate) fn check_binder(&mut self, id: BinderId, expected: TyId) {
+        let origin = Origin::Binder(id);
+        self.binders.insert(id, expected);
+        match &self.body.binders[id] {
+            Binder::Missing | Binder::Wildcard => {}
+            Binder::Variable(_) => {
+                self.locals.insert(id, expected);
+            }
+            &Binder::Named { binder, .. } => {
+                self.locals.insert(id, expected);
+                self.check_binder(binder, expected);
+            }
+            Binder::Constructor { arguments, .. } => {
+                let resolution = self.resolution.binder_resolution(id).cloned();
+                let ty = self.resolution_type(Namespace::Constructor, resolution.as_ref());
+                let mut ty = self.instantiate(ty, origin);
+                for &argument in arguments {
+                    let (parameter, result) = self.expect_function(ty, origin);
+                    self.check_binder(argument, parameter);
+                    ty = result;
+                }
+                self.unify(ty, expected, origin);
+            }
+            // Record binders only match on some of the fields of a record.
+            Binder::Literal(Literal::Record(fields)) => {
+                let fields = fields
+                    .iter()
+                    .map(|&(label, field)| {
+                        let ty = self.fresh();
+                        self.check_binder(field, ty);
+                        (label, ty)
+                    })
+                    .collect();
+                let tail = self.fresh();
+                let ty = self.types.record(fields, Some(tail));
+                self.unify(ty, expected, origin);
+            }
+            Binder::Literal(literal) => {
+                let ty = self.infer_literal(literal);
+                self.unify(ty, expected, origin);
+            }
+            &Binder::Typed { binder, type_ } => {
+                let ty = self.convert_annotation(type_);
+                self.unify(ty, expected, origin);
+                self.check_binder(binder, ty);
+            }
+            Binder::OperatorChain { .. } => {
+                if let Some(tree) = self.chains.binder(id).cloned() {
+                    self.check_binder_chain(id, &tree, expected);
+                }
+            }
+        }
+    }
+
+    fn check_binder_chain(&mut self, chain: BinderId, tree: &OperatorTree, expected: TyId) {
+        let Binder::OperatorChain { head, tail } = &self.body.binders[chain] else {
+            unreachable!()
+        };
+        match tree {
+            &OperatorTree::Operand(0) => self.check_binder(*head, expected),
+            &OperatorTree::Operand(index) => self.check_binder(tail[index - 1].1, expected),
+            OperatorTree::Operator { index, left, right } => {
+                let resolution = self.resolution.binder_operator_resolution(chain, *index).cloned();
+                let ty = self.resolution_type(Namespace::Operator, resolution.as_ref());
+                let mut ty = self.instantiate(ty, Origin::Binder(chain));
+                for operand in [left, right] {
+                    let (parameter, result) = self.expect_function(ty, Origin::Binder(chain));
+                    self.check_binder_chain(chain, operand, parameter);
+                    ty = result;
+                }
+                self.unify(ty, expected, Origin::Binder(chain));
+            }
+        }
+    }
+
+    // Names
+
+    /// The type of a resolved name, which may still be polymorphic.
+    pub(crate) fn resolution_type(
+        &mut self,
+        namespace: Namespace,
+        resolution: Option<&Resolution>,
+    ) -> TyId {
+        match resolution {
+            Some(Resolution::Local(binder)) => match self.locals.get(binder) {
+                Some(&ty) => ty,
+                None => self.types.alloc(Ty::Error),
+            },
+            Some(&Resolution::Global(definition)) => match namespace {
+                Namespace::Operator => self.operator_type(definition),
+                _ => self.definition_type(definition),
+            },
+            _ => self.types.alloc(Ty::Error),
+        }
+    }
+
+    /// The type of an operator is the type of the value or constructor its
+    /// fixity declaration refers to.
+    fn operator_type(&mut self, definition: Definition) -> TyId {
+        let workspace = self.workspace;
+        let target = workspace.item_tree(definition.module).and_then(|tree| {
+            let Item::Fixity(fixity) = &tree.items[definition.item] else { return None };
+            let scope = workspace.scope(definition.module)?;
+            let namespace = if fixity.target.name.as_str().starts_with(char::is_uppercase) {
+                Namespace::Constructor
+            } else {
+                Namespace::Value
+            };
+            match scope.resolve(namespace, fixity.target)? {
+                Resolution::Global(target) => Some(target),
+                _ => None,
+            }
+        });
+        match target {
+            Some(target) => self.definition_type(target),
+            None => self.types.alloc(Ty::Error),
+        }
+    }
+
+    /// The type of a value, class member, or constructor declared anywhere
+    /// in the workspace. Values without a signature aren't inferred here, so
+    /// they aren't checked where they're used.
+    fn definition_type(&mut self, definition: Definition) -> TyId {
+        let Some(tree) = self.workspace.item_tree(definition.module) else {
+            return self.types.alloc(Ty::Error);
+        };
+        match &tree.items[definition.item] {
+            Item::Value(_) | Item::ForeignValue(_) => match self.value_signature(definition) {
+                Some(ty) => ty,
+                None => self.types.alloc(Ty::Error),
+            },
+            Item::Class(class) => {
+                let Some(member) = definition.member.and_then(|index| class.members.get(index))
+                else {
+                    return self.types.alloc(Ty::Error);
+                };
+                let mut ty = self.convert_tree_type(tree, definition.module, member.type_);
+                let class_constructor = TypeConstructor {
+                    name: class.name,
+                    definition: Some(Definition { member: None, ..definition }),
+                };
+                let arguments = class
+                    .variables
+                    .iter()
+                    .map(|variable| self.types.alloc(Ty::Variable(variable.name)))
+                    .collect();
+                let constraint = Constraint { class: class_constructor, arguments };
+                ty = self.types.alloc(Ty::Constrained(constraint, ty));
+                // The variables of the class can be applied to its members,
+                // e.g. `mempty @String`.
+                for variable in class.variables.iter().rev() {
+                    ty = self.types.alloc(Ty::Forall(variable.name, Visibility::Visible, ty));
+                }
+                ty
+            }
+            Item::Data(data) => {
+                let Some(constructor) =
+                    definition.member.and_then(|index| data.constructors.get(index))
+                else {
+                    return self.types.alloc(Ty::Error);
+                };
+                let data_definition = Definition { member: None, ..definition };
+                self.constructor_type(
+                    tree,
+                    data_definition,
+                    data.name,
+                    &data.variables,
+                    &constructor.fields,
+                )
+            }
+            Item::Newtype(newtype) => {
+                let data_definition = Definition { member: None, ..definition };
+                let fields = &newtype.constructor.fields;
+                self.constructor_type(
+                    tree,
+                    data_definition,
+                    newtype.name,
+                    &newtype.variables,
+                    fields,
+                )
+            }
+            _ => self.types.alloc(Ty::Error),
+        }
+    }
+
+    fn constructor_type(
+        &mut self,
+        tree: &ItemTree,
+        data: Definition,
+        name: Name,
+        variables: &[lowering::hir::TypeVariable],
+        fields: &[TypeId],
+    ) -> TyId {
+        let constructor = TypeConstructor { name, definition: Some(data) };
+        let mut ty = self.types.alloc(Ty::Constructor(constructor));
+        for variable in variables {
+            let variable = self.types.alloc(Ty::Variable(variable.name));
+            ty = self.types.application(ty, variable);
+        }
+        for &field in fields.iter().rev() {
+            let field = self.convert_tree_type(tree, data.module, field);
+            ty = self.types.function(field, ty);
+        }
+        for variable in variables.iter().rev() {
+            ty = self.types.alloc(Ty::Forall(variable.name, Visibility::Visible, ty));
+        }
+        ty
+    }
+
+    /// The signature of a value or foreign value declared anywhere in the
+    /// workspace.
+    pub(crate) fn value_signature(&mut self, definition: Definition) -> Option<TyId> {
+        let tree = self.workspace.item_tree(definition.module)?;
+        let signature = match &tree.items[definition.item] {
+            Item::Value(value) => value.signature?,
+            Item::ForeignValue(value) => value.type_,
+            _ => return None,
+        };
+        Some(self.convert_tree_type(tree, definition.module, signature))
+    }
+
+    pub(crate) fn convert_tree_type(
+        &mut self,
+        tree: &ItemTree,
+        module: ModuleName,
+        id: TypeId,
+    ) -> TyId {
+        match self.tree_resolution(tree, module) {
+            Some(resolution) => self.convert(&tree.types, &resolution, id),
+            None => self.types.alloc(Ty::Error),
+        }
+    }
+
+    pub(crate) fn tree_resolution(
+        &mut self,
+        tree: &ItemTree,
+        module: ModuleName,
+    ) -> Option<Rc<TypeResolution>> {
+        if let Some(resolution) = self.tree_types.get(&module) {
+            return Some(resolution.clone());
+        }
+        let scope = self.workspace.scope(module)?;
+        let resolution = Rc::new(TypeResolution::new(&tree.types, scope));
+        self.tree_types.insert(module, resolution.clone());
+        Some(resolution)
+    }
+
+    // Conversion from the HIR
+
+    /// Converts a type from the HIR. Wildcards become unknowns, and type
+    /// synonyms are expanded where they're fully applied.
+    pub(crate) fn convert(
+        &mut self,
+        types: &Arena<Type>,
+        resolution: &TypeResolution,
+        id: TypeId,
+    ) -> TyId {
+        match &types[id] {
+            Type::Forall { variables, type_ } => {
+                let mut ty = self.convert(types, resolution, *type_);
+                for variable in variables.iter().rev() {
+                    let visibility =
+                        if variable.visible { Visibility::Visible } else { Visibility::Invisible };
+                    ty = self.types.alloc(Ty::Forall(variable.name, visibility, ty));
+                }
+                ty
+            }
+            &Type::Constrained { constraint, type_ } => {
+                let constraints = self.convert_constraints(types, resolution, constraint);
+                let mut ty = self.convert(types, resolution, type_);
+                for constraint in constraints.into_iter().rev() {
+                    ty = self.types.alloc(Ty::Constrained(constraint, ty));
+                }
+                ty
+            }
+            &Type::Arrow { argument, result } => {
+                let argument = self.convert(types, resolution, argument);
+                let result = self.convert(types, resolution, result);
+                self.types.function(argument, result)
+            }
+            &Type::Kinded { type_, .. } => self.convert(types, resolution, type_),
+            Type::Application { function, arguments } => {
+                let arguments: Vec<_> = arguments
+                    .iter()
+                    .map(|&argument| self.convert(types, resolution, argument))
+                    .collect();
+                let expanded = self.expand_synonym(resolution, *function, &arguments);
+                let mut ty = self.convert(types, resolution, *function);
+                for &argument in &arguments {
+                    ty = self.types.application(ty, argument);
+                }
+                match expanded {
+                    Some(expanded) => self.expanded_from(expanded, ty, &arguments),
+                    None => ty,
+                }
+            }
+            &Type::Constructor(name) => {
+                let expanded = self.expand_synonym(resolution, id, &[]);
+                let ty = match self.type_constructor(name, resolution.resolution(id)) {
+                    Some(constructor) => self.types.alloc(Ty::Constructor(constructor)),
+                    None => self.types.alloc(Ty::Error),
+                };
+                match expanded {
+                    Some(expanded) => self.expanded_from(expanded, ty, &[]),
+                    None => ty,
+                }
+            }
+            &Type::Variable(name) => self.types.alloc(Ty::Variable(name)),
+            Type::Wildcard | Type::Hole(_) => self.fresh(),
+            Type::String(value) => self.types.alloc(Ty::Symbol(value.clone())),
+            Type::Function => self.types.prim("Function"),
+            Type::Record(row) => {
+                let row = self.convert_row(types, resolution, row);
+                let record = self.types.prim("Record");
+                self.types.application(record, row)
+            }
+            Type::Row(row) => self.convert_row(types, resolution, row),
+            Type::Missing
+            | Type::OperatorChain { .. }
+            | Type::Integer(_)
+            | Type::OperatorName(_)
+            | Type::Constraints(_) => self.types.alloc(Ty::Error),
+        }
+    }
+
+    /// Converts a constraint, or a parenthesized list of them, skipping any
+    /// that don't resolve to a class.
+    pub(crate) fn convert_constraints(
+        &mut self,
+        types: &Arena<Type>,
+        resolution: &TypeResolution,
+        id: TypeId,
+    ) -> Vec<Constraint> {
+        let (class, arguments) = match &types[id] {
+            Type::Constraints(constraints) => {
+                return constraints
+                    .iter()
+                    .flat_map(|&constraint| self.convert_constraints(types, resolution, constraint))
+                    .collect();
+            }
+            Type::Application { function, arguments } => (*function, arguments.as_slice()),
+            Type::Constructor(_) => (id, [].as_slice()),
+            _ => return vec![],
+        };
+        let Type::Constructor(name) = types[class] else { return vec![] };
+        let Some(class) = self.type_constructor(name, resolution.resolution(class)) else {
+            return vec![];
+        };
+        let arguments =
+            arguments.iter().map(|&argument| self.convert(types, resolution, argument)).collect();
+        vec![Constraint { class, arguments }]
+    }
+
+    fn convert_row(&mut self, types: &Arena<Type>, resolution: &TypeResolution, row: &Row) -> TyId {
+        let fields = row
+            .fields
+            .iter()
+            .map(|&(label, type_)| (label, self.convert(types, resolution, type_)))
+            .collect();
+        let tail = row.tail.map(|tail| self.convert(types, resolution, tail));
+        self.types.alloc(Ty::Row(fields, tail))
+    }
+
+    pub(crate) fn type_constructor(
+        &self,
+        name: QualifiedName,
+        resolution: Option<&Resolution>,
+    ) -> Option<TypeConstructor> {
+        match *resolution? {
+            Resolution::Global(definition) => {
+                Some(TypeConstructor { name: name.name, definition: Some(definition) })
+            }
+            Resolution::Prim(name) => Some(TypeConstructor { name, definition: None }),
+            _ => None,
+        }
+    }
+
+    /// Keeps `synonym`, as it's written, as the way to display the type it
+    /// expands to. A synonym that expands to one of its arguments is
+    /// displayed as that argument, since the argument is displayed on its
+    /// own elsewhere.
+    fn expanded_from(&mut self, expanded: TyId, synonym: TyId, arguments: &[TyId]) -> TyId {
+        if !arguments.contains(&expanded) {
+            self.types.expanded_from(expanded, synonym);
+        }
+        expanded
+    }
+
+    /// Expands `function` applied to `arguments` if it's a synonym that takes
+    /// exactly as many arguments.
+    fn expand_synonym(
+        &mut self,
+        resolution: &TypeResolution,
+        function: TypeId,
+        arguments: &[TyId],
+    ) -> Option<TyId> {
+        let &Resolution::Global(definition) = resolution.resolution(function)? else {
+            return None;
+        };
+        let tree = self.workspace.item_tree(definition.module)?;
+        let Item::Synonym(synonym) = &tree.items[definition.item] else { return None };
+        if synonym.variables.len() != arguments.len() {
+            return None;
+        }
+        let ty = self.convert_tree_type(tree, definition.module, synonym.type_);
+        let variables = synonym.variables.iter().map(|variable| variable.name);
+        let bindings: Vec<_> = variables.zip(arguments.iter().copied()).collect();
+        Some(self.substitute(ty, &bindings))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnifyError {
+    Mismatch,
+    Infinite(TyId),
+}
+
+/// Checks the elements of literals, which are expressions or binders.
+pub(crate) trait CheckElement<I> {
+    fn check_element(&mut self, element: I, expected: TyId);
+}
+
+impl CheckElement<ExprId> for Infer<'_> {
+    fn check_element(&mut self, element: ExprId, expected: TyId) {
+        self.check_expr(element, expected);
+    }
+}
+
+impl CheckElement<BinderId> for Infer<'_> {
+    fn check_element(&mut self, element: BinderId, expected: TyId) {
+        self.check_binder(element, expected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{hir::Declaration, item_tree::ItemTree, lower_module, name::ModuleName};
+    use resolution::{body::ModuleScope, imports::Imports};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_module;
+    use crate::{solve::check_coverage, Workspace};
+
+    struct Single(ItemTree, ModuleScope);
+
+    impl Workspace for Single {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            (module == ModuleName::new("Main")).then_some(&self.0)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            (module == ModuleName::new("Main")).then_some(&self.1)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            vec![ModuleName::new("Main")]
+        }
+    }
+
+    /// The type of each value in `source`, followed by its diagnostics.
+    fn check(source: &str) -> Vec<String> {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let module = lower_module(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let workspace = Single(tree, scope);
+
+        let bodies = check_module(&workspace, ModuleName::new("Main"), &module);
+        let mut lines = vec![];
+        for (_, declaration) in module.declarations.iter() {
+            let Declaration::Value(value) = declaration else { continue };
+            let body = bodies.get(value.body).unwrap();
+            lines.push(format!("{} :: {}", value.name, body.types.display(body.ty.unwrap())));
+            for diagnostic in body.diagnostics() {
+                let message = diagnostic.message(&body.types);
+                lines.extend(message.lines().map(|line| format!("  {line}")));
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn infer_values() {
+        let source = "module Main where\n\nidentity :: forall a. a -> a\nidentity x = x\n\nconst a _ = a\n\nn = let f x = x in f f 0\n\nwrong :: Int\nwrong = \"x\"\n";
+        assert_eq!(
+            check(source),
+            [
+                "identity :: forall a. a -> a",
+                "const :: forall t0 t1. t0 -> t1 -> t0",
+                "n :: Int",
+                "wrong :: Int",
+                "  expected  Int",
+                "            ^^^",
+                "  but found String",
+                "            ^^^^^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn infer_records() {
+        let source = "module Main where\n\nname :: forall r. { name :: String | r } -> String\nname r = r.name\n\nperson = { age: 1, name: \"\" }\n\nolder p = p { age = 1 }\n\nfirst { x } = x\n\nclosed :: { name :: String } -> String\nclosed = name\n\nmissing :: { age :: Int } -> String\nmissing = name\n";
+        assert_eq!(
+            check(source),
+            [
+                "name :: forall r. { name :: String | r } -> String",
+                "person :: { age :: Int, name :: String }",
+                "older :: forall t0 t1. { age :: t0 | t1 } -> { age :: Int | t1 }",
+                "first :: forall t0 t1. { x :: t0 | t1 } -> t0",
+                "closed :: { name :: String } -> String",
+                "missing :: { age :: Int } -> String",
+                "  expected  { age :: Int } -> String",
+                "  but found { name :: String, age :: Int | t0 } -> String",
+                "              ^^^^^^^^^^^^^^               ^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn display_synonyms() {
+        let source = "module Main where\n\ntype Person = { name :: String, age :: Int }\n\ntype Pair a = { first :: a, second :: a }\n\ngreet :: Person -> String\ngreet p = p.name\n\npair :: Pair Int\npair = { first: 1, second: 2 }\n\nnumber :: Int\nnumber = greet\n\nstrings :: Pair String -> String\nstrings p = p.first\n\nwrong :: Pair Int -> String\nwrong = strings\n";
+        assert_eq!(
+            check(source),
+            [
+                "greet :: Person -> String",
+                "pair :: Pair Int",
+                "number :: Int",
+                "  expected  Int",
+                "            ^^^",
+                "  but found Person -> String",
+                "            ^^^^^^^^^^^^^^^^",
+                "strings :: Pair String -> String",
+                "wrong :: Pair Int -> String",
+                "  expected  { first :: Int, second :: Int } -> String",
+                "                       ^^^            ^^^",
+                "  but found { first :: String, second :: String } -> String",
+                "                       ^^^^^^            ^^^^^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_constraints() {
+        let source = "module Main where\n\nclass Show a where\n  show :: a -> String\n\nclass Show a <= Pretty a where\n  pretty :: a -> String\n\ninstance Show Int where\n  show _ = \"\"\n\ninstance Show a => Show (Array a) where\n  show _ = \"\"\n\ndata Box a = Box a\n\ndisplay x = show [x]\n\nsuper :: forall a. Pretty a => a -> String\nsuper x = show x\n\nnumbers = show [1]\n\nboxed = show (Box 1)\n";
+        assert_eq!(
+            check(source),
+            [
+                "display :: forall t0. Show t0 => t0 -> String",
+                "super :: forall a. Pretty a => a -> String",
+                "numbers :: String",
+                "boxed :: String",
+                "  no instance found for Show (Box Int)",
+            ]
+        );
+    }
+
+    #[test]
+    fn improve_by_functional_dependencies() {
+        let source = "module Main where\n\nclass Convert a b | a -> b where\n  convert :: a -> b\n\ninstance Convert Int String where\n  convert _ = \"\"\n\ninstance Convert Boolean Int where\n  convert _ = 0\n\nconverted = convert 1\n\nstuck x = convert x\n\nwrong :: Int\nwrong = convert 1\n\ngiven :: forall a b. Convert a b => a -> b\ngiven x = convert x\n";
+        assert_eq!(
+            check(source),
+            [
+                "converted :: String",
+                "stuck :: forall t0 t1. Convert t0 t1 => t0 -> t1",
+                "wrong :: Int",
+                "  expected  String",
+                "            ^^^^^^",
+                "  but found Int",
+                "            ^^^",
+                "given :: forall a b. Convert a b => a -> b",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_uncovered_instances() {
+        let source = "module Main where\n\nclass Convert a b | a -> b\n\ninstance Convert (Array a) a\n\ninstance Convert Int b\n\nclass Both a b | a -> b, b -> a\n\ninstance Both a (Array b)\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let module = lower_module(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let workspace = Single(tree, scope);
+
+        let diagnostics = check_coverage(&workspace, ModuleName::new("Main"), &module);
+        let types = Default::default();
+        let messages: Vec<_> =
+            diagnostics.iter().map(|diagnostic| diagnostic.message(&types)).collect();
+        assert_eq!(
+            messages,
+            [
+                "b isn't determined by the functional dependencies of Convert",
+                "b isn't determined by the functional dependencies of Both",
+                "a isn't determined by the functional dependencies of Both",
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_instance_chains() {
+        let source = "module Main where\n\nclass IsInt a where\n  isInt :: a -> Boolean\n\ninstance IsInt Int where\n  isInt _ = true\nelse instance IsInt a where\n  isInt _ = false\n\nclass Show a where\n  show :: a -> String\n\ninstance Show Int where\n  show _ = \"\"\n\ninstance Show a where\n  show _ = \"\"\n\nint = isInt 1\n\nstring = isInt \"\"\n\ngeneric x = isInt x\n\nshown = show \"\"\n\noverlapping = show 1\n";
+        assert_eq!(
+            check(source),
+            [
+                "int :: Boolean",
+                "string :: Boolean",
+                "generic :: forall t0. IsInt t0 => t0 -> Boolean",
+                "shown :: String",
+                "overlapping :: String",
+                "  overlapping instances found for Show Int",
+            ]
+        );
+    }
+
+    #[test]
+    fn suggest_hole_candidates() {
+        let source = "module Main where\n\nidentity :: forall a. a -> a\nidentity x = x\n\nzero :: Int\nzero = 0\n\nadd :: Int -> Int -> Int\nadd a _ = a\n\nfilled :: Int -> String -> Int\nfilled x y = add ?a 1\n\napplied :: Int\napplied = ?f 1\n";
+        assert_eq!(
+            check(source),
+            [
+                "identity :: forall a. a -> a",
+                "zero :: Int",
+                "add :: Int -> Int -> Int",
+                "filled :: Int -> String -> Int",
+                "  hole ?a has the type Int, and could be x, applied, zero",
+                "applied :: Int",
+                "  hole ?f has the type Int -> Int, and could be identity",
+            ]
+        );
+    }
+
+    #[test]
+    fn scope_type_variables() {
+        let source = "module Main where\n\nident :: forall a. a -> a\nident = go\n  where\n  go :: a -> a\n  go y = y\n\npair :: forall a. a -> { first :: a, second :: a }\npair x = go x\n  where\n  go :: a -> { first :: a, second :: a }\n  go y = { first: y, second: x }\n\napply :: (forall a. a -> a) -> Int\napply f = f 1\n\napplied :: Int\napplied = apply (\\x -> x)\n\nwrong :: Int\nwrong = apply (\\_ -> 1)\n\nrun :: forall r. (forall s. s -> r) -> r\nrun f = run f\n\nescaped = run (\\x -> x)\n";
+        assert_eq!(
+            check(source),
+            [
+                "ident :: forall a. a -> a",
+                "pair :: forall a. a -> { first :: a, second :: a }",
+                "apply :: (forall a. a -> a) -> Int",
+                "applied :: Int",
+                "wrong :: Int",
+                "  expected  a",
+                "            ^",
+                "  but found Int",
+                "            ^^^",
+                "run :: forall r. (forall s. s -> r) -> r",
+                "escaped :: s",
+                "  the type variable s escapes its scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_visible_types() {
+        let source = "module Main where\n\nidentity :: forall @a. a -> a\nidentity x = x\n\nint = identity @Int\n\nwrong = identity @Int \"x\"\n\nclass Default a where\n  default :: a\n\ninstance Default Int where\n  default = 0\n\nzero = default @Int\n\nhidden :: forall a. a -> a\nhidden x = x\n\ninvisible = hidden @Int\n";
+        assert_eq!(
+            check(source),
+            [
+                "identity :: forall @a. a -> a",
+                "int :: Int -> Int",
+                "wrong :: Int",
+                "  expected  Int",
+                "            ^^^",
+                "  but found String",
+                "            ^^^^^^",
+                "zero :: Int",
+                "hidden :: forall a. a -> a",
+                "invisible :: forall t0. t0",
+                "  no visible type variable to apply in forall a. a -> a",
+            ]
+        );
+    }
+}
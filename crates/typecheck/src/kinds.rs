@@ -0,0 +1,679 @@
+//! Kind inference for the declarations and signatures of a module.
+//!
+//! Kinds are represented as types, such that they reuse the unification of
+//! [`Infer`]. The type-level declarations of a module are inferred together
+//! and generalized once all of them are checked, after which every other type
+//! in the module is checked against them.
+
+use lowering::{
+    arena::Arena,
+    hir::{
+        Binder, Body, Declaration, Expr, GuardedExpr, LetBinding, Module, Row, Type, TypeId,
+        TypeVariable,
+    },
+    item_tree::Item,
+    name::{ModuleName, Name},
+};
+use resolution::{
+    body::{BodyResolution, ModuleScope, Resolution},
+    fixity::{Chains, Fixities, OperatorTree},
+    interface::{Definition, Namespace},
+    types::TypeResolution,
+};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    infer::{Infer, Origin, TypeDiagnostic},
+    solve::Instances,
+    ty::{Ty, TyId, Types, Visibility},
+    Workspace,
+};
+
+/// The kinds of the type-level declarations of a module, and the kind errors
+/// in every type within it, which are reported at [`Origin::Type`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ModuleKinds {
+    pub types: Types,
+    kinds: FxHashMap<Definition, TyId>,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl ModuleKinds {
+    pub fn kind(&self, definition: Definition) -> Option<TyId> {
+        self.kinds.get(&definition).copied()
+    }
+
+    pub fn diagnostics(&self) -> &[TypeDiagnostic] {
+        &self.diagnostics
+    }
+}
+
+pub fn check_kinds(workspace: &dyn Workspace, name: ModuleName, module: &Module) -> ModuleKinds {
+    let Some(scope) = workspace.scope(name) else { return ModuleKinds::default() };
+    // No expressions are involved, so inference runs over an empty body.
+    let body = Body {
+        exprs: Arena::default(),
+        binders: Arena::default(),
+        guarded: GuardedExpr::Guarded(vec![]),
+    };
+    let instances = Instances::default();
+    let resolution = BodyResolution::new(&body, scope);
+    let infer =
+        Infer::new(workspace, &instances, module, &body, scope, resolution, Chains::default());
+
+    let type_resolution = TypeResolution::new(&module.types, scope);
+    let fixities = Fixities::new(scope, |module| workspace.item_tree(module));
+    let chains = Chains::types(&module.types, &type_resolution, &fixities);
+    let mut kinds = Kinds {
+        infer,
+        name,
+        scope,
+        types: &module.types,
+        resolution: &type_resolution,
+        chains: &chains,
+        declarations: FxHashMap::default(),
+    };
+    kinds.declarations(module);
+    kinds.signatures(module);
+
+    let Kinds { mut infer, declarations, .. } = kinds;
+    let declarations =
+        declarations.into_iter().map(|(definition, kind)| (definition, infer.zonk(kind)));
+    let kinds = declarations.collect();
+    let diagnostics = infer.take_diagnostics();
+    ModuleKinds { types: infer.types, kinds, diagnostics }
+}
+
+struct Kinds<'a> {
+    infer: Infer<'a>,
+    name: ModuleName,
+    scope: &'a ModuleScope,
+    types: &'a Arena<Type>,
+    resolution: &'a TypeResolution,
+    chains: &'a Chains,
+    /// The kinds of the type-level declarations of the module, which are
+    /// unknowns until the module's declarations are generalized.
+    declarations: FxHashMap<Definition, TyId>,
+}
+
+impl Kinds<'_> {
+    fn declarations(&mut self, module: &Module) {
+        let scope = self.scope;
+        let definition =
+            |namespace, name| scope.declarations.names.get(namespace).get(&name).copied();
+
+        // Kind signatures are trusted while the declarations they belong to
+        // are checked, and only checked against them once they're inferred.
+        let mut signatures = FxHashMap::default();
+        for (_, declaration) in module.declarations.iter() {
+            match declaration {
+                Declaration::KindSignature(signature) => {
+                    let namespace = match signature.target {
+                        lowering::hir::KindSignatureTarget::Class => Namespace::Class,
+                        _ => Namespace::Type,
+                    };
+                    let Some(definition) = definition(namespace, signature.name) else { continue };
+                    self.check_prim(signature.kind, "Type", &mut vec![]);
+                    let kind = self.convert(signature.kind);
+                    self.declarations.insert(definition, kind);
+                    signatures.insert(definition, (signature.kind, kind));
+                }
+                Declaration::ForeignData(data) => {
+                    let Some(definition) = definition(Namespace::Type, data.name) else { continue };
+                    self.check_prim(data.kind, "Type", &mut vec![]);
+                    let kind = self.convert(data.kind);
+                    self.declarations.insert(definition, kind);
+                }
+                _ => {}
+            }
+        }
+
+        self.infer.level += 1;
+        let mut inferred = vec![];
+        for (_, declaration) in module.declarations.iter() {
+            let (namespace, name) = match declaration {
+                Declaration::Data(data) => (Namespace::Type, data.name),
+                Declaration::Newtype(newtype) => (Namespace::Type, newtype.name),
+                Declaration::Synonym(synonym) => (Namespace::Type, synonym.name),
+                Declaration::Class(class) => (Namespace::Class, class.name),
+                _ => continue,
+            };
+            let Some(definition) = definition(namespace, name) else { continue };
+            if !self.declarations.contains_key(&definition) {
+                let kind = self.infer.fresh();
+                self.declarations.insert(definition, kind);
+            }
+            inferred.push((definition, declaration));
+        }
+
+        // Headers are unified with the kinds of declarations before any of
+        // their bodies are checked, so misuses of a declaration are reported
+        // where it's used.
+        let mut scopes = vec![];
+        for &(definition, declaration) in &inferred {
+            let (variables, result) = match declaration {
+                Declaration::Data(data) => (&data.variables, "Type"),
+                Declaration::Newtype(newtype) => (&newtype.variables, "Type"),
+                Declaration::Synonym(synonym) => (&synonym.variables, ""),
+                Declaration::Class(class) => (&class.variables, "Constraint"),
+                _ => unreachable!(),
+            };
+            let mut scope = vec![];
+            self.bind(variables, &mut scope);
+            let result = if result.is_empty() { self.infer.fresh() } else { self.prim(result) };
+            let mut header = result;
+            for &(_, parameter) in scope.iter().rev() {
+                header = self.infer.types.function(parameter, header);
+            }
+            match signatures.get(&definition) {
+                Some(&(signature, kind)) => {
+                    let kind = self.infer.instantiate(kind, Origin::Type(signature));
+                    self.infer.unify(header, kind, Origin::Type(signature));
+                }
+                None => {
+                    // The kind is an unknown that nothing has used yet.
+                    let declared = self.declarations[&definition];
+                    let _ = self.infer.unify_types(header, declared);
+                }
+            }
+            scopes.push((scope, result));
+        }
+
+        for (&(_, declaration), (mut variables, result)) in inferred.iter().zip(scopes) {
+            match declaration {
+                Declaration::Data(data) => {
+                    for constructor in &data.constructors {
+                        for &field in &constructor.fields {
+                            self.check_prim(field, "Type", &mut variables);
+                        }
+                    }
+                }
+                Declaration::Newtype(newtype) => {
+                    for &field in &newtype.constructor.fields {
+                        self.check_prim(field, "Type", &mut variables);
+                    }
+                }
+                Declaration::Synonym(synonym) => {
+                    self.check(synonym.type_, result, &mut variables);
+                }
+                Declaration::Class(class) => {
+                    for &constraint in &class.constraints {
+                        self.check_prim(constraint, "Constraint", &mut variables);
+                    }
+                    for member in &class.members {
+                        let mut variables = variables.clone();
+                        self.check_prim(member.type_, "Type", &mut variables);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        self.infer.level -= 1;
+
+        for (definition, _) in inferred {
+            if signatures.contains_key(&definition) {
+                continue;
+            }
+            let kind = self.declarations[&definition];
+            let kind = self.infer.generalize(kind, self.infer.wanted.len());
+            self.declarations.insert(definition, kind);
+        }
+    }
+
+    /// Checks the types of values, instance heads, and the annotations within
+    /// bodies, which must all be of kind `Type` or `Constraint`.
+    fn signatures(&mut self, module: &Module) {
+        for (_, declaration) in module.declarations.iter() {
+            match declaration {
+                Declaration::Signature(signature) => {
+                    self.check_prim(signature.type_, "Type", &mut vec![]);
+                }
+                Declaration::ForeignValue(value) => {
+                    self.check_prim(value.type_, "Type", &mut vec![]);
+                }
+                Declaration::InstanceChain(chain) => {
+                    for instance in chain {
+                        self.instance_head(&instance.head);
+                        for member in &instance.members {
+                            if let lowering::hir::InstanceMember::Signature(signature) = member {
+                                self.check_prim(signature.type_, "Type", &mut vec![]);
+                            }
+                        }
+                    }
+                }
+                Declaration::Derive(derive) => self.instance_head(&derive.head),
+                _ => {}
+            }
+        }
+
+        for (_, body) in module.bodies.iter() {
+            let mut annotations = vec![];
+            guarded_signatures(&body.guarded, &mut annotations);
+            for (_, expr) in body.exprs.iter() {
+                match expr {
+                    &Expr::Typed { type_, .. } => annotations.push(type_),
+                    Expr::LetIn { bindings, .. } => let_signatures(bindings, &mut annotations),
+                    Expr::Case { branches, .. } => {
+                        for branch in branches {
+                            guarded_signatures(&branch.guarded, &mut annotations);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            for (_, binder) in body.binders.iter() {
+                if let &Binder::Typed { type_, .. } = binder {
+                    annotations.push(type_);
+                }
+            }
+            for type_ in annotations {
+                self.check_prim(type_, "Type", &mut vec![]);
+            }
+        }
+    }
+
+    fn instance_head(&mut self, head: &lowering::hir::InstanceHead) {
+        let mut variables = vec![];
+        for &constraint in &head.constraints {
+            self.check_prim(constraint, "Constraint", &mut variables);
+        }
+        let Some(Resolution::Global(class)) = self.scope.resolve(Namespace::Class, head.class)
+        else {
+            for &argument in &head.arguments {
+                self.infer(argument, &mut variables);
+            }
+            return;
+        };
+        let mut kind = self.definition_kind(class);
+        for &argument in &head.arguments {
+            let origin = Origin::Type(argument);
+            let kind_ = self.infer.instantiate(kind, origin);
+            let (parameter, result) = self.infer.expect_function(kind_, origin);
+            self.check(argument, parameter, &mut variables);
+            kind = result;
+        }
+        let constraint = self.prim("Constraint");
+        if let Some(&last) = head.arguments.last() {
+            self.infer.unify(kind, constraint, Origin::Type(last));
+        }
+    }
+
+    fn bind(&mut self, variables: &[TypeVariable], scope: &mut Vec<(Name, TyId)>) {
+        for variable in variables {
+            let kind = match variable.kind {
+                Some(kind) => {
+                    self.check_prim(kind, "Type", &mut vec![]);
+                    self.convert(kind)
+                }
+                None => self.infer.fresh(),
+            };
+            scope.push((variable.name, kind));
+        }
+    }
+
+    fn check(&mut self, id: TypeId, expected: TyId, variables: &mut Vec<(Name, TyId)>) {
+        let kind = self.infer(id, variables);
+        self.infer.unify(kind, expected, Origin::Type(id));
+    }
+
+    fn check_prim(&mut self, id: TypeId, expected: &str, variables: &mut Vec<(Name, TyId)>) {
+        let expected = self.prim(expected);
+        self.check(id, expected, variables);
+    }
+
+    /// Infers the kind of a type. Variables that aren't bound by a `forall`
+    /// are bound implicitly where they're first used.
+    fn infer(&mut self, id: TypeId, variables: &mut Vec<(Name, TyId)>) -> TyId {
+        let origin = Origin::Type(id);
+        match &self.types[id] {
+            Type::Missing => self.error(),
+            Type::Forall { variables: bound, type_ } => {
+                let depth = variables.len();
+                self.bind(bound, variables);
+                let kind = self.infer(*type_, variables);
+                variables.truncate(depth);
+                kind
+            }
+            &Type::Constrained { constraint, type_ } => {
+                self.check_prim(constraint, "Constraint", variables);
+                self.check_prim(type_, "Type", variables);
+                self.prim("Type")
+            }
+            &Type::Arrow { argument, result } => {
+                self.check_prim(argument, "Type", variables);
+                self.check_prim(result, "Type", variables);
+                self.prim("Type")
+            }
+            Type::OperatorChain { .. } => match self.chains.type_(id).cloned() {
+                Some(tree) => self.infer_chain(id, &tree, variables),
+                None => self.error(),
+            },
+            &Type::Kinded { type_, kind } => {
+                self.check_prim(kind, "Type", &mut vec![]);
+                let kind = self.convert(kind);
+                self.check(type_, kind, variables);
+                kind
+            }
+            Type::Application { function, arguments } => {
+                let mut kind = self.infer(*function, variables);
+                for &argument in arguments {
+                    let kind_ = self.infer.instantiate(kind, origin);
+                    let (parameter, result) = self.infer.expect_function(kind_, origin);
+                    self.check(argument, parameter, variables);
+                    kind = result;
+                }
+                kind
+            }
+            Type::Constructor(_) | Type::OperatorName(_) => {
+                let resolution = self.resolution.resolution(id).cloned();
+                let namespace = self.resolution.namespace(id);
+                let kind = match (resolution, namespace) {
+                    (Some(Resolution::Global(definition)), Some(Namespace::TypeOperator)) => {
+                        self.operator_kind(definition)
+                    }
+                    (Some(Resolution::Global(definition)), _) => self.definition_kind(definition),
+                    (Some(Resolution::Prim(name)), namespace) => self.prim_kind(name, namespace),
+                    _ => self.error(),
+                };
+                self.infer.instantiate(kind, origin)
+            }
+            &Type::Variable(name) => {
+                match variables.iter().rev().find(|&&(variable, _)| variable == name) {
+                    Some(&(_, kind)) => kind,
+                    None => {
+                        let kind = self.infer.fresh();
+                        variables.push((name, kind));
+                        kind
+                    }
+                }
+            }
+            Type::Wildcard | Type::Hole(_) => self.infer.fresh(),
+            Type::String(_) => self.prim("Symbol"),
+            Type::Integer(_) => self.prim("Int"),
+            Type::Record(row) => {
+                let type_ = self.prim("Type");
+                self.check_row(row, type_, variables);
+                type_
+            }
+            Type::Row(row) => {
+                let element = self.infer.fresh();
+                self.check_row(row, element, variables);
+                let row = self.prim("Row");
+                self.infer.types.application(row, element)
+            }
+            Type::Function => {
+                let type_ = self.prim("Type");
+                let result = self.infer.types.function(type_, type_);
+                self.infer.types.function(type_, result)
+            }
+            Type::Constraints(constraints) => {
+                for &constraint in constraints {
+                    self.check_prim(constraint, "Constraint", variables);
+                }
+                self.prim("Constraint")
+            }
+        }
+    }
+
+    fn check_row(&mut self, row: &Row, element: TyId, variables: &mut Vec<(Name, TyId)>) {
+        for &(_, field) in &row.fields {
+            self.check(field, element, variables);
+        }
+        if let Some(tail) = row.tail {
+            let row = self.prim("Row");
+            let row = self.infer.types.application(row, element);
+            self.check(tail, row, variables);
+        }
+    }
+
+    fn infer_chain(
+        &mut self,
+        chain: TypeId,
+        tree: &OperatorTree,
+        variables: &mut Vec<(Name, TyId)>,
+    ) -> TyId {
+        let Type::OperatorChain { head, tail } = &self.types[chain] else { unreachable!() };
+        match tree {
+            &OperatorTree::Operand(0) => self.infer(*head, variables),
+            &OperatorTree::Operand(index) => self.infer(tail[index - 1].1, variables),
+            OperatorTree::Operator { index, left, right } => {
+                let kind = match self.resolution.operator_resolution(chain, *index).cloned() {
+                    Some(Resolution::Global(definition)) => self.operator_kind(definition),
+                    _ => self.error(),
+                };
+                let mut kind = self.infer.instantiate(kind, Origin::Type(chain));
+                for operand in [left, right] {
+                    let (parameter, result) = self.infer.expect_function(kind, Origin::Type(chain));
+                    let argument = self.infer_chain(chain, operand, variables);
+                    let origin = match **operand {
+                        OperatorTree::Operand(0) => Origin::Type(*head),
+                        OperatorTree::Operand(index) => Origin::Type(tail[index - 1].1),
+                        OperatorTree::Operator { .. } => Origin::Type(chain),
+                    };
+                    self.infer.unify(argument, parameter, origin);
+                    kind = result;
+                }
+                kind
+            }
+        }
+    }
+
+    /// The kind of a type operator is the kind of the type its fixity
+    /// declaration refers to.
+    fn operator_kind(&mut self, definition: Definition) -> TyId {
+        let workspace = self.infer.workspace;
+        let target = workspace.item_tree(definition.module).and_then(|tree| {
+            let Item::Fixity(fixity) = &tree.items[definition.item] else { return None };
+            let scope = workspace.scope(definition.module)?;
+            match scope.resolve(Namespace::Type, fixity.target)? {
+                Resolution::Global(target) => Some(target),
+                _ => None,
+            }
+        });
+        match target {
+            Some(target) => self.definition_kind(target),
+            None => self.error(),
+        }
+    }
+
+    /// The kind of a type or class. Declarations in other modules aren't
+    /// inferred here, so unless they declare their kind, their parameters
+    /// have unknown kinds.
+    fn definition_kind(&mut self, definition: Definition) -> TyId {
+        if definition.module == self.name {
+            return match self.declarations.get(&definition) {
+                Some(&kind) => kind,
+                None => self.error(),
+            };
+        }
+        let workspace = self.infer.workspace;
+        let Some(tree) = workspace.item_tree(definition.module) else { return self.error() };
+        let (kind, variables, result) = match &tree.items[definition.item] {
+            Item::Data(data) => (data.kind, &data.variables, "Type"),
+            Item::Newtype(newtype) => (newtype.kind, &newtype.variables, "Type"),
+            Item::Synonym(synonym) => (synonym.kind, &synonym.variables, ""),
+            Item::Class(class) => (class.kind, &class.variables, "Constraint"),
+            Item::ForeignData(data) => (Some(data.kind), &vec![], ""),
+            _ => return self.error(),
+        };
+        if let Some(kind) = kind {
+            let kind = self.infer.convert_tree_type(tree, definition.module, kind);
+            return self.quantify(kind);
+        }
+        let mut kind = if result.is_empty() { self.infer.fresh() } else { self.prim(result) };
+        for variable in variables.iter().rev() {
+            let parameter = match variable.kind {
+                Some(parameter) => self.infer.convert_tree_type(tree, definition.module, parameter),
+                None => self.infer.fresh(),
+            };
+            kind = self.infer.types.function(parameter, kind);
+        }
+        self.quantify(kind)
+    }
+
+    fn prim_kind(&mut self, name: Name, namespace: Option<Namespace>) -> TyId {
+        if namespace == Some(Namespace::Class) {
+            return self.prim("Constraint");
+        }
+        let type_ = self.prim("Type");
+        match name.as_str() {
+            "Array" => self.infer.types.function(type_, type_),
+            "Row" => self.infer.types.function(type_, type_),
+            "Record" => {
+                let row = self.prim("Row");
+                let row = self.infer.types.application(row, type_);
+                self.infer.types.function(row, type_)
+            }
+            "Function" => {
+                let result = self.infer.types.function(type_, type_);
+                self.infer.types.function(type_, result)
+            }
+            _ => type_,
+        }
+    }
+
+    /// Converts a kind annotation, where variables are implicitly bound.
+    fn convert(&mut self, kind: TypeId) -> TyId {
+        let kind = self.infer.convert(self.types, self.resolution, kind);
+        self.quantify(kind)
+    }
+
+    /// Binds the free variables of a kind with a `forall`.
+    fn quantify(&mut self, kind: TyId) -> TyId {
+        let mut free = vec![];
+        self.free_variables(kind, &mut vec![], &mut free);
+        let mut kind = kind;
+        for name in free.into_iter().rev() {
+            kind = self.infer.types.alloc(Ty::Forall(name, Visibility::Invisible, kind));
+        }
+        kind
+    }
+
+    fn free_variables(&mut self, ty: TyId, bound: &mut Vec<Name>, free: &mut Vec<Name>) {
+        let ty = self.infer.prune(ty);
+        match self.infer.types[ty].clone() {
+            Ty::Variable(name) if !bound.contains(&name) && !free.contains(&name) => {
+                free.push(name)
+            }
+            Ty::Application(function, argument) => {
+                self.free_variables(function, bound, free);
+                self.free_variables(argument, bound, free);
+            }
+            Ty::Forall(name, _, body) => {
+                bound.push(name);
+                self.free_variables(body, bound, free);
+                bound.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn prim(&mut self, name: &str) -> TyId {
+        self.infer.types.prim(name)
+    }
+
+    fn error(&mut self) -> TyId {
+        self.infer.types.alloc(Ty::Error)
+    }
+}
+
+fn guarded_signatures(guarded: &GuardedExpr, annotations: &mut Vec<TypeId>) {
+    if let GuardedExpr::LetIn { bindings, .. } = guarded {
+        let_signatures(bindings, annotations);
+    }
+}
+
+fn let_signatures(bindings: &[LetBinding], annotations: &mut Vec<TypeId>) {
+    for binding in bindings {
+        match binding {
+            LetBinding::Signature(signature) => annotations.push(signature.type_),
+            LetBinding::Value { guarded, .. } | LetBinding::Pattern { guarded, .. } => {
+                guarded_signatures(guarded, annotations);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{
+        hir::Declaration, item_tree::ItemTree, lower_module_with_source_map, name::ModuleName,
+    };
+    use resolution::{body::ModuleScope, imports::Imports, interface::Namespace};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_kinds;
+    use crate::{infer::Origin, Workspace};
+
+    struct Single(ItemTree, ModuleScope);
+
+    impl Workspace for Single {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            (module == ModuleName::new("Main")).then_some(&self.0)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            (module == ModuleName::new("Main")).then_some(&self.1)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            vec![ModuleName::new("Main")]
+        }
+    }
+
+    #[test]
+    fn infer_declaration_kinds() {
+        let source = "module Main where\n\ndata Proxy a = Proxy\n\ndata Maybe a = Nothing | Just a\n\nnewtype Fix f = Fix (f (Fix f))\n\ntype Pair a = { first :: a, second :: a }\n\nclass Functor f where\n  map :: forall a b. (a -> b) -> f a -> f b\n\nbad :: Maybe\nbad = Nothing\n\nwrong :: Functor Int => Int\nwrong = 0\n\nclass Convert a b\n\ninstance Convert (Array a) b\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let (module, source_map) = lower_module_with_source_map(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let workspace = Single(tree, scope);
+
+        let kinds = check_kinds(&workspace, ModuleName::new("Main"), &module);
+        let mut lines = vec![];
+        for (_, declaration) in module.declarations.iter() {
+            let (namespace, name) = match declaration {
+                Declaration::Data(data) => (Namespace::Type, data.name),
+                Declaration::Newtype(newtype) => (Namespace::Type, newtype.name),
+                Declaration::Synonym(synonym) => (Namespace::Type, synonym.name),
+                Declaration::Class(class) => (Namespace::Class, class.name),
+                _ => continue,
+            };
+            let definition = workspace.1.declarations.names.get(namespace)[&name];
+            let kind = kinds.kind(definition).unwrap();
+            lines.push(format!("{name} :: {}", kinds.types.display(kind)));
+        }
+        for diagnostic in kinds.diagnostics() {
+            let Origin::Type(type_) = diagnostic.origin() else { unreachable!() };
+            let range = source_map.type_syntax(type_).unwrap().text_range();
+            lines.push(format!("{}:", &source[range]));
+            let message = diagnostic.message(&kinds.types);
+            lines.extend(message.lines().map(|line| format!("  {line}")));
+        }
+        assert_eq!(
+            lines,
+            [
+                "Proxy :: forall t0. t0 -> Type",
+                "Maybe :: Type -> Type",
+                "Fix :: (Type -> Type) -> Type",
+                "Pair :: Type -> Type",
+                "Functor :: (Type -> Type) -> Constraint",
+                "Convert :: forall t0 t1. t0 -> t1 -> Constraint",
+                "Maybe:",
+                "  expected  Type",
+                "            ^^^^",
+                "  but found Type -> Type",
+                "            ^^^^^^^^^^^^",
+                "Int:",
+                "  expected  Type -> Type",
+                "            ^^^^^^^^^^^^",
+                "  but found Type",
+                "            ^^^^",
+            ]
+        );
+    }
+}
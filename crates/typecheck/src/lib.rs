@@ -0,0 +1,24 @@
+pub mod derive;
+pub mod infer;
+pub mod kinds;
+pub mod patterns;
+mod prim;
+pub mod query;
+pub mod solve;
+pub mod ty;
+
+use lowering::{item_tree::ItemTree, name::ModuleName};
+use resolution::body::ModuleScope;
+
+/// The modules of a workspace, as seen by the type checker. Signatures are
+/// read from the [`ItemTree`] of the module that declares them, and resolved
+/// against its [`ModuleScope`].
+pub trait Workspace {
+    fn item_tree(&self, module: ModuleName) -> Option<&ItemTree>;
+
+    fn scope(&self, module: ModuleName) -> Option<&ModuleScope>;
+
+    /// Every module in the workspace, which is where instances are
+    /// collected from.
+    fn modules(&self) -> Vec<ModuleName>;
+}
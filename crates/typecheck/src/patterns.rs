@@ -0,0 +1,585 @@
+//! Exhaustiveness and redundancy checking of pattern matches.
+//!
+//! Each `case` expression, including the ones that the equations of a value
+//! are lowered to, is checked as a matrix of patterns with a row for each
+//! branch, using the usefulness algorithm from Maranget's "Warnings for
+//! pattern matching". A branch is redundant if it isn't useful after the
+//! branches before it, and the patterns that would be useful after every
+//! branch are the ones that are missing.
+
+use std::fmt;
+
+use lowering::{
+    arena::ArenaMap,
+    hir::{
+        Binder, BinderId, Body, BodyId, CaseBranch, Expr, ExprId, Guard, GuardedExpr, Literal,
+        Module,
+    },
+    item_tree::Item,
+    name::{ModuleName, Name},
+};
+use resolution::{
+    body::{BodyResolution, Resolution},
+    fixity::{Chains, Fixities, OperatorTree},
+    interface::{Definition, Namespace},
+};
+
+use crate::{infer::Origin, Workspace};
+
+/// How many missing patterns are reported for a single match, since there
+/// can be exponentially many of them.
+const MISSING_LIMIT: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternDiagnostic {
+    /// Rows of patterns that no branch matches, reported at the `case`
+    /// expression or lambda. Each row has a pattern for every scrutinee.
+    Missing { origin: Origin, patterns: Vec<String> },
+    /// A branch that only matches values that earlier branches already do,
+    /// reported at its first binder.
+    Redundant { origin: Origin },
+}
+
+impl PatternDiagnostic {
+    pub fn message(&self) -> String {
+        match self {
+            PatternDiagnostic::Missing { patterns, .. } => {
+                format!("patterns not matched: {}", patterns.join(", "))
+            }
+            PatternDiagnostic::Redundant { .. } => "this branch is unreachable".to_string(),
+        }
+    }
+
+    pub fn origin(&self) -> Origin {
+        match self {
+            PatternDiagnostic::Missing { origin, .. } | PatternDiagnostic::Redundant { origin } => {
+                *origin
+            }
+        }
+    }
+}
+
+/// Checks the pattern matches in every body of `module`.
+pub fn check_patterns(
+    workspace: &dyn Workspace,
+    name: ModuleName,
+    module: &Module,
+) -> ArenaMap<BodyId, Vec<PatternDiagnostic>> {
+    let mut bodies = ArenaMap::default();
+    let Some(scope) = workspace.scope(name) else { return bodies };
+    let fixities = Fixities::new(scope, |module| workspace.item_tree(module));
+    for (id, body) in module.bodies.iter() {
+        let resolution = BodyResolution::new(body, scope);
+        let chains = Chains::body(body, &resolution, &fixities);
+        let checker = Checker { workspace, body, resolution: &resolution, chains: &chains };
+        let mut diagnostics = vec![];
+        for (expr, _) in body.exprs.iter() {
+            checker.check(expr, &mut diagnostics);
+        }
+        bodies.insert(id, diagnostics);
+    }
+    bodies
+}
+
+/// The constructor of a pattern, where records are a single constructor
+/// over the labels that any pattern in the same column mentions.
+#[derive(Debug, Clone, PartialEq)]
+enum Head {
+    Constructor(Definition),
+    Boolean(bool),
+    Int(i32),
+    Number(u64),
+    String(String),
+    Char(char),
+    Array(usize),
+    Record(Vec<Name>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    Wildcard,
+    Constructor(Head, Vec<Pattern>),
+}
+
+struct Checker<'a> {
+    workspace: &'a dyn Workspace,
+    body: &'a Body,
+    resolution: &'a BodyResolution,
+    chains: &'a Chains,
+}
+
+impl Checker<'_> {
+    fn check(&self, id: ExprId, diagnostics: &mut Vec<PatternDiagnostic>) {
+        let (arity, rows): (_, Vec<_>) = match &self.body.exprs[id] {
+            Expr::Case { scrutinees, branches } => (
+                scrutinees.len(),
+                branches
+                    .iter()
+                    .map(|CaseBranch { binders, guarded }| (binders.clone(), self.covers(guarded)))
+                    .collect(),
+            ),
+            Expr::Lambda { binders, .. } => (binders.len(), vec![(binders.clone(), true)]),
+            _ => return,
+        };
+        // Branches with the wrong number of binders, such as ones that are
+        // still being typed, can't be compared with the rest of the matrix.
+        let rows: Vec<_> = rows.into_iter().filter(|(binders, _)| binders.len() == arity).collect();
+        if arity == 0 || rows.is_empty() {
+            return;
+        }
+
+        let mut matrix: Vec<Vec<Pattern>> = vec![];
+        for (binders, covers) in rows {
+            let row: Vec<_> = binders.iter().map(|&binder| self.pattern(binder)).collect();
+            if !self.useful(&matrix, &row) {
+                if let Some(&first) = binders.first() {
+                    let origin = Origin::Binder(first);
+                    diagnostics.push(PatternDiagnostic::Redundant { origin });
+                }
+            }
+            // Branches with guards may fall through, so they don't cover
+            // anything for the branches after them.
+            if covers {
+                matrix.push(row);
+            }
+        }
+
+        let missing = self.missing(&matrix, arity);
+        if !missing.is_empty() {
+            let patterns = missing
+                .iter()
+                .map(|row| {
+                    let row: Vec<_> = row
+                        .iter()
+                        .map(|pattern| self.display(pattern, arity > 1).to_string())
+                        .collect();
+                    row.join(" ")
+                })
+                .collect();
+            diagnostics.push(PatternDiagnostic::Missing { origin: Origin::Expr(id), patterns });
+        }
+    }
+
+    /// Whether a branch matches whenever its binders do, i.e. one of its
+    /// alternatives only has guards that are `otherwise` or `true`.
+    fn covers(&self, guarded: &GuardedExpr) -> bool {
+        let branches = match guarded {
+            GuardedExpr::Unconditional(_) => return true,
+            GuardedExpr::Guarded(branches) | GuardedExpr::LetIn { branches, .. } => branches,
+        };
+        let trivial = |guard: &Guard| {
+            let &Guard::Boolean(condition) = guard else { return false };
+            match &self.body.exprs[condition] {
+                Expr::Literal(Literal::Boolean(true)) => true,
+                Expr::Variable(name) => {
+                    name.qualifier.is_none() && name.name.as_str() == "otherwise"
+                }
+                _ => false,
+            }
+        };
+        branches.iter().any(|branch| branch.guards.iter().all(trivial))
+    }
+
+    fn pattern(&self, id: BinderId) -> Pattern {
+        match &self.body.binders[id] {
+            Binder::Missing | Binder::Wildcard | Binder::Variable(_) => Pattern::Wildcard,
+            &Binder::Named { binder, .. } | &Binder::Typed { binder, .. } => self.pattern(binder),
+            Binder::Constructor { arguments, .. } => {
+                let Some(Resolution::Global(definition)) = self.resolution.binder_resolution(id)
+                else {
+                    return Pattern::Wildcard;
+                };
+                // Constructors applied to the wrong number of arguments are
+                // reported by type checking.
+                let head = Head::Constructor(*definition);
+                if arguments.len() != self.arity(&head) {
+                    return Pattern::Wildcard;
+                }
+                let arguments = arguments.iter().map(|&argument| self.pattern(argument));
+                Pattern::Constructor(head, arguments.collect())
+            }
+            Binder::Literal(literal) => match literal {
+                &Literal::Int(value) => Pattern::Constructor(Head::Int(value), vec![]),
+                Literal::Number(value) => {
+                    Pattern::Constructor(Head::Number(value.0.to_bits()), vec![])
+                }
+                Literal::String(value) => Pattern::Constructor(Head::String(value.clone()), vec![]),
+                &Literal::Char(value) => Pattern::Constructor(Head::Char(value), vec![]),
+                &Literal::Boolean(value) => Pattern::Constructor(Head::Boolean(value), vec![]),
+                Literal::Array(elements) => {
+                    let elements = elements.iter().map(|&element| self.pattern(element));
+                    Pattern::Constructor(Head::Array(elements.len()), elements.collect())
+                }
+                Literal::Record(fields) => {
+                    let labels = fields.iter().map(|&(label, _)| label).collect();
+                    let fields = fields.iter().map(|&(_, field)| self.pattern(field));
+                    Pattern::Constructor(Head::Record(labels), fields.collect())
+                }
+            },
+            Binder::OperatorChain { .. } => match self.chains.binder(id) {
+                Some(tree) => self.chain_pattern(id, tree),
+                None => Pattern::Wildcard,
+            },
+        }
+    }
+
+    fn chain_pattern(&self, chain: BinderId, tree: &OperatorTree) -> Pattern {
+        let Binder::OperatorChain { head, tail } = &self.body.binders[chain] else {
+            unreachable!()
+        };
+        match tree {
+            &OperatorTree::Operand(0) => self.pattern(*head),
+            &OperatorTree::Operand(index) => self.pattern(tail[index - 1].1),
+            OperatorTree::Operator { index, left, right } => {
+                let Some(constructor) = self.operator_constructor(chain, *index) else {
+                    return Pattern::Wildcard;
+                };
+                let arguments =
+                    vec![self.chain_pattern(chain, left), self.chain_pattern(chain, right)];
+                Pattern::Constructor(Head::Constructor(constructor), arguments)
+            }
+        }
+    }
+
+    /// The constructor an operator in a binder is an alias for.
+    fn operator_constructor(&self, chain: BinderId, index: usize) -> Option<Definition> {
+        let Resolution::Global(fixity) =
+            self.resolution.binder_operator_resolution(chain, index)?
+        else {
+            return None;
+        };
+        let tree = self.workspace.item_tree(fixity.module)?;
+        let Item::Fixity(item) = &tree.items[fixity.item] else { return None };
+        let scope = self.workspace.scope(fixity.module)?;
+        match scope.resolve(Namespace::Constructor, item.target)? {
+            Resolution::Global(constructor) => Some(constructor),
+            _ => None,
+        }
+    }
+
+    // Usefulness
+
+    /// Whether `row` matches any value that no row of `matrix` does.
+    fn useful(&self, matrix: &[Vec<Pattern>], row: &[Pattern]) -> bool {
+        let Some((first, rest)) = row.split_first() else { return matrix.is_empty() };
+        match first {
+            Pattern::Constructor(head, arguments) => {
+                let head = self.column_head(matrix, head);
+                let mut row =
+                    self.specialize_row(&head, first).unwrap_or_else(|| arguments.clone());
+                row.extend(rest.iter().cloned());
+                self.useful(&self.specialize(matrix, &head), &row)
+            }
+            Pattern::Wildcard => match self.signature(matrix) {
+                Some(heads) => heads.into_iter().any(|head| {
+                    let mut row = vec![Pattern::Wildcard; self.arity(&head)];
+                    row.extend(rest.iter().cloned());
+                    self.useful(&self.specialize(matrix, &head), &row)
+                }),
+                None => self.useful(&self.default(matrix), rest),
+            },
+        }
+    }
+
+    /// Rows of `arity` patterns that no row of `matrix` matches.
+    fn missing(&self, matrix: &[Vec<Pattern>], arity: usize) -> Vec<Vec<Pattern>> {
+        if arity == 0 {
+            return if matrix.is_empty() { vec![vec![]] } else { vec![] };
+        }
+        if let Some(heads) = self.signature(matrix) {
+            let mut missing = vec![];
+            for head in heads {
+                let head_arity = self.arity(&head);
+                let specialized = self.specialize(matrix, &head);
+                for mut row in self.missing(&specialized, head_arity + arity - 1) {
+                    let rest = row.split_off(head_arity);
+                    let mut pattern = vec![Pattern::Constructor(head.clone(), row)];
+                    pattern.extend(rest);
+                    missing.push(pattern);
+                    if missing.len() == MISSING_LIMIT {
+                        return missing;
+                    }
+                }
+            }
+            return missing;
+        }
+
+        let rows = self.missing(&self.default(matrix), arity - 1);
+        let first = self.missing_head(matrix).map_or(Pattern::Wildcard, |head| {
+            let arity = self.arity(&head);
+            Pattern::Constructor(head, vec![Pattern::Wildcard; arity])
+        });
+        let rows = rows.into_iter().map(|rest| {
+            let mut row = vec![first.clone()];
+            row.extend(rest);
+            row
+        });
+        rows.collect()
+    }
+
+    /// Every head of the type matched by the first column of `matrix`, if
+    /// each of them appears in it.
+    fn signature(&self, matrix: &[Vec<Pattern>]) -> Option<Vec<Head>> {
+        let heads = self.heads(matrix);
+        let first = heads.first()?;
+        let signature = match first {
+            Head::Constructor(definition) => self.constructors(*definition)?,
+            Head::Boolean(_) => vec![Head::Boolean(true), Head::Boolean(false)],
+            Head::Record(_) => return Some(vec![self.column_head(matrix, first)]),
+            _ => return None,
+        };
+        signature.iter().all(|head| heads.contains(head)).then_some(signature)
+    }
+
+    /// A head of the type matched by the first column of `matrix` that
+    /// doesn't appear in it, if the type has finitely many.
+    fn missing_head(&self, matrix: &[Vec<Pattern>]) -> Option<Head> {
+        let heads = self.heads(matrix);
+        let signature = match heads.first()? {
+            Head::Constructor(definition) => self.constructors(*definition)?,
+            Head::Boolean(_) => vec![Head::Boolean(true), Head::Boolean(false)],
+            _ => return None,
+        };
+        signature.into_iter().find(|head| !heads.contains(head))
+    }
+
+    fn heads(&self, matrix: &[Vec<Pattern>]) -> Vec<Head> {
+        let mut heads = vec![];
+        for row in matrix {
+            if let Pattern::Constructor(head, _) = &row[0] {
+                if !heads.contains(head) {
+                    heads.push(head.clone());
+                }
+            }
+        }
+        heads
+    }
+
+    /// Widens a record head to every label that the first column of `matrix`
+    /// mentions.
+    fn column_head(&self, matrix: &[Vec<Pattern>], head: &Head) -> Head {
+        let Head::Record(labels) = head else { return head.clone() };
+        let mut labels = labels.clone();
+        for row in matrix {
+            if let Pattern::Constructor(Head::Record(other), _) = &row[0] {
+                for &label in other {
+                    if !labels.contains(&label) {
+                        labels.push(label);
+                    }
+                }
+            }
+        }
+        Head::Record(labels)
+    }
+
+    fn arity(&self, head: &Head) -> usize {
+        match head {
+            &Head::Constructor(definition) => self.constructor(definition).map_or(0, |(_, n)| n),
+            &Head::Array(length) => length,
+            Head::Record(labels) => labels.len(),
+            _ => 0,
+        }
+    }
+
+    /// The rows of `matrix` that match `head`, with the first column replaced
+    /// by the arguments of the head.
+    fn specialize(&self, matrix: &[Vec<Pattern>], head: &Head) -> Vec<Vec<Pattern>> {
+        let rows = matrix.iter().filter_map(|row| {
+            let mut specialized = self.specialize_row(head, &row[0])?;
+            specialized.extend(row[1..].iter().cloned());
+            Some(specialized)
+        });
+        rows.collect()
+    }
+
+    /// The arguments of `pattern` if it matches `head`, where wildcards
+    /// match anything.
+    fn specialize_row(&self, head: &Head, pattern: &Pattern) -> Option<Vec<Pattern>> {
+        match (head, pattern) {
+            (_, Pattern::Wildcard) => Some(vec![Pattern::Wildcard; self.arity(head)]),
+            (Head::Record(labels), Pattern::Constructor(Head::Record(fields), arguments)) => {
+                let arguments = labels.iter().map(|label| {
+                    let position = fields.iter().position(|field| field == label);
+                    position.map_or(Pattern::Wildcard, |position| arguments[position].clone())
+                });
+                Some(arguments.collect())
+            }
+            (_, Pattern::Constructor(other, arguments)) if other == head => Some(arguments.clone()),
+            _ => None,
+        }
+    }
+
+    /// The rows of `matrix` whose first pattern is a wildcard, without it.
+    fn default(&self, matrix: &[Vec<Pattern>]) -> Vec<Vec<Pattern>> {
+        let rows = matrix.iter().filter(|row| row[0] == Pattern::Wildcard);
+        rows.map(|row| row[1..].to_vec()).collect()
+    }
+
+    // Constructors
+
+    /// The name and number of fields of a constructor.
+    fn constructor(&self, definition: Definition) -> Option<(Name, usize)> {
+        let tree = self.workspace.item_tree(definition.module)?;
+        match &tree.items[definition.item] {
+            Item::Data(data) => {
+                let constructor = data.constructors.get(definition.member?)?;
+                Some((constructor.name, constructor.fields.len()))
+            }
+            Item::Newtype(newtype) => Some((newtype.constructor.name, 1)),
+            _ => None,
+        }
+    }
+
+    /// Every constructor of the type that declares `definition`.
+    fn constructors(&self, definition: Definition) -> Option<Vec<Head>> {
+        let tree = self.workspace.item_tree(definition.module)?;
+        let count = match &tree.items[definition.item] {
+            Item::Data(data) => data.constructors.len(),
+            Item::Newtype(_) => 1,
+            _ => return None,
+        };
+        let constructors = (0..count)
+            .map(|member| Head::Constructor(Definition { member: Some(member), ..definition }));
+        Some(constructors.collect())
+    }
+
+    fn display<'a>(&'a self, pattern: &'a Pattern, atom: bool) -> impl fmt::Display + 'a {
+        PatternDisplay { checker: self, pattern, atom }
+    }
+}
+
+struct PatternDisplay<'a> {
+    checker: &'a Checker<'a>,
+    pattern: &'a Pattern,
+    /// Whether the pattern is an argument, which needs parentheses if it
+    /// has arguments of its own.
+    atom: bool,
+}
+
+impl fmt::Display for PatternDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Pattern::Constructor(head, arguments) = self.pattern else { return write!(f, "_") };
+        let argument = |pattern, atom| PatternDisplay { checker: self.checker, pattern, atom };
+        match head {
+            &Head::Constructor(definition) => {
+                let name = self.checker.constructor(definition).map(|(name, _)| name);
+                let name = name.map_or("?".to_string(), |name| name.to_string());
+                if arguments.is_empty() {
+                    return write!(f, "{name}");
+                }
+                if self.atom {
+                    write!(f, "(")?;
+                }
+                write!(f, "{name}")?;
+                for pattern in arguments {
+                    write!(f, " {}", argument(pattern, true))?;
+                }
+                if self.atom {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Head::Boolean(value) => write!(f, "{value}"),
+            Head::Int(value) => write!(f, "{value}"),
+            Head::Number(bits) => write!(f, "{}", f64::from_bits(*bits)),
+            Head::String(value) => write!(f, "{value:?}"),
+            Head::Char(value) => write!(f, "{value:?}"),
+            Head::Array(_) => {
+                let elements: Vec<_> =
+                    arguments.iter().map(|pattern| argument(pattern, false).to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Head::Record(labels) => {
+                let fields: Vec<_> = labels
+                    .iter()
+                    .zip(arguments)
+                    .map(|(label, pattern)| format!("{label}: {}", argument(pattern, false)))
+                    .collect();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{hir::Declaration, item_tree::ItemTree, lower_module, name::ModuleName};
+    use resolution::{body::ModuleScope, imports::Imports};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::check_patterns;
+    use crate::Workspace;
+
+    struct Single(ItemTree, ModuleScope);
+
+    impl Workspace for Single {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            (module == ModuleName::new("Main")).then_some(&self.0)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            (module == ModuleName::new("Main")).then_some(&self.1)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            vec![ModuleName::new("Main")]
+        }
+    }
+
+    fn diagnostics(source: &str) -> Vec<String> {
+        let (node, _) = parsing::parse_module(source);
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let module = lower_module(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let workspace = Single(tree, scope);
+
+        let bodies = check_patterns(&workspace, ModuleName::new("Main"), &module);
+        let mut lines = vec![];
+        for (_, declaration) in module.declarations.iter() {
+            let Declaration::Value(value) = declaration else { continue };
+            for diagnostic in bodies.get(value.body).unwrap() {
+                lines.push(format!("{}: {}", value.name, diagnostic.message()));
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn report_missing_and_redundant_patterns() {
+        let source = "module Main where\n\ndata Maybe a = Nothing | Just a\n\nfromMaybe m = case m of\n  Just x -> x\n\nboth a b = case a, b of\n  true, true -> 1\n  false, _ -> 2\n\nredundant m = case m of\n  Just _ -> 1\n  Nothing -> 2\n  Just 1 -> 3\n\nguarded x = case x of\n  Just y | y -> 1\n  Nothing -> 0\n\nrecord r = case r of\n  { a: Just _ } -> 1\n  { b: 0 } -> 2\n\nfirst 0 = 1\nfirst n = n\n\nnested (Just (Just _)) = 1\nnested Nothing = 0\n";
+        assert!(parsing::parse_module(source).1.is_empty());
+        assert_eq!(
+            diagnostics(source),
+            [
+                "fromMaybe: patterns not matched: Nothing",
+                "both: patterns not matched: true false",
+                "redundant: this branch is unreachable",
+                "guarded: patterns not matched: Just _",
+                "record: patterns not matched: { a: Nothing, b: _ }",
+                "nested: patterns not matched: Just Nothing",
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_branches_of_another_arity() {
+        let branches = [
+            "case a, b of\n  0, _ -> 1\n  x -> x",
+            "case a of\n  true, _ -> 1\n  _ -> 2",
+            "case a of\n  1. -> 1\n  _ -> 2",
+        ];
+        let strays = ["@", "?", "\\", "|", "\"", "::", "$"];
+        let strays = strays.map(|stray| format!("case a of\n  {stray}x -> 1\n  _ -> 2"));
+        for case in branches.iter().copied().chain(strays.iter().map(String::as_str)) {
+            let source = format!("module Main where\n\nf a b = {}\n", case.replace('\n', "\n  "));
+            diagnostics(&source);
+        }
+        // The branches that do match the scrutinees are still checked.
+        assert_eq!(
+            diagnostics("module Main where\n\nf a b = case a, b of\n  true, _ -> 1\n  x -> x\n"),
+            ["f: patterns not matched: false _"]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Solving of the classes in the submodules of `Prim`, which the compiler
+//! solves itself rather than through instances.
+//!
+//! Each class is solved once the arguments that determine the others are
+//! known, like an instance chosen through a functional dependency, and is
+//! stuck until then.
+
+use lowering::{
+    item_tree::Item,
+    name::{ModuleName, Name},
+};
+use resolution::interface::Definition;
+
+use crate::{
+    infer::{Infer, Origin},
+    solve::Entailment,
+    ty::{Constraint, Ty, TyId, TypeConstructor},
+};
+
+type Fields = Vec<(Name, TyId)>;
+
+impl Infer<'_> {
+    /// Solves a constraint on a class of `Prim.Row`, `Prim.RowList`, or
+    /// `Prim.Symbol`. Other classes in `Prim`, such as `Prim.TypeError.Warn`,
+    /// are trusted to hold.
+    pub(crate) fn entail_prim(
+        &mut self,
+        module: ModuleName,
+        constraint: &Constraint,
+        origin: Origin,
+    ) -> Entailment {
+        let class = (module.as_str(), constraint.class.name.as_str());
+        let entailment = match (class, constraint.arguments.as_slice()) {
+            (("Prim.Row", "Cons"), &[label, ty, tail, row]) => {
+                self.row_cons(label, ty, tail, row, origin)
+            }
+            (("Prim.Row", "Lacks"), &[label, row]) => self.row_lacks(constraint, label, row),
+            (("Prim.Row", "Union"), &[left, right, union]) => {
+                self.row_union(constraint, left, right, union, origin)
+            }
+            (("Prim.Row", "Nub"), &[original, nubbed]) => {
+                self.row(original).filter(|(_, tail)| tail.is_none()).map(|(fields, _)| {
+                    let mut nubbed_fields: Fields = vec![];
+                    for (label, ty) in fields {
+                        if !nubbed_fields.iter().any(|&(other, _)| other == label) {
+                            nubbed_fields.push((label, ty));
+                        }
+                    }
+                    let row = self.types.alloc(Ty::Row(nubbed_fields, None));
+                    self.improve(&[(nubbed, row)], origin)
+                })
+            }
+            (("Prim.RowList", "RowToList"), &[row, list]) => {
+                self.row(row).filter(|(_, tail)| tail.is_none()).map(|(mut fields, _)| {
+                    // Labels that repeat keep their order.
+                    fields.sort_by_key(|(label, _)| label.as_str());
+                    let mut ty = self.prim_type("Prim.RowList", "Nil");
+                    let cons = self.prim_type("Prim.RowList", "Cons");
+                    for (label, field) in fields.into_iter().rev() {
+                        let label = self.types.alloc(Ty::Symbol(label.as_str().to_string()));
+                        let applied = self.types.application(cons, label);
+                        let applied = self.types.application(applied, field);
+                        ty = self.types.application(applied, ty);
+                    }
+                    self.improve(&[(list, ty)], origin)
+                })
+            }
+            (("Prim.Symbol", "Append"), &[left, right, appended]) => {
+                match (self.symbol(left), self.symbol(right), self.symbol(appended)) {
+                    (Some(left), Some(right), _) => {
+                        let ty = self.types.alloc(Ty::Symbol(left + &right));
+                        Some(self.improve(&[(appended, ty)], origin))
+                    }
+                    (Some(left), None, Some(appended)) => {
+                        let rest = appended.strip_prefix(&left).map(str::to_string);
+                        Some(self.improve_symbol(right, rest, origin))
+                    }
+                    (None, Some(right), Some(appended)) => {
+                        let rest = appended.strip_suffix(&right).map(str::to_string);
+                        Some(self.improve_symbol(left, rest, origin))
+                    }
+                    _ => None,
+                }
+            }
+            (("Prim.Symbol", "Compare"), &[left, right, ordering]) => {
+                match (self.symbol(left), self.symbol(right)) {
+                    (Some(left), Some(right)) => {
+                        let name = match left.cmp(&right) {
+                            std::cmp::Ordering::Less => "LT",
+                            std::cmp::Ordering::Equal => "EQ",
+                            std::cmp::Ordering::Greater => "GT",
+                        };
+                        let ty = self.prim_type("Prim.Ordering", name);
+                        Some(self.improve(&[(ordering, ty)], origin))
+                    }
+                    _ => None,
+                }
+            }
+            (("Prim.Symbol", "Cons"), &[head, tail, symbol]) => {
+                match (self.symbol(head), self.symbol(tail), self.symbol(symbol)) {
+                    (Some(head), Some(tail), _) if head.chars().count() == 1 => {
+                        let ty = self.types.alloc(Ty::Symbol(head + &tail));
+                        Some(self.improve(&[(symbol, ty)], origin))
+                    }
+                    (Some(_), Some(_), _) => Some(Entailment::Failed),
+                    (_, _, Some(symbol)) => {
+                        let mut characters = symbol.chars();
+                        match characters.next() {
+                            Some(first) => {
+                                let first = self.types.alloc(Ty::Symbol(first.to_string()));
+                                let rest = self.types.alloc(Ty::Symbol(characters.collect()));
+                                Some(self.improve(&[(head, first), (tail, rest)], origin))
+                            }
+                            None => Some(Entailment::Failed),
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => Some(Entailment::Solved(vec![])),
+        };
+        entailment.unwrap_or_else(|| self.undetermined(constraint))
+    }
+
+    /// `Cons label ty tail row` holds when `row` is `tail` with `label`
+    /// added in front, so either row determines the other.
+    fn row_cons(
+        &mut self,
+        label: TyId,
+        ty: TyId,
+        tail: TyId,
+        row: TyId,
+        origin: Origin,
+    ) -> Option<Entailment> {
+        let label = Name::new(&self.symbol(label)?);
+        if let Some((mut fields, rest)) = self.row(row) {
+            if let Some(index) = fields.iter().position(|&(other, _)| other == label) {
+                let (_, field) = fields.remove(index);
+                let remaining = self.types.alloc(Ty::Row(fields, rest));
+                return Some(self.improve(&[(ty, field), (tail, remaining)], origin));
+            }
+        }
+        let (fields, rest) = self.row(tail)?;
+        let mut extended = vec![(label, ty)];
+        extended.extend(fields);
+        let extended = self.types.alloc(Ty::Row(extended, rest));
+        Some(self.improve(&[(row, extended)], origin))
+    }
+
+    /// `Lacks label row` holds when neither the fields of `row` nor its
+    /// tail have `label`.
+    fn row_lacks(&mut self, constraint: &Constraint, label: TyId, row: TyId) -> Option<Entailment> {
+        let label = Name::new(&self.symbol(label)?);
+        let (fields, tail) = self.row(row)?;
+        if fields.iter().any(|&(other, _)| other == label) {
+            return Some(Entailment::Failed);
+        }
+        match tail {
+            None => Some(Entailment::Solved(vec![])),
+            Some(tail) if !fields.is_empty() => {
+                let arguments = vec![constraint.arguments[0], tail];
+                Some(Entailment::Solved(vec![Constraint { class: constraint.class, arguments }]))
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// `Union left right union` holds when `union` has the fields of `left`
+    /// followed by those of `right`. A closed `left` determines `union`,
+    /// while an open one determines its fields and leaves the union of its
+    /// tail, and closed `union` and `right` determine `left`.
+    fn row_union(
+        &mut self,
+        constraint: &Constraint,
+        left: TyId,
+        right: TyId,
+        union: TyId,
+        origin: Origin,
+    ) -> Option<Entailment> {
+        match self.row(left) {
+            Some((fields, None)) => {
+                let row = match self.row(right) {
+                    Some((right_fields, tail)) => {
+                        Ty::Row(fields.into_iter().chain(right_fields).collect(), tail)
+                    }
+                    None => Ty::Row(fields, Some(right)),
+                };
+                let row = self.types.alloc(row);
+                return Some(self.improve(&[(union, row)], origin));
+            }
+            Some((fields, Some(tail))) if !fields.is_empty() => {
+                let rest = self.fresh();
+                let row = self.types.alloc(Ty::Row(fields, Some(rest)));
+                self.unify(union, row, origin);
+                let arguments = vec![tail, right, rest];
+                let constraint = Constraint { class: constraint.class, arguments };
+                return Some(Entailment::Solved(vec![constraint]));
+            }
+            _ => {}
+        }
+
+        let (Some((mut fields, None)), Some((right_fields, None))) =
+            (self.row(union), self.row(right))
+        else {
+            return None;
+        };
+        let mut improvements = vec![];
+        for (label, ty) in right_fields.into_iter().rev() {
+            let Some(index) = fields.iter().rposition(|&(other, _)| other == label) else {
+                return Some(Entailment::Failed);
+            };
+            let (_, field) = fields.remove(index);
+            improvements.push((ty, field));
+        }
+        let row = self.types.alloc(Ty::Row(fields, None));
+        improvements.push((left, row));
+        Some(self.improve(&improvements, origin))
+    }
+
+    /// Unifies each argument with the type it's determined to be.
+    fn improve(&mut self, improvements: &[(TyId, TyId)], origin: Origin) -> Entailment {
+        for &(actual, expected) in improvements {
+            self.unify(actual, expected, origin);
+        }
+        Entailment::Solved(vec![])
+    }
+
+    fn improve_symbol(&mut self, ty: TyId, value: Option<String>, origin: Origin) -> Entailment {
+        match value {
+            Some(value) => {
+                let expected = self.types.alloc(Ty::Symbol(value));
+                self.improve(&[(ty, expected)], origin)
+            }
+            None => Entailment::Failed,
+        }
+    }
+
+    fn undetermined(&mut self, constraint: &Constraint) -> Entailment {
+        if self.has_unknowns(constraint) {
+            Entailment::Stuck
+        } else {
+            Entailment::Failed
+        }
+    }
+
+    fn symbol(&mut self, ty: TyId) -> Option<String> {
+        let ty = self.prune(ty);
+        match &self.types[ty] {
+            Ty::Symbol(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// The fields and tail of a row, once it's known to be one.
+    fn row(&mut self, ty: TyId) -> Option<(Fields, Option<TyId>)> {
+        let ty = self.zonk(ty);
+        match &self.types[ty] {
+            Ty::Row(fields, tail) => Some((fields.clone(), *tail)),
+            _ => None,
+        }
+    }
+
+    /// A type declared by a submodule of `Prim`, such as `Prim.Ordering.LT`.
+    fn prim_type(&mut self, module: &str, name: &str) -> TyId {
+        let module = ModuleName::new(module);
+        let name = Name::new(name);
+        let tree = self.workspace.item_tree(module);
+        let item = tree.and_then(|tree| {
+            tree.iter().find_map(|(item, kind)| match kind {
+                Item::ForeignData(data) if data.name == name => Some(item),
+                _ => None,
+            })
+        });
+        match item {
+            Some(item) => {
+                let definition = Some(Definition { module, item, member: None });
+                self.types.alloc(Ty::Constructor(TypeConstructor { name, definition }))
+            }
+            None => self.types.alloc(Ty::Error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::{hir::Declaration, item_tree::ItemTree, lower_module, name::ModuleName};
+    use resolution::{body::ModuleScope, imports::Imports, interface::Interface, prim};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use crate::{infer::check_module, Workspace};
+
+    struct Modules(Vec<(ItemTree, ModuleScope)>);
+
+    impl Workspace for Modules {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            self.0.iter().find(|(tree, _)| tree.name == Some(module)).map(|(tree, _)| tree)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            self.0.iter().find(|(tree, _)| tree.name == Some(module)).map(|(_, scope)| scope)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            self.0.iter().filter_map(|(tree, _)| tree.name).collect()
+        }
+    }
+
+    #[test]
+    fn solve_prim_classes() {
+        let source = "module Main where\n\nimport Prim.Row as Row\nimport Prim.RowList (class RowToList)\nimport Prim.Symbol as Symbol\n\ndata Proxy a = Proxy\n\nforeign import insert :: forall l a r1 r2. Row.Cons l a r1 r2 => Row.Lacks l r1 => Proxy l -> a -> Record r1 -> Record r2\nforeign import union :: forall r1 r2 r3. Row.Union r1 r2 r3 => Record r1 -> Record r2 -> Record r3\nforeign import append :: forall l r o. Symbol.Append l r o => Proxy l -> Proxy r -> Proxy o\nforeign import compare :: forall l r o. Symbol.Compare l r o => Proxy l -> Proxy r -> Proxy o\nforeign import toList :: forall r l. RowToList r l => Proxy r -> Proxy l\n\ninserted = insert (Proxy :: Proxy \"b\") 1 { a: \"x\" }\n\nmerged = union { a: 1 } { b: \"x\" }\n\nappended = append (Proxy :: Proxy \"ab\") (Proxy :: Proxy \"cd\")\n\ncompared = compare (Proxy :: Proxy \"a\") (Proxy :: Proxy \"b\")\n\nlisted = toList (Proxy :: Proxy (b :: Int, a :: String))\n\nduplicate = insert (Proxy :: Proxy \"a\") 1 { a: \"x\" }\n";
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let mut trees = prim::modules();
+        trees.push(ItemTree::lower(&root));
+        let interfaces: Vec<_> =
+            trees.iter().map(|tree| (tree.name, Interface::local(tree))).collect();
+        let modules = trees.into_iter().map(|tree| {
+            let imports = Imports::resolve(&tree, |module| {
+                let (_, interface) = interfaces.iter().find(|(name, _)| *name == Some(module))?;
+                Some(interface)
+            });
+            let scope = ModuleScope::new(&tree, imports);
+            (tree, scope)
+        });
+        let workspace = Modules(modules.collect());
+
+        let module = lower_module(&root);
+        let bodies = check_module(&workspace, ModuleName::new("Main"), &module);
+        let mut lines = vec![];
+        for (_, declaration) in module.declarations.iter() {
+            let Declaration::Value(value) = declaration else { continue };
+            let body = bodies.get(value.body).unwrap();
+            lines.push(format!("{} :: {}", value.name, body.types.display(body.ty.unwrap())));
+            for diagnostic in body.diagnostics() {
+                lines.push(format!("  {}", diagnostic.message(&body.types)));
+            }
+        }
+        assert_eq!(
+            lines,
+            [
+                "inserted :: { b :: Int, a :: String }",
+                "merged :: { a :: Int, b :: String }",
+                "appended :: Proxy \"abcd\"",
+                "compared :: Proxy LT",
+                "listed :: Proxy (Cons \"a\" String (Cons \"b\" Int Nil))",
+                "duplicate :: { a :: Int, a :: String }",
+                "  no instance found for Lacks \"a\" ( a :: String )",
+            ]
+        );
+    }
+}
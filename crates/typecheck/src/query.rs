@@ -0,0 +1,341 @@
+//! Checking modules one declaration at a time.
+//!
+//! A [`Checker`] remembers the types it inferred for each body, along with
+//! what the body was checked against: its own syntax, its signature, and the
+//! types of the values, constructors, and operators it refers to. Checking a
+//! module again only infers the bodies where any of these changed, so editing
+//! one body leaves every other body as it was, while editing a signature
+//! re-infers the bodies that refer to it.
+
+use std::rc::Rc;
+
+use lowering::{
+    arena::ArenaMap,
+    hir::{
+        Binder, Body, BodyId, Declaration, Expr, GuardedExpr, InstanceMember, LetBinding, Module,
+        Operator, TypeId, Value,
+    },
+    item_tree::Item,
+    name::{ModuleName, Name},
+    pretty,
+};
+use resolution::{body::Resolution, interface::Namespace};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    infer::{BodyTypes, Infer},
+    solve::Instances,
+    ty::TyId,
+    Workspace,
+};
+
+/// Identifies a body across edits to its module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeclarationKey {
+    Value(Name),
+    /// A member of the instance at this position among the instances of its
+    /// module.
+    Member {
+        instance: usize,
+        member: Name,
+    },
+}
+
+/// Everything that the types of a body depend on, rendered such that they
+/// can be compared across edits.
+#[derive(Debug, PartialEq, Eq)]
+struct Inputs {
+    body: String,
+    signature: Option<String>,
+    givens: Vec<String>,
+    /// The definitions that the body refers to, with their types, followed by
+    /// the annotations in the body.
+    references: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Checker {
+    bodies: FxHashMap<(ModuleName, DeclarationKey), (Inputs, Rc<BodyTypes>)>,
+    /// The heads of every instance in the workspace, as written. Any change
+    /// to them can change how constraints are solved in any body, so the
+    /// bodies of every module are checked again.
+    instances: Vec<String>,
+}
+
+impl Checker {
+    pub fn new() -> Checker {
+        Checker::default()
+    }
+
+    /// Checks the body of every value in `module`, reusing the types of the
+    /// bodies that are unchanged since it was last checked.
+    pub fn check_module(
+        &mut self,
+        workspace: &dyn Workspace,
+        name: ModuleName,
+        module: &Module,
+    ) -> ArenaMap<BodyId, Rc<BodyTypes>> {
+        let mut bodies = ArenaMap::default();
+        let Some(scope) = workspace.scope(name) else { return bodies };
+        let heads = instance_heads(workspace);
+        if heads != self.instances {
+            self.bodies.clear();
+            self.instances = heads;
+        }
+
+        let instances = Instances::new(workspace);
+        let mut checked = vec![];
+        for (key, value) in declarations(module) {
+            let body = value.body;
+            let (mut infer, signature) = Infer::body(workspace, &instances, module, scope, body);
+            let inputs = infer.inputs(pretty::print_value(module, value), signature);
+            let entry = (name, key);
+            let types = match self.bodies.remove(&entry) {
+                Some((previous, types)) if previous == inputs => types,
+                _ => Rc::new(infer.check_body(signature)),
+            };
+            bodies.insert(body, types.clone());
+            checked.push((entry, (inputs, types)));
+        }
+        // Bodies that were removed from the module are forgotten.
+        self.bodies.retain(|&(module, _), _| module != name);
+        self.bodies.extend(checked);
+        bodies
+    }
+}
+
+/// The values of a module and the values of its instance members, by the
+/// keys that identify them.
+fn declarations(module: &Module) -> Vec<(DeclarationKey, &Value)> {
+    let mut declarations = vec![];
+    let mut instance = 0;
+    for (_, declaration) in module.declarations.iter() {
+        match declaration {
+            Declaration::Value(value) => {
+                declarations.push((DeclarationKey::Value(value.name), value))
+            }
+            Declaration::InstanceChain(chain) => {
+                for members in chain.iter().map(|instance| &instance.members) {
+                    for member in members {
+                        if let InstanceMember::Value(value) = member {
+                            let key = DeclarationKey::Member { instance, member: value.name };
+                            declarations.push((key, value));
+                        }
+                    }
+                    instance += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    declarations
+}
+
+fn instance_heads(workspace: &dyn Workspace) -> Vec<String> {
+    let mut heads = vec![];
+    for module in workspace.modules() {
+        let Some(tree) = workspace.item_tree(module) else { continue };
+        for (_, item) in tree.items.iter() {
+            let Item::Instance(instance) = item else { continue };
+            let head = &instance.head;
+            let types = head.constraints.iter().chain(&head.arguments);
+            let types: Vec<_> = types.map(|&id| pretty::print_type(&tree.types, id)).collect();
+            heads.push(format!("{module} {} {} {types:?}", instance.chain_index, head.class));
+        }
+    }
+    heads
+}
+
+impl Infer<'_> {
+    /// Renders what the body depends on. The types are converted into a
+    /// separate arena, and any unknowns for wildcards are discarded, so the
+    /// body is checked as if they were never converted.
+    fn inputs(&mut self, body: String, signature: Option<TyId>) -> Inputs {
+        let signature = signature.map(|signature| self.types.display(signature).to_string());
+        let givens =
+            self.givens.iter().map(|given| self.types.display_constraint(given).to_string());
+        let givens = givens.collect();
+
+        let types = std::mem::take(&mut self.types);
+        let unknowns = self.unknowns.len();
+        let diagnostics = self.diagnostics.len();
+        let mut references = vec![];
+        for (namespace, resolution) in self.references() {
+            let Some(&Resolution::Global(definition)) = resolution.as_ref() else {
+                continue;
+            };
+            let ty = self.resolution_type(namespace, resolution.as_ref());
+            references.push(format!("{definition:?} {}", self.types.display(ty)));
+        }
+        let (module_types, resolution) = self.module_types.clone();
+        for annotation in annotations(self.body) {
+            let ty = self.convert(module_types, &resolution, annotation);
+            references.push(self.types.display(ty).to_string());
+        }
+        self.types = types;
+        self.unknowns.truncate(unknowns);
+        self.diagnostics.truncate(diagnostics);
+
+        Inputs { body, signature, givens, references }
+    }
+
+    /// The resolutions of the names that the body refers to.
+    fn references(&self) -> Vec<(Namespace, Option<Resolution>)> {
+        let mut references = vec![];
+        for (id, expr) in self.body.exprs.iter() {
+            let namespace = match expr {
+                Expr::Variable(_) => Namespace::Value,
+                Expr::Constructor(_) => Namespace::Constructor,
+                Expr::OperatorName(_) => Namespace::Operator,
+                Expr::OperatorChain { tail, .. } => {
+                    for (index, (operator, _)) in tail.iter().enumerate() {
+                        if let Operator::Symbol(_) = operator {
+                            let resolution = self.resolution.operator_resolution(id, index);
+                            references.push((Namespace::Operator, resolution.cloned()));
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            references.push((namespace, self.resolution.resolution(id).cloned()));
+        }
+        for (id, binder) in self.body.binders.iter() {
+            match binder {
+                Binder::Constructor { .. } => {
+                    let resolution = self.resolution.binder_resolution(id).cloned();
+                    references.push((Namespace::Constructor, resolution));
+                }
+                Binder::OperatorChain { tail, .. } => {
+                    for index in 0..tail.len() {
+                        let resolution = self.resolution.binder_operator_resolution(id, index);
+                        references.push((Namespace::Operator, resolution.cloned()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        references
+    }
+}
+
+/// The types that annotate expressions, binders, and `let` bindings in a
+/// body.
+fn annotations(body: &Body) -> Vec<TypeId> {
+    fn bindings(bindings: &[LetBinding], annotations: &mut Vec<TypeId>) {
+        for binding in bindings {
+            match binding {
+                LetBinding::Signature(signature) => annotations.push(signature.type_),
+                LetBinding::Value { guarded: value, .. }
+                | LetBinding::Pattern { guarded: value, .. } => guarded(value, annotations),
+            }
+        }
+    }
+
+    fn guarded(guarded: &GuardedExpr, annotations: &mut Vec<TypeId>) {
+        if let GuardedExpr::LetIn { bindings: let_bindings, .. } = guarded {
+            bindings(let_bindings, annotations);
+        }
+    }
+
+    let mut annotations = vec![];
+    guarded(&body.guarded, &mut annotations);
+    for (_, expr) in body.exprs.iter() {
+        match expr {
+            &Expr::Typed { type_, .. } => annotations.push(type_),
+            Expr::LetIn { bindings: let_bindings, .. } => {
+                bindings(let_bindings, &mut annotations);
+            }
+            Expr::Case { branches, .. } => {
+                for branch in branches {
+                    guarded(&branch.guarded, &mut annotations);
+                }
+            }
+            _ => {}
+        }
+    }
+    for (_, binder) in body.binders.iter() {
+        if let &Binder::Typed { type_, .. } = binder {
+            annotations.push(type_);
+        }
+    }
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use lowering::{
+        hir::{Declaration, Module},
+        item_tree::ItemTree,
+        lower_module,
+        name::{ModuleName, Name},
+    };
+    use resolution::{body::ModuleScope, imports::Imports};
+    use rowan::ast::AstNode;
+    use syntax::ast;
+
+    use super::Checker;
+    use crate::{infer::BodyTypes, Workspace};
+
+    struct Single(ItemTree, ModuleScope);
+
+    impl Workspace for Single {
+        fn item_tree(&self, module: ModuleName) -> Option<&ItemTree> {
+            (module == ModuleName::new("Main")).then_some(&self.0)
+        }
+
+        fn scope(&self, module: ModuleName) -> Option<&ModuleScope> {
+            (module == ModuleName::new("Main")).then_some(&self.1)
+        }
+
+        fn modules(&self) -> Vec<ModuleName> {
+            vec![ModuleName::new("Main")]
+        }
+    }
+
+    /// The types of each value in `source`, by name.
+    fn check(checker: &mut Checker, source: &str) -> Vec<(Name, Rc<BodyTypes>)> {
+        let (node, errors) = parsing::parse_module(source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = ast::Module::cast(node).unwrap();
+        let tree = ItemTree::lower(&root);
+        let module: Module = lower_module(&root);
+        let scope = ModuleScope::new(&tree, Imports::default());
+        let workspace = Single(tree, scope);
+
+        let bodies = checker.check_module(&workspace, ModuleName::new("Main"), &module);
+        let values = module.declarations.iter().filter_map(|(_, declaration)| match declaration {
+            Declaration::Value(value) => Some((value.name, bodies.get(value.body)?.clone())),
+            _ => None,
+        });
+        values.collect()
+    }
+
+    #[test]
+    fn recheck_changed_declarations() {
+        let mut checker = Checker::new();
+        let before = check(
+            &mut checker,
+            "module Main where\n\nf :: Int -> Int\nf x = x\n\ng = f 1\n\nh = 2\n",
+        );
+        let body = check(
+            &mut checker,
+            "module Main where\n\nf :: Int -> Int\nf x = 1\n\ng = f 1\n\nh = 2\n",
+        );
+        let unchanged: Vec<_> =
+            before.iter().zip(&body).map(|((_, a), (_, b))| Rc::ptr_eq(a, b)).collect();
+        assert_eq!(unchanged, [false, true, true]);
+
+        let signature = check(
+            &mut checker,
+            "module Main where\n\nf :: Int -> String\nf x = \"x\"\n\ng = f 1\n\nh = 2\n",
+        );
+        let unchanged: Vec<_> =
+            body.iter().zip(&signature).map(|((_, a), (_, b))| Rc::ptr_eq(a, b)).collect();
+        assert_eq!(unchanged, [false, false, true]);
+        let (_, g) = &signature[1];
+        assert_eq!(g.types.display(g.ty.unwrap()).to_string(), "String");
+    }
+}
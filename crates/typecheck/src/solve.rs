@@ -0,0 +1,585 @@
+//! Solving of class constraints against the instances in the workspace.
+//!
+//! Constraints are wanted wherever a constrained type is instantiated, and
+//! solved lazily, since an instance can only be chosen once the arguments of
+//! a constraint are known well enough to match the head of one. Constraints
+//! that are given by a signature, along with their superclasses, are tried
+//! before any instance.
+//!
+//! Instances in an `else` chain are tried in order, such that a later one is
+//! only chosen once every earlier one can no longer match. Separate chains
+//! that both match a constraint overlap, which is reported rather than
+//! picking either.
+//!
+//! The functional dependencies of a class let an instance be chosen by the
+//! arguments that determine the others, which are then unified with the
+//! types the instance has for them.
+
+use lowering::{
+    arena::Arena,
+    hir::{Declaration, InstanceHead, Module, Type, TypeId},
+    item_tree::Item,
+    name::{ModuleName, Name},
+};
+use resolution::{
+    body::ModuleScope,
+    instances::{covering_sets, dependencies, resolve_class},
+    interface::Definition,
+    prim,
+};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    infer::{Infer, Origin, TypeDiagnostic},
+    ty::{Constraint, Ty, TyId},
+    Workspace,
+};
+
+/// The instance chains of every class in the workspace, where an instance
+/// that isn't part of an `else` chain is a chain of its own.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Instances {
+    instances: FxHashMap<Definition, Vec<Vec<Definition>>>,
+    /// The sets of arguments that determine every other argument through the
+    /// functional dependencies of a class, for the classes that have any.
+    covering: FxHashMap<Definition, Vec<Vec<usize>>>,
+}
+
+impl Instances {
+    pub fn new(workspace: &dyn Workspace) -> Instances {
+        let mut instances: FxHashMap<_, Vec<Vec<Definition>>> = FxHashMap::default();
+        let mut covering = FxHashMap::default();
+        for module in workspace.modules() {
+            let (Some(tree), Some(scope)) = (workspace.item_tree(module), workspace.scope(module))
+            else {
+                continue;
+            };
+            for (item, instance) in tree.items.iter() {
+                if let Item::Class(class) = instance {
+                    if !class.dependencies.is_empty() {
+                        let definition = Definition { module, item, member: None };
+                        covering.insert(definition, covering_sets(class));
+                    }
+                }
+                let Item::Instance(instance) = instance else { continue };
+                let tree = |module| workspace.item_tree(module);
+                let Some((class, _)) = resolve_class(scope, instance.head.class, tree) else {
+                    continue;
+                };
+                let chains = instances.entry(class).or_default();
+                let definition = Definition { module, item, member: None };
+                match chains.last_mut() {
+                    Some(chain) if instance.chain_index > 0 && chain[0].module == module => {
+                        chain.push(definition);
+                    }
+                    _ => chains.push(vec![definition]),
+                }
+            }
+        }
+        Instances { instances, covering }
+    }
+
+    /// The instance chains of a class, in the order of the modules of the
+    /// workspace.
+    pub fn of(&self, class: Definition) -> &[Vec<Definition>] {
+        self.instances.get(&class).map_or(&[], |instances| instances)
+    }
+
+    /// The sets of argument positions that an instance of a class can be
+    /// chosen by, starting with every argument.
+    fn covering(&self, class: Definition, arity: usize) -> Vec<Vec<usize>> {
+        let every = (0..arity).collect();
+        let covering = self.covering.get(&class).into_iter().flatten().cloned();
+        std::iter::once(every).chain(covering).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    Yes,
+    /// The type has unknowns where the pattern doesn't, so whether it
+    /// matches depends on what they're solved to.
+    Stuck,
+    No,
+}
+
+/// An instance whose head matches a constraint.
+struct Matched {
+    /// The context of the instance, with its variables substituted.
+    context: Vec<Constraint>,
+    /// Arguments of the constraint that weren't matched, paired with the
+    /// types that functional dependencies determine for them.
+    improvements: Vec<(TyId, TyId)>,
+}
+
+pub(crate) enum Entailment {
+    /// Solved, provided that the constraints of the instance's context are.
+    Solved(Vec<Constraint>),
+    Stuck,
+    Failed,
+    /// More than one instance chain matches.
+    Overlapping,
+}
+
+impl Infer<'_> {
+    /// Adds a constraint that holds in the expression being checked, along
+    /// with its superclasses.
+    pub(crate) fn give(&mut self, constraint: Constraint) {
+        for given in self.givens.clone() {
+            if self.equal_constraints(&given, &constraint) {
+                return;
+            }
+        }
+        let superclasses = self.superclasses(&constraint);
+        self.givens.push(constraint);
+        for superclass in superclasses {
+            self.give(superclass);
+        }
+    }
+
+    fn superclasses(&mut self, constraint: &Constraint) -> Vec<Constraint> {
+        let workspace = self.workspace;
+        let Some(definition) = constraint.class.definition else { return vec![] };
+        let Some(tree) = workspace.item_tree(definition.module) else { return vec![] };
+        let Item::Class(class) = &tree.items[definition.item] else { return vec![] };
+        let Some(resolution) = self.tree_resolution(tree, definition.module) else {
+            return vec![];
+        };
+        let variables = class.variables.iter().map(|variable| variable.name);
+        let bindings: Vec<_> = variables.zip(constraint.arguments.iter().copied()).collect();
+        let mut superclasses = vec![];
+        for &superclass in &class.constraints {
+            for superclass in self.convert_constraints(&tree.types, &resolution, superclass) {
+                superclasses.push(self.substitute_constraint(&superclass, &bindings));
+            }
+        }
+        superclasses
+    }
+
+    /// The type of an instance member, which is the type of the class member
+    /// with the arguments of the instance head substituted. The context of
+    /// the instance is given while checking it.
+    pub(crate) fn member_signature(
+        &mut self,
+        scope: &ModuleScope,
+        head: &InstanceHead,
+        member: Name,
+    ) -> Option<TyId> {
+        let workspace = self.workspace;
+        let tree = |module| workspace.item_tree(module);
+        let (definition, class) = resolve_class(scope, head.class, tree)?;
+        let signature = class.members.iter().find(|signature| signature.name == member)?;
+        let ty = self.convert_tree_type(
+            workspace.item_tree(definition.module)?,
+            definition.module,
+            signature.type_,
+        );
+
+        let (types, resolution) = self.module_types.clone();
+        let arguments =
+            head.arguments.iter().map(|&argument| self.convert(types, &resolution, argument));
+        let arguments: Vec<_> = arguments.collect();
+        for &constraint in &head.constraints {
+            for constraint in self.convert_constraints(types, &resolution, constraint) {
+                self.give(constraint);
+            }
+        }
+        let variables = class.variables.iter().map(|variable| variable.name);
+        let bindings: Vec<_> = variables.zip(arguments).collect();
+        Some(self.substitute(ty, &bindings))
+    }
+
+    /// Solves the constraints wanted since `start` as far as possible,
+    /// leaving the ones that are stuck, and reporting the ones that can't be
+    /// solved.
+    pub(crate) fn solve_constraints(&mut self, start: usize) {
+        loop {
+            let mut progress = false;
+            for (constraint, origin) in self.wanted.split_off(start) {
+                match self.entail(&constraint, origin) {
+                    Entailment::Solved(context) => {
+                        progress = true;
+                        self.wanted
+                            .extend(context.into_iter().map(|constraint| (constraint, origin)));
+                    }
+                    Entailment::Stuck => self.wanted.push((constraint, origin)),
+                    Entailment::Failed => {
+                        let constraint = self.zonk_constraint(constraint);
+                        self.diagnostics.push(TypeDiagnostic::NoInstance { origin, constraint });
+                    }
+                    Entailment::Overlapping => {
+                        let constraint = self.zonk_constraint(constraint);
+                        self.diagnostics.push(TypeDiagnostic::Overlapping { origin, constraint });
+                    }
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+    }
+
+    fn entail(&mut self, constraint: &Constraint, origin: Origin) -> Entailment {
+        let constraint = self.zonk_constraint(constraint.clone());
+        // Classes in `Prim` are built into the compiler, and are trusted to
+        // hold until they're solved here as well.
+        let Some(class) = constraint.class.definition else { return Entailment::Solved(vec![]) };
+        if constraint.arguments.iter().any(|&argument| self.contains_error(argument)) {
+            return Entailment::Solved(vec![]);
+        }
+        for given in self.givens.clone() {
+            if self.equal_constraints(&given, &constraint) {
+                return Entailment::Solved(vec![]);
+            }
+        }
+        if prim::is_prim_module(class.module) {
+            return self.entail_prim(class.module, &constraint, origin);
+        }
+
+        let covering = self.instances.covering(class, constraint.arguments.len());
+        // A given constraint that agrees on the arguments of a covering set
+        // determines the rest of them.
+        for given in self.givens.clone() {
+            if given.class != constraint.class {
+                continue;
+            }
+            let agrees = |infer: &mut Self, covering: &[usize]| {
+                covering.iter().all(|&position| {
+                    infer.equal(given.arguments[position], constraint.arguments[position])
+                })
+            };
+            let Some(covering) = covering[1..].iter().find(|covering| agrees(self, covering))
+            else {
+                continue;
+            };
+            for position in 0..constraint.arguments.len() {
+                if !covering.contains(&position) {
+                    let (actual, expected) =
+                        (constraint.arguments[position], given.arguments[position]);
+                    self.unify(actual, expected, origin);
+                }
+            }
+            return Entailment::Solved(vec![]);
+        }
+
+        let mut matched = vec![];
+        let mut stuck = false;
+        for chain in self.instances.of(class) {
+            // Later instances in a chain are only tried once the earlier ones
+            // are known not to match.
+            for &instance in chain {
+                match self.match_instance(instance, &constraint, &covering) {
+                    (Match::Yes, instance) => {
+                        matched.extend(instance);
+                        break;
+                    }
+                    (Match::Stuck, _) => {
+                        stuck = true;
+                        break;
+                    }
+                    (Match::No, _) => {}
+                }
+            }
+        }
+        // An instance is only chosen once no other instance could match.
+        if matched.len() > 1 && !stuck {
+            return Entailment::Overlapping;
+        }
+        match matched.pop() {
+            Some(Matched { context, improvements }) if !stuck => {
+                for (actual, expected) in improvements {
+                    self.unify(actual, expected, origin);
+                }
+                Entailment::Solved(context)
+            }
+            _ if stuck || self.has_unknowns(&constraint) => Entailment::Stuck,
+            _ => Entailment::Failed,
+        }
+    }
+
+    /// Matches the head of an instance against a constraint by each set of
+    /// arguments in `covering`, stopping at the first one that matches.
+    fn match_instance(
+        &mut self,
+        instance: Definition,
+        constraint: &Constraint,
+        covering: &[Vec<usize>],
+    ) -> (Match, Option<Matched>) {
+        let workspace = self.workspace;
+        let Some(tree) = workspace.item_tree(instance.module) else { return (Match::No, None) };
+        let Item::Instance(item) = &tree.items[instance.item] else { return (Match::No, None) };
+        if item.head.arguments.len() != constraint.arguments.len() {
+            return (Match::No, None);
+        }
+        let Some(resolution) = self.tree_resolution(tree, instance.module) else {
+            return (Match::No, None);
+        };
+        let patterns: Vec<_> = item
+            .head
+            .arguments
+            .iter()
+            .map(|&pattern| self.convert(&tree.types, &resolution, pattern))
+            .collect();
+
+        let mut result = Match::No;
+        for covering in covering {
+            let mut bindings = vec![];
+            let mut matched = Match::Yes;
+            for &position in covering {
+                let argument = constraint.arguments[position];
+                matched = matched.max(self.match_type(patterns[position], argument, &mut bindings));
+            }
+            if matched != Match::Yes {
+                result = result.min(matched);
+                continue;
+            }
+
+            // Variables that only appear in determined arguments are solved
+            // by unifying those arguments.
+            let determined: Vec<_> =
+                (0..patterns.len()).filter(|position| !covering.contains(position)).collect();
+            for &position in &determined {
+                self.bind_variables(patterns[position], &mut bindings);
+            }
+            let improvements = determined
+                .iter()
+                .map(|&position| {
+                    (constraint.arguments[position], self.substitute(patterns[position], &bindings))
+                })
+                .collect();
+            let mut context = vec![];
+            for &constraint in &item.head.constraints {
+                for constraint in self.convert_constraints(&tree.types, &resolution, constraint) {
+                    context.push(self.substitute_constraint(&constraint, &bindings));
+                }
+            }
+            return (Match::Yes, Some(Matched { context, improvements }));
+        }
+        (result, None)
+    }
+
+    /// Binds the variables of `ty` that aren't bound yet to fresh unknowns.
+    fn bind_variables(&mut self, ty: TyId, bindings: &mut Vec<(Name, TyId)>) {
+        match self.types[ty].clone() {
+            Ty::Variable(name) if !bindings.iter().any(|&(bound, _)| bound == name) => {
+                let unknown = self.fresh();
+                bindings.push((name, unknown));
+            }
+            Ty::Application(function, argument) => {
+                self.bind_variables(function, bindings);
+                self.bind_variables(argument, bindings);
+            }
+            Ty::Row(fields, tail) => {
+                for ty in fields.into_iter().map(|(_, ty)| ty).chain(tail) {
+                    self.bind_variables(ty, bindings);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Matches a type from an instance head against the argument of a
+    /// constraint, binding the variables of the head. Unlike unification,
+    /// matching never solves an unknown.
+    fn match_type(&mut self, pattern: TyId, ty: TyId, bindings: &mut Vec<(Name, TyId)>) -> Match {
+        let (pattern, ty) = (self.prune(pattern), self.prune(ty));
+        match (self.types[pattern].clone(), self.types[ty].clone()) {
+            (Ty::Variable(name), _) => {
+                let Some(&(_, bound)) = bindings.iter().find(|&&(bound, _)| bound == name) else {
+                    bindings.push((name, ty));
+                    return Match::Yes;
+                };
+                if self.equal(bound, ty) {
+                    Match::Yes
+                } else if self.has_unknowns_in(bound) || self.has_unknowns_in(ty) {
+                    Match::Stuck
+                } else {
+                    Match::No
+                }
+            }
+            (_, Ty::Unknown(_)) => Match::Stuck,
+            (Ty::Constructor(left), Ty::Constructor(right)) if left == right => Match::Yes,
+            (
+                Ty::Application(pattern_function, pattern_argument),
+                Ty::Application(function, argument),
+            ) => {
+                let function = self.match_type(pattern_function, function, bindings);
+                function.max(self.match_type(pattern_argument, argument, bindings))
+            }
+            _ if self.equal(pattern, ty) => Match::Yes,
+            _ => Match::No,
+        }
+    }
+
+    fn equal_constraints(&mut self, left: &Constraint, right: &Constraint) -> bool {
+        left.class == right.class
+            && left.arguments.len() == right.arguments.len()
+            && left
+                .arguments
+                .iter()
+                .zip(&right.arguments)
+                .all(|(&left, &right)| self.equal(left, right))
+    }
+
+    /// Whether two types are the same without solving any unknowns.
+    fn equal(&mut self, left: TyId, right: TyId) -> bool {
+        let (left, right) = (self.prune(left), self.prune(right));
+        if left == right {
+            return true;
+        }
+        match (self.types[left].clone(), self.types[right].clone()) {
+            (
+                Ty::Application(left_function, left_argument),
+                Ty::Application(right_function, right_argument),
+            ) => {
+                self.equal(left_function, right_function)
+                    && self.equal(left_argument, right_argument)
+            }
+            (Ty::Forall(left_name, _, left_body), Ty::Forall(right_name, _, right_body)) => {
+                left_name == right_name && self.equal(left_body, right_body)
+            }
+            (Ty::Row(left_fields, left_tail), Ty::Row(right_fields, right_tail)) => {
+                left_fields.len() == right_fields.len()
+                    && left_fields.iter().zip(&right_fields).all(
+                        |(&(left_label, left), &(right_label, right))| {
+                            left_label == right_label && self.equal(left, right)
+                        },
+                    )
+                    && match (left_tail, right_tail) {
+                        (Some(left), Some(right)) => self.equal(left, right),
+                        (left, right) => left == right,
+                    }
+            }
+            (Ty::Constrained(left, left_body), Ty::Constrained(right, right_body)) => {
+                self.equal_constraints(&left, &right) && self.equal(left_body, right_body)
+            }
+            (Ty::Skolem(_, left), Ty::Skolem(_, right)) => left == right,
+            (left, right) => left == right,
+        }
+    }
+
+    pub(crate) fn has_unknowns(&mut self, constraint: &Constraint) -> bool {
+        constraint.arguments.iter().any(|&argument| self.has_unknowns_in(argument))
+    }
+
+    fn has_unknowns_in(&mut self, ty: TyId) -> bool {
+        let mut unknowns = vec![];
+        self.collect_unknowns(ty, &mut unknowns);
+        !unknowns.is_empty()
+    }
+
+    fn contains_error(&mut self, ty: TyId) -> bool {
+        let ty = self.prune(ty);
+        match self.types[ty].clone() {
+            Ty::Error => true,
+            Ty::Application(function, argument) => {
+                self.contains_error(function) || self.contains_error(argument)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Reports the arguments of instances that have variables their class's
+/// functional dependencies don't determine, which would let more than one
+/// instance match the same determining arguments.
+pub fn check_coverage(
+    workspace: &dyn Workspace,
+    name: ModuleName,
+    module: &Module,
+) -> Vec<TypeDiagnostic> {
+    let mut diagnostics = vec![];
+    let Some(scope) = workspace.scope(name) else { return diagnostics };
+    let heads = module.declarations.iter().flat_map(|(_, declaration)| match declaration {
+        Declaration::InstanceChain(chain) => chain.iter().map(|instance| &instance.head).collect(),
+        Declaration::Derive(derive) => vec![&derive.head],
+        _ => vec![],
+    });
+    for head in heads {
+        let tree = |module| workspace.item_tree(module);
+        let Some((_, class)) = resolve_class(scope, head.class, tree) else { continue };
+        if head.arguments.len() != class.variables.len() {
+            continue;
+        }
+        let variables: Vec<_> = head
+            .arguments
+            .iter()
+            .map(|&argument| {
+                let mut variables = vec![];
+                type_variables(&module.types, argument, &mut variables);
+                variables
+            })
+            .collect();
+        for (determiners, determined) in dependencies(&class.variables, &class.dependencies) {
+            let covered: Vec<_> =
+                determiners.iter().flat_map(|&position| &variables[position]).collect();
+            for position in determined {
+                let uncovered: Vec<_> = variables[position]
+                    .iter()
+                    .copied()
+                    .filter(|variable| !covered.contains(&variable))
+                    .collect();
+                if !uncovered.is_empty() {
+                    diagnostics.push(TypeDiagnostic::Uncovered {
+                        origin: Origin::Type(head.arguments[position]),
+                        class: class.name,
+                        variables: uncovered,
+                    });
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+/// The type variables that appear free in a type, in order.
+fn type_variables(types: &Arena<Type>, id: TypeId, variables: &mut Vec<Name>) {
+    match &types[id] {
+        &Type::Variable(name) if !variables.contains(&name) => variables.push(name),
+        Type::Forall { variables: bound, type_ } => {
+            let mut inner = vec![];
+            type_variables(types, *type_, &mut inner);
+            for name in inner {
+                if !bound.iter().any(|variable| variable.name == name) && !variables.contains(&name)
+                {
+                    variables.push(name);
+                }
+            }
+        }
+        &Type::Constrained { constraint, type_ } => {
+            type_variables(types, constraint, variables);
+            type_variables(types, type_, variables);
+        }
+        &Type::Arrow { argument, result } => {
+            type_variables(types, argument, variables);
+            type_variables(types, result, variables);
+        }
+        Type::OperatorChain { head, tail } => {
+            type_variables(types, *head, variables);
+            for &(_, operand) in tail {
+                type_variables(types, operand, variables);
+            }
+        }
+        &Type::Kinded { type_, .. } => type_variables(types, type_, variables),
+        Type::Application { function, arguments } => {
+            type_variables(types, *function, variables);
+            for &argument in arguments {
+                type_variables(types, argument, variables);
+            }
+        }
+        Type::Record(row) | Type::Row(row) => {
+            for &(_, field) in &row.fields {
+                type_variables(types, field, variables);
+            }
+            if let Some(tail) = row.tail {
+                type_variables(types, tail, variables);
+            }
+        }
+        Type::Constraints(constraints) => {
+            for &constraint in constraints {
+                type_variables(types, constraint, variables);
+            }
+        }
+        _ => {}
+    }
+}
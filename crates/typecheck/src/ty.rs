@@ -0,0 +1,761 @@
+//! The representation of types during inference.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    ops::{Index, Range},
+};
+
+use lowering::{
+    arena::{Arena, Idx},
+    name::Name,
+};
+use resolution::interface::{Definition, Namespace};
+use rustc_hash::FxHashMap;
+
+pub type TyId = Idx<Ty>;
+
+/// A label of a row along with its type.
+pub type Field = (Name, TyId);
+
+/// A unification variable, solved by [`Infer`](crate::infer::Infer).
+pub type Unknown = u32;
+
+/// A type constructor, which is either declared in the workspace or built
+/// into the compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeConstructor {
+    pub name: Name,
+    /// `None` for the types in `Prim`.
+    pub definition: Option<Definition>,
+}
+
+impl TypeConstructor {
+    pub fn prim(name: &str) -> TypeConstructor {
+        TypeConstructor { name: Name::new(name), definition: None }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+    /// A type that failed to convert or check, which unifies with anything
+    /// such that a single error isn't reported over and over.
+    Error,
+    Unknown(Unknown),
+    /// A type variable bound by a `forall`.
+    Variable(Name),
+    /// A type variable that only unifies with itself, which replaces the
+    /// variables of a `forall` that an expression is checked against.
+    Skolem(Name, u32),
+    Constructor(TypeConstructor),
+    Application(TyId, TyId),
+    Forall(Name, Visibility, TyId),
+    /// A row of labelled types, which is closed if it has no tail. Labels
+    /// may repeat, in which case the order of their types matters.
+    Row(Vec<(Name, TyId)>, Option<TyId>),
+    Constrained(Constraint, TyId),
+    /// A type-level string, of kind `Symbol`.
+    Symbol(String),
+}
+
+/// Whether the variable of a `forall` can be instantiated by a visible type
+/// application, e.g. `identity @Int`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Invisible,
+    Visible,
+}
+
+/// A class applied to types, where the class is a [`TypeConstructor`] since
+/// classes have their own namespace but are otherwise named the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub class: TypeConstructor,
+    pub arguments: Vec<TyId>,
+}
+
+/// The types allocated while checking a body.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Types {
+    types: Arena<Ty>,
+    /// The synonyms that types were expanded from, applied to their
+    /// arguments the way they're written.
+    synonyms: FxHashMap<TyId, TyId>,
+}
+
+impl Types {
+    pub fn alloc(&mut self, ty: Ty) -> TyId {
+        self.types.alloc(ty)
+    }
+
+    /// Displays `expanded` as `synonym` wherever it isn't compared against a
+    /// type that it differs from.
+    pub(crate) fn expanded_from(&mut self, expanded: TyId, synonym: TyId) {
+        self.synonyms.insert(expanded, synonym);
+    }
+
+    /// The synonym that `ty` was expanded from, if it was.
+    pub fn synonym(&self, ty: TyId) -> Option<TyId> {
+        self.synonyms.get(&ty).copied()
+    }
+
+    pub fn prim(&mut self, name: &str) -> TyId {
+        self.alloc(Ty::Constructor(TypeConstructor::prim(name)))
+    }
+
+    pub fn application(&mut self, function: TyId, argument: TyId) -> TyId {
+        self.alloc(Ty::Application(function, argument))
+    }
+
+    pub fn function(&mut self, argument: TyId, result: TyId) -> TyId {
+        let function = self.prim("Function");
+        let function = self.application(function, argument);
+        self.application(function, result)
+    }
+
+    pub fn record(&mut self, fields: Vec<(Name, TyId)>, tail: Option<TyId>) -> TyId {
+        let record = self.prim("Record");
+        let row = self.alloc(Ty::Row(fields, tail));
+        self.application(record, row)
+    }
+
+    /// Splits an application of `Function` into its argument and result.
+    pub fn as_function(&self, ty: TyId) -> Option<(TyId, TyId)> {
+        let Ty::Application(function, result) = self[ty] else { return None };
+        let Ty::Application(function, argument) = self[function] else { return None };
+        match &self[function] {
+            Ty::Constructor(constructor) if *constructor == TypeConstructor::prim("Function") => {
+                Some((argument, result))
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits an application of `Record` into the fields of its row and its
+    /// tail, which is only a row if the type hasn't been zonked.
+    pub fn as_record(&self, ty: TyId) -> Option<(&[Field], Option<TyId>)> {
+        let Ty::Application(record, row) = self[ty] else { return None };
+        match &self[row] {
+            Ty::Row(fields, tail) if self.is_record(record) => Some((fields, *tail)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_record(&self, ty: TyId) -> bool {
+        matches!(&self[ty], Ty::Constructor(constructor) if *constructor == TypeConstructor::prim("Record"))
+    }
+
+    /// Splits an application into its constructor and arguments.
+    pub fn spine(&self, mut ty: TyId) -> (TyId, Vec<TyId>) {
+        let mut arguments = vec![];
+        while let Ty::Application(function, argument) = self[ty] {
+            arguments.push(argument);
+            ty = function;
+        }
+        arguments.reverse();
+        (ty, arguments)
+    }
+
+    /// Renders a type the way it would be written, where unknowns are
+    /// printed as `?0`, `?1`, and so on. Unknowns aren't substituted, so
+    /// types should be zonked before they're displayed.
+    pub fn display(&self, ty: TyId) -> impl fmt::Display + '_ {
+        Display { types: self, ty }
+    }
+
+    pub fn display_constraint<'a>(&'a self, constraint: &'a Constraint) -> impl fmt::Display + 'a {
+        ConstraintDisplay { types: self, constraint }
+    }
+
+    /// Renders `ty` with the names of types and classes as `name` writes
+    /// them, e.g. qualified the way a module imports them.
+    pub fn display_with(&self, ty: TyId, name: &NameWriter<'_>) -> String {
+        let mut renderer = Renderer::new(self);
+        renderer.names = Some(name);
+        renderer.ty(ty, None, Precedence::Top);
+        renderer.text
+    }
+
+    /// Renders `ty` for a message, with its unknowns named by `unknowns`.
+    pub fn describe(&self, ty: TyId, unknowns: &UnknownNames) -> String {
+        let write = |unknown| unknowns.name(unknown);
+        let mut renderer = Renderer::new(self);
+        renderer.unknowns = Some(&write);
+        renderer.ty(ty, None, Precedence::Top);
+        renderer.text
+    }
+
+    /// Renders `constraint` for a message, like [`Types::describe`].
+    pub fn describe_constraint(&self, constraint: &Constraint, unknowns: &UnknownNames) -> String {
+        let write = |unknown| unknowns.name(unknown);
+        let mut renderer = Renderer::new(self);
+        renderer.unknowns = Some(&write);
+        renderer.constraint(constraint, None);
+        renderer.text
+    }
+
+    /// Renders `ty` like [`Types::display_with`], with its unknowns and
+    /// errors written as `unknown` writes them, e.g. as type variables.
+    pub fn display_with_unknowns(
+        &self,
+        ty: TyId,
+        name: &NameWriter<'_>,
+        unknown: &UnknownWriter<'_>,
+    ) -> String {
+        let mut renderer = Renderer::new(self);
+        renderer.names = Some(name);
+        renderer.unknowns = Some(unknown);
+        renderer.ty(ty, None, Precedence::Top);
+        renderer.text
+    }
+
+    /// Whether `ty` has neither errors nor unknowns in it, such that it can
+    /// be written down.
+    pub fn is_known(&self, ty: TyId) -> bool {
+        match &self[ty] {
+            Ty::Error | Ty::Unknown(_) => false,
+            Ty::Variable(_) | Ty::Skolem(..) | Ty::Constructor(_) | Ty::Symbol(_) => true,
+            &Ty::Application(function, argument) => {
+                self.is_known(function) && self.is_known(argument)
+            }
+            &Ty::Forall(_, _, body) => self.is_known(body),
+            Ty::Row(fields, tail) => {
+                fields.iter().all(|&(_, ty)| self.is_known(ty))
+                    && tail.is_none_or(|tail| self.is_known(tail))
+            }
+            Ty::Constrained(constraint, body) => {
+                constraint.arguments.iter().all(|&argument| self.is_known(argument))
+                    && self.is_known(*body)
+            }
+        }
+    }
+
+    /// Renders the type of a function with the arguments it takes as the
+    /// highlights, e.g. `a` and `Maybe a` in `forall a. a -> Maybe a -> a`.
+    pub fn signature(&self, ty: TyId) -> Rendered {
+        let mut renderer = Renderer::new(self);
+        renderer.signature(ty);
+        Rendered { text: renderer.text, highlights: renderer.highlights }
+    }
+
+    /// Renders two types that failed to unify side by side, highlighting
+    /// the parts where they differ. Fields that records agree on are elided
+    /// once a record has more than a few of them. Both types share the
+    /// names of their unknowns.
+    pub fn diff(&self, expected: TyId, actual: TyId) -> TypeDiff {
+        let unknowns = UnknownNames::default();
+        let write = |unknown| unknowns.name(unknown);
+        let render = |ty, other| {
+            let mut renderer = Renderer::new(self);
+            renderer.unknowns = Some(&write);
+            renderer.ty(ty, Some(other), Precedence::Top);
+            Rendered { text: renderer.text, highlights: renderer.highlights }
+        };
+        TypeDiff { expected: render(expected, actual), actual: render(actual, expected) }
+    }
+
+    /// The fields and tail of a row, where a type that isn't a row is the
+    /// tail of an empty one.
+    fn row_of(&self, ty: TyId) -> RowParts<'_> {
+        match &self[ty] {
+            Ty::Row(fields, tail) => (fields, *tail),
+            _ => (&[], Some(ty)),
+        }
+    }
+
+    /// Whether two types are the same, where unknowns are only equal to
+    /// themselves.
+    fn equal(&self, left: TyId, right: TyId) -> bool {
+        if left == right {
+            return true;
+        }
+        match (&self[left], &self[right]) {
+            (&Ty::Application(left_function, left), &Ty::Application(right_function, right)) => {
+                self.equal(left_function, right_function) && self.equal(left, right)
+            }
+            (&Ty::Forall(left_name, _, left), &Ty::Forall(right_name, _, right)) => {
+                left_name == right_name && self.equal(left, right)
+            }
+            (Ty::Row(left_fields, left_tail), Ty::Row(right_fields, right_tail)) => {
+                left_fields.len() == right_fields.len()
+                    && left_fields.iter().zip(right_fields).all(
+                        |(&(left_label, left), &(right_label, right))| {
+                            left_label == right_label && self.equal(left, right)
+                        },
+                    )
+                    && self.equal_tails(*left_tail, *right_tail)
+            }
+            (Ty::Constrained(left, left_body), Ty::Constrained(right, right_body)) => {
+                self.equal_constraints(left, right) && self.equal(*left_body, *right_body)
+            }
+            (left, right) => left == right,
+        }
+    }
+
+    fn equal_tails(&self, left: Option<TyId>, right: Option<TyId>) -> bool {
+        match (left, right) {
+            (Some(left), Some(right)) => self.equal(left, right),
+            (left, right) => left == right,
+        }
+    }
+
+    fn equal_constraints(&self, left: &Constraint, right: &Constraint) -> bool {
+        left.class == right.class
+            && left.arguments.len() == right.arguments.len()
+            && left
+                .arguments
+                .iter()
+                .zip(&right.arguments)
+                .all(|(&left, &right)| self.equal(left, right))
+    }
+}
+
+/// Two types rendered by [`Types::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDiff {
+    pub expected: Rendered,
+    pub actual: Rendered,
+}
+
+/// A rendered type, with the byte ranges of the parts to highlight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    pub text: String,
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl Rendered {
+    /// The text, followed by a line that marks its highlights with `^` if it
+    /// has any, where every line but the first starts with `indent`.
+    pub fn underlined(&self, indent: &str) -> String {
+        let mut marks = String::new();
+        for range in &self.highlights {
+            let start = self.text[..range.start].chars().count();
+            let width = self.text[range.clone()].chars().count().max(1);
+            let written = marks.chars().count();
+            marks.extend(std::iter::repeat_n(' ', start.saturating_sub(written)));
+            marks.extend(std::iter::repeat_n('^', width));
+        }
+        if marks.is_empty() {
+            return self.text.clone();
+        }
+        format!("{}\n{indent}{marks}", self.text)
+    }
+}
+
+impl fmt::Display for Rendered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Index<TyId> for Types {
+    type Output = Ty;
+
+    fn index(&self, index: TyId) -> &Ty {
+        &self.types[index]
+    }
+}
+
+/// Writes the name of a type or class, of the namespace it's in.
+pub type NameWriter<'a> = dyn Fn(Namespace, &TypeConstructor) -> String + 'a;
+
+/// Writes an unknown, or an error if it's `None`.
+pub type UnknownWriter<'a> = dyn Fn(Option<Unknown>) -> String + 'a;
+
+/// Names unknowns the way messages show them: `t0`, `t1`, and so on in the
+/// order they're first rendered in, like the variables of a generalized
+/// type, rather than by their number. Errors are written as `?`.
+#[derive(Debug, Default)]
+pub struct UnknownNames {
+    named: RefCell<Vec<Unknown>>,
+}
+
+impl UnknownNames {
+    fn name(&self, unknown: Option<Unknown>) -> String {
+        let Some(unknown) = unknown else { return "?".to_string() };
+        let mut named = self.named.borrow_mut();
+        let index = named.iter().position(|&named| named == unknown).unwrap_or_else(|| {
+            named.push(unknown);
+            named.len() - 1
+        });
+        format!("t{index}")
+    }
+}
+
+/// The fields and tail of a row.
+type RowParts<'a> = (&'a [(Name, TyId)], Option<TyId>);
+
+/// How many fields a record can have before the fields it has in common
+/// with the record it's compared to are elided.
+const ELIDE_FIELDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Top,
+    /// The argument of a function type, which can't be another function
+    /// type without parentheses.
+    Function,
+    /// The argument of an application.
+    Atom,
+}
+
+/// Writes types into a string. When a type is rendered against another,
+/// the parts of it that differ from the other type are highlighted.
+struct Renderer<'a> {
+    types: &'a Types,
+    text: String,
+    highlights: Vec<Range<usize>>,
+    /// How the names of types and classes are written, which is their name
+    /// alone by default.
+    names: Option<&'a NameWriter<'a>>,
+    /// How unknowns and errors are written, which is `?0` and `?` by
+    /// default.
+    unknowns: Option<&'a UnknownWriter<'a>>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(types: &'a Types) -> Renderer<'a> {
+        Renderer { types, text: String::new(), highlights: vec![], names: None, unknowns: None }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn name(&mut self, namespace: Namespace, constructor: &TypeConstructor) {
+        match self.names {
+            Some(name) => self.text.push_str(&name(namespace, constructor)),
+            None => self.text.push_str(constructor.name.as_str()),
+        }
+    }
+
+    fn unknown(&mut self, unknown: Option<Unknown>) {
+        match (self.unknowns, unknown) {
+            (Some(write), _) => self.text.push_str(&write(unknown)),
+            (None, Some(unknown)) => self.text.push_str(&format!("?{unknown}")),
+            (None, None) => self.text.push('?'),
+        }
+    }
+
+    fn highlighted(&mut self, render: impl FnOnce(&mut Self)) {
+        let start = self.text.len();
+        render(self);
+        self.highlights.push(start..self.text.len());
+    }
+
+    fn parenthesized(&mut self, needed: bool, render: impl FnOnce(&mut Self)) {
+        if needed {
+            self.write("(");
+        }
+        render(self);
+        if needed {
+            self.write(")");
+        }
+    }
+
+    /// Renders `ty`, highlighting where it differs from `other` if given.
+    /// Synonyms are rendered as they're written, unless their expansion
+    /// differs from `other` in parts that can be compared, in which case
+    /// they're expanded to show where.
+    fn ty(&mut self, ty: TyId, other: Option<TyId>, precedence: Precedence) {
+        let types = self.types;
+        if let Some(synonym) = types.synonym(ty) {
+            match other {
+                Some(other) if !types.equal(ty, other) && self.same_shape(ty, other) => {}
+                Some(other) if !types.equal(ty, other) => {
+                    return self.highlighted(|renderer| renderer.ty(synonym, None, precedence));
+                }
+                _ => return self.ty(synonym, None, precedence),
+            }
+        }
+        if let Some(other) = other {
+            if !types.equal(ty, other) && !self.same_shape(ty, other) {
+                return self.highlighted(|renderer| renderer.ty(ty, None, precedence));
+            }
+        }
+        let other_function = other.and_then(|other| types.as_function(other));
+        if let Some((argument, result)) = types.as_function(ty) {
+            return self.parenthesized(precedence > Precedence::Top, |renderer| {
+                renderer.ty(argument, other_function.map(|(other, _)| other), Precedence::Function);
+                renderer.write(" -> ");
+                renderer.ty(result, other_function.map(|(_, other)| other), Precedence::Top);
+            });
+        }
+        match &types[ty] {
+            Ty::Error => self.unknown(None),
+            &Ty::Unknown(unknown) => self.unknown(Some(unknown)),
+            Ty::Variable(name) | Ty::Skolem(name, _) => self.write(name.as_str()),
+            Ty::Constructor(constructor) => self.name(Namespace::Type, constructor),
+            Ty::Symbol(value) => self.write(&format!("{value:?}")),
+            Ty::Row(fields, tail) => {
+                let other = other.map(|other| types.row_of(other));
+                self.row(fields, *tail, other, "(", ")");
+            }
+            &Ty::Application(function, row) if types.is_record(function) => match &types[row] {
+                Ty::Row(fields, tail) => {
+                    let other = other.and_then(|other| match types[other] {
+                        Ty::Application(_, other) => Some(types.row_of(other)),
+                        _ => None,
+                    });
+                    self.row(fields, *tail, other, "{", "}");
+                }
+                _ => {
+                    self.write("{ | ");
+                    self.ty(row, None, Precedence::Top);
+                    self.write(" }");
+                }
+            },
+            Ty::Application(..) => {
+                let (function, arguments) = types.spine(ty);
+                let other = other.map(|other| types.spine(other));
+                self.parenthesized(precedence == Precedence::Atom, |renderer| {
+                    renderer.ty(
+                        function,
+                        other.as_ref().map(|(other, _)| *other),
+                        Precedence::Atom,
+                    );
+                    for (index, &argument) in arguments.iter().enumerate() {
+                        renderer.write(" ");
+                        let other = other.as_ref().map(|(_, arguments)| arguments[index]);
+                        renderer.ty(argument, other, Precedence::Atom);
+                    }
+                });
+            }
+            Ty::Constrained(constraint, body) => {
+                let other = other.and_then(|other| match &types[other] {
+                    Ty::Constrained(other, body) => Some((other, *body)),
+                    _ => None,
+                });
+                self.parenthesized(precedence > Precedence::Top, |renderer| {
+                    renderer.constraint(constraint, other.map(|(other, _)| other));
+                    renderer.write(" => ");
+                    renderer.ty(*body, other.map(|(_, other)| other), Precedence::Top);
+                });
+            }
+            Ty::Forall(..) => self.parenthesized(precedence > Precedence::Top, |renderer| {
+                let (mut ty, mut other) = (ty, other);
+                renderer.write("forall");
+                while let Ty::Forall(name, visibility, body) = &types[ty] {
+                    match visibility {
+                        Visibility::Visible => renderer.write(&format!(" @{name}")),
+                        Visibility::Invisible => renderer.write(&format!(" {name}")),
+                    }
+                    ty = *body;
+                    other = other.and_then(|other| match types[other] {
+                        Ty::Forall(_, _, body) => Some(body),
+                        _ => None,
+                    });
+                }
+                renderer.write(". ");
+                renderer.ty(ty, other, Precedence::Top);
+            }),
+        }
+    }
+
+    /// Renders a type like [`Renderer::ty`], highlighting the arguments of
+    /// the function it is under any `forall`s and constraints.
+    fn signature(&mut self, ty: TyId) {
+        let types = self.types;
+        if let Some((argument, result)) = types.as_function(ty) {
+            self.highlighted(|renderer| renderer.ty(argument, None, Precedence::Function));
+            self.write(" -> ");
+            return self.signature(result);
+        }
+        match &types[ty] {
+            Ty::Forall(name, visibility, body) => {
+                match visibility {
+                    Visibility::Visible => self.write(&format!("forall @{name}")),
+                    Visibility::Invisible => self.write(&format!("forall {name}")),
+                }
+                let mut body = *body;
+                while let Ty::Forall(name, visibility, next) = &types[body] {
+                    match visibility {
+                        Visibility::Visible => self.write(&format!(" @{name}")),
+                        Visibility::Invisible => self.write(&format!(" {name}")),
+                    }
+                    body = *next;
+                }
+                self.write(". ");
+                self.signature(body);
+            }
+            Ty::Constrained(constraint, body) => {
+                self.constraint(constraint, None);
+                self.write(" => ");
+                self.signature(*body);
+            }
+            _ => self.ty(ty, None, Precedence::Top),
+        }
+    }
+
+    /// Whether two types differ in their parts rather than as a whole, such
+    /// that only those parts are highlighted.
+    fn same_shape(&self, left: TyId, right: TyId) -> bool {
+        let types = self.types;
+        if types.as_function(left).is_some() || types.as_function(right).is_some() {
+            return types.as_function(left).is_some() && types.as_function(right).is_some();
+        }
+        match (&types[left], &types[right]) {
+            (Ty::Row(..), Ty::Row(..)) => true,
+            (&Ty::Application(left, _), &Ty::Application(right, _))
+                if types.is_record(left) || types.is_record(right) =>
+            {
+                types.is_record(left) && types.is_record(right)
+            }
+            (Ty::Application(..), Ty::Application(..)) => {
+                let ((left, left_arguments), (right, right_arguments)) =
+                    (types.spine(left), types.spine(right));
+                left_arguments.len() == right_arguments.len() && types.equal(left, right)
+            }
+            (Ty::Constrained(left, _), Ty::Constrained(right, _)) => {
+                left.class == right.class && left.arguments.len() == right.arguments.len()
+            }
+            (Ty::Forall(..), Ty::Forall(..)) => {
+                let (mut left, mut right) = (left, right);
+                while let (
+                    Ty::Forall(left_name, _, left_body),
+                    Ty::Forall(right_name, _, right_body),
+                ) = (&types[left], &types[right])
+                {
+                    if left_name != right_name {
+                        return false;
+                    }
+                    (left, right) = (*left_body, *right_body);
+                }
+                !matches!(types[left], Ty::Forall(..)) && !matches!(types[right], Ty::Forall(..))
+            }
+            _ => false,
+        }
+    }
+
+    fn row(
+        &mut self,
+        fields: &[(Name, TyId)],
+        tail: Option<TyId>,
+        other: Option<RowParts<'_>>,
+        open: &str,
+        close: &str,
+    ) {
+        if fields.is_empty() && tail.is_none() {
+            let highlight =
+                other.is_some_and(|(fields, tail)| !fields.is_empty() || tail.is_some());
+            let empty = |renderer: &mut Self| renderer.write(&format!("{open}{close}"));
+            return if highlight { self.highlighted(empty) } else { empty(self) };
+        }
+        self.write(open);
+        // Both sides elide the same fields, based on the larger record.
+        let elide = fields.len().max(other.map_or(0, |(other, _)| other.len())) > ELIDE_FIELDS;
+        let mut first = true;
+        let mut elided = false;
+        for (index, &(label, ty)) in fields.iter().enumerate() {
+            // Repeated labels are paired up by their position among the
+            // fields with the same label.
+            let occurrence = fields[..index].iter().filter(|&&(other, _)| other == label).count();
+            let other_field = other.map(|(other, _)| {
+                let mut matching = other.iter().filter(|&&(other, _)| other == label);
+                matching.nth(occurrence).map(|&(_, ty)| ty)
+            });
+            if let Some(Some(other)) = other_field {
+                if elide && self.types.equal(ty, other) {
+                    elided = true;
+                    continue;
+                }
+            }
+            self.write(if first { " " } else { ", " });
+            first = false;
+            let field = |renderer: &mut Self, other| {
+                renderer.write(&format!("{label} :: "));
+                renderer.ty(ty, other, Precedence::Top);
+            };
+            match other_field {
+                Some(None) => self.highlighted(|renderer| field(renderer, None)),
+                Some(other) => field(self, other),
+                None => field(self, None),
+            }
+        }
+        if elided {
+            self.write(if first { " .." } else { ", .." });
+        }
+        if let Some(tail) = tail {
+            self.write(" | ");
+            match other {
+                Some((_, Some(other))) => self.ty(tail, Some(other), Precedence::Top),
+                Some((_, None)) => {
+                    self.highlighted(|renderer| renderer.ty(tail, None, Precedence::Top))
+                }
+                None => self.ty(tail, None, Precedence::Top),
+            }
+        }
+        self.write(&format!(" {close}"));
+    }
+
+    fn constraint(&mut self, constraint: &Constraint, other: Option<&Constraint>) {
+        self.name(Namespace::Class, &constraint.class);
+        for (index, &argument) in constraint.arguments.iter().enumerate() {
+            self.write(" ");
+            let other = other.and_then(|other| other.arguments.get(index).copied());
+            self.ty(argument, other, Precedence::Atom);
+        }
+    }
+}
+
+struct Display<'a> {
+    types: &'a Types,
+    ty: TyId,
+}
+
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut renderer = Renderer::new(self.types);
+        renderer.ty(self.ty, None, Precedence::Top);
+        write!(f, "{}", renderer.text)
+    }
+}
+
+struct ConstraintDisplay<'a> {
+    types: &'a Types,
+    constraint: &'a Constraint,
+}
+
+impl fmt::Display for ConstraintDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut renderer = Renderer::new(self.types);
+        renderer.constraint(self.constraint, None);
+        write!(f, "{}", renderer.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lowering::name::Name;
+
+    use super::{Rendered, Ty, Types};
+
+    #[test]
+    fn highlight_differences() {
+        let mut types = Types::default();
+        let (int, string) = (types.prim("Int"), types.prim("String"));
+        let fields = |types: &mut Types, age| {
+            let array = types.prim("Array");
+            let tags = types.application(array, string);
+            let fields = [("name", string), ("age", age), ("tags", tags), ("id", int)];
+            fields.into_iter().map(|(label, ty)| (Name::new(label), ty)).collect::<Vec<_>>()
+        };
+        let expected = fields(&mut types, int);
+        let expected = types.record(expected, None);
+        let mut actual = fields(&mut types, string);
+        actual.pop();
+        let tail = types.alloc(Ty::Variable(Name::new("r")));
+        let actual = types.record(actual, Some(tail));
+        let (expected, actual) = (types.function(expected, int), types.function(actual, int));
+
+        let diff = types.diff(expected, actual);
+        fn highlighted(rendered: &Rendered) -> Vec<&str> {
+            rendered.highlights.iter().map(|range| &rendered.text[range.clone()]).collect()
+        }
+        assert_eq!(diff.expected.text, "{ age :: Int, id :: Int, .. } -> Int");
+        assert_eq!(highlighted(&diff.expected), ["Int", "id :: Int"]);
+        assert_eq!(diff.actual.text, "{ age :: String, .. | r } -> Int");
+        assert_eq!(highlighted(&diff.actual), ["String", "r"]);
+    }
+}
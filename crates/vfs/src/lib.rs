@@ -0,0 +1,197 @@
+//! The contents of every file the analyzer knows about.
+//!
+//! Files are interned by path into a [`FileId`], and their contents are
+//! read from disk unless the editor has an overlay for them, which is the
+//! text of an open document that may not have been saved yet. The rest of
+//! the analyzer only ever sees files through their [`FileId`], and learns
+//! about edits from the changes the [`Vfs`] records.
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use rustc_hash::FxHashMap;
+
+/// Identifies a file for as long as the [`Vfs`] exists, regardless of edits
+/// to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub u32);
+
+/// A path relative to the directory of another file, such as one found in
+/// a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchoredPath<'a> {
+    pub anchor: FileId,
+    pub path: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangedFile {
+    pub file_id: FileId,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Default)]
+pub struct Vfs {
+    paths: Vec<PathBuf>,
+    ids: FxHashMap<PathBuf, FileId>,
+    disk: FxHashMap<FileId, String>,
+    overlays: FxHashMap<FileId, String>,
+    changes: Vec<ChangedFile>,
+}
+
+impl Vfs {
+    /// The [`FileId`] of a path, interning it if it hasn't been seen yet.
+    pub fn intern(&mut self, path: &Path) -> FileId {
+        let path = normalize(path);
+        if let Some(&file_id) = self.ids.get(&path) {
+            return file_id;
+        }
+        let file_id = FileId(self.paths.len() as u32);
+        self.paths.push(path.clone());
+        self.ids.insert(path, file_id);
+        file_id
+    }
+
+    pub fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.ids.get(&normalize(path)).copied()
+    }
+
+    pub fn file_path(&self, file_id: FileId) -> &Path {
+        &self.paths[file_id.0 as usize]
+    }
+
+    /// Resolves a path relative to the directory of its anchor, which only
+    /// succeeds if the path has been interned.
+    pub fn resolve(&self, path: AnchoredPath) -> Option<FileId> {
+        let directory = self.file_path(path.anchor).parent()?;
+        self.file_id(&directory.join(path.path))
+    }
+
+    /// The contents of a file, preferring the overlay over the disk.
+    pub fn contents(&self, file_id: FileId) -> Option<&str> {
+        self.overlays.get(&file_id).or_else(|| self.disk.get(&file_id)).map(String::as_str)
+    }
+
+    /// Every file that currently has contents, in order.
+    pub fn files(&self) -> Vec<FileId> {
+        let mut files: Vec<_> = self
+            .disk
+            .keys()
+            .chain(self.overlays.keys().filter(|file_id| !self.disk.contains_key(file_id)))
+            .copied()
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Sets the contents of a file on disk, where `None` means it was
+    /// deleted.
+    pub fn set_disk_contents(&mut self, path: &Path, contents: Option<String>) -> FileId {
+        let file_id = self.intern(path);
+        let before = self.contents(file_id).map(str::to_string);
+        match contents {
+            Some(contents) => self.disk.insert(file_id, contents),
+            None => self.disk.remove(&file_id),
+        };
+        self.record(file_id, before);
+        file_id
+    }
+
+    /// Sets the contents of a file in the editor, which shadows the disk
+    /// until it's cleared with `None`.
+    pub fn set_overlay(&mut self, path: &Path, contents: Option<String>) -> FileId {
+        let file_id = self.intern(path);
+        let before = self.contents(file_id).map(str::to_string);
+        match contents {
+            Some(contents) => self.overlays.insert(file_id, contents),
+            None => self.overlays.remove(&file_id),
+        };
+        self.record(file_id, before);
+        file_id
+    }
+
+    /// Reads a file from disk, treating a file that doesn't exist as deleted.
+    pub fn load(&mut self, path: &Path) -> io::Result<FileId> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(self.set_disk_contents(path, Some(contents))),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Ok(self.set_disk_contents(path, None))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The changes since they were last taken, in the order they happened.
+    pub fn take_changes(&mut self) -> Vec<ChangedFile> {
+        std::mem::take(&mut self.changes)
+    }
+
+    fn record(&mut self, file_id: FileId, before: Option<String>) {
+        let kind = match (before, self.contents(file_id)) {
+            (None, None) => return,
+            (None, Some(_)) => ChangeKind::Created,
+            (Some(_), None) => ChangeKind::Deleted,
+            (Some(before), Some(after)) if before == after => return,
+            (Some(_), Some(_)) => ChangeKind::Modified,
+        };
+        self.changes.push(ChangedFile { file_id, kind });
+    }
+}
+
+/// Removes `.` and `..` from a path without touching the filesystem, such
+/// that every spelling of a path is interned as the same file.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{AnchoredPath, ChangeKind, ChangedFile, Vfs};
+
+    #[test]
+    fn overlay_the_disk() {
+        let mut vfs = Vfs::default();
+        let main = vfs.set_disk_contents(Path::new("/project/src/Main.purs"), Some("a".into()));
+        let config = vfs.set_disk_contents(Path::new("/project/spago.yaml"), Some("b".into()));
+        assert_eq!(vfs.file_id(Path::new("/project/src/../src/./Main.purs")), Some(main));
+        assert_eq!(vfs.resolve(AnchoredPath { anchor: config, path: "src/Main.purs" }), Some(main));
+        assert_eq!(vfs.resolve(AnchoredPath { anchor: config, path: "test/Main.purs" }), None);
+        vfs.take_changes();
+
+        vfs.set_overlay(Path::new("/project/src/Main.purs"), Some("c".into()));
+        assert_eq!(vfs.contents(main), Some("c"));
+        vfs.set_disk_contents(Path::new("/project/src/Main.purs"), Some("d".into()));
+        assert_eq!(vfs.contents(main), Some("c"));
+        vfs.set_overlay(Path::new("/project/src/Main.purs"), None);
+        assert_eq!(vfs.contents(main), Some("d"));
+        vfs.set_disk_contents(Path::new("/project/src/Main.purs"), None);
+
+        let kinds: Vec<_> =
+            vfs.take_changes().into_iter().map(|ChangedFile { kind, .. }| kind).collect();
+        assert_eq!(kinds, [ChangeKind::Modified, ChangeKind::Modified, ChangeKind::Deleted]);
+        assert_eq!(vfs.files(), [config]);
+    }
+}